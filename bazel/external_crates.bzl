@@ -171,6 +171,9 @@ def external_crates_repository(name, cargo_lockfile, lockfile, sanitizers_enable
                     "idna",
                 ],
             ),
+            "aes-gcm": crate.spec(
+                version = "^0.10.3",
+            ),
             "aide": crate.spec(
                 version = "^0.13.4",
                 features = [
@@ -15,6 +15,11 @@ use crate::flag_status::FlagStatus;
 pub(crate) const MAX_GLOBALS: usize = 1000;
 // The maximum number of functions allowed in a Wasm module.
 pub(crate) const MAX_FUNCTIONS: usize = 50000;
+// The maximum size in bytes of the body of a single function in a Wasm module.
+// Compilation time grows faster than linearly with the size of a function, so
+// a few multi-megabyte functions are much more expensive to compile than the
+// same amount of code split across many functions.
+pub(crate) const MAX_FUNCTION_BODY_SIZE_BYTES: usize = 4 * 1024 * 1024;
 // The maximum number of custom sections allowed in a Wasm module.
 pub(crate) const MAX_CUSTOM_SECTIONS: usize = 16;
 // The total size of the exported custom sections in bytes.
@@ -112,6 +117,10 @@ pub struct FeatureFlags {
     pub best_effort_responses: FlagStatus,
     /// Collect a backtrace from the canister when it panics.
     pub canister_backtrace: FlagStatus,
+    /// Replace the canister memory contents embedded in error messages, e.g.
+    /// the message passed to `ic0.trap`, with their length and hash, such that
+    /// they don't end up in replica logs and reject responses.
+    pub redact_error_payloads: FlagStatus,
 }
 
 impl FeatureFlags {
@@ -123,6 +132,7 @@ impl FeatureFlags {
             wasm64: FlagStatus::Enabled,
             best_effort_responses: FlagStatus::Disabled,
             canister_backtrace: FlagStatus::Enabled,
+            redact_error_payloads: FlagStatus::Disabled,
         }
     }
 }
@@ -161,6 +171,9 @@ pub struct Config {
     /// Maximum number of functions allowed in a Wasm module.
     pub max_functions: usize,
 
+    /// Maximum size in bytes of the body of a function in a Wasm module.
+    pub max_function_body_size_bytes: usize,
+
     /// Maximum number of custom sections allowed in a Wasm module.
     pub max_custom_sections: usize,
 
@@ -255,6 +268,7 @@ impl Config {
             query_execution_threads_per_canister: QUERY_EXECUTION_THREADS_PER_CANISTER,
             max_globals: MAX_GLOBALS,
             max_functions: MAX_FUNCTIONS,
+            max_function_body_size_bytes: MAX_FUNCTION_BODY_SIZE_BYTES,
             max_custom_sections: MAX_CUSTOM_SECTIONS,
             max_custom_sections_size: MAX_CUSTOM_SECTIONS_SIZE,
             max_number_exported_functions: MAX_NUMBER_EXPORTED_FUNCTIONS,
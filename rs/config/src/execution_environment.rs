@@ -39,6 +39,10 @@ const SUBNET_GUARANTEED_RESPONSE_MESSAGE_MEMORY_CAPACITY: NumBytes = NumBytes::n
 /// but the constraint is restored at the end of the round by shedding messages.
 const SUBNET_BEST_EFFORT_MESSAGE_MEMORY_CAPACITY: NumBytes = NumBytes::new(5 * GIB);
 
+/// The lifetime of guaranteed response call requests in output queues, from
+/// which their deadline is computed when they are enqueued.
+const GUARANTEED_RESPONSE_REQUEST_LIFETIME: Duration = Duration::from_secs(300);
+
 /// This is the upper limit on how much memory can be used by the ingress
 /// history on a given subnet. It is lower than the subnet message memory
 /// capacity because here we count actual memory consumption as opposed to
@@ -192,6 +196,10 @@ pub struct Config {
     /// messages across the whole subnet.
     pub best_effort_message_memory_capacity: NumBytes,
 
+    /// The lifetime of guaranteed response call requests in output queues, from
+    /// which their deadline is computed when they are enqueued.
+    pub guaranteed_response_request_lifetime: Duration,
+
     /// The maximum amount of logical storage available to the ingress history
     /// across the whole subnet.
     pub ingress_history_memory_capacity: NumBytes,
@@ -338,6 +346,7 @@ impl Default for Config {
             subnet_memory_capacity: SUBNET_MEMORY_CAPACITY,
             subnet_message_memory_capacity: SUBNET_GUARANTEED_RESPONSE_MESSAGE_MEMORY_CAPACITY,
             best_effort_message_memory_capacity: SUBNET_BEST_EFFORT_MESSAGE_MEMORY_CAPACITY,
+            guaranteed_response_request_lifetime: GUARANTEED_RESPONSE_REQUEST_LIFETIME,
             ingress_history_memory_capacity: INGRESS_HISTORY_MEMORY_CAPACITY,
             subnet_wasm_custom_sections_memory_capacity:
                 SUBNET_WASM_CUSTOM_SECTIONS_MEMORY_CAPACITY,
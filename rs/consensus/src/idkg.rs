@@ -353,6 +353,18 @@ impl IDkgImpl {
                     .with_label_values(&["retain_active_transcripts_transient"])
                     .inc();
             }
+            Err(IDkgRetainKeysError::Timeout { method, elapsed }) => {
+                warn!(
+                    self.logger,
+                    "purge_inactive_transcripts(): RPC call {} timed out after {:?}",
+                    method,
+                    elapsed
+                );
+                self.metrics
+                    .client_errors
+                    .with_label_values(&["retain_active_transcripts_transient"])
+                    .inc();
+            }
             Err(error) => {
                 error!(
                     self.logger,
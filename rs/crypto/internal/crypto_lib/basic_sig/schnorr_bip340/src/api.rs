@@ -0,0 +1,84 @@
+//! API for BIP340 Schnorr basic signatures over secp256k1
+//!
+//! See [BIP340](https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki).
+use super::types;
+use ic_crypto_secrets_containers::SecretArray;
+use ic_types::crypto::{AlgorithmId, CryptoError, CryptoResult};
+use rand::{CryptoRng, Rng};
+
+#[cfg(test)]
+mod tests;
+
+/// Generates a secp256k1 keypair for BIP340 signatures.
+///
+/// The public key is returned in the x-only encoding of BIP340.
+pub fn keypair_from_rng<R: Rng + CryptoRng>(
+    csprng: &mut R,
+) -> (types::SecretKeyBytes, types::PublicKeyBytes) {
+    let signing_key = ic_crypto_secp256k1::PrivateKey::generate_using_rng(csprng);
+    let mut sk_bytes: [u8; types::SecretKeyBytes::SIZE] = signing_key
+        .serialize_sec1()
+        .try_into()
+        .expect("secp256k1 secret keys are 32 bytes");
+    let sk = types::SecretKeyBytes(SecretArray::new_and_zeroize_argument(&mut sk_bytes));
+    let pk = types::PublicKeyBytes(
+        signing_key
+            .public_key()
+            .serialize_bip340()
+            .try_into()
+            .expect("BIP340 public keys are 32 bytes"),
+    );
+    (sk, pk)
+}
+
+/// Signs a message with a BIP340 Schnorr signature.
+///
+/// The randomness from `csprng` is used as auxiliary randomness of the
+/// signature.
+///
+/// # Errors
+/// * `MalformedSecretKey` if the secret key is malformed
+pub fn sign<R: Rng + CryptoRng>(
+    msg: &[u8],
+    sk: &types::SecretKeyBytes,
+    csprng: &mut R,
+) -> CryptoResult<types::SignatureBytes> {
+    let signing_key = ic_crypto_secp256k1::PrivateKey::deserialize_sec1(sk.0.expose_secret())
+        .map_err(|e| CryptoError::MalformedSecretKey {
+            algorithm: AlgorithmId::SchnorrSecp256k1,
+            internal_error: format!("{:?}", e),
+        })?;
+    Ok(types::SignatureBytes(
+        signing_key.sign_message_with_bip340(msg, csprng),
+    ))
+}
+
+/// Verifies a BIP340 Schnorr signature using an x-only public key.
+///
+/// # Errors
+/// * `MalformedPublicKey` if the public key is malformed
+/// * `SignatureVerification` if the signature is invalid
+pub fn verify(
+    sig: &types::SignatureBytes,
+    msg: &[u8],
+    pk: &types::PublicKeyBytes,
+) -> CryptoResult<()> {
+    let public_key = ic_crypto_secp256k1::PublicKey::deserialize_bip340(&pk.0).map_err(|e| {
+        CryptoError::MalformedPublicKey {
+            algorithm: AlgorithmId::SchnorrSecp256k1,
+            key_bytes: Some(pk.0.to_vec()),
+            internal_error: format!("{:?}", e),
+        }
+    })?;
+
+    if public_key.verify_bip340_signature(msg, &sig.0) {
+        Ok(())
+    } else {
+        Err(CryptoError::SignatureVerification {
+            algorithm: AlgorithmId::SchnorrSecp256k1,
+            public_key_bytes: pk.0.to_vec(),
+            sig_bytes: sig.0.to_vec(),
+            internal_error: "BIP340 signature verification failed".to_string(),
+        })
+    }
+}
@@ -0,0 +1,113 @@
+use crate::types::{PublicKeyBytes, SecretKeyBytes, SignatureBytes};
+use crate::{keypair_from_rng, sign, verify};
+use assert_matches::assert_matches;
+use ic_crypto_secrets_containers::SecretArray;
+use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+use ic_types::crypto::{AlgorithmId, CryptoError};
+use rand::Rng;
+
+#[test]
+fn should_generate_key_pair_with_bip340_public_key() {
+    let rng = &mut reproducible_rng();
+    let (sk, pk) = keypair_from_rng(rng);
+
+    let signing_key =
+        ic_crypto_secp256k1::PrivateKey::deserialize_sec1(sk.0.expose_secret()).expect("valid key");
+    assert_eq!(signing_key.public_key().serialize_bip340(), pk.0.to_vec());
+}
+
+#[test]
+fn should_verify_valid_signature() {
+    let rng = &mut reproducible_rng();
+    let (sk, pk) = keypair_from_rng(rng);
+    let msg: Vec<u8> = (0..rng.gen_range(0..100)).map(|_| rng.gen()).collect();
+
+    let sig = sign(&msg, &sk, rng).expect("failed to sign");
+
+    assert_eq!(verify(&sig, &msg, &pk), Ok(()));
+    let public_key = ic_crypto_secp256k1::PublicKey::deserialize_bip340(&pk.0).expect("valid key");
+    assert!(public_key.verify_bip340_signature(&msg, &sig.0));
+}
+
+#[test]
+fn should_fail_to_verify_signature_on_other_message() {
+    let rng = &mut reproducible_rng();
+    let (sk, pk) = keypair_from_rng(rng);
+
+    let sig = sign(b"message", &sk, rng).expect("failed to sign");
+
+    assert_matches!(
+        verify(&sig, b"other message", &pk),
+        Err(CryptoError::SignatureVerification {
+            algorithm: AlgorithmId::SchnorrSecp256k1,
+            ..
+        })
+    );
+}
+
+#[test]
+fn should_fail_to_verify_signature_with_other_public_key() {
+    let rng = &mut reproducible_rng();
+    let (sk, _pk) = keypair_from_rng(rng);
+    let (_other_sk, other_pk) = keypair_from_rng(rng);
+
+    let sig = sign(b"message", &sk, rng).expect("failed to sign");
+
+    assert_matches!(
+        verify(&sig, b"message", &other_pk),
+        Err(CryptoError::SignatureVerification { .. })
+    );
+}
+
+#[test]
+fn should_fail_to_verify_with_malformed_public_key() {
+    // 2^256 - 1 exceeds the field modulus of secp256k1
+    let x = [0xff; PublicKeyBytes::SIZE];
+
+    assert_matches!(
+        verify(
+            &SignatureBytes([0; SignatureBytes::SIZE]),
+            b"message",
+            &PublicKeyBytes(x)
+        ),
+        Err(CryptoError::MalformedPublicKey {
+            algorithm: AlgorithmId::SchnorrSecp256k1,
+            ..
+        })
+    );
+}
+
+#[test]
+fn should_fail_to_sign_with_zero_secret_key() {
+    let rng = &mut reproducible_rng();
+    let sk = SecretKeyBytes(SecretArray::new_and_dont_zeroize_argument(
+        &[0; SecretKeyBytes::SIZE],
+    ));
+
+    assert_matches!(
+        sign(b"message", &sk, rng),
+        Err(CryptoError::MalformedSecretKey {
+            algorithm: AlgorithmId::SchnorrSecp256k1,
+            ..
+        })
+    );
+}
+
+#[test]
+fn should_serialize_and_deserialize_with_cbor() {
+    let rng = &mut reproducible_rng();
+    let (sk, pk) = keypair_from_rng(rng);
+    let sig = sign(b"message", &sk, rng).expect("failed to sign");
+
+    let pk_cbor = serde_cbor::to_vec(&pk).expect("failed to serialize");
+    let sig_cbor = serde_cbor::to_vec(&sig).expect("failed to serialize");
+
+    assert_eq!(
+        serde_cbor::from_slice::<PublicKeyBytes>(&pk_cbor).expect("failed to deserialize"),
+        pk
+    );
+    assert_eq!(
+        serde_cbor::from_slice::<SignatureBytes>(&sig_cbor).expect("failed to deserialize"),
+        sig
+    );
+}
@@ -0,0 +1,7 @@
+#![forbid(unsafe_code)]
+#![deny(clippy::unwrap_used)]
+
+//! Basic signatures implemented with BIP340 Schnorr signatures over secp256k1
+pub mod api;
+pub mod types;
+pub use api::*;
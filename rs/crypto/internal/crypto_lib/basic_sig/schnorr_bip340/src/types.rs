@@ -0,0 +1,28 @@
+//! Types for BIP340 Schnorr basic signatures
+use ic_crypto_secrets_containers::SecretArray;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// A wrapper for secp256k1 secret key bytes.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize, Zeroize, ZeroizeOnDrop)]
+pub struct SecretKeyBytes(pub SecretArray<{ SecretKeyBytes::SIZE }>);
+impl SecretKeyBytes {
+    pub const SIZE: usize = 32;
+}
+
+/// A wrapper for BIP340 public key bytes, i.e., the x-coordinate of the
+/// public key point.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct PublicKeyBytes(pub [u8; PublicKeyBytes::SIZE]);
+ic_crypto_internal_types::derive_serde!(PublicKeyBytes, PublicKeyBytes::SIZE);
+impl PublicKeyBytes {
+    pub const SIZE: usize = 32;
+}
+
+/// A wrapper for BIP340 signature bytes.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct SignatureBytes(pub [u8; SignatureBytes::SIZE]);
+ic_crypto_internal_types::derive_serde!(SignatureBytes, SignatureBytes::SIZE);
+impl SignatureBytes {
+    pub const SIZE: usize = 64;
+}
@@ -153,3 +153,53 @@ pub fn verify_combined(
         })
     }
 }
+
+/// Verifies a batch of combined multisignatures, each over its own message
+/// using its own array of public keys.
+///
+/// The batch is verified with multi-pairings, which is faster than
+/// verifying the signatures one by one. A batch of one signature is verified
+/// with `verify_combined`. Randomness from `rng` ensures that invalid
+/// signatures cannot cancel each other out.
+///
+/// # Errors
+/// * `CryptoError::MalformedSignature` if any of the signatures cannot be
+///   parsed as a G1 point.
+/// * `CryptoError::MalformedPublicKey` if any of the public keys cannot be
+///   parsed as a valid G2 point.
+/// * `CryptoError::SignatureVerification` if verification of the batch
+///   fails, i.e. if any of the signatures is invalid.
+pub fn verify_combined_batch<R: Rng + CryptoRng>(
+    batch: &[(&[u8], &CombinedSignatureBytes, &[PublicKeyBytes])],
+    rng: &mut R,
+) -> Result<(), CryptoError> {
+    if let [(message, signature, public_keys)] = batch {
+        return verify_combined(message, signature, public_keys);
+    }
+    let signatures: Vec<CombinedSignature> = batch
+        .iter()
+        .map(|(_, signature, _)| CombinedSignature::try_from(*signature))
+        .collect::<Result<_, _>>()?;
+    let public_keys: Vec<Vec<PublicKey>> = batch
+        .iter()
+        .map(|(_, _, public_keys)| public_keys.iter().map(PublicKey::try_from).collect())
+        .collect::<Result<_, _>>()?;
+    let batch: Vec<(&[u8], &CombinedSignature, &[PublicKey])> = batch
+        .iter()
+        .zip(signatures.iter())
+        .zip(public_keys.iter())
+        .map(|(((message, _, _), signature), public_keys)| {
+            (*message, signature, public_keys.as_slice())
+        })
+        .collect();
+    if crypto::verify_combined_message_signature_batch(&batch, rng) {
+        Ok(())
+    } else {
+        Err(CryptoError::SignatureVerification {
+            algorithm: AlgorithmId::MultiBls12_381,
+            public_key_bytes: Vec::new(),
+            sig_bytes: Vec::new(),
+            internal_error: "Batch verification of multisignatures failed".to_string(),
+        })
+    }
+}
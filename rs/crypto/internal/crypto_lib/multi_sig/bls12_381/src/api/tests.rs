@@ -4,7 +4,7 @@ use crate::types::{
     arbitrary, CombinedSignatureBytes, IndividualSignatureBytes, PopBytes, PublicKeyBytes,
     SecretKeyBytes,
 };
-use ic_types::crypto::CryptoResult;
+use ic_types::crypto::{CryptoError, CryptoResult};
 use proptest::prelude::*;
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
@@ -72,6 +72,72 @@ fn test_happy_path(
     (signatures, signature, public_keys)
 }
 
+/// Returns `num_signatures` combined signatures, each by its own set of
+/// signers over its own message.
+fn combined_signatures(
+    num_signatures: usize,
+    csprng: &mut ChaCha20Rng,
+) -> Vec<(Vec<u8>, CombinedSignatureBytes, Vec<PublicKeyBytes>)> {
+    (0..num_signatures)
+        .map(|i| {
+            let keys: Vec<_> = (0..1 + i % 3)
+                .map(|_| multi_sig::keypair_from_rng(csprng))
+                .collect();
+            let message = format!("message {i}").into_bytes();
+            let (_, signature, public_keys) = test_happy_path(&keys, &message);
+            (message, signature, public_keys)
+        })
+        .collect()
+}
+
+fn as_batch(
+    signatures: &[(Vec<u8>, CombinedSignatureBytes, Vec<PublicKeyBytes>)],
+) -> Vec<(&[u8], &CombinedSignatureBytes, &[PublicKeyBytes])> {
+    signatures
+        .iter()
+        .map(|(message, signature, public_keys)| {
+            (message.as_slice(), signature, public_keys.as_slice())
+        })
+        .collect()
+}
+
+#[test]
+fn batch_of_valid_combined_signatures_verifies() {
+    let mut csprng = ChaCha20Rng::seed_from_u64(42);
+    for num_signatures in [0, 1, 2, 10] {
+        let signatures = combined_signatures(num_signatures, &mut csprng);
+        assert_eq!(
+            multi_sig::verify_combined_batch(&as_batch(&signatures), &mut csprng),
+            Ok(())
+        );
+    }
+}
+
+#[test]
+fn batch_with_an_invalid_combined_signature_fails() {
+    let mut csprng = ChaCha20Rng::seed_from_u64(42);
+    for num_signatures in [1, 2, 10] {
+        let mut signatures = combined_signatures(num_signatures, &mut csprng);
+        signatures[num_signatures / 2].0 = b"another message".to_vec();
+        assert!(
+            multi_sig::verify_combined_batch(&as_batch(&signatures), &mut csprng)
+                .unwrap_err()
+                .is_signature_verification_error()
+        );
+    }
+}
+
+#[test]
+fn batch_with_a_malformed_public_key_fails() {
+    let mut csprng = ChaCha20Rng::seed_from_u64(42);
+    let mut signatures = combined_signatures(2, &mut csprng);
+    signatures[1].2[0] = PublicKeyBytes([0xff; PublicKeyBytes::SIZE]);
+    assert!(matches!(
+        multi_sig::verify_combined_batch(&as_batch(&signatures), &mut csprng),
+        Err(CryptoError::MalformedPublicKey { .. })
+    ));
+}
+
 // Slow tests
 proptest! {
     #![proptest_config(ProptestConfig {
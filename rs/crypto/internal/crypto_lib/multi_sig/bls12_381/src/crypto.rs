@@ -7,7 +7,8 @@ use crate::types::{
 };
 
 use ic_crypto_internal_bls12_381_type::{
-    verify_bls_signature, G1Projective, G2Affine, G2Projective, Scalar,
+    verify_bls_signature, verify_bls_signature_batch, G1Affine, G1Projective, G2Affine,
+    G2Projective, Scalar,
 };
 
 use ic_crypto_sha2::{Context, DomainSeparationContext};
@@ -116,3 +117,26 @@ pub fn verify_combined_message_signature(
     let public_key = combine_public_keys(public_keys);
     verify_point(&hash, signature, &public_key)
 }
+
+/// Verifies a batch of combined signatures, each over its own message and
+/// signed by its own set of public keys, see `verify_bls_signature_batch`.
+pub fn verify_combined_message_signature_batch<R: Rng + CryptoRng>(
+    batch: &[(&[u8], &CombinedSignature, &[PublicKey])],
+    rng: &mut R,
+) -> bool {
+    let sigs_pks_msgs: Vec<(G1Affine, G2Affine, G1Affine)> = batch
+        .iter()
+        .map(|(message, signature, public_keys)| {
+            (
+                signature.to_affine(),
+                combine_public_keys(public_keys).to_affine(),
+                hash_message_to_g1(message).to_affine(),
+            )
+        })
+        .collect();
+    let sigs_pks_msgs: Vec<(&G1Affine, &G2Affine, &G1Affine)> = sigs_pks_msgs
+        .iter()
+        .map(|(signature, public_key, message)| (signature, public_key, message))
+        .collect();
+    verify_bls_signature_batch(&sigs_pks_msgs, rng)
+}
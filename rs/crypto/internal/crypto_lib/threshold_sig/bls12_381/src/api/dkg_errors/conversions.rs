@@ -65,6 +65,13 @@ impl From<CspDkgLoadPrivateKeyError> for DkgLoadTranscriptError {
                     },
                 )
             }
+            CspDkgLoadPrivateKeyError::Timeout { method, elapsed } => {
+                DkgLoadTranscriptError::TransientInternalError(
+                    ic_types::crypto::error::InternalError {
+                        internal_error: format!("RPC call {method} timed out after {elapsed:?}"),
+                    },
+                )
+            }
             CspDkgLoadPrivateKeyError::MalformedPublicKeyError(error) => {
                 // Forward to the caller because the argument is malformed.
                 DkgLoadTranscriptError::MalformedFsEncryptionPublicKey(
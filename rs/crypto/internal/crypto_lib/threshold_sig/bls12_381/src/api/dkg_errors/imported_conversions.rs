@@ -86,6 +86,11 @@ mod create_dealing_error_conversions_v2 {
                         internal_error: error.internal_error,
                     })
                 }
+                CspDkgCreateReshareDealingError::Timeout { method, elapsed } => {
+                    DkgCreateDealingError::TransientInternalError(InternalError {
+                        internal_error: format!("RPC call {method} timed out after {elapsed:?}"),
+                    })
+                }
                 CspDkgCreateReshareDealingError::ReshareKeyIdComputationError(
                     crate::api::dkg_errors::InternalError { internal_error },
                 ) => DkgCreateDealingError::ReshareKeyIdComputationError(InvalidArgumentError {
@@ -173,6 +178,11 @@ mod verify_dealing_error_conversions {
                     // TODO (CRP-576): implement `Display`?
                     panic!("{}SizeError: {:?}", panic_prefix, error);
                 }
+                CspDkgVerifyReshareDealingError::TransientInternalError(error) => {
+                    // Dealings are verified without calling the remote vault, which is the
+                    // only source of this error, so we panic:
+                    panic!("{}TransientInternalError: {:?}", panic_prefix, error);
+                }
             }
         }
     }
@@ -199,6 +209,11 @@ mod retain_active_keys_error_conversions {
                         internal_error: e.internal_error,
                     })
                 }
+                CspDkgUpdateFsEpochError::Timeout { method, elapsed } => {
+                    DkgKeyRemovalError::TransientInternalError(InternalError {
+                        internal_error: format!("RPC call {method} timed out after {elapsed:?}"),
+                    })
+                }
                 CspDkgUpdateFsEpochError::KeyNotFoundError(e) => {
                     DkgKeyRemovalError::KeyNotFoundError(e)
                 }
@@ -223,6 +238,11 @@ mod retain_active_keys_error_conversions {
                         internal_error: e.internal_error,
                     })
                 }
+                CspDkgRetainThresholdKeysError::Timeout { method, elapsed } => {
+                    DkgKeyRemovalError::TransientInternalError(InternalError {
+                        internal_error: format!("RPC call {method} timed out after {elapsed:?}"),
+                    })
+                }
                 CspDkgRetainThresholdKeysError::KeyIdInstantiationError(internal_error) => {
                     DkgKeyRemovalError::KeyIdInstantiationError(InternalError { internal_error })
                 }
@@ -5,6 +5,7 @@ use ic_crypto_internal_types::sign::threshold_sig::public_key::bls12_381::Public
 use ic_types::crypto::AlgorithmId;
 use ic_types::{NodeIndex, NumberOfNodes};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 // These are the base error types used by ni_dkg
 // TODO(CRP-574): Move these up, out of dkg.
@@ -50,6 +51,11 @@ pub enum CspDkgCreateFsKeyError {
     InternalError(InternalError),
     DuplicateKeyId(String),
     TransientInternalError(String),
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
 }
 
 /// Verification of a DKG forward-secure key failed.
@@ -71,6 +77,11 @@ pub enum CspDkgUpdateFsEpochError {
     UnsupportedAlgorithmId(AlgorithmId),
     FsKeyNotInSecretKeyStoreError(KeyNotFoundError),
     TransientInternalError(InternalError),
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
     /// Precondition error: The encryption key was not found.
     KeyNotFoundError(KeyNotFoundError),
     /// The public key could not be parsed.
@@ -187,6 +198,8 @@ pub enum CspDkgCreateReshareDealingError {
     SizeError(SizeError),
     /// Transient internal error, e.g. an RPC error.
     TransientInternalError(InternalError),
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout { method: String, elapsed: Duration },
 }
 
 impl From<EncryptAndZKProveError> for CspDkgCreateReshareDealingError {
@@ -274,6 +287,11 @@ impl From<CspDkgCreateReshareDealingError> for CspDkgCreateDealingError {
             CspDkgCreateReshareDealingError::TransientInternalError(error) => {
                 CspDkgCreateDealingError::TransientInternalError(error)
             }
+            CspDkgCreateReshareDealingError::Timeout { method, elapsed } => {
+                CspDkgCreateDealingError::TransientInternalError(InternalError {
+                    internal_error: format!("RPC call {method} timed out after {elapsed:?}"),
+                })
+            }
             CspDkgCreateReshareDealingError::ReshareKeyIdComputationError(_) => {
                 panic!("This error cannot be converted")
             }
@@ -282,7 +300,7 @@ impl From<CspDkgCreateReshareDealingError> for CspDkgCreateDealingError {
 }
 
 /// Verification of a DKG dealing failed.
-#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
 pub enum CspDkgVerifyDealingError {
     /// Precondition error: The AlgorithmId does not correspond to a NiDkg
     /// variant.
@@ -311,6 +329,10 @@ pub enum CspDkgVerifyDealingError {
     /// Hardware error: This machine cannot handle this request because some
     /// parameter was too large.
     SizeError(SizeError),
+    /// Transient internal error, e.g. an RPC error.
+    TransientInternalError(InternalError),
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout { method: String, elapsed: Duration },
 }
 
 /// Verification of a DKG resharing dealing failed.
@@ -345,6 +367,8 @@ pub enum CspDkgVerifyReshareDealingError {
     /// Hardware error: This machine cannot handle this request because some
     /// parameter was too large.
     SizeError(SizeError),
+    /// Transient internal error, e.g. an RPC error.
+    TransientInternalError(InternalError),
 }
 
 impl From<CspDkgVerifyDealingError> for CspDkgVerifyReshareDealingError {
@@ -379,6 +403,14 @@ impl From<CspDkgVerifyDealingError> for CspDkgVerifyReshareDealingError {
             CspDkgVerifyDealingError::SizeError(error) => {
                 CspDkgVerifyReshareDealingError::SizeError(error)
             }
+            CspDkgVerifyDealingError::TransientInternalError(error) => {
+                CspDkgVerifyReshareDealingError::TransientInternalError(error)
+            }
+            CspDkgVerifyDealingError::Timeout { method, elapsed } => {
+                CspDkgVerifyReshareDealingError::TransientInternalError(InternalError {
+                    internal_error: format!("RPC call {method} timed out after {elapsed:?}"),
+                })
+            }
         }
     }
 }
@@ -439,6 +471,8 @@ pub enum CspDkgRetainThresholdKeysError {
     KeyIdInstantiationError(String),
     /// An internal error, e.g. an RPC error.
     TransientInternalError(InternalError),
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout { method: String, elapsed: Duration },
 }
 
 impl From<CspDkgCreateTranscriptError> for CspDkgCreateReshareTranscriptError {
@@ -493,6 +527,8 @@ pub enum CspDkgLoadPrivateKeyError {
     InternalError(InternalError),
     /// A transient internal error, e.g. an RPC error.
     TransientInternalError(InternalError),
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout { method: String, elapsed: Duration },
     /// The public key could not be parsed.
     MalformedPublicKeyError(MalformedDataError),
 }
@@ -15,9 +15,15 @@ pub use common::arb_node_id;
 pub use common::arb_seed;
 pub use crypto_error::arb_crypto_error;
 pub use csp_basic_signature_error::arb_csp_basic_signature_error;
+pub use csp_basic_signature_keygen_algorithm::arb_csp_basic_signature_keygen_algorithm;
 pub use csp_basic_signature_keygen_error::arb_csp_basic_signature_keygen_error;
+pub use csp_dkg_load_private_key_error::arb_csp_dkg_load_private_key_error;
+pub use csp_dkg_retain_threshold_keys_error::arb_csp_dkg_retain_threshold_keys_error;
+pub use csp_dkg_verify_dealing_error::arb_csp_dkg_verify_dealing_error;
+pub use csp_fs_encryption_public_key::arb_csp_fs_encryption_public_key;
 pub use csp_multi_signature_error::arb_csp_multi_signature_error;
 pub use csp_multi_signature_keygen_error::arb_csp_multi_signature_keygen_error;
+pub use csp_ni_dkg_dealing::arb_csp_ni_dkg_dealing;
 pub use csp_pop::arb_csp_pop;
 pub use csp_public_key::arb_csp_public_key;
 pub use csp_public_key_store_error::arb_csp_public_key_store_error;
@@ -26,6 +32,7 @@ pub use csp_signature::arb_csp_signature;
 pub use csp_threshold_sign_error::arb_csp_threshold_sign_error;
 pub use csp_tls_keygen_error::arb_csp_tls_keygen_error;
 pub use csp_tls_sign_error::arb_csp_tls_sign_error;
+pub use ni_dkg_transcript::arb_ni_dkg_transcript;
 pub use node_public_keys::arb_current_node_public_keys;
 pub use node_public_keys::arb_external_public_keys;
 pub use pks_and_sks_contains_errors::arb_pks_and_sks_contains_errors;
@@ -117,6 +124,7 @@ mod common {
     use ic_types::SubnetId;
     use proptest::array::uniform24;
     use proptest::prelude::{prop, Strategy};
+    use std::time::Duration;
     use strum::IntoEnumIterator;
 
     pub(crate) const MAX_ALGORITHM_ID_INDEX: i32 = 19;
@@ -149,6 +157,12 @@ mod common {
         }
     }
 
+    prop_compose! {
+        pub fn arb_duration()(nanos in any::<u64>()) -> Duration {
+            Duration::from_nanos(nanos)
+        }
+    }
+
     prop_compose! {
         pub fn arb_registry_version()(version in any::<u64>()) -> RegistryVersion {
             RegistryVersion::from(version)
@@ -183,6 +197,7 @@ mod common {
 mod csp_basic_signature_error {
     use super::*;
     use crate::common::arb_algorithm_id;
+    use crate::common::arb_duration;
     use crate::common::arb_key_id;
     use ic_crypto_internal_csp::vault::api::CspBasicSignatureError;
 
@@ -191,7 +206,8 @@ mod csp_basic_signature_error {
         UnsupportedAlgorithm => {algorithm in arb_algorithm_id()},
         WrongSecretKeyType => {algorithm in arb_algorithm_id(), secret_key_variant in ".*"},
         MalformedSecretKey => {algorithm in arb_algorithm_id()},
-        TransientInternalError => {internal_error in ".*"}
+        TransientInternalError => {internal_error in ".*"},
+        Timeout => {method in ".*", elapsed in arb_duration()}
     );
 }
 
@@ -203,6 +219,7 @@ mod csp_signature {
     use ic_crypto_internal_basic_sig_ecdsa_secp256k1::types as ecdsa_secp256k1_types;
     use ic_crypto_internal_basic_sig_ecdsa_secp256r1::types as ecdsa_secp256r1_types;
     use ic_crypto_internal_basic_sig_ed25519::types as ed25519_types;
+    use ic_crypto_internal_basic_sig_schnorr_bip340::types as schnorr_bip340_types;
     use ic_crypto_internal_csp::types::CspSignature;
 
     proptest_strategy_for_enum!(CspSignature;
@@ -211,7 +228,8 @@ mod csp_signature {
         Ed25519 => (ed25519_types::SignatureBytes: bytes in arb_64_bytes()),
         MultiBls12_381 => (signature in arb_multi_bls12_381_signature()),
         ThresBls12_381 => (signature in arb_thres_bls12_381_signature()),
-        RsaSha256 => (bytes in vec(any::<u8>(), 0..100))
+        RsaSha256 => (bytes in vec(any::<u8>(), 0..100)),
+        SchnorrSecp256k1Bip340 => (schnorr_bip340_types::SignatureBytes: bytes in arb_64_bytes())
     );
 }
 
@@ -246,6 +264,7 @@ mod csp_public_key {
     use ic_crypto_internal_basic_sig_ecdsa_secp256r1::types as ecdsa_secp256r1_types;
     use ic_crypto_internal_basic_sig_ed25519::types as ed25519_types;
     use ic_crypto_internal_basic_sig_rsa_pkcs1::RsaPublicKey;
+    use ic_crypto_internal_basic_sig_schnorr_bip340::types as schnorr_bip340_types;
     use ic_crypto_internal_csp::types::CspPublicKey;
     use ic_crypto_internal_multi_sig_bls12381::types as multi_types;
 
@@ -254,7 +273,8 @@ mod csp_public_key {
         EcdsaSecp256k1 => (ecdsa_secp256k1_types::PublicKeyBytes: bytes in vec(any::<u8>(), 0..100)),
         Ed25519 => (ed25519_types::PublicKeyBytes: bytes in uniform32(any::<u8>())),
         MultiBls12_381 => (multi_types::PublicKeyBytes: bytes in arb_96_bytes()),
-        RsaSha256 => (public_key in arb_rsa_public_key())
+        RsaSha256 => (public_key in arb_rsa_public_key()),
+        SchnorrSecp256k1Bip340 => (schnorr_bip340_types::PublicKeyBytes: bytes in uniform32(any::<u8>()))
     );
 
     prop_compose! {
@@ -281,44 +301,73 @@ mod csp_pop {
     );
 }
 
+mod csp_basic_signature_keygen_algorithm {
+    use super::*;
+    use ic_crypto_internal_csp::vault::api::CspBasicSignatureKeygenAlgorithm;
+
+    proptest_strategy_for_enum!(CspBasicSignatureKeygenAlgorithm;
+        SchnorrSecp256k1Bip340
+    );
+}
+
 mod csp_basic_signature_keygen_error {
     use super::*;
+    use crate::common::arb_duration;
     use crate::common::arb_key_id;
     use ic_crypto_internal_csp::vault::api::CspBasicSignatureKeygenError;
 
     proptest_strategy_for_enum!(CspBasicSignatureKeygenError;
         InternalError => {internal_error in ".*"},
         DuplicateKeyId => {key_id in arb_key_id()},
-        TransientInternalError => {internal_error in ".*"}
+        TransientInternalError => {internal_error in ".*"},
+        Timeout => {method in ".*", elapsed in arb_duration()}
     );
 }
 
 mod csp_multi_signature_error {
     use super::*;
+    use crate::common::arb_duration;
     use ic_crypto_internal_csp::vault::api::CspMultiSignatureError;
+    use proptest::strategy::Strategy;
 
     proptest_strategy_for_enum!(CspMultiSignatureError;
         SecretKeyNotFound => {algorithm in arb_algorithm_id(), key_id in arb_key_id()},
         UnsupportedAlgorithm => {algorithm in arb_algorithm_id()},
         WrongSecretKeyType => {algorithm in arb_algorithm_id(), secret_key_variant in ".*"},
-        TransientInternalError => {internal_error in ".*"}
+        TransientInternalError => {internal_error in ".*"},
+        Timeout => {method in ".*", elapsed in arb_duration()},
+        BatchMessageError => {index in any::<usize>(), error in arb_batch_message_cause()}
     );
+
+    // Not nested any further, which would recurse indefinitely.
+    fn arb_batch_message_cause() -> impl Strategy<Value = Box<CspMultiSignatureError>> {
+        proptest::prop_oneof![
+            arb_secret_key_not_found_variant(),
+            arb_unsupported_algorithm_variant(),
+            arb_wrong_secret_key_type_variant(),
+            arb_transient_internal_error_variant(),
+        ]
+        .prop_map(Box::new)
+    }
 }
 
 mod csp_multi_signature_keygen_error {
     use super::*;
+    use crate::common::arb_duration;
     use ic_crypto_internal_csp::vault::api::CspMultiSignatureKeygenError;
 
     proptest_strategy_for_enum!(CspMultiSignatureKeygenError;
         MalformedPublicKey => {algorithm in arb_algorithm_id(), key_bytes in proptest::option::of(vec(any::<u8>(), 0..100)), internal_error in ".*"},
         InternalError => {internal_error in ".*"},
         DuplicateKeyId => {key_id in arb_key_id()},
-        TransientInternalError => {internal_error in ".*"}
+        TransientInternalError => {internal_error in ".*"},
+        Timeout => {method in ".*", elapsed in arb_duration()}
     );
 }
 
 mod csp_threshold_sign_error {
     use super::*;
+    use crate::common::arb_duration;
     use ic_crypto_internal_csp::api::CspThresholdSignError;
 
     proptest_strategy_for_enum!(CspThresholdSignError;
@@ -327,16 +376,20 @@ mod csp_threshold_sign_error {
         WrongSecretKeyType => {},
         MalformedSecretKey => {algorithm in arb_algorithm_id()},
         KeyIdInstantiationError => (error in ".*"),
-        TransientInternalError => {internal_error in ".*"}
+        InvalidArgument => {message in ".*"},
+        TransientInternalError => {internal_error in ".*"},
+        Timeout => {method in ".*", elapsed in arb_duration()}
     );
 }
 
 mod csp_secret_key_store_contains_error {
     use super::*;
+    use crate::common::arb_duration;
     use ic_crypto_internal_csp::vault::api::CspSecretKeyStoreContainsError;
 
     proptest_strategy_for_enum!(CspSecretKeyStoreContainsError;
-        TransientInternalError => {internal_error in ".*"}
+        TransientInternalError => {internal_error in ".*"},
+        Timeout => {method in ".*", elapsed in arb_duration()}
     );
 }
 
@@ -426,10 +479,12 @@ mod crypto_error {
 
 mod csp_public_key_store_error {
     use super::*;
+    use crate::common::arb_duration;
     use ic_crypto_internal_csp::vault::api::CspPublicKeyStoreError;
 
     proptest_strategy_for_enum!(CspPublicKeyStoreError;
-        TransientInternalError => (error in ".*")
+        TransientInternalError => (error in ".*"),
+        Timeout => {method in ".*", elapsed in arb_duration()}
     );
 }
 
@@ -505,6 +560,7 @@ mod node_public_keys {
 
 mod pks_and_sks_contains_errors {
     use super::*;
+    use crate::common::arb_duration;
     use ic_crypto_internal_csp::vault::api::{
         ExternalPublicKeyError, LocalPublicKeyError, NodeKeysError, NodeKeysErrors,
         PksAndSksContainsErrors, SecretKeyError,
@@ -514,7 +570,8 @@ mod pks_and_sks_contains_errors {
 
     proptest_strategy_for_enum!(PksAndSksContainsErrors;
         NodeKeysErrors => (errors in arb_node_keys_errors()),
-        TransientInternalError => (error in ".*")
+        TransientInternalError => (error in ".*"),
+        Timeout => {method in ".*", elapsed in arb_duration()}
     );
 
     prop_compose! {
@@ -581,6 +638,7 @@ mod validate_pks_and_sks_key_pair_error {
 
 mod validate_pks_and_sks_error {
     use super::*;
+    use crate::common::arb_duration;
     use crate::validate_pks_and_sks_key_pair_error::arb_validate_pks_and_sks_key_pair_error;
     use ic_crypto_internal_csp::vault::api::ValidatePksAndSksError;
 
@@ -591,21 +649,25 @@ mod validate_pks_and_sks_error {
         TlsCertificateError => (error in arb_validate_pks_and_sks_key_pair_error()),
         DkgDealingEncryptionKeyError => (error in arb_validate_pks_and_sks_key_pair_error()),
         IdkgDealingEncryptionKeyError => (error in arb_validate_pks_and_sks_key_pair_error()),
-        TransientInternalError => (error in ".*")
+        TransientInternalError => (error in ".*"),
+        Timeout => {method in ".*", elapsed in arb_duration()}
     );
 }
 
 mod public_random_seed_generator_error {
     use super::*;
+    use crate::common::arb_duration;
     use ic_crypto_internal_csp::vault::api::PublicRandomSeedGeneratorError;
 
     proptest_strategy_for_enum!(PublicRandomSeedGeneratorError;
-        TransientInternalError => {internal_error in ".*"}
+        TransientInternalError => {internal_error in ".*"},
+        Timeout => {method in ".*", elapsed in arb_duration()}
     );
 }
 
 mod csp_tls_keygen_error {
     use super::*;
+    use crate::common::arb_duration;
     use ic_crypto_internal_csp::vault::api::CspTlsKeygenError;
 
     proptest_strategy_for_enum!(CspTlsKeygenError;
@@ -613,11 +675,13 @@ mod csp_tls_keygen_error {
         InternalError => {internal_error in ".*"},
         DuplicateKeyId => {key_id in arb_key_id()},
         TransientInternalError => {internal_error in ".*"},
+        Timeout => {method in ".*", elapsed in arb_duration()},
     );
 }
 
 mod csp_tls_sign_error {
     use super::*;
+    use crate::common::arb_duration;
     use ic_crypto_internal_csp::vault::api::CspTlsSignError;
 
     proptest_strategy_for_enum!(CspTlsSignError;
@@ -625,6 +689,218 @@ mod csp_tls_sign_error {
         WrongSecretKeyType => {algorithm in arb_algorithm_id(), secret_key_variant in ".*"},
         MalformedSecretKey => {error in ".*"},
         SigningFailed => {error in ".*"},
-        TransientInternalError => {internal_error in ".*"}
+        TransientInternalError => {internal_error in ".*"},
+        Timeout => {method in ".*", elapsed in arb_duration()}
+    );
+}
+
+mod ni_dkg_transcript {
+    use super::*;
+    use crate::common::{arb_96_bytes, arb_node_id, arb_registry_version, arb_subnet_id};
+    use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::ni_dkg_groth20_bls12_381::Transcript;
+    use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::CspNiDkgTranscript;
+    use ic_crypto_internal_types::sign::threshold_sig::public_coefficients::bls12_381::PublicCoefficientsBytes;
+    use ic_crypto_internal_types::sign::threshold_sig::public_key::bls12_381::PublicKeyBytes;
+    use ic_types::crypto::threshold_sig::ni_dkg::config::NiDkgThreshold;
+    use ic_types::crypto::threshold_sig::ni_dkg::{
+        NiDkgId, NiDkgReceivers, NiDkgTag, NiDkgTargetId, NiDkgTargetSubnet, NiDkgTranscript,
+    };
+    use ic_types::{Height, NumberOfNodes};
+    use proptest::collection::btree_set;
+    use proptest::prelude::{prop_oneof, Just, Strategy};
+    use std::collections::BTreeMap;
+
+    fn arb_ni_dkg_tag() -> impl Strategy<Value = NiDkgTag> {
+        prop_oneof![Just(NiDkgTag::LowThreshold), Just(NiDkgTag::HighThreshold)]
+    }
+
+    fn arb_ni_dkg_target_subnet() -> impl Strategy<Value = NiDkgTargetSubnet> {
+        prop_oneof![
+            Just(NiDkgTargetSubnet::Local),
+            uniform32(any::<u8>()).prop_map(|id| NiDkgTargetSubnet::Remote(NiDkgTargetId::new(id)))
+        ]
+    }
+
+    prop_compose! {
+        fn arb_ni_dkg_id()(
+            start_block_height in any::<u64>(),
+            dealer_subnet in arb_subnet_id(),
+            dkg_tag in arb_ni_dkg_tag(),
+            target_subnet in arb_ni_dkg_target_subnet()
+        ) -> NiDkgId {
+            NiDkgId {
+                start_block_height: Height::from(start_block_height),
+                dealer_subnet,
+                dkg_tag,
+                target_subnet
+            }
+        }
+    }
+
+    prop_compose! {
+        /// Generates an NI-DKG transcript whose threshold equals the number of
+        /// public coefficients. The transcript contains no receiver data.
+        pub fn arb_ni_dkg_transcript()(
+            dkg_id in arb_ni_dkg_id(),
+            committee in btree_set(arb_node_id(), 1..10),
+            registry_version in arb_registry_version(),
+            coefficients in vec(arb_96_bytes(), 1..10)
+        ) -> NiDkgTranscript {
+            let threshold = NumberOfNodes::from(coefficients.len() as u32);
+            NiDkgTranscript {
+                dkg_id,
+                threshold: NiDkgThreshold::new(threshold).expect("threshold must be non-zero"),
+                committee: NiDkgReceivers::new(committee).expect("committee must be non-empty"),
+                registry_version,
+                internal_csp_transcript: CspNiDkgTranscript::Groth20_Bls12_381(Transcript {
+                    public_coefficients: PublicCoefficientsBytes {
+                        coefficients: coefficients.into_iter().map(PublicKeyBytes).collect(),
+                    },
+                    receiver_data: BTreeMap::new(),
+                }),
+            }
+        }
+    }
+}
+
+mod csp_fs_encryption_public_key {
+    use super::*;
+    use crate::common::arb_48_bytes;
+    use ic_crypto_internal_types::curves::bls12_381::G1Bytes;
+    use ic_crypto_internal_types::encrypt::forward_secure::groth20_bls12_381::FsEncryptionPublicKey;
+    use ic_crypto_internal_types::encrypt::forward_secure::CspFsEncryptionPublicKey;
+
+    prop_compose! {
+        pub fn arb_csp_fs_encryption_public_key()(bytes in arb_48_bytes()) -> CspFsEncryptionPublicKey {
+            CspFsEncryptionPublicKey::Groth20_Bls12_381(FsEncryptionPublicKey(G1Bytes(bytes)))
+        }
+    }
+}
+
+mod csp_ni_dkg_dealing {
+    use super::*;
+    use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::CspNiDkgDealing;
+
+    prop_compose! {
+        /// Generates a well-formed NI-DKG dealing for a single receiver, which
+        /// does not verify.
+        pub fn arb_csp_ni_dkg_dealing()(seed in any::<u8>()) -> CspNiDkgDealing {
+            CspNiDkgDealing::placeholder_to_delete(seed)
+        }
+    }
+}
+
+mod csp_dkg_verify_dealing_error {
+    use super::*;
+    use crate::common::arb_duration;
+    use ic_crypto_internal_threshold_sig_bls12381::api::dkg_errors::{
+        InternalError, InvalidArgumentError, MalformedPublicKeyError, SizeError,
+    };
+    use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors::CspDkgVerifyDealingError;
+    use ic_types::NumberOfNodes;
+    use proptest::prelude::Strategy;
+
+    prop_compose! {
+        pub(crate) fn arb_invalid_argument_error()(message in ".*") -> InvalidArgumentError {
+            InvalidArgumentError { message }
+        }
+    }
+
+    prop_compose! {
+        fn arb_malformed_public_key_error()(
+            algorithm in arb_algorithm_id(),
+            key_bytes in proptest::option::of(vec(any::<u8>(), 0..100)),
+            internal_error in ".*"
+        ) -> MalformedPublicKeyError {
+            MalformedPublicKeyError { algorithm, key_bytes, internal_error }
+        }
+    }
+
+    prop_compose! {
+        fn arb_size_error()(message in ".*") -> SizeError {
+            SizeError { message }
+        }
+    }
+
+    prop_compose! {
+        pub(crate) fn arb_internal_error()(internal_error in ".*") -> InternalError {
+            InternalError { internal_error }
+        }
+    }
+
+    proptest_strategy_for_enum!(CspDkgVerifyDealingError;
+        UnsupportedAlgorithmId => (algorithm_id in arb_algorithm_id()),
+        InvalidThresholdError => (error in arb_invalid_argument_error()),
+        MisnumberedReceiverError => {receiver_index in any::<u32>(), number_of_receivers in any::<u32>().prop_map(NumberOfNodes::from)},
+        MalformedFsPublicKeyError => {receiver_index in any::<u32>(), error in arb_malformed_public_key_error()},
+        MalformedDealingError => (error in arb_invalid_argument_error()),
+        InvalidDealingError => (error in arb_invalid_argument_error()),
+        SizeError => (error in arb_size_error()),
+        TransientInternalError => (error in arb_internal_error()),
+        Timeout => {method in ".*", elapsed in arb_duration()}
+    );
+}
+
+mod csp_dkg_load_private_key_error {
+    use super::*;
+    use crate::common::arb_duration;
+    use crate::csp_dkg_verify_dealing_error::{arb_internal_error, arb_invalid_argument_error};
+    use ic_crypto_internal_threshold_sig_bls12381::api::dkg_errors::{
+        KeyNotFoundError, MalformedDataError, MalformedSecretKeyError,
+    };
+    use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors::CspDkgLoadPrivateKeyError;
+    use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::Epoch;
+    use proptest::prelude::Strategy;
+
+    prop_compose! {
+        fn arb_key_not_found_error()(internal_error in ".*", key_id in ".*") -> KeyNotFoundError {
+            KeyNotFoundError { internal_error, key_id }
+        }
+    }
+
+    prop_compose! {
+        fn arb_malformed_secret_key_error()(
+            algorithm in arb_algorithm_id(),
+            internal_error in ".*"
+        ) -> MalformedSecretKeyError {
+            MalformedSecretKeyError { algorithm, internal_error }
+        }
+    }
+
+    prop_compose! {
+        fn arb_malformed_data_error()(
+            algorithm in arb_algorithm_id(),
+            internal_error in ".*",
+            data in proptest::option::of(vec(any::<u8>(), 0..100))
+        ) -> MalformedDataError {
+            MalformedDataError { algorithm, internal_error, data }
+        }
+    }
+
+    proptest_strategy_for_enum!(CspDkgLoadPrivateKeyError;
+        UnsupportedAlgorithmId => (algorithm_id in arb_algorithm_id()),
+        KeyNotFoundError => (error in arb_key_not_found_error()),
+        MalformedSecretKeyError => (error in arb_malformed_secret_key_error()),
+        MalformedTranscriptError => (error in arb_malformed_data_error()),
+        InvalidTranscriptError => (error in arb_invalid_argument_error()),
+        EpochTooOldError => {ciphertext_epoch in any::<u32>().prop_map(Epoch::from), secret_key_epoch in any::<u32>().prop_map(Epoch::from)},
+        KeyIdInstantiationError => (error in ".*"),
+        InternalError => (error in arb_internal_error()),
+        TransientInternalError => (error in arb_internal_error()),
+        Timeout => {method in ".*", elapsed in arb_duration()},
+        MalformedPublicKeyError => (error in arb_malformed_data_error())
+    );
+}
+
+mod csp_dkg_retain_threshold_keys_error {
+    use super::*;
+    use crate::common::arb_duration;
+    use crate::csp_dkg_verify_dealing_error::arb_internal_error;
+    use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors::CspDkgRetainThresholdKeysError;
+
+    proptest_strategy_for_enum!(CspDkgRetainThresholdKeysError;
+        KeyIdInstantiationError => (error in ".*"),
+        TransientInternalError => (error in arb_internal_error()),
+        Timeout => {method in ".*", elapsed in arb_duration()}
     );
 }
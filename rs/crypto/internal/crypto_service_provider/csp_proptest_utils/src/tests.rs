@@ -41,7 +41,8 @@ should_have_a_strategy_for_each_variant!(
     UnsupportedAlgorithm { .. },
     WrongSecretKeyType { .. },
     MalformedSecretKey { .. },
-    TransientInternalError { .. }
+    TransientInternalError { .. },
+    Timeout { .. }
 );
 
 use ic_crypto_internal_csp::types::CspSignature;
@@ -53,7 +54,8 @@ should_have_a_strategy_for_each_variant!(
     Ed25519(_),
     MultiBls12_381(_),
     ThresBls12_381(_),
-    RsaSha256(_)
+    RsaSha256(_),
+    SchnorrSecp256k1Bip340(_)
 );
 
 use ic_crypto_internal_csp::types::MultiBls12_381_Signature;
@@ -85,7 +87,8 @@ should_have_a_strategy_for_each_variant!(
     EcdsaSecp256k1(_),
     Ed25519(_),
     MultiBls12_381(_),
-    RsaSha256(_)
+    RsaSha256(_),
+    SchnorrSecp256k1Bip340(_)
 );
 
 use ic_crypto_internal_csp::types::CspPop;
@@ -95,6 +98,13 @@ should_have_a_strategy_for_each_variant!(
     MultiBls12_381(_)
 );
 
+use ic_crypto_internal_csp::vault::api::CspBasicSignatureKeygenAlgorithm;
+should_have_a_strategy_for_each_variant!(
+    CspBasicSignatureKeygenAlgorithm,
+    CspBasicSignatureKeygenAlgorithm::SchnorrSecp256k1Bip340,
+    SchnorrSecp256k1Bip340
+);
+
 use ic_crypto_internal_csp::vault::api::CspBasicSignatureKeygenError;
 should_have_a_strategy_for_each_variant!(
     CspBasicSignatureKeygenError,
@@ -103,7 +113,8 @@ should_have_a_strategy_for_each_variant!(
     },
     InternalError { .. },
     DuplicateKeyId { .. },
-    TransientInternalError { .. }
+    TransientInternalError { .. },
+    Timeout { .. }
 );
 
 use ic_crypto_internal_csp::vault::api::CspMultiSignatureError;
@@ -115,7 +126,9 @@ should_have_a_strategy_for_each_variant!(
     SecretKeyNotFound { .. },
     UnsupportedAlgorithm { .. },
     WrongSecretKeyType { .. },
-    TransientInternalError { .. }
+    TransientInternalError { .. },
+    Timeout { .. },
+    BatchMessageError { .. }
 );
 
 use ic_crypto_internal_csp::vault::api::CspMultiSignatureKeygenError;
@@ -127,7 +140,8 @@ should_have_a_strategy_for_each_variant!(
     MalformedPublicKey { .. },
     InternalError { .. },
     DuplicateKeyId { .. },
-    TransientInternalError { .. }
+    TransientInternalError { .. },
+    Timeout { .. }
 );
 
 use ic_crypto_internal_csp::api::CspThresholdSignError;
@@ -141,7 +155,9 @@ should_have_a_strategy_for_each_variant!(
     WrongSecretKeyType { .. },
     MalformedSecretKey { .. },
     KeyIdInstantiationError(..),
-    TransientInternalError { .. }
+    InvalidArgument { .. },
+    TransientInternalError { .. },
+    Timeout { .. }
 );
 
 use ic_crypto_internal_csp::vault::api::CspSecretKeyStoreContainsError;
@@ -150,7 +166,8 @@ should_have_a_strategy_for_each_variant!(
     CspSecretKeyStoreContainsError::TransientInternalError {
         internal_error: "dummy error to match upon".to_string(),
     },
-    TransientInternalError { .. }
+    TransientInternalError { .. },
+    Timeout { .. }
 );
 
 use ic_types::registry::RegistryClientError;
@@ -198,6 +215,7 @@ should_have_a_strategy_for_each_variant!(
     CspPublicKeyStoreError,
     CspPublicKeyStoreError::TransientInternalError("dummy error to match upon".to_string(),),
     TransientInternalError(_),
+    Timeout { .. },
 );
 
 use ic_crypto_internal_csp::vault::api::PksAndSksContainsErrors;
@@ -206,6 +224,7 @@ should_have_a_strategy_for_each_variant!(
     PksAndSksContainsErrors::TransientInternalError("dummy error to match upon".to_string()),
     NodeKeysErrors(_),
     TransientInternalError(_),
+    Timeout { .. },
 );
 
 use ic_crypto_internal_csp::vault::api::ValidatePksAndSksKeyPairError;
@@ -227,6 +246,7 @@ should_have_a_strategy_for_each_variant!(
     DkgDealingEncryptionKeyError(_),
     IdkgDealingEncryptionKeyError(_),
     TransientInternalError(_),
+    Timeout { .. },
     EmptyPublicKeyStore
 );
 
@@ -237,6 +257,7 @@ should_have_a_strategy_for_each_variant!(
         internal_error: "dummy error to match upon".to_string()
     },
     TransientInternalError { .. },
+    Timeout { .. },
 );
 
 use ic_crypto_internal_csp::vault::api::CspTlsKeygenError;
@@ -249,6 +270,7 @@ should_have_a_strategy_for_each_variant!(
     InternalError { .. },
     DuplicateKeyId { .. },
     TransientInternalError { .. },
+    Timeout { .. },
 );
 
 use ic_crypto_internal_csp::vault::api::CspTlsSignError;
@@ -262,4 +284,53 @@ should_have_a_strategy_for_each_variant!(
     MalformedSecretKey { .. },
     SigningFailed { .. },
     TransientInternalError { .. },
+    Timeout { .. },
+);
+
+use ic_crypto_internal_threshold_sig_bls12381::api::dkg_errors::InternalError;
+use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors::CspDkgVerifyDealingError;
+should_have_a_strategy_for_each_variant!(
+    CspDkgVerifyDealingError,
+    CspDkgVerifyDealingError::TransientInternalError(InternalError {
+        internal_error: "dummy error to match upon".to_string()
+    }),
+    UnsupportedAlgorithmId(_),
+    InvalidThresholdError(_),
+    MisnumberedReceiverError { .. },
+    MalformedFsPublicKeyError { .. },
+    MalformedDealingError(_),
+    InvalidDealingError(_),
+    SizeError(_),
+    TransientInternalError(_),
+    Timeout { .. },
+);
+
+use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors::CspDkgLoadPrivateKeyError;
+should_have_a_strategy_for_each_variant!(
+    CspDkgLoadPrivateKeyError,
+    CspDkgLoadPrivateKeyError::TransientInternalError(InternalError {
+        internal_error: "dummy error to match upon".to_string()
+    }),
+    UnsupportedAlgorithmId(_),
+    KeyNotFoundError(_),
+    MalformedSecretKeyError(_),
+    MalformedTranscriptError(_),
+    InvalidTranscriptError(_),
+    EpochTooOldError { .. },
+    KeyIdInstantiationError(_),
+    InternalError(_),
+    TransientInternalError(_),
+    Timeout { .. },
+    MalformedPublicKeyError(_),
+);
+
+use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors::CspDkgRetainThresholdKeysError;
+should_have_a_strategy_for_each_variant!(
+    CspDkgRetainThresholdKeysError,
+    CspDkgRetainThresholdKeysError::TransientInternalError(InternalError {
+        internal_error: "dummy error to match upon".to_string()
+    }),
+    KeyIdInstantiationError(_),
+    TransientInternalError(_),
+    Timeout { .. },
 );
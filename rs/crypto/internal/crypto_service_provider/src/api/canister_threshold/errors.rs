@@ -3,14 +3,26 @@ use crate::KeyId;
 use ic_crypto_internal_threshold_sig_canister_threshold_sig::CanisterThresholdSerializationError;
 use ic_interfaces::crypto::IDkgDealingEncryptionKeyRotationError;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Errors encountered during generation of a MEGa encryption key pair.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum CspCreateMEGaKeyError {
     SerializationError(CanisterThresholdSerializationError),
-    TransientInternalError { internal_error: String },
-    DuplicateKeyId { key_id: KeyId },
-    InternalError { internal_error: String },
+    TransientInternalError {
+        internal_error: String,
+    },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
+    DuplicateKeyId {
+        key_id: KeyId,
+    },
+    InternalError {
+        internal_error: String,
+    },
 }
 
 impl std::fmt::Display for CspCreateMEGaKeyError {
@@ -26,6 +38,11 @@ impl std::fmt::Display for CspCreateMEGaKeyError {
                 "Error creating MEGa keypair: Transient internal error: {}",
                 internal_error
             ),
+            Self::Timeout { method, elapsed } => write!(
+                f,
+                "Error creating MEGa keypair: RPC call {} timed out after {:?}",
+                method, elapsed
+            ),
             Self::DuplicateKeyId { key_id } => {
                 write!(f, "A key with ID {} has already been inserted", key_id)
             }
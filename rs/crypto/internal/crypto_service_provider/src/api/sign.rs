@@ -111,4 +111,28 @@ pub trait CspSigner {
         msg: &[u8],
         algorithm_id: AlgorithmId,
     ) -> CryptoResult<()>;
+
+    /// Verify a batch of multisignatures
+    ///
+    /// This is faster than verifying the multisignatures one by one with
+    /// `verify_multisig`, as the batch is verified with multi-pairings that
+    /// share their final exponentiation.
+    ///
+    /// # Arguments
+    /// * `batch` a slice of multisignatures, each along with the message data
+    ///   it is to be verified on and the public keys used to create it
+    /// * `algorithm_id` the signature algorithm
+    /// # Errors
+    /// * `CryptoError::AlgorithmNotSupported` if the signature algorithm used
+    ///   does not support multisignatures.
+    /// * `CryptoError::SignatureVerification` if any of the multisignatures
+    ///   was found to be invalid, or a public key is not of the algorithm.
+    /// * `CryptoError::MalformedSignature` if any multisignature is malformed.
+    /// # Returns
+    /// `Ok(())` if all signatures are valid or an `Err` otherwise
+    fn verify_multisig_batch(
+        &self,
+        batch: &[(CspSignature, &[u8], Vec<CspPublicKey>)],
+        algorithm_id: AlgorithmId,
+    ) -> CryptoResult<()>;
 }
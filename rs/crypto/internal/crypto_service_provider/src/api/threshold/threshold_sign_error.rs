@@ -1,6 +1,7 @@
 use super::*;
 use crate::KeyId;
 use ic_crypto_internal_threshold_sig_bls12381::api::threshold_sign_error::ClibThresholdSignError;
+use std::time::Duration;
 
 /// Errors occurring while performing threshold signature generation
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
@@ -17,9 +18,17 @@ pub enum CspThresholdSignError {
         algorithm: AlgorithmId,
     },
     KeyIdInstantiationError(String),
+    InvalidArgument {
+        message: String,
+    },
     TransientInternalError {
         internal_error: String,
     },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
 }
 
 impl From<ClibThresholdSignError> for CspThresholdSignError {
@@ -65,9 +74,15 @@ impl fmt::Display for CspThresholdSignError {
                 "Unable to parse the secret key with algorithm id {:?}",
                 algorithm
             ),
+            CspThresholdSignError::InvalidArgument { message } => {
+                write!(f, "Invalid argument: {}", message)
+            }
             CspThresholdSignError::TransientInternalError { internal_error } => {
                 write!(f, "Transient internal error: {}", internal_error)
             }
+            CspThresholdSignError::Timeout { method, elapsed } => {
+                write!(f, "RPC call {} timed out after {:?}", method, elapsed)
+            }
             CspThresholdSignError::KeyIdInstantiationError(message) => {
                 write!(f, "KeyID instantiation error: {}", message)
             }
@@ -7,6 +7,10 @@ pub struct SecretKeyV1 {
     /// Rust's `to_string()` of `Scope`
     #[prost(string, tag = "2")]
     pub scope: ::prost::alloc::string::String,
+    /// Whether the key was marked inactive, e.g., after a key rotation. Inactive
+    /// keys are kept, but must not be used to sign new content.
+    #[prost(bool, tag = "3")]
+    pub inactive: bool,
 }
 /// SecretKeyStore stores secret keys.
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -36,6 +36,16 @@ impl KeyId {
     pub fn get(&self) -> [u8; 32] {
         self.0
     }
+
+    /// Derives the `KeyId` of a key pair from its public key, such that the
+    /// same public key always results in the same `KeyId`, independently of
+    /// the vault that generated or stores the key.
+    ///
+    /// The derivation is the one of `KeyId::try_from((AlgorithmId, &B))`
+    /// applied to the algorithm and the raw bytes of the public key.
+    pub fn from_public_key(public_key: &CspPublicKey) -> KeyId {
+        KeyId::try_from(public_key).expect("the size of a public key always fits in a u32")
+    }
 }
 
 impl fmt::Debug for KeyId {
@@ -13,6 +13,49 @@ fn should_fail_to_create_key_id_from_mega_key_with_unsupported_curve() {
     );
 }
 
+mod from_public_key {
+    use super::*;
+    use crate::CspPublicKey;
+    use ic_crypto_internal_test_vectors::ed25519::TESTVEC_MESSAGE_LEN_256_BIT_STABILITY_1_PK;
+    use ic_crypto_internal_test_vectors::ed25519::TESTVEC_RFC8032_ED25519_SHA_ABC_PK;
+    use ic_crypto_internal_test_vectors::multi_bls12_381::TESTVEC_MULTI_BLS12_381_1_PK;
+
+    #[test]
+    fn should_be_consistent_with_try_from_public_key() {
+        for public_key in [
+            CspPublicKey::ed25519_from_hex(TESTVEC_RFC8032_ED25519_SHA_ABC_PK),
+            CspPublicKey::multi_bls12381_from_hex(TESTVEC_MULTI_BLS12_381_1_PK),
+        ] {
+            assert_eq!(
+                KeyId::from_public_key(&public_key),
+                KeyId::try_from(&public_key).expect("failed to compute key ID")
+            );
+        }
+    }
+
+    #[test]
+    fn should_produce_same_key_id_for_same_public_key() {
+        let public_key = CspPublicKey::ed25519_from_hex(TESTVEC_RFC8032_ED25519_SHA_ABC_PK);
+
+        assert_eq!(
+            KeyId::from_public_key(&public_key),
+            KeyId::from_public_key(&public_key.clone())
+        );
+    }
+
+    #[test]
+    fn should_produce_different_key_ids_for_different_public_keys() {
+        let public_key_1 = CspPublicKey::ed25519_from_hex(TESTVEC_RFC8032_ED25519_SHA_ABC_PK);
+        let public_key_2 =
+            CspPublicKey::ed25519_from_hex(TESTVEC_MESSAGE_LEN_256_BIT_STABILITY_1_PK);
+
+        assert_ne!(
+            KeyId::from_public_key(&public_key_1),
+            KeyId::from_public_key(&public_key_2)
+        );
+    }
+}
+
 mod stability_tests {
     use super::*;
     use crate::CspPublicKey;
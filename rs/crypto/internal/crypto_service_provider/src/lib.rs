@@ -108,6 +108,11 @@ impl<T> CspRwLock<T> {
         read_guard
     }
 
+    /// Returns whether the lock is currently held by a writer.
+    pub fn is_locked_exclusive(&self) -> bool {
+        self.rw_lock.is_locked_exclusive()
+    }
+
     fn observe(&self, metrics: &CryptoMetrics, access: &str, start_time: Option<Instant>) {
         metrics.observe_lock_acquisition_duration_seconds(&self.name, access, start_time);
     }
@@ -14,7 +14,12 @@ mock! {
         fn insert_or_replace(&mut self, id: KeyId, key: CspSecretKey, scope: Option<Scope>) -> Result<(), SecretKeyStoreWriteError>;
         fn get(&self, id: &KeyId) -> Option<CspSecretKey>;
         fn contains(&self, id: &KeyId) -> bool;
+        fn key_count(&self) -> usize;
+        #[cfg(feature = "key_import_export")]
+        fn entries(&self) -> Vec<(KeyId, CspSecretKey, Option<Scope>)>;
         fn remove(&mut self, id: &KeyId) -> Result<bool, SecretKeyStoreWriteError>;
+        fn set_key_active(&mut self, id: &KeyId, active: bool) -> Result<bool, SecretKeyStoreWriteError>;
+        fn is_key_inactive(&self, id: &KeyId) -> bool;
         fn retain<F>(&mut self, filter: F, scope: Scope) -> Result<(), SecretKeyStoreWriteError>
             where F: Fn(&KeyId, &CspSecretKey) -> bool + 'static;
         fn retain_would_modify_keystore<F>(&self, filter: F, scope: Scope) -> bool
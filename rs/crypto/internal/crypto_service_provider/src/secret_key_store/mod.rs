@@ -58,6 +58,14 @@ pub trait SecretKeyStore: Send + Sync {
     /// Checks if the store contains a key with the given `id`.
     fn contains(&self, id: &KeyId) -> bool;
 
+    /// Returns the number of keys in the store.
+    fn key_count(&self) -> usize;
+
+    /// Returns all keys in the store together with their scope, in no
+    /// particular order.
+    #[cfg(feature = "key_import_export")]
+    fn entries(&self) -> Vec<(KeyId, CspSecretKey, Option<Scope>)>;
+
     /// Removes the key with the given `id` from the store.
     ///
     /// The return value indicates whether a key with the given `id` was
@@ -65,6 +73,27 @@ pub trait SecretKeyStore: Send + Sync {
     /// could not be written.
     fn remove(&mut self, id: &KeyId) -> Result<bool, SecretKeyStoreWriteError>;
 
+    /// Marks the key with the given `id` as active or inactive.
+    ///
+    /// Inactive keys are kept in the store, e.g., to decrypt messages in
+    /// flight after a key rotation, but must not be used to sign new content.
+    /// Keys are active when inserted and stay active when replaced.
+    ///
+    /// The return value indicates whether the store contains a key with the
+    /// given `id`, or an error if the updated secret key store could not be
+    /// written.
+    fn set_key_active(
+        &mut self,
+        id: &KeyId,
+        active: bool,
+    ) -> Result<bool, SecretKeyStoreWriteError>;
+
+    /// Checks if the key with the given `id` was marked inactive with
+    /// [`Self::set_key_active`].
+    ///
+    /// Returns `false` if the store does not contain a key with the given `id`.
+    fn is_key_inactive(&self, id: &KeyId) -> bool;
+
     /// Keeps only entries in a scope for which the filter function returns
     /// `true` and removes the rest.
     ///
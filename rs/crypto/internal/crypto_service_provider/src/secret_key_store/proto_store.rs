@@ -12,7 +12,7 @@ use ic_logger::{debug, info, replica_logger::no_op_logger, warn, ReplicaLogger};
 use parking_lot::RwLock;
 use prost::Message;
 use std::borrow::BorrowMut;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{ErrorKind, Write};
 use std::path::{Path, PathBuf};
@@ -43,6 +43,9 @@ pub struct ProtoSecretKeyStore {
     proto_file: PathBuf,
     old_proto_file_to_zeroize: PathBuf,
     keys: Arc<RwLock<SecretKeys>>,
+    // Only accessed while holding the lock on `keys`, and released before
+    // the keys are written to disk.
+    inactive_key_ids: RwLock<HashSet<KeyId>>,
     logger: ReplicaLogger,
     metrics: Arc<CryptoMetrics>,
 }
@@ -122,12 +125,13 @@ impl ProtoSecretKeyStore {
             Self::check_proto_file_is_regular_file_or_panic(&proto_file);
         }
         let old_proto_file_to_zeroize = dir.join(format!("{}.old", file_name));
-        let secret_keys = Self::sks_data_from_disk_or_new(&proto_file);
+        let (secret_keys, inactive_key_ids) = Self::sks_data_from_disk_or_new(&proto_file);
         let logger = logger.unwrap_or_else(no_op_logger);
         let sks = ProtoSecretKeyStore {
             proto_file,
             old_proto_file_to_zeroize,
             keys: Arc::new(RwLock::new(secret_keys)),
+            inactive_key_ids: RwLock::new(inactive_key_ids),
             logger,
             metrics,
         };
@@ -270,7 +274,10 @@ impl ProtoSecretKeyStore {
         &self,
         secret_keys: &SecretKeys,
     ) -> Result<(), SecretKeyStoreWriteError> {
-        let sks_proto = ProtoSecretKeyStore::secret_keys_to_sks_proto(secret_keys)?;
+        let sks_proto = ProtoSecretKeyStore::secret_keys_to_sks_proto(
+            secret_keys,
+            &self.inactive_key_ids.read(),
+        )?;
         match ic_sys::fs::write_protobuf_using_tmp_file(&self.proto_file, &sks_proto) {
             Ok(()) => {
                 debug!(
@@ -297,14 +304,15 @@ impl ProtoSecretKeyStore {
         }
     }
 
-    fn sks_data_from_disk_or_new(sks_data_file: &Path) -> SecretKeys {
+    fn sks_data_from_disk_or_new(sks_data_file: &Path) -> (SecretKeys, HashSet<KeyId>) {
         let proto_file = match fs::read(sks_data_file) {
             Ok(data) => {
                 let sks_pb = pb::SecretKeyStore::decode(&*data).unwrap_or_else(
                     |_ignored_so_that_no_data_is_leaked| panic!("error parsing SKS protobuf data"),
                 );
+                let inactive_key_ids = ProtoSecretKeyStore::sks_proto_to_inactive_key_ids(&sks_pb);
                 let keys = ProtoSecretKeyStore::migrate_to_current_version(sks_pb);
-                Some(keys)
+                Some((keys, inactive_key_ids))
             }
             Err(err) => {
                 if err.kind() == ErrorKind::NotFound {
@@ -373,6 +381,15 @@ impl ProtoSecretKeyStore {
         secret_keys
     }
 
+    fn sks_proto_to_inactive_key_ids(sks_proto: &pb::SecretKeyStore) -> HashSet<KeyId> {
+        sks_proto
+            .key_id_to_secret_key_v1
+            .iter()
+            .filter(|(_, sk_proto)| sk_proto.inactive)
+            .map(|(key_id_hex, _)| key_id_from_hex(key_id_hex))
+            .collect()
+    }
+
     fn ensure_version_is_supported(version: u32) {
         let supported_versions = [1, 2, CURRENT_SKS_VERSION];
         if !supported_versions.contains(&version) {
@@ -382,6 +399,7 @@ impl ProtoSecretKeyStore {
 
     fn secret_keys_to_sks_proto(
         secret_keys: &SecretKeys,
+        inactive_key_ids: &HashSet<KeyId>,
     ) -> Result<pb::SecretKeyStore, SecretKeyStoreWriteError> {
         let mut sks_proto = pb::SecretKeyStore {
             version: CURRENT_SKS_VERSION,
@@ -396,14 +414,17 @@ impl ProtoSecretKeyStore {
                         key_id
                     ))
                 })?;
+            let inactive = inactive_key_ids.contains(key_id);
             let sk_pb = match maybe_scope {
                 Some(scope) => pb::SecretKeyV1 {
                     csp_secret_key: key_as_cbor,
                     scope: String::from(scope),
+                    inactive,
                 },
                 None => pb::SecretKeyV1 {
                     csp_secret_key: key_as_cbor,
                     scope: String::from(""),
+                    inactive,
                 },
             };
             sks_proto.key_id_to_secret_key_v1.insert(key_id_hex, sk_pb);
@@ -462,10 +483,24 @@ impl SecretKeyStore for ProtoSecretKeyStore {
         self.get(id).is_some()
     }
 
+    fn key_count(&self) -> usize {
+        self.keys.read().len()
+    }
+
+    #[cfg(feature = "key_import_export")]
+    fn entries(&self) -> Vec<(KeyId, CspSecretKey, Option<Scope>)> {
+        self.keys
+            .read()
+            .iter()
+            .map(|(id, (key, scope))| (*id, key.clone(), *scope))
+            .collect()
+    }
+
     fn remove(&mut self, id: &KeyId) -> Result<bool, SecretKeyStoreWriteError> {
         with_write_lock(&self.keys, |keys| match keys.get(id) {
             Some(_) => {
                 keys.remove(id);
+                self.inactive_key_ids.write().remove(id);
                 self.write_secret_keys_to_disk_and_cleanup_old_file(keys)?;
                 debug!(self.logger, "Removed secret key {}", id);
                 Ok(true)
@@ -474,6 +509,38 @@ impl SecretKeyStore for ProtoSecretKeyStore {
         })
     }
 
+    fn set_key_active(
+        &mut self,
+        id: &KeyId,
+        active: bool,
+    ) -> Result<bool, SecretKeyStoreWriteError> {
+        with_write_lock(&self.keys, |keys| {
+            if !keys.contains_key(id) {
+                return Ok(false);
+            }
+            let modified = if active {
+                self.inactive_key_ids.write().remove(id)
+            } else {
+                self.inactive_key_ids.write().insert(*id)
+            };
+            if modified {
+                self.write_secret_keys_to_disk_and_cleanup_old_file(keys)?;
+                debug!(
+                    self.logger,
+                    "Marked secret key {} as {}",
+                    id,
+                    if active { "active" } else { "inactive" }
+                );
+            }
+            Ok(true)
+        })
+    }
+
+    fn is_key_inactive(&self, id: &KeyId) -> bool {
+        let _keys = self.keys.read();
+        self.inactive_key_ids.read().contains(id)
+    }
+
     fn retain<F>(&mut self, filter: F, scope: Scope) -> Result<(), SecretKeyStoreWriteError>
     where
         F: Fn(&KeyId, &CspSecretKey) -> bool,
@@ -486,6 +553,7 @@ impl SecretKeyStore for ProtoSecretKeyStore {
                 if maybe_scope != Some(scope) || filter(&key_id, &csp_key) {
                     keys.insert(key_id, (csp_key, maybe_scope));
                 } else {
+                    self.inactive_key_ids.write().remove(&key_id);
                     info!(
                         self.logger,
                         "Deleting key with ID {} with scope {}", key_id, scope
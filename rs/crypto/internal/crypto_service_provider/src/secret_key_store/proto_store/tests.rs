@@ -275,6 +275,85 @@ fn no_overwrites() {
     assert_eq!(key_1, key_store.get(&key_id_1).unwrap());
 }
 
+#[test]
+fn should_mark_key_inactive_and_active_again() {
+    let rng = &mut reproducible_rng();
+    let mut key_store = proto_key_store();
+    let key_id: KeyId = make_key_id(rng);
+    let key = make_secret_key(rng);
+    key_store.insert(key_id, key.clone(), None).unwrap();
+    assert!(!key_store.is_key_inactive(&key_id));
+
+    assert!(key_store.set_key_active(&key_id, false).unwrap());
+    assert!(key_store.is_key_inactive(&key_id));
+    assert_eq!(key_store.get(&key_id), Some(key));
+
+    assert!(key_store.set_key_active(&key_id, true).unwrap());
+    assert!(!key_store.is_key_inactive(&key_id));
+}
+
+#[test]
+fn should_not_mark_nonexisting_key_inactive() {
+    let rng = &mut reproducible_rng();
+    let mut key_store = proto_key_store();
+    let non_existing_key_id: KeyId = make_key_id(rng);
+
+    assert!(!key_store
+        .set_key_active(&non_existing_key_id, false)
+        .unwrap());
+    assert!(!key_store.is_key_inactive(&non_existing_key_id));
+}
+
+#[test]
+fn should_persist_inactive_keys() {
+    let rng = &mut reproducible_rng();
+    let temp_dir = mk_temp_dir_with_permissions(0o700);
+    let file_name = "sks_data.pb";
+    let open = || {
+        ProtoSecretKeyStore::open(
+            temp_dir.path(),
+            file_name,
+            None,
+            Arc::new(CryptoMetrics::none()),
+        )
+    };
+    let inactive_key_id: KeyId = make_key_id(rng);
+    let active_key_id: KeyId = make_key_id(rng);
+    {
+        let mut key_store = open();
+        key_store
+            .insert(inactive_key_id, make_secret_key(rng), None)
+            .unwrap();
+        key_store
+            .insert(active_key_id, make_secret_key(rng), None)
+            .unwrap();
+        key_store.set_key_active(&inactive_key_id, false).unwrap();
+    }
+
+    let key_store = open();
+
+    assert!(key_store.is_key_inactive(&inactive_key_id));
+    assert!(!key_store.is_key_inactive(&active_key_id));
+}
+
+#[test]
+fn should_not_mark_reinserted_key_inactive() {
+    let rng = &mut reproducible_rng();
+    let mut key_store = proto_key_store();
+    let key_id: KeyId = make_key_id(rng);
+    key_store
+        .insert(key_id, make_secret_key(rng), None)
+        .unwrap();
+    key_store.set_key_active(&key_id, false).unwrap();
+
+    assert!(key_store.remove(&key_id).unwrap());
+    key_store
+        .insert(key_id, make_secret_key(rng), None)
+        .unwrap();
+
+    assert!(!key_store.is_key_inactive(&key_id));
+}
+
 #[test]
 fn should_deserialize_all_existing_secret_key_stores() {
     for version in SecretKeyStoreVersion::all_versions() {
@@ -1315,6 +1394,38 @@ mod insert_or_replace {
         ]
     }
 }
+
+#[cfg(feature = "key_import_export")]
+mod entries {
+    use super::*;
+
+    #[test]
+    fn should_return_no_entries_for_empty_store() {
+        let key_store = proto_key_store();
+
+        assert!(key_store.entries().is_empty());
+    }
+
+    #[test]
+    fn should_return_all_keys_with_their_scope() {
+        let rng = &mut reproducible_rng();
+        let mut key_store = proto_key_store();
+        let mut inserted = Vec::new();
+        for scope in [None, Some(Scope::Const(ConstScope::Test0)), None] {
+            let key_id = make_key_id(rng);
+            let secret_key = make_secret_key(rng);
+            assert!(key_store.insert(key_id, secret_key.clone(), scope).is_ok());
+            inserted.push((key_id, secret_key, scope));
+        }
+
+        let mut entries = key_store.entries();
+
+        entries.sort_by_key(|(key_id, _, _)| *key_id);
+        inserted.sort_by_key(|(key_id, _, _)| *key_id);
+        assert_eq!(entries, inserted);
+    }
+}
+
 fn copy_file_to_dir(source_file: &Path, target_dir: &Path) {
     let filename = source_file.file_name().expect("expected file name");
     let target_file = target_dir.join(filename);
@@ -75,10 +75,31 @@ impl SecretKeyStore for TempSecretKeyStore {
         self.store.contains(id)
     }
 
+    fn key_count(&self) -> usize {
+        self.store.key_count()
+    }
+
+    #[cfg(feature = "key_import_export")]
+    fn entries(&self) -> Vec<(KeyId, CspSecretKey, Option<Scope>)> {
+        self.store.entries()
+    }
+
     fn remove(&mut self, id: &KeyId) -> Result<bool, SecretKeyStoreWriteError> {
         self.store.remove(id)
     }
 
+    fn set_key_active(
+        &mut self,
+        id: &KeyId,
+        active: bool,
+    ) -> Result<bool, SecretKeyStoreWriteError> {
+        self.store.set_key_active(id, active)
+    }
+
+    fn is_key_inactive(&self, id: &KeyId) -> bool {
+        self.store.is_key_inactive(id)
+    }
+
     fn retain<F>(&mut self, filter: F, scope: Scope) -> Result<(), SecretKeyStoreWriteError>
     where
         F: Fn(&KeyId, &CspSecretKey) -> bool + 'static,
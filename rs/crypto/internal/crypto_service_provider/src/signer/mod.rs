@@ -7,10 +7,12 @@ use crate::vault::api::{CspBasicSignatureError, CspMultiSignatureError};
 use ic_crypto_internal_basic_sig_ecdsa_secp256k1 as ecdsa_secp256k1;
 use ic_crypto_internal_basic_sig_ecdsa_secp256r1 as ecdsa_secp256r1;
 use ic_crypto_internal_basic_sig_ed25519 as ed25519;
+use ic_crypto_internal_basic_sig_schnorr_bip340 as schnorr_bip340;
 use ic_crypto_internal_logmon::metrics::{MetricsDomain, MetricsResult};
 use ic_crypto_internal_multi_sig_bls12381 as multi_sig;
 use ic_crypto_sha2::Sha256;
 use ic_types::crypto::{AlgorithmId, CryptoError, CryptoResult};
+use rand::rngs::OsRng;
 
 #[cfg(test)]
 mod tests;
@@ -107,6 +109,11 @@ impl CspSigner for Csp {
             {
                 public_key.verify_pkcs1_sha256(msg, signature)
             }
+            (
+                AlgorithmId::SchnorrSecp256k1,
+                CspSignature::SchnorrSecp256k1Bip340(signature),
+                CspPublicKey::SchnorrSecp256k1Bip340(public_key),
+            ) => schnorr_bip340::verify(signature, msg, &public_key),
             (
                 AlgorithmId::MultiBls12_381,
                 CspSignature::MultiBls12_381(MultiBls12_381_Signature::Individual(signature)),
@@ -185,19 +192,8 @@ impl CspSigner for Csp {
                 AlgorithmId::MultiBls12_381,
                 CspSignature::MultiBls12_381(MultiBls12_381_Signature::Combined(signature)),
             ) => {
-                let signers: CryptoResult<Vec<multi_sig::types::PublicKeyBytes>> = signers
-                    .iter()
-                    .map(|signer| match signer {
-                        CspPublicKey::MultiBls12_381(signer) => Ok(*signer),
-                        _ => Err(CryptoError::SignatureVerification {
-                            algorithm: algorithm_id,
-                            public_key_bytes: signer.as_ref().to_vec(),
-                            sig_bytes: signature.0.to_vec(),
-                            internal_error: "Public key not of type MultiBls12_381".to_string(),
-                        }),
-                    })
-                    .collect();
-                multi_sig::verify_combined(msg, &signature, &signers?[..])
+                let signers = multi_bls12_381_public_keys(&signers, &signature, algorithm_id)?;
+                multi_sig::verify_combined(msg, &signature, &signers[..])
             }
             _ => Err(CryptoError::AlgorithmNotSupported {
                 algorithm: algorithm_id,
@@ -205,4 +201,56 @@ impl CspSigner for Csp {
             }),
         }
     }
+
+    fn verify_multisig_batch(
+        &self,
+        batch: &[(CspSignature, &[u8], Vec<CspPublicKey>)],
+        algorithm_id: AlgorithmId,
+    ) -> CryptoResult<()> {
+        if algorithm_id != AlgorithmId::MultiBls12_381 {
+            return Err(CryptoError::AlgorithmNotSupported {
+                algorithm: algorithm_id,
+                reason: "Not a multi-signature algorithm".to_string(),
+            });
+        }
+        let batch: Vec<_> = batch
+            .iter()
+            .map(|(signature, msg, signers)| match signature {
+                CspSignature::MultiBls12_381(MultiBls12_381_Signature::Combined(signature)) => {
+                    let signers = multi_bls12_381_public_keys(signers, signature, algorithm_id)?;
+                    Ok((*msg, signature, signers))
+                }
+                _ => Err(CryptoError::AlgorithmNotSupported {
+                    algorithm: signature.algorithm(),
+                    reason: "Not a combined multi-signature".to_string(),
+                }),
+            })
+            .collect::<CryptoResult<_>>()?;
+        let batch: Vec<_> = batch
+            .iter()
+            .map(|(msg, signature, signers)| (*msg, *signature, &signers[..]))
+            .collect();
+        multi_sig::verify_combined_batch(&batch, &mut OsRng)
+    }
+}
+
+/// Returns the MultiBls12_381 public keys of `signers`, or an error if any of
+/// them is of another type.
+fn multi_bls12_381_public_keys(
+    signers: &[CspPublicKey],
+    signature: &multi_sig::types::CombinedSignatureBytes,
+    algorithm_id: AlgorithmId,
+) -> CryptoResult<Vec<multi_sig::types::PublicKeyBytes>> {
+    signers
+        .iter()
+        .map(|signer| match signer {
+            CspPublicKey::MultiBls12_381(signer) => Ok(*signer),
+            _ => Err(CryptoError::SignatureVerification {
+                algorithm: algorithm_id,
+                public_key_bytes: signer.as_ref().to_vec(),
+                sig_bytes: signature.0.to_vec(),
+                internal_error: "Public key not of type MultiBls12_381".to_string(),
+            }),
+        })
+        .collect()
 }
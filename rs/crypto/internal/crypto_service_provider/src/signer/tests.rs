@@ -321,6 +321,52 @@ mod verify_secp256k1 {
     }
 }
 
+mod verify_schnorr_bip340 {
+    use super::*;
+    use ic_crypto_internal_basic_sig_schnorr_bip340 as schnorr_bip340;
+    use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+    use ic_types::crypto::AlgorithmId::SchnorrSecp256k1;
+
+    #[test]
+    fn should_correctly_verify_signature() {
+        let rng = &mut reproducible_rng();
+        let (csp_pk, csp_sig) = signed_message(b"message", rng);
+        let csp = Csp::builder_for_test()
+            .with_vault(LocalCspVault::builder_for_test().with_mock_stores().build())
+            .build();
+
+        assert_eq!(
+            csp.verify(&csp_sig, b"message", SchnorrSecp256k1, csp_pk),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn should_fail_to_verify_under_wrong_message() {
+        let rng = &mut reproducible_rng();
+        let (csp_pk, csp_sig) = signed_message(b"message", rng);
+        let csp = Csp::builder_for_test()
+            .with_vault(LocalCspVault::builder_for_test().with_mock_stores().build())
+            .build();
+
+        let result = csp.verify(&csp_sig, b"wrong message", SchnorrSecp256k1, csp_pk);
+
+        assert!(result.unwrap_err().is_signature_verification_error());
+    }
+
+    fn signed_message<R: Rng + rand::CryptoRng>(
+        message: &[u8],
+        rng: &mut R,
+    ) -> (CspPublicKey, CspSignature) {
+        let (sk, pk) = schnorr_bip340::keypair_from_rng(rng);
+        let sig = schnorr_bip340::sign(message, &sk, rng).expect("failed to sign");
+        (
+            CspPublicKey::SchnorrSecp256k1Bip340(pk),
+            CspSignature::SchnorrSecp256k1Bip340(sig),
+        )
+    }
+}
+
 mod verify_ed25519 {
     use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
 
@@ -491,6 +537,7 @@ mod verify_ed25519 {
 
 fn secret_key_store_returning_none() -> impl SecretKeyStore {
     let mut sks = MockSecretKeyStore::new();
+    sks.expect_is_key_inactive().return_const(false);
     sks.expect_get().returning(|_| None);
     sks
 }
@@ -506,6 +553,7 @@ fn secret_key_store_panicking_on_usage() -> MockSecretKeyStore {
     let mut sks = MockSecretKeyStore::new();
     sks.expect_insert().never();
     sks.expect_get().never();
+    sks.expect_is_key_inactive().never();
     sks.expect_contains().never();
     sks.expect_remove().never();
     sks
@@ -717,6 +765,152 @@ mod multi {
             .is_ok());
     }
 
+    #[test]
+    fn combined_signatures_verify_in_batch() {
+        let [csp1, csp2, verifier] = csp_and_verifier_with_different_seeds();
+        let signatures = combined_signatures(&[csp1, csp2], &verifier, 10);
+
+        for batch_size in [0, 1, 2, 10] {
+            assert_eq!(
+                verifier.verify_multisig_batch(
+                    &as_batch(&signatures[..batch_size]),
+                    AlgorithmId::MultiBls12_381
+                ),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn batch_verification_fails_if_any_combined_signature_is_invalid() {
+        let [csp1, csp2, verifier] = csp_and_verifier_with_different_seeds();
+        let mut signatures = combined_signatures(&[csp1, csp2], &verifier, 10);
+
+        for batch_size in [1, 2, 10] {
+            let original_message = std::mem::replace(
+                &mut signatures[batch_size - 1].1,
+                b"Four calling birds".to_vec(),
+            );
+            let result = verifier.verify_multisig_batch(
+                &as_batch(&signatures[..batch_size]),
+                AlgorithmId::MultiBls12_381,
+            );
+            assert!(result.unwrap_err().is_signature_verification_error());
+            signatures[batch_size - 1].1 = original_message;
+        }
+    }
+
+    #[test]
+    fn batch_verification_fails_gracefully_for_unsuitable_algorithm_id() {
+        let [csp1, verifier] = csp_and_verifier_with_different_seeds();
+        let signatures = combined_signatures(&[csp1], &verifier, 2);
+
+        let result = verifier.verify_multisig_batch(&as_batch(&signatures), AlgorithmId::Ed25519);
+        assert!(result.unwrap_err().is_algorithm_not_supported());
+    }
+
+    #[test]
+    fn batch_verification_fails_gracefully_on_individual_signature() {
+        let [csp1, verifier] = csp_and_verifier_with_different_seeds();
+        let mut signatures = combined_signatures(std::slice::from_ref(&csp1), &verifier, 2);
+        let public_key = signatures[0].2[0].clone();
+        signatures[0].0 = csp1
+            .sign(
+                AlgorithmId::MultiBls12_381,
+                signatures[0].1.clone(),
+                KeyId::try_from(&public_key).unwrap(),
+            )
+            .expect("Signing failed");
+
+        let result =
+            verifier.verify_multisig_batch(&as_batch(&signatures), AlgorithmId::MultiBls12_381);
+        assert!(result.unwrap_err().is_algorithm_not_supported());
+    }
+
+    #[test]
+    fn batch_verification_of_100_combined_signatures_is_faster_than_sequential_verification() {
+        let [csp1, verifier] = csp_and_verifier_with_different_seeds();
+        let signatures = combined_signatures(&[csp1], &verifier, 100);
+
+        let start = std::time::Instant::now();
+        for (signature, message, signers) in &signatures {
+            assert!(verifier
+                .verify_multisig(
+                    signers.clone(),
+                    signature.clone(),
+                    message,
+                    AlgorithmId::MultiBls12_381
+                )
+                .is_ok());
+        }
+        let sequential = start.elapsed();
+
+        let start = std::time::Instant::now();
+        assert!(verifier
+            .verify_multisig_batch(&as_batch(&signatures), AlgorithmId::MultiBls12_381)
+            .is_ok());
+        let batched = start.elapsed();
+
+        assert!(
+            batched < sequential,
+            "batch verification took {batched:?}, sequential verification {sequential:?}"
+        );
+    }
+
+    /// Returns `num_signatures` signatures, each combined from the signatures
+    /// of all `signers` on its own message, along with the message and the
+    /// public keys of the signers.
+    fn combined_signatures(
+        signers: &[Csp],
+        combiner: &Csp,
+        num_signatures: usize,
+    ) -> Vec<(CspSignature, Vec<u8>, Vec<CspPublicKey>)> {
+        let public_keys: Vec<CspPublicKey> = signers
+            .iter()
+            .map(|signer| {
+                signer
+                    .csp_vault
+                    .gen_committee_signing_key_pair()
+                    .expect("Failed to generate key pair with PoP")
+                    .0
+            })
+            .collect();
+        (0..num_signatures)
+            .map(|i| {
+                let message = format!("{i} turtle doves").into_bytes();
+                let signatures = signers
+                    .iter()
+                    .zip(public_keys.iter())
+                    .map(|(signer, public_key)| {
+                        let signature = signer
+                            .sign(
+                                AlgorithmId::MultiBls12_381,
+                                message.clone(),
+                                KeyId::try_from(public_key).unwrap(),
+                            )
+                            .expect("Signing failed");
+                        (public_key.clone(), signature)
+                    })
+                    .collect();
+                let combined_signature = combiner
+                    .combine_sigs(signatures, AlgorithmId::MultiBls12_381)
+                    .expect("Failed to combine signatures");
+                (combined_signature, message, public_keys.clone())
+            })
+            .collect()
+    }
+
+    fn as_batch(
+        signatures: &[(CspSignature, Vec<u8>, Vec<CspPublicKey>)],
+    ) -> Vec<(CspSignature, &[u8], Vec<CspPublicKey>)> {
+        signatures
+            .iter()
+            .map(|(signature, message, signers)| {
+                (signature.clone(), message.as_slice(), signers.clone())
+            })
+            .collect()
+    }
+
     #[test]
     fn combining_signatures_fails_gracefully_for_unsuitable_algorithm_id() {
         // Actors:
@@ -290,6 +290,11 @@ fn dkg_dealing_encryption_key_id<T: PublicKeyStoreCspVault + ?Sized>(
                 CspPublicKeyStoreError::TransientInternalError(msg) => {
                     DkgDealingEncryptionKeyIdRetrievalError::TransientInternalError(msg)
                 }
+                CspPublicKeyStoreError::Timeout { method, elapsed } => {
+                    DkgDealingEncryptionKeyIdRetrievalError::TransientInternalError(format!(
+                        "RPC call {method} timed out after {elapsed:?}"
+                    ))
+                }
             })?
             .dkg_dealing_encryption_public_key
             .ok_or(DkgDealingEncryptionKeyIdRetrievalError::KeyNotFound)?,
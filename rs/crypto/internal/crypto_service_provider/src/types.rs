@@ -11,6 +11,7 @@ use ic_crypto_internal_basic_sig_ecdsa_secp256k1::types as ecdsa_secp256k1_types
 use ic_crypto_internal_basic_sig_ecdsa_secp256r1::types as ecdsa_secp256r1_types;
 use ic_crypto_internal_basic_sig_ed25519::types as ed25519_types;
 use ic_crypto_internal_basic_sig_rsa_pkcs1 as rsa;
+use ic_crypto_internal_basic_sig_schnorr_bip340::types as schnorr_bip340_types;
 use ic_crypto_internal_multi_sig_bls12381::types as multi_types;
 use ic_crypto_internal_threshold_sig_bls12381::ni_dkg::types::CspFsEncryptionKeySet;
 use ic_crypto_internal_threshold_sig_bls12381::types as threshold_types;
@@ -66,6 +67,8 @@ pub enum CspSecretKey {
     MEGaEncryptionK256(MEGaKeySetK256Bytes),
     #[cfg_attr(test, proptest(strategy(arbitrary_threshold_ecdsa_opening)))]
     IDkgCommitmentOpening(CommitmentOpeningBytes),
+    #[cfg_attr(test, proptest(strategy(arbitrary_schnorr_bip340_secret_key)))]
+    SchnorrSecp256k1Bip340(schnorr_bip340_types::SecretKeyBytes),
 }
 
 impl CspSecretKey {
@@ -102,6 +105,9 @@ impl std::fmt::Debug for CspSecretKey {
                     s.curve_type()
                 )
             }
+            CspSecretKey::SchnorrSecp256k1Bip340(_) => {
+                write!(f, "CspSecretKey::SchnorrSecp256k1Bip340 - REDACTED")
+            }
         }
     }
 }
@@ -120,6 +126,8 @@ pub enum CspPublicKey {
     MultiBls12_381(multi_types::PublicKeyBytes),
     #[cfg_attr(test, proptest(strategy(arbitrary_rsa_public_key)))]
     RsaSha256(rsa::RsaPublicKey),
+    #[cfg_attr(test, proptest(strategy(arbitrary_schnorr_bip340_public_key)))]
+    SchnorrSecp256k1Bip340(schnorr_bip340_types::PublicKeyBytes),
 }
 
 impl CspPublicKey {
@@ -155,6 +163,7 @@ impl CspPublicKey {
             CspPublicKey::Ed25519(_) => AlgorithmId::Ed25519,
             CspPublicKey::MultiBls12_381(_) => AlgorithmId::MultiBls12_381,
             CspPublicKey::RsaSha256(_) => AlgorithmId::RsaSha256,
+            CspPublicKey::SchnorrSecp256k1Bip340(_) => AlgorithmId::SchnorrSecp256k1,
         }
     }
 
@@ -166,6 +175,7 @@ impl CspPublicKey {
             CspPublicKey::Ed25519(pk_bytes) => &pk_bytes.0,
             CspPublicKey::MultiBls12_381(pk_bytes) => &pk_bytes.0,
             CspPublicKey::RsaSha256(pk_bytes) => pk_bytes.as_der(),
+            CspPublicKey::SchnorrSecp256k1Bip340(pk_bytes) => &pk_bytes.0,
         }
     }
 }
@@ -189,6 +199,8 @@ pub enum CspSignature {
     MultiBls12_381(MultiBls12_381_Signature),
     ThresBls12_381(ThresBls12_381_Signature),
     RsaSha256(Vec<u8>),
+    #[cfg_attr(test, proptest(strategy(arbitrary_schnorr_bip340_signature)))]
+    SchnorrSecp256k1Bip340(schnorr_bip340_types::SignatureBytes),
 }
 
 impl std::fmt::Debug for CspSignature {
@@ -202,6 +214,11 @@ impl std::fmt::Debug for CspSignature {
             MultiBls12_381(data) => write!(f, "CspSignature::MultiBls12_381({:?})", data),
             ThresBls12_381(data) => write!(f, "CspSignature::ThresBls12_381({:?})", data),
             RsaSha256(data) => write!(f, "CspSignature::RsaSha256({:?})", base64::encode(data)),
+            SchnorrSecp256k1Bip340(data) => write!(
+                f,
+                "CspSignature::SchnorrSecp256k1Bip340({:?})",
+                base64::encode(data.0)
+            ),
         }
     }
 }
@@ -255,6 +272,7 @@ impl CspSignature {
             CspSignature::MultiBls12_381(_) => AlgorithmId::MultiBls12_381,
             CspSignature::ThresBls12_381(_) => AlgorithmId::ThresBls12_381,
             CspSignature::RsaSha256(_) => AlgorithmId::RsaSha256,
+            CspSignature::SchnorrSecp256k1Bip340(_) => AlgorithmId::SchnorrSecp256k1,
         }
     }
 }
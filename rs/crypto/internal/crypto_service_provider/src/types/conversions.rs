@@ -4,8 +4,10 @@ use super::{
     CspPop, CspPublicKey, CspSecretKey, CspSignature, MultiBls12_381_Signature, SigConverter,
     ThresBls12_381_Signature,
 };
+use ic_crypto_internal_basic_sig_ecdsa_secp256k1 as ecdsa_secp256k1;
 use ic_crypto_internal_basic_sig_ecdsa_secp256k1::types as ecdsa_secp256k1_types;
 use ic_crypto_internal_basic_sig_ecdsa_secp256r1::types as ecdsa_secp256r1_types;
+use ic_crypto_internal_basic_sig_ed25519 as ed25519;
 use ic_crypto_internal_basic_sig_ed25519::types as ed25519_types;
 use ic_crypto_internal_basic_sig_rsa_pkcs1 as rsa;
 use ic_crypto_internal_multi_sig_bls12381::types as multi_types;
@@ -28,6 +30,7 @@ impl From<&CspPublicKey> for AlgorithmId {
             CspPublicKey::Ed25519(_) => AlgorithmId::Ed25519,
             CspPublicKey::MultiBls12_381(_) => AlgorithmId::MultiBls12_381,
             CspPublicKey::RsaSha256(_) => AlgorithmId::RsaSha256,
+            CspPublicKey::SchnorrSecp256k1Bip340(_) => AlgorithmId::SchnorrSecp256k1,
         }
     }
 }
@@ -168,6 +171,7 @@ impl AsRef<[u8]> for CspPublicKey {
             CspPublicKey::Ed25519(bytes) => &bytes.0,
             CspPublicKey::MultiBls12_381(public_key_bytes) => &public_key_bytes.0,
             CspPublicKey::RsaSha256(public_key_bytes) => public_key_bytes.as_der(),
+            CspPublicKey::SchnorrSecp256k1Bip340(bytes) => &bytes.0,
         }
     }
 }
@@ -205,6 +209,7 @@ impl AsRef<[u8]> for CspSignature {
                 ThresBls12_381_Signature::Combined(sig_bytes) => &sig_bytes.0,
             },
             CspSignature::RsaSha256(bytes) => bytes,
+            CspSignature::SchnorrSecp256k1Bip340(bytes) => &bytes.0,
         }
     }
 }
@@ -249,6 +254,53 @@ impl TryFrom<&UserPublicKey> for CspPublicKey {
     }
 }
 
+impl CspPublicKey {
+    /// DER-encodes the public key as `SubjectPublicKeyInfo`, according to
+    /// [RFC 8410](https://tools.ietf.org/html/rfc8410) for Ed25519 keys and to
+    /// [SEC 1](https://www.secg.org/sec1-v2.pdf) with an uncompressed point for
+    /// secp256k1 keys.
+    ///
+    /// # Errors
+    /// * `CryptoError::MalformedPublicKey` if the secp256k1 key is not a valid
+    ///   point on the curve.
+    /// * `CryptoError::AlgorithmNotSupported` for keys of any other algorithm.
+    pub fn to_der(&self) -> Result<Vec<u8>, CryptoError> {
+        match self {
+            CspPublicKey::Ed25519(pk) => Ok(ed25519::public_key_to_der(*pk)),
+            CspPublicKey::EcdsaSecp256k1(pk) => ecdsa_secp256k1::public_key_to_der(pk),
+            _ => Err(CryptoError::AlgorithmNotSupported {
+                algorithm: self.algorithm_id(),
+                reason: "Could not DER-encode CspPublicKey".to_string(),
+            }),
+        }
+    }
+
+    /// Decodes a DER-encoded Ed25519 or secp256k1 public key, as produced by
+    /// [`Self::to_der`], such that `from_der(&pk.to_der()?) == Ok(pk)`.
+    ///
+    /// secp256k1 keys must be encoded with an uncompressed point.
+    ///
+    /// # Errors
+    /// * `CryptoError::InvalidArgument` if `bytes` is not the DER encoding of an
+    ///   Ed25519 or secp256k1 public key.
+    pub fn from_der(bytes: &[u8]) -> Result<Self, CryptoError> {
+        let ed25519_error = match ed25519::public_key_from_der(bytes) {
+            Ok(pk) => return Ok(CspPublicKey::Ed25519(pk)),
+            Err(error) => error,
+        };
+        let secp256k1_error = match ecdsa_secp256k1::public_key_from_der(bytes) {
+            Ok(pk) => return Ok(CspPublicKey::EcdsaSecp256k1(pk)),
+            Err(error) => error,
+        };
+        Err(CryptoError::InvalidArgument {
+            message: format!(
+                "Neither a DER-encoded Ed25519 public key ({ed25519_error}) \
+                 nor a DER-encoded secp256k1 public key ({secp256k1_error})"
+            ),
+        })
+    }
+}
+
 impl TryFrom<CspSecretKey> for threshold_types::SecretKeyBytes {
     type Error = CspSecretKeyConversionError;
     fn try_from(value: CspSecretKey) -> Result<Self, Self::Error> {
@@ -222,3 +222,138 @@ fn csp_pop_from_public_key_proto_error_debug_print() {
         assert_eq!(format!("{:?}", value), *formatted);
     }
 }
+
+mod csp_public_key_der {
+    use super::*;
+    use assert_matches::assert_matches;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use x509_parser::prelude::FromDer;
+    use x509_parser::x509::SubjectPublicKeyInfo;
+
+    const ED25519_OID: &str = "1.3.101.112";
+    const EC_PUBLIC_KEY_OID: &str = "1.2.840.10045.2.1";
+    const SECP256K1_OID: &str = "1.3.132.0.10";
+
+    prop_compose! {
+        fn arb_ed25519_public_key()(seed in any::<[u8; 32]>()) -> CspPublicKey {
+            let (_sk, pk) = ed25519::keypair_from_rng(&mut ChaCha20Rng::from_seed(seed));
+            CspPublicKey::Ed25519(pk)
+        }
+    }
+
+    prop_compose! {
+        fn arb_secp256k1_public_key()(seed in any::<[u8; 32]>()) -> CspPublicKey {
+            let sk = ic_crypto_secp256k1::PrivateKey::generate_using_rng(
+                &mut ChaCha20Rng::from_seed(seed),
+            );
+            CspPublicKey::EcdsaSecp256k1(ecdsa_secp256k1_types::PublicKeyBytes(
+                sk.public_key().serialize_sec1(false),
+            ))
+        }
+    }
+
+    fn parse_spki(der: &[u8]) -> SubjectPublicKeyInfo<'_> {
+        let (remainder, spki) =
+            SubjectPublicKeyInfo::from_der(der).expect("failed to parse SubjectPublicKeyInfo");
+        assert!(
+            remainder.is_empty(),
+            "trailing bytes after SubjectPublicKeyInfo"
+        );
+        spki
+    }
+
+    proptest! {
+        #[test]
+        fn should_round_trip_supported_public_keys(
+            pk in prop_oneof![arb_ed25519_public_key(), arb_secp256k1_public_key()]
+        ) {
+            let der = pk.to_der().expect("failed to DER-encode public key");
+
+            prop_assert_eq!(CspPublicKey::from_der(&der), Ok(pk));
+        }
+
+        #[test]
+        fn should_encode_ed25519_public_key_as_rfc8410_spki(pk in arb_ed25519_public_key()) {
+            let der = pk.to_der().expect("failed to DER-encode public key");
+
+            let spki = parse_spki(&der);
+            prop_assert_eq!(spki.algorithm.algorithm.to_id_string(), ED25519_OID);
+            prop_assert!(spki.algorithm.parameters.is_none());
+            prop_assert_eq!(&spki.subject_public_key.data[..], pk.pk_bytes());
+        }
+
+        #[test]
+        fn should_encode_secp256k1_public_key_as_sec1_spki(pk in arb_secp256k1_public_key()) {
+            let der = pk.to_der().expect("failed to DER-encode public key");
+
+            let spki = parse_spki(&der);
+            prop_assert_eq!(spki.algorithm.algorithm.to_id_string(), EC_PUBLIC_KEY_OID);
+            let curve = spki
+                .algorithm
+                .parameters
+                .as_ref()
+                .and_then(|parameters| parameters.as_oid().ok())
+                .map(|oid| oid.to_id_string());
+            prop_assert_eq!(curve.as_deref(), Some(SECP256K1_OID));
+            prop_assert_eq!(&spki.subject_public_key.data[..], pk.pk_bytes());
+        }
+
+        #[test]
+        fn should_fail_to_encode_unsupported_public_keys(pk: CspPublicKey) {
+            prop_assume!(!matches!(
+                pk,
+                CspPublicKey::Ed25519(_) | CspPublicKey::EcdsaSecp256k1(_)
+            ));
+
+            prop_assert_eq!(
+                pk.to_der(),
+                Err(CryptoError::AlgorithmNotSupported {
+                    algorithm: pk.algorithm_id(),
+                    reason: "Could not DER-encode CspPublicKey".to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn should_decode_ed25519_public_key_from_external_encoder() {
+        let key_pair = rcgen::KeyPair::generate_for(&rcgen::PKCS_ED25519)
+            .expect("failed to generate Ed25519 key pair");
+
+        let pk = CspPublicKey::from_der(&key_pair.public_key_der())
+            .expect("failed to decode public key");
+
+        assert_eq!(pk.algorithm_id(), AlgorithmId::Ed25519);
+        assert_eq!(pk.pk_bytes(), key_pair.public_key_raw());
+    }
+
+    #[test]
+    fn should_fail_to_encode_invalid_secp256k1_point() {
+        let pk = CspPublicKey::EcdsaSecp256k1(ecdsa_secp256k1_types::PublicKeyBytes(vec![
+            4;
+            ecdsa_secp256k1_types::PublicKeyBytes::SIZE
+        ]));
+
+        assert_matches!(
+            pk.to_der(),
+            Err(CryptoError::MalformedPublicKey {
+                algorithm: AlgorithmId::EcdsaSecp256k1,
+                ..
+            })
+        );
+    }
+
+    #[test]
+    fn should_fail_to_decode_garbage() {
+        assert_matches!(
+            CspPublicKey::from_der(b"not a public key"),
+            Err(CryptoError::InvalidArgument { message })
+                if message.contains("Ed25519") && message.contains("secp256k1")
+        );
+        assert_matches!(
+            CspPublicKey::from_der(&[]),
+            Err(CryptoError::InvalidArgument { .. })
+        );
+    }
+}
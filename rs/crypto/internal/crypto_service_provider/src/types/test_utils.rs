@@ -3,6 +3,7 @@ use ic_crypto_internal_basic_sig_ecdsa_secp256k1::types as ecdsa_secp256k1_types
 use ic_crypto_internal_basic_sig_ecdsa_secp256r1::types as ecdsa_secp256r1_types;
 use ic_crypto_internal_basic_sig_ed25519::types as ed25519_types;
 use ic_crypto_internal_basic_sig_rsa_pkcs1::RsaPublicKey as IcRsaPublicKey;
+use ic_crypto_internal_basic_sig_schnorr_bip340::types as schnorr_bip340_types;
 use ic_crypto_internal_multi_sig_bls12381::types as multi_sig_types;
 use ic_crypto_internal_seed::Seed;
 use ic_crypto_internal_test_vectors::unhex::{
@@ -83,6 +84,32 @@ prop_compose! {
     }
 }
 
+prop_compose! {
+    pub fn arbitrary_schnorr_bip340_public_key()(
+        random_bytes in [any::<u8>(); schnorr_bip340_types::PublicKeyBytes::SIZE]
+    ) -> CspPublicKey {
+        CspPublicKey::SchnorrSecp256k1Bip340(schnorr_bip340_types::PublicKeyBytes(random_bytes))
+    }
+}
+
+prop_compose! {
+    pub fn arbitrary_schnorr_bip340_secret_key()(
+        random_bytes in [any::<u8>(); schnorr_bip340_types::SecretKeyBytes::SIZE]
+    ) -> CspSecretKey {
+        CspSecretKey::SchnorrSecp256k1Bip340(schnorr_bip340_types::SecretKeyBytes(
+            SecretArray::new_and_dont_zeroize_argument(&random_bytes),
+        ))
+    }
+}
+
+prop_compose! {
+    pub fn arbitrary_schnorr_bip340_signature()(
+        random_bytes in [any::<u8>(); schnorr_bip340_types::SignatureBytes::SIZE]
+    ) -> CspSignature {
+        CspSignature::SchnorrSecp256k1Bip340(schnorr_bip340_types::SignatureBytes(random_bytes))
+    }
+}
+
 prop_compose! {
 
     pub fn arbitrary_multi_bls12381_public_key()(
@@ -175,8 +175,16 @@ fn should_return_correct_enum_variant() {
     ));
     assert_eq!(key.enum_variant(), "IDkgCommitmentOpening");
 
+    // SchnorrSecp256k1Bip340
+    let key = CspSecretKey::SchnorrSecp256k1Bip340(schnorr_bip340_types::SecretKeyBytes(
+        SecretArray::new_and_dont_zeroize_argument(
+            &[0; schnorr_bip340_types::SecretKeyBytes::SIZE],
+        ),
+    ));
+    assert_eq!(key.enum_variant(), "SchnorrSecp256k1Bip340");
+
     // Please add here tests for newly added ’CspSecretKey’ enums and increment the counter to match their count.
-    assert_eq!(CspSecretKey::COUNT, 7);
+    assert_eq!(CspSecretKey::COUNT, 8);
 }
 
 #[test]
@@ -16,7 +16,7 @@ use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::{
 };
 use ic_crypto_node_key_validation::ValidNodePublicKeys;
 use ic_crypto_tls_interfaces::TlsPublicKeyCert;
-use ic_interfaces::crypto::CurrentNodePublicKeysError;
+use ic_interfaces::crypto::{CurrentNodePublicKeysError, IDkgDealingEncryptionKeyRotationError};
 use ic_protobuf::registry::crypto::v1::{AlgorithmId as AlgorithmIdProto, PublicKey};
 use ic_types::crypto::canister_threshold_sig::error::{
     IDkgLoadTranscriptError, IDkgOpenTranscriptError, IDkgRetainKeysError,
@@ -25,11 +25,13 @@ use ic_types::crypto::canister_threshold_sig::error::{
 use ic_types::crypto::canister_threshold_sig::idkg::{
     BatchSignedIDkgDealing, IDkgTranscriptOperation,
 };
+use ic_types::crypto::threshold_sig::ni_dkg::NiDkgTranscript;
 use ic_types::crypto::ExtendedDerivationPath;
 use ic_types::crypto::{AlgorithmId, CryptoError, CurrentNodePublicKeys};
-use ic_types::{NodeId, NodeIndex, NumberOfNodes, Randomness};
+use ic_types::{NodeId, NodeIndex, NumberOfNodes, Randomness, Time};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
 
 #[cfg(test)]
 mod tests;
@@ -53,13 +55,37 @@ pub enum CspBasicSignatureError {
     TransientInternalError {
         internal_error: String,
     },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
+}
+
+/// Algorithms of basic signature keys that can be generated with
+/// `BasicSignatureCspVault::gen_basic_signature_key_pair`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
+pub enum CspBasicSignatureKeygenAlgorithm {
+    /// BIP340 Schnorr signatures over secp256k1 with x-only public keys.
+    SchnorrSecp256k1Bip340,
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
 pub enum CspBasicSignatureKeygenError {
-    InternalError { internal_error: String },
-    DuplicateKeyId { key_id: KeyId },
-    TransientInternalError { internal_error: String },
+    InternalError {
+        internal_error: String,
+    },
+    DuplicateKeyId {
+        key_id: KeyId,
+    },
+    TransientInternalError {
+        internal_error: String,
+    },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
@@ -78,6 +104,17 @@ pub enum CspMultiSignatureError {
     TransientInternalError {
         internal_error: String,
     },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
+    /// Signing the message at `index` of a batch failed with `error`, see
+    /// `MultiSignatureCspVault::multi_sign_batch`.
+    BatchMessageError {
+        index: usize,
+        error: Box<CspMultiSignatureError>,
+    },
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
@@ -96,6 +133,11 @@ pub enum CspMultiSignatureKeygenError {
     TransientInternalError {
         internal_error: String,
     },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
@@ -105,14 +147,70 @@ pub enum CspThresholdSignatureKeygenError {
     InternalError { internal_error: String },
 }
 
+/// Error exporting keys from the CSP vault (see
+/// `LocalCspVault::export_key_pair_pkcs8` and
+/// `LocalCspVault::export_key_store`).
+#[cfg(feature = "key_import_export")]
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
-pub enum CspSecretKeyStoreContainsError {
+pub enum CspVaultExportError {
+    SecretKeyNotFound { key_id: KeyId },
+    UnsupportedKeyType { secret_key_variant: String },
+    InternalError { internal_error: String },
+}
+
+/// Error importing keys into the CSP vault (see
+/// `LocalCspVault::import_key_pair_pkcs8` and
+/// `LocalCspVault::import_key_store`).
+#[cfg(feature = "key_import_export")]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
+pub enum CspVaultImportError {
+    MalformedPkcs8 { internal_error: String },
+    MalformedBackup { internal_error: String },
+    DuplicateKeyId { key_id: KeyId },
+    InternalError { internal_error: String },
     TransientInternalError { internal_error: String },
 }
 
+/// Error marking a key in the CSP vault as active or inactive (see
+/// [`KeyActivationCspVault`]).
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
+pub enum CspVaultKeyActivationError {
+    SecretKeyNotFound {
+        key_id: KeyId,
+    },
+    InternalError {
+        internal_error: String,
+    },
+    TransientInternalError {
+        internal_error: String,
+    },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
+}
+
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
+pub enum CspSecretKeyStoreContainsError {
+    TransientInternalError {
+        internal_error: String,
+    },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
+}
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
 pub enum CspPublicKeyStoreError {
     TransientInternalError(String),
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
 }
 
 impl From<CspPublicKeyStoreError> for CryptoError {
@@ -123,6 +221,13 @@ impl From<CspPublicKeyStoreError> for CryptoError {
                     internal_error: format!("Error retrieving public keys: {:?}", details),
                 }
             }
+            CspPublicKeyStoreError::Timeout { method, elapsed } => {
+                CryptoError::TransientInternalError {
+                    internal_error: format!(
+                        "Error retrieving public keys: RPC call {method} timed out after {elapsed:?}"
+                    ),
+                }
+            }
         }
     }
 }
@@ -133,16 +238,49 @@ impl From<CspPublicKeyStoreError> for CurrentNodePublicKeysError {
             CspPublicKeyStoreError::TransientInternalError(details) => {
                 CurrentNodePublicKeysError::TransientInternalError(details)
             }
+            CspPublicKeyStoreError::Timeout { method, elapsed } => {
+                CurrentNodePublicKeysError::TransientInternalError(format!(
+                    "RPC call {method} timed out after {elapsed:?}"
+                ))
+            }
+        }
+    }
+}
+
+impl From<CspPublicKeyStoreError> for IDkgDealingEncryptionKeyRotationError {
+    fn from(e: CspPublicKeyStoreError) -> IDkgDealingEncryptionKeyRotationError {
+        match e {
+            CspPublicKeyStoreError::TransientInternalError(internal_error) => {
+                IDkgDealingEncryptionKeyRotationError::TransientInternalError(internal_error)
+            }
+            CspPublicKeyStoreError::Timeout { method, elapsed } => {
+                IDkgDealingEncryptionKeyRotationError::TransientInternalError(format!(
+                    "RPC call {method} timed out after {elapsed:?}"
+                ))
+            }
         }
     }
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
 pub enum CspTlsKeygenError {
-    InvalidArguments { message: String },
-    InternalError { internal_error: String },
-    DuplicateKeyId { key_id: KeyId },
-    TransientInternalError { internal_error: String },
+    InvalidArguments {
+        message: String,
+    },
+    InternalError {
+        internal_error: String,
+    },
+    DuplicateKeyId {
+        key_id: KeyId,
+    },
+    TransientInternalError {
+        internal_error: String,
+    },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
@@ -163,6 +301,11 @@ pub enum CspTlsSignError {
     TransientInternalError {
         internal_error: String,
     },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Default, Deserialize, Serialize)]
@@ -346,6 +489,8 @@ pub enum PksAndSksContainsErrors {
     NodeKeysErrors(NodeKeysErrors),
     /// If a transient internal error occurs, e.g., an RPC error communicating with the remote vault
     TransientInternalError(String),
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout { method: String, elapsed: Duration },
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
@@ -364,6 +509,8 @@ pub enum ValidatePksAndSksError {
     IdkgDealingEncryptionKeyError(ValidatePksAndSksKeyPairError),
     /// If a transient internal error occurs, e.g., an RPC error communicating with the remote vault
     TransientInternalError(String),
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout { method: String, elapsed: Duration },
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
@@ -391,6 +538,8 @@ pub trait CspVault:
     + PublicRandomSeedGenerator
     + PublicAndSecretKeyStoreCspVault
     + PublicKeyStoreCspVault
+    + HealthStatusCspVault
+    + KeyActivationCspVault
 {
 }
 
@@ -409,6 +558,8 @@ impl<T> CspVault for T where
         + PublicRandomSeedGenerator
         + PublicAndSecretKeyStoreCspVault
         + PublicKeyStoreCspVault
+        + HealthStatusCspVault
+        + KeyActivationCspVault
 {
 }
 
@@ -454,6 +605,32 @@ pub trait BasicSignatureCspVault {
     ///   transient internal error, e.g., an IO error when writing a key to
     ///   disk, or an RPC error when calling a remote CSP vault.
     fn gen_node_signing_key_pair(&self) -> Result<CspPublicKey, CspBasicSignatureKeygenError>;
+
+    /// Generates a basic signature public/private key pair for the given
+    /// algorithm.
+    ///
+    /// Unlike `gen_node_signing_key_pair`, the secret key is only stored in
+    /// the secret key store, and the public key store is left untouched.
+    /// The secret key can then be used with `sign` under the key ID derived
+    /// from the returned public key.
+    ///
+    /// # Returns
+    /// Generated public key.
+    ///
+    /// # Errors
+    /// * `CspBasicSignatureKeygenError::DuplicateKeyId` if there already
+    ///   exists a secret key in the store for the secret key ID derived from
+    ///   the public part of the randomly generated key pair. This error
+    ///   most likely indicates a bad randomness source.
+    /// * `CspBasicSignatureKeygenError::InternalError` if there is an internal
+    ///   error, e.g., the key ID cannot be derived from the public key.
+    /// * `CspBasicSignatureKeygenError::TransientInternalError` if there is a
+    ///   transient internal error, e.g., an IO error when writing a key to
+    ///   disk, or an RPC error when calling a remote CSP vault.
+    fn gen_basic_signature_key_pair(
+        &self,
+        algorithm: CspBasicSignatureKeygenAlgorithm,
+    ) -> Result<CspPublicKey, CspBasicSignatureKeygenError>;
 }
 
 /// Operations of `CspVault` related to multi-signatures
@@ -479,6 +656,51 @@ pub trait MultiSignatureCspVault {
         key_id: KeyId,
     ) -> Result<CspSignature, CspMultiSignatureError>;
 
+    /// Signs each of the given messages using the specified algorithm and key
+    /// ID.
+    ///
+    /// The secret key is looked up only once for the whole batch, which makes
+    /// this more efficient than calling `multi_sign` for every message.
+    ///
+    /// # Arguments
+    /// * `algorithm_id` specifies the signature algorithm
+    /// * `messages` are the messages to be signed
+    /// * `key_id` determines the private key to sign with
+    /// # Returns
+    /// The result of signing each message, in the order of `messages`.
+    fn batch_sign(
+        &self,
+        algorithm_id: AlgorithmId,
+        messages: &[&[u8]],
+        key_id: KeyId,
+    ) -> Vec<Result<CspSignature, CspMultiSignatureError>>;
+
+    /// Signs all of the given messages using the specified algorithm and key
+    /// ID, failing if any of them cannot be signed.
+    ///
+    /// Like `batch_sign`, the secret key is looked up only once, and a remote
+    /// vault signs the whole batch in a single RPC.
+    ///
+    /// # Arguments
+    /// * `algorithm_id` specifies the signature algorithm
+    /// * `messages` are the messages to be signed
+    /// * `key_id` determines the private key to sign with
+    /// # Returns
+    /// The signatures, in the order of `messages`. An empty batch results in
+    /// no signatures.
+    /// # Errors
+    /// * `CspMultiSignatureError::BatchMessageError` with the index of the
+    ///   first message that could not be signed, and the error that
+    ///   `multi_sign` would have returned for it.
+    /// * `CspMultiSignatureError::TransientInternalError` if there is an RPC
+    ///   error when calling a remote CSP vault.
+    fn multi_sign_batch(
+        &self,
+        algorithm_id: AlgorithmId,
+        messages: Vec<Vec<u8>>,
+        key_id: KeyId,
+    ) -> Result<Vec<CspSignature>, CspMultiSignatureError>;
+
     /// Generates a public/private key pair, with a proof of possession.
     ///
     /// # Returns
@@ -521,6 +743,32 @@ pub trait ThresholdSignatureCspVault {
         message: Vec<u8>,
         key_id: KeyId,
     ) -> Result<CspSignature, CspThresholdSignError>;
+
+    /// Combines individual threshold signature shares into a combined
+    /// threshold signature.
+    ///
+    /// No secret key material is used: the shares and the transcript are
+    /// public. The shares are not verified, but if enough valid shares are
+    /// given, the result is a valid combined signature.
+    ///
+    /// # Arguments
+    /// * `shares` are the individual signature shares, indexed by the node
+    ///   that computed them
+    /// * `transcript` is the NI-DKG transcript the shares were computed
+    ///   with; its committee determines the index of each share and its
+    ///   threshold the number of shares required
+    /// # Returns
+    /// The combined threshold signature.
+    /// # Errors
+    /// * `CspThresholdSignError::InvalidArgument` if a share was computed by
+    ///   a node that is not in the committee of the transcript, a share is
+    ///   not an individual threshold signature, or there are fewer shares
+    ///   than the threshold.
+    fn combine_threshold_sig_shares(
+        &self,
+        shares: &BTreeMap<NodeId, CspSignature>,
+        transcript: &NiDkgTranscript,
+    ) -> Result<CspSignature, CspThresholdSignError>;
 }
 
 /// Operations of `CspVault` related to NI-DKG (cf. `NiDkgCspClient`).
@@ -583,6 +831,33 @@ pub trait NiDkgCspVault {
         maybe_resharing_secret: Option<KeyId>,
     ) -> Result<CspNiDkgDealing, ni_dkg_errors::CspDkgCreateReshareDealingError>;
 
+    /// Verifies dealings that were all created for the same receivers,
+    /// threshold and epoch, e.g. the dealings of all dealers of a DKG.
+    ///
+    /// The receiver keys are parsed only once for the whole batch, which makes
+    /// this more efficient than verifying every dealing on its own.
+    ///
+    /// # Arguments
+    /// * `algorithm_id` selects the algorithm suite to use for the scheme.
+    /// * `threshold` is the minimum number of nodes required to generate a
+    ///   valid threshold signature.
+    /// * `epoch` is a monotonic increasing counter used to select forward
+    ///   secure keys.
+    /// * `receiver_keys` is a map storing a forward-secure public key for each
+    ///   receiver, indexed by their corresponding NodeIndex.
+    /// * `dealings` are the dealings to verify, each with the index of its
+    ///   dealer.
+    /// # Returns
+    /// The result of verifying each dealing, in the order of `dealings`.
+    fn verify_dealings_batch(
+        &self,
+        algorithm_id: AlgorithmId,
+        threshold: NumberOfNodes,
+        epoch: Epoch,
+        receiver_keys: BTreeMap<NodeIndex, CspFsEncryptionPublicKey>,
+        dealings: &[(NodeIndex, CspNiDkgDealing)],
+    ) -> Vec<Result<(), ni_dkg_errors::CspDkgVerifyDealingError>>;
+
     /// Computes a threshold signing key and stores it in the secret key store.
     ///
     /// After calling this method the threshold signature API can be used
@@ -728,6 +1003,24 @@ pub trait TlsHandshakeCspVault: Send + Sync {
     /// * if a malformed X509 certificate is generated
     fn gen_tls_key_pair(&self, node: NodeId) -> Result<TlsPublicKeyCert, CspTlsKeygenError>;
 
+    /// Generates TLS key material for node with ID `node_id` like
+    /// [`Self::gen_tls_key_pair`], except that the certificate's `notAfter`
+    /// value is set to `not_after` instead of `99991231235959Z`.
+    ///
+    /// Note that `not_after` may lie in the past, in which case the generated
+    /// certificate is already expired. Such a certificate is not a valid node
+    /// TLS certificate and is mainly meant for testing.
+    ///
+    /// # Errors
+    /// * if a malformed X509 certificate is generated
+    /// * if `not_after` is not after the certificate's `notBefore` value, i.e.,
+    ///   more than two minutes before the time of calling this method
+    fn gen_tls_key_pair_with_validity(
+        &self,
+        node: NodeId,
+        not_after: Time,
+    ) -> Result<TlsPublicKeyCert, CspTlsKeygenError>;
+
     /// Signs the given message using the specified algorithm and key ID.
     ///
     /// # Arguments
@@ -824,6 +1117,11 @@ pub enum IDkgCreateDealingVaultError {
     },
     UnsupportedAlgorithm(Option<AlgorithmIdProto>),
     TransientInternalError(String),
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
     SerializationError(String),
     InternalError(String),
     SecretSharesNotFound {
@@ -851,7 +1149,7 @@ pub trait ThresholdEcdsaSignerCspVault {
 }
 
 /// Type-safe serialization of [`IDkgTranscriptInternal`].
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct IDkgTranscriptInternalBytes(#[serde(with = "serde_bytes")] Vec<u8>);
 
 impl From<Vec<u8>> for IDkgTranscriptInternalBytes {
@@ -869,7 +1167,7 @@ impl AsRef<[u8]> for IDkgTranscriptInternalBytes {
 }
 
 /// Type-safe serialization of [`IDkgDealingInternalBytes`].
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct IDkgDealingInternalBytes(#[serde(with = "serde_bytes")] Vec<u8>);
 
 impl IDkgDealingInternalBytes {
@@ -952,6 +1250,8 @@ pub enum ThresholdSchnorrCreateSigShareVaultError {
     InternalError(String),
     /// If a transient internal error occurs, e.g., an RPC error communicating with the remote vault
     TransientInternalError(String),
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout { method: String, elapsed: Duration },
 }
 
 /// An error returned by failing to generate a public seed from [`CspVault`].
@@ -959,6 +1259,8 @@ pub enum ThresholdSchnorrCreateSigShareVaultError {
 pub enum PublicRandomSeedGeneratorError {
     /// Internal error, e.g., an RPC error.
     TransientInternalError { internal_error: String },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout { method: String, elapsed: Duration },
 }
 
 impl From<PublicRandomSeedGeneratorError> for CryptoError {
@@ -967,10 +1269,81 @@ impl From<PublicRandomSeedGeneratorError> for CryptoError {
             PublicRandomSeedGeneratorError::TransientInternalError { internal_error } => {
                 CryptoError::TransientInternalError { internal_error }
             }
+            PublicRandomSeedGeneratorError::Timeout { method, elapsed } => {
+                CryptoError::TransientInternalError {
+                    internal_error: format!("RPC call {method} timed out after {elapsed:?}"),
+                }
+            }
         }
     }
 }
 
+/// Health status of a [`CspVault`], see [`HealthStatusCspVault::health_status`].
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct CspVaultHealthStatus {
+    /// Number of keys in the node's and the canister secret key store.
+    pub key_count: usize,
+    /// Time of the last successful operation involving secret keys or the
+    /// vault's randomness, if any.
+    pub last_successful_operation: Option<Time>,
+    /// Whether any of the key stores was locked by a writer when the status
+    /// was determined.
+    pub is_locked: bool,
+}
+
+/// An error returned by failing to determine the health status of [`CspVault`].
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub enum CspVaultHealthStatusError {
+    /// Internal error, e.g., an RPC error.
+    TransientInternalError { internal_error: String },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout { method: String, elapsed: Duration },
+}
+
+/// Operations of [`CspVault`] for monitoring the vault.
+pub trait HealthStatusCspVault {
+    /// Returns the [`CspVaultHealthStatus`] of the vault.
+    ///
+    /// Meant to be polled by operators to check whether the vault is alive and
+    /// its key stores are accessible. Note that determining the key count
+    /// waits for the secret key stores to be readable.
+    fn health_status(&self) -> Result<CspVaultHealthStatus, CspVaultHealthStatusError>;
+}
+
+/// Operations of [`CspVault`] for soft deletion of keys in the node secret key
+/// store.
+///
+/// An inactive key is kept in the store, but cannot be used to sign: `sign`,
+/// `batch_sign`, `multi_sign`, `threshold_sign` and `tls_sign` fail with a
+/// `SecretKeyNotFound` error for it.
+pub trait KeyActivationCspVault {
+    /// Marks the key with the given ID in the node secret key store as
+    /// inactive. The key can be reactivated with
+    /// [`KeyActivationCspVault::mark_key_active`].
+    ///
+    /// Marking an inactive key as inactive has no effect.
+    ///
+    /// # Errors
+    /// * `CspVaultKeyActivationError::SecretKeyNotFound` if the node secret key
+    ///   store does not contain a key with the given ID.
+    /// * `CspVaultKeyActivationError::InternalError` if the secret key store
+    ///   cannot be serialized.
+    /// * `CspVaultKeyActivationError::TransientInternalError` if there is a
+    ///   transient error persisting the secret key store, e.g., an IO error,
+    ///   or an RPC error.
+    fn mark_key_inactive(&self, key_id: KeyId) -> Result<(), CspVaultKeyActivationError>;
+
+    /// Marks the key with the given ID in the node secret key store as active
+    /// again, after it was marked inactive with
+    /// [`KeyActivationCspVault::mark_key_inactive`].
+    ///
+    /// Marking an active key as active has no effect.
+    ///
+    /// # Errors
+    /// See [`KeyActivationCspVault::mark_key_inactive`].
+    fn mark_key_active(&self, key_id: KeyId) -> Result<(), CspVaultKeyActivationError>;
+}
+
 /// Operations of [`CspVault`] for generating public random seed.
 pub trait PublicRandomSeedGenerator {
     /// Returns a public random [`Seed`].
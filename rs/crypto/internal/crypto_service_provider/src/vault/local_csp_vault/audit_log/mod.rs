@@ -0,0 +1,104 @@
+//! Audit log of security-relevant operations performed by the CSP vault.
+use crate::key_id::KeyId;
+use crate::public_key_store::PublicKeyStore;
+use crate::secret_key_store::SecretKeyStore;
+use crate::vault::local_csp_vault::LocalCspVault;
+use ic_crypto_internal_types::scope::Scope;
+use ic_types::crypto::AlgorithmId;
+use ic_types::Time;
+use rand::{CryptoRng, Rng};
+use std::collections::VecDeque;
+
+#[cfg(test)]
+mod tests;
+
+/// Maximum number of entries kept in the audit log of a vault, unless
+/// configured otherwise.
+pub const DEFAULT_AUDIT_LOG_MAX_ENTRIES: usize = 10_000;
+
+/// A security-relevant operation performed by the vault.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum VaultAuditEvent {
+    /// A key pair was generated and its secret key stored in the vault.
+    KeyGeneration {
+        algorithm: AlgorithmId,
+        key_id: KeyId,
+    },
+    /// A message was signed with a secret key stored in the vault.
+    Signing {
+        algorithm: AlgorithmId,
+        key_id: KeyId,
+    },
+    /// Secret keys in the given scope that are no longer active were deleted.
+    KeyDeletion { scope: Scope },
+}
+
+/// An event in the audit log together with the time at which it occurred.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct VaultAuditEntry {
+    pub timestamp: Time,
+    pub event: VaultAuditEvent,
+}
+
+/// An append-only log of audit entries.
+///
+/// The log holds at most `max_entries` entries. When it is full, appending an
+/// entry evicts the oldest one.
+#[derive(Clone, Debug)]
+pub struct VaultAuditLog {
+    entries: VecDeque<VaultAuditEntry>,
+    max_entries: usize,
+}
+
+impl VaultAuditLog {
+    pub fn new(max_entries: usize) -> Self {
+        VaultAuditLog {
+            entries: VecDeque::new(),
+            max_entries,
+        }
+    }
+
+    /// Appends an entry, evicting the oldest entry if the log is full.
+    pub fn append(&mut self, entry: VaultAuditEntry) {
+        if self.max_entries == 0 {
+            return;
+        }
+        if self.entries.len() == self.max_entries {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Returns the entries in the order in which they were appended.
+    pub fn entries(&self) -> impl Iterator<Item = &VaultAuditEntry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore>
+    LocalCspVault<R, S, C, P>
+{
+    /// Returns a snapshot of the audit log, oldest entry first.
+    pub fn audit_log(&self) -> Vec<VaultAuditEntry> {
+        self.audit_log.lock().entries().cloned().collect()
+    }
+
+    /// Appends the given event to the audit log, timestamped with the
+    /// current time of the vault's time source.
+    pub(crate) fn record_audit_event(&self, event: VaultAuditEvent) {
+        self.update_key_usage_stats(&event);
+        let entry = VaultAuditEntry {
+            timestamp: self.time_source.get_relative_time(),
+            event,
+        };
+        self.audit_log.lock().append(entry);
+    }
+}
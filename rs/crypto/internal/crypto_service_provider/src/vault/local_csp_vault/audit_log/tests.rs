@@ -0,0 +1,107 @@
+use super::*;
+use crate::vault::api::BasicSignatureCspVault;
+use crate::LocalCspVault;
+use ic_interfaces::time_source::TimeSource;
+use ic_test_utilities_time::FastForwardTimeSource;
+use std::time::Duration;
+
+#[test]
+fn should_have_empty_audit_log_for_new_vault() {
+    let vault = LocalCspVault::builder_for_test().build();
+
+    assert_eq!(vault.audit_log(), vec![]);
+}
+
+#[test]
+fn should_record_key_generation_and_signing_with_timestamps() {
+    let time_source = FastForwardTimeSource::new();
+    time_source.advance_time(Duration::from_secs(1_000));
+    let vault = LocalCspVault::builder_for_test()
+        .with_time_source(time_source.clone())
+        .build();
+    let key_generation_time = time_source.get_relative_time();
+    let public_key = vault
+        .gen_node_signing_key_pair()
+        .expect("failed to generate node signing key pair");
+    let key_id = KeyId::try_from(&public_key).expect("failed to compute key ID");
+    time_source.advance_time(Duration::from_secs(1));
+    let signing_time = time_source.get_relative_time();
+
+    vault
+        .sign(AlgorithmId::Ed25519, b"message".to_vec(), key_id)
+        .expect("failed to sign");
+
+    assert_eq!(
+        vault.audit_log(),
+        vec![
+            VaultAuditEntry {
+                timestamp: key_generation_time,
+                event: VaultAuditEvent::KeyGeneration {
+                    algorithm: AlgorithmId::Ed25519,
+                    key_id
+                },
+            },
+            VaultAuditEntry {
+                timestamp: signing_time,
+                event: VaultAuditEvent::Signing {
+                    algorithm: AlgorithmId::Ed25519,
+                    key_id
+                },
+            },
+        ]
+    );
+}
+
+#[test]
+fn should_not_record_failed_signing() {
+    let vault = LocalCspVault::builder_for_test().build();
+
+    assert!(vault
+        .sign(
+            AlgorithmId::Ed25519,
+            b"message".to_vec(),
+            KeyId::from([42; 32])
+        )
+        .is_err());
+
+    assert_eq!(vault.audit_log(), vec![]);
+}
+
+#[test]
+fn should_evict_oldest_entries_when_audit_log_is_full() {
+    let vault = LocalCspVault::builder_for_test()
+        .with_audit_log_max_entries(2)
+        .build();
+    let public_key = vault
+        .gen_node_signing_key_pair()
+        .expect("failed to generate node signing key pair");
+    let key_id = KeyId::try_from(&public_key).expect("failed to compute key ID");
+
+    for _ in 0..3 {
+        vault
+            .sign(AlgorithmId::Ed25519, b"message".to_vec(), key_id)
+            .expect("failed to sign");
+    }
+
+    let audit_log = vault.audit_log();
+    assert_eq!(audit_log.len(), 2);
+    assert!(audit_log.iter().all(|entry| entry.event
+        == VaultAuditEvent::Signing {
+            algorithm: AlgorithmId::Ed25519,
+            key_id
+        }));
+}
+
+#[test]
+fn should_not_record_anything_if_max_entries_is_zero() {
+    let mut audit_log = VaultAuditLog::new(0);
+
+    audit_log.append(VaultAuditEntry {
+        timestamp: Time::from_nanos_since_unix_epoch(0),
+        event: VaultAuditEvent::KeyDeletion {
+            scope: crate::canister_threshold::IDKG_THRESHOLD_KEYS_SCOPE,
+        },
+    });
+
+    assert!(audit_log.is_empty());
+}
@@ -5,15 +5,19 @@ use crate::public_key_store::{PublicKeySetOnceError, PublicKeyStore};
 use crate::secret_key_store::{SecretKeyStore, SecretKeyStoreInsertionError};
 use crate::types::{CspPublicKey, CspSecretKey, CspSignature};
 use crate::vault::api::{
-    BasicSignatureCspVault, CspBasicSignatureError, CspBasicSignatureKeygenError,
+    BasicSignatureCspVault, CspBasicSignatureError, CspBasicSignatureKeygenAlgorithm,
+    CspBasicSignatureKeygenError,
 };
+use crate::vault::local_csp_vault::audit_log::VaultAuditEvent;
 use crate::vault::local_csp_vault::LocalCspVault;
 use ic_crypto_internal_basic_sig_ed25519 as ed25519;
+use ic_crypto_internal_basic_sig_schnorr_bip340 as schnorr_bip340;
 use ic_crypto_internal_logmon::metrics::{MetricsDomain, MetricsResult, MetricsScope};
 use ic_crypto_node_key_validation::ValidNodeSigningPublicKey;
 use ic_protobuf::registry::crypto::v1::PublicKey;
 use ic_types::crypto::AlgorithmId;
 use rand::{CryptoRng, Rng};
+use std::time::Instant;
 
 #[cfg(test)]
 mod tests;
@@ -28,21 +32,29 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         key_id: KeyId,
     ) -> Result<CspSignature, CspBasicSignatureError> {
         let start_time = self.metrics.now();
+        let signing_start_time = Instant::now();
         let result = self.sign_internal(algorithm_id, &message[..], key_id);
-        self.metrics.observe_duration_seconds(
+        if result.is_ok() {
+            self.record_audit_event(VaultAuditEvent::Signing {
+                algorithm: algorithm_id,
+                key_id,
+            });
+        }
+        self.observe_operation(
             MetricsDomain::BasicSignature,
             MetricsScope::Local,
             "sign",
             MetricsResult::from(&result),
             start_time,
         );
+        self.observe_signing(algorithm_id, "sign", signing_start_time);
         result
     }
 
     fn gen_node_signing_key_pair(&self) -> Result<CspPublicKey, CspBasicSignatureKeygenError> {
         let start_time = self.metrics.now();
         let result = self.gen_node_signing_key_pair_internal();
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::BasicSignature,
             MetricsScope::Local,
             "gen_node_signing_key_pair",
@@ -51,6 +63,22 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         );
         result
     }
+
+    fn gen_basic_signature_key_pair(
+        &self,
+        algorithm: CspBasicSignatureKeygenAlgorithm,
+    ) -> Result<CspPublicKey, CspBasicSignatureKeygenError> {
+        let start_time = self.metrics.now();
+        let result = self.gen_basic_signature_key_pair_internal(algorithm);
+        self.observe_operation(
+            MetricsDomain::BasicSignature,
+            MetricsScope::Local,
+            "gen_basic_signature_key_pair",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        result
+    }
 }
 
 impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore>
@@ -62,13 +90,50 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         let (sk_bytes, pk_bytes) = ed25519::keypair_from_rng(&mut *self.rng_write_lock());
         let secret_key = CspSecretKey::Ed25519(sk_bytes);
         let public_key = CspPublicKey::Ed25519(pk_bytes);
-        let key_id = KeyId::try_from(&public_key)?;
+        let key_id = KeyId::from_public_key(&public_key);
         let public_key_proto = node_signing_pk_to_proto(public_key.clone());
         let valid_public_key = validate_node_signing_public_key(public_key_proto)?;
         self.store_node_signing_key_pair(key_id, secret_key, valid_public_key.get().clone())?;
+        self.record_audit_event(VaultAuditEvent::KeyGeneration {
+            algorithm: AlgorithmId::Ed25519,
+            key_id,
+        });
+        Ok(public_key)
+    }
+
+    fn gen_basic_signature_key_pair_internal(
+        &self,
+        algorithm: CspBasicSignatureKeygenAlgorithm,
+    ) -> Result<CspPublicKey, CspBasicSignatureKeygenError> {
+        let (secret_key, public_key) = match algorithm {
+            CspBasicSignatureKeygenAlgorithm::SchnorrSecp256k1Bip340 => {
+                let (sk_bytes, pk_bytes) =
+                    schnorr_bip340::keypair_from_rng(&mut *self.rng_write_lock());
+                (
+                    CspSecretKey::SchnorrSecp256k1Bip340(sk_bytes),
+                    CspPublicKey::SchnorrSecp256k1Bip340(pk_bytes),
+                )
+            }
+        };
+        let key_id = KeyId::from_public_key(&public_key);
+        self.store_basic_signature_secret_key(key_id, secret_key)?;
+        self.record_audit_event(VaultAuditEvent::KeyGeneration {
+            algorithm: public_key.algorithm_id(),
+            key_id,
+        });
         Ok(public_key)
     }
 
+    fn store_basic_signature_secret_key(
+        &self,
+        key_id: KeyId,
+        secret_key: CspSecretKey,
+    ) -> Result<(), CspBasicSignatureKeygenError> {
+        self.sks_write_lock()
+            .insert(key_id, secret_key, None)
+            .map_err(basic_signature_keygen_error_from_sks_error)
+    }
+
     fn store_node_signing_key_pair(
         &self,
         key_id: KeyId,
@@ -78,25 +143,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         let (mut sks_write_lock, mut pks_write_lock) = self.sks_and_pks_write_locks();
         sks_write_lock
             .insert(key_id, secret_key, None)
-            .map_err(|sks_error| match sks_error {
-                SecretKeyStoreInsertionError::DuplicateKeyId(key_id) => {
-                    CspBasicSignatureKeygenError::DuplicateKeyId { key_id }
-                }
-                SecretKeyStoreInsertionError::TransientError(e) => {
-                    CspBasicSignatureKeygenError::TransientInternalError {
-                        internal_error: format!(
-                            "Error persisting secret key store during CSP basic signature key generation: {}",
-                            e
-                        ),
-                    }
-                }
-                SecretKeyStoreInsertionError::SerializationError(e) => CspBasicSignatureKeygenError::InternalError {
-                    internal_error: format!(
-                        "Error persisting secret key store during CSP basic signature key generation: {}",
-                        e
-                    ),
-                },
-            })
+            .map_err(basic_signature_keygen_error_from_sks_error)
             .and_then(|()| {
                 pks_write_lock
                     .set_once_node_signing_pubkey(public_key_proto)
@@ -128,7 +175,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         message: &[u8],
         key_id: KeyId,
     ) -> Result<CspSignature, CspBasicSignatureError> {
-        let maybe_secret_key = self.sks_read_lock().get(&key_id);
+        let maybe_secret_key = self.active_node_secret_key(&key_id);
         let secret_key: CspSecretKey =
             maybe_secret_key.ok_or(CspBasicSignatureError::SecretKeyNotFound {
                 algorithm: algorithm_id,
@@ -150,6 +197,20 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
                     secret_key_variant: secret_key.enum_variant().to_string(),
                 }),
             },
+            AlgorithmId::SchnorrSecp256k1 => match &secret_key {
+                CspSecretKey::SchnorrSecp256k1Bip340(secret_key) => {
+                    let sig_bytes =
+                        schnorr_bip340::sign(message, secret_key, &mut *self.rng_write_lock())
+                            .map_err(|_e| CspBasicSignatureError::MalformedSecretKey {
+                                algorithm: AlgorithmId::SchnorrSecp256k1,
+                            })?;
+                    Ok(CspSignature::SchnorrSecp256k1Bip340(sig_bytes))
+                }
+                _ => Err(CspBasicSignatureError::WrongSecretKeyType {
+                    algorithm: algorithm_id,
+                    secret_key_variant: secret_key.enum_variant().to_string(),
+                }),
+            },
             _ => Err(CspBasicSignatureError::UnsupportedAlgorithm {
                 algorithm: algorithm_id,
             }),
@@ -157,6 +218,32 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
     }
 }
 
+fn basic_signature_keygen_error_from_sks_error(
+    sks_error: SecretKeyStoreInsertionError,
+) -> CspBasicSignatureKeygenError {
+    match sks_error {
+        SecretKeyStoreInsertionError::DuplicateKeyId(key_id) => {
+            CspBasicSignatureKeygenError::DuplicateKeyId { key_id }
+        }
+        SecretKeyStoreInsertionError::TransientError(e) => {
+            CspBasicSignatureKeygenError::TransientInternalError {
+                internal_error: format!(
+                    "Error persisting secret key store during CSP basic signature key generation: {}",
+                    e
+                ),
+            }
+        }
+        SecretKeyStoreInsertionError::SerializationError(e) => {
+            CspBasicSignatureKeygenError::InternalError {
+                internal_error: format!(
+                    "Error persisting secret key store during CSP basic signature key generation: {}",
+                    e
+                ),
+            }
+        }
+    }
+}
+
 fn validate_node_signing_public_key(
     public_key_proto: PublicKey,
 ) -> Result<ValidNodeSigningPublicKey, CspBasicSignatureKeygenError> {
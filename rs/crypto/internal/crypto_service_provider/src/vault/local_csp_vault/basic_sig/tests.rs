@@ -8,7 +8,9 @@ use crate::secret_key_store::{SecretKeyStore, SecretKeyStoreInsertionError};
 use crate::types::{CspPublicKey, CspSignature};
 use crate::vault::api::PublicKeyStoreCspVault;
 use crate::vault::api::SecretKeyStoreCspVault;
-use crate::vault::api::{BasicSignatureCspVault, CspBasicSignatureKeygenError};
+use crate::vault::api::{
+    BasicSignatureCspVault, CspBasicSignatureKeygenAlgorithm, CspBasicSignatureKeygenError,
+};
 use crate::vault::api::{CspBasicSignatureError, CspVault};
 use crate::vault::local_csp_vault::basic_sig::node_signing_pk_to_proto;
 use crate::vault::local_csp_vault::LocalCspVault;
@@ -281,6 +283,155 @@ fn should_fail_to_sign_if_secret_key_in_store_has_wrong_type() {
     );
 }
 
+#[test]
+fn should_generate_schnorr_bip340_key_pair_and_store_only_secret_key() {
+    let csp_vault = LocalCspVault::builder_for_test().build_into_arc();
+
+    let public_key = csp_vault
+        .gen_basic_signature_key_pair(CspBasicSignatureKeygenAlgorithm::SchnorrSecp256k1Bip340)
+        .expect("failed creating key pair");
+
+    assert_matches!(public_key, CspPublicKey::SchnorrSecp256k1Bip340(_));
+    assert_eq!(
+        csp_vault.sks_contains(KeyId::try_from(&public_key).unwrap()),
+        Ok(true)
+    );
+    assert_eq!(
+        csp_vault
+            .current_node_public_keys()
+            .expect("missing public keys")
+            .node_signing_public_key,
+        None
+    );
+}
+
+#[test]
+fn should_store_same_key_under_same_key_id_in_different_vaults() {
+    let rng = &mut reproducible_rng();
+    let seed: [u8; 32] = rng.gen();
+    let vault_with_seed = || {
+        LocalCspVault::builder_for_test()
+            .with_rng(ChaCha20Rng::from_seed(seed))
+            .build_into_arc()
+    };
+    let (vault_1, vault_2) = (vault_with_seed(), vault_with_seed());
+
+    let public_key_1 = vault_1
+        .gen_basic_signature_key_pair(CspBasicSignatureKeygenAlgorithm::SchnorrSecp256k1Bip340)
+        .expect("failed creating key pair");
+    let public_key_2 = vault_2
+        .gen_basic_signature_key_pair(CspBasicSignatureKeygenAlgorithm::SchnorrSecp256k1Bip340)
+        .expect("failed creating key pair");
+
+    assert_eq!(public_key_1, public_key_2);
+    let key_id = KeyId::from_public_key(&public_key_1);
+    assert_eq!(vault_1.sks_contains(key_id), Ok(true));
+    assert_eq!(vault_2.sks_contains(key_id), Ok(true));
+}
+
+#[test]
+fn should_store_different_keys_under_different_key_ids() {
+    let rng = &mut reproducible_rng();
+    let csp_vault = LocalCspVault::builder_for_test()
+        .with_rng(ChaCha20Rng::from_seed(rng.gen()))
+        .build_into_arc();
+
+    let public_key_1 = csp_vault
+        .gen_basic_signature_key_pair(CspBasicSignatureKeygenAlgorithm::SchnorrSecp256k1Bip340)
+        .expect("failed creating key pair");
+    let public_key_2 = csp_vault
+        .gen_basic_signature_key_pair(CspBasicSignatureKeygenAlgorithm::SchnorrSecp256k1Bip340)
+        .expect("failed creating key pair");
+
+    assert_ne!(public_key_1, public_key_2);
+    assert_ne!(
+        KeyId::from_public_key(&public_key_1),
+        KeyId::from_public_key(&public_key_2)
+    );
+    assert_eq!(
+        csp_vault.sks_contains(KeyId::from_public_key(&public_key_2)),
+        Ok(true)
+    );
+}
+
+#[test]
+fn should_fail_with_duplicate_key_id_if_schnorr_bip340_secret_key_already_stored() {
+    let mut sks_returning_duplicate_key_id = MockSecretKeyStore::new();
+    sks_returning_duplicate_key_id
+        .expect_insert()
+        .times(1)
+        .returning(|key_id, _key, _scope| {
+            Err(SecretKeyStoreInsertionError::DuplicateKeyId(key_id))
+        });
+    let vault = LocalCspVault::builder_for_test()
+        .with_node_secret_key_store(sks_returning_duplicate_key_id)
+        .build();
+
+    let result = vault
+        .gen_basic_signature_key_pair(CspBasicSignatureKeygenAlgorithm::SchnorrSecp256k1Bip340);
+
+    assert_matches!(
+        result,
+        Err(CspBasicSignatureKeygenError::DuplicateKeyId { .. })
+    );
+}
+
+#[test]
+fn should_sign_with_schnorr_bip340_key_verifiably_with_standalone_verifier() {
+    let rng = &mut reproducible_rng();
+    let csp_vault = LocalCspVault::builder_for_test()
+        .with_rng(ChaCha20Rng::from_seed(rng.gen()))
+        .build_into_arc();
+    let public_key = csp_vault
+        .gen_basic_signature_key_pair(CspBasicSignatureKeygenAlgorithm::SchnorrSecp256k1Bip340)
+        .expect("failed to generate keys");
+    let msg_len_in_bytes = rng.gen_range(0..1024);
+    let message = random_message(rng, msg_len_in_bytes);
+
+    let signature = csp_vault
+        .sign(
+            AlgorithmId::SchnorrSecp256k1,
+            message.clone(),
+            KeyId::try_from(&public_key).unwrap(),
+        )
+        .expect("failed to sign");
+
+    let pk_bytes = match public_key {
+        CspPublicKey::SchnorrSecp256k1Bip340(pk_bytes) => pk_bytes,
+        _ => panic!("Wrong CspPublicKey: {:?}", public_key),
+    };
+    let signature_bytes = match signature {
+        CspSignature::SchnorrSecp256k1Bip340(signature_bytes) => signature_bytes,
+        _ => panic!("Wrong CspSignature: {:?}", signature),
+    };
+    let verifier = ic_crypto_secp256k1::PublicKey::deserialize_bip340(&pk_bytes.0)
+        .expect("invalid BIP340 public key");
+    assert!(verifier.verify_bip340_signature(&message, &signature_bytes.0));
+    assert!(!verifier.verify_bip340_signature(b"other message", &signature_bytes.0));
+}
+
+#[test]
+fn should_fail_to_sign_with_schnorr_bip340_if_secret_key_in_store_has_wrong_type() {
+    let csp_vault = LocalCspVault::builder_for_test().build_into_arc();
+    let public_key = csp_vault
+        .gen_node_signing_key_pair()
+        .expect("failed to generate keys");
+
+    let result = csp_vault.sign(
+        AlgorithmId::SchnorrSecp256k1,
+        b"sample message".to_vec(),
+        KeyId::try_from(&public_key).unwrap(),
+    );
+
+    assert_eq!(
+        result.expect_err("Unexpected success."),
+        CspBasicSignatureError::WrongSecretKeyType {
+            algorithm: AlgorithmId::SchnorrSecp256k1,
+            secret_key_variant: "Ed25519".to_string()
+        }
+    );
+}
+
 pub fn generate_key_pair_and_sign_and_verify_message(csp_vault: Arc<dyn CspVault>, message: &[u8]) {
     let (pk_bytes, sign_result) = generate_key_pair_and_sign_message(csp_vault, message.to_vec());
     assert!(sign_result.is_ok());
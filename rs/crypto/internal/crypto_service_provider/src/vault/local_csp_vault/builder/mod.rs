@@ -1,4 +1,5 @@
 use super::*;
+use crate::vault::local_csp_vault::audit_log::DEFAULT_AUDIT_LOG_MAX_ENTRIES;
 use rand::rngs::OsRng;
 
 pub struct LocalCspVaultBuilder<R, S, C, P> {
@@ -9,6 +10,8 @@ pub struct LocalCspVaultBuilder<R, S, C, P> {
     time_source: Arc<dyn TimeSource>,
     metrics: Arc<CryptoMetrics>,
     logger: ReplicaLogger,
+    audit_log_max_entries: usize,
+    vault_metrics: Arc<CspVaultMetrics>,
 }
 
 impl ProdLocalCspVault {
@@ -28,6 +31,8 @@ impl ProdLocalCspVault {
             time_source: Arc::new(SysTimeSource::new()),
             metrics,
             logger,
+            audit_log_max_entries: DEFAULT_AUDIT_LOG_MAX_ENTRIES,
+            vault_metrics: Arc::new(CspVaultMetrics::new()),
         }
     }
 
@@ -88,6 +93,8 @@ where
             time_source: self.time_source,
             metrics: self.metrics,
             logger: self.logger,
+            audit_log_max_entries: self.audit_log_max_entries,
+            vault_metrics: self.vault_metrics,
         }
     }
 
@@ -103,6 +110,8 @@ where
             time_source: self.time_source,
             metrics: self.metrics,
             logger: self.logger,
+            audit_log_max_entries: self.audit_log_max_entries,
+            vault_metrics: self.vault_metrics,
         }
     }
 
@@ -118,6 +127,8 @@ where
             time_source: self.time_source,
             metrics: self.metrics,
             logger: self.logger,
+            audit_log_max_entries: self.audit_log_max_entries,
+            vault_metrics: self.vault_metrics,
         }
     }
 
@@ -133,6 +144,8 @@ where
             time_source: self.time_source,
             metrics: self.metrics,
             logger: self.logger,
+            audit_log_max_entries: self.audit_log_max_entries,
+            vault_metrics: self.vault_metrics,
         }
     }
 
@@ -146,6 +159,19 @@ where
         self
     }
 
+    /// Sets the maximum number of entries kept in the vault's audit log.
+    pub fn with_audit_log_max_entries(mut self, audit_log_max_entries: usize) -> Self {
+        self.audit_log_max_entries = audit_log_max_entries;
+        self
+    }
+
+    /// Sets the metrics in which the vault records its signing operations,
+    /// e.g., to share them with a Prometheus scrape endpoint.
+    pub fn with_vault_metrics(mut self, vault_metrics: Arc<CspVaultMetrics>) -> Self {
+        self.vault_metrics = vault_metrics;
+        self
+    }
+
     pub fn build(self) -> LocalCspVault<R, S, C, P> {
         LocalCspVault {
             csprng: CspRwLock::new_for_rng((self.csprng)(), Arc::clone(&self.metrics)),
@@ -164,6 +190,10 @@ where
             time_source: self.time_source,
             metrics: self.metrics,
             logger: self.logger,
+            last_successful_operation: Mutex::new(None),
+            audit_log: Mutex::new(VaultAuditLog::new(self.audit_log_max_entries)),
+            key_usage_stats: Mutex::new(BTreeMap::new()),
+            vault_metrics: self.vault_metrics,
         }
     }
 
@@ -200,6 +230,8 @@ mod test_utils {
                 time_source: FastForwardTimeSource::new(),
                 logger: no_op_logger(),
                 metrics: Arc::new(CryptoMetrics::none()),
+                audit_log_max_entries: DEFAULT_AUDIT_LOG_MAX_ENTRIES,
+                vault_metrics: Arc::new(CspVaultMetrics::new()),
             }
         }
     }
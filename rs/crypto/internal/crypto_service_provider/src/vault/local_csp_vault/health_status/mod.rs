@@ -0,0 +1,28 @@
+use crate::public_key_store::PublicKeyStore;
+use crate::secret_key_store::SecretKeyStore;
+use crate::vault::api::{CspVaultHealthStatus, CspVaultHealthStatusError, HealthStatusCspVault};
+use crate::LocalCspVault;
+use rand::{CryptoRng, Rng};
+
+#[cfg(test)]
+mod tests;
+
+impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore>
+    HealthStatusCspVault for LocalCspVault<R, S, C, P>
+{
+    fn health_status(&self) -> Result<CspVaultHealthStatus, CspVaultHealthStatusError> {
+        // Determine whether a writer holds a lock before waiting for the read
+        // locks below, which would hide it.
+        let is_locked = self.csprng.is_locked_exclusive()
+            || self.node_secret_key_store.is_locked_exclusive()
+            || self.canister_secret_key_store.is_locked_exclusive()
+            || self.public_key_store.is_locked_exclusive();
+        let key_count =
+            self.sks_read_lock().key_count() + self.canister_sks_read_lock().key_count();
+        Ok(CspVaultHealthStatus {
+            key_count,
+            last_successful_operation: *self.last_successful_operation.lock(),
+            is_locked,
+        })
+    }
+}
@@ -0,0 +1,73 @@
+use crate::vault::api::{
+    BasicSignatureCspVault, CspVaultHealthStatus, HealthStatusCspVault, MultiSignatureCspVault,
+    TlsHandshakeCspVault,
+};
+use crate::KeyId;
+use crate::LocalCspVault;
+use ic_interfaces::time_source::TimeSource;
+use ic_test_utilities_time::FastForwardTimeSource;
+use ic_types::crypto::AlgorithmId;
+use ic_types_test_utils::ids::node_test_id;
+use std::time::Duration;
+
+#[test]
+fn should_report_no_keys_and_no_operation_for_new_vault() {
+    let vault = LocalCspVault::builder_for_test().build();
+
+    assert_eq!(
+        vault.health_status(),
+        Ok(CspVaultHealthStatus {
+            key_count: 0,
+            last_successful_operation: None,
+            is_locked: false,
+        })
+    );
+}
+
+#[test]
+fn should_count_generated_keys_and_record_time_of_last_successful_operation() {
+    let time_source = FastForwardTimeSource::new();
+    time_source.advance_time(Duration::from_secs(1_000));
+    let vault = LocalCspVault::builder_for_test()
+        .with_time_source(time_source.clone())
+        .build();
+    let key_generation_time = time_source.get_relative_time();
+
+    vault
+        .gen_node_signing_key_pair()
+        .expect("failed to generate node signing key pair");
+    vault
+        .gen_committee_signing_key_pair()
+        .expect("failed to generate committee signing key pair");
+    vault
+        .gen_tls_key_pair(node_test_id(42))
+        .expect("failed to generate TLS key pair");
+    time_source.advance_time(Duration::from_secs(1));
+    let non_existent_key_id = KeyId::from([42; 32]);
+    assert!(vault
+        .sign(
+            AlgorithmId::Ed25519,
+            b"message".to_vec(),
+            non_existent_key_id
+        )
+        .is_err());
+
+    assert_eq!(
+        vault.health_status(),
+        Ok(CspVaultHealthStatus {
+            key_count: 3,
+            last_successful_operation: Some(key_generation_time),
+            is_locked: false,
+        })
+    );
+}
+
+#[test]
+fn should_report_locked_if_key_store_is_locked_by_writer() {
+    let vault = LocalCspVault::builder_for_test().build();
+    let _public_key_store_write_lock = vault.public_key_store.write();
+
+    let status = vault.health_status().expect("failed to get health status");
+
+    assert!(status.is_locked);
+}
@@ -15,6 +15,7 @@ use crate::vault::api::{
     IDkgCreateDealingVaultError, IDkgDealingInternalBytes, IDkgProtocolCspVault,
     IDkgTranscriptInternalBytes,
 };
+use crate::vault::local_csp_vault::audit_log::VaultAuditEvent;
 use crate::vault::local_csp_vault::LocalCspVault;
 use ic_crypto_internal_logmon::metrics::{MetricsDomain, MetricsResult, MetricsScope};
 use ic_crypto_internal_threshold_sig_canister_threshold_sig::{
@@ -96,7 +97,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             &receiver_keys_typed[..],
             &transcript_operation_internal,
         );
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::IdkgProtocol,
             MetricsScope::Local,
             "idkg_create_dealing",
@@ -131,7 +132,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             receiver_key_id,
             &context_data,
         );
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::IdkgProtocol,
             MetricsScope::Local,
             "idkg_verify_dealing_private",
@@ -164,7 +165,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             &key_id,
             &internal_transcript,
         );
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::IdkgProtocol,
             MetricsScope::Local,
             "idkg_load_transcript",
@@ -199,7 +200,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             &key_id,
             &internal_transcript,
         );
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::IdkgProtocol,
             MetricsScope::Local,
             "idkg_load_transcript_with_openings",
@@ -213,7 +214,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         debug!(self.logger; crypto.method_name => "idkg_gen_dealing_encryption_key_pair");
         let start_time = self.metrics.now();
         let result = self.idkg_gen_dealing_encryption_key_pair_internal();
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::IdkgProtocol,
             MetricsScope::Local,
             "idkg_gen_dealing_encryption_key_pair",
@@ -249,7 +250,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             opener_index,
             &opener_key_id,
         );
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::IdkgProtocol,
             MetricsScope::Local,
             "idkg_open_dealing",
@@ -267,7 +268,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         debug!(self.logger; crypto.method_name => "idkg_retain_active_keys");
         let start_time = self.metrics.now();
         let result = self.idkg_retain_active_keys_internal(active_key_ids, oldest_public_key);
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::IdkgProtocol,
             MetricsScope::Local,
             "idkg_retain_active_keys",
@@ -503,6 +504,10 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             csp_secret_key,
             valid_public_key.get().clone(),
         )?;
+        self.record_audit_event(VaultAuditEvent::KeyGeneration {
+            algorithm: AlgorithmId::MegaSecp256k1,
+            key_id,
+        });
         Ok(public_key)
     }
 
@@ -674,7 +679,11 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
                         }
 
                     }
-                })
+                })?;
+            self.record_audit_event(VaultAuditEvent::KeyDeletion {
+                scope: IDKG_THRESHOLD_KEYS_SCOPE,
+            });
+            Ok(())
         } else {
             Ok(())
         }
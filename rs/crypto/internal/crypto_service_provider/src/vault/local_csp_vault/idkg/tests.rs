@@ -833,6 +833,10 @@ mod idkg_retain_active_keys {
             .in_sequence(&mut seq)
             .return_const(Ok(()));
 
+        // The key usage stats look up which keys were deleted.
+        mnsks.expect_contains().return_const(false);
+        mcsks.expect_contains().return_const(false);
+
         let vault = LocalCspVault::builder_for_test()
             .with_public_key_store(pks)
             .with_canister_secret_key_store(mcsks)
@@ -0,0 +1,68 @@
+//! Soft deletion of keys in the node secret key store.
+//!
+//! When a key is rotated, the old key may still be needed, e.g., to decrypt
+//! messages in flight, so it is marked inactive instead of being deleted.
+//! Inactive keys cannot be used to sign, but are still exported.
+//!
+//! Signing operations must therefore look up node secret keys with
+//! `LocalCspVault::active_node_secret_key` rather than the secret key store.
+use crate::key_id::KeyId;
+use crate::public_key_store::PublicKeyStore;
+use crate::secret_key_store::{SecretKeyStore, SecretKeyStoreWriteError};
+use crate::types::CspSecretKey;
+use crate::vault::api::{CspVaultKeyActivationError, KeyActivationCspVault};
+use crate::vault::local_csp_vault::LocalCspVault;
+use rand::{CryptoRng, Rng};
+
+#[cfg(test)]
+mod tests;
+
+impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore>
+    KeyActivationCspVault for LocalCspVault<R, S, C, P>
+{
+    fn mark_key_inactive(&self, key_id: KeyId) -> Result<(), CspVaultKeyActivationError> {
+        self.set_key_active(&key_id, false)
+    }
+
+    fn mark_key_active(&self, key_id: KeyId) -> Result<(), CspVaultKeyActivationError> {
+        self.set_key_active(&key_id, true)
+    }
+}
+
+impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore>
+    LocalCspVault<R, S, C, P>
+{
+    /// Returns the key with the given ID from the node secret key store, or
+    /// `None` if there is no such key or it is inactive.
+    pub(crate) fn active_node_secret_key(&self, key_id: &KeyId) -> Option<CspSecretKey> {
+        let sks = self.sks_read_lock();
+        if sks.is_key_inactive(key_id) {
+            None
+        } else {
+            sks.get(key_id)
+        }
+    }
+
+    fn set_key_active(
+        &self,
+        key_id: &KeyId,
+        active: bool,
+    ) -> Result<(), CspVaultKeyActivationError> {
+        let contained = self
+            .sks_write_lock()
+            .set_key_active(key_id, active)
+            .map_err(|error| match error {
+                SecretKeyStoreWriteError::SerializationError(internal_error) => {
+                    CspVaultKeyActivationError::InternalError { internal_error }
+                }
+                SecretKeyStoreWriteError::TransientError(internal_error) => {
+                    CspVaultKeyActivationError::TransientInternalError { internal_error }
+                }
+            })?;
+        if contained {
+            Ok(())
+        } else {
+            Err(CspVaultKeyActivationError::SecretKeyNotFound { key_id: *key_id })
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use super::*;
+use crate::vault::api::{
+    BasicSignatureCspVault, CspBasicSignatureError, CspMultiSignatureError, CspTlsSignError,
+    MultiSignatureCspVault, TlsHandshakeCspVault,
+};
+use assert_matches::assert_matches;
+use ic_types::crypto::AlgorithmId;
+use ic_types_test_utils::ids::NODE_42;
+
+#[test]
+fn should_not_sign_with_inactive_key_until_reactivated() {
+    let vault = LocalCspVault::builder_for_test().build();
+    let key_id = KeyId::try_from(
+        &vault
+            .gen_node_signing_key_pair()
+            .expect("failed to generate node signing key pair"),
+    )
+    .expect("failed to compute key ID");
+    let sign = || vault.sign(AlgorithmId::Ed25519, b"message".to_vec(), key_id);
+
+    assert_matches!(sign(), Ok(_));
+
+    vault
+        .mark_key_inactive(key_id)
+        .expect("failed to mark key inactive");
+    assert_eq!(
+        sign(),
+        Err(CspBasicSignatureError::SecretKeyNotFound {
+            algorithm: AlgorithmId::Ed25519,
+            key_id
+        })
+    );
+
+    vault
+        .mark_key_active(key_id)
+        .expect("failed to mark key active");
+    assert_matches!(sign(), Ok(_));
+}
+
+#[test]
+fn should_not_multi_sign_with_inactive_key_until_reactivated() {
+    let vault = LocalCspVault::builder_for_test().build();
+    let (public_key, _pop) = vault
+        .gen_committee_signing_key_pair()
+        .expect("failed to generate committee signing key pair");
+    let key_id = KeyId::try_from(&public_key).expect("failed to compute key ID");
+    let multi_sign = || vault.multi_sign(AlgorithmId::MultiBls12_381, b"message".to_vec(), key_id);
+    let expected_error = CspMultiSignatureError::SecretKeyNotFound {
+        algorithm: AlgorithmId::MultiBls12_381,
+        key_id,
+    };
+
+    assert_matches!(multi_sign(), Ok(_));
+
+    vault
+        .mark_key_inactive(key_id)
+        .expect("failed to mark key inactive");
+    assert_eq!(multi_sign(), Err(expected_error.clone()));
+    assert_eq!(
+        vault.batch_sign(AlgorithmId::MultiBls12_381, &[b"message"], key_id),
+        vec![Err(expected_error)]
+    );
+
+    vault
+        .mark_key_active(key_id)
+        .expect("failed to mark key active");
+    assert_matches!(multi_sign(), Ok(_));
+}
+
+#[test]
+fn should_not_tls_sign_with_inactive_key_until_reactivated() {
+    let vault = LocalCspVault::builder_for_test().build();
+    let key_id = KeyId::try_from(
+        &vault
+            .gen_tls_key_pair(NODE_42)
+            .expect("failed to generate TLS key pair"),
+    )
+    .expect("failed to compute key ID");
+    let tls_sign = || vault.tls_sign(b"message".to_vec(), key_id);
+
+    assert_matches!(tls_sign(), Ok(_));
+
+    vault
+        .mark_key_inactive(key_id)
+        .expect("failed to mark key inactive");
+    assert_eq!(
+        tls_sign(),
+        Err(CspTlsSignError::SecretKeyNotFound { key_id })
+    );
+
+    vault
+        .mark_key_active(key_id)
+        .expect("failed to mark key active");
+    assert_matches!(tls_sign(), Ok(_));
+}
+
+#[test]
+fn should_keep_inactive_key_in_secret_key_store() {
+    let vault = LocalCspVault::builder_for_test().build();
+    let key_id = KeyId::try_from(
+        &vault
+            .gen_node_signing_key_pair()
+            .expect("failed to generate node signing key pair"),
+    )
+    .expect("failed to compute key ID");
+
+    vault
+        .mark_key_inactive(key_id)
+        .expect("failed to mark key inactive");
+
+    assert!(vault.sks_read_lock().contains(&key_id));
+    assert!(vault.sks_read_lock().is_key_inactive(&key_id));
+}
+
+#[test]
+fn should_fail_to_mark_nonexistent_key() {
+    let vault = LocalCspVault::builder_for_test().build();
+    let key_id = KeyId::from([42; 32]);
+
+    assert_eq!(
+        vault.mark_key_inactive(key_id),
+        Err(CspVaultKeyActivationError::SecretKeyNotFound { key_id })
+    );
+    assert_eq!(
+        vault.mark_key_active(key_id),
+        Err(CspVaultKeyActivationError::SecretKeyNotFound { key_id })
+    );
+}
+
+#[cfg(feature = "key_import_export")]
+#[test]
+fn should_export_inactive_key() {
+    let vault = LocalCspVault::builder_for_test().build();
+    let key_id = KeyId::try_from(
+        &vault
+            .gen_node_signing_key_pair()
+            .expect("failed to generate node signing key pair"),
+    )
+    .expect("failed to compute key ID");
+    let exported_while_active = vault
+        .export_key_pair_pkcs8(&key_id)
+        .expect("failed to export key pair");
+
+    vault
+        .mark_key_inactive(key_id)
+        .expect("failed to mark key inactive");
+
+    assert_eq!(
+        vault.export_key_pair_pkcs8(&key_id),
+        Ok(exported_while_active)
+    );
+}
@@ -0,0 +1,310 @@
+//! Export and import of key pairs in PKCS#8 format, and of encrypted backups
+//! of all secret keys.
+//!
+//! Only available with the `key_import_export` feature. Key pairs exported in
+//! PKCS#8 format are *not* encrypted, so they must be handled with the same
+//! care as the secret key store itself.
+use crate::key_id::KeyId;
+use crate::public_key_store::PublicKeyStore;
+use crate::secret_key_store::{
+    Scope, SecretKeyStore, SecretKeyStoreInsertionError, SecretKeyStoreWriteError,
+};
+use crate::types::{CspPublicKey, CspSecretKey};
+use crate::vault::api::{CspVaultExportError, CspVaultImportError};
+use crate::vault::local_csp_vault::builder::LocalCspVaultBuilder;
+use crate::vault::local_csp_vault::LocalCspVault;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use ic_crypto_ed25519::{PrivateKey, PrivateKeyFormat};
+use ic_crypto_internal_basic_sig_ed25519::types::{PublicKeyBytes, SecretKeyBytes};
+use ic_crypto_secrets_containers::SecretArray;
+use rand::{CryptoRng, Rng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use zeroize::Zeroizing;
+
+#[cfg(test)]
+mod tests;
+
+impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore>
+    LocalCspVault<R, S, C, P>
+{
+    /// Exports the key pair with the given ID from the node secret key store
+    /// as unencrypted PKCS#8 v1 (RFC 5208) in DER encoding.
+    ///
+    /// # Errors
+    /// * `CspVaultExportError::SecretKeyNotFound` if the node secret key store
+    ///   does not contain a key with the given ID.
+    /// * `CspVaultExportError::UnsupportedKeyType` if the key is not an Ed25519
+    ///   key.
+    pub fn export_key_pair_pkcs8(&self, key_id: &KeyId) -> Result<Vec<u8>, CspVaultExportError> {
+        let secret_key = self
+            .sks_read_lock()
+            .get(key_id)
+            .ok_or(CspVaultExportError::SecretKeyNotFound { key_id: *key_id })?;
+        match &secret_key {
+            CspSecretKey::Ed25519(secret_key_bytes) => {
+                let private_key =
+                    PrivateKey::deserialize_raw_32(secret_key_bytes.0.expose_secret());
+                Ok(private_key.serialize_pkcs8(PrivateKeyFormat::Pkcs8v1))
+            }
+            _ => Err(CspVaultExportError::UnsupportedKeyType {
+                secret_key_variant: secret_key.enum_variant().to_string(),
+            }),
+        }
+    }
+
+    /// Imports an Ed25519 key pair given as PKCS#8 (v1 or v2) in DER encoding
+    /// into the node secret key store.
+    ///
+    /// The public key store is not modified.
+    ///
+    /// # Returns
+    /// The ID of the imported key.
+    ///
+    /// # Errors
+    /// * `CspVaultImportError::MalformedPkcs8` if the input is not a valid
+    ///   PKCS#8 encoding of an Ed25519 key.
+    /// * `CspVaultImportError::DuplicateKeyId` if the node secret key store
+    ///   already contains a key with the same ID.
+    /// * `CspVaultImportError::InternalError` if the secret key store cannot be
+    ///   serialized.
+    /// * `CspVaultImportError::TransientInternalError` if there is a transient
+    ///   error persisting the secret key store, e.g., an IO error.
+    pub fn import_key_pair_pkcs8(&self, pkcs8_bytes: &[u8]) -> Result<KeyId, CspVaultImportError> {
+        let private_key = PrivateKey::deserialize_pkcs8(pkcs8_bytes).map_err(|error| {
+            CspVaultImportError::MalformedPkcs8 {
+                internal_error: error.to_string(),
+            }
+        })?;
+        let public_key =
+            CspPublicKey::Ed25519(PublicKeyBytes(private_key.public_key().serialize_raw()));
+        let key_id = KeyId::from_public_key(&public_key);
+        let secret_key = CspSecretKey::Ed25519(SecretKeyBytes(
+            SecretArray::new_and_dont_zeroize_argument(&private_key.serialize_raw()),
+        ));
+        self.sks_write_lock()
+            .insert(key_id, secret_key, None)
+            .map_err(import_error)?;
+        Ok(key_id)
+    }
+
+    /// Exports all keys of the node and canister secret key stores, together
+    /// with their scopes and whether they are inactive, as a backup encrypted
+    /// with AES-256-GCM under `encryption_key`.
+    ///
+    /// The public key store is not exported.
+    ///
+    /// # Returns
+    /// A fresh random nonce followed by the ciphertext.
+    ///
+    /// # Errors
+    /// * `CspVaultExportError::InternalError` if the keys cannot be serialized
+    ///   or encrypted.
+    pub fn export_key_store(
+        &self,
+        encryption_key: &[u8; 32],
+    ) -> Result<Vec<u8>, CspVaultExportError> {
+        let nonce: [u8; NONCE_LENGTH] = self.rng_write_lock().gen();
+        let backup = KeyStoreBackup {
+            node_secret_keys: backup_entries(&*self.sks_read_lock()),
+            canister_secret_keys: backup_entries(&*self.canister_sks_read_lock()),
+        };
+        let plaintext = Zeroizing::new(serde_cbor::to_vec(&backup).map_err(|error| {
+            CspVaultExportError::InternalError {
+                internal_error: format!("Failed to serialize key store backup: {}", error),
+            }
+        })?);
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(encryption_key))
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: KEY_STORE_BACKUP_DOMAIN,
+                },
+            )
+            .map_err(|error| CspVaultExportError::InternalError {
+                internal_error: format!("Failed to encrypt key store backup: {}", error),
+            })?;
+        let mut result = Vec::with_capacity(NONCE_LENGTH + ciphertext.len());
+        result.extend_from_slice(&nonce);
+        result.extend_from_slice(&ciphertext);
+        Ok(result)
+    }
+
+    /// Builds a vault with `builder` and imports into its node and canister
+    /// secret key stores all keys of a backup created with
+    /// [`Self::export_key_store`] under `encryption_key`. Keys that were
+    /// inactive when exported are marked inactive again.
+    ///
+    /// The key stores of the builder are expected to be empty. The public key
+    /// store is not modified. All keys of the backup are checked for duplicate
+    /// IDs before any of them is inserted, so that a backup that cannot be
+    /// imported leaves the key stores untouched. Only a failure to persist a
+    /// secret key store may leave it partially written.
+    ///
+    /// # Errors
+    /// * `CspVaultImportError::MalformedBackup` if `ciphertext` cannot be
+    ///   decrypted with `encryption_key`, or is not a key store backup.
+    /// * `CspVaultImportError::DuplicateKeyId` if a secret key store of the
+    ///   builder already contains a key with the same ID, or if the backup
+    ///   contains the same key ID twice for the same secret key store.
+    /// * `CspVaultImportError::InternalError` if a secret key store cannot be
+    ///   serialized.
+    /// * `CspVaultImportError::TransientInternalError` if there is a transient
+    ///   error persisting a secret key store, e.g., an IO error.
+    pub fn import_key_store(
+        builder: LocalCspVaultBuilder<R, S, C, P>,
+        ciphertext: &[u8],
+        encryption_key: &[u8; 32],
+    ) -> Result<Self, CspVaultImportError>
+    where
+        R: 'static,
+        S: 'static,
+        C: 'static,
+        P: 'static,
+    {
+        let malformed_backup =
+            |internal_error: String| CspVaultImportError::MalformedBackup { internal_error };
+        if ciphertext.len() < NONCE_LENGTH {
+            return Err(malformed_backup(format!(
+                "Key store backup of {} bytes is too short",
+                ciphertext.len()
+            )));
+        }
+        let (nonce, ciphertext) = ciphertext.split_at(NONCE_LENGTH);
+        let plaintext = Zeroizing::new(
+            Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(encryption_key))
+                .decrypt(
+                    Nonce::from_slice(nonce),
+                    Payload {
+                        msg: ciphertext,
+                        aad: KEY_STORE_BACKUP_DOMAIN,
+                    },
+                )
+                .map_err(|error| {
+                    malformed_backup(format!("Failed to decrypt key store backup: {}", error))
+                })?,
+        );
+        let backup: KeyStoreBackup = serde_cbor::from_slice(&plaintext).map_err(|error| {
+            malformed_backup(format!("Failed to deserialize key store backup: {}", error))
+        })?;
+
+        let vault = builder.build();
+        {
+            let mut node_sks = vault.sks_write_lock();
+            let mut canister_sks = vault.canister_sks_write_lock();
+            ensure_no_duplicate_key_ids(&*node_sks, &backup.node_secret_keys)?;
+            ensure_no_duplicate_key_ids(&*canister_sks, &backup.canister_secret_keys)?;
+            import_backup_entries(&mut *node_sks, backup.node_secret_keys)?;
+            import_backup_entries(&mut *canister_sks, backup.canister_secret_keys)?;
+        }
+        Ok(vault)
+    }
+}
+
+const NONCE_LENGTH: usize = 12;
+
+/// Associated data binding the ciphertext of a backup to its purpose.
+const KEY_STORE_BACKUP_DOMAIN: &[u8] = b"ic-crypto-csp-vault-key-store-backup-v1";
+
+/// The plaintext of a backup created by `LocalCspVault::export_key_store`.
+#[derive(Deserialize, Serialize)]
+struct KeyStoreBackup {
+    node_secret_keys: Vec<KeyStoreBackupEntry>,
+    canister_secret_keys: Vec<KeyStoreBackupEntry>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct KeyStoreBackupEntry {
+    key_id: KeyId,
+    secret_key: CspSecretKey,
+    scope: Option<Scope>,
+    /// Whether the key was marked inactive, see `SecretKeyStore::set_key_active`.
+    inactive: bool,
+}
+
+fn backup_entries<S: SecretKeyStore>(sks: &S) -> Vec<KeyStoreBackupEntry> {
+    sks.entries()
+        .into_iter()
+        .map(|(key_id, secret_key, scope)| KeyStoreBackupEntry {
+            key_id,
+            secret_key,
+            scope,
+            inactive: sks.is_key_inactive(&key_id),
+        })
+        .collect()
+}
+
+fn ensure_no_duplicate_key_ids<S: SecretKeyStore>(
+    sks: &S,
+    entries: &[KeyStoreBackupEntry],
+) -> Result<(), CspVaultImportError> {
+    let mut key_ids = BTreeSet::new();
+    for entry in entries {
+        if sks.contains(&entry.key_id) || !key_ids.insert(entry.key_id) {
+            return Err(CspVaultImportError::DuplicateKeyId {
+                key_id: entry.key_id,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn import_backup_entries<S: SecretKeyStore>(
+    sks: &mut S,
+    entries: Vec<KeyStoreBackupEntry>,
+) -> Result<(), CspVaultImportError> {
+    for entry in entries {
+        sks.insert(entry.key_id, entry.secret_key, entry.scope)
+            .map_err(import_error)?;
+        if entry.inactive {
+            sks.set_key_active(&entry.key_id, false)
+                .map_err(activation_import_error)?;
+        }
+    }
+    Ok(())
+}
+
+fn import_error(sks_error: SecretKeyStoreInsertionError) -> CspVaultImportError {
+    match sks_error {
+        SecretKeyStoreInsertionError::DuplicateKeyId(key_id) => {
+            CspVaultImportError::DuplicateKeyId { key_id }
+        }
+        SecretKeyStoreInsertionError::SerializationError(error) => {
+            CspVaultImportError::InternalError {
+                internal_error: format!(
+                    "Error persisting secret key store during key import: {}",
+                    error
+                ),
+            }
+        }
+        SecretKeyStoreInsertionError::TransientError(error) => {
+            CspVaultImportError::TransientInternalError {
+                internal_error: format!(
+                    "Error persisting secret key store during key import: {}",
+                    error
+                ),
+            }
+        }
+    }
+}
+
+fn activation_import_error(sks_error: SecretKeyStoreWriteError) -> CspVaultImportError {
+    match sks_error {
+        SecretKeyStoreWriteError::SerializationError(error) => CspVaultImportError::InternalError {
+            internal_error: format!(
+                "Error persisting secret key store during key import: {}",
+                error
+            ),
+        },
+        SecretKeyStoreWriteError::TransientError(error) => {
+            CspVaultImportError::TransientInternalError {
+                internal_error: format!(
+                    "Error persisting secret key store during key import: {}",
+                    error
+                ),
+            }
+        }
+    }
+}
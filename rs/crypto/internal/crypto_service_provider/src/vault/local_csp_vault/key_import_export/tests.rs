@@ -0,0 +1,409 @@
+//! Tests of key export and import in the CSP vault.
+use crate::canister_threshold::IDKG_THRESHOLD_KEYS_SCOPE;
+use crate::secret_key_store::proto_store::ProtoSecretKeyStore;
+use crate::secret_key_store::temp_secret_key_store::TempSecretKeyStore;
+use crate::secret_key_store::test_utils::{make_key_id, make_secret_key};
+use crate::secret_key_store::SecretKeyStore;
+use crate::types::{CspPublicKey, CspSignature};
+use crate::vault::api::{
+    BasicSignatureCspVault, CspVaultExportError, CspVaultImportError, IDkgProtocolCspVault,
+    KeyActivationCspVault, MultiSignatureCspVault, NiDkgCspVault, SecretKeyStoreCspVault,
+    TlsHandshakeCspVault,
+};
+use crate::KeyId;
+use crate::LocalCspVault;
+use assert_matches::assert_matches;
+use ic_crypto_internal_basic_sig_ed25519 as ed25519;
+use ic_crypto_internal_csp_test_utils::files::mk_temp_dir_with_permissions;
+use ic_crypto_internal_logmon::metrics::CryptoMetrics;
+use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+use ic_types::crypto::AlgorithmId;
+use ic_types_test_utils::ids::NODE_42;
+use rand::Rng;
+use std::sync::Arc;
+
+#[test]
+fn should_sign_with_reimported_key_pair_verifiable_with_original_public_key() {
+    let rng = &mut reproducible_rng();
+    let exporting_vault = LocalCspVault::builder_for_test()
+        .with_rng(rng.fork())
+        .build();
+    let public_key = exporting_vault
+        .gen_node_signing_key_pair()
+        .expect("failed to generate keys");
+    let key_id = KeyId::try_from(&public_key).unwrap();
+    let importing_vault = LocalCspVault::builder_for_test()
+        .with_rng(rng.fork())
+        .build();
+
+    let pkcs8 = exporting_vault
+        .export_key_pair_pkcs8(&key_id)
+        .expect("failed to export key pair");
+    let imported_key_id = importing_vault
+        .import_key_pair_pkcs8(&pkcs8)
+        .expect("failed to import key pair");
+
+    assert_eq!(imported_key_id, key_id);
+    let msg: Vec<u8> = (0..rng.gen_range(0..1024)).map(|_| rng.gen()).collect();
+    let signature = importing_vault
+        .sign(AlgorithmId::Ed25519, msg.clone(), imported_key_id)
+        .expect("failed to sign with imported key");
+    let (CspSignature::Ed25519(signature), CspPublicKey::Ed25519(public_key)) =
+        (signature, public_key)
+    else {
+        panic!("expected Ed25519 signature and public key");
+    };
+    assert!(ed25519::verify(&signature, &msg, &public_key).is_ok());
+}
+
+#[test]
+fn should_export_key_pair_as_pkcs8_v1() {
+    let csp_vault = LocalCspVault::builder_for_test().build();
+    let public_key = csp_vault
+        .gen_node_signing_key_pair()
+        .expect("failed to generate keys");
+
+    let pkcs8 = csp_vault
+        .export_key_pair_pkcs8(&KeyId::try_from(&public_key).unwrap())
+        .expect("failed to export key pair");
+
+    let secret_key = ed25519::secret_key_from_pkcs8_v1_der(
+        &ic_crypto_secrets_containers::SecretBytes::new(pkcs8),
+    )
+    .expect("export is not PKCS#8 v1");
+    let signature = ed25519::sign(b"message", &secret_key).unwrap();
+    let CspPublicKey::Ed25519(public_key) = public_key else {
+        panic!("expected Ed25519 public key");
+    };
+    assert!(ed25519::verify(&signature, b"message", &public_key).is_ok());
+}
+
+#[test]
+fn should_fail_to_export_nonexistent_key() {
+    let csp_vault = LocalCspVault::builder_for_test().build();
+    let key_id = KeyId::from([42; 32]);
+
+    assert_eq!(
+        csp_vault.export_key_pair_pkcs8(&key_id),
+        Err(CspVaultExportError::SecretKeyNotFound { key_id })
+    );
+}
+
+#[test]
+fn should_fail_to_export_non_ed25519_key() {
+    let csp_vault = LocalCspVault::builder_for_test().build();
+    let (public_key, _pop) = csp_vault
+        .gen_committee_signing_key_pair()
+        .expect("failed to generate keys");
+
+    assert_eq!(
+        csp_vault.export_key_pair_pkcs8(&KeyId::try_from(&public_key).unwrap()),
+        Err(CspVaultExportError::UnsupportedKeyType {
+            secret_key_variant: "MultiBls12_381".to_string()
+        })
+    );
+}
+
+#[test]
+fn should_fail_to_import_malformed_pkcs8() {
+    let csp_vault = LocalCspVault::builder_for_test().build();
+
+    assert_matches!(
+        csp_vault.import_key_pair_pkcs8(&[48, 46, 2, 1, 0]),
+        Err(CspVaultImportError::MalformedPkcs8 { .. })
+    );
+}
+
+#[test]
+fn should_fail_to_import_key_pair_already_in_store() {
+    let csp_vault = LocalCspVault::builder_for_test().build();
+    let public_key = csp_vault
+        .gen_node_signing_key_pair()
+        .expect("failed to generate keys");
+    let key_id = KeyId::try_from(&public_key).unwrap();
+    let pkcs8 = csp_vault
+        .export_key_pair_pkcs8(&key_id)
+        .expect("failed to export key pair");
+
+    assert_eq!(
+        csp_vault.import_key_pair_pkcs8(&pkcs8),
+        Err(CspVaultImportError::DuplicateKeyId { key_id })
+    );
+    assert!(csp_vault.sks_contains(key_id).expect("sks_contains failed"));
+}
+
+mod key_store_backup {
+    use super::*;
+
+    const ENCRYPTION_KEY: [u8; 32] = [42; 32];
+
+    #[test]
+    fn should_import_all_node_secret_keys() {
+        let exporting_vault = LocalCspVault::builder_for_test().build();
+        let key_ids = vec![
+            KeyId::try_from(&exporting_vault.gen_node_signing_key_pair().unwrap()).unwrap(),
+            KeyId::try_from(&exporting_vault.gen_committee_signing_key_pair().unwrap().0).unwrap(),
+            KeyId::from(
+                &exporting_vault
+                    .gen_dealing_encryption_key_pair(NODE_42)
+                    .unwrap()
+                    .0,
+            ),
+            KeyId::try_from(&exporting_vault.gen_tls_key_pair(NODE_42).unwrap()).unwrap(),
+            KeyId::try_from(
+                &exporting_vault
+                    .idkg_gen_dealing_encryption_key_pair()
+                    .unwrap(),
+            )
+            .unwrap(),
+        ];
+
+        let backup = exporting_vault
+            .export_key_store(&ENCRYPTION_KEY)
+            .expect("failed to export key store");
+        let importing_vault = LocalCspVault::import_key_store(
+            LocalCspVault::builder_for_test(),
+            &backup,
+            &ENCRYPTION_KEY,
+        )
+        .expect("failed to import key store");
+
+        assert_eq!(importing_vault.sks_read_lock().key_count(), key_ids.len());
+        for key_id in key_ids {
+            assert!(importing_vault
+                .sks_contains(key_id)
+                .expect("sks_contains failed"));
+            assert_eq!(
+                importing_vault.sks_read_lock().get(&key_id),
+                exporting_vault.sks_read_lock().get(&key_id)
+            );
+        }
+    }
+
+    #[test]
+    fn should_sign_with_imported_keys_like_with_original_keys() {
+        let rng = &mut reproducible_rng();
+        let exporting_vault = LocalCspVault::builder_for_test()
+            .with_rng(rng.fork())
+            .build();
+        let node_signing_key_id =
+            KeyId::try_from(&exporting_vault.gen_node_signing_key_pair().unwrap()).unwrap();
+        let committee_signing_key_id =
+            KeyId::try_from(&exporting_vault.gen_committee_signing_key_pair().unwrap().0).unwrap();
+
+        let backup = exporting_vault
+            .export_key_store(&ENCRYPTION_KEY)
+            .expect("failed to export key store");
+        let importing_vault = LocalCspVault::import_key_store(
+            LocalCspVault::builder_for_test().with_rng(rng.fork()),
+            &backup,
+            &ENCRYPTION_KEY,
+        )
+        .expect("failed to import key store");
+
+        let msg: Vec<u8> = (0..rng.gen_range(0..1024)).map(|_| rng.gen()).collect();
+        assert_eq!(
+            importing_vault
+                .sign(AlgorithmId::Ed25519, msg.clone(), node_signing_key_id)
+                .expect("failed to sign with imported key"),
+            exporting_vault
+                .sign(AlgorithmId::Ed25519, msg.clone(), node_signing_key_id)
+                .expect("failed to sign with original key")
+        );
+        assert_eq!(
+            importing_vault
+                .multi_sign(
+                    AlgorithmId::MultiBls12_381,
+                    msg.clone(),
+                    committee_signing_key_id
+                )
+                .expect("failed to sign with imported key"),
+            exporting_vault
+                .multi_sign(AlgorithmId::MultiBls12_381, msg, committee_signing_key_id)
+                .expect("failed to sign with original key")
+        );
+    }
+
+    #[test]
+    fn should_import_inactive_keys_as_inactive() {
+        let exporting_vault = LocalCspVault::builder_for_test().build();
+        let active_key_id =
+            KeyId::try_from(&exporting_vault.gen_node_signing_key_pair().unwrap()).unwrap();
+        let inactive_key_id =
+            KeyId::try_from(&exporting_vault.gen_committee_signing_key_pair().unwrap().0).unwrap();
+        exporting_vault
+            .mark_key_inactive(inactive_key_id)
+            .expect("failed to mark key inactive");
+
+        let backup = exporting_vault
+            .export_key_store(&ENCRYPTION_KEY)
+            .expect("failed to export key store");
+        let importing_vault = LocalCspVault::import_key_store(
+            LocalCspVault::builder_for_test(),
+            &backup,
+            &ENCRYPTION_KEY,
+        )
+        .expect("failed to import key store");
+
+        let sks = importing_vault.sks_read_lock();
+        assert_eq!(sks.key_count(), 2);
+        assert!(!sks.is_key_inactive(&active_key_id));
+        assert!(sks.is_key_inactive(&inactive_key_id));
+        assert_eq!(
+            sks.get(&inactive_key_id),
+            exporting_vault.sks_read_lock().get(&inactive_key_id)
+        );
+    }
+
+    #[test]
+    fn should_import_canister_secret_keys_with_their_scope() {
+        let rng = &mut reproducible_rng();
+        let mut canister_sks = TempSecretKeyStore::new();
+        let key_id = make_key_id(rng);
+        let secret_key = make_secret_key(rng);
+        canister_sks
+            .insert(key_id, secret_key.clone(), Some(IDKG_THRESHOLD_KEYS_SCOPE))
+            .expect("failed to insert key");
+        let exporting_vault = LocalCspVault::builder_for_test()
+            .with_canister_secret_key_store(canister_sks)
+            .build();
+
+        let backup = exporting_vault
+            .export_key_store(&ENCRYPTION_KEY)
+            .expect("failed to export key store");
+        let importing_vault = LocalCspVault::import_key_store(
+            LocalCspVault::builder_for_test(),
+            &backup,
+            &ENCRYPTION_KEY,
+        )
+        .expect("failed to import key store");
+
+        assert_eq!(importing_vault.sks_read_lock().key_count(), 0);
+        assert_eq!(
+            importing_vault.canister_sks_read_lock().entries(),
+            vec![(key_id, secret_key, Some(IDKG_THRESHOLD_KEYS_SCOPE))]
+        );
+    }
+
+    #[test]
+    fn should_use_fresh_nonce_for_each_export() {
+        let vault = LocalCspVault::builder_for_test().build();
+        let _ = vault.gen_node_signing_key_pair().unwrap();
+
+        let first_backup = vault.export_key_store(&ENCRYPTION_KEY).unwrap();
+        let second_backup = vault.export_key_store(&ENCRYPTION_KEY).unwrap();
+
+        assert_ne!(first_backup, second_backup);
+    }
+
+    #[test]
+    fn should_fail_to_import_with_wrong_encryption_key() {
+        let vault = LocalCspVault::builder_for_test().build();
+        let _ = vault.gen_node_signing_key_pair().unwrap();
+        let backup = vault.export_key_store(&ENCRYPTION_KEY).unwrap();
+
+        let result =
+            LocalCspVault::import_key_store(LocalCspVault::builder_for_test(), &backup, &[13; 32]);
+
+        assert_matches!(result, Err(CspVaultImportError::MalformedBackup { .. }));
+    }
+
+    #[test]
+    fn should_fail_to_import_modified_backup() {
+        let vault = LocalCspVault::builder_for_test().build();
+        let _ = vault.gen_node_signing_key_pair().unwrap();
+        let mut backup = vault.export_key_store(&ENCRYPTION_KEY).unwrap();
+        let last = backup.len() - 1;
+        backup[last] ^= 1;
+
+        let result = LocalCspVault::import_key_store(
+            LocalCspVault::builder_for_test(),
+            &backup,
+            &ENCRYPTION_KEY,
+        );
+
+        assert_matches!(result, Err(CspVaultImportError::MalformedBackup { .. }));
+    }
+
+    #[test]
+    fn should_fail_to_import_truncated_backup() {
+        let result = LocalCspVault::import_key_store(
+            LocalCspVault::builder_for_test(),
+            &[0; 11],
+            &ENCRYPTION_KEY,
+        );
+
+        assert_matches!(result, Err(CspVaultImportError::MalformedBackup { internal_error })
+            if internal_error.contains("too short")
+        );
+    }
+
+    #[test]
+    fn should_fail_to_import_key_already_in_store() {
+        let vault = LocalCspVault::builder_for_test().build();
+        let key_id = KeyId::try_from(&vault.gen_node_signing_key_pair().unwrap()).unwrap();
+        let backup = vault.export_key_store(&ENCRYPTION_KEY).unwrap();
+        let mut node_sks = TempSecretKeyStore::new();
+        node_sks
+            .insert(key_id, vault.sks_read_lock().get(&key_id).unwrap(), None)
+            .expect("failed to insert key");
+
+        let result = LocalCspVault::import_key_store(
+            LocalCspVault::builder_for_test().with_node_secret_key_store(node_sks),
+            &backup,
+            &ENCRYPTION_KEY,
+        );
+
+        assert_matches!(result, Err(CspVaultImportError::DuplicateKeyId { key_id: id }) if id == key_id);
+    }
+
+    #[test]
+    fn should_not_write_any_key_if_import_fails_due_to_duplicate() {
+        let rng = &mut reproducible_rng();
+        let canister_key_id = make_key_id(rng);
+        let canister_secret_key = make_secret_key(rng);
+        let mut exporting_canister_sks = TempSecretKeyStore::new();
+        exporting_canister_sks
+            .insert(
+                canister_key_id,
+                canister_secret_key.clone(),
+                Some(IDKG_THRESHOLD_KEYS_SCOPE),
+            )
+            .expect("failed to insert key");
+        let vault = LocalCspVault::builder_for_test()
+            .with_canister_secret_key_store(exporting_canister_sks)
+            .build();
+        vault.gen_node_signing_key_pair().unwrap();
+        let backup = vault.export_key_store(&ENCRYPTION_KEY).unwrap();
+        let mut canister_sks = TempSecretKeyStore::new();
+        canister_sks
+            .insert(
+                canister_key_id,
+                canister_secret_key,
+                Some(IDKG_THRESHOLD_KEYS_SCOPE),
+            )
+            .expect("failed to insert key");
+        let key_store_dir = mk_temp_dir_with_permissions(0o700);
+        let open_node_sks = || {
+            ProtoSecretKeyStore::open(
+                key_store_dir.path(),
+                "sks_data.pb",
+                None,
+                Arc::new(CryptoMetrics::none()),
+            )
+        };
+
+        let result = LocalCspVault::import_key_store(
+            LocalCspVault::builder_for_test()
+                .with_node_secret_key_store(open_node_sks())
+                .with_canister_secret_key_store(canister_sks),
+            &backup,
+            &ENCRYPTION_KEY,
+        );
+
+        assert_matches!(result, Err(CspVaultImportError::DuplicateKeyId { key_id })
+            if key_id == canister_key_id
+        );
+        assert_eq!(open_node_sks().key_count(), 0);
+    }
+}
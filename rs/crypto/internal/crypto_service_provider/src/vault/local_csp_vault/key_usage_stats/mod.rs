@@ -0,0 +1,51 @@
+//! Usage statistics of the keys stored in the CSP vault, to detect keys that
+//! are used unexpectedly frequently.
+use crate::key_id::KeyId;
+use crate::public_key_store::PublicKeyStore;
+use crate::secret_key_store::SecretKeyStore;
+use crate::vault::local_csp_vault::audit_log::VaultAuditEvent;
+use crate::vault::local_csp_vault::LocalCspVault;
+use rand::{CryptoRng, Rng};
+use std::collections::BTreeMap;
+
+#[cfg(test)]
+mod tests;
+
+impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore>
+    LocalCspVault<R, S, C, P>
+{
+    /// Returns the number of signatures created with each key.
+    ///
+    /// The count of a key is reset to 0 when the key is (re-)generated, and
+    /// dropped when the key is deleted from the secret key stores. Keys that
+    /// were generated but never used are included with a count of 0.
+    pub fn key_usage_stats(&self) -> BTreeMap<KeyId, u64> {
+        self.key_usage_stats.lock().clone()
+    }
+
+    /// Updates the key usage statistics according to the given audit event.
+    pub(crate) fn update_key_usage_stats(&self, event: &VaultAuditEvent) {
+        match event {
+            VaultAuditEvent::Signing { key_id, .. } => {
+                *self.key_usage_stats.lock().entry(*key_id).or_insert(0) += 1;
+            }
+            VaultAuditEvent::KeyGeneration { key_id, .. } => {
+                self.key_usage_stats.lock().insert(*key_id, 0);
+            }
+            VaultAuditEvent::KeyDeletion { .. } => {
+                let key_ids: Vec<KeyId> = self.key_usage_stats.lock().keys().copied().collect();
+                let deleted_key_ids: Vec<KeyId> = key_ids
+                    .into_iter()
+                    .filter(|key_id| {
+                        !self.sks_read_lock().contains(key_id)
+                            && !self.canister_sks_read_lock().contains(key_id)
+                    })
+                    .collect();
+                let mut stats = self.key_usage_stats.lock();
+                for key_id in deleted_key_ids.iter() {
+                    stats.remove(key_id);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use super::*;
+use crate::canister_threshold::IDKG_THRESHOLD_KEYS_SCOPE;
+use crate::vault::api::{BasicSignatureCspVault, MultiSignatureCspVault};
+use crate::LocalCspVault;
+use ic_types::crypto::AlgorithmId;
+
+#[test]
+fn should_have_no_key_usage_stats_for_new_vault() {
+    let vault = LocalCspVault::builder_for_test().build();
+
+    assert_eq!(vault.key_usage_stats(), BTreeMap::new());
+}
+
+#[test]
+fn should_count_signatures_per_key() {
+    let vault = LocalCspVault::builder_for_test().build();
+    let node_signing_key_id = KeyId::try_from(
+        &vault
+            .gen_node_signing_key_pair()
+            .expect("failed to generate node signing key pair"),
+    )
+    .expect("failed to compute key ID");
+    let (committee_signing_public_key, _pop) = vault
+        .gen_committee_signing_key_pair()
+        .expect("failed to generate committee signing key pair");
+    let committee_signing_key_id =
+        KeyId::try_from(&committee_signing_public_key).expect("failed to compute key ID");
+
+    for _ in 0..5 {
+        vault
+            .sign(
+                AlgorithmId::Ed25519,
+                b"message".to_vec(),
+                node_signing_key_id,
+            )
+            .expect("failed to sign");
+    }
+
+    assert_eq!(
+        vault.key_usage_stats(),
+        BTreeMap::from([(node_signing_key_id, 5), (committee_signing_key_id, 0)])
+    );
+
+    vault
+        .multi_sign(
+            AlgorithmId::MultiBls12_381,
+            b"message".to_vec(),
+            committee_signing_key_id,
+        )
+        .expect("failed to multi-sign");
+
+    assert_eq!(
+        vault.key_usage_stats(),
+        BTreeMap::from([(node_signing_key_id, 5), (committee_signing_key_id, 1)])
+    );
+}
+
+#[test]
+fn should_not_count_failed_signing() {
+    let vault = LocalCspVault::builder_for_test().build();
+
+    assert!(vault
+        .sign(
+            AlgorithmId::Ed25519,
+            b"message".to_vec(),
+            KeyId::from([42; 32])
+        )
+        .is_err());
+
+    assert_eq!(vault.key_usage_stats(), BTreeMap::new());
+}
+
+#[test]
+fn should_reset_count_when_key_is_generated() {
+    let vault = LocalCspVault::builder_for_test().build();
+    let key_id = KeyId::from([42; 32]);
+    let signing = VaultAuditEvent::Signing {
+        algorithm: AlgorithmId::Ed25519,
+        key_id,
+    };
+    vault.record_audit_event(signing.clone());
+    vault.record_audit_event(signing);
+
+    vault.record_audit_event(VaultAuditEvent::KeyGeneration {
+        algorithm: AlgorithmId::Ed25519,
+        key_id,
+    });
+
+    assert_eq!(vault.key_usage_stats(), BTreeMap::from([(key_id, 0)]));
+}
+
+#[test]
+fn should_drop_count_of_deleted_keys() {
+    let vault = LocalCspVault::builder_for_test().build();
+    let existing_key_id = KeyId::try_from(
+        &vault
+            .gen_node_signing_key_pair()
+            .expect("failed to generate node signing key pair"),
+    )
+    .expect("failed to compute key ID");
+    vault
+        .sign(AlgorithmId::Ed25519, b"message".to_vec(), existing_key_id)
+        .expect("failed to sign");
+    let deleted_key_id = KeyId::from([42; 32]);
+    vault.record_audit_event(VaultAuditEvent::Signing {
+        algorithm: AlgorithmId::ThresBls12_381,
+        key_id: deleted_key_id,
+    });
+
+    vault.record_audit_event(VaultAuditEvent::KeyDeletion {
+        scope: IDKG_THRESHOLD_KEYS_SCOPE,
+    });
+
+    assert_eq!(
+        vault.key_usage_stats(),
+        BTreeMap::from([(existing_key_id, 1)])
+    );
+}
@@ -1,6 +1,12 @@
+pub mod audit_log;
 mod basic_sig;
 pub mod builder;
+mod health_status;
 mod idkg;
+mod key_activation;
+#[cfg(feature = "key_import_export")]
+mod key_import_export;
+mod key_usage_stats;
 mod multi_sig;
 mod ni_dkg;
 mod public_and_secret_key_store;
@@ -13,6 +19,7 @@ mod tests;
 mod threshold_sig;
 mod tls;
 mod tschnorr;
+pub mod vault_metrics;
 
 use crate::public_key_store::proto_pubkey_store::ProtoPublicKeyStore;
 use crate::public_key_store::PublicKeyStore;
@@ -20,8 +27,12 @@ use crate::secret_key_store::proto_store::ProtoSecretKeyStore;
 use crate::secret_key_store::SecretKeyStore;
 use crate::types::CspSecretKey;
 use crate::vault::api::ThresholdSchnorrCreateSigShareVaultError;
+use crate::vault::local_csp_vault::audit_log::VaultAuditLog;
+use crate::vault::local_csp_vault::vault_metrics::CspVaultMetrics;
 use crate::{CspRwLock, KeyId};
-use ic_crypto_internal_logmon::metrics::CryptoMetrics;
+use ic_crypto_internal_logmon::metrics::{
+    CryptoMetrics, MetricsDomain, MetricsResult, MetricsScope,
+};
 use ic_crypto_internal_seed::Seed;
 use ic_crypto_internal_threshold_sig_canister_threshold_sig::{
     CombinedCommitment, CommitmentOpening,
@@ -30,12 +41,14 @@ use ic_interfaces::time_source::{SysTimeSource, TimeSource};
 use ic_logger::{new_logger, ReplicaLogger};
 use ic_protobuf::registry::crypto::v1::PublicKey;
 use ic_types::crypto::canister_threshold_sig::error::ThresholdEcdsaCreateSigShareError;
-use parking_lot::{RwLockReadGuard, RwLockWriteGuard};
+use ic_types::Time;
+use parking_lot::{Mutex, RwLockReadGuard, RwLockWriteGuard};
 use rand::rngs::OsRng;
 use rand::{CryptoRng, Rng};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Instant;
 
 /// An implementation of `CspVault`-trait that runs in-process
 /// and uses local secret key stores.
@@ -87,6 +100,10 @@ pub struct LocalCspVault<
     time_source: Arc<dyn TimeSource>,
     logger: ReplicaLogger,
     metrics: Arc<CryptoMetrics>,
+    last_successful_operation: Mutex<Option<Time>>,
+    audit_log: Mutex<VaultAuditLog>,
+    key_usage_stats: Mutex<BTreeMap<KeyId, u64>>,
+    vault_metrics: Arc<CspVaultMetrics>,
 }
 
 pub type ProdLocalCspVault =
@@ -141,6 +158,23 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
                 .as_millis_since_unix_epoch(),
         );
     }
+
+    /// Observes the duration of an operation in the metrics and, if the
+    /// operation succeeded, records its time for the vault's health status.
+    fn observe_operation(
+        &self,
+        domain: MetricsDomain,
+        scope: MetricsScope,
+        method_name: &str,
+        result: MetricsResult,
+        start_time: Option<Instant>,
+    ) {
+        if result == MetricsResult::Ok {
+            *self.last_successful_operation.lock() = Some(self.time_source.get_relative_time());
+        }
+        self.metrics
+            .observe_duration_seconds(domain, scope, method_name, result, start_time);
+    }
 }
 
 // CRP-1248: inline the following methods
@@ -7,6 +7,7 @@ use crate::types::{CspPop, CspPublicKey, CspSecretKey, CspSignature, MultiBls12_
 use crate::vault::api::{
     CspMultiSignatureError, CspMultiSignatureKeygenError, MultiSignatureCspVault,
 };
+use crate::vault::local_csp_vault::audit_log::VaultAuditEvent;
 use crate::vault::local_csp_vault::LocalCspVault;
 use ic_crypto_internal_logmon::metrics::{MetricsDomain, MetricsResult, MetricsScope};
 use ic_crypto_internal_multi_sig_bls12381 as multi_bls12381;
@@ -14,6 +15,7 @@ use ic_crypto_node_key_validation::ValidCommitteeSigningPublicKey;
 use ic_protobuf::registry::crypto::v1::PublicKey;
 use ic_types::crypto::{AlgorithmId, CryptoError};
 use rand::{CryptoRng, Rng};
+use std::time::Instant;
 
 #[cfg(test)]
 mod tests;
@@ -28,14 +30,81 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         key_id: KeyId,
     ) -> Result<CspSignature, CspMultiSignatureError> {
         let start_time = self.metrics.now();
+        let signing_start_time = Instant::now();
         let result = self.multi_sign_internal(algorithm_id, &message[..], key_id);
-        self.metrics.observe_duration_seconds(
+        if result.is_ok() {
+            self.record_audit_event(VaultAuditEvent::Signing {
+                algorithm: algorithm_id,
+                key_id,
+            });
+        }
+        self.observe_operation(
             MetricsDomain::MultiSignature,
             MetricsScope::Local,
             "multi_sign",
             MetricsResult::from(&result),
             start_time,
         );
+        self.observe_signing(algorithm_id, "multi_sign", signing_start_time);
+        result
+    }
+
+    fn batch_sign(
+        &self,
+        algorithm_id: AlgorithmId,
+        messages: &[&[u8]],
+        key_id: KeyId,
+    ) -> Vec<Result<CspSignature, CspMultiSignatureError>> {
+        let start_time = self.metrics.now();
+        let signing_start_time = Instant::now();
+        let results = self.batch_sign_internal(algorithm_id, messages, key_id);
+        for _ in results.iter().filter(|result| result.is_ok()) {
+            self.record_audit_event(VaultAuditEvent::Signing {
+                algorithm: algorithm_id,
+                key_id,
+            });
+        }
+        let metrics_result = if results.iter().all(Result::is_ok) {
+            MetricsResult::Ok
+        } else {
+            MetricsResult::Err
+        };
+        self.observe_operation(
+            MetricsDomain::MultiSignature,
+            MetricsScope::Local,
+            "batch_sign",
+            metrics_result,
+            start_time,
+        );
+        self.observe_signing(algorithm_id, "batch_sign", signing_start_time);
+        results
+    }
+
+    fn multi_sign_batch(
+        &self,
+        algorithm_id: AlgorithmId,
+        messages: Vec<Vec<u8>>,
+        key_id: KeyId,
+    ) -> Result<Vec<CspSignature>, CspMultiSignatureError> {
+        let start_time = self.metrics.now();
+        let signing_start_time = Instant::now();
+        let result = self.multi_sign_batch_internal(algorithm_id, &messages, key_id);
+        if let Ok(signatures) = &result {
+            for _ in signatures {
+                self.record_audit_event(VaultAuditEvent::Signing {
+                    algorithm: algorithm_id,
+                    key_id,
+                });
+            }
+        }
+        self.observe_operation(
+            MetricsDomain::MultiSignature,
+            MetricsScope::Local,
+            "multi_sign_batch",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        self.observe_signing(algorithm_id, "multi_sign_batch", signing_start_time);
         result
     }
 
@@ -44,7 +113,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
     ) -> Result<(CspPublicKey, CspPop), CspMultiSignatureKeygenError> {
         let start_time = self.metrics.now();
         let result = self.gen_committee_signing_key_pair_internal();
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::MultiSignature,
             MetricsScope::Local,
             "gen_committee_signing_key_pair",
@@ -62,10 +131,14 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         &self,
     ) -> Result<(CspPublicKey, CspPop), CspMultiSignatureKeygenError> {
         let (secret_key, pk_and_pop) = self.gen_multi_bls12381_keypair_with_pop()?;
-        let key_id = KeyId::try_from(&pk_and_pop.0)?;
+        let key_id = KeyId::from_public_key(&pk_and_pop.0);
         let committee_public_key_proto = committee_signing_pk_to_proto(pk_and_pop.clone());
         let valid_public_key = validate_committee_signing_public_key(committee_public_key_proto)?;
         self.store_committee_signing_key_pair(key_id, secret_key, valid_public_key.get().clone())?;
+        self.record_audit_event(VaultAuditEvent::KeyGeneration {
+            algorithm: AlgorithmId::MultiBls12_381,
+            key_id,
+        });
         Ok(pk_and_pop)
     }
 
@@ -122,30 +195,44 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         message: &[u8],
         key_id: KeyId,
     ) -> Result<CspSignature, CspMultiSignatureError> {
-        let maybe_secret_key = self.sks_read_lock().get(&key_id);
-        let secret_key: CspSecretKey =
-            maybe_secret_key.ok_or(CspMultiSignatureError::SecretKeyNotFound {
-                algorithm: algorithm_id,
-                key_id,
-            })?;
-
-        match algorithm_id {
-            AlgorithmId::MultiBls12_381 => match &secret_key {
-                CspSecretKey::MultiBls12_381(key) => {
-                    let sig = multi_bls12381::sign(message, key);
-                    Ok(CspSignature::MultiBls12_381(
-                        MultiBls12_381_Signature::Individual(sig),
-                    ))
-                }
-                _ => Err(CspMultiSignatureError::WrongSecretKeyType {
-                    algorithm: algorithm_id,
-                    secret_key_variant: secret_key.enum_variant().to_string(),
-                }),
-            },
-            _ => Err(CspMultiSignatureError::UnsupportedAlgorithm {
-                algorithm: algorithm_id,
-            }),
-        }
+        let maybe_secret_key = self.active_node_secret_key(&key_id);
+        multi_sign_with_secret_key(algorithm_id, message, key_id, maybe_secret_key.as_ref())
+    }
+
+    fn batch_sign_internal(
+        &self,
+        algorithm_id: AlgorithmId,
+        messages: &[&[u8]],
+        key_id: KeyId,
+    ) -> Vec<Result<CspSignature, CspMultiSignatureError>> {
+        // The secret key store lock is acquired once for the whole batch.
+        let maybe_secret_key = self.active_node_secret_key(&key_id);
+        messages
+            .iter()
+            .map(|message| {
+                multi_sign_with_secret_key(algorithm_id, message, key_id, maybe_secret_key.as_ref())
+            })
+            .collect()
+    }
+
+    fn multi_sign_batch_internal(
+        &self,
+        algorithm_id: AlgorithmId,
+        messages: &[Vec<u8>],
+        key_id: KeyId,
+    ) -> Result<Vec<CspSignature>, CspMultiSignatureError> {
+        let maybe_secret_key = self.active_node_secret_key(&key_id);
+        messages
+            .iter()
+            .enumerate()
+            .map(|(index, message)| {
+                multi_sign_with_secret_key(algorithm_id, message, key_id, maybe_secret_key.as_ref())
+                    .map_err(|error| CspMultiSignatureError::BatchMessageError {
+                        index,
+                        error: Box::new(error),
+                    })
+            })
+            .collect()
     }
 
     fn gen_multi_bls12381_keypair_with_pop(
@@ -186,3 +273,33 @@ fn validate_committee_signing_public_key(
         }
     })
 }
+
+fn multi_sign_with_secret_key(
+    algorithm_id: AlgorithmId,
+    message: &[u8],
+    key_id: KeyId,
+    maybe_secret_key: Option<&CspSecretKey>,
+) -> Result<CspSignature, CspMultiSignatureError> {
+    let secret_key = maybe_secret_key.ok_or(CspMultiSignatureError::SecretKeyNotFound {
+        algorithm: algorithm_id,
+        key_id,
+    })?;
+
+    match algorithm_id {
+        AlgorithmId::MultiBls12_381 => match secret_key {
+            CspSecretKey::MultiBls12_381(key) => {
+                let sig = multi_bls12381::sign(message, key);
+                Ok(CspSignature::MultiBls12_381(
+                    MultiBls12_381_Signature::Individual(sig),
+                ))
+            }
+            _ => Err(CspMultiSignatureError::WrongSecretKeyType {
+                algorithm: algorithm_id,
+                secret_key_variant: secret_key.enum_variant().to_string(),
+            }),
+        },
+        _ => Err(CspMultiSignatureError::UnsupportedAlgorithm {
+            algorithm: algorithm_id,
+        }),
+    }
+}
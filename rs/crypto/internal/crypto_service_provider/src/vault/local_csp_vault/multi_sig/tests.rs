@@ -253,3 +253,166 @@ fn should_fail_to_multi_sign_if_secret_key_in_store_has_wrong_type() {
         }
     );
 }
+
+#[test]
+fn should_batch_sign_with_same_signatures_as_multi_sign() {
+    let rng = &mut reproducible_rng();
+    let csp_vault = LocalCspVault::builder_for_test()
+        .with_rng(rng.fork())
+        .build();
+    let (csp_pub_key, _csp_pop) = csp_vault
+        .gen_committee_signing_key_pair()
+        .expect("failed to generate keys");
+    let key_id = KeyId::try_from(&csp_pub_key).unwrap();
+    let messages: Vec<Vec<u8>> = (0..5)
+        .map(|_| {
+            let msg_len: usize = rng.gen_range(0..1024);
+            (0..msg_len).map(|_| rng.gen::<u8>()).collect()
+        })
+        .collect();
+    let message_refs: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+
+    let results = csp_vault.batch_sign(AlgorithmId::MultiBls12_381, &message_refs, key_id);
+
+    assert_eq!(results.len(), messages.len());
+    for (result, message) in results.into_iter().zip(messages) {
+        let expected_sig = csp_vault
+            .multi_sign(AlgorithmId::MultiBls12_381, message, key_id)
+            .expect("failed to generate signature");
+        assert_eq!(result, Ok(expected_sig));
+    }
+}
+
+#[test]
+fn should_batch_sign_empty_batch() {
+    let csp_vault = LocalCspVault::builder_for_test().build();
+    let (csp_pub_key, _csp_pop) = csp_vault
+        .gen_committee_signing_key_pair()
+        .expect("failed to generate keys");
+
+    let results = csp_vault.batch_sign(
+        AlgorithmId::MultiBls12_381,
+        &[],
+        KeyId::try_from(&csp_pub_key).unwrap(),
+    );
+
+    assert!(results.is_empty());
+}
+
+#[test]
+fn should_fail_to_batch_sign_every_message_if_secret_key_not_found() {
+    let csp_vault = LocalCspVault::builder_for_test().build();
+    let key_id = KeyId::from([42; 32]);
+
+    let results = csp_vault.batch_sign(
+        AlgorithmId::MultiBls12_381,
+        &[b"message 1".as_slice(), b"message 2".as_slice()],
+        key_id,
+    );
+
+    assert_eq!(
+        results,
+        vec![
+            Err(CspMultiSignatureError::SecretKeyNotFound {
+                algorithm: AlgorithmId::MultiBls12_381,
+                key_id
+            });
+            2
+        ]
+    );
+}
+
+#[test]
+fn should_multi_sign_batch_with_same_signatures_as_multi_sign() {
+    let rng = &mut reproducible_rng();
+    let csp_vault = LocalCspVault::builder_for_test()
+        .with_rng(rng.fork())
+        .build();
+    let (csp_pub_key, _csp_pop) = csp_vault
+        .gen_committee_signing_key_pair()
+        .expect("failed to generate keys");
+    let key_id = KeyId::try_from(&csp_pub_key).unwrap();
+    let messages: Vec<Vec<u8>> = (0..5)
+        .map(|_| {
+            let msg_len: usize = rng.gen_range(0..1024);
+            (0..msg_len).map(|_| rng.gen::<u8>()).collect()
+        })
+        .collect();
+
+    let signatures = csp_vault
+        .multi_sign_batch(AlgorithmId::MultiBls12_381, messages.clone(), key_id)
+        .expect("failed to sign batch");
+
+    let expected_signatures: Vec<_> = messages
+        .into_iter()
+        .map(|message| {
+            csp_vault
+                .multi_sign(AlgorithmId::MultiBls12_381, message, key_id)
+                .expect("failed to generate signature")
+        })
+        .collect();
+    assert_eq!(signatures, expected_signatures);
+}
+
+#[test]
+fn should_multi_sign_empty_batch() {
+    let csp_vault = LocalCspVault::builder_for_test().build();
+    let (csp_pub_key, _csp_pop) = csp_vault
+        .gen_committee_signing_key_pair()
+        .expect("failed to generate keys");
+
+    let result = csp_vault.multi_sign_batch(
+        AlgorithmId::MultiBls12_381,
+        vec![],
+        KeyId::try_from(&csp_pub_key).unwrap(),
+    );
+
+    assert_eq!(result, Ok(vec![]));
+}
+
+#[test]
+fn should_fail_to_multi_sign_batch_with_index_if_secret_key_not_found() {
+    let csp_vault = LocalCspVault::builder_for_test().build();
+    let key_id = KeyId::from([42; 32]);
+
+    let result = csp_vault.multi_sign_batch(
+        AlgorithmId::MultiBls12_381,
+        vec![b"message 1".to_vec(), b"message 2".to_vec()],
+        key_id,
+    );
+
+    assert_eq!(
+        result,
+        Err(CspMultiSignatureError::BatchMessageError {
+            index: 0,
+            error: Box::new(CspMultiSignatureError::SecretKeyNotFound {
+                algorithm: AlgorithmId::MultiBls12_381,
+                key_id
+            }),
+        })
+    );
+}
+
+#[test]
+fn should_fail_to_multi_sign_batch_with_index_if_algorithm_unsupported() {
+    let csp_vault = LocalCspVault::builder_for_test().build();
+    let (csp_pub_key, _csp_pop) = csp_vault
+        .gen_committee_signing_key_pair()
+        .expect("failed to generate keys");
+
+    let result = csp_vault.multi_sign_batch(
+        AlgorithmId::Ed25519,
+        vec![b"message".to_vec()],
+        KeyId::try_from(&csp_pub_key).unwrap(),
+    );
+
+    assert_eq!(
+        result,
+        Err(CspMultiSignatureError::BatchMessageError {
+            index: 0,
+            error: Box::new(CspMultiSignatureError::UnsupportedAlgorithm {
+                algorithm: AlgorithmId::Ed25519,
+            }),
+        })
+    );
+}
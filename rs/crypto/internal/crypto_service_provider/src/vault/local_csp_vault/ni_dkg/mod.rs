@@ -6,6 +6,7 @@ use crate::threshold::ni_dkg::specialise;
 use crate::threshold::ni_dkg::{NIDKG_FS_SCOPE, NIDKG_THRESHOLD_SCOPE};
 use crate::types::{CspPublicCoefficients, CspSecretKey};
 use crate::vault::api::NiDkgCspVault;
+use crate::vault::local_csp_vault::audit_log::VaultAuditEvent;
 use crate::vault::local_csp_vault::LocalCspVault;
 use crate::KeyId;
 use ic_crypto_internal_logmon::metrics::{MetricsDomain, MetricsResult, MetricsScope};
@@ -46,7 +47,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         debug!(self.logger; crypto.method_name => "gen_dealing_encryption_key_pair");
         let start_time = self.metrics.now();
         let result = self.gen_dealing_encryption_key_pair_internal(node_id);
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::NiDkgAlgorithm,
             MetricsScope::Local,
             "gen_dealing_encryption_key_pair",
@@ -66,7 +67,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         let start_time = self.metrics.now();
 
         let result = self.update_forward_secure_epoch_internal(algorithm_id, key_id, epoch);
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::NiDkgAlgorithm,
             MetricsScope::Local,
             "update_forward_secure_epoch",
@@ -95,7 +96,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             &receiver_keys,
             maybe_resharing_secret_key_id,
         );
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::NiDkgAlgorithm,
             MetricsScope::Local,
             "create_dealing",
@@ -105,6 +106,38 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         result
     }
 
+    fn verify_dealings_batch(
+        &self,
+        algorithm_id: AlgorithmId,
+        threshold: NumberOfNodes,
+        epoch: Epoch,
+        receiver_keys: BTreeMap<NodeIndex, CspFsEncryptionPublicKey>,
+        dealings: &[(NodeIndex, CspNiDkgDealing)],
+    ) -> Vec<Result<(), ni_dkg_errors::CspDkgVerifyDealingError>> {
+        debug!(self.logger; crypto.method_name => "verify_dealings_batch", crypto.dkg_epoch => epoch.get());
+        let start_time = self.metrics.now();
+        let results = verify_dealings_batch_internal(
+            algorithm_id,
+            threshold,
+            epoch,
+            &receiver_keys,
+            dealings,
+        );
+        let metrics_result = if results.iter().all(Result::is_ok) {
+            MetricsResult::Ok
+        } else {
+            MetricsResult::Err
+        };
+        self.observe_operation(
+            MetricsDomain::NiDkgAlgorithm,
+            MetricsScope::Local,
+            "verify_dealings_batch",
+            metrics_result,
+            start_time,
+        );
+        results
+    }
+
     fn load_threshold_signing_key(
         &self,
         algorithm_id: AlgorithmId,
@@ -122,7 +155,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             fs_key_id,
             receiver_index,
         );
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::NiDkgAlgorithm,
             MetricsScope::Local,
             "load_threshold_signing_key",
@@ -158,8 +191,11 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             self.sks_write_lock()
                 .retain(filter, NIDKG_THRESHOLD_SCOPE)
                 .unwrap_or_else(|e| panic!("error retaining threshold keys: {}", e));
+            self.record_audit_event(VaultAuditEvent::KeyDeletion {
+                scope: NIDKG_THRESHOLD_SCOPE,
+            });
         }
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::NiDkgAlgorithm,
             MetricsScope::Local,
             "retain_threshold_keys_if_present",
@@ -184,6 +220,10 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         let public_key_proto = dkg_dealing_encryption_pk_to_proto(public_key, pop);
         let valid_public_key = validate_dealing_encryption_public_key(node_id, public_key_proto)?;
         self.store_dealing_encryption_key_pair(key_id, secret_key, valid_public_key.get().clone())?;
+        self.record_audit_event(VaultAuditEvent::KeyGeneration {
+            algorithm: AlgorithmId::Groth20_Bls12_381,
+            key_id,
+        });
         Ok((public_key, pop))
     }
 
@@ -478,6 +518,40 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
     }
 }
 
+/// Verifies each of `dealings`, parsing the receiver keys only once for the
+/// whole batch. Dealings only contain public data, so no key store is
+/// accessed.
+fn verify_dealings_batch_internal(
+    algorithm_id: AlgorithmId,
+    threshold: NumberOfNodes,
+    epoch: Epoch,
+    receiver_keys: &BTreeMap<NodeIndex, CspFsEncryptionPublicKey>,
+    dealings: &[(NodeIndex, CspNiDkgDealing)],
+) -> Vec<Result<(), ni_dkg_errors::CspDkgVerifyDealingError>> {
+    let receiver_keys = match algorithm_id {
+        AlgorithmId::NiDkg_Groth20_Bls12_381 => specialise::groth20::receiver_keys(receiver_keys)
+            .map_err(|(receiver_index, error)| {
+                ni_dkg_errors::CspDkgVerifyDealingError::MalformedFsPublicKeyError {
+                    receiver_index,
+                    error,
+                }
+            }),
+        other => Err(ni_dkg_errors::CspDkgVerifyDealingError::UnsupportedAlgorithmId(other)),
+    };
+    let receiver_keys = match receiver_keys {
+        Ok(receiver_keys) => receiver_keys,
+        Err(error) => return vec![Err(error); dealings.len()],
+    };
+    dealings
+        .iter()
+        .map(|(dealer_index, dealing)| {
+            let dealing = specialise::groth20::dealing(dealing.clone())
+                .map_err(ni_dkg_errors::CspDkgVerifyDealingError::MalformedDealingError)?;
+            ni_dkg_clib::verify_dealing(*dealer_index, threshold, epoch, &receiver_keys, &dealing)
+        })
+        .collect()
+}
+
 fn gen_dealing_encryption_key_pair_from_seed(
     node_id: NodeId,
     seed: Seed,
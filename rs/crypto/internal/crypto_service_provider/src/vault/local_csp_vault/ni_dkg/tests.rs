@@ -480,6 +480,124 @@ fn should_update_forward_secure_key_in_sks_if_epoch_in_sks_was_updated_to_an_old
     );
 }
 
+mod verify_dealings_batch {
+    use super::*;
+    use crate::threshold::ni_dkg::static_api;
+    use crate::vault::test_utils::ni_dkg::fixtures::{
+        MockDkgConfig, StateWithConfig, StateWithDealings,
+    };
+    use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors::CspDkgVerifyDealingError;
+    use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::CspNiDkgDealing;
+    use ic_crypto_internal_types::NodeIndex;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn should_return_results_in_order_of_dealings() {
+        let state = state_with_dealings(&mut reproducible_rng());
+        let config = &state.config;
+        let (dealer_index, valid_dealing) = state.dealings.iter().next().expect("no dealings");
+        let dealings = vec![
+            (*dealer_index, valid_dealing.clone()),
+            (*dealer_index, CspNiDkgDealing::placeholder_to_delete(42)),
+            (*dealer_index, valid_dealing.clone()),
+        ];
+
+        let results = LocalCspVault::builder_for_test()
+            .build()
+            .verify_dealings_batch(
+                config.algorithm_id,
+                config.threshold.get(),
+                config.epoch,
+                config.receiver_keys.clone(),
+                &dealings,
+            );
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], Ok(()));
+        assert_matches!(results[1], Err(_));
+        assert_eq!(results[2], Ok(()));
+    }
+
+    #[test]
+    fn should_return_same_results_as_verifying_each_dealing() {
+        let state = state_with_dealings(&mut reproducible_rng());
+        let config = &state.config;
+        let mut dealings: Vec<(NodeIndex, CspNiDkgDealing)> = state
+            .dealings
+            .iter()
+            .map(|(dealer_index, dealing)| (*dealer_index, dealing.clone()))
+            .collect();
+        dealings.push((0, CspNiDkgDealing::placeholder_to_delete(42)));
+
+        let results = LocalCspVault::builder_for_test()
+            .build()
+            .verify_dealings_batch(
+                config.algorithm_id,
+                config.threshold.get(),
+                config.epoch,
+                config.receiver_keys.clone(),
+                &dealings,
+            );
+
+        let expected_results: Vec<_> = dealings
+            .iter()
+            .map(|(dealer_index, dealing)| {
+                static_api::verify_dealing(
+                    config.algorithm_id,
+                    *dealer_index,
+                    config.threshold.get(),
+                    config.epoch,
+                    config.receiver_keys.clone(),
+                    dealing.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(results, expected_results);
+    }
+
+    #[test]
+    fn should_fail_every_dealing_with_unsupported_algorithm() {
+        let state = state_with_dealings(&mut reproducible_rng());
+        let config = &state.config;
+        let dealings: Vec<(NodeIndex, CspNiDkgDealing)> = state
+            .dealings
+            .iter()
+            .map(|(dealer_index, dealing)| (*dealer_index, dealing.clone()))
+            .collect();
+
+        let results = LocalCspVault::builder_for_test()
+            .build()
+            .verify_dealings_batch(
+                AlgorithmId::Ed25519,
+                config.threshold.get(),
+                config.epoch,
+                config.receiver_keys.clone(),
+                &dealings,
+            );
+
+        assert_eq!(
+            results,
+            vec![
+                Err(CspDkgVerifyDealingError::UnsupportedAlgorithmId(
+                    AlgorithmId::Ed25519
+                ));
+                dealings.len()
+            ]
+        );
+    }
+
+    fn state_with_dealings<R: Rng>(rng: &mut R) -> StateWithDealings {
+        let rng = &mut ChaCha20Rng::from_seed(rng.gen());
+        let network = MockNetwork::random(rng, 3, || {
+            LocalCspVault::builder_for_test().build_into_arc()
+        });
+        let config = MockDkgConfig::from_network(rng, &network, None);
+        StateWithDealings::from_state_with_config(StateWithConfig { network, config })
+            .expect("failed to create dealings")
+    }
+}
+
 fn key_epoch_matches(key_id: KeyId, csp_secret_key: CspSecretKey, epoch: Epoch) -> bool {
     let (_key_set, secret_key) =
         super::specialize_key_set_and_deserialize_secret_key(key_id, Some(csp_secret_key))
@@ -17,7 +17,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
     fn new_public_seed(&self) -> Result<Seed, PublicRandomSeedGeneratorError> {
         let start_time = self.metrics.now();
         let result = Ok(self.generate_seed());
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::PublicSeed,
             MetricsScope::Local,
             "new_public_seed",
@@ -60,7 +60,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             &key_times_lambda,
             algorithm_id,
         );
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::ThresholdEcdsa,
             MetricsScope::Local,
             "ecdsa_sign_share",
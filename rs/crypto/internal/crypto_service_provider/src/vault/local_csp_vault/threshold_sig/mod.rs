@@ -6,14 +6,20 @@ use crate::types::{CspPublicCoefficients, CspSecretKey};
 use crate::types::{CspSignature, ThresBls12_381_Signature};
 use crate::vault::api::CspThresholdSignatureKeygenError;
 use crate::vault::api::ThresholdSignatureCspVault;
+use crate::vault::local_csp_vault::audit_log::VaultAuditEvent;
 use crate::vault::local_csp_vault::LocalCspVault;
 use ic_crypto_internal_logmon::metrics::{MetricsDomain, MetricsResult, MetricsScope};
 use ic_crypto_internal_seed::Seed;
 use ic_crypto_internal_threshold_sig_bls12381 as bls12381_clib;
+use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::CspNiDkgTranscript;
+use ic_types::crypto::threshold_sig::ni_dkg::NiDkgTranscript;
 use ic_types::crypto::AlgorithmId;
 use ic_types::crypto::CryptoError;
+use ic_types::NodeId;
 use rand::{CryptoRng, Rng};
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
+use std::time::Instant;
 
 #[cfg(test)]
 pub(crate) mod tests;
@@ -128,14 +134,39 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         key_id: KeyId,
     ) -> Result<CspSignature, CspThresholdSignError> {
         let start_time = self.metrics.now();
+        let signing_start_time = Instant::now();
         let result = self.threshold_sign_internal(algorithm_id, &message[..], key_id);
-        self.metrics.observe_duration_seconds(
+        if result.is_ok() {
+            self.record_audit_event(VaultAuditEvent::Signing {
+                algorithm: algorithm_id,
+                key_id,
+            });
+        }
+        self.observe_operation(
             MetricsDomain::ThresholdSignature,
             MetricsScope::Local,
             "threshold_sign",
             MetricsResult::from(&result),
             start_time,
         );
+        self.observe_signing(algorithm_id, "threshold_sign", signing_start_time);
+        result
+    }
+
+    fn combine_threshold_sig_shares(
+        &self,
+        shares: &BTreeMap<NodeId, CspSignature>,
+        transcript: &NiDkgTranscript,
+    ) -> Result<CspSignature, CspThresholdSignError> {
+        let start_time = self.metrics.now();
+        let result = combine_threshold_sig_shares_internal(shares, transcript);
+        self.observe_operation(
+            MetricsDomain::ThresholdSignature,
+            MetricsScope::Local,
+            "combine_threshold_sig_shares",
+            MetricsResult::from(&result),
+            start_time,
+        );
         result
     }
 }
@@ -149,9 +180,9 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         message: &[u8],
         key_id: KeyId,
     ) -> Result<CspSignature, CspThresholdSignError> {
-        let result = match algorithm_id {
+        match algorithm_id {
             AlgorithmId::ThresBls12_381 => {
-                let maybe_csp_key = self.sks_read_lock().get(&key_id);
+                let maybe_csp_key = self.active_node_secret_key(&key_id);
                 let csp_key = maybe_csp_key.ok_or({
                     CspThresholdSignError::SecretKeyNotFound {
                         algorithm: AlgorithmId::ThresBls12_381,
@@ -168,7 +199,45 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             _ => Err(CspThresholdSignError::UnsupportedAlgorithm {
                 algorithm: algorithm_id,
             }),
-        };
-        result
+        }
+    }
+}
+
+fn combine_threshold_sig_shares_internal(
+    shares: &BTreeMap<NodeId, CspSignature>,
+    transcript: &NiDkgTranscript,
+) -> Result<CspSignature, CspThresholdSignError> {
+    match &transcript.internal_csp_transcript {
+        CspNiDkgTranscript::Groth20_Bls12_381(_) => {
+            // The share of the i'th committee member must be in the i'th place.
+            let mut clib_shares: Vec<Option<bls12381_clib::types::IndividualSignatureBytes>> =
+                vec![None; transcript.committee.count().get() as usize];
+            for (node_id, share) in shares {
+                let index = transcript.committee.position(*node_id).ok_or_else(|| {
+                    CspThresholdSignError::InvalidArgument {
+                        message: format!(
+                            "Node {} is not in the committee of the NI-DKG transcript {}",
+                            node_id, transcript.dkg_id
+                        ),
+                    }
+                })?;
+                let clib_share =
+                    bls12381_clib::types::IndividualSignatureBytes::try_from(share.clone())
+                        .map_err(invalid_argument_from_crypto_error)?;
+                clib_shares[index as usize] = Some(clib_share);
+            }
+            let clib_combined_signature =
+                bls12381_clib::api::combine_signatures(&clib_shares, transcript.threshold.get())
+                    .map_err(invalid_argument_from_crypto_error)?;
+            Ok(CspSignature::ThresBls12_381(
+                ThresBls12_381_Signature::Combined(clib_combined_signature),
+            ))
+        }
+    }
+}
+
+fn invalid_argument_from_crypto_error(crypto_error: CryptoError) -> CspThresholdSignError {
+    CspThresholdSignError::InvalidArgument {
+        message: crypto_error.to_string(),
     }
 }
@@ -24,3 +24,188 @@ proptest! {
         test_utils::threshold_sig::test_threshold_scheme_with_basic_keygen(Seed::from_rng(rng), csp_vault, &message);
     }
 }
+
+mod combine_threshold_sig_shares {
+    use crate::api::CspThresholdSignError;
+    use crate::key_id::KeyId;
+    use crate::public_key_store::temp_pubkey_store::TempPublicKeyStore;
+    use crate::secret_key_store::temp_secret_key_store::TempSecretKeyStore;
+    use crate::types::{CspPublicCoefficients, CspSignature, ThresBls12_381_Signature};
+    use crate::vault::api::ThresholdSignatureCspVault;
+    use crate::vault::local_csp_vault::LocalCspVault;
+    use assert_matches::assert_matches;
+    use ic_crypto_internal_threshold_sig_bls12381 as bls12381_clib;
+    use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::ni_dkg_groth20_bls12_381::Transcript;
+    use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::CspNiDkgTranscript;
+    use ic_crypto_test_utils_reproducible_rng::ReproducibleRng;
+    use ic_types::crypto::threshold_sig::ni_dkg::config::NiDkgThreshold;
+    use ic_types::crypto::threshold_sig::ni_dkg::{
+        NiDkgId, NiDkgReceivers, NiDkgTag, NiDkgTargetSubnet, NiDkgTranscript,
+    };
+    use ic_types::crypto::AlgorithmId;
+    use ic_types::{Height, NodeId, NumberOfNodes, PrincipalId, RegistryVersion, SubnetId};
+    use std::collections::BTreeMap;
+
+    const MESSAGE: &[u8] = b"message to be signed";
+
+    type LocalCspVaultForTest =
+        LocalCspVault<ReproducibleRng, TempSecretKeyStore, TempSecretKeyStore, TempPublicKeyStore>;
+
+    #[test]
+    fn should_combine_shares_into_valid_signature() {
+        let setup = Setup::new(2, 3);
+        let shares = setup.shares_of(&[0, 2]);
+
+        let combined = setup
+            .csp_vault
+            .combine_threshold_sig_shares(&shares, &setup.transcript)
+            .expect("failed to combine shares");
+
+        let combined_bytes = match combined {
+            CspSignature::ThresBls12_381(ThresBls12_381_Signature::Combined(bytes)) => bytes,
+            other => panic!(
+                "expected a combined threshold signature but got {:?}",
+                other
+            ),
+        };
+        let public_key = match setup.public_coefficients {
+            CspPublicCoefficients::Bls12_381(coefficients) => coefficients.coefficients[0],
+        };
+        assert_eq!(
+            bls12381_clib::api::verify_combined_signature(MESSAGE, combined_bytes, public_key),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn should_combine_any_sufficient_subset_of_shares_into_same_signature() {
+        let setup = Setup::new(2, 3);
+
+        let combined_from_first_shares = setup
+            .csp_vault
+            .combine_threshold_sig_shares(&setup.shares_of(&[0, 1]), &setup.transcript);
+        let combined_from_all_shares = setup
+            .csp_vault
+            .combine_threshold_sig_shares(&setup.shares_of(&[0, 1, 2]), &setup.transcript);
+
+        assert!(combined_from_first_shares.is_ok());
+        assert_eq!(combined_from_first_shares, combined_from_all_shares);
+    }
+
+    #[test]
+    fn should_fail_if_there_are_fewer_shares_than_the_threshold() {
+        let setup = Setup::new(2, 3);
+        let shares = setup.shares_of(&[1]);
+
+        let result = setup
+            .csp_vault
+            .combine_threshold_sig_shares(&shares, &setup.transcript);
+
+        assert_matches!(
+            result,
+            Err(CspThresholdSignError::InvalidArgument { message })
+                if message.contains("Threshold too high")
+        );
+    }
+
+    #[test]
+    fn should_fail_if_share_is_from_node_not_in_committee() {
+        let setup = Setup::new(2, 3);
+        let mut shares = setup.shares_of(&[0, 1]);
+        let (_, share) = shares.pop_first().expect("shares are not empty");
+        shares.insert(node_id(42), share);
+
+        let result = setup
+            .csp_vault
+            .combine_threshold_sig_shares(&shares, &setup.transcript);
+
+        assert_matches!(
+            result,
+            Err(CspThresholdSignError::InvalidArgument { message })
+                if message.contains("is not in the committee")
+        );
+    }
+
+    #[test]
+    fn should_fail_if_share_is_not_an_individual_threshold_signature() {
+        let setup = Setup::new(2, 3);
+        let mut shares = setup.shares_of(&[0, 1]);
+        let (node_id, _) = shares.pop_first().expect("shares are not empty");
+        shares.insert(node_id, CspSignature::RsaSha256(vec![1, 2, 3]));
+
+        let result = setup
+            .csp_vault
+            .combine_threshold_sig_shares(&shares, &setup.transcript);
+
+        assert_matches!(result, Err(CspThresholdSignError::InvalidArgument { .. }));
+    }
+
+    struct Setup {
+        csp_vault: LocalCspVaultForTest,
+        public_coefficients: CspPublicCoefficients,
+        transcript: NiDkgTranscript,
+        signers: Vec<(NodeId, KeyId)>,
+    }
+
+    impl Setup {
+        fn new(threshold: u32, receivers: u32) -> Self {
+            let csp_vault = LocalCspVault::builder_for_test().build();
+            let (public_coefficients, key_ids) = csp_vault
+                .threshold_keygen_for_test(
+                    AlgorithmId::ThresBls12_381,
+                    NumberOfNodes::from(threshold),
+                    NumberOfNodes::from(receivers),
+                )
+                .expect("failed to generate threshold keys");
+            let committee =
+                NiDkgReceivers::new((0..receivers).map(|i| node_id(u64::from(i))).collect())
+                    .expect("failed to create committee");
+            let signers = committee
+                .iter()
+                .map(|(index, node_id)| (node_id, key_ids[index as usize]))
+                .collect();
+            let transcript = NiDkgTranscript {
+                dkg_id: NiDkgId {
+                    start_block_height: Height::from(0),
+                    dealer_subnet: SubnetId::from(PrincipalId::new_subnet_test_id(0)),
+                    dkg_tag: NiDkgTag::HighThreshold,
+                    target_subnet: NiDkgTargetSubnet::Local,
+                },
+                threshold: NiDkgThreshold::new(NumberOfNodes::from(threshold))
+                    .expect("failed to create threshold"),
+                committee,
+                registry_version: RegistryVersion::from(1),
+                internal_csp_transcript: CspNiDkgTranscript::Groth20_Bls12_381(Transcript {
+                    public_coefficients: match public_coefficients.clone() {
+                        CspPublicCoefficients::Bls12_381(coefficients) => coefficients,
+                    },
+                    receiver_data: BTreeMap::new(),
+                }),
+            };
+            Self {
+                csp_vault,
+                public_coefficients,
+                transcript,
+                signers,
+            }
+        }
+
+        fn shares_of(&self, indices: &[usize]) -> BTreeMap<NodeId, CspSignature> {
+            indices
+                .iter()
+                .map(|index| {
+                    let (node_id, key_id) = self.signers[*index];
+                    let share = self
+                        .csp_vault
+                        .threshold_sign(AlgorithmId::ThresBls12_381, MESSAGE.to_vec(), key_id)
+                        .expect("failed to sign");
+                    (node_id, share)
+                })
+                .collect()
+        }
+    }
+
+    fn node_id(id: u64) -> NodeId {
+        NodeId::from(PrincipalId::new_node_test_id(id))
+    }
+}
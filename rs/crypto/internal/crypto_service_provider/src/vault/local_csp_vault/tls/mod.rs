@@ -4,6 +4,7 @@ use crate::public_key_store::{PublicKeySetOnceError, PublicKeyStore};
 use crate::secret_key_store::{SecretKeyStore, SecretKeyStoreInsertionError};
 use crate::types::{CspSecretKey, CspSignature};
 use crate::vault::api::{CspTlsKeygenError, CspTlsSignError, TlsHandshakeCspVault};
+use crate::vault::local_csp_vault::audit_log::VaultAuditEvent;
 use crate::vault::local_csp_vault::LocalCspVault;
 use ic_crypto_internal_logmon::metrics::{MetricsDomain, MetricsResult, MetricsScope};
 use ic_crypto_internal_tls::{generate_tls_key_pair_der, TlsKeyPairAndCertGenerationError};
@@ -13,7 +14,7 @@ use ic_protobuf::registry::crypto::v1::X509PublicKeyCert;
 use ic_types::crypto::AlgorithmId;
 use ic_types::{NodeId, Time};
 use rand::{CryptoRng, Rng};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use time::macros::datetime;
 
 #[cfg(test)]
@@ -27,8 +28,8 @@ impl<R: Rng + CryptoRng + Send + Sync, S: SecretKeyStore, C: SecretKeyStore, P:
 {
     fn gen_tls_key_pair(&self, node: NodeId) -> Result<TlsPublicKeyCert, CspTlsKeygenError> {
         let start_time = self.metrics.now();
-        let result = self.gen_tls_key_pair_internal(node);
-        self.metrics.observe_duration_seconds(
+        let result = self.gen_tls_key_pair_internal(node, None);
+        self.observe_operation(
             MetricsDomain::TlsHandshake,
             MetricsScope::Local,
             "gen_tls_key_pair",
@@ -38,16 +39,41 @@ impl<R: Rng + CryptoRng + Send + Sync, S: SecretKeyStore, C: SecretKeyStore, P:
         result
     }
 
+    fn gen_tls_key_pair_with_validity(
+        &self,
+        node: NodeId,
+        not_after: Time,
+    ) -> Result<TlsPublicKeyCert, CspTlsKeygenError> {
+        let start_time = self.metrics.now();
+        let result = self.gen_tls_key_pair_internal(node, Some(not_after));
+        self.observe_operation(
+            MetricsDomain::TlsHandshake,
+            MetricsScope::Local,
+            "gen_tls_key_pair_with_validity",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        result
+    }
+
     fn tls_sign(&self, message: Vec<u8>, key_id: KeyId) -> Result<CspSignature, CspTlsSignError> {
         let start_time = self.metrics.now();
+        let signing_start_time = Instant::now();
         let result = self.tls_sign_internal(&message[..], &key_id);
-        self.metrics.observe_duration_seconds(
+        if result.is_ok() {
+            self.record_audit_event(VaultAuditEvent::Signing {
+                algorithm: AlgorithmId::Ed25519,
+                key_id,
+            });
+        }
+        self.observe_operation(
             MetricsDomain::TlsHandshake,
             MetricsScope::Local,
             "tls_sign",
             MetricsResult::from(&result),
             start_time,
         );
+        self.observe_signing(AlgorithmId::Ed25519, "tls_sign", signing_start_time);
         result
     }
 }
@@ -55,9 +81,12 @@ impl<R: Rng + CryptoRng + Send + Sync, S: SecretKeyStore, C: SecretKeyStore, P:
 impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore>
     LocalCspVault<R, S, C, P>
 {
+    /// Generates the TLS key material. The certificate's notAfter date is
+    /// `not_after`, or 99991231235959Z if `None`.
     fn gen_tls_key_pair_internal(
         &self,
         node: NodeId,
+        not_after: Option<Time>,
     ) -> Result<TlsPublicKeyCert, CspTlsKeygenError> {
         const TWO_MINUTES: Duration = Duration::from_secs(120);
 
@@ -72,7 +101,10 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             &mut *self.rng_write_lock(),
             common_name,
             issuance_time.as_secs_since_unix_epoch(),
-            RFC5280_NO_WELL_DEFINED_CERTIFICATE_EXPIRATION_DATE as u64,
+            not_after.map_or(
+                RFC5280_NO_WELL_DEFINED_CERTIFICATE_EXPIRATION_DATE as u64,
+                |not_after| not_after.as_secs_since_unix_epoch(),
+            ),
         )?;
         let x509_pk_cert = TlsPublicKeyCert::new_from_der(cert.bytes).map_err(|err| {
             CspTlsKeygenError::InternalError {
@@ -89,8 +121,12 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             })?;
         let secret_key = CspSecretKey::TlsEd25519(secret_key);
         let cert_proto = x509_pk_cert.to_proto();
-        let valid_cert = validate_tls_certificate(cert_proto, node, issuance_time)?;
+        let valid_cert = validate_tls_certificate(cert_proto, node, issuance_time, not_after)?;
         self.store_tls_key_pair(key_id, secret_key, valid_cert.get().clone())?;
+        self.record_audit_event(VaultAuditEvent::KeyGeneration {
+            algorithm: AlgorithmId::Tls,
+            key_id,
+        });
 
         Ok(x509_pk_cert)
     }
@@ -149,7 +185,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         message: &[u8],
         key_id: &KeyId,
     ) -> Result<CspSignature, CspTlsSignError> {
-        let maybe_secret_key = self.sks_read_lock().get(key_id);
+        let maybe_secret_key = self.active_node_secret_key(key_id);
         let secret_key: CspSecretKey =
             maybe_secret_key.ok_or(CspTlsSignError::SecretKeyNotFound { key_id: *key_id })?;
 
@@ -184,11 +220,16 @@ fn validate_tls_certificate(
     cert_proto: X509PublicKeyCert,
     node: NodeId,
     current_time: Time,
+    not_after: Option<Time>,
 ) -> Result<ValidTlsCertificate, CspTlsKeygenError> {
-    ValidTlsCertificate::try_from((cert_proto, node, current_time)).map_err(|error| {
-        CspTlsKeygenError::InternalError {
-            internal_error: format!("TLS certificate validation error: {}", error),
+    match not_after {
+        None => ValidTlsCertificate::try_from((cert_proto, node, current_time)),
+        Some(not_after) => {
+            ValidTlsCertificate::try_from_with_not_after(cert_proto, node, current_time, not_after)
         }
+    }
+    .map_err(|error| CspTlsKeygenError::InternalError {
+        internal_error: format!("TLS certificate validation error: {}", error),
     })
 }
 
@@ -234,6 +234,69 @@ mod keygen {
         assert_eq!(x509(&cert).validity().not_after.timestamp(), not_after_unix);
     }
 
+    #[test]
+    fn should_set_cert_not_after_to_given_time() {
+        let time_source = FastForwardTimeSource::new();
+        time_source.advance_time(Duration::from_secs(1_000_000));
+        let not_after = time_source.get_relative_time() + Duration::from_secs(3_600);
+        let csp_vault = LocalCspVault::builder_for_test()
+            .with_time_source(time_source)
+            .build();
+
+        let cert = csp_vault
+            .gen_tls_key_pair_with_validity(node_test_id(NODE_1), not_after)
+            .expect("Generation of TLS keys failed.");
+
+        assert_eq!(
+            x509(&cert).validity().not_after.timestamp(),
+            not_after.as_secs_since_unix_epoch() as i64
+        );
+    }
+
+    #[test]
+    fn should_store_certificate_with_given_not_after() {
+        let time_source = FastForwardTimeSource::new();
+        time_source.advance_time(Duration::from_secs(1_000_000));
+        let not_after = time_source
+            .get_relative_time()
+            .saturating_sub(Duration::from_secs(1));
+        let csp_vault = LocalCspVault::builder_for_test()
+            .with_time_source(time_source)
+            .build();
+
+        let cert = csp_vault
+            .gen_tls_key_pair_with_validity(node_test_id(NODE_1), not_after)
+            .expect("Generation of TLS keys failed.");
+
+        assert_eq!(
+            csp_vault
+                .current_node_public_keys()
+                .expect("missing public keys")
+                .tls_certificate
+                .expect("missing tls certificate"),
+            cert.to_proto()
+        );
+    }
+
+    #[test]
+    fn should_fail_if_not_after_is_not_after_not_before() {
+        const MORE_THAN_GRACE_PERIOD: Duration = Duration::from_secs(121);
+        let time_source = FastForwardTimeSource::new();
+        time_source.advance_time(Duration::from_secs(1_000_000));
+        let not_after = time_source
+            .get_relative_time()
+            .saturating_sub(MORE_THAN_GRACE_PERIOD);
+        let csp_vault = LocalCspVault::builder_for_test()
+            .with_time_source(time_source)
+            .build();
+
+        let result = csp_vault.gen_tls_key_pair_with_validity(node_test_id(NODE_1), not_after);
+
+        assert_matches!(result, Err(CspTlsKeygenError::InvalidArguments { message })
+            if message.contains("must be before notAfter date")
+        );
+    }
+
     proptest! {
         #[test]
         fn should_pass_the_correct_time_and_date(secs in 0..i64::MAX / NANOS_PER_SEC) {
@@ -425,16 +488,20 @@ mod sign {
     use super::*;
     use crate::api::CspSigner;
     use crate::key_id::KeyId;
+    use crate::types::CspSignature;
     use crate::vault::api::BasicSignatureCspVault;
     use crate::vault::api::CspTlsSignError;
     use crate::vault::api::SecretKeyStoreCspVault;
     use crate::vault::api::TlsHandshakeCspVault;
     use crate::vault::test_utils::ed25519_csp_pubkey_from_tls_pubkey_cert;
     use crate::Csp;
+    use ic_crypto_node_key_validation::{verify_tls_signature, TlsSignatureVerificationError};
     use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+    use ic_interfaces::time_source::TimeSource;
     use ic_types::crypto::AlgorithmId;
     use rand::{CryptoRng, Rng, SeedableRng};
     use rand_chacha::ChaCha20Rng;
+    use std::time::Duration;
 
     #[test]
     fn should_sign_with_valid_key() {
@@ -551,6 +618,43 @@ mod sign {
         );
     }
 
+    #[test]
+    fn should_fail_to_verify_signature_if_certificate_has_expired() {
+        let rng = &mut reproducible_rng();
+        let time_source = FastForwardTimeSource::new();
+        time_source.advance_time(Duration::from_secs(1_000_000));
+        let now = time_source.get_relative_time();
+        let one_second_ago = now.saturating_sub(Duration::from_secs(1));
+        let csp_vault = LocalCspVault::builder_for_test()
+            .with_rng(ChaCha20Rng::from_seed(rng.gen()))
+            .with_time_source(time_source)
+            .build();
+        let public_key_cert = csp_vault
+            .gen_tls_key_pair_with_validity(node_test_id(NODE_1), one_second_ago)
+            .expect("Generation of TLS keys failed.");
+        let msg = random_message(rng);
+        let sig = csp_vault
+            .tls_sign(
+                msg.clone(),
+                KeyId::try_from(&public_key_cert).expect("cannot instantiate KeyId"),
+            )
+            .expect("failed to generate signature");
+        let sig_bytes = match sig {
+            CspSignature::Ed25519(sig_bytes) => sig_bytes,
+            _ => panic!("expected an Ed25519 signature"),
+        };
+
+        let result = verify_tls_signature(&sig_bytes, &msg, &public_key_cert.to_proto(), now);
+
+        assert_eq!(
+            result,
+            Err(TlsSignatureVerificationError::CertificateExpired {
+                not_after_secs_since_unix_epoch: one_second_ago.as_secs_since_unix_epoch() as i64,
+                current_time: now,
+            })
+        );
+    }
+
     fn random_message<R: Rng + CryptoRng>(rng: &mut R) -> Vec<u8> {
         let msg_len: usize = rng.gen_range(0..1024);
         (0..msg_len).map(|_| rng.gen::<u8>()).collect()
@@ -84,7 +84,7 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             )),
         };
 
-        self.metrics.observe_duration_seconds(
+        self.observe_operation(
             MetricsDomain::ThresholdSchnorr,
             MetricsScope::Local,
             "create_schnorr_sig_share",
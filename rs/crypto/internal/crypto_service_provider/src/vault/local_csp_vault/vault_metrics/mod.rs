@@ -0,0 +1,89 @@
+//! Prometheus metrics of the signing operations of the CSP vault, to be
+//! embedded in a Prometheus scrape endpoint.
+use crate::public_key_store::PublicKeyStore;
+use crate::secret_key_store::SecretKeyStore;
+use crate::vault::local_csp_vault::LocalCspVault;
+use ic_metrics::buckets::decimal_buckets;
+use ic_metrics::MetricsRegistry;
+use ic_types::crypto::AlgorithmId;
+use prometheus::proto::MetricFamily;
+use prometheus::{HistogramVec, IntCounterVec};
+use rand::{CryptoRng, Rng};
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+mod tests;
+
+/// Metrics of the signing operations of a CSP vault, kept in a registry of
+/// their own.
+pub struct CspVaultMetrics {
+    registry: MetricsRegistry,
+    signing_operations: IntCounterVec,
+    operation_duration_seconds: HistogramVec,
+}
+
+impl CspVaultMetrics {
+    pub fn new() -> Self {
+        let registry = MetricsRegistry::new();
+        let signing_operations = registry.int_counter_vec(
+            "csp_vault_signing_operations_total",
+            "Number of signing operations performed by the CSP vault, by algorithm.",
+            &["algorithm"],
+        );
+        let operation_duration_seconds = registry.histogram_vec(
+            "csp_vault_operation_duration_seconds",
+            "Duration of the signing operations performed by the CSP vault, in seconds.",
+            // 10μs, 20μs, 50μs, ..., 1s, 2s, 5s
+            decimal_buckets(-5, 0),
+            &["operation", "algorithm"],
+        );
+        Self {
+            registry,
+            signing_operations,
+            operation_duration_seconds,
+        }
+    }
+
+    /// Records a signing `operation` with `algorithm` that took `duration`,
+    /// whether it succeeded or not.
+    pub fn observe_signing(&self, algorithm: AlgorithmId, operation: &str, duration: Duration) {
+        let algorithm = algorithm.to_string();
+        self.signing_operations
+            .with_label_values(&[&algorithm])
+            .inc();
+        self.operation_duration_seconds
+            .with_label_values(&[operation, &algorithm])
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Returns the current values of all metrics.
+    pub fn gather(&self) -> Vec<MetricFamily> {
+        self.registry.prometheus_registry().gather()
+    }
+}
+
+impl Default for CspVaultMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore>
+    LocalCspVault<R, S, C, P>
+{
+    pub fn vault_metrics(&self) -> &CspVaultMetrics {
+        &self.vault_metrics
+    }
+
+    /// Records a signing `operation` that started at `start_time` in the
+    /// vault metrics.
+    pub(crate) fn observe_signing(
+        &self,
+        algorithm: AlgorithmId,
+        operation: &str,
+        start_time: Instant,
+    ) {
+        self.vault_metrics
+            .observe_signing(algorithm, operation, start_time.elapsed());
+    }
+}
@@ -0,0 +1,128 @@
+use super::*;
+use crate::vault::api::{BasicSignatureCspVault, MultiSignatureCspVault};
+use crate::KeyId;
+use prometheus::proto::Metric;
+use std::sync::Arc;
+
+const SIGNING_OPERATIONS: &str = "csp_vault_signing_operations_total";
+const OPERATION_DURATION_SECONDS: &str = "csp_vault_operation_duration_seconds";
+
+#[test]
+fn should_record_every_signing_with_its_algorithm() {
+    let vault = LocalCspVault::builder_for_test().build();
+    let key_id = KeyId::from_public_key(
+        &vault
+            .gen_node_signing_key_pair()
+            .expect("failed to generate node signing key pair"),
+    );
+
+    for _ in 0..10 {
+        vault
+            .sign(AlgorithmId::Ed25519, b"message".to_vec(), key_id)
+            .expect("failed to sign");
+    }
+
+    let metrics = vault.vault_metrics().gather();
+    let counter = find_metric(&metrics, SIGNING_OPERATIONS, &[("algorithm", "ed25519")]);
+    assert_eq!(counter.get_counter().get_value(), 10.0);
+    let histogram = find_metric(
+        &metrics,
+        OPERATION_DURATION_SECONDS,
+        &[("operation", "sign"), ("algorithm", "ed25519")],
+    );
+    assert_eq!(histogram.get_histogram().get_sample_count(), 10);
+}
+
+#[test]
+fn should_record_signings_per_algorithm() {
+    let vault = LocalCspVault::builder_for_test().build();
+    let node_signing_key_id = KeyId::from_public_key(
+        &vault
+            .gen_node_signing_key_pair()
+            .expect("failed to generate node signing key pair"),
+    );
+    let (committee_signing_public_key, _pop) = vault
+        .gen_committee_signing_key_pair()
+        .expect("failed to generate committee signing key pair");
+    let committee_signing_key_id = KeyId::from_public_key(&committee_signing_public_key);
+
+    vault
+        .sign(
+            AlgorithmId::Ed25519,
+            b"message".to_vec(),
+            node_signing_key_id,
+        )
+        .expect("failed to sign");
+    for _ in 0..2 {
+        vault
+            .multi_sign(
+                AlgorithmId::MultiBls12_381,
+                b"message".to_vec(),
+                committee_signing_key_id,
+            )
+            .expect("failed to multi-sign");
+    }
+
+    let metrics = vault.vault_metrics().gather();
+    let ed25519 = find_metric(&metrics, SIGNING_OPERATIONS, &[("algorithm", "ed25519")]);
+    assert_eq!(ed25519.get_counter().get_value(), 1.0);
+    let multi_bls = find_metric(
+        &metrics,
+        SIGNING_OPERATIONS,
+        &[("algorithm", "multi_bls12_381")],
+    );
+    assert_eq!(multi_bls.get_counter().get_value(), 2.0);
+}
+
+#[test]
+fn should_record_failed_signing() {
+    let vault = LocalCspVault::builder_for_test().build();
+    let unknown_key_id = KeyId::from([42; 32]);
+
+    assert!(vault
+        .sign(AlgorithmId::Ed25519, b"message".to_vec(), unknown_key_id)
+        .is_err());
+
+    let metrics = vault.vault_metrics().gather();
+    let counter = find_metric(&metrics, SIGNING_OPERATIONS, &[("algorithm", "ed25519")]);
+    assert_eq!(counter.get_counter().get_value(), 1.0);
+}
+
+#[test]
+fn should_share_metrics_set_in_builder() {
+    let vault_metrics = Arc::new(CspVaultMetrics::new());
+    let vault = LocalCspVault::builder_for_test()
+        .with_vault_metrics(Arc::clone(&vault_metrics))
+        .build();
+    let key_id = KeyId::from_public_key(
+        &vault
+            .gen_node_signing_key_pair()
+            .expect("failed to generate node signing key pair"),
+    );
+
+    vault
+        .sign(AlgorithmId::Ed25519, b"message".to_vec(), key_id)
+        .expect("failed to sign");
+
+    let metrics = vault_metrics.gather();
+    let counter = find_metric(&metrics, SIGNING_OPERATIONS, &[("algorithm", "ed25519")]);
+    assert_eq!(counter.get_counter().get_value(), 1.0);
+}
+
+fn find_metric<'a>(metrics: &'a [MetricFamily], name: &str, labels: &[(&str, &str)]) -> &'a Metric {
+    let family = metrics
+        .iter()
+        .find(|family| family.get_name() == name)
+        .unwrap_or_else(|| panic!("missing metric family {name}"));
+    family
+        .get_metric()
+        .iter()
+        .find(|metric| {
+            labels.iter().all(|(label_name, label_value)| {
+                metric.get_label().iter().any(|label| {
+                    label.get_name() == *label_name && label.get_value() == *label_value
+                })
+            })
+        })
+        .unwrap_or_else(|| panic!("missing metric {name} with labels {labels:?}"))
+}
@@ -126,6 +126,11 @@ impl From<CspBasicSignatureError> for CryptoError {
             CspBasicSignatureError::TransientInternalError { internal_error } => {
                 CryptoError::TransientInternalError { internal_error }
             }
+            CspBasicSignatureError::Timeout { method, elapsed } => {
+                CryptoError::TransientInternalError {
+                    internal_error: format!("RPC call {method} timed out after {elapsed:?}"),
+                }
+            }
         }
     }
 }
@@ -156,6 +161,15 @@ impl From<CspMultiSignatureError> for CryptoError {
             CspMultiSignatureError::TransientInternalError { internal_error } => {
                 CryptoError::TransientInternalError { internal_error }
             }
+            CspMultiSignatureError::Timeout { method, elapsed } => {
+                CryptoError::TransientInternalError {
+                    internal_error: format!("RPC call {method} timed out after {elapsed:?}"),
+                }
+            }
+            // `CryptoError` has no notion of a batch, so only the cause is kept.
+            CspMultiSignatureError::BatchMessageError { index: _, error } => {
+                CryptoError::from(*error)
+            }
         }
     }
 }
@@ -166,6 +180,11 @@ impl From<CspSecretKeyStoreContainsError> for CryptoError {
             CspSecretKeyStoreContainsError::TransientInternalError { internal_error } => {
                 CryptoError::TransientInternalError { internal_error }
             }
+            CspSecretKeyStoreContainsError::Timeout { method, elapsed } => {
+                CryptoError::TransientInternalError {
+                    internal_error: format!("RPC call {method} timed out after {elapsed:?}"),
+                }
+            }
         }
     }
 }
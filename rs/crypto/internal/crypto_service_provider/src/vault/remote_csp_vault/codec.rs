@@ -289,12 +289,17 @@ impl DeserializationObserver<ClientMessage<TarpcCspVaultRequest>> for CspVaultOb
 enum CspVaultMethod {
     Sign,
     GenNodeSigningKeyPair,
+    GenBasicSignatureKeyPair,
     MultiSign,
+    BatchSign,
+    MultiSignBatch,
     GenCommitteeSigningKeyPair,
     ThresholdSign,
+    CombineThresholdSigShares,
     GenDealingEncryptionKeyPair,
     UpdateForwardSecureEpoch,
     CreateDealing,
+    VerifyDealingsBatch,
     LoadThresholdSigningKey,
     RetainThresholdKeysIfPresent,
     SksContains,
@@ -304,6 +309,7 @@ enum CspVaultMethod {
     CurrentNodePublicKeysWithTimestamps,
     IdkgKeyCount,
     GenTlsKeyPair,
+    GenTlsKeyPairWithValidity,
     TlsSign,
     IdkgCreateDealing,
     IdkgVerifyDealingPrivate,
@@ -315,6 +321,9 @@ enum CspVaultMethod {
     CreateEcdsaSigShare,
     CreateSchnorrSigShare,
     NewPublicSeed,
+    HealthStatus,
+    MarkKeyInactive,
+    MarkKeyActive,
 }
 
 impl CspVaultMethod {
@@ -324,12 +333,22 @@ impl CspVaultMethod {
             CspVaultMethod::GenNodeSigningKeyPair => {
                 (MetricsDomain::BasicSignature, "gen_node_signing_key_pair")
             }
+            CspVaultMethod::GenBasicSignatureKeyPair => (
+                MetricsDomain::BasicSignature,
+                "gen_basic_signature_key_pair",
+            ),
             CspVaultMethod::MultiSign => (MetricsDomain::MultiSignature, "multi_sign"),
+            CspVaultMethod::BatchSign => (MetricsDomain::MultiSignature, "batch_sign"),
+            CspVaultMethod::MultiSignBatch => (MetricsDomain::MultiSignature, "multi_sign_batch"),
             CspVaultMethod::GenCommitteeSigningKeyPair => (
                 MetricsDomain::MultiSignature,
                 "gen_committee_signing_key_pair",
             ),
             CspVaultMethod::ThresholdSign => (MetricsDomain::ThresholdSignature, "threshold_sign"),
+            CspVaultMethod::CombineThresholdSigShares => (
+                MetricsDomain::ThresholdSignature,
+                "combine_threshold_sig_shares",
+            ),
             CspVaultMethod::GenDealingEncryptionKeyPair => (
                 MetricsDomain::NiDkgAlgorithm,
                 "gen_dealing_encryption_key_pair",
@@ -338,6 +357,9 @@ impl CspVaultMethod {
                 (MetricsDomain::NiDkgAlgorithm, "update_forward_secure_epoch")
             }
             CspVaultMethod::CreateDealing => (MetricsDomain::NiDkgAlgorithm, "create_dealing"),
+            CspVaultMethod::VerifyDealingsBatch => {
+                (MetricsDomain::NiDkgAlgorithm, "verify_dealings_batch")
+            }
             CspVaultMethod::LoadThresholdSigningKey => {
                 (MetricsDomain::NiDkgAlgorithm, "load_threshold_signing_key")
             }
@@ -361,6 +383,10 @@ impl CspVaultMethod {
             ),
             CspVaultMethod::IdkgKeyCount => (MetricsDomain::KeyManagement, "idkg_key_count"),
             CspVaultMethod::GenTlsKeyPair => (MetricsDomain::TlsHandshake, "gen_tls_key_pair"),
+            CspVaultMethod::GenTlsKeyPairWithValidity => (
+                MetricsDomain::TlsHandshake,
+                "gen_tls_key_pair_with_validity",
+            ),
             CspVaultMethod::TlsSign => (MetricsDomain::TlsHandshake, "tls_sign"),
             CspVaultMethod::IdkgCreateDealing => {
                 (MetricsDomain::IdkgProtocol, "idkg_create_dealing")
@@ -390,6 +416,9 @@ impl CspVaultMethod {
                 (MetricsDomain::ThresholdSchnorr, "create_schnorr_sig_share")
             }
             CspVaultMethod::NewPublicSeed => (MetricsDomain::PublicSeed, "new_public_seed"),
+            CspVaultMethod::HealthStatus => (MetricsDomain::KeyManagement, "health_status"),
+            CspVaultMethod::MarkKeyInactive => (MetricsDomain::KeyManagement, "mark_key_inactive"),
+            CspVaultMethod::MarkKeyActive => (MetricsDomain::KeyManagement, "mark_key_active"),
         }
     }
 }
@@ -401,12 +430,17 @@ impl From<&TarpcCspVaultRequest> for CspVaultMethod {
         match request {
             Req::Sign { .. } => Method::Sign,
             Req::GenNodeSigningKeyPair { .. } => Method::GenNodeSigningKeyPair,
+            Req::GenBasicSignatureKeyPair { .. } => Method::GenBasicSignatureKeyPair,
             Req::MultiSign { .. } => Method::MultiSign,
+            Req::BatchSign { .. } => Method::BatchSign,
+            Req::MultiSignBatch { .. } => Method::MultiSignBatch,
             Req::GenCommitteeSigningKeyPair { .. } => Method::GenCommitteeSigningKeyPair,
             Req::ThresholdSign { .. } => Method::ThresholdSign,
+            Req::CombineThresholdSigShares { .. } => Method::CombineThresholdSigShares,
             Req::GenDealingEncryptionKeyPair { .. } => Method::GenDealingEncryptionKeyPair,
             Req::UpdateForwardSecureEpoch { .. } => Method::UpdateForwardSecureEpoch,
             Req::CreateDealing { .. } => Method::CreateDealing,
+            Req::VerifyDealingsBatch { .. } => Method::VerifyDealingsBatch,
             Req::LoadThresholdSigningKey { .. } => Method::LoadThresholdSigningKey,
             Req::RetainThresholdKeysIfPresent { .. } => Method::RetainThresholdKeysIfPresent,
             Req::SksContains { .. } => Method::SksContains,
@@ -418,6 +452,7 @@ impl From<&TarpcCspVaultRequest> for CspVaultMethod {
             }
             Req::IdkgKeyCount { .. } => Method::IdkgKeyCount,
             Req::GenTlsKeyPair { .. } => Method::GenTlsKeyPair,
+            Req::GenTlsKeyPairWithValidity { .. } => Method::GenTlsKeyPairWithValidity,
             Req::TlsSign { .. } => Method::TlsSign,
             Req::IdkgCreateDealing { .. } => Method::IdkgCreateDealing,
             Req::IdkgVerifyDealingPrivate { .. } => Method::IdkgVerifyDealingPrivate,
@@ -429,6 +464,9 @@ impl From<&TarpcCspVaultRequest> for CspVaultMethod {
             Req::CreateEcdsaSigShare { .. } => Method::CreateEcdsaSigShare,
             Req::CreateSchnorrSigShare { .. } => Method::CreateSchnorrSigShare,
             Req::NewPublicSeed { .. } => Method::NewPublicSeed,
+            Req::HealthStatus { .. } => Method::HealthStatus,
+            Req::MarkKeyInactive { .. } => Method::MarkKeyInactive,
+            Req::MarkKeyActive { .. } => Method::MarkKeyActive,
         }
     }
 }
@@ -440,12 +478,17 @@ impl From<&TarpcCspVaultResponse> for CspVaultMethod {
         match response {
             Resp::Sign { .. } => Method::Sign,
             Resp::GenNodeSigningKeyPair { .. } => Method::GenNodeSigningKeyPair,
+            Resp::GenBasicSignatureKeyPair { .. } => Method::GenBasicSignatureKeyPair,
             Resp::MultiSign { .. } => Method::MultiSign,
+            Resp::BatchSign { .. } => Method::BatchSign,
+            Resp::MultiSignBatch { .. } => Method::MultiSignBatch,
             Resp::GenCommitteeSigningKeyPair { .. } => Method::GenCommitteeSigningKeyPair,
             Resp::ThresholdSign { .. } => Method::ThresholdSign,
+            Resp::CombineThresholdSigShares { .. } => Method::CombineThresholdSigShares,
             Resp::GenDealingEncryptionKeyPair { .. } => Method::GenDealingEncryptionKeyPair,
             Resp::UpdateForwardSecureEpoch { .. } => Method::UpdateForwardSecureEpoch,
             Resp::CreateDealing { .. } => Method::CreateDealing,
+            Resp::VerifyDealingsBatch { .. } => Method::VerifyDealingsBatch,
             Resp::LoadThresholdSigningKey { .. } => Method::LoadThresholdSigningKey,
             Resp::RetainThresholdKeysIfPresent { .. } => Method::RetainThresholdKeysIfPresent,
             Resp::SksContains { .. } => Method::SksContains,
@@ -457,6 +500,7 @@ impl From<&TarpcCspVaultResponse> for CspVaultMethod {
             }
             Resp::IdkgKeyCount { .. } => Method::IdkgKeyCount,
             Resp::GenTlsKeyPair { .. } => Method::GenTlsKeyPair,
+            Resp::GenTlsKeyPairWithValidity { .. } => Method::GenTlsKeyPairWithValidity,
             Resp::TlsSign { .. } => Method::TlsSign,
             Resp::IdkgCreateDealing { .. } => Method::IdkgCreateDealing,
             Resp::IdkgVerifyDealingPrivate { .. } => Method::IdkgVerifyDealingPrivate,
@@ -468,6 +512,9 @@ impl From<&TarpcCspVaultResponse> for CspVaultMethod {
             Resp::CreateEcdsaSigShare { .. } => Method::CreateEcdsaSigShare,
             Resp::CreateSchnorrSigShare { .. } => Method::CreateSchnorrSigShare,
             Resp::NewPublicSeed { .. } => Method::NewPublicSeed,
+            Resp::HealthStatus { .. } => Method::HealthStatus,
+            Resp::MarkKeyInactive { .. } => Method::MarkKeyInactive,
+            Resp::MarkKeyActive { .. } => Method::MarkKeyActive,
         }
     }
 }
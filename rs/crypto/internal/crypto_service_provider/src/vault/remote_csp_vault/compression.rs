@@ -0,0 +1,130 @@
+//! Optional compression of the messages exchanged between the remote CSP vault
+//! client and server.
+//!
+//! Every frame starts with a byte identifying the encoding of the rest of the
+//! frame. Since a peer decodes frames of any encoding, whether to compress is
+//! decided by each peer for the messages it sends and needs no negotiation: a
+//! client that does not compress can talk to a server that does, and vice
+//! versa.
+
+use bytes::{Bytes, BytesMut};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::io::{self, Read, Write};
+use std::pin::Pin;
+use tokio_serde::{Deserializer, Serializer};
+
+/// The algorithm used to compress messages sent to the other peer.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum CompressionEncoding {
+    Gzip,
+    Zstd,
+}
+
+const IDENTITY_TAG: u8 = 0;
+const GZIP_TAG: u8 = 1;
+const ZSTD_TAG: u8 = 2;
+
+/// Wraps a codec to compress the serialized messages with `encoding`, if any,
+/// and to decompress received messages of any encoding. A message is sent
+/// uncompressed if compressing it does not make it smaller.
+///
+/// Decompressed messages are limited to `max_decompressed_length` bytes, so
+/// that a small frame cannot expand to an arbitrary amount of memory.
+pub struct CompressingCodec<Codec> {
+    inner_codec: Codec,
+    encoding: Option<CompressionEncoding>,
+    max_decompressed_length: usize,
+}
+
+impl<Codec> CompressingCodec<Codec> {
+    pub fn new(
+        codec: Codec,
+        encoding: Option<CompressionEncoding>,
+        max_decompressed_length: usize,
+    ) -> Self {
+        CompressingCodec {
+            inner_codec: codec,
+            encoding,
+            max_decompressed_length,
+        }
+    }
+}
+
+impl<Codec, S> Serializer<S> for CompressingCodec<Codec>
+where
+    Codec: Serializer<S, Error = io::Error> + Unpin,
+{
+    type Error = io::Error;
+
+    fn serialize(mut self: Pin<&mut Self>, item: &S) -> Result<Bytes, Self::Error> {
+        let serialized = Pin::new(&mut self.inner_codec).serialize(item)?;
+        let compressed = match self.encoding {
+            None => None,
+            Some(CompressionEncoding::Gzip) => {
+                let mut encoder = GzEncoder::new(vec![GZIP_TAG], flate2::Compression::default());
+                encoder.write_all(&serialized)?;
+                Some(encoder.finish()?)
+            }
+            Some(CompressionEncoding::Zstd) => {
+                let mut frame = vec![ZSTD_TAG];
+                zstd::stream::copy_encode(&serialized[..], &mut frame, 0)?;
+                Some(frame)
+            }
+        };
+        match compressed {
+            // Messages consisting mostly of key material, such as NiDKG
+            // dealings, are hardly compressible, and compressing them may
+            // even add a few bytes.
+            Some(frame) if frame.len() <= serialized.len() => Ok(frame.into()),
+            _ => {
+                let mut frame = Vec::with_capacity(serialized.len() + 1);
+                frame.push(IDENTITY_TAG);
+                frame.extend_from_slice(&serialized);
+                Ok(frame.into())
+            }
+        }
+    }
+}
+
+impl<Codec, D> Deserializer<D> for CompressingCodec<Codec>
+where
+    Codec: Deserializer<D, Error = io::Error> + Unpin,
+{
+    type Error = io::Error;
+
+    fn deserialize(mut self: Pin<&mut Self>, src: &BytesMut) -> Result<D, Self::Error> {
+        let (tag, payload) = src
+            .split_first()
+            .ok_or_else(|| invalid_data("empty frame".to_string()))?;
+        let decoded = match *tag {
+            IDENTITY_TAG => BytesMut::from(payload),
+            GZIP_TAG => read_limited(GzDecoder::new(payload), self.max_decompressed_length)?,
+            ZSTD_TAG => read_limited(
+                zstd::stream::read::Decoder::new(payload)?,
+                self.max_decompressed_length,
+            )?,
+            unknown => {
+                return Err(invalid_data(format!(
+                    "unknown compression encoding {unknown}"
+                )))
+            }
+        };
+        Pin::new(&mut self.inner_codec).deserialize(&decoded)
+    }
+}
+
+fn read_limited<R: Read>(reader: R, limit: usize) -> io::Result<BytesMut> {
+    let mut decoded = Vec::new();
+    reader.take(limit as u64 + 1).read_to_end(&mut decoded)?;
+    if decoded.len() > limit {
+        return Err(invalid_data(format!(
+            "decompressed message exceeds the maximum length of {limit} bytes"
+        )));
+    }
+    Ok(BytesMut::from(&decoded[..]))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
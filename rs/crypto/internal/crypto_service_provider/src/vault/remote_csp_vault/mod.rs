@@ -1,10 +1,11 @@
 use crate::api::{CspCreateMEGaKeyError, CspThresholdSignError};
 use crate::types::{CspPop, CspPublicKey, CspSignature};
 use crate::vault::api::{
-    CspBasicSignatureError, CspBasicSignatureKeygenError, CspMultiSignatureError,
-    CspMultiSignatureKeygenError, CspPublicKeyStoreError, CspSecretKeyStoreContainsError,
-    CspTlsKeygenError, CspTlsSignError, IDkgCreateDealingVaultError, IDkgDealingInternalBytes,
-    IDkgTranscriptInternalBytes, PksAndSksContainsErrors, ThresholdSchnorrCreateSigShareVaultError,
+    CspBasicSignatureError, CspBasicSignatureKeygenAlgorithm, CspBasicSignatureKeygenError,
+    CspMultiSignatureError, CspMultiSignatureKeygenError, CspPublicKeyStoreError,
+    CspSecretKeyStoreContainsError, CspTlsKeygenError, CspTlsSignError,
+    IDkgCreateDealingVaultError, IDkgDealingInternalBytes, IDkgTranscriptInternalBytes,
+    PksAndSksContainsErrors, ThresholdSchnorrCreateSigShareVaultError,
     ThresholdSchnorrSigShareBytes, ValidatePksAndSksError,
 };
 use ic_crypto_internal_seed::Seed;
@@ -28,9 +29,10 @@ use ic_types::crypto::canister_threshold_sig::error::{
 use ic_types::crypto::canister_threshold_sig::idkg::{
     BatchSignedIDkgDealing, IDkgTranscriptOperation,
 };
+use ic_types::crypto::threshold_sig::ni_dkg::NiDkgTranscript;
 use ic_types::crypto::ExtendedDerivationPath;
 use ic_types::crypto::{AlgorithmId, CurrentNodePublicKeys};
-use ic_types::{NodeId, NodeIndex, NumberOfNodes, Randomness};
+use ic_types::{NodeId, NodeIndex, NumberOfNodes, Randomness, Time};
 use serde_bytes::ByteBuf;
 use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
@@ -38,22 +40,29 @@ use tokio::net::UnixListener;
 
 const FOUR_GIGA_BYTES: usize = 4 * 1024 * 1024 * 1024;
 mod codec;
+mod compression;
 mod robust_unix_socket;
 mod tarpc_csp_vault_client;
 mod tarpc_csp_vault_server;
+mod tls;
 
 use crate::key_id::KeyId;
 pub use crate::vault::local_csp_vault::ProdLocalCspVault;
 use crate::ExternalPublicKeys;
+pub use compression::CompressionEncoding;
 use ic_crypto_internal_logmon::metrics::CryptoMetrics;
 use ic_crypto_node_key_validation::ValidNodePublicKeys;
 use std::sync::Arc;
-pub use tarpc_csp_vault_client::{RemoteCspVault, RemoteCspVaultBuilder};
+pub use tarpc_csp_vault_client::{RemoteCspVault, RemoteCspVaultBuilder, RemoteCspVaultError};
 pub use tarpc_csp_vault_server::{TarpcCspVaultServerImpl, TarpcCspVaultServerImplBuilder};
+pub use tls::{
+    VaultClientTlsConfig, VaultServerTlsConfig, VaultTlsConfigError, DEFAULT_VAULT_SERVER_NAME,
+};
 use tokio_util::codec::length_delimited::Builder;
 use tokio_util::codec::LengthDelimitedCodec;
 
 use super::api::PublicRandomSeedGeneratorError;
+use super::api::{CspVaultHealthStatus, CspVaultHealthStatusError, CspVaultKeyActivationError};
 
 #[cfg(test)]
 mod tests;
@@ -75,6 +84,11 @@ pub trait TarpcCspVault {
     // Corresponds to `BasicSignatureCspVault.gen_node_signing_key_pair()`.
     async fn gen_node_signing_key_pair() -> Result<CspPublicKey, CspBasicSignatureKeygenError>;
 
+    // Corresponds to `BasicSignatureCspVault.gen_basic_signature_key_pair()`.
+    async fn gen_basic_signature_key_pair(
+        algorithm: CspBasicSignatureKeygenAlgorithm,
+    ) -> Result<CspPublicKey, CspBasicSignatureKeygenError>;
+
     // Corresponds to `MultiSignatureCspVault.multi_sign()`.
     async fn multi_sign(
         algorithm_id: AlgorithmId,
@@ -82,6 +96,20 @@ pub trait TarpcCspVault {
         key_id: KeyId,
     ) -> Result<CspSignature, CspMultiSignatureError>;
 
+    // Corresponds to `MultiSignatureCspVault.batch_sign()`.
+    async fn batch_sign(
+        algorithm_id: AlgorithmId,
+        messages: Vec<ByteBuf>,
+        key_id: KeyId,
+    ) -> Vec<Result<CspSignature, CspMultiSignatureError>>;
+
+    // Corresponds to `MultiSignatureCspVault.multi_sign_batch()`.
+    async fn multi_sign_batch(
+        algorithm_id: AlgorithmId,
+        messages: Vec<ByteBuf>,
+        key_id: KeyId,
+    ) -> Result<Vec<CspSignature>, CspMultiSignatureError>;
+
     // Corresponds to `MultiSignatureCspVault.gen_committee_signing_key_pair()`.
     async fn gen_committee_signing_key_pair(
     ) -> Result<(CspPublicKey, CspPop), CspMultiSignatureKeygenError>;
@@ -93,6 +121,12 @@ pub trait TarpcCspVault {
         key_id: KeyId,
     ) -> Result<CspSignature, CspThresholdSignError>;
 
+    // Corresponds to `ThresholdSignatureCspVault.combine_threshold_sig_shares()`.
+    async fn combine_threshold_sig_shares(
+        shares: BTreeMap<NodeId, CspSignature>,
+        transcript: NiDkgTranscript,
+    ) -> Result<CspSignature, CspThresholdSignError>;
+
     // Corresponds to `NiDkgCspVault.gen_dealing_encryption_key_pair()`.
     async fn gen_dealing_encryption_key_pair(
         node_id: NodeId,
@@ -116,6 +150,15 @@ pub trait TarpcCspVault {
         maybe_resharing_secret: Option<KeyId>,
     ) -> Result<CspNiDkgDealing, ni_dkg_errors::CspDkgCreateReshareDealingError>;
 
+    // Corresponds to `NiDkgCspVault.verify_dealings_batch()`.
+    async fn verify_dealings_batch(
+        algorithm_id: AlgorithmId,
+        threshold: NumberOfNodes,
+        epoch: Epoch,
+        receiver_keys: BTreeMap<NodeIndex, CspFsEncryptionPublicKey>,
+        dealings: Vec<(NodeIndex, CspNiDkgDealing)>,
+    ) -> Vec<Result<(), ni_dkg_errors::CspDkgVerifyDealingError>>;
+
     // Corresponds to `NiDkgCspVault.load_threshold_signing_key()`.
     async fn load_threshold_signing_key(
         algorithm_id: AlgorithmId,
@@ -154,6 +197,12 @@ pub trait TarpcCspVault {
     // Corresponds to `TlsHandshakeCspVault.gen_tls_key_pair()`.
     async fn gen_tls_key_pair(node: NodeId) -> Result<TlsPublicKeyCert, CspTlsKeygenError>;
 
+    // Corresponds to `TlsHandshakeCspVault.gen_tls_key_pair_with_validity()`.
+    async fn gen_tls_key_pair_with_validity(
+        node: NodeId,
+        not_after: Time,
+    ) -> Result<TlsPublicKeyCert, CspTlsKeygenError>;
+
     // Corresponds to `TlsHandshakeCspVault.tls_sign()`.
     async fn tls_sign(message: ByteBuf, key_id: KeyId) -> Result<CspSignature, CspTlsSignError>;
 
@@ -245,6 +294,15 @@ pub trait TarpcCspVault {
     ) -> Result<ThresholdSchnorrSigShareBytes, ThresholdSchnorrCreateSigShareVaultError>;
 
     async fn new_public_seed() -> Result<Seed, PublicRandomSeedGeneratorError>;
+
+    // Corresponds to `HealthStatusCspVault.health_status()`.
+    async fn health_status() -> Result<CspVaultHealthStatus, CspVaultHealthStatusError>;
+
+    // Corresponds to `KeyActivationCspVault.mark_key_inactive()`.
+    async fn mark_key_inactive(key_id: KeyId) -> Result<(), CspVaultKeyActivationError>;
+
+    // Corresponds to `KeyActivationCspVault.mark_key_active()`.
+    async fn mark_key_active(key_id: KeyId) -> Result<(), CspVaultKeyActivationError>;
 }
 
 pub async fn run_csp_vault_server(
@@ -4,6 +4,7 @@ use std::io;
 use std::io::Error;
 use std::path::PathBuf;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 use stubborn_io::strategies::ExpBackoffStrategy;
@@ -11,6 +12,7 @@ use stubborn_io::tokio::{StubbornIo, UnderlyingIo};
 use stubborn_io::ReconnectOptions;
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use tokio::net::UnixStream;
+use tokio::sync::Notify;
 
 pub struct RobustUnixStream(UnixStream);
 
@@ -56,7 +58,14 @@ impl UnderlyingIo<(PathBuf, ReplicaLogger)> for RobustUnixStream {
 
 pub type RobustUnixSocket = StubbornIo<RobustUnixStream, (PathBuf, ReplicaLogger)>;
 
-pub async fn connect(socket_path: PathBuf, logger: ReplicaLogger) -> io::Result<RobustUnixSocket> {
+/// Connects to the socket at `socket_path`, transparently reconnecting when the
+/// connection is lost. Waiters on `on_disconnect` are notified whenever a
+/// disconnection is detected, so it must not be shared with other connections.
+pub async fn connect(
+    socket_path: PathBuf,
+    logger: ReplicaLogger,
+    on_disconnect: Arc<Notify>,
+) -> io::Result<RobustUnixSocket> {
     const MINIMUM_DELAY: Duration = Duration::from_millis(100);
     const MAXIMUM_DELAY: Duration = Duration::from_secs(1);
     const EXPONENTIAL_BACKOFF_FACTOR: f64 = 2.0;
@@ -71,6 +80,7 @@ pub async fn connect(socket_path: PathBuf, logger: ReplicaLogger) -> io::Result<
                     "Detected disconnection from socket {:?}. Attempting to reconnect...",
                     socket_path
                 );
+                on_disconnect.notify_waiters();
             }
         })
         .with_on_connect_callback({
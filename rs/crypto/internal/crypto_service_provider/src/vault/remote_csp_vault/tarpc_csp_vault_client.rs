@@ -2,17 +2,21 @@ use crate::api::{CspCreateMEGaKeyError, CspThresholdSignError};
 use crate::key_id::KeyId;
 use crate::types::{CspPop, CspPublicKey, CspSignature};
 use crate::vault::api::{
-    BasicSignatureCspVault, CspBasicSignatureError, CspBasicSignatureKeygenError,
-    CspMultiSignatureError, CspMultiSignatureKeygenError, CspPublicKeyStoreError,
-    CspSecretKeyStoreContainsError, CspTlsKeygenError, CspTlsSignError,
-    IDkgCreateDealingVaultError, IDkgDealingInternalBytes, IDkgProtocolCspVault,
-    IDkgTranscriptInternalBytes, MultiSignatureCspVault, NiDkgCspVault, PksAndSksContainsErrors,
+    BasicSignatureCspVault, CspBasicSignatureError, CspBasicSignatureKeygenAlgorithm,
+    CspBasicSignatureKeygenError, CspMultiSignatureError, CspMultiSignatureKeygenError,
+    CspPublicKeyStoreError, CspSecretKeyStoreContainsError, CspTlsKeygenError, CspTlsSignError,
+    CspVaultHealthStatus, CspVaultHealthStatusError, CspVaultKeyActivationError,
+    HealthStatusCspVault, IDkgCreateDealingVaultError, IDkgDealingInternalBytes,
+    IDkgProtocolCspVault, IDkgTranscriptInternalBytes, KeyActivationCspVault,
+    MultiSignatureCspVault, NiDkgCspVault, PksAndSksContainsErrors,
     PublicAndSecretKeyStoreCspVault, PublicKeyStoreCspVault, PublicRandomSeedGenerator,
     PublicRandomSeedGeneratorError, SecretKeyStoreCspVault, ThresholdEcdsaSignerCspVault,
     ThresholdSchnorrSigShareBytes, ThresholdSchnorrSignerCspVault, ThresholdSignatureCspVault,
     ValidatePksAndSksError,
 };
 use crate::vault::remote_csp_vault::codec::{Bincode, CspVaultObserver, ObservableCodec};
+use crate::vault::remote_csp_vault::compression::{CompressingCodec, CompressionEncoding};
+use crate::vault::remote_csp_vault::tls::VaultClientTlsConfig;
 use crate::vault::remote_csp_vault::ThresholdSchnorrCreateSigShareVaultError;
 use crate::vault::remote_csp_vault::{
     remote_vault_codec_builder, robust_unix_socket, TarpcCspVaultClient, FOUR_GIGA_BYTES,
@@ -23,7 +27,7 @@ use ic_crypto_internal_seed::Seed;
 use ic_crypto_internal_threshold_sig_bls12381::api::dkg_errors::InternalError;
 use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors::{
     CspDkgCreateFsKeyError, CspDkgCreateReshareDealingError, CspDkgLoadPrivateKeyError,
-    CspDkgRetainThresholdKeysError, CspDkgUpdateFsEpochError,
+    CspDkgRetainThresholdKeysError, CspDkgUpdateFsEpochError, CspDkgVerifyDealingError,
 };
 use ic_crypto_internal_threshold_sig_canister_threshold_sig::{
     CommitmentOpening, IDkgComplaintInternal, MEGaPublicKey, ThresholdEcdsaSigShareInternal,
@@ -36,7 +40,7 @@ use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::{
 };
 use ic_crypto_internal_types::NodeIndex;
 use ic_crypto_tls_interfaces::TlsPublicKeyCert;
-use ic_logger::{debug, new_logger, ReplicaLogger};
+use ic_logger::{debug, new_logger, warn, ReplicaLogger};
 use ic_protobuf::registry::crypto::v1::PublicKey;
 use ic_types::crypto::canister_threshold_sig::error::{
     IDkgLoadTranscriptError, IDkgOpenTranscriptError, IDkgRetainKeysError,
@@ -45,16 +49,25 @@ use ic_types::crypto::canister_threshold_sig::error::{
 use ic_types::crypto::canister_threshold_sig::idkg::{
     BatchSignedIDkgDealing, IDkgTranscriptOperation,
 };
+use ic_types::crypto::threshold_sig::ni_dkg::NiDkgTranscript;
 use ic_types::crypto::ExtendedDerivationPath;
 use ic_types::crypto::{AlgorithmId, CurrentNodePublicKeys};
-use ic_types::{NodeId, NumberOfNodes, Randomness};
+use ic_types::{NodeId, NumberOfNodes, Randomness, Time};
 use serde::{Deserialize, Serialize};
 use serde_bytes::ByteBuf;
 use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+use tarpc::client::RpcError;
 use tarpc::serde_transport;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::UnixStream;
+use tokio::sync::Notify;
 use tracing::instrument;
 
 #[cfg(test)]
@@ -70,7 +83,15 @@ use slog_async::AsyncGuard;
 /// An implementation of `CspVault`-trait that talks to a remote CSP vault.
 #[allow(dead_code)]
 pub struct RemoteCspVault {
-    tarpc_csp_client: TarpcCspVaultClient,
+    // Replaced by a fresh connection if the server refused or reset the
+    // current one.
+    connection: RwLock<Connection>,
+    socket_path: PathBuf,
+    max_frame_length: usize,
+    tls_config: Option<VaultClientTlsConfig>,
+    compression: Option<CompressionEncoding>,
+    retry_policy: RetryPolicy,
+    retry_count: AtomicU64,
     // default timeout for RPC calls that can timeout.
     rpc_timeout: Duration,
     // special, long timeout for RPC calls that should not really timeout.
@@ -91,6 +112,18 @@ pub enum RemoteCspVaultError {
     },
 }
 
+/// A tarpc client together with the state of its connection to the server.
+struct Connection {
+    client: TarpcCspVaultClient,
+    // Notified whenever this connection is detected to be lost. Each
+    // connection has its own, so that the loss of a replaced connection does
+    // not abort calls on the current one.
+    disconnected: Arc<Notify>,
+    // Incremented with every reconnect, so that callers that observed the loss
+    // of the same connection replace it only once.
+    generation: u64,
+}
+
 impl RemoteCspVault {
     fn tokio_block_on<T: Future>(&self, task: T) -> T::Output {
         self.tokio_runtime_handle.block_on(task)
@@ -99,6 +132,179 @@ impl RemoteCspVault {
 
 const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(300); // 5 minutes
 const LONG_RPC_TIMEOUT: Duration = Duration::from_secs(3600 * 24 * 100); // 100 days
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(100);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Determines how often and how long to wait before an RPC call is retried
+/// after the connection to the server was refused or reset.
+#[derive(Clone, Debug)]
+struct RetryPolicy {
+    // Total number of attempts, including the first one.
+    max_attempts: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Exponential backoff: the delay before the `retry`-th retry (starting
+    /// from 1) is `initial_delay * 2^(retry - 1)`, capped at `max_delay`.
+    fn delay(&self, retry: u32) -> Duration {
+        self.initial_delay
+            .saturating_mul(2_u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_delay)
+    }
+}
+
+type RpcFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, RpcError>> + 'a>>;
+
+/// Error of an RPC call made via [`RemoteCspVault::call_with_retry`].
+#[derive(Debug)]
+enum RpcCallError {
+    Rpc(RpcError),
+    /// The connection to the server was lost while waiting for the response.
+    ConnectionReset,
+    /// The server did not respond to the call of `method` before the deadline
+    /// of the request. The connection remains usable for subsequent calls.
+    Timeout {
+        method: &'static str,
+        elapsed: Duration,
+    },
+}
+
+/// Whether an RPC call may safely be resent after the connection to the server
+/// was reset, when the server may already have executed it.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Idempotency {
+    /// Read-only calls and calls whose repeated execution has the same effect
+    /// as a single one. Retried if the connection was refused or reset.
+    Idempotent,
+    /// Calls that generate keys, dealings or randomness, or that otherwise change
+    /// the state of the vault in a way that must not happen twice. Only retried
+    /// if the connection was refused, i.e. the request was never delivered.
+    NonIdempotent,
+}
+
+impl RpcCallError {
+    fn is_retryable(&self, idempotency: Idempotency) -> bool {
+        match (self, idempotency) {
+            (RpcCallError::Timeout { .. }, _) => false,
+            (RpcCallError::ConnectionReset, Idempotency::Idempotent) => true,
+            (RpcCallError::ConnectionReset, Idempotency::NonIdempotent) => false,
+            (RpcCallError::Rpc(_), Idempotency::Idempotent) => {
+                self.is_connection_refused_or_reset()
+            }
+            (RpcCallError::Rpc(_), Idempotency::NonIdempotent) => {
+                self.io_error_kind() == Some(io::ErrorKind::ConnectionRefused)
+            }
+        }
+    }
+
+    /// Whether the transport of the client failed because the server refused
+    /// or reset the connection. The client is unusable afterwards.
+    fn is_connection_refused_or_reset(&self) -> bool {
+        matches!(
+            self.io_error_kind(),
+            Some(io::ErrorKind::ConnectionRefused | io::ErrorKind::ConnectionReset)
+        )
+    }
+
+    /// The kind of the I/O error that caused the transport of the client to
+    /// fail, if any.
+    fn io_error_kind(&self) -> Option<io::ErrorKind> {
+        let RpcCallError::Rpc(rpc_error) = self else {
+            return None;
+        };
+        let mut source = std::error::Error::source(rpc_error);
+        while let Some(error) = source {
+            if let Some(io_error) = error.downcast_ref::<io::Error>() {
+                return Some(io_error.kind());
+            }
+            source = error.source();
+        }
+        None
+    }
+}
+
+impl fmt::Display for RpcCallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcCallError::Rpc(rpc_error) => write!(f, "{}", rpc_error),
+            RpcCallError::ConnectionReset => {
+                write!(f, "the connection to the server was reset")
+            }
+            RpcCallError::Timeout { method, elapsed } => write!(
+                f,
+                "RPC call {} timed out after {:?}: {}",
+                method,
+                elapsed,
+                RpcError::DeadlineExceeded
+            ),
+        }
+    }
+}
+
+/// Implements `From<RpcCallError>` for vault error types: a timed out call is
+/// reported as the type's `Timeout` variant, any other RPC error as the
+/// transient internal error constructed from the error message by the
+/// closure-like expression given for the group of types.
+macro_rules! impl_from_rpc_call_error {
+    ($(|$internal_error:ident| $transient:expr => [$($error:ty),* $(,)?];)*) => {
+        $($(
+            impl From<RpcCallError> for $error {
+                fn from(error: RpcCallError) -> Self {
+                    match error {
+                        RpcCallError::Timeout { method, elapsed } => Self::Timeout {
+                            method: method.to_string(),
+                            elapsed,
+                        },
+                        error => {
+                            let $internal_error = error.to_string();
+                            $transient
+                        }
+                    }
+                }
+            }
+        )*)*
+    };
+}
+
+impl_from_rpc_call_error! {
+    |internal_error| Self::TransientInternalError { internal_error } => [
+        CspBasicSignatureError,
+        CspBasicSignatureKeygenError,
+        CspMultiSignatureError,
+        CspMultiSignatureKeygenError,
+        CspThresholdSignError,
+        CspSecretKeyStoreContainsError,
+        CspTlsKeygenError,
+        CspTlsSignError,
+        IDkgVerifyDealingPrivateError,
+        IDkgLoadTranscriptError,
+        IDkgRetainKeysError,
+        CspCreateMEGaKeyError,
+        IDkgOpenTranscriptError,
+        ThresholdEcdsaCreateSigShareError,
+        PublicRandomSeedGeneratorError,
+        CspVaultHealthStatusError,
+        CspVaultKeyActivationError,
+    ];
+    |internal_error| Self::TransientInternalError(internal_error) => [
+        CspPublicKeyStoreError,
+        PksAndSksContainsErrors,
+        ValidatePksAndSksError,
+        CspDkgCreateFsKeyError,
+        IDkgCreateDealingVaultError,
+        ThresholdSchnorrCreateSigShareVaultError,
+    ];
+    |internal_error| Self::TransientInternalError(InternalError { internal_error }) => [
+        CspDkgUpdateFsEpochError,
+        CspDkgCreateReshareDealingError,
+        CspDkgVerifyDealingError,
+        CspDkgLoadPrivateKeyError,
+        CspDkgRetainThresholdKeysError,
+    ];
+}
 
 #[allow(dead_code)]
 impl RemoteCspVault {
@@ -124,14 +330,152 @@ impl RemoteCspVault {
     ) -> RemoteCspVaultBuilder {
         RemoteCspVaultBuilder::new(socket_path, rt_handle)
     }
+
+    /// Returns the number of times RPC calls were retried by this client
+    /// because the connection to the server was refused or reset.
+    pub fn retry_count(&self) -> u64 {
+        self.retry_count.load(Ordering::Relaxed)
+    }
+
+    /// Performs the idempotent RPC `call`, retrying it with exponential backoff
+    /// if the connection to the server was refused or reset, e.g. because the
+    /// server restarted. Other errors, in particular application-level errors
+    /// returned by the server, are not retried. In particular, a call that
+    /// exceeds its deadline fails with [`RpcCallError::Timeout`] for `method`.
+    fn call_with_retry<T>(
+        &self,
+        method: &'static str,
+        call: impl for<'a> Fn(&'a TarpcCspVaultClient) -> RpcFuture<'a, T>,
+    ) -> Result<T, RpcCallError> {
+        self.call_with_retry_impl(method, Idempotency::Idempotent, call)
+    }
+
+    /// Performs the non-idempotent RPC `call`, like
+    /// [`RemoteCspVault::call_with_retry`], but only retries it if the
+    /// connection to the server was refused. If the connection is reset, the
+    /// server may already have executed the call, so it is not resent.
+    fn call_non_idempotent<T>(
+        &self,
+        method: &'static str,
+        call: impl for<'a> Fn(&'a TarpcCspVaultClient) -> RpcFuture<'a, T>,
+    ) -> Result<T, RpcCallError> {
+        self.call_with_retry_impl(method, Idempotency::NonIdempotent, call)
+    }
+
+    fn call_with_retry_impl<T>(
+        &self,
+        method: &'static str,
+        idempotency: Idempotency,
+        call: impl for<'a> Fn(&'a TarpcCspVaultClient) -> RpcFuture<'a, T>,
+    ) -> Result<T, RpcCallError> {
+        let mut attempt = 1;
+        loop {
+            let (client, disconnected, generation) = {
+                let connection = self
+                    .connection
+                    .read()
+                    .expect("failed to acquire lock on tarpc client");
+                (
+                    connection.client.clone(),
+                    Arc::clone(&connection.disconnected),
+                    connection.generation,
+                )
+            };
+            let result = self.tokio_block_on(async {
+                // The request is lost if the connection is reset before the
+                // response was received, so stop waiting for it.
+                let disconnected = disconnected.notified();
+                let start = Instant::now();
+                tokio::select! {
+                    result = call(&client) => result.map_err(|error| match error {
+                        RpcError::DeadlineExceeded => RpcCallError::Timeout {
+                            method,
+                            elapsed: start.elapsed(),
+                        },
+                        error => RpcCallError::Rpc(error),
+                    }),
+                    _ = disconnected => Err(RpcCallError::ConnectionReset),
+                }
+            });
+            match result {
+                Err(error)
+                    if error.is_retryable(idempotency)
+                        && attempt < self.retry_policy.max_attempts =>
+                {
+                    let delay = self.retry_policy.delay(attempt);
+                    warn!(
+                        self.logger,
+                        "RPC call to remote CSP vault failed: {}. Retrying in {:?} (attempt {}/{})",
+                        error,
+                        delay,
+                        attempt + 1,
+                        self.retry_policy.max_attempts
+                    );
+                    std::thread::sleep(delay);
+                    if error.is_connection_refused_or_reset() {
+                        self.reconnect(generation);
+                    }
+                    self.retry_count.fetch_add(1, Ordering::Relaxed);
+                    attempt += 1;
+                }
+                Err(error) => {
+                    // Make the client usable again for subsequent calls, even if
+                    // this one is not retried.
+                    if error.is_connection_refused_or_reset() {
+                        self.reconnect(generation);
+                    }
+                    return Err(error);
+                }
+                Ok(value) => return Ok(value),
+            }
+        }
+    }
+
+    /// Replaces the connection of the given `generation` by a new connection
+    /// to the server. If the connection was already replaced, e.g. by a
+    /// concurrent call that observed the same loss of connection, nothing is
+    /// done. If connecting fails, the current connection is kept and the next
+    /// attempt will fail again, so that connecting is retried as well.
+    fn reconnect(&self, generation: u64) {
+        let mut connection = self
+            .connection
+            .write()
+            .expect("failed to acquire lock on tarpc client");
+        if connection.generation != generation {
+            return;
+        }
+        match connect(
+            &self.socket_path,
+            self.max_frame_length,
+            self.tls_config.as_ref(),
+            self.compression,
+            &self.tokio_runtime_handle,
+            &self.logger,
+            &self.metrics,
+            generation + 1,
+        ) {
+            Ok(new_connection) => {
+                *connection = new_connection;
+            }
+            Err(error) => {
+                warn!(
+                    self.logger,
+                    "Failed to reconnect to remote CSP vault: {:?}", error
+                );
+            }
+        }
+    }
 }
 
 pub struct RemoteCspVaultBuilder {
     socket_path: PathBuf,
     rt_handle: tokio::runtime::Handle,
     max_frame_length: usize,
+    tls_config: Option<VaultClientTlsConfig>,
+    compression: Option<CompressionEncoding>,
     rpc_timeout: Duration,
     long_rpc_timeout: Duration,
+    max_attempts: u32,
     logger: ReplicaLogger,
     metrics: Arc<CryptoMetrics>,
     #[cfg(test)]
@@ -144,8 +488,11 @@ impl RemoteCspVaultBuilder {
             socket_path,
             rt_handle,
             max_frame_length: FOUR_GIGA_BYTES,
+            tls_config: None,
+            compression: None,
             rpc_timeout: DEFAULT_RPC_TIMEOUT,
             long_rpc_timeout: LONG_RPC_TIMEOUT,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
             logger: no_op_logger(),
             metrics: Arc::new(CryptoMetrics::none()),
             #[cfg(test)]
@@ -178,11 +525,33 @@ impl RemoteCspVaultBuilder {
         self
     }
 
+    /// Sets the total number of attempts (including the first one) for an RPC
+    /// call whose connection to the server was refused or reset. At least one
+    /// attempt is always made.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
     pub fn with_max_frame_length(mut self, new_length: usize) -> Self {
         self.max_frame_length = new_length;
         self
     }
 
+    /// Connects to the server via TLS, only accepting a server that
+    /// authenticates itself according to `tls_config`.
+    pub fn with_tls_config(mut self, tls_config: VaultClientTlsConfig) -> Self {
+        self.tls_config = Some(tls_config);
+        self
+    }
+
+    /// Compresses the requests sent to the server with `encoding`. Responses
+    /// are decompressed regardless of this setting.
+    pub fn with_compression(mut self, encoding: CompressionEncoding) -> Self {
+        self.compression = Some(encoding);
+        self
+    }
+
     pub fn with_logger(mut self, logger: ReplicaLogger) -> Self {
         self.logger = logger;
         self
@@ -194,32 +563,29 @@ impl RemoteCspVaultBuilder {
     }
 
     pub fn build(self) -> Result<RemoteCspVault, RemoteCspVaultError> {
-        let conn = self
-            .rt_handle
-            .block_on(robust_unix_socket::connect(
-                self.socket_path.clone(),
-                new_logger!(&self.logger),
-            ))
-            .map_err(|e| RemoteCspVaultError::TransportError {
-                server_address: self.socket_path.to_string_lossy().to_string(),
-                message: e.to_string(),
-            })?;
-        let transport = serde_transport::new(
-            remote_vault_codec_builder()
-                .max_frame_length(self.max_frame_length)
-                .new_framed(conn),
-            ObservableCodec::new(
-                Bincode::default(),
-                CspVaultObserver::new(new_logger!(&self.logger), Arc::clone(&self.metrics)),
-            ),
-        );
-        let client = {
-            let _enter_guard = self.rt_handle.enter();
-            TarpcCspVaultClient::new(Default::default(), transport).spawn()
-        };
+        let connection = connect(
+            &self.socket_path,
+            self.max_frame_length,
+            self.tls_config.as_ref(),
+            self.compression,
+            &self.rt_handle,
+            &self.logger,
+            &self.metrics,
+            0,
+        )?;
         debug!(self.logger, "Instantiated remote CSP vault client");
         Ok(RemoteCspVault {
-            tarpc_csp_client: client,
+            connection: RwLock::new(connection),
+            socket_path: self.socket_path,
+            max_frame_length: self.max_frame_length,
+            tls_config: self.tls_config,
+            compression: self.compression,
+            retry_policy: RetryPolicy {
+                max_attempts: self.max_attempts,
+                initial_delay: INITIAL_RETRY_DELAY,
+                max_delay: MAX_RETRY_DELAY,
+            },
+            retry_count: AtomicU64::new(0),
             rpc_timeout: self.rpc_timeout,
             long_rpc_timeout: self.long_rpc_timeout,
             tokio_runtime_handle: self.rt_handle,
@@ -235,6 +601,95 @@ impl RemoteCspVaultBuilder {
     }
 }
 
+fn connect(
+    socket_path: &Path,
+    max_frame_length: usize,
+    tls_config: Option<&VaultClientTlsConfig>,
+    compression: Option<CompressionEncoding>,
+    rt_handle: &tokio::runtime::Handle,
+    logger: &ReplicaLogger,
+    metrics: &Arc<CryptoMetrics>,
+    generation: u64,
+) -> Result<Connection, RemoteCspVaultError> {
+    let transport_error = |message: String| RemoteCspVaultError::TransportError {
+        server_address: socket_path.to_string_lossy().to_string(),
+        message,
+    };
+    let disconnected = Arc::new(Notify::new());
+    let client = match tls_config {
+        None => {
+            let conn = rt_handle
+                .block_on(robust_unix_socket::connect(
+                    socket_path.to_path_buf(),
+                    new_logger!(logger),
+                    Arc::clone(&disconnected),
+                ))
+                .map_err(|e| transport_error(e.to_string()))?;
+            spawn_client(
+                conn,
+                max_frame_length,
+                compression,
+                rt_handle,
+                logger,
+                metrics,
+            )
+        }
+        // A TLS session cannot survive a transparent reconnect of the
+        // underlying socket, so the connection is not wrapped in a
+        // `RobustUnixSocket`. A lost connection instead fails the RPC call,
+        // and `call_with_retry` reconnects with a new handshake if the
+        // failure was a refused or reset connection.
+        Some(tls_config) => {
+            let conn = rt_handle
+                .block_on(async {
+                    let stream = UnixStream::connect(socket_path).await?;
+                    tls_config
+                        .connector()
+                        .connect(tls_config.server_name(), stream)
+                        .await
+                })
+                .map_err(|e| transport_error(format!("TLS connection failed: {e}")))?;
+            spawn_client(
+                conn,
+                max_frame_length,
+                compression,
+                rt_handle,
+                logger,
+                metrics,
+            )
+        }
+    };
+    Ok(Connection {
+        client,
+        disconnected,
+        generation,
+    })
+}
+
+fn spawn_client<S>(
+    conn: S,
+    max_frame_length: usize,
+    compression: Option<CompressionEncoding>,
+    rt_handle: &tokio::runtime::Handle,
+    logger: &ReplicaLogger,
+    metrics: &Arc<CryptoMetrics>,
+) -> TarpcCspVaultClient
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let transport = serde_transport::new(
+        remote_vault_codec_builder()
+            .max_frame_length(max_frame_length)
+            .new_framed(conn),
+        ObservableCodec::new(
+            CompressingCodec::new(Bincode::default(), compression, max_frame_length),
+            CspVaultObserver::new(new_logger!(logger), Arc::clone(metrics)),
+        ),
+    );
+    let _enter_guard = rt_handle.enter();
+    TarpcCspVaultClient::new(Default::default(), transport).spawn()
+}
+
 fn deadline_from_now(timeout: Duration) -> SystemTime {
     SystemTime::now() + timeout
 }
@@ -256,30 +711,39 @@ impl BasicSignatureCspVault for RemoteCspVault {
         message: Vec<u8>,
         key_id: KeyId,
     ) -> Result<CspSignature, CspBasicSignatureError> {
-        self.tokio_block_on(self.tarpc_csp_client.sign(
-            context_with_timeout(self.rpc_timeout),
-            algorithm_id,
-            ByteBuf::from(message),
-            key_id,
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspBasicSignatureError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_with_retry("sign", |client| {
+            Box::pin(client.sign(
+                context_with_timeout(self.rpc_timeout),
+                algorithm_id,
+                ByteBuf::from(message.clone()),
+                key_id,
+            ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
     fn gen_node_signing_key_pair(&self) -> Result<CspPublicKey, CspBasicSignatureKeygenError> {
-        self.tokio_block_on(
-            self.tarpc_csp_client
-                .gen_node_signing_key_pair(context_with_timeout(self.rpc_timeout)),
-        )
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspBasicSignatureKeygenError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_non_idempotent("gen_node_signing_key_pair", |client| {
+            Box::pin(client.gen_node_signing_key_pair(context_with_timeout(self.rpc_timeout)))
+        })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
+    }
+
+    #[instrument(skip_all)]
+    fn gen_basic_signature_key_pair(
+        &self,
+        algorithm: CspBasicSignatureKeygenAlgorithm,
+    ) -> Result<CspPublicKey, CspBasicSignatureKeygenError> {
+        self.call_non_idempotent("gen_basic_signature_key_pair", |client| {
+            Box::pin(
+                client.gen_basic_signature_key_pair(
+                    context_with_timeout(self.rpc_timeout),
+                    algorithm,
+                ),
+            )
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 }
 
@@ -291,32 +755,69 @@ impl MultiSignatureCspVault for RemoteCspVault {
         message: Vec<u8>,
         key_id: KeyId,
     ) -> Result<CspSignature, CspMultiSignatureError> {
-        self.tokio_block_on(self.tarpc_csp_client.multi_sign(
-            context_with_timeout(self.rpc_timeout),
-            algorithm_id,
-            ByteBuf::from(message),
-            key_id,
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspMultiSignatureError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_with_retry("multi_sign", |client| {
+            Box::pin(client.multi_sign(
+                context_with_timeout(self.rpc_timeout),
+                algorithm_id,
+                ByteBuf::from(message.clone()),
+                key_id,
+            ))
+        })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
+    }
+
+    #[instrument(skip_all)]
+    fn batch_sign(
+        &self,
+        algorithm_id: AlgorithmId,
+        messages: &[&[u8]],
+        key_id: KeyId,
+    ) -> Vec<Result<CspSignature, CspMultiSignatureError>> {
+        let messages: Vec<ByteBuf> = messages
+            .iter()
+            .map(|message| ByteBuf::from(message.to_vec()))
+            .collect();
+        self.call_with_retry("batch_sign", |client| {
+            Box::pin(client.batch_sign(
+                context_with_timeout(self.rpc_timeout),
+                algorithm_id,
+                messages.clone(),
+                key_id,
+            ))
+        })
+        .unwrap_or_else(|error: RpcCallError| {
+            let error = CspMultiSignatureError::from(error);
+            vec![Err(error); messages.len()]
+        })
+    }
+
+    #[instrument(skip_all)]
+    fn multi_sign_batch(
+        &self,
+        algorithm_id: AlgorithmId,
+        messages: Vec<Vec<u8>>,
+        key_id: KeyId,
+    ) -> Result<Vec<CspSignature>, CspMultiSignatureError> {
+        let messages: Vec<ByteBuf> = messages.into_iter().map(ByteBuf::from).collect();
+        self.call_with_retry("multi_sign_batch", |client| {
+            Box::pin(client.multi_sign_batch(
+                context_with_timeout(self.rpc_timeout),
+                algorithm_id,
+                messages.clone(),
+                key_id,
+            ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
     fn gen_committee_signing_key_pair(
         &self,
     ) -> Result<(CspPublicKey, CspPop), CspMultiSignatureKeygenError> {
-        self.tokio_block_on(
-            self.tarpc_csp_client
-                .gen_committee_signing_key_pair(context_with_timeout(self.rpc_timeout)),
-        )
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspMultiSignatureKeygenError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_non_idempotent("gen_committee_signing_key_pair", |client| {
+            Box::pin(client.gen_committee_signing_key_pair(context_with_timeout(self.rpc_timeout)))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 }
 
@@ -328,75 +829,73 @@ impl ThresholdSignatureCspVault for RemoteCspVault {
         message: Vec<u8>,
         key_id: KeyId,
     ) -> Result<CspSignature, CspThresholdSignError> {
-        self.tokio_block_on(self.tarpc_csp_client.threshold_sign(
-            context_with_timeout(self.rpc_timeout),
-            algorithm_id,
-            ByteBuf::from(message),
-            key_id,
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspThresholdSignError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_with_retry("threshold_sign", |client| {
+            Box::pin(client.threshold_sign(
+                context_with_timeout(self.rpc_timeout),
+                algorithm_id,
+                ByteBuf::from(message.clone()),
+                key_id,
+            ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
+    }
+
+    #[instrument(skip_all)]
+    fn combine_threshold_sig_shares(
+        &self,
+        shares: &BTreeMap<NodeId, CspSignature>,
+        transcript: &NiDkgTranscript,
+    ) -> Result<CspSignature, CspThresholdSignError> {
+        self.call_with_retry("combine_threshold_sig_shares", |client| {
+            Box::pin(client.combine_threshold_sig_shares(
+                context_with_timeout(self.rpc_timeout),
+                shares.clone(),
+                transcript.clone(),
+            ))
+        })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 }
 
 impl SecretKeyStoreCspVault for RemoteCspVault {
     #[instrument(skip_all)]
     fn sks_contains(&self, key_id: KeyId) -> Result<bool, CspSecretKeyStoreContainsError> {
-        self.tokio_block_on(
-            self.tarpc_csp_client
-                .sks_contains(context_with_timeout(self.rpc_timeout), key_id),
-        )
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspSecretKeyStoreContainsError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_with_retry("sks_contains", |client| {
+            Box::pin(client.sks_contains(context_with_timeout(self.rpc_timeout), key_id))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 }
 
 impl PublicKeyStoreCspVault for RemoteCspVault {
     #[instrument(skip_all)]
     fn current_node_public_keys(&self) -> Result<CurrentNodePublicKeys, CspPublicKeyStoreError> {
-        self.tokio_block_on(
-            self.tarpc_csp_client
-                .current_node_public_keys(context_with_timeout(self.rpc_timeout)),
-        )
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspPublicKeyStoreError::TransientInternalError(
-                rpc_error.to_string(),
-            ))
+        self.call_with_retry("current_node_public_keys", |client| {
+            Box::pin(client.current_node_public_keys(context_with_timeout(self.rpc_timeout)))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
     fn current_node_public_keys_with_timestamps(
         &self,
     ) -> Result<CurrentNodePublicKeys, CspPublicKeyStoreError> {
-        self.tokio_block_on(
-            self.tarpc_csp_client
-                .current_node_public_keys_with_timestamps(context_with_timeout(self.rpc_timeout)),
-        )
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspPublicKeyStoreError::TransientInternalError(
-                rpc_error.to_string(),
-            ))
+        self.call_with_retry("current_node_public_keys_with_timestamps", |client| {
+            Box::pin(
+                client.current_node_public_keys_with_timestamps(context_with_timeout(
+                    self.rpc_timeout,
+                )),
+            )
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
     fn idkg_dealing_encryption_pubkeys_count(&self) -> Result<usize, CspPublicKeyStoreError> {
-        self.tokio_block_on(
-            self.tarpc_csp_client
-                .idkg_key_count(context_with_timeout(self.rpc_timeout)),
-        )
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspPublicKeyStoreError::TransientInternalError(
-                rpc_error.to_string(),
-            ))
+        self.call_with_retry("idkg_key_count", |client| {
+            Box::pin(client.idkg_key_count(context_with_timeout(self.rpc_timeout)))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 }
 
@@ -406,28 +905,21 @@ impl PublicAndSecretKeyStoreCspVault for RemoteCspVault {
         &self,
         external_public_keys: ExternalPublicKeys,
     ) -> Result<(), PksAndSksContainsErrors> {
-        self.tokio_block_on(
-            self.tarpc_csp_client
-                .pks_and_sks_contains(context_with_timeout(self.rpc_timeout), external_public_keys),
-        )
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(PksAndSksContainsErrors::TransientInternalError(
-                rpc_error.to_string(),
+        self.call_with_retry("pks_and_sks_contains", |client| {
+            Box::pin(client.pks_and_sks_contains(
+                context_with_timeout(self.rpc_timeout),
+                external_public_keys.clone(),
             ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
     fn validate_pks_and_sks(&self) -> Result<ValidNodePublicKeys, ValidatePksAndSksError> {
-        self.tokio_block_on(
-            self.tarpc_csp_client
-                .validate_pks_and_sks(context_with_timeout(self.rpc_timeout)),
-        )
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(ValidatePksAndSksError::TransientInternalError(
-                rpc_error.to_string(),
-            ))
+        self.call_with_retry("validate_pks_and_sks", |client| {
+            Box::pin(client.validate_pks_and_sks(context_with_timeout(self.rpc_timeout)))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 }
 
@@ -437,15 +929,15 @@ impl NiDkgCspVault for RemoteCspVault {
         &self,
         node_id: NodeId,
     ) -> Result<(CspFsEncryptionPublicKey, CspFsEncryptionPop), CspDkgCreateFsKeyError> {
-        self.tokio_block_on(
-            self.tarpc_csp_client
-                .gen_dealing_encryption_key_pair(context_with_timeout(self.rpc_timeout), node_id),
-        )
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspDkgCreateFsKeyError::TransientInternalError(
-                rpc_error.to_string(),
-            ))
+        self.call_non_idempotent("gen_dealing_encryption_key_pair", |client| {
+            Box::pin(
+                client.gen_dealing_encryption_key_pair(
+                    context_with_timeout(self.rpc_timeout),
+                    node_id,
+                ),
+            )
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
@@ -455,19 +947,15 @@ impl NiDkgCspVault for RemoteCspVault {
         key_id: KeyId,
         epoch: Epoch,
     ) -> Result<(), CspDkgUpdateFsEpochError> {
-        self.tokio_block_on(self.tarpc_csp_client.update_forward_secure_epoch(
-            context_with_timeout(self.rpc_timeout),
-            algorithm_id,
-            key_id,
-            epoch,
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspDkgUpdateFsEpochError::TransientInternalError(
-                InternalError {
-                    internal_error: rpc_error.to_string(),
-                },
+        self.call_non_idempotent("update_forward_secure_epoch", |client| {
+            Box::pin(client.update_forward_secure_epoch(
+                context_with_timeout(self.rpc_timeout),
+                algorithm_id,
+                key_id,
+                epoch,
             ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
@@ -480,22 +968,43 @@ impl NiDkgCspVault for RemoteCspVault {
         receiver_keys: BTreeMap<NodeIndex, CspFsEncryptionPublicKey>,
         maybe_resharing_secret: Option<KeyId>,
     ) -> Result<CspNiDkgDealing, CspDkgCreateReshareDealingError> {
-        self.tokio_block_on(self.tarpc_csp_client.create_dealing(
-            context_with_timeout(self.rpc_timeout),
-            algorithm_id,
-            dealer_index,
-            threshold,
-            epoch,
-            receiver_keys,
-            maybe_resharing_secret,
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspDkgCreateReshareDealingError::TransientInternalError(
-                InternalError {
-                    internal_error: rpc_error.to_string(),
-                },
+        self.call_non_idempotent("create_dealing", |client| {
+            Box::pin(client.create_dealing(
+                context_with_timeout(self.rpc_timeout),
+                algorithm_id,
+                dealer_index,
+                threshold,
+                epoch,
+                receiver_keys.clone(),
+                maybe_resharing_secret,
             ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
+    }
+
+    #[instrument(skip_all)]
+    fn verify_dealings_batch(
+        &self,
+        algorithm_id: AlgorithmId,
+        threshold: NumberOfNodes,
+        epoch: Epoch,
+        receiver_keys: BTreeMap<NodeIndex, CspFsEncryptionPublicKey>,
+        dealings: &[(NodeIndex, CspNiDkgDealing)],
+    ) -> Vec<Result<(), CspDkgVerifyDealingError>> {
+        self.call_with_retry("verify_dealings_batch", |client| {
+            Box::pin(client.verify_dealings_batch(
+                context_with_timeout(self.rpc_timeout),
+                algorithm_id,
+                threshold,
+                epoch,
+                receiver_keys.clone(),
+                dealings.to_vec(),
+            ))
+        })
+        .unwrap_or_else(|error: RpcCallError| {
+            let error = CspDkgVerifyDealingError::from(error);
+            vec![Err(error); dealings.len()]
+        })
     }
 
     #[instrument(skip_all)]
@@ -507,21 +1016,17 @@ impl NiDkgCspVault for RemoteCspVault {
         fs_key_id: KeyId,
         receiver_index: NodeIndex,
     ) -> Result<(), CspDkgLoadPrivateKeyError> {
-        self.tokio_block_on(self.tarpc_csp_client.load_threshold_signing_key(
-            context_with_timeout(self.long_rpc_timeout),
-            algorithm_id,
-            epoch,
-            csp_transcript,
-            fs_key_id,
-            receiver_index,
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspDkgLoadPrivateKeyError::TransientInternalError(
-                InternalError {
-                    internal_error: rpc_error.to_string(),
-                },
+        self.call_with_retry("load_threshold_signing_key", |client| {
+            Box::pin(client.load_threshold_signing_key(
+                context_with_timeout(self.long_rpc_timeout),
+                algorithm_id,
+                epoch,
+                csp_transcript.clone(),
+                fs_key_id,
+                receiver_index,
             ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
@@ -529,32 +1034,39 @@ impl NiDkgCspVault for RemoteCspVault {
         &self,
         active_key_ids: BTreeSet<KeyId>,
     ) -> Result<(), CspDkgRetainThresholdKeysError> {
-        self.tokio_block_on(self.tarpc_csp_client.retain_threshold_keys_if_present(
-            context_with_timeout(self.rpc_timeout),
-            active_key_ids,
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspDkgRetainThresholdKeysError::TransientInternalError(
-                InternalError {
-                    internal_error: rpc_error.to_string(),
-                },
+        self.call_with_retry("retain_threshold_keys_if_present", |client| {
+            Box::pin(client.retain_threshold_keys_if_present(
+                context_with_timeout(self.rpc_timeout),
+                active_key_ids.clone(),
             ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 }
 
 impl TlsHandshakeCspVault for RemoteCspVault {
     #[instrument(skip_all)]
     fn gen_tls_key_pair(&self, node: NodeId) -> Result<TlsPublicKeyCert, CspTlsKeygenError> {
-        self.tokio_block_on(
-            self.tarpc_csp_client
-                .gen_tls_key_pair(context_with_timeout(self.rpc_timeout), node),
-        )
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspTlsKeygenError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_non_idempotent("gen_tls_key_pair", |client| {
+            Box::pin(client.gen_tls_key_pair(context_with_timeout(self.rpc_timeout), node))
+        })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
+    }
+
+    #[instrument(skip_all)]
+    fn gen_tls_key_pair_with_validity(
+        &self,
+        node: NodeId,
+        not_after: Time,
+    ) -> Result<TlsPublicKeyCert, CspTlsKeygenError> {
+        self.call_non_idempotent("gen_tls_key_pair_with_validity", |client| {
+            Box::pin(client.gen_tls_key_pair_with_validity(
+                context_with_timeout(self.rpc_timeout),
+                node,
+                not_after,
+            ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
@@ -567,16 +1079,14 @@ impl TlsHandshakeCspVault for RemoteCspVault {
         // `TlsHandshake::perform_tls_server_handshake`.
         #[allow(clippy::disallowed_methods)]
         tokio::task::block_in_place(|| {
-            self.tokio_block_on(self.tarpc_csp_client.tls_sign(
-                context_with_timeout(self.rpc_timeout),
-                ByteBuf::from(message),
-                key_id,
-            ))
-            .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-                Err(CspTlsSignError::TransientInternalError {
-                    internal_error: rpc_error.to_string(),
-                })
+            self.call_with_retry("tls_sign", |client| {
+                Box::pin(client.tls_sign(
+                    context_with_timeout(self.rpc_timeout),
+                    ByteBuf::from(message.clone()),
+                    key_id,
+                ))
             })
+            .unwrap_or_else(|error: RpcCallError| Err(error.into()))
         })
     }
 }
@@ -592,20 +1102,18 @@ impl IDkgProtocolCspVault for RemoteCspVault {
         receiver_keys: Vec<PublicKey>,
         transcript_operation: IDkgTranscriptOperation,
     ) -> Result<IDkgDealingInternalBytes, IDkgCreateDealingVaultError> {
-        self.tokio_block_on(self.tarpc_csp_client.idkg_create_dealing(
-            context_with_timeout(self.rpc_timeout),
-            algorithm_id,
-            ByteBuf::from(context_data),
-            dealer_index,
-            reconstruction_threshold,
-            receiver_keys,
-            transcript_operation,
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(IDkgCreateDealingVaultError::TransientInternalError(
-                rpc_error.to_string(),
+        self.call_non_idempotent("idkg_create_dealing", |client| {
+            Box::pin(client.idkg_create_dealing(
+                context_with_timeout(self.rpc_timeout),
+                algorithm_id,
+                ByteBuf::from(context_data.clone()),
+                dealer_index,
+                reconstruction_threshold,
+                receiver_keys.clone(),
+                transcript_operation.clone(),
             ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
@@ -618,20 +1126,18 @@ impl IDkgProtocolCspVault for RemoteCspVault {
         receiver_key_id: KeyId,
         context_data: Vec<u8>,
     ) -> Result<(), IDkgVerifyDealingPrivateError> {
-        self.tokio_block_on(self.tarpc_csp_client.idkg_verify_dealing_private(
-            context_with_timeout(self.rpc_timeout),
-            algorithm_id,
-            dealing,
-            dealer_index,
-            receiver_index,
-            receiver_key_id,
-            ByteBuf::from(context_data),
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(IDkgVerifyDealingPrivateError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_with_retry("idkg_verify_dealing_private", |client| {
+            Box::pin(client.idkg_verify_dealing_private(
+                context_with_timeout(self.rpc_timeout),
+                algorithm_id,
+                dealing.clone(),
+                dealer_index,
+                receiver_index,
+                receiver_key_id,
+                ByteBuf::from(context_data.clone()),
+            ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
@@ -644,20 +1150,18 @@ impl IDkgProtocolCspVault for RemoteCspVault {
         key_id: KeyId,
         transcript: IDkgTranscriptInternalBytes,
     ) -> Result<BTreeMap<NodeIndex, IDkgComplaintInternal>, IDkgLoadTranscriptError> {
-        self.tokio_block_on(self.tarpc_csp_client.idkg_load_transcript(
-            context_with_timeout(self.rpc_timeout),
-            algorithm_id,
-            dealings,
-            ByteBuf::from(context_data),
-            receiver_index,
-            key_id,
-            transcript,
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(IDkgLoadTranscriptError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_with_retry("idkg_load_transcript", |client| {
+            Box::pin(client.idkg_load_transcript(
+                context_with_timeout(self.rpc_timeout),
+                algorithm_id,
+                dealings.clone(),
+                ByteBuf::from(context_data.clone()),
+                receiver_index,
+                key_id,
+                transcript.clone(),
+            ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
@@ -671,21 +1175,19 @@ impl IDkgProtocolCspVault for RemoteCspVault {
         key_id: KeyId,
         transcript: IDkgTranscriptInternalBytes,
     ) -> Result<(), IDkgLoadTranscriptError> {
-        self.tokio_block_on(self.tarpc_csp_client.idkg_load_transcript_with_openings(
-            context_with_timeout(self.rpc_timeout),
-            alg,
-            dealings,
-            openings,
-            ByteBuf::from(context_data),
-            receiver_index,
-            key_id,
-            transcript,
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(IDkgLoadTranscriptError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_with_retry("idkg_load_transcript_with_openings", |client| {
+            Box::pin(client.idkg_load_transcript_with_openings(
+                context_with_timeout(self.rpc_timeout),
+                alg,
+                dealings.clone(),
+                openings.clone(),
+                ByteBuf::from(context_data.clone()),
+                receiver_index,
+                key_id,
+                transcript.clone(),
+            ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
@@ -694,29 +1196,24 @@ impl IDkgProtocolCspVault for RemoteCspVault {
         active_key_ids: BTreeSet<KeyId>,
         oldest_public_key: MEGaPublicKey,
     ) -> Result<(), IDkgRetainKeysError> {
-        self.tokio_block_on(self.tarpc_csp_client.idkg_retain_active_keys(
-            context_with_timeout(self.rpc_timeout),
-            active_key_ids,
-            oldest_public_key,
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(IDkgRetainKeysError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_with_retry("idkg_retain_active_keys", |client| {
+            Box::pin(client.idkg_retain_active_keys(
+                context_with_timeout(self.rpc_timeout),
+                active_key_ids.clone(),
+                oldest_public_key.clone(),
+            ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
     fn idkg_gen_dealing_encryption_key_pair(&self) -> Result<MEGaPublicKey, CspCreateMEGaKeyError> {
-        self.tokio_block_on(
-            self.tarpc_csp_client
-                .idkg_gen_dealing_encryption_key_pair(context_with_timeout(self.rpc_timeout)),
-        )
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(CspCreateMEGaKeyError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_non_idempotent("idkg_gen_dealing_encryption_key_pair", |client| {
+            Box::pin(
+                client.idkg_gen_dealing_encryption_key_pair(context_with_timeout(self.rpc_timeout)),
+            )
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 
     #[instrument(skip_all)]
@@ -729,20 +1226,18 @@ impl IDkgProtocolCspVault for RemoteCspVault {
         opener_index: NodeIndex,
         opener_key_id: KeyId,
     ) -> Result<CommitmentOpening, IDkgOpenTranscriptError> {
-        self.tokio_block_on(self.tarpc_csp_client.idkg_open_dealing(
-            context_with_timeout(self.rpc_timeout),
-            alg,
-            dealing,
-            dealer_index,
-            ByteBuf::from(context_data),
-            opener_index,
-            opener_key_id,
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(IDkgOpenTranscriptError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_with_retry("idkg_open_dealing", |client| {
+            Box::pin(client.idkg_open_dealing(
+                context_with_timeout(self.rpc_timeout),
+                alg,
+                dealing.clone(),
+                dealer_index,
+                ByteBuf::from(context_data.clone()),
+                opener_index,
+                opener_key_id,
+            ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 }
 
@@ -761,23 +1256,21 @@ impl ThresholdEcdsaSignerCspVault for RemoteCspVault {
         key_times_lambda_raw: IDkgTranscriptInternalBytes,
         algorithm_id: AlgorithmId,
     ) -> Result<ThresholdEcdsaSigShareInternal, ThresholdEcdsaCreateSigShareError> {
-        self.tokio_block_on(self.tarpc_csp_client.create_ecdsa_sig_share(
-            context_with_timeout(self.rpc_timeout),
-            derivation_path,
-            ByteBuf::from(hashed_message),
-            nonce,
-            key_raw,
-            kappa_unmasked_raw,
-            lambda_masked_raw,
-            kappa_times_lambda_raw,
-            key_times_lambda_raw,
-            algorithm_id,
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(ThresholdEcdsaCreateSigShareError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_with_retry("create_ecdsa_sig_share", |client| {
+            Box::pin(client.create_ecdsa_sig_share(
+                context_with_timeout(self.rpc_timeout),
+                derivation_path.clone(),
+                ByteBuf::from(hashed_message.clone()),
+                nonce,
+                key_raw.clone(),
+                kappa_unmasked_raw.clone(),
+                lambda_masked_raw.clone(),
+                kappa_times_lambda_raw.clone(),
+                key_times_lambda_raw.clone(),
+                algorithm_id,
+            ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 }
 
@@ -794,37 +1287,56 @@ impl ThresholdSchnorrSignerCspVault for RemoteCspVault {
         presig_raw: IDkgTranscriptInternalBytes,
         algorithm_id: AlgorithmId,
     ) -> Result<ThresholdSchnorrSigShareBytes, ThresholdSchnorrCreateSigShareVaultError> {
-        self.tokio_block_on(self.tarpc_csp_client.create_schnorr_sig_share(
-            context_with_timeout(self.rpc_timeout),
-            derivation_path,
-            ByteBuf::from(message),
-            taproot_tree_root.map(ByteBuf::from),
-            nonce,
-            key_raw,
-            presig_raw,
-            algorithm_id,
-        ))
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(
-                ThresholdSchnorrCreateSigShareVaultError::TransientInternalError(
-                    rpc_error.to_string(),
-                ),
-            )
+        self.call_with_retry("create_schnorr_sig_share", |client| {
+            Box::pin(client.create_schnorr_sig_share(
+                context_with_timeout(self.rpc_timeout),
+                derivation_path.clone(),
+                ByteBuf::from(message.clone()),
+                taproot_tree_root.clone().map(ByteBuf::from),
+                nonce,
+                key_raw.clone(),
+                presig_raw.clone(),
+                algorithm_id,
+            ))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 }
 
 impl PublicRandomSeedGenerator for RemoteCspVault {
     #[instrument(skip_all)]
     fn new_public_seed(&self) -> Result<Seed, PublicRandomSeedGeneratorError> {
-        self.tokio_block_on(
-            self.tarpc_csp_client
-                .new_public_seed(context_with_timeout(self.rpc_timeout)),
-        )
-        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
-            Err(PublicRandomSeedGeneratorError::TransientInternalError {
-                internal_error: rpc_error.to_string(),
-            })
+        self.call_non_idempotent("new_public_seed", |client| {
+            Box::pin(client.new_public_seed(context_with_timeout(self.rpc_timeout)))
+        })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
+    }
+}
+
+impl HealthStatusCspVault for RemoteCspVault {
+    #[instrument(skip_all)]
+    fn health_status(&self) -> Result<CspVaultHealthStatus, CspVaultHealthStatusError> {
+        self.call_with_retry("health_status", |client| {
+            Box::pin(client.health_status(context_with_timeout(self.rpc_timeout)))
+        })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
+    }
+}
+
+impl KeyActivationCspVault for RemoteCspVault {
+    #[instrument(skip_all)]
+    fn mark_key_inactive(&self, key_id: KeyId) -> Result<(), CspVaultKeyActivationError> {
+        self.call_with_retry("mark_key_inactive", |client| {
+            Box::pin(client.mark_key_inactive(context_with_timeout(self.rpc_timeout), key_id))
+        })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
+    }
+
+    #[instrument(skip_all)]
+    fn mark_key_active(&self, key_id: KeyId) -> Result<(), CspVaultKeyActivationError> {
+        self.call_with_retry("mark_key_active", |client| {
+            Box::pin(client.mark_key_active(context_with_timeout(self.rpc_timeout), key_id))
         })
+        .unwrap_or_else(|error: RpcCallError| Err(error.into()))
     }
 }
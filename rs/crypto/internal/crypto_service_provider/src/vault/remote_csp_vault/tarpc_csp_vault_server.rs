@@ -2,15 +2,17 @@ use crate::api::{CspCreateMEGaKeyError, CspThresholdSignError};
 use crate::key_id::KeyId;
 use crate::types::{CspPop, CspPublicKey, CspSignature};
 use crate::vault::api::{
-    CspBasicSignatureError, CspBasicSignatureKeygenError, CspMultiSignatureError,
-    CspMultiSignatureKeygenError, CspSecretKeyStoreContainsError, CspTlsKeygenError,
-    CspTlsSignError, IDkgCreateDealingVaultError, PublicRandomSeedGeneratorError,
+    CspBasicSignatureError, CspBasicSignatureKeygenAlgorithm, CspBasicSignatureKeygenError,
+    CspMultiSignatureError, CspMultiSignatureKeygenError, CspSecretKeyStoreContainsError,
+    CspTlsKeygenError, CspTlsSignError, CspVaultHealthStatus, CspVaultHealthStatusError,
+    CspVaultKeyActivationError, IDkgCreateDealingVaultError, PublicRandomSeedGeneratorError,
     ThresholdSchnorrSigShareBytes, ValidatePksAndSksError,
 };
 use crate::vault::api::{
     CspPublicKeyStoreError, CspVault, IDkgDealingInternalBytes, IDkgTranscriptInternalBytes,
 };
 use crate::vault::local_csp_vault::{LocalCspVault, ProdLocalCspVault};
+use crate::vault::remote_csp_vault::tls::VaultServerTlsConfig;
 use crate::vault::remote_csp_vault::ThresholdSchnorrCreateSigShareVaultError;
 use crate::vault::remote_csp_vault::{remote_vault_codec_builder, TarpcCspVault};
 use crate::vault::remote_csp_vault::{PksAndSksContainsErrors, FOUR_GIGA_BYTES};
@@ -20,7 +22,7 @@ use ic_crypto_internal_logmon::metrics::CryptoMetrics;
 use ic_crypto_internal_seed::Seed;
 use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors::{
     CspDkgCreateFsKeyError, CspDkgCreateReshareDealingError, CspDkgLoadPrivateKeyError,
-    CspDkgRetainThresholdKeysError, CspDkgUpdateFsEpochError,
+    CspDkgRetainThresholdKeysError, CspDkgUpdateFsEpochError, CspDkgVerifyDealingError,
 };
 use ic_crypto_internal_threshold_sig_canister_threshold_sig::{
     CommitmentOpening, IDkgComplaintInternal, MEGaPublicKey, ThresholdEcdsaSigShareInternal,
@@ -44,9 +46,10 @@ use ic_types::crypto::canister_threshold_sig::error::{
 use ic_types::crypto::canister_threshold_sig::idkg::{
     BatchSignedIDkgDealing, IDkgTranscriptOperation,
 };
+use ic_types::crypto::threshold_sig::ni_dkg::NiDkgTranscript;
 use ic_types::crypto::ExtendedDerivationPath;
 use ic_types::crypto::{AlgorithmId, CurrentNodePublicKeys};
-use ic_types::{NodeId, NumberOfNodes, Randomness};
+use ic_types::{NodeId, NumberOfNodes, Randomness, Time};
 use rayon::{ThreadPool, ThreadPoolBuilder};
 use serde_bytes::ByteBuf;
 use std::collections::{BTreeMap, BTreeSet};
@@ -56,9 +59,12 @@ use tarpc::server::BaseChannel;
 #[allow(unused_imports)]
 use tarpc::server::Serve;
 use tarpc::{context, serde_transport, server::Channel};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::UnixListener;
+use tokio_util::codec::length_delimited::Builder;
 
 use super::codec::{Bincode, CspVaultObserver, ObservableCodec};
+use super::compression::{CompressingCodec, CompressionEncoding};
 
 /// Crypto service provider (CSP) vault server based on the tarpc RPC framework.
 pub struct TarpcCspVaultServerImpl<C: CspVault> {
@@ -66,6 +72,8 @@ pub struct TarpcCspVaultServerImpl<C: CspVault> {
     listener: UnixListener,
     thread_pool: Arc<ThreadPool>,
     max_frame_length: usize,
+    tls_config: Option<VaultServerTlsConfig>,
+    compression: Option<CompressionEncoding>,
     metrics: Arc<CryptoMetrics>,
     #[allow(unused)]
     logger: ReplicaLogger,
@@ -135,6 +143,16 @@ impl<C: CspVault + 'static> TarpcCspVault for TarpcCspVaultServerWorker<C> {
         execute_on_thread_pool(&self.thread_pool, job).await
     }
 
+    async fn gen_basic_signature_key_pair(
+        self,
+        _: context::Context,
+        algorithm: CspBasicSignatureKeygenAlgorithm,
+    ) -> Result<CspPublicKey, CspBasicSignatureKeygenError> {
+        let vault = self.local_csp_vault;
+        let job = move || vault.gen_basic_signature_key_pair(algorithm);
+        execute_on_thread_pool(&self.thread_pool, job).await
+    }
+
     // `MultiSignatureCspVault`-methods.
     async fn multi_sign(
         self,
@@ -148,6 +166,36 @@ impl<C: CspVault + 'static> TarpcCspVault for TarpcCspVaultServerWorker<C> {
         execute_on_thread_pool(&self.thread_pool, job).await
     }
 
+    async fn batch_sign(
+        self,
+        _: context::Context,
+        algorithm_id: AlgorithmId,
+        messages: Vec<ByteBuf>,
+        key_id: KeyId,
+    ) -> Vec<Result<CspSignature, CspMultiSignatureError>> {
+        let vault = self.local_csp_vault;
+        let job = move || {
+            let messages: Vec<&[u8]> = messages.iter().map(|message| message.as_slice()).collect();
+            vault.batch_sign(algorithm_id, &messages, key_id)
+        };
+        execute_on_thread_pool(&self.thread_pool, job).await
+    }
+
+    async fn multi_sign_batch(
+        self,
+        _: context::Context,
+        algorithm_id: AlgorithmId,
+        messages: Vec<ByteBuf>,
+        key_id: KeyId,
+    ) -> Result<Vec<CspSignature>, CspMultiSignatureError> {
+        let vault = self.local_csp_vault;
+        let job = move || {
+            let messages = messages.into_iter().map(ByteBuf::into_vec).collect();
+            vault.multi_sign_batch(algorithm_id, messages, key_id)
+        };
+        execute_on_thread_pool(&self.thread_pool, job).await
+    }
+
     async fn gen_committee_signing_key_pair(
         self,
         _: context::Context,
@@ -170,6 +218,17 @@ impl<C: CspVault + 'static> TarpcCspVault for TarpcCspVaultServerWorker<C> {
         execute_on_thread_pool(&self.thread_pool, job).await
     }
 
+    async fn combine_threshold_sig_shares(
+        self,
+        _: context::Context,
+        shares: BTreeMap<NodeId, CspSignature>,
+        transcript: NiDkgTranscript,
+    ) -> Result<CspSignature, CspThresholdSignError> {
+        let vault = self.local_csp_vault;
+        let job = move || vault.combine_threshold_sig_shares(&shares, &transcript);
+        execute_on_thread_pool(&self.thread_pool, job).await
+    }
+
     // `NiDkgCspVault`-methods.
     async fn gen_dealing_encryption_key_pair(
         self,
@@ -217,6 +276,22 @@ impl<C: CspVault + 'static> TarpcCspVault for TarpcCspVaultServerWorker<C> {
         execute_on_thread_pool(&self.thread_pool, job).await
     }
 
+    async fn verify_dealings_batch(
+        self,
+        _: context::Context,
+        algorithm_id: AlgorithmId,
+        threshold: NumberOfNodes,
+        epoch: Epoch,
+        receiver_keys: BTreeMap<NodeIndex, CspFsEncryptionPublicKey>,
+        dealings: Vec<(NodeIndex, CspNiDkgDealing)>,
+    ) -> Vec<Result<(), CspDkgVerifyDealingError>> {
+        let vault = self.local_csp_vault;
+        let job = move || {
+            vault.verify_dealings_batch(algorithm_id, threshold, epoch, receiver_keys, &dealings)
+        };
+        execute_on_thread_pool(&self.thread_pool, job).await
+    }
+
     async fn load_threshold_signing_key(
         self,
         _: context::Context,
@@ -316,6 +391,17 @@ impl<C: CspVault + 'static> TarpcCspVault for TarpcCspVaultServerWorker<C> {
         execute_on_thread_pool(&self.thread_pool, job).await
     }
 
+    async fn gen_tls_key_pair_with_validity(
+        self,
+        _: context::Context,
+        node: NodeId,
+        not_after: Time,
+    ) -> Result<TlsPublicKeyCert, CspTlsKeygenError> {
+        let vault = self.local_csp_vault;
+        let job = move || vault.gen_tls_key_pair_with_validity(node, not_after);
+        execute_on_thread_pool(&self.thread_pool, job).await
+    }
+
     async fn tls_sign(
         self,
         _: context::Context,
@@ -536,13 +622,46 @@ impl<C: CspVault + 'static> TarpcCspVault for TarpcCspVaultServerWorker<C> {
         let job = move || vault.new_public_seed();
         execute_on_thread_pool(&self.thread_pool, job).await
     }
+
+    // `HealthStatusCspVault`-methods.
+    async fn health_status(
+        self,
+        _: context::Context,
+    ) -> Result<CspVaultHealthStatus, CspVaultHealthStatusError> {
+        let vault = self.local_csp_vault;
+        let job = move || vault.health_status();
+        execute_on_thread_pool(&self.thread_pool, job).await
+    }
+
+    // `KeyActivationCspVault`-methods.
+    async fn mark_key_inactive(
+        self,
+        _: context::Context,
+        key_id: KeyId,
+    ) -> Result<(), CspVaultKeyActivationError> {
+        let vault = self.local_csp_vault;
+        let job = move || vault.mark_key_inactive(key_id);
+        execute_on_thread_pool(&self.thread_pool, job).await
+    }
+
+    async fn mark_key_active(
+        self,
+        _: context::Context,
+        key_id: KeyId,
+    ) -> Result<(), CspVaultKeyActivationError> {
+        let vault = self.local_csp_vault;
+        let job = move || vault.mark_key_active(key_id);
+        execute_on_thread_pool(&self.thread_pool, job).await
+    }
 }
 
 type VaultFactory<C> = dyn Fn(&ReplicaLogger, Arc<CryptoMetrics>) -> Arc<C> + Send + Sync;
 
 pub struct TarpcCspVaultServerImplBuilder<C> {
-    local_csp_vault_factory: Box<VaultFactory<C>>,
+    local_csp_vault_factory: Arc<VaultFactory<C>>,
     max_frame_length: usize,
+    tls_config: Option<VaultServerTlsConfig>,
+    compression: Option<CompressionEncoding>,
     logger: ReplicaLogger,
     metrics: Arc<CryptoMetrics>,
 }
@@ -550,7 +669,7 @@ pub struct TarpcCspVaultServerImplBuilder<C> {
 impl TarpcCspVaultServerImplBuilder<ProdLocalCspVault> {
     pub fn new(key_store_dir: &Path) -> Self {
         let key_store_path = key_store_dir.to_path_buf();
-        let local_csp_vault_factory = Box::new(move |logger: &ReplicaLogger, metrics| {
+        let local_csp_vault_factory = Arc::new(move |logger: &ReplicaLogger, metrics| {
             Arc::new(LocalCspVault::new_in_dir(
                 &key_store_path,
                 metrics,
@@ -564,16 +683,18 @@ impl TarpcCspVaultServerImplBuilder<ProdLocalCspVault> {
 impl<C: 'static + Send + Sync> TarpcCspVaultServerImplBuilder<C> {
     pub fn new_with_local_csp_vault(local_csp_vault: Arc<C>) -> Self {
         let local_csp_vault_factory =
-            Box::new(move |_logger: &ReplicaLogger, _metrics| Arc::clone(&local_csp_vault));
+            Arc::new(move |_logger: &ReplicaLogger, _metrics| Arc::clone(&local_csp_vault));
         Self::new_internal(local_csp_vault_factory)
     }
 }
 
 impl<C> TarpcCspVaultServerImplBuilder<C> {
-    fn new_internal(local_csp_vault_factory: Box<VaultFactory<C>>) -> Self {
+    fn new_internal(local_csp_vault_factory: Arc<VaultFactory<C>>) -> Self {
         TarpcCspVaultServerImplBuilder {
             local_csp_vault_factory,
             max_frame_length: FOUR_GIGA_BYTES,
+            tls_config: None,
+            compression: None,
             logger: no_op_logger(),
             metrics: Arc::new(CryptoMetrics::none()),
         }
@@ -593,6 +714,34 @@ impl<C> TarpcCspVaultServerImplBuilder<C> {
         self.max_frame_length = max_frame_length;
         self
     }
+
+    /// Requires clients to connect via TLS, with the server authenticating
+    /// itself according to `tls_config`.
+    pub fn with_tls_config(mut self, tls_config: VaultServerTlsConfig) -> Self {
+        self.tls_config = Some(tls_config);
+        self
+    }
+
+    /// Compresses the responses sent to clients with `encoding`. Requests are
+    /// decompressed regardless of this setting.
+    pub fn with_compression(mut self, encoding: CompressionEncoding) -> Self {
+        self.compression = Some(encoding);
+        self
+    }
+}
+
+// Not derived, as that would require `C: Clone`.
+impl<C> Clone for TarpcCspVaultServerImplBuilder<C> {
+    fn clone(&self) -> Self {
+        TarpcCspVaultServerImplBuilder {
+            local_csp_vault_factory: Arc::clone(&self.local_csp_vault_factory),
+            max_frame_length: self.max_frame_length,
+            tls_config: self.tls_config.clone(),
+            compression: self.compression,
+            logger: self.logger.clone(),
+            metrics: Arc::clone(&self.metrics),
+        }
+    }
 }
 
 impl<C: CspVault> TarpcCspVaultServerImplBuilder<C> {
@@ -616,6 +765,8 @@ impl<C: CspVault> TarpcCspVaultServerImplBuilder<C> {
                     .expect("failed to instantiate a thread pool"),
             ),
             max_frame_length: self.max_frame_length,
+            tls_config: self.tls_config.clone(),
+            compression: self.compression,
             metrics: Arc::clone(&self.metrics),
             logger: new_logger!(&self.logger),
         }
@@ -655,29 +806,74 @@ impl<C: CspVault + 'static> TarpcCspVaultServerImpl<C> {
             });
             let local_csp_vault = Arc::clone(&self.local_csp_vault);
             let thread_pool = Arc::clone(&self.thread_pool);
-            let codec = ObservableCodec::new(
-                Bincode::default(),
-                CspVaultObserver::new(new_logger!(&self.logger), Arc::clone(&self.metrics)),
-            );
+            let observer =
+                CspVaultObserver::new(new_logger!(&self.logger), Arc::clone(&self.metrics));
+            let worker = TarpcCspVaultServerWorker {
+                local_csp_vault,
+                thread_pool,
+            };
+            let tls_config = self.tls_config.clone();
+            let compression = self.compression;
+            let max_frame_length = self.max_frame_length;
+            let logger = new_logger!(&self.logger);
             tokio::spawn(async move {
-                let framed = codec_builder.new_framed(conn);
-                let transport = serde_transport::new(framed, codec);
-                let worker = TarpcCspVaultServerWorker {
-                    local_csp_vault,
-                    thread_pool,
-                };
-                let channel = BaseChannel::with_defaults(transport);
-                channel
-                    .execute(worker.serve())
-                    .for_each(|rpc| async {
-                        tokio::spawn(rpc);
-                    })
-                    .await;
+                match tls_config {
+                    None => {
+                        serve_connection(
+                            conn,
+                            &codec_builder,
+                            compression,
+                            max_frame_length,
+                            observer,
+                            worker,
+                        )
+                        .await
+                    }
+                    Some(tls_config) => match tls_config.acceptor().accept(conn).await {
+                        Ok(tls_stream) => {
+                            serve_connection(
+                                tls_stream,
+                                &codec_builder,
+                                compression,
+                                max_frame_length,
+                                observer,
+                                worker,
+                            )
+                            .await
+                        }
+                        Err(e) => {
+                            warn!(logger, "TLS handshake with CSP vault client failed: {}", e)
+                        }
+                    },
+                }
             });
         }
     }
 }
 
+async fn serve_connection<C, S>(
+    conn: S,
+    codec_builder: &Builder,
+    compression: Option<CompressionEncoding>,
+    max_frame_length: usize,
+    observer: CspVaultObserver,
+    worker: TarpcCspVaultServerWorker<C>,
+) where
+    C: CspVault + 'static,
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let framed = codec_builder.new_framed(conn);
+    let codec = CompressingCodec::new(Bincode::default(), compression, max_frame_length);
+    let transport = serde_transport::new(framed, ObservableCodec::new(codec, observer));
+    let channel = BaseChannel::with_defaults(transport);
+    channel
+        .execute(worker.serve())
+        .for_each(|rpc| async {
+            tokio::spawn(rpc);
+        })
+        .await;
+}
+
 impl<C: CspVault> Drop for TarpcCspVaultServerImpl<C> {
     fn drop(&mut self) {
         warn!(self.logger, "Dropping RPC CSP vault server")
@@ -79,8 +79,8 @@ mod timeout {
         let gen_key_result = csp_vault.gen_dealing_encryption_key_pair(node_id);
 
         assert_matches!(gen_key_result,
-            Err(CspDkgCreateFsKeyError::TransientInternalError ( internal_error ))
-            if internal_error.contains("the request exceeded its deadline")
+            Err(CspDkgCreateFsKeyError::Timeout { method, .. })
+            if method == "gen_dealing_encryption_key_pair"
         );
     }
 }
@@ -346,11 +346,11 @@ mod logging {
             .has_only_one_message_containing(&Level::Debug, "Instantiated remote CSP vault client")
             .has_only_one_message_containing(
                 &Level::Debug,
-                "CSP vault client sent 37 bytes (request to 'gen_node_signing_key_pair')",
+                "CSP vault client sent 38 bytes (request to 'gen_node_signing_key_pair')",
             )
             .has_only_one_message_containing(
                 &Level::Debug,
-                "CSP vault client received 38 bytes (response of 'gen_node_signing_key_pair')",
+                "CSP vault client received 39 bytes (response of 'gen_node_signing_key_pair')",
             );
     }
 }
@@ -437,6 +437,126 @@ mod single_call_bincode {
     }
 }
 
+mod compression {
+    use crate::vault::remote_csp_vault::codec::Bincode;
+    use crate::vault::remote_csp_vault::compression::CompressingCodec;
+    use crate::vault::remote_csp_vault::CompressionEncoding;
+    use assert_matches::assert_matches;
+    use bytes::BytesMut;
+    use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+    use rand::RngCore;
+    use std::pin::Pin;
+    use tokio_serde::{Deserializer, Serializer};
+
+    const MAX_LENGTH: usize = 1024 * 1024;
+    const ENCODINGS: [Option<CompressionEncoding>; 3] = [
+        None,
+        Some(CompressionEncoding::Gzip),
+        Some(CompressionEncoding::Zstd),
+    ];
+
+    type BytesCodec = CompressingCodec<Bincode<Vec<u8>, Vec<u8>>>;
+
+    fn codec(encoding: Option<CompressionEncoding>) -> BytesCodec {
+        CompressingCodec::new(Bincode::default(), encoding, MAX_LENGTH)
+    }
+
+    fn encode(encoding: Option<CompressionEncoding>, message: &Vec<u8>) -> BytesMut {
+        let bytes = Pin::new(&mut codec(encoding))
+            .serialize(message)
+            .expect("failed to serialize");
+        BytesMut::from(&bytes[..])
+    }
+
+    fn decode(encoding: Option<CompressionEncoding>, frame: &BytesMut) -> std::io::Result<Vec<u8>> {
+        Pin::new(&mut codec(encoding)).deserialize(frame)
+    }
+
+    fn compressible_message() -> Vec<u8> {
+        b"a highly repetitive message ".repeat(100)
+    }
+
+    fn incompressible_message() -> Vec<u8> {
+        let mut message = vec![0; 1000];
+        reproducible_rng().fill_bytes(&mut message);
+        message
+    }
+
+    #[test]
+    fn should_decode_message_of_any_encoding_regardless_of_own_encoding() {
+        for message in [compressible_message(), incompressible_message()] {
+            for sender_encoding in ENCODINGS {
+                let frame = encode(sender_encoding, &message);
+                for receiver_encoding in ENCODINGS {
+                    assert_eq!(
+                        decode(receiver_encoding, &frame).expect("failed to deserialize"),
+                        message,
+                        "sent with {sender_encoding:?}, received with {receiver_encoding:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_compress_compressible_message() {
+        let message = compressible_message();
+        let uncompressed = encode(None, &message);
+        for encoding in [CompressionEncoding::Gzip, CompressionEncoding::Zstd] {
+            let compressed = encode(Some(encoding), &message);
+            assert!(
+                compressed.len() < uncompressed.len() / 2,
+                "{encoding:?}: {} !< {} / 2",
+                compressed.len(),
+                uncompressed.len()
+            );
+        }
+    }
+
+    #[test]
+    fn should_not_compress_incompressible_message() {
+        let message = incompressible_message();
+        let uncompressed = encode(None, &message);
+        for encoding in [CompressionEncoding::Gzip, CompressionEncoding::Zstd] {
+            assert_eq!(encode(Some(encoding), &message), uncompressed);
+        }
+    }
+
+    #[test]
+    fn should_fail_to_decode_unknown_encoding() {
+        let mut frame = encode(None, &compressible_message());
+        frame[0] = 42;
+
+        let result = decode(None, &frame);
+
+        assert_matches!(result, Err(e)
+            if e.to_string().contains("unknown compression encoding 42")
+        );
+    }
+
+    #[test]
+    fn should_fail_to_decode_empty_frame() {
+        let result = decode(None, &BytesMut::new());
+
+        assert_matches!(result, Err(e) if e.to_string().contains("empty frame"));
+    }
+
+    #[test]
+    fn should_fail_to_decode_message_exceeding_maximum_length_when_decompressed() {
+        let message = vec![0; MAX_LENGTH + 1];
+        for encoding in [CompressionEncoding::Gzip, CompressionEncoding::Zstd] {
+            let frame = encode(Some(encoding), &message);
+            assert!(frame.len() < MAX_LENGTH);
+
+            let result = decode(None, &frame);
+
+            assert_matches!(result, Err(e)
+                if e.to_string().contains("exceeds the maximum length")
+            );
+        }
+    }
+}
+
 mod worker_thread_pool {
     // This test is to ensure that the number of threads returned by
     // `std::thread::available_parallelism()` is the same as was set by the
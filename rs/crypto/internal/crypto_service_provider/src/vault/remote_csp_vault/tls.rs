@@ -0,0 +1,131 @@
+//! TLS for the connection between the remote CSP vault client and server.
+//!
+//! By default, client and server talk over a plain Unix socket. If TLS is
+//! configured, the server authenticates itself with a user-supplied
+//! certificate and the client only accepts servers whose certificate chains
+//! up to a user-supplied trust anchor.
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::{ClientConfig, RootCertStore, ServerConfig};
+use std::fmt;
+use std::sync::Arc;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+/// The server name the client expects in the server's certificate, unless
+/// configured otherwise with [`VaultClientTlsConfig::with_server_name`].
+pub const DEFAULT_VAULT_SERVER_NAME: &str = "localhost";
+
+#[derive(Clone, Eq, PartialEq, Debug, thiserror::Error)]
+pub enum VaultTlsConfigError {
+    #[error("malformed PEM: {0}")]
+    MalformedPem(String),
+    #[error("invalid server name {0:?}")]
+    InvalidServerName(String),
+    #[error("invalid TLS config: {0}")]
+    InvalidConfig(String),
+}
+
+/// TLS config of the remote CSP vault server.
+#[derive(Clone)]
+pub struct VaultServerTlsConfig {
+    acceptor: TlsAcceptor,
+}
+
+impl VaultServerTlsConfig {
+    /// Creates a server config from the PEM-encoded certificate chain (leaf
+    /// first) and the PEM-encoded private key of the server.
+    pub fn from_pem(cert_pem: &[u8], key_pem: &[u8]) -> Result<Self, VaultTlsConfigError> {
+        let cert_chain = CertificateDer::pem_slice_iter(cert_pem)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| VaultTlsConfigError::MalformedPem(format!("certificate: {e}")))?;
+        if cert_chain.is_empty() {
+            return Err(VaultTlsConfigError::MalformedPem(
+                "no certificate found".to_string(),
+            ));
+        }
+        let key = PrivateKeyDer::from_pem_slice(key_pem)
+            .map_err(|e| VaultTlsConfigError::MalformedPem(format!("private key: {e}")))?;
+        let config =
+            ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .map_err(|e| VaultTlsConfigError::InvalidConfig(e.to_string()))?
+                .with_no_client_auth()
+                .with_single_cert(cert_chain, key)
+                .map_err(|e| VaultTlsConfigError::InvalidConfig(e.to_string()))?;
+        Ok(Self {
+            acceptor: TlsAcceptor::from(Arc::new(config)),
+        })
+    }
+
+    pub(crate) fn acceptor(&self) -> &TlsAcceptor {
+        &self.acceptor
+    }
+}
+
+impl fmt::Debug for VaultServerTlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VaultServerTlsConfig")
+            .finish_non_exhaustive()
+    }
+}
+
+/// TLS config of the remote CSP vault client.
+#[derive(Clone)]
+pub struct VaultClientTlsConfig {
+    connector: TlsConnector,
+    server_name: ServerName<'static>,
+}
+
+impl VaultClientTlsConfig {
+    /// Creates a client config that trusts the PEM-encoded certificates in
+    /// `trust_anchor_pem`, e.g. the CA that issued the server's certificate.
+    pub fn from_pem(trust_anchor_pem: &[u8]) -> Result<Self, VaultTlsConfigError> {
+        let mut roots = RootCertStore::empty();
+        for cert in CertificateDer::pem_slice_iter(trust_anchor_pem) {
+            let cert =
+                cert.map_err(|e| VaultTlsConfigError::MalformedPem(format!("trust anchor: {e}")))?;
+            roots
+                .add(cert)
+                .map_err(|e| VaultTlsConfigError::InvalidConfig(e.to_string()))?;
+        }
+        if roots.is_empty() {
+            return Err(VaultTlsConfigError::MalformedPem(
+                "no trust anchor found".to_string(),
+            ));
+        }
+        let config =
+            ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .map_err(|e| VaultTlsConfigError::InvalidConfig(e.to_string()))?
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+        Ok(Self {
+            connector: TlsConnector::from(Arc::new(config)),
+            server_name: ServerName::try_from(DEFAULT_VAULT_SERVER_NAME)
+                .expect("default server name is valid"),
+        })
+    }
+
+    /// Sets the name the server's certificate must be valid for.
+    pub fn with_server_name(mut self, server_name: &str) -> Result<Self, VaultTlsConfigError> {
+        self.server_name = ServerName::try_from(server_name.to_string())
+            .map_err(|_| VaultTlsConfigError::InvalidServerName(server_name.to_string()))?;
+        Ok(self)
+    }
+
+    pub(crate) fn connector(&self) -> &TlsConnector {
+        &self.connector
+    }
+
+    pub(crate) fn server_name(&self) -> ServerName<'static> {
+        self.server_name.clone()
+    }
+}
+
+impl fmt::Debug for VaultClientTlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VaultClientTlsConfig")
+            .field("server_name", &self.server_name)
+            .finish_non_exhaustive()
+    }
+}
@@ -2,18 +2,23 @@ use assert_matches::assert_matches;
 use ic_crypto_internal_basic_sig_ed25519 as ed25519;
 use ic_crypto_internal_csp::key_id::KeyId;
 use ic_crypto_internal_csp::types::{CspPublicKey, CspSignature};
+use ic_crypto_internal_csp::vault::api::{
+    CspBasicSignatureError, CspBasicSignatureKeygenAlgorithm,
+};
 use ic_crypto_internal_csp_proptest_utils::{
-    arb_algorithm_id, arb_csp_basic_signature_error, arb_csp_basic_signature_keygen_error,
-    arb_csp_public_key, arb_csp_signature, arb_key_id,
+    arb_algorithm_id, arb_csp_basic_signature_error, arb_csp_basic_signature_keygen_algorithm,
+    arb_csp_basic_signature_keygen_error, arb_csp_public_key, arb_csp_signature, arb_key_id,
 };
 use ic_crypto_temp_crypto_vault::RemoteVaultEnvironment;
 use ic_crypto_test_utils_local_csp_vault::MockLocalCspVault;
 use ic_types::crypto::AlgorithmId;
 use proptest::collection::vec;
-use proptest::prelude::any;
+use proptest::prelude::{any, ProptestConfig};
 use proptest::result::maybe_err;
-use proptest::{prop_assert_eq, proptest};
+use proptest::{prop_assert, prop_assert_eq, proptest};
 use std::sync::Arc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
 
 mod common;
 use common::{local_vault_in_temp_dir, proptest_config_for_delegation};
@@ -45,6 +50,45 @@ proptest! {
     }
 }
 
+proptest! {
+    // Each case waits for the request timeout, so only run a few.
+    #![proptest_config(ProptestConfig { cases: 8, ..proptest_config_for_delegation() })]
+    #[test]
+    fn should_return_timeout_error_if_local_vault_does_not_respond_in_time(
+        algorithm_id in arb_algorithm_id(),
+        key_id in arb_key_id(),
+        message in vec(any::<u8>(), 0..1024),
+        local_vault_result in maybe_err(arb_csp_signature(), arb_csp_basic_signature_error())
+    ) {
+        const LOCAL_VAULT_DELAY: Duration = Duration::from_secs(2);
+        const REQUEST_TIMEOUT: Duration = Duration::from_millis(100);
+        let mut local_vault = MockLocalCspVault::new();
+        local_vault
+            .expect_sign()
+            .times(1)
+            .returning(move |_algorithm_id, _message, _key_id| {
+                sleep(LOCAL_VAULT_DELAY);
+                local_vault_result.clone()
+            });
+        let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(local_vault))
+            .with_request_timeout(REQUEST_TIMEOUT);
+        let remote_vault = env.new_vault_client();
+
+        let start = Instant::now();
+        let result = remote_vault.sign(algorithm_id, message, key_id);
+
+        prop_assert!(
+            matches!(
+                &result,
+                Err(CspBasicSignatureError::Timeout { method, .. }) if method == "sign"
+            ),
+            "unexpected result {:?}",
+            result
+        );
+        prop_assert!(start.elapsed() < LOCAL_VAULT_DELAY);
+    }
+}
+
 proptest! {
     #![proptest_config(proptest_config_for_delegation())]
     #[test]
@@ -65,6 +109,59 @@ proptest! {
     }
 }
 
+proptest! {
+    #![proptest_config(proptest_config_for_delegation())]
+    #[test]
+    fn should_delegate_for_gen_basic_signature_key_pair(
+        algorithm in arb_csp_basic_signature_keygen_algorithm(),
+        expected_result in maybe_err(arb_csp_public_key(), arb_csp_basic_signature_keygen_error())
+    ) {
+        let mut local_vault = MockLocalCspVault::new();
+        local_vault
+            .expect_gen_basic_signature_key_pair()
+            .times(1)
+            .withf(move |algorithm_| *algorithm_ == algorithm)
+            .return_const(expected_result.clone());
+        let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(local_vault));
+        let remote_vault = env.new_vault_client();
+
+        let result = remote_vault.gen_basic_signature_key_pair(algorithm);
+
+        prop_assert_eq!(result, expected_result);
+    }
+}
+
+#[test]
+fn should_sign_with_schnorr_bip340_key_generated_through_remote_vault() {
+    let (vault, _temp_dir) = local_vault_in_temp_dir();
+    let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(vault));
+    let remote_vault = env.new_vault_client();
+    let message = b"message signed with BIP340".to_vec();
+
+    let public_key = remote_vault
+        .gen_basic_signature_key_pair(CspBasicSignatureKeygenAlgorithm::SchnorrSecp256k1Bip340)
+        .expect("failed to generate keys");
+    let signature = remote_vault
+        .sign(
+            AlgorithmId::SchnorrSecp256k1,
+            message.clone(),
+            KeyId::try_from(&public_key).unwrap(),
+        )
+        .expect("failed to sign");
+
+    match (public_key, signature) {
+        (
+            CspPublicKey::SchnorrSecp256k1Bip340(public_key_bytes),
+            CspSignature::SchnorrSecp256k1Bip340(signature_bytes),
+        ) => {
+            let verifier = ic_crypto_secp256k1::PublicKey::deserialize_bip340(&public_key_bytes.0)
+                .expect("invalid BIP340 public key");
+            assert!(verifier.verify_bip340_signature(&message, &signature_bytes.0));
+        }
+        _ => panic!("unexpected type for BIP340 public key or signature"),
+    }
+}
+
 #[test]
 fn should_sign_a_large_hundred_megabytes_message() {
     const HUNDRED_MEGA_BYTES: usize = 100 * 1024 * 1024;
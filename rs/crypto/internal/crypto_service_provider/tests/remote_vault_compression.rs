@@ -0,0 +1,161 @@
+use assert_matches::assert_matches;
+use ic_crypto_internal_csp::key_id::KeyId;
+use ic_crypto_internal_csp::vault::api::CspVault;
+use ic_crypto_internal_csp::vault::remote_csp_vault::CompressionEncoding;
+use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::Epoch;
+use ic_crypto_temp_crypto_vault::RemoteVaultEnvironment;
+use ic_logger::ReplicaLogger;
+use ic_protobuf::log::log_entry::v1::LogEntry;
+use ic_test_utilities_in_memory_logger::InMemoryReplicaLogger;
+use ic_types::crypto::AlgorithmId;
+use ic_types::NumberOfNodes;
+use ic_types_test_utils::ids::node_test_id;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+mod common;
+use common::local_vault_in_temp_dir;
+
+const NUM_RECEIVERS: u32 = 28;
+
+#[test]
+fn should_not_transmit_more_bytes_for_ni_dkg_dealing_with_compression() {
+    for encoding in [CompressionEncoding::Gzip, CompressionEncoding::Zstd] {
+        let uncompressed = wire_bytes_of_ni_dkg_dealing(None);
+        let compressed = wire_bytes_of_ni_dkg_dealing(Some(encoding));
+
+        // A dealing consists almost entirely of random group elements and
+        // thus hardly compresses, but it must never get larger.
+        assert!(
+            compressed <= uncompressed,
+            "{encoding:?}: {compressed} > {uncompressed}"
+        );
+    }
+}
+
+#[test]
+fn should_transmit_fewer_bytes_for_compressible_request_with_compression() {
+    for encoding in [CompressionEncoding::Gzip, CompressionEncoding::Zstd] {
+        let uncompressed = wire_bytes_of_large_sign_request(None);
+        let compressed = wire_bytes_of_large_sign_request(Some(encoding));
+
+        assert!(
+            compressed < uncompressed / 2,
+            "{encoding:?}: {compressed} !< {uncompressed} / 2"
+        );
+    }
+}
+
+#[test]
+fn should_talk_to_compressing_server_without_compression() {
+    let (vault, _temp_dir) = local_vault_in_temp_dir();
+    let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(vault))
+        .with_server_compression(CompressionEncoding::Gzip);
+    let remote_vault = env.new_vault_client();
+
+    assert_matches!(create_ni_dkg_dealing(remote_vault.as_ref()), Ok(_));
+    assert_matches!(sign_large_message(remote_vault.as_ref()), Ok(_));
+}
+
+#[test]
+fn should_talk_to_non_compressing_server_with_compression() {
+    let (vault, _temp_dir) = local_vault_in_temp_dir();
+    let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(vault))
+        .with_vault_client_compression(CompressionEncoding::Zstd);
+    let remote_vault = env.new_vault_client();
+
+    assert_matches!(create_ni_dkg_dealing(remote_vault.as_ref()), Ok(_));
+    assert_matches!(sign_large_message(remote_vault.as_ref()), Ok(_));
+}
+
+/// Returns the size of the `create_dealing` response as received by a client
+/// of a server that compresses with `compression`.
+fn wire_bytes_of_ni_dkg_dealing(compression: Option<CompressionEncoding>) -> usize {
+    let logs = run_with_logging_vault_client(compression, |remote_vault| {
+        create_ni_dkg_dealing(remote_vault).expect("failed to create dealing");
+    });
+    bytes_from_logs(&logs, "received", "response of 'create_dealing'")
+}
+
+/// Returns the size of a `sign` request for a large compressible message as
+/// sent by a client that compresses with `compression`.
+fn wire_bytes_of_large_sign_request(compression: Option<CompressionEncoding>) -> usize {
+    let logs = run_with_logging_vault_client(compression, |remote_vault| {
+        sign_large_message(remote_vault).expect("failed to sign");
+    });
+    bytes_from_logs(&logs, "sent", "request to 'sign'")
+}
+
+fn run_with_logging_vault_client(
+    compression: Option<CompressionEncoding>,
+    run: impl FnOnce(&dyn CspVault),
+) -> Vec<LogEntry> {
+    let (vault, _temp_dir) = local_vault_in_temp_dir();
+    let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(vault));
+    let env = match compression {
+        Some(encoding) => env.with_compression(encoding),
+        None => env,
+    };
+    let in_memory_logger = InMemoryReplicaLogger::new();
+    let remote_vault = env
+        .new_vault_client_builder()
+        .with_logger(ReplicaLogger::from(&in_memory_logger))
+        .build()
+        .expect("failed to build vault client");
+
+    run(&remote_vault);
+
+    drop(remote_vault);
+    in_memory_logger.drain_logs()
+}
+
+fn bytes_from_logs(logs: &[LogEntry], direction: &str, rpc: &str) -> usize {
+    let prefix = format!("CSP vault client {direction} ");
+    let suffix = format!(" bytes ({rpc})");
+    logs.iter()
+        .find_map(|entry| {
+            entry
+                .message
+                .strip_prefix(&prefix)?
+                .strip_suffix(&suffix)?
+                .parse()
+                .ok()
+        })
+        .unwrap_or_else(|| panic!("no log entry for {rpc} in {logs:?}"))
+}
+
+fn create_ni_dkg_dealing(remote_vault: &dyn CspVault) -> Result<(), String> {
+    let (receiver_key, _pop) = remote_vault
+        .gen_dealing_encryption_key_pair(node_test_id(42))
+        .map_err(|e| format!("{e:?}"))?;
+    // The vault holds a single dealing encryption key, which is used for all
+    // receivers to make the dealing large.
+    let receiver_keys = (0..NUM_RECEIVERS)
+        .map(|index| (index, receiver_key))
+        .collect::<BTreeMap<_, _>>();
+    remote_vault
+        .create_dealing(
+            AlgorithmId::NiDkg_Groth20_Bls12_381,
+            0,
+            NumberOfNodes::from(NUM_RECEIVERS / 3 + 1),
+            Epoch::from(0),
+            receiver_keys,
+            None,
+        )
+        .map(|_dealing| ())
+        .map_err(|e| format!("{e:?}"))
+}
+
+fn sign_large_message(remote_vault: &dyn CspVault) -> Result<(), String> {
+    let public_key = remote_vault
+        .gen_node_signing_key_pair()
+        .map_err(|e| format!("{e:?}"))?;
+    remote_vault
+        .sign(
+            AlgorithmId::Ed25519,
+            b"a highly repetitive message ".repeat(1_000),
+            KeyId::try_from(&public_key).map_err(|e| format!("{e:?}"))?,
+        )
+        .map(|_signature| ())
+        .map_err(|e| format!("{e:?}"))
+}
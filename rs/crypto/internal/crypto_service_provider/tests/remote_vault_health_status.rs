@@ -0,0 +1,66 @@
+use ic_crypto_internal_csp::vault::api::{CspVaultHealthStatus, CspVaultHealthStatusError};
+use ic_crypto_temp_crypto_vault::RemoteVaultEnvironment;
+use ic_crypto_test_utils_local_csp_vault::MockLocalCspVault;
+use ic_types::time::UNIX_EPOCH;
+use ic_types_test_utils::ids::node_test_id;
+use std::sync::Arc;
+
+mod common;
+use common::local_vault_in_temp_dir;
+
+#[test]
+fn should_count_keys_generated_via_remote_vault() {
+    let (vault, _temp_dir) = local_vault_in_temp_dir();
+    let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(vault));
+    let remote_vault = env.new_vault_client();
+    assert_eq!(
+        remote_vault
+            .health_status()
+            .expect("failed to get health status")
+            .key_count,
+        0
+    );
+
+    remote_vault
+        .gen_node_signing_key_pair()
+        .expect("failed to generate node signing key pair");
+    remote_vault
+        .gen_committee_signing_key_pair()
+        .expect("failed to generate committee signing key pair");
+    remote_vault
+        .gen_tls_key_pair(node_test_id(42))
+        .expect("failed to generate TLS key pair");
+    let status = remote_vault
+        .health_status()
+        .expect("failed to get health status");
+
+    assert_eq!(status.key_count, 3);
+    assert!(status.last_successful_operation.is_some());
+    assert!(!status.is_locked);
+}
+
+#[test]
+fn should_delegate_for_health_status() {
+    for expected_result in [
+        Ok(CspVaultHealthStatus {
+            key_count: 5,
+            last_successful_operation: Some(UNIX_EPOCH),
+            is_locked: true,
+        }),
+        Err(CspVaultHealthStatusError::TransientInternalError {
+            internal_error: "key store unavailable".to_string(),
+        }),
+    ] {
+        let mut local_vault = MockLocalCspVault::new();
+        local_vault
+            .expect_health_status()
+            .times(1)
+            .return_const(expected_result.clone());
+        let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(local_vault));
+        let remote_vault = env.new_vault_client();
+
+        let result = remote_vault.health_status();
+
+        assert_eq!(result, expected_result);
+    }
+}
@@ -0,0 +1,66 @@
+use assert_matches::assert_matches;
+use ic_crypto_internal_csp::key_id::KeyId;
+use ic_crypto_internal_csp::vault::api::{CspBasicSignatureError, CspVaultKeyActivationError};
+use ic_crypto_temp_crypto_vault::RemoteVaultEnvironment;
+use ic_crypto_test_utils_local_csp_vault::MockLocalCspVault;
+use ic_types::crypto::AlgorithmId;
+use std::sync::Arc;
+
+mod common;
+use common::local_vault_in_temp_dir;
+
+#[test]
+fn should_not_sign_with_key_marked_inactive_via_remote_vault() {
+    let (vault, _temp_dir) = local_vault_in_temp_dir();
+    let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(vault));
+    let remote_vault = env.new_vault_client();
+    let public_key = remote_vault
+        .gen_node_signing_key_pair()
+        .expect("failed to generate node signing key pair");
+    let key_id = KeyId::from_public_key(&public_key);
+
+    remote_vault
+        .mark_key_inactive(key_id)
+        .expect("failed to mark key inactive");
+    assert_matches!(
+        remote_vault.sign(AlgorithmId::Ed25519, b"message".to_vec(), key_id),
+        Err(CspBasicSignatureError::SecretKeyNotFound { key_id: id, .. }) if id == key_id
+    );
+
+    remote_vault
+        .mark_key_active(key_id)
+        .expect("failed to mark key active");
+    assert_matches!(
+        remote_vault.sign(AlgorithmId::Ed25519, b"message".to_vec(), key_id),
+        Ok(_)
+    );
+}
+
+#[test]
+fn should_delegate_for_mark_key_inactive_and_active() {
+    let key_id = KeyId::from([42; 32]);
+    for expected_result in [
+        Ok(()),
+        Err(CspVaultKeyActivationError::SecretKeyNotFound { key_id }),
+        Err(CspVaultKeyActivationError::TransientInternalError {
+            internal_error: "key store unavailable".to_string(),
+        }),
+    ] {
+        let mut local_vault = MockLocalCspVault::new();
+        local_vault
+            .expect_mark_key_inactive()
+            .times(1)
+            .withf(move |key_id_| *key_id_ == key_id)
+            .return_const(expected_result.clone());
+        local_vault
+            .expect_mark_key_active()
+            .times(1)
+            .withf(move |key_id_| *key_id_ == key_id)
+            .return_const(expected_result.clone());
+        let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(local_vault));
+        let remote_vault = env.new_vault_client();
+
+        assert_eq!(remote_vault.mark_key_inactive(key_id), expected_result);
+        assert_eq!(remote_vault.mark_key_active(key_id), expected_result);
+    }
+}
@@ -40,6 +40,63 @@ proptest! {
     }
 }
 
+proptest! {
+    #![proptest_config(proptest_config_for_delegation())]
+    #[test]
+    fn should_delegate_for_batch_sign(
+        algorithm_id in arb_algorithm_id(),
+        key_id in arb_key_id(),
+        messages in vec(vec(any::<u8>(), 0..1024), 0..10),
+        expected_results in vec(maybe_err(arb_csp_signature(), arb_csp_multi_signature_error()), 0..10)
+    ) {
+        let expected_messages = messages.clone();
+        let mut local_vault = MockLocalCspVault::new();
+        local_vault
+            .expect_batch_sign()
+            .times(1)
+            .withf(move |algorithm_id_, messages_, key_id_| {
+                *algorithm_id_ == algorithm_id
+                    && messages_.iter().map(|message| message.to_vec()).eq(expected_messages.iter().cloned())
+                    && *key_id_ == key_id
+            })
+            .return_const(expected_results.clone());
+        let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(local_vault));
+        let remote_vault = env.new_vault_client();
+
+        let messages: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+        let results = remote_vault.batch_sign(algorithm_id, &messages, key_id);
+
+        prop_assert_eq!(results, expected_results);
+    }
+}
+
+proptest! {
+    #![proptest_config(proptest_config_for_delegation())]
+    #[test]
+    fn should_delegate_for_multi_sign_batch(
+        algorithm_id in arb_algorithm_id(),
+        key_id in arb_key_id(),
+        messages in vec(vec(any::<u8>(), 0..1024), 0..10),
+        expected_result in maybe_err(vec(arb_csp_signature(), 0..10), arb_csp_multi_signature_error())
+    ) {
+        let expected_messages = messages.clone();
+        let mut local_vault = MockLocalCspVault::new();
+        local_vault
+            .expect_multi_sign_batch()
+            .times(1)
+            .withf(move |algorithm_id_, messages_, key_id_| {
+                *algorithm_id_ == algorithm_id && messages_ == &expected_messages && *key_id_ == key_id
+            })
+            .return_const(expected_result.clone());
+        let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(local_vault));
+        let remote_vault = env.new_vault_client();
+
+        let result = remote_vault.multi_sign_batch(algorithm_id, messages, key_id);
+
+        prop_assert_eq!(result, expected_result);
+    }
+}
+
 proptest! {
     #![proptest_config(proptest_config_for_delegation())]
     #[test]
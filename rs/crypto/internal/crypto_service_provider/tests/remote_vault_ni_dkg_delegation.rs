@@ -0,0 +1,58 @@
+use ic_crypto_internal_csp_proptest_utils::{
+    arb_algorithm_id, arb_csp_dkg_verify_dealing_error, arb_csp_fs_encryption_public_key,
+    arb_csp_ni_dkg_dealing,
+};
+use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::Epoch;
+use ic_crypto_temp_crypto_vault::RemoteVaultEnvironment;
+use ic_crypto_test_utils_local_csp_vault::MockLocalCspVault;
+use ic_types::NumberOfNodes;
+use proptest::collection::{btree_map, vec};
+use proptest::prelude::{any, Just};
+use proptest::result::maybe_err;
+use proptest::{prop_assert_eq, proptest};
+use std::sync::Arc;
+
+mod common;
+use common::proptest_config_for_delegation;
+
+proptest! {
+    #![proptest_config(proptest_config_for_delegation())]
+    #[test]
+    fn should_delegate_for_verify_dealings_batch(
+        algorithm_id in arb_algorithm_id(),
+        threshold in any::<u32>(),
+        epoch in any::<u32>(),
+        receiver_keys in btree_map(any::<u32>(), arb_csp_fs_encryption_public_key(), 0..5),
+        dealings in vec((any::<u32>(), arb_csp_ni_dkg_dealing()), 0..5),
+        expected_results in vec(maybe_err(Just(()), arb_csp_dkg_verify_dealing_error()), 0..5)
+    ) {
+        let threshold = NumberOfNodes::from(threshold);
+        let epoch = Epoch::from(epoch);
+        let expected_receiver_keys = receiver_keys.clone();
+        let expected_dealings = dealings.clone();
+        let mut local_vault = MockLocalCspVault::new();
+        local_vault
+            .expect_verify_dealings_batch()
+            .times(1)
+            .withf(move |algorithm_id_, threshold_, epoch_, receiver_keys_, dealings_| {
+                *algorithm_id_ == algorithm_id
+                    && *threshold_ == threshold
+                    && *epoch_ == epoch
+                    && receiver_keys_ == &expected_receiver_keys
+                    && dealings_ == expected_dealings.as_slice()
+            })
+            .return_const(expected_results.clone());
+        let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(local_vault));
+        let remote_vault = env.new_vault_client();
+
+        let results = remote_vault.verify_dealings_batch(
+            algorithm_id,
+            threshold,
+            epoch,
+            receiver_keys,
+            &dealings,
+        );
+
+        prop_assert_eq!(results, expected_results);
+    }
+}
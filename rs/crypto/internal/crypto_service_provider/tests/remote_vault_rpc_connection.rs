@@ -6,23 +6,25 @@ use ic_crypto_internal_csp::key_id::KeyId;
 use ic_crypto_internal_csp::types::CspSignature;
 use ic_crypto_internal_csp::vault::api::CspBasicSignatureError::TransientInternalError;
 use ic_crypto_internal_csp::vault::api::{
-    BasicSignatureCspVault, CspBasicSignatureError, CspPublicKeyStoreError, IDkgProtocolCspVault,
-    PublicKeyStoreCspVault,
+    BasicSignatureCspVault, CspBasicSignatureError, CspBasicSignatureKeygenError,
+    CspPublicKeyStoreError, IDkgProtocolCspVault, PublicKeyStoreCspVault,
 };
 use ic_crypto_internal_csp::vault::remote_csp_vault::{
     RemoteCspVault, RemoteCspVaultBuilder, TarpcCspVaultServerImplBuilder,
 };
 use ic_crypto_temp_crypto_vault::RemoteVaultEnvironment;
+use ic_crypto_test_utils_local_csp_vault::MockLocalCspVault;
 use ic_logger::{info, new_logger, new_replica_logger_from_config, ReplicaLogger};
 use ic_test_utilities_in_memory_logger::assertions::LogEntriesAssert;
 use ic_test_utilities_in_memory_logger::InMemoryReplicaLogger;
 use ic_types::crypto::AlgorithmId;
 use slog::Level;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread;
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 mod common;
 use common::local_vault_in_temp_dir;
@@ -73,7 +75,7 @@ fn should_reconnect_after_request_from_client_cannot_be_received_by_server_becau
     assert_matches!(signature_before_error, Ok(_));
 
     let signature = sign_message(TooLarge, key_id, &client);
-    assert_matches!(signature, Err(TransientInternalError {internal_error}) if internal_error.contains("the request exceeded its deadline"));
+    assert_matches!(signature, Err(TransientInternalError {internal_error}) if internal_error.contains("the connection to the server was reset"));
 
     let signature_after_error = sign_message(Small, key_id, &client);
     assert_eq!(signature_before_error, signature_after_error);
@@ -124,7 +126,7 @@ fn should_reconnect_after_response_from_server_cannot_be_sent_because_too_large(
     assert_matches!(&client.idkg_gen_dealing_encryption_key_pair(), Ok(_)); //encoded response from server has 39 bytes
 
     let keys = &client.current_node_public_keys_with_timestamps(); //encoded response from server has 93 bytes
-    assert_matches!(keys, Err(CspPublicKeyStoreError::TransientInternalError(msg)) if msg.contains("the request exceeded its deadline"));
+    assert_matches!(keys, Err(CspPublicKeyStoreError::TransientInternalError(msg)) if msg.contains("the connection to the server was reset"));
 
     assert_matches!(&client.idkg_gen_dealing_encryption_key_pair(), Ok(_));
 }
@@ -195,6 +197,103 @@ fn should_connect_with_new_client_after_server_killed_and_restarted() {
     assert_matches!(signature, Ok(_));
 }
 
+#[test]
+fn should_retry_requests_interrupted_by_server_restart() {
+    activate_tracing();
+    let (vault, _temp_dir) = local_vault_in_temp_dir();
+    let mut env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(vault))
+        .with_vault_client_max_attempts(10);
+    let client = vault_client_with_short_timeouts(&env).build_expecting_ok();
+    let node_signing_public_key = client
+        .gen_node_signing_key_pair()
+        .expect("failed generating node signing key pair");
+    let key_id = KeyId::try_from(&node_signing_public_key).unwrap();
+    let expected_signature = sign_message(Small, key_id, &client);
+    assert_matches!(expected_signature, Ok(_));
+    assert_eq!(client.retry_count(), 0);
+
+    let stop_signing = AtomicBool::new(false);
+    let signatures = thread::scope(|s| {
+        let signer = s.spawn(|| {
+            let mut signatures = vec![];
+            while !stop_signing.load(Ordering::Relaxed) {
+                signatures.push(sign_message(Small, key_id, &client));
+            }
+            signatures
+        });
+        sleep(Duration::from_millis(200));
+        env.shutdown_server_now();
+        sleep(Duration::from_secs(1));
+        env.restart_server();
+        sleep(Duration::from_millis(200));
+        stop_signing.store(true, Ordering::Relaxed);
+        signer.join().expect("signer thread panicked")
+    });
+
+    assert!(!signatures.is_empty());
+    for signature in signatures {
+        // Without retries, a request in flight when the server shuts down
+        // would fail once its deadline is exceeded.
+        assert_eq!(signature, expected_signature);
+    }
+    assert!(client.retry_count() > 0);
+}
+
+#[test]
+fn should_not_resend_key_generation_interrupted_by_server_restart() {
+    activate_tracing();
+    const SERVER_DELAY: Duration = Duration::from_secs(1);
+    let mut vault = MockLocalCspVault::new();
+    vault
+        .expect_gen_node_signing_key_pair()
+        .times(1)
+        .returning(|| {
+            sleep(SERVER_DELAY);
+            Err(CspBasicSignatureKeygenError::InternalError {
+                internal_error: "response is lost".to_string(),
+            })
+        });
+    let mut env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(vault))
+        .with_vault_client_max_attempts(10);
+    let client = vault_client_with_short_timeouts(&env).build_expecting_ok();
+
+    let result = thread::scope(|s| {
+        let keygen = s.spawn(|| client.gen_node_signing_key_pair());
+        // Shut the server down while it is generating the key, i.e. after the
+        // request was delivered, then restart it.
+        sleep(SERVER_DELAY / 4);
+        env.shutdown_server_now();
+        env.restart_server();
+        keygen.join().expect("keygen thread panicked")
+    });
+
+    // The server may already have generated the key, so resending the request
+    // could generate a second one.
+    assert_matches!(
+        result,
+        Err(CspBasicSignatureKeygenError::TransientInternalError { .. })
+    );
+    assert_eq!(client.retry_count(), 0);
+}
+
+#[test]
+fn should_not_retry_application_level_errors() {
+    activate_tracing();
+    let (vault, _temp_dir) = local_vault_in_temp_dir();
+    let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(vault))
+        .with_vault_client_max_attempts(10);
+    let client = vault_client_with_short_timeouts(&env).build_expecting_ok();
+
+    let non_existing_key_id = KeyId::from([42; 32]);
+    let signature = sign_message(Small, non_existing_key_id, &client);
+
+    assert_matches!(
+        signature,
+        Err(CspBasicSignatureError::SecretKeyNotFound { .. })
+    );
+    assert_eq!(client.retry_count(), 0);
+}
+
 #[test]
 fn should_automatically_detect_disconnection() {
     activate_tracing();
@@ -221,6 +320,73 @@ fn should_automatically_detect_disconnection() {
         .has_only_one_message_containing(&Level::Warning, "Detected disconnection from socket");
 }
 
+#[test]
+fn should_time_out_requests_to_unresponsive_server() {
+    activate_tracing();
+    const SERVER_DELAY: Duration = Duration::from_secs(2);
+    let mut vault = MockLocalCspVault::new();
+    vault
+        .expect_sign()
+        .returning(|algorithm, _message, key_id| {
+            sleep(SERVER_DELAY);
+            Err(CspBasicSignatureError::SecretKeyNotFound { algorithm, key_id })
+        });
+    let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(vault))
+        .with_request_timeout(Duration::from_millis(100));
+    let client = env.new_vault_client_builder().build_expecting_ok();
+
+    let start = Instant::now();
+    let signature = sign_message(Small, KeyId::from([42; 32]), &client);
+
+    assert_matches!(
+        signature,
+        Err(CspBasicSignatureError::Timeout { method, .. }) if method == "sign"
+    );
+    assert!(
+        start.elapsed() < SERVER_DELAY,
+        "the client waited {:?} for the response",
+        start.elapsed()
+    );
+}
+
+#[test]
+fn should_not_poison_connection_after_request_timed_out() {
+    const SERVER_DELAY: Duration = Duration::from_secs(2);
+    let is_first_request = Arc::new(AtomicBool::new(true));
+    let mut vault = MockLocalCspVault::new();
+    vault
+        .expect_sign()
+        .times(2)
+        .returning(move |algorithm, _message, key_id| {
+            if is_first_request.swap(false, Ordering::SeqCst) {
+                sleep(SERVER_DELAY);
+            }
+            Err(CspBasicSignatureError::SecretKeyNotFound { algorithm, key_id })
+        });
+    let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(vault))
+        .with_request_timeout(Duration::from_millis(500));
+    let client = env.new_vault_client_builder().build_expecting_ok();
+    let key_id = KeyId::from([42; 32]);
+
+    assert_matches!(
+        sign_message(Small, key_id, &client),
+        Err(CspBasicSignatureError::Timeout { method, .. }) if method == "sign"
+    );
+    // Let the server finish the timed-out request, whose late response must
+    // not be mistaken for the response to the next one. Otherwise, the next
+    // request may time out as well if the server's thread pool has only one
+    // thread.
+    sleep(SERVER_DELAY);
+    assert_eq!(
+        sign_message(Small, key_id, &client),
+        Err(CspBasicSignatureError::SecretKeyNotFound {
+            algorithm: AlgorithmId::Ed25519,
+            key_id
+        })
+    );
+    assert_eq!(client.retry_count(), 0);
+}
+
 fn vault_client_with_short_timeouts<B>(env: &RemoteVaultEnvironment<B>) -> RemoteCspVaultBuilder {
     env.new_vault_client_builder()
         .with_rpc_timeout(Duration::from_secs(10))
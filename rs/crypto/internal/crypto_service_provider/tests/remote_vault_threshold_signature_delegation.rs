@@ -1,10 +1,13 @@
 use ic_crypto_internal_csp_proptest_utils::{
-    arb_algorithm_id, arb_csp_signature, arb_csp_threshold_sign_error, arb_key_id,
+    arb_algorithm_id, arb_csp_dkg_load_private_key_error, arb_csp_dkg_retain_threshold_keys_error,
+    arb_csp_signature, arb_csp_threshold_sign_error, arb_key_id, arb_ni_dkg_transcript,
+    arb_node_id,
 };
+use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::Epoch;
 use ic_crypto_temp_crypto_vault::RemoteVaultEnvironment;
 use ic_crypto_test_utils_local_csp_vault::MockLocalCspVault;
-use proptest::collection::vec;
-use proptest::prelude::any;
+use proptest::collection::{btree_map, btree_set, vec};
+use proptest::prelude::{any, Just};
 use proptest::result::maybe_err;
 use proptest::{prop_assert_eq, proptest};
 use std::sync::Arc;
@@ -38,3 +41,94 @@ proptest! {
         prop_assert_eq!(result, expected_result);
     }
 }
+
+proptest! {
+    #![proptest_config(proptest_config_for_delegation())]
+    #[test]
+    fn should_delegate_for_combine_threshold_sig_shares(
+        shares in btree_map(arb_node_id(), arb_csp_signature(), 0..10),
+        transcript in arb_ni_dkg_transcript(),
+        expected_result in maybe_err(arb_csp_signature(), arb_csp_threshold_sign_error())
+    ) {
+        let expected_shares = shares.clone();
+        let expected_transcript = transcript.clone();
+        let mut local_vault = MockLocalCspVault::new();
+        local_vault
+            .expect_combine_threshold_sig_shares()
+            .times(1)
+            .withf(move |shares_, transcript_| {
+                *shares_ == expected_shares && *transcript_ == expected_transcript
+            })
+            .return_const(expected_result.clone());
+        let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(local_vault));
+        let remote_vault = env.new_vault_client();
+
+        let result = remote_vault.combine_threshold_sig_shares(&shares, &transcript);
+
+        prop_assert_eq!(result, expected_result);
+    }
+}
+
+proptest! {
+    #![proptest_config(proptest_config_for_delegation())]
+    #[test]
+    fn should_delegate_for_load_threshold_signing_key(
+        algorithm_id in arb_algorithm_id(),
+        epoch in any::<u32>(),
+        transcript in arb_ni_dkg_transcript(),
+        fs_key_id in arb_key_id(),
+        receiver_index in any::<u32>(),
+        expected_result in maybe_err(Just(()), arb_csp_dkg_load_private_key_error())
+    ) {
+        let epoch = Epoch::from(epoch);
+        let csp_transcript = transcript.internal_csp_transcript;
+        let expected_csp_transcript = csp_transcript.clone();
+        let mut local_vault = MockLocalCspVault::new();
+        local_vault
+            .expect_load_threshold_signing_key()
+            .times(1)
+            .withf(move |algorithm_id_, epoch_, csp_transcript_, fs_key_id_, receiver_index_| {
+                *algorithm_id_ == algorithm_id
+                    && *epoch_ == epoch
+                    && *csp_transcript_ == expected_csp_transcript
+                    && *fs_key_id_ == fs_key_id
+                    && *receiver_index_ == receiver_index
+            })
+            .return_const(expected_result.clone());
+        let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(local_vault));
+        let remote_vault = env.new_vault_client();
+
+        let result = remote_vault.load_threshold_signing_key(
+            algorithm_id,
+            epoch,
+            csp_transcript,
+            fs_key_id,
+            receiver_index,
+        );
+
+        prop_assert_eq!(result, expected_result);
+    }
+}
+
+proptest! {
+    #![proptest_config(proptest_config_for_delegation())]
+    #[test]
+    fn should_delegate_for_retain_threshold_keys_if_present(
+        active_key_ids in btree_set(arb_key_id(), 0..10),
+        expected_result in maybe_err(Just(()), arb_csp_dkg_retain_threshold_keys_error())
+    ) {
+        let expected_active_key_ids = active_key_ids.clone();
+        let mut local_vault = MockLocalCspVault::new();
+        local_vault
+            .expect_retain_threshold_keys_if_present()
+            .times(1)
+            .withf(move |active_key_ids_| *active_key_ids_ == expected_active_key_ids)
+            .return_const(expected_result.clone());
+        let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(local_vault));
+        let remote_vault = env.new_vault_client();
+
+        let result = remote_vault.retain_threshold_keys_if_present(active_key_ids);
+
+        prop_assert_eq!(result, expected_result);
+    }
+}
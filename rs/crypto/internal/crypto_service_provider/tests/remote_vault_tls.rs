@@ -0,0 +1,127 @@
+use assert_matches::assert_matches;
+use ic_crypto_internal_basic_sig_ed25519 as ed25519;
+use ic_crypto_internal_csp::key_id::KeyId;
+use ic_crypto_internal_csp::types::{CspPublicKey, CspSignature};
+use ic_crypto_internal_csp::vault::remote_csp_vault::RemoteCspVaultError;
+use ic_crypto_temp_crypto_vault::RemoteVaultEnvironment;
+use ic_types::crypto::AlgorithmId;
+use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, KeyPair};
+use std::sync::Arc;
+
+mod common;
+use common::local_vault_in_temp_dir;
+
+#[test]
+fn should_sign_via_tls_if_client_trusts_server_ca() {
+    let ca = TestCa::new("vault test CA");
+    let (cert_pem, key_pem) = ca.issue_server_cert("localhost");
+    let (vault, _temp_dir) = local_vault_in_temp_dir();
+    let env = RemoteVaultEnvironment::start_server_with_tls(
+        Arc::new(vault),
+        cert_pem.as_bytes(),
+        key_pem.as_bytes(),
+    )
+    .with_vault_client_trust_anchor(ca.cert_pem.as_bytes());
+    let remote_vault = env.new_vault_client();
+    let node_signing_public_key = remote_vault
+        .gen_node_signing_key_pair()
+        .expect("failed to generate node signing key pair");
+    let message = b"message signed via TLS".to_vec();
+
+    let signature = remote_vault
+        .sign(
+            AlgorithmId::Ed25519,
+            message.clone(),
+            KeyId::try_from(&node_signing_public_key).unwrap(),
+        )
+        .expect("failed to sign");
+
+    match (node_signing_public_key, signature) {
+        (CspPublicKey::Ed25519(public_key_bytes), CspSignature::Ed25519(signature_bytes)) => {
+            let verification = ed25519::verify(&signature_bytes, &message, &public_key_bytes);
+            assert_matches!(verification, Ok(()))
+        }
+        _ => panic!("unexpected type for node signing public key or signature"),
+    }
+}
+
+#[test]
+fn should_fail_to_connect_if_client_trusts_different_ca() {
+    let ca = TestCa::new("vault test CA");
+    let other_ca = TestCa::new("other test CA");
+    let (cert_pem, key_pem) = ca.issue_server_cert("localhost");
+    let (vault, _temp_dir) = local_vault_in_temp_dir();
+    let env = RemoteVaultEnvironment::start_server_with_tls(
+        Arc::new(vault),
+        cert_pem.as_bytes(),
+        key_pem.as_bytes(),
+    )
+    .with_vault_client_trust_anchor(other_ca.cert_pem.as_bytes());
+
+    let result = env.new_vault_client_builder().build();
+
+    assert_matches!(
+        result.err(),
+        Some(RemoteCspVaultError::TransportError { message, .. })
+            if message.contains("invalid peer certificate")
+    );
+}
+
+#[test]
+fn should_fail_to_connect_if_server_cert_is_for_different_name() {
+    let ca = TestCa::new("vault test CA");
+    let (cert_pem, key_pem) = ca.issue_server_cert("vault.example.com");
+    let (vault, _temp_dir) = local_vault_in_temp_dir();
+    let env = RemoteVaultEnvironment::start_server_with_tls(
+        Arc::new(vault),
+        cert_pem.as_bytes(),
+        key_pem.as_bytes(),
+    )
+    .with_vault_client_trust_anchor(ca.cert_pem.as_bytes());
+
+    let result = env.new_vault_client_builder().build();
+
+    assert_matches!(
+        result.err(),
+        Some(RemoteCspVaultError::TransportError { message, .. })
+            if message.contains("invalid peer certificate")
+    );
+}
+
+struct TestCa {
+    cert: rcgen::Certificate,
+    cert_pem: String,
+    key_pair: KeyPair,
+}
+
+impl TestCa {
+    fn new(common_name: &str) -> Self {
+        let mut params = CertificateParams::new(vec![]).expect("invalid CA params");
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params
+            .distinguished_name
+            .push(DnType::CommonName, common_name);
+        let key_pair = KeyPair::generate().expect("failed to generate CA key pair");
+        let cert = params
+            .self_signed(&key_pair)
+            .expect("failed to self-sign CA certificate");
+        Self {
+            cert_pem: cert.pem(),
+            cert,
+            key_pair,
+        }
+    }
+
+    /// Returns the PEM-encoded certificate and private key of a server with
+    /// the given DNS name, issued by this CA.
+    fn issue_server_cert(&self, dns_name: &str) -> (String, String) {
+        let mut params =
+            CertificateParams::new(vec![dns_name.to_string()]).expect("invalid server params");
+        params.distinguished_name.push(DnType::CommonName, dns_name);
+        let key_pair = KeyPair::generate().expect("failed to generate server key pair");
+        let cert = params
+            .signed_by(&key_pair, &self.cert, &self.key_pair)
+            .expect("failed to issue server certificate");
+        (cert.pem(), key_pair.serialize_pem())
+    }
+}
@@ -13,6 +13,7 @@ use ic_crypto_internal_csp_proptest_utils::{
 };
 use ic_crypto_test_utils_keys::public_keys::valid_tls_certificate_and_validation_time;
 use ic_crypto_tls_interfaces::TlsPublicKeyCert;
+use ic_types::Time;
 use proptest::{prelude::Just, result::maybe_err_weighted};
 
 proptest! {
@@ -39,6 +40,32 @@ proptest! {
     }
 }
 
+proptest! {
+    #![proptest_config(proptest_config_for_delegation())]
+    #[test]
+    fn should_delegate_for_gen_tls_key_pair_with_validity(
+        node_id in arb_node_id(),
+        not_after_nanos in any::<u64>(),
+        expected_result in maybe_err_weighted(0.95, Just(valid_tls_public_key_cert()), arb_csp_tls_keygen_error())
+    ) {
+        let not_after = Time::from_nanos_since_unix_epoch(not_after_nanos);
+        let mut local_vault = MockLocalCspVault::new();
+        local_vault
+            .expect_gen_tls_key_pair_with_validity()
+            .times(1)
+            .withf(move |node_id_, not_after_| {
+                *node_id_ == node_id && *not_after_ == not_after
+            })
+            .return_const(expected_result.clone());
+        let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(local_vault));
+        let remote_vault = env.new_vault_client();
+
+        let result = remote_vault.gen_tls_key_pair_with_validity(node_id, not_after);
+
+        prop_assert_eq!(result, expected_result);
+    }
+}
+
 proptest! {
     #![proptest_config(proptest_config_for_delegation())]
     #[test]
@@ -68,6 +68,9 @@ pub fn generate_idkg_dealing_encryption_keys(
             CspCreateMEGaKeyError::TransientInternalError { internal_error } => {
                 IDkgDealingEncryptionKeysGenerationError::TransientInternalError(internal_error)
             }
+            CspCreateMEGaKeyError::Timeout { .. } => {
+                IDkgDealingEncryptionKeysGenerationError::TransientInternalError(format!("{}", e))
+            }
             _ => IDkgDealingEncryptionKeysGenerationError::InternalError(format!("{}", e)),
         })?;
     Ok(ic_crypto_internal_csp::keygen::utils::idkg_dealing_encryption_pk_to_proto(pubkey))
@@ -158,12 +161,22 @@ fn generate_node_keys_once_internal(
                 ValidatePksAndSksError::TransientInternalError(transient_error) => {
                     NodeKeyGenerationError::TransientInternalError(transient_error)
                 }
+                ValidatePksAndSksError::Timeout { method, elapsed } => {
+                    NodeKeyGenerationError::TransientInternalError(format!(
+                        "RPC call {method} timed out after {elapsed:?}"
+                    ))
+                }
                 _ => panic!("Node contains inconsistent key material: {:?}", error),
             })
         }
         Err(ValidatePksAndSksError::TransientInternalError(transient_error)) => Err(
             NodeKeyGenerationError::TransientInternalError(transient_error),
         ),
+        Err(ValidatePksAndSksError::Timeout { method, elapsed }) => {
+            Err(NodeKeyGenerationError::TransientInternalError(format!(
+                "RPC call {method} timed out after {elapsed:?}"
+            )))
+        }
         Err(error) => panic!("Node contains inconsistent key material: {:?}", error),
     }
 }
@@ -42,7 +42,9 @@ use ic_crypto_internal_multi_sig_bls12381::types::PublicKeyBytes as MultiSigBls1
 use ic_crypto_internal_threshold_sig_canister_threshold_sig::{
     verify_mega_public_key, EccCurveType,
 };
+pub use ic_crypto_tls_cert_validation::verify_tls_signature;
 pub use ic_crypto_tls_cert_validation::TlsCertValidationError;
+pub use ic_crypto_tls_cert_validation::TlsSignatureVerificationError;
 pub use ic_crypto_tls_cert_validation::ValidTlsCertificate;
 use ic_protobuf::registry::crypto::v1::AlgorithmId as AlgorithmIdProto;
 use ic_protobuf::registry::crypto::v1::PublicKey;
@@ -53,21 +53,46 @@ impl ValidTlsCertificate {
     pub fn get(&self) -> &X509PublicKeyCert {
         &self.certificate
     }
-}
 
-impl TryFrom<(X509PublicKeyCert, NodeId, Time)> for ValidTlsCertificate {
-    type Error = TlsCertValidationError;
+    /// Validates a node's TLS certificate with a limited validity period.
+    ///
+    /// The validation is the same as for `try_from((X509PublicKeyCert, NodeId,
+    /// Time))`, except that the certificate's notAfter date must equal
+    /// `not_after` instead of indicating that the certificate has no
+    /// well-defined expiration date. Note that the certificate may already be
+    /// expired at `current_time`.
+    pub fn try_from_with_not_after(
+        certificate: X509PublicKeyCert,
+        node_id: NodeId,
+        current_time: Time,
+        not_after: Time,
+    ) -> Result<Self, TlsCertValidationError> {
+        Self::validate(
+            certificate,
+            node_id,
+            current_time,
+            ExpectedNotAfter::At(not_after),
+        )
+    }
 
-    fn try_from(
-        (certificate, node_id, current_time): (X509PublicKeyCert, NodeId, Time),
-    ) -> Result<Self, Self::Error> {
+    fn validate(
+        certificate: X509PublicKeyCert,
+        node_id: NodeId,
+        current_time: Time,
+        expected_not_after: ExpectedNotAfter,
+    ) -> Result<Self, TlsCertValidationError> {
         let x509_cert = parse_x509_v3_certificate(&certificate.certificate_der)?;
         let subject_cn = single_subject_cn_as_str(&x509_cert)?;
         ensure_subject_cn_equals_node_id(subject_cn, node_id)?;
         ensure_single_issuer_cn_equals_subject_cn(&x509_cert, subject_cn)?;
         ensure_not_ca(&x509_cert)?;
         ensure_notbefore_date_is_latest_at(&x509_cert, current_time)?;
-        ensure_notafter_date_equals_99991231235959z(&x509_cert)?;
+        match expected_not_after {
+            ExpectedNotAfter::NoWellDefinedExpirationDate => {
+                ensure_notafter_date_equals_99991231235959z(&x509_cert)?
+            }
+            ExpectedNotAfter::At(not_after) => ensure_notafter_date_equals(&x509_cert, not_after)?,
+        }
         ensure_signature_algorithm_is_ed25519(&x509_cert)?;
         let public_key = ed25519_pubkey_from_x509_cert(&x509_cert)?;
         verify_ed25519_public_key(&public_key)?;
@@ -78,6 +103,70 @@ impl TryFrom<(X509PublicKeyCert, NodeId, Time)> for ValidTlsCertificate {
     }
 }
 
+enum ExpectedNotAfter {
+    NoWellDefinedExpirationDate,
+    At(Time),
+}
+
+impl TryFrom<(X509PublicKeyCert, NodeId, Time)> for ValidTlsCertificate {
+    type Error = TlsCertValidationError;
+
+    fn try_from(
+        (certificate, node_id, current_time): (X509PublicKeyCert, NodeId, Time),
+    ) -> Result<Self, Self::Error> {
+        Self::validate(
+            certificate,
+            node_id,
+            current_time,
+            ExpectedNotAfter::NoWellDefinedExpirationDate,
+        )
+    }
+}
+
+/// Verifies a signature created during a TLS handshake with the secret key
+/// corresponding to a node's TLS `certificate`.
+///
+/// Besides verifying that `signature` is a valid Ed25519 signature on
+/// `message` w.r.t. the certificate's public key, this checks that
+/// `current_time` lies within the certificate's validity period.
+///
+/// Note that this does not validate the certificate as a node's TLS
+/// certificate, see [`ValidTlsCertificate`] for that.
+pub fn verify_tls_signature(
+    signature: &BasicSigEd25519SignatureBytes,
+    message: &[u8],
+    certificate: &X509PublicKeyCert,
+    current_time: Time,
+) -> Result<(), TlsSignatureVerificationError> {
+    let malformed_certificate = |e: TlsCertValidationError| {
+        TlsSignatureVerificationError::MalformedCertificate { error: e.error }
+    };
+    let x509_cert =
+        parse_x509_v3_certificate(&certificate.certificate_der).map_err(malformed_certificate)?;
+    let current_time_asn1 = asn1_time(current_time)
+        .map_err(|error| TlsSignatureVerificationError::MalformedCertificate { error })?;
+    let validity = x509_cert.validity();
+    if current_time_asn1 < validity.not_before {
+        return Err(TlsSignatureVerificationError::CertificateNotYetValid {
+            not_before_secs_since_unix_epoch: validity.not_before.timestamp(),
+            current_time,
+        });
+    }
+    if current_time_asn1 > validity.not_after {
+        return Err(TlsSignatureVerificationError::CertificateExpired {
+            not_after_secs_since_unix_epoch: validity.not_after.timestamp(),
+            current_time,
+        });
+    }
+    ensure_signature_algorithm_is_ed25519(&x509_cert).map_err(malformed_certificate)?;
+    let public_key = ed25519_pubkey_from_x509_cert(&x509_cert).map_err(malformed_certificate)?;
+    ic_crypto_internal_basic_sig_ed25519::verify(signature, message, &public_key).map_err(|e| {
+        TlsSignatureVerificationError::InvalidSignature {
+            error: format!("{}", e),
+        }
+    })
+}
+
 fn single_subject_cn_as_str<'a>(
     x509_cert: &'a X509Certificate,
 ) -> Result<&'a str, TlsCertValidationError> {
@@ -142,19 +231,7 @@ fn ensure_notbefore_date_is_latest_at(
     x509_cert: &X509Certificate,
     current_time: Time,
 ) -> Result<(), TlsCertValidationError> {
-    let current_time_u64 = current_time.as_secs_since_unix_epoch();
-    let current_time_i64 = i64::try_from(current_time_u64).map_err(|e| {
-        invalid_tls_certificate_error(format!(
-            "failed to convert current time ({current_time_u64}) to i64: {}",
-            e
-        ))
-    })?;
-    let current_time_asn1 = ASN1Time::from_timestamp(current_time_i64).map_err(|e| {
-        invalid_tls_certificate_error(format!(
-            "failed to convert current time ({current_time_i64}) to ASN1Time: {}",
-            e
-        ))
-    })?;
+    let current_time_asn1 = asn1_time(current_time).map_err(invalid_tls_certificate_error)?;
 
     if x509_cert.validity().not_before > current_time_asn1 {
         return Err(invalid_tls_certificate_error(format!(
@@ -182,6 +259,33 @@ fn ensure_notafter_date_equals_99991231235959z(
     Ok(())
 }
 
+fn ensure_notafter_date_equals(
+    x509_cert: &X509Certificate,
+    not_after: Time,
+) -> Result<(), TlsCertValidationError> {
+    let not_after_asn1 = asn1_time(not_after).map_err(invalid_tls_certificate_error)?;
+    if x509_cert.validity().not_after != not_after_asn1 {
+        return Err(invalid_tls_certificate_error(format!(
+            "notAfter date (={:?}) does not match the expected date (={:?})",
+            x509_cert.validity().not_after,
+            not_after_asn1,
+        )));
+    }
+    Ok(())
+}
+
+fn asn1_time(time: Time) -> Result<ASN1Time, String> {
+    let time_u64 = time.as_secs_since_unix_epoch();
+    let time_i64 = i64::try_from(time_u64)
+        .map_err(|e| format!("failed to convert time ({time_u64}) to i64: {}", e))?;
+    ASN1Time::from_timestamp(time_i64).map_err(|e| {
+        format!(
+            "failed to convert time ({time_i64}) to ASN1Time: {}",
+            e
+        )
+    })
+}
+
 fn ensure_signature_algorithm_is_ed25519(
     x509_cert: &X509Certificate,
 ) -> Result<(), TlsCertValidationError> {
@@ -271,3 +375,28 @@ impl fmt::Display for TlsCertValidationError {
         write!(f, "{:?}", self)
     }
 }
+
+/// An error returned by [`verify_tls_signature`].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum TlsSignatureVerificationError {
+    MalformedCertificate {
+        error: String,
+    },
+    CertificateNotYetValid {
+        not_before_secs_since_unix_epoch: i64,
+        current_time: Time,
+    },
+    CertificateExpired {
+        not_after_secs_since_unix_epoch: i64,
+        current_time: Time,
+    },
+    InvalidSignature {
+        error: String,
+    },
+}
+
+impl fmt::Display for TlsSignatureVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
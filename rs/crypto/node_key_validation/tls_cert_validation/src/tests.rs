@@ -1,14 +1,16 @@
 use super::*;
 use assert_matches::assert_matches;
+use ic_crypto_internal_basic_sig_ed25519::types::SecretKeyBytes as BasicSigEd25519SecretKeyBytes;
 use ic_crypto_test_utils_keys::public_keys::valid_tls_certificate_and_validation_time;
 use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
 use ic_crypto_test_utils_tls::x509_certificates::{
-    ed25519_key_pair, prime256v1_key_pair, CertBuilder, CertWithPrivateKey,
+    ed25519_key_pair, prime256v1_key_pair, CertBuilder, CertWithPrivateKey, KeyPair,
 };
 use ic_types::time::UNIX_EPOCH;
 use ic_types::PrincipalId;
 use std::ops::Range;
 use std::str::FromStr;
+use std::time::Duration;
 
 #[test]
 fn should_fail_on_default_certificate() {
@@ -323,6 +325,116 @@ fn should_fail_if_tls_certificate_is_ca() {
     );
 }
 
+const ONE_DAY_AFTER_UNIX_EPOCH_SECS: u64 = 24 * 60 * 60;
+
+#[test]
+fn should_accept_tls_certificate_with_expected_notafter_date() {
+    let rng = &mut reproducible_rng();
+    let node_id = node_id(1);
+    let cert = X509PublicKeyCert {
+        certificate_der: valid_cert_builder(node_id)
+            .not_before_unix(0)
+            .not_after("19700102000000Z")
+            .build_ed25519(rng)
+            .cert_der(),
+    };
+
+    let result = ValidTlsCertificate::try_from_with_not_after(
+        cert,
+        node_id,
+        UNIX_EPOCH,
+        one_day_after_unix_epoch(),
+    );
+
+    assert_matches!(result, Ok(_));
+}
+
+#[test]
+fn should_fail_if_tls_certificate_notafter_date_is_not_the_expected_one() {
+    let rng = &mut reproducible_rng();
+    let node_id = node_id(1);
+    let cert = X509PublicKeyCert {
+        certificate_der: valid_cert_builder(node_id)
+            .not_before_unix(0)
+            .build_ed25519(rng)
+            .cert_der(),
+    };
+
+    let result = ValidTlsCertificate::try_from_with_not_after(
+        cert,
+        node_id,
+        UNIX_EPOCH,
+        one_day_after_unix_epoch(),
+    );
+
+    assert_matches!(result, Err(TlsCertValidationError { error })
+        if error.contains("invalid TLS certificate: notAfter date") && error.contains("does not match the expected date")
+    );
+}
+
+#[test]
+fn should_verify_tls_signature_within_validity_period() {
+    let (cert, secret_key) = cert_valid_for_one_day_after_unix_epoch();
+    let message = b"TLS handshake message";
+    let signature = ic_crypto_internal_basic_sig_ed25519::sign(message, &secret_key).unwrap();
+
+    let result = verify_tls_signature(&signature, message, &cert, one_day_after_unix_epoch());
+
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn should_fail_to_verify_tls_signature_if_certificate_has_expired() {
+    let (cert, secret_key) = cert_valid_for_one_day_after_unix_epoch();
+    let message = b"TLS handshake message";
+    let signature = ic_crypto_internal_basic_sig_ed25519::sign(message, &secret_key).unwrap();
+    let one_second_too_late = one_day_after_unix_epoch() + Duration::from_secs(1);
+
+    let result = verify_tls_signature(&signature, message, &cert, one_second_too_late);
+
+    assert_eq!(
+        result,
+        Err(TlsSignatureVerificationError::CertificateExpired {
+            not_after_secs_since_unix_epoch: ONE_DAY_AFTER_UNIX_EPOCH_SECS as i64,
+            current_time: one_second_too_late,
+        })
+    );
+}
+
+#[test]
+fn should_fail_to_verify_tls_signature_on_different_message() {
+    let (cert, secret_key) = cert_valid_for_one_day_after_unix_epoch();
+    let signature =
+        ic_crypto_internal_basic_sig_ed25519::sign(b"TLS handshake message", &secret_key).unwrap();
+
+    let result = verify_tls_signature(&signature, b"other message", &cert, UNIX_EPOCH);
+
+    assert_matches!(
+        result,
+        Err(TlsSignatureVerificationError::InvalidSignature { .. })
+    );
+}
+
+fn cert_valid_for_one_day_after_unix_epoch() -> (X509PublicKeyCert, BasicSigEd25519SecretKeyBytes) {
+    let rng = &mut reproducible_rng();
+    let cert_with_key = valid_cert_builder(node_id(1))
+        .not_before_unix(0)
+        .not_after("19700102000000Z")
+        .build_ed25519(rng);
+    let secret_key = match cert_with_key.key_pair() {
+        KeyPair::Ed25519 { secret_key, .. } => secret_key.clone(),
+        KeyPair::Secp256r1 { .. } => panic!("expected an Ed25519 key pair"),
+    };
+    let cert = X509PublicKeyCert {
+        certificate_der: cert_with_key.cert_der(),
+    };
+    (cert, secret_key)
+}
+
+fn one_day_after_unix_epoch() -> Time {
+    Time::from_secs_since_unix_epoch(ONE_DAY_AFTER_UNIX_EPOCH_SECS).unwrap()
+}
+
 fn valid_cert_builder(node_id: NodeId) -> CertBuilder {
     CertWithPrivateKey::builder().cn(node_id.get().to_string())
 }
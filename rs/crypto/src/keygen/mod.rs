@@ -84,6 +84,16 @@ impl<C: CryptoServiceProvider> KeyManager for CryptoComponentImpl<C> {
                             ),
                         })
                     }
+
+                    Err(PksAndSksContainsErrors::Timeout { method, elapsed }) => {
+                        self.observe_all_key_counts(&KeyCounts::ZERO, MetricsResult::Err);
+                        Err(CryptoError::TransientInternalError {
+                            internal_error: format!(
+                                "Transient error calling pks_and_sks_contains: RPC call {} timed out after {:?}",
+                                method, elapsed
+                            ),
+                        })
+                    }
                 }?;
                 Ok(())
             }
@@ -344,21 +354,13 @@ impl<C: CryptoServiceProvider> CryptoComponentImpl<C> {
         let current_idkg_public_key_proto = self
             .vault
             .current_node_public_keys()
-            .map_err(
-                |CspPublicKeyStoreError::TransientInternalError(internal_error)| {
-                    IDkgDealingEncryptionKeyRotationError::TransientInternalError(internal_error)
-                },
-            )?
+            .map_err(IDkgDealingEncryptionKeyRotationError::from)?
             .idkg_dealing_encryption_public_key
             .ok_or(IDkgDealingEncryptionKeyRotationError::PublicKeyNotFound)?;
         let current_idkg_public_key_proto_with_timestamp = self
             .vault
             .current_node_public_keys_with_timestamps()
-            .map_err(
-                |CspPublicKeyStoreError::TransientInternalError(internal_error)| {
-                    IDkgDealingEncryptionKeyRotationError::TransientInternalError(internal_error)
-                },
-            )?
+            .map_err(IDkgDealingEncryptionKeyRotationError::from)?
             .idkg_dealing_encryption_public_key
             .ok_or(IDkgDealingEncryptionKeyRotationError::PublicKeyNotFound)?;
         if current_idkg_public_key_proto
@@ -464,6 +466,16 @@ impl<C: CryptoServiceProvider> CryptoComponentImpl<C> {
                 self.metrics
                     .observe_idkg_dealing_encryption_pubkey_count(0, MetricsResult::Err);
             }
+            Err(CspPublicKeyStoreError::Timeout { method, elapsed }) => {
+                warn!(
+                    self.logger,
+                    "Timeout retrieving local iDKG dealing encryption public key count: RPC call {} timed out after {:?}",
+                    method,
+                    elapsed
+                );
+                self.metrics
+                    .observe_idkg_dealing_encryption_pubkey_count(0, MetricsResult::Err);
+            }
         };
     }
 }
@@ -92,6 +92,11 @@ impl BasicSigVerifierInternal {
             PublicRandomSeedGeneratorError::TransientInternalError { internal_error } => {
                 CryptoError::TransientInternalError { internal_error }
             }
+            PublicRandomSeedGeneratorError::Timeout { method, elapsed } => {
+                CryptoError::TransientInternalError {
+                    internal_error: format!("RPC call {method} timed out after {elapsed:?}"),
+                }
+            }
         })?;
         let rng = &mut seed.into_rng();
 
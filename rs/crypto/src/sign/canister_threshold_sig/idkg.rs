@@ -491,7 +491,8 @@ impl<C: CryptoServiceProvider> IDkgProtocol for CryptoComponentImpl<C> {
                 | IDkgLoadTranscriptError::InternalError { .. }
                 | IDkgLoadTranscriptError::UnsupportedAlgorithm { .. }
                 | IDkgLoadTranscriptError::RegistryError(_)
-                | IDkgLoadTranscriptError::TransientInternalError { .. } => {
+                | IDkgLoadTranscriptError::TransientInternalError { .. }
+                | IDkgLoadTranscriptError::Timeout { .. } => {
                     // Errors that should not lead to the key being lost
                 }
             }
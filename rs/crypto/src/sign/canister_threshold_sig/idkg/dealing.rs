@@ -286,6 +286,11 @@ fn idkg_create_dealing_vault_error_into_idkg_create_dealing_error(
                 internal_error,
             }
         }
+        IDkgCreateDealingVaultError::Timeout { method, elapsed } => {
+            IDkgCreateDealingError::TransientInternalError {
+                internal_error: format!("RPC call {method} timed out after {elapsed:?}"),
+            }
+        }
         IDkgCreateDealingVaultError::SerializationError(internal_error) => {
             IDkgCreateDealingError::SerializationError {
                 internal_error,
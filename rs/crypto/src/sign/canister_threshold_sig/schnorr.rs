@@ -85,6 +85,9 @@ pub fn create_sig_share(
                 }
                 F::InternalError(s) => T::InternalError(s),
                 F::TransientInternalError(s) => T::TransientInternalError(s),
+                F::Timeout { method, elapsed } => T::TransientInternalError(format!(
+                    "RPC call {method} timed out after {elapsed:?}"
+                )),
             }
         })?;
     let sig_share_raw = sig_share_raw_typed.into_vec();
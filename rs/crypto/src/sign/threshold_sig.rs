@@ -112,13 +112,19 @@ fn map_threshold_sign_error_or_panic(
         CspThresholdSignError::TransientInternalError { internal_error } => {
             ThresholdSignError::TransientInternalError { internal_error }
         }
+        CspThresholdSignError::Timeout { method, elapsed } => {
+            ThresholdSignError::TransientInternalError {
+                internal_error: format!("RPC call {method} timed out after {elapsed:?}"),
+            }
+        }
         CspThresholdSignError::KeyIdInstantiationError(internal_error) => {
             ThresholdSignError::KeyIdInstantiationError(internal_error)
         }
         // Panic, since these would be implementation errors:
         CspThresholdSignError::UnsupportedAlgorithm { .. }
         | CspThresholdSignError::MalformedSecretKey { .. }
-        | CspThresholdSignError::WrongSecretKeyType { .. } => {
+        | CspThresholdSignError::WrongSecretKeyType { .. }
+        | CspThresholdSignError::InvalidArgument { .. } => {
             panic!("Illegal state: {}", error)
         }
     }
@@ -123,7 +123,7 @@ mod create_dealing_error_conversions {
 }
 
 mod verify_dealing_error_conversions {
-    use ic_crypto_internal_threshold_sig_bls12381::api::dkg_errors::SizeError;
+    use ic_crypto_internal_threshold_sig_bls12381::api::dkg_errors::{InternalError, SizeError};
     use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors::CspDkgVerifyReshareDealingError;
     use ic_types::crypto::error::{InvalidArgumentError, MalformedPublicKeyError};
     use ic_types::crypto::threshold_sig::ni_dkg::errors::verify_dealing_error::DkgVerifyDealingError;
@@ -253,4 +253,16 @@ mod verify_dealing_error_conversions {
 
         let _panic = DkgVerifyDealingError::from(csp_error);
     }
+
+    #[test]
+    #[should_panic(
+        expected = "NI-DKG verify_dealing error - TransientInternalError: InternalError { internal_error: \"some error\" }"
+    )]
+    fn should_panic_on_transient_internal_error() {
+        let csp_error = CspDkgVerifyReshareDealingError::TransientInternalError(InternalError {
+            internal_error: "some error".to_string(),
+        });
+
+        let _panic = DkgVerifyDealingError::from(csp_error);
+    }
 }
@@ -75,7 +75,8 @@ impl rustls::sign::Signer for CspServerEd25519Signer {
                 | CspTlsSignError::WrongSecretKeyType { .. }
                 | CspTlsSignError::MalformedSecretKey { .. }
                 | CspTlsSignError::SigningFailed { .. }
-                | CspTlsSignError::TransientInternalError { .. } => TLSError::General(format!(
+                | CspTlsSignError::TransientInternalError { .. }
+                | CspTlsSignError::Timeout { .. } => TLSError::General(format!(
                     "Failed to create signature during \
                      TLS handshake by means of the CspServerEd25519Signer: {:?}",
                     e
@@ -278,6 +278,10 @@ pub mod internal {
                     vault_client_runtime: TokioRuntimeOrHandle::new(
                         self.vault_client_runtime_handle,
                     ),
+                    vault_client_max_attempts: None,
+                    vault_client_tls_config: None,
+                    vault_client_compression: None,
+                    vault_client_request_timeout: None,
                 }
             });
 
@@ -1,16 +1,31 @@
 use ic_crypto_internal_csp::vault::api::CspVault;
 use ic_crypto_internal_csp::vault::local_csp_vault::ProdLocalCspVault;
 use ic_crypto_internal_csp::vault::remote_csp_vault::{
-    RemoteCspVault, RemoteCspVaultBuilder, TarpcCspVaultServerImpl, TarpcCspVaultServerImplBuilder,
+    CompressionEncoding, RemoteCspVault, RemoteCspVaultBuilder, TarpcCspVaultServerImpl,
+    TarpcCspVaultServerImplBuilder, VaultClientTlsConfig, VaultServerTlsConfig,
 };
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tempfile::TempDir;
 use tokio::net::UnixListener;
 
 pub struct RemoteVaultEnvironment<C> {
     pub vault_server: TempCspVaultServer<C>,
     pub vault_client_runtime: TokioRuntimeOrHandle,
+    /// Maximum number of attempts of vault clients created by this environment
+    /// for RPC calls whose connection was refused or reset. Uses the default of
+    /// `RemoteCspVaultBuilder` if `None`.
+    pub vault_client_max_attempts: Option<u32>,
+    /// TLS config of vault clients created by this environment. Clients
+    /// connect without TLS if `None`.
+    pub vault_client_tls_config: Option<VaultClientTlsConfig>,
+    /// Compression of the requests sent by vault clients created by this
+    /// environment. Requests are not compressed if `None`.
+    pub vault_client_compression: Option<CompressionEncoding>,
+    /// Timeout of all RPC calls of vault clients created by this environment.
+    /// Uses the defaults of `RemoteCspVaultBuilder` if `None`.
+    pub vault_client_request_timeout: Option<Duration>,
 }
 
 impl<C: CspVault + 'static> RemoteVaultEnvironment<C> {
@@ -18,8 +33,27 @@ impl<C: CspVault + 'static> RemoteVaultEnvironment<C> {
         RemoteVaultEnvironment {
             vault_server: TempCspVaultServer::start_with_local_csp_vault(local_csp_vault),
             vault_client_runtime: TokioRuntimeOrHandle::new(None),
+            vault_client_max_attempts: None,
+            vault_client_tls_config: None,
+            vault_client_compression: None,
+            vault_client_request_timeout: None,
         }
     }
+
+    /// Starts a server that requires clients to connect via TLS, with the
+    /// server authenticating itself with the given PEM-encoded certificate
+    /// chain and private key.
+    ///
+    /// Clients created by the returned environment connect without TLS and
+    /// are thus rejected unless configured with
+    /// [`Self::with_vault_client_trust_anchor`].
+    pub fn start_server_with_tls(local_csp_vault: Arc<C>, cert_pem: &[u8], key_pem: &[u8]) -> Self {
+        let tls_config = VaultServerTlsConfig::from_pem(cert_pem, key_pem)
+            .expect("invalid server certificate or key");
+        Self::start_server(
+            TarpcCspVaultServerImpl::builder_for_test(local_csp_vault).with_tls_config(tls_config),
+        )
+    }
 }
 
 impl<Builder> RemoteVaultEnvironment<Builder> {
@@ -32,10 +66,56 @@ impl<Builder> RemoteVaultEnvironment<Builder> {
     }
 
     pub fn new_vault_client_builder(&self) -> RemoteCspVaultBuilder {
-        RemoteCspVault::builder(
+        let builder = RemoteCspVault::builder(
             self.vault_server.vault_socket_path(),
             self.vault_client_runtime.handle().clone(),
-        )
+        );
+        let builder = match self.vault_client_max_attempts {
+            Some(max_attempts) => builder.with_max_attempts(max_attempts),
+            None => builder,
+        };
+        let builder = match &self.vault_client_tls_config {
+            Some(tls_config) => builder.with_tls_config(tls_config.clone()),
+            None => builder,
+        };
+        let builder = match self.vault_client_compression {
+            Some(encoding) => builder.with_compression(encoding),
+            None => builder,
+        };
+        match self.vault_client_request_timeout {
+            Some(timeout) => builder.with_rpc_timeouts(timeout),
+            None => builder,
+        }
+    }
+
+    pub fn with_vault_client_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.vault_client_max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Makes vault clients connect via TLS, trusting the PEM-encoded
+    /// certificates in `trust_anchor_pem` to authenticate the server.
+    pub fn with_vault_client_trust_anchor(mut self, trust_anchor_pem: &[u8]) -> Self {
+        self.vault_client_tls_config =
+            Some(VaultClientTlsConfig::from_pem(trust_anchor_pem).expect("invalid trust anchor"));
+        self
+    }
+
+    /// Makes vault clients created by this environment compress their
+    /// requests with `encoding`. The server is not affected, see
+    /// [`RemoteVaultEnvironment::with_compression`].
+    pub fn with_vault_client_compression(mut self, encoding: CompressionEncoding) -> Self {
+        self.vault_client_compression = Some(encoding);
+        self
+    }
+
+    /// Makes all RPC calls of vault clients created by this environment time
+    /// out after `timeout`, so that tests do not hang if the server does not
+    /// respond. Calls that time out fail with the transient error of the
+    /// respective vault method, naming the method and the elapsed time.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.vault_client_request_timeout = Some(timeout);
+        self
     }
 
     pub fn shutdown_server_now(&mut self) {
@@ -48,12 +128,31 @@ impl<C: CspVault + 'static> RemoteVaultEnvironment<C> {
         RemoteVaultEnvironment {
             vault_server: TempCspVaultServer::start_server(server_builder),
             vault_client_runtime: TokioRuntimeOrHandle::new(None),
+            vault_client_max_attempts: None,
+            vault_client_tls_config: None,
+            vault_client_compression: None,
+            vault_client_request_timeout: None,
         }
     }
 
     pub fn restart_server(&mut self) {
         self.vault_server.restart();
     }
+
+    /// Compresses the messages sent by both the server, which is restarted,
+    /// and the vault clients created by this environment with `encoding`.
+    pub fn with_compression(mut self, encoding: CompressionEncoding) -> Self {
+        self.vault_server.restart_with_compression(encoding);
+        self.with_vault_client_compression(encoding)
+    }
+
+    /// Restarts the server with compression of its responses, leaving the
+    /// vault clients created by this environment uncompressed unless
+    /// configured with [`Self::with_vault_client_compression`].
+    pub fn with_server_compression(mut self, encoding: CompressionEncoding) -> Self {
+        self.vault_server.restart_with_compression(encoding);
+        self
+    }
 }
 
 pub enum TokioRuntimeOrHandle {
@@ -205,6 +304,13 @@ impl<C: CspVault + 'static> TempCspVaultServer<C> {
             _join_handle: join_handle,
         };
     }
+
+    /// Restarts the server such that it compresses its responses with
+    /// `encoding`.
+    pub fn restart_with_compression(&mut self, encoding: CompressionEncoding) {
+        self.server_builder = self.server_builder.clone().with_compression(encoding);
+        self.restart();
+    }
 }
 
 impl<Builder> TempCspVaultServer<Builder> {
@@ -57,6 +57,12 @@ mock! {
             msg: &[u8],
             algorithm_id: AlgorithmId,
         ) -> CryptoResult<()>;
+
+        fn verify_multisig_batch<'a>(
+            &self,
+            batch: &[(CspSignature, &'a [u8], Vec<CspPublicKey>)],
+            algorithm_id: AlgorithmId,
+        ) -> CryptoResult<()>;
     }
 
     impl ThresholdSignatureCspClient for AllCryptoServiceProvider {
@@ -3,6 +3,7 @@ use ic_crypto_internal_csp::key_id::KeyId;
 use ic_crypto_internal_csp::types::{CspPop, CspPublicKey, CspSignature, ExternalPublicKeys};
 use ic_crypto_internal_csp::vault::api::BasicSignatureCspVault;
 use ic_crypto_internal_csp::vault::api::CspBasicSignatureError;
+use ic_crypto_internal_csp::vault::api::CspBasicSignatureKeygenAlgorithm;
 use ic_crypto_internal_csp::vault::api::CspBasicSignatureKeygenError;
 use ic_crypto_internal_csp::vault::api::CspMultiSignatureError;
 use ic_crypto_internal_csp::vault::api::CspMultiSignatureKeygenError;
@@ -10,10 +11,15 @@ use ic_crypto_internal_csp::vault::api::CspPublicKeyStoreError;
 use ic_crypto_internal_csp::vault::api::CspSecretKeyStoreContainsError;
 use ic_crypto_internal_csp::vault::api::CspTlsKeygenError;
 use ic_crypto_internal_csp::vault::api::CspTlsSignError;
+use ic_crypto_internal_csp::vault::api::CspVaultHealthStatus;
+use ic_crypto_internal_csp::vault::api::CspVaultHealthStatusError;
+use ic_crypto_internal_csp::vault::api::CspVaultKeyActivationError;
+use ic_crypto_internal_csp::vault::api::HealthStatusCspVault;
 use ic_crypto_internal_csp::vault::api::IDkgCreateDealingVaultError;
 use ic_crypto_internal_csp::vault::api::IDkgDealingInternalBytes;
 use ic_crypto_internal_csp::vault::api::IDkgProtocolCspVault;
 use ic_crypto_internal_csp::vault::api::IDkgTranscriptInternalBytes;
+use ic_crypto_internal_csp::vault::api::KeyActivationCspVault;
 use ic_crypto_internal_csp::vault::api::MultiSignatureCspVault;
 use ic_crypto_internal_csp::vault::api::NiDkgCspVault;
 use ic_crypto_internal_csp::vault::api::PksAndSksContainsErrors;
@@ -50,9 +56,10 @@ use ic_types::crypto::canister_threshold_sig::error::{
 use ic_types::crypto::canister_threshold_sig::idkg::{
     BatchSignedIDkgDealing, IDkgTranscriptOperation,
 };
+use ic_types::crypto::threshold_sig::ni_dkg::NiDkgTranscript;
 use ic_types::crypto::ExtendedDerivationPath;
 use ic_types::crypto::{AlgorithmId, CurrentNodePublicKeys};
-use ic_types::{NodeId, NodeIndex, NumberOfNodes, Randomness};
+use ic_types::{NodeId, NodeIndex, NumberOfNodes, Randomness, Time};
 use mockall::mock;
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -68,6 +75,11 @@ mock! {
         ) -> Result<CspSignature, CspBasicSignatureError>;
 
         fn gen_node_signing_key_pair(&self) -> Result<CspPublicKey, CspBasicSignatureKeygenError>;
+
+        fn gen_basic_signature_key_pair(
+            &self,
+            algorithm: CspBasicSignatureKeygenAlgorithm,
+        ) -> Result<CspPublicKey, CspBasicSignatureKeygenError>;
     }
 
     impl MultiSignatureCspVault for LocalCspVault {
@@ -78,6 +90,20 @@ mock! {
             key_id: KeyId,
         ) -> Result<CspSignature, CspMultiSignatureError>;
 
+        fn batch_sign<'a>(
+            &self,
+            algorithm_id: AlgorithmId,
+            messages: &[&'a [u8]],
+            key_id: KeyId,
+        ) -> Vec<Result<CspSignature, CspMultiSignatureError>>;
+
+        fn multi_sign_batch(
+            &self,
+            algorithm_id: AlgorithmId,
+            messages: Vec<Vec<u8>>,
+            key_id: KeyId,
+        ) -> Result<Vec<CspSignature>, CspMultiSignatureError>;
+
         fn gen_committee_signing_key_pair(
             &self,
         ) -> Result<(CspPublicKey, CspPop), CspMultiSignatureKeygenError>;
@@ -90,6 +116,12 @@ mock! {
             message: Vec<u8>,
             key_id: KeyId,
         ) -> Result<CspSignature, CspThresholdSignError>;
+
+        fn combine_threshold_sig_shares(
+            &self,
+            shares: &BTreeMap<NodeId, CspSignature>,
+            transcript: &NiDkgTranscript,
+        ) -> Result<CspSignature, CspThresholdSignError>;
     }
 
     impl NiDkgCspVault for LocalCspVault {
@@ -115,6 +147,15 @@ mock! {
             maybe_resharing_secret: Option<KeyId>,
         ) -> Result<CspNiDkgDealing, ni_dkg_errors::CspDkgCreateReshareDealingError>;
 
+        fn verify_dealings_batch(
+            &self,
+            algorithm_id: AlgorithmId,
+            threshold: NumberOfNodes,
+            epoch: Epoch,
+            receiver_keys: BTreeMap<NodeIndex, CspFsEncryptionPublicKey>,
+            dealings: &[(NodeIndex, CspNiDkgDealing)],
+        ) -> Vec<Result<(), ni_dkg_errors::CspDkgVerifyDealingError>>;
+
         fn load_threshold_signing_key(
             &self,
             algorithm_id: AlgorithmId,
@@ -238,6 +279,12 @@ mock! {
             node: NodeId,
         ) -> Result<TlsPublicKeyCert, CspTlsKeygenError>;
 
+        fn gen_tls_key_pair_with_validity(
+            &self,
+            node: NodeId,
+            not_after: Time,
+        ) -> Result<TlsPublicKeyCert, CspTlsKeygenError>;
+
         fn tls_sign(&self, message: Vec<u8>, key_id: KeyId) -> Result<CspSignature, CspTlsSignError>;
     }
 
@@ -245,6 +292,16 @@ mock! {
         fn new_public_seed(&self) -> Result<Seed, PublicRandomSeedGeneratorError>;
     }
 
+    impl HealthStatusCspVault for LocalCspVault {
+        fn health_status(&self) -> Result<CspVaultHealthStatus, CspVaultHealthStatusError>;
+    }
+
+    impl KeyActivationCspVault for LocalCspVault {
+        fn mark_key_inactive(&self, key_id: KeyId) -> Result<(), CspVaultKeyActivationError>;
+
+        fn mark_key_active(&self, key_id: KeyId) -> Result<(), CspVaultKeyActivationError>;
+    }
+
     impl PublicKeyStoreCspVault for LocalCspVault {
         fn current_node_public_keys(&self) -> Result<CurrentNodePublicKeys, CspPublicKeyStoreError>;
 
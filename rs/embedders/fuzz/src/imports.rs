@@ -41,6 +41,7 @@ pub(crate) fn system_api_imports() -> Vec<u8> {
         *MAX_SUBNET_AVAILABLE_MEMORY,
         config.feature_flags.wasm_native_stable_memory,
         config.feature_flags.canister_backtrace,
+        config.feature_flags.redact_error_payloads,
         config.max_sum_exported_function_name_lengths,
         Memory::new_for_testing(),
         NumWasmPages::from(0),
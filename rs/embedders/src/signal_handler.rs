@@ -1,7 +1,10 @@
 use crate::wasmtime_embedder::host_memory::MemoryPageSize;
+use crate::wasmtime_embedder::{CanisterMemoryType, SharedPageAccessTrace};
 
+use ic_replicated_state::PageIndex;
+use ic_sys::PAGE_SIZE;
 use libc::c_void;
-use memory_tracker::{signal_access_kind_and_address, SigsegvMemoryTracker};
+use memory_tracker::{signal_access_kind_and_address, PageAccess, SigsegvMemoryTracker};
 use std::convert::TryFrom;
 use std::sync::MutexGuard;
 use std::sync::{atomic::Ordering, Arc, Mutex};
@@ -9,18 +12,26 @@ use std::sync::{atomic::Ordering, Arc, Mutex};
 const WASM_PAGE_SIZE: u32 = wasmtime_environ::Memory::DEFAULT_PAGE_SIZE;
 
 /// Helper function to create a memory tracking SIGSEGV handler function.
+///
+/// If `page_access_trace` holds a trace, every fault handled by one of the
+/// memory trackers is recorded in it.
 pub(crate) fn sigsegv_memory_tracker_handler(
-    memories: Vec<(Arc<Mutex<SigsegvMemoryTracker>>, MemoryPageSize)>,
+    memories: Vec<(
+        Arc<Mutex<SigsegvMemoryTracker>>,
+        MemoryPageSize,
+        CanisterMemoryType,
+    )>,
+    page_access_trace: SharedPageAccessTrace,
 ) -> impl Fn(i32, *const libc::siginfo_t, *const libc::c_void) -> bool + Send + Sync {
     let mut memories: Vec<_> = memories
         .into_iter()
-        .map(|(t, size)| {
+        .map(|(t, size, memory_type)| {
             let base = t.lock().unwrap().area().addr();
-            (base, t, size)
+            (base, t, size, memory_type)
         })
         .collect();
 
-    memories.sort_by_key(|(base, _, _)| *base);
+    memories.sort_by_key(|(base, _, _, _)| *base);
 
     let check_if_expanded =
         move |tracker: &mut MutexGuard<SigsegvMemoryTracker>,
@@ -64,16 +75,24 @@ pub(crate) fn sigsegv_memory_tracker_handler(
         // generate accesses extending beyond the guard pages on either end of
         // each memory. So any access within a given memory range is guaranteed
         // to be an access that was intended for that memory.
-        let (_, memory_tracker, memory_page_size) = memories
+        let (_, memory_tracker, memory_page_size, memory_type) = memories
             .iter()
             .rev()
-            .find(|(base, _, _)| *base as *mut c_void <= si_addr)
+            .find(|(base, _, _, _)| *base as *mut c_void <= si_addr)
             .unwrap_or(&memories[0]);
 
         let mut memory_tracker = memory_tracker.lock().unwrap();
 
         // We handle SIGSEGV from the Wasm module heap ourselves.
         if memory_tracker.area().is_within(si_addr) {
+            if let Some(trace) = page_access_trace.lock().unwrap().as_mut() {
+                let page = (si_addr as usize - memory_tracker.area().addr()) / PAGE_SIZE;
+                trace.record(PageAccess {
+                    memory: *memory_type,
+                    page: PageIndex::new(page as u64),
+                    access_kind,
+                });
+            }
             // Returns true if the signal has been handled by our handler which indicates
             // that the instance should continue.
             memory_tracker.handle_sigsegv(access_kind, si_addr)
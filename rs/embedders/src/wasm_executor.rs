@@ -601,6 +601,7 @@ pub fn process(
         subnet_available_memory,
         embedder.config().feature_flags.wasm_native_stable_memory,
         embedder.config().feature_flags.canister_backtrace,
+        embedder.config().feature_flags.redact_error_payloads,
         embedder.config().max_sum_exported_function_name_lengths,
         stable_memory.clone(),
         wasm_memory.size,
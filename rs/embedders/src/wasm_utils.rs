@@ -10,7 +10,7 @@ use ic_replicated_state::{
     EmbedderCache, NumWasmPages, PageIndex,
 };
 use ic_sys::{PageBytes, PAGE_SIZE};
-use ic_types::{methods::WasmMethod, NumInstructions};
+use ic_types::{methods::WasmMethod, NumBytes, NumInstructions};
 use ic_wasm_types::{BinaryEncodedWasm, WasmInstrumentationError};
 use serde::{Deserialize, Serialize};
 
@@ -46,6 +46,10 @@ pub struct WasmValidationDetails {
     pub wasm_metadata: WasmMetadata,
     pub largest_function_instruction_count: NumInstructions,
     pub max_complexity: Complexity,
+    /// The number of functions defined (not imported) by the module.
+    pub function_count: usize,
+    /// The size of the largest function body in the module.
+    pub largest_function_body_size: NumBytes,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
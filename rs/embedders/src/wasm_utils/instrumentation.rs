@@ -98,6 +98,12 @@
 //! bound by the length of the longest execution path consisting of
 //! non-reentrant basic blocks.
 //!
+//! Every branch (`br`, `br_if`, `br_table`, `else`), `end` and instruction that
+//! leaves the function (`return`, `unreachable` and tail calls) ends the current
+//! basic block. As every branch target (right after a `loop`, `if`, `else` or
+//! `end`) starts a new basic block as well, early exits out of nested blocks
+//! charge exactly the instructions on the taken path.
+//!
 //! # Wasm-native stable memory
 //!
 //! Two additional memories are inserted for stable memory. One is the actual
@@ -434,6 +440,8 @@ pub fn instruction_to_cost(i: &Operator, mem_type: WasmMemoryType) -> u64 {
         Operator::Return { .. } | Operator::Drop | Operator::Unreachable | Operator::Nop => 1,
 
         // Branching instructions should be of cost 2.
+        // `br_table` is compiled to a bounds check and a jump through a table, so its
+        // cost does not depend on the number of targets.
         Operator::If { .. }
         | Operator::Br { .. }
         | Operator::BrIf { .. }
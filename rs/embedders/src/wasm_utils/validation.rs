@@ -1043,21 +1043,6 @@ fn validate_global_section(module: &Module, max_globals: usize) -> Result<(), Wa
     Ok(())
 }
 
-// Checks that no more than `max_functions` are defined in the
-// module.
-fn validate_function_section(
-    module: &Module,
-    max_functions: usize,
-) -> Result<(), WasmValidationError> {
-    if module.functions.len() > max_functions {
-        return Err(WasmValidationError::TooManyFunctions {
-            defined: module.functions.len(),
-            allowed: max_functions,
-        });
-    }
-    Ok(())
-}
-
 // Checks that the initial size of the wasm (heap) memory is not larger than
 // the allowed maximum size. This is only needed for Wasm64, because in Wasm32 this
 // is checked by Wasmtime.
@@ -1514,6 +1499,56 @@ fn check_code_section_size(wasm: &BinaryEncodedWasm) -> Result<(), WasmValidatio
     Ok(())
 }
 
+/// Checks that no more than `max_functions` are defined in the module and
+/// that no function body exceeds `max_function_body_size_bytes`.
+///
+/// This only reads the section headers and the size prefixes of the function
+/// bodies, so oversized modules are rejected without decoding or compiling
+/// their code. Returns the number of defined functions and the size of the
+/// largest function body.
+fn check_function_limits(
+    wasm: &BinaryEncodedWasm,
+    max_functions: usize,
+    max_function_body_size_bytes: usize,
+) -> Result<(usize, NumBytes), WasmValidationError> {
+    let mut function_count = 0;
+    let mut largest_function_body_size = 0;
+    let mut index = 0;
+    let parser = wasmparser::Parser::new(0);
+    for payload in parser.parse_all(wasm.as_slice()) {
+        match payload.map_err(|e| {
+            WasmValidationError::DecodingError(format!("Error finding function bodies: {}", e))
+        })? {
+            wasmparser::Payload::FunctionSection(reader) => {
+                function_count = reader.count() as usize;
+                if function_count > max_functions {
+                    return Err(WasmValidationError::TooManyFunctions {
+                        defined: function_count,
+                        allowed: max_functions,
+                    });
+                }
+            }
+            wasmparser::Payload::CodeSectionEntry(body) => {
+                let size = body.range().len();
+                if size > max_function_body_size_bytes {
+                    return Err(WasmValidationError::FunctionBodyTooLarge {
+                        index,
+                        size,
+                        allowed: max_function_body_size_bytes,
+                    });
+                }
+                largest_function_body_size = cmp::max(largest_function_body_size, size);
+                index += 1;
+            }
+            _ => {}
+        }
+    }
+    Ok((
+        function_count,
+        NumBytes::new(largest_function_body_size as u64),
+    ))
+}
+
 /// Validates a Wasm binary against the requirements of the interface spec
 /// defined in https://internetcomputer.org/docs/current/references/ic-interface-spec#system-api-module.
 ///
@@ -1535,6 +1570,11 @@ pub(super) fn validate_wasm_binary<'a>(
 ) -> Result<(WasmValidationDetails, Module<'a>), WasmValidationError> {
     check_code_section_size(wasm)?;
     can_compile(wasm, config)?;
+    let (function_count, largest_function_body_size) = check_function_limits(
+        wasm,
+        config.max_functions,
+        config.max_function_body_size_bytes,
+    )?;
     let module = Module::parse(wasm.as_slice(), false)
         .map_err(|err| WasmValidationError::DecodingError(format!("{}", err)))?;
     let imports_details = validate_import_section(&module)?;
@@ -1545,7 +1585,6 @@ pub(super) fn validate_wasm_binary<'a>(
     )?;
     validate_data_section(&module)?;
     validate_global_section(&module, config.max_globals)?;
-    validate_function_section(&module, config.max_functions)?;
     validate_initial_wasm_memory_size(&module, config.max_wasm_memory_size)?;
     let (largest_function_instruction_count, max_complexity) = validate_code_section(&module)?;
     let wasm_metadata = validate_custom_section(&module, config)?;
@@ -1555,6 +1594,8 @@ pub(super) fn validate_wasm_binary<'a>(
             wasm_metadata,
             largest_function_instruction_count,
             max_complexity,
+            function_count,
+            largest_function_body_size,
         },
         module,
     ))
@@ -1,4 +1,5 @@
 pub mod host_memory;
+pub mod integer_only;
 mod signal_stack;
 /// pub for usage in fuzzing
 #[doc(hidden)]
@@ -35,7 +36,7 @@ use ic_types::{
     CanisterId, NumInstructions, NumOsPages, MAX_STABLE_MEMORY_IN_BYTES,
 };
 use ic_wasm_types::{BinaryEncodedWasm, WasmEngineError};
-use memory_tracker::{DirtyPageTracking, PageBitmap, SigsegvMemoryTracker};
+use memory_tracker::{DirtyPageTracking, PageAccessTrace, PageBitmap, SigsegvMemoryTracker};
 use signal_stack::WasmtimeSignalStack;
 
 use crate::wasm_utils::instrumentation::{
@@ -183,6 +184,10 @@ impl std::fmt::Display for CanisterMemoryType {
     }
 }
 
+/// Page access trace shared between a `WasmtimeInstance` and its SIGSEGV
+/// handler. Tracing is enabled iff it holds a trace.
+pub(crate) type SharedPageAccessTrace = Arc<Mutex<Option<PageAccessTrace<CanisterMemoryType>>>>;
+
 /// Information needed to instantiate a Wasm memory.
 struct WasmMemoryInfo {
     /// The exported name of the memory.
@@ -299,6 +304,14 @@ impl WasmtimeEmbedder {
             )))
         })?;
 
+        // Trust but verify: having been successfully linked, the module's function
+        // imports match the System API signatures, none of which take or return
+        // floats.
+        debug_assert!(module
+            .imports()
+            .filter_map(|import| import.ty().func().cloned())
+            .all(|ty| integer_only::is_integer_only_signature(&ty)));
+
         // Note that a wasmtime::InstancePre object is cheaply clonable (just doing
         // a bit of reference counting, i.e. it is a "shallow copy"). This is
         // important because EmbedderCache is cloned frequently, and that must
@@ -524,7 +537,13 @@ impl WasmtimeEmbedder {
             }
         }
 
-        let memory_trackers = sigsegv_memory_tracker(memories, &mut store, self.log.clone());
+        let page_access_trace = SharedPageAccessTrace::default();
+        let memory_trackers = sigsegv_memory_tracker(
+            memories,
+            &mut store,
+            self.log.clone(),
+            Arc::clone(&page_access_trace),
+        );
 
         let signal_stack = WasmtimeSignalStack::new();
         let mut main_memory_type = WasmMemoryType::Wasm32;
@@ -543,7 +562,9 @@ impl WasmtimeEmbedder {
 
         Ok(WasmtimeInstance {
             instance,
+            module: instance_pre.module().clone(),
             memory_trackers,
+            page_access_trace,
             signal_stack,
             log: self.log.clone(),
             instance_stats: InstanceStats::default(),
@@ -684,6 +705,7 @@ fn sigsegv_memory_tracker<S>(
     memories: HashMap<CanisterMemoryType, MemorySigSegvInfo>,
     store: &mut wasmtime::Store<S>,
     log: ReplicaLogger,
+    page_access_trace: SharedPageAccessTrace,
 ) -> HashMap<CanisterMemoryType, Arc<Mutex<SigsegvMemoryTracker>>> {
     let mut tracked_memories = vec![];
     let mut result = HashMap::new();
@@ -720,10 +742,15 @@ fn sigsegv_memory_tracker<S>(
             ))
         };
         result.insert(mem_type, Arc::clone(&sigsegv_memory_tracker));
-        tracked_memories.push((sigsegv_memory_tracker, current_memory_size_in_pages));
+        tracked_memories.push((
+            sigsegv_memory_tracker,
+            current_memory_size_in_pages,
+            mem_type,
+        ));
     }
 
-    let handler = crate::signal_handler::sigsegv_memory_tracker_handler(tracked_memories);
+    let handler =
+        crate::signal_handler::sigsegv_memory_tracker_handler(tracked_memories, page_access_trace);
     // http://man7.org/linux/man-pages/man7/signal-safety.7.html
     unsafe {
         store.set_signal_handler(handler);
@@ -789,10 +816,80 @@ pub struct PageAccessResults {
     pub stable_copy_page_count: usize,
 }
 
+/// Rough size of the store and `vmctx` structures wasmtime allocates for
+/// every instance, independent of the module.
+const STORE_OVERHEAD_ESTIMATE: usize = 16 * 1024;
+
+/// Size of a wasmtime global definition; values are padded to 16 bytes.
+const GLOBAL_DEFINITION_SIZE_ESTIMATE: usize = 16;
+
+/// Memory consumed by the engine on behalf of a single instance.
+///
+/// `compiled_code_bytes`, `linear_memory_reserved_bytes` and
+/// `linear_memory_resident_bytes` are measured. `instance_bookkeeping_bytes`
+/// is an estimate: wasmtime does not expose the size of its internal
+/// structures, so it is derived from the number of table elements and
+/// globals of the module plus a fixed store overhead.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EngineMemoryReport {
+    /// Size of the compiled machine code of the module. The code is shared by
+    /// all instances of the same module.
+    pub compiled_code_bytes: usize,
+    /// Estimated size of the tables, globals and store of the instance.
+    pub instance_bookkeeping_bytes: usize,
+    /// Address space reserved for the linear memories, excluding guard
+    /// regions. Reserved memory does not consume physical memory until it is
+    /// touched.
+    pub linear_memory_reserved_bytes: usize,
+    /// Physical memory backing the tracked linear memories (Wasm heap and
+    /// stable memory). On Linux this is measured with `mincore`, elsewhere it
+    /// is the number of accessed pages.
+    pub linear_memory_resident_bytes: usize,
+}
+
+impl EngineMemoryReport {
+    /// Returns the reserved address space of the linear memories that is not
+    /// backed by physical memory.
+    pub fn linear_memory_untouched_bytes(&self) -> usize {
+        self.linear_memory_reserved_bytes
+            .saturating_sub(self.linear_memory_resident_bytes)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn resident_bytes(tracker: &SigsegvMemoryTracker) -> usize {
+    let area = tracker.area();
+    let num_pages = area.size() / PAGE_SIZE;
+    if num_pages == 0 {
+        return 0;
+    }
+    let mut residency = vec![0_u8; num_pages];
+    // SAFETY: the area is a page-aligned mapping owned by the instance and
+    // `residency` has one entry per page of it.
+    let result = unsafe {
+        libc::mincore(
+            area.addr() as *mut libc::c_void,
+            area.size(),
+            residency.as_mut_ptr(),
+        )
+    };
+    if result != 0 {
+        return tracker.num_accessed_pages() * PAGE_SIZE;
+    }
+    residency.iter().filter(|page| *page & 1 != 0).count() * PAGE_SIZE
+}
+
+#[cfg(not(target_os = "linux"))]
+fn resident_bytes(tracker: &SigsegvMemoryTracker) -> usize {
+    tracker.num_accessed_pages() * PAGE_SIZE
+}
+
 /// Encapsulates a Wasmtime instance on the Internet Computer.
 pub struct WasmtimeInstance {
     instance: wasmtime::Instance,
+    module: Module,
     memory_trackers: HashMap<CanisterMemoryType, Arc<Mutex<SigsegvMemoryTracker>>>,
+    page_access_trace: SharedPageAccessTrace,
     signal_stack: WasmtimeSignalStack,
     log: ReplicaLogger,
     instance_stats: InstanceStats,
@@ -823,6 +920,21 @@ impl WasmtimeInstance {
         self.store.data()
     }
 
+    /// Starts recording the page faults handled by the memory trackers, up to
+    /// `max_len` faults. Discards any previously recorded trace.
+    ///
+    /// Tracing is meant for tuning the prefetching heuristics in tests and
+    /// benchmarks; it does not affect execution or its stats.
+    pub fn enable_page_access_trace(&mut self, max_len: usize) {
+        *self.page_access_trace.lock().unwrap() = Some(PageAccessTrace::new(max_len));
+    }
+
+    /// Stops recording page faults and returns the trace recorded since
+    /// `enable_page_access_trace()`, if it was called.
+    pub fn take_page_access_trace(&mut self) -> Option<PageAccessTrace<CanisterMemoryType>> {
+        self.page_access_trace.lock().unwrap().take()
+    }
+
     fn invoke_export(&mut self, export: &str, args: &[Val]) -> HypervisorResult<()> {
         self.instance
             .get_export(&mut self.store, export)
@@ -1316,6 +1428,43 @@ impl WasmtimeInstance {
     ///
     /// Note that stats must be available even if this instance trapped.
     pub fn get_stats(&self) -> InstanceStats {
-        self.instance_stats.clone()
+        let report = self.engine_memory_report();
+        InstanceStats {
+            engine_compiled_code_bytes: report.compiled_code_bytes,
+            engine_instance_bookkeeping_bytes: report.instance_bookkeeping_bytes,
+            linear_memory_reserved_bytes: report.linear_memory_reserved_bytes,
+            linear_memory_resident_bytes: report.linear_memory_resident_bytes,
+            ..self.instance_stats.clone()
+        }
+    }
+
+    /// Returns the memory the engine currently consumes on behalf of this
+    /// instance, for capacity planning. See [`EngineMemoryReport`] for which
+    /// numbers are measured and which are estimated.
+    pub fn engine_memory_report(&self) -> EngineMemoryReport {
+        let compiled_code = self.module.image_range();
+        let resources = self.module.resources_required();
+        let table_bytes = resources.num_tables as usize
+            * resources.max_initial_table_size.unwrap_or(0) as usize
+            * size_of::<usize>();
+        let num_globals = self
+            .module
+            .exports()
+            .filter(|export| matches!(export.ty(), wasmtime::ExternType::Global(_)))
+            .count();
+        let linear_memory_resident_bytes = self
+            .memory_trackers
+            .values()
+            .map(|tracker| resident_bytes(&tracker.lock().unwrap()))
+            .sum();
+        EngineMemoryReport {
+            compiled_code_bytes: compiled_code.end as usize - compiled_code.start as usize,
+            instance_bookkeeping_bytes: table_bytes
+                + num_globals * GLOBAL_DEFINITION_SIZE_ESTIMATE
+                + STORE_OVERHEAD_ESTIMATE,
+            linear_memory_reserved_bytes: resources.num_memories as usize
+                * MAX_STABLE_MEMORY_IN_BYTES as usize,
+            linear_memory_resident_bytes,
+        }
     }
 }
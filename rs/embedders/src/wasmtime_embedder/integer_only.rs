@@ -0,0 +1,134 @@
+//! Type-level enforcement that values flowing from the System API back into a
+//! canister are never computed from floating point numbers.
+//!
+//! Floating point results may differ across CPUs, compilers and optimization
+//! levels, so any float that influences a value observable by a canister risks
+//! replica divergence. All System API dispatch helpers in
+//! [`super::system_api`] require their results to implement [`IntegerOnly`],
+//! which is implemented for integer types only.
+
+use ic_types::Time;
+use wasmtime::{FuncType, ValType};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Marker trait for types whose values are exact integers (or contain no data
+/// at all) and may thus be returned to Wasm by System API implementations.
+///
+/// The trait is sealed and deliberately not implemented for `f32` or `f64`:
+///
+/// ```
+/// use ic_embedders::wasmtime_embedder::integer_only::IntegerOnly;
+///
+/// fn returned_to_wasm<T: IntegerOnly>(value: T) -> T {
+///     value
+/// }
+/// returned_to_wasm(42_u64);
+/// ```
+///
+/// ```compile_fail
+/// use ic_embedders::wasmtime_embedder::integer_only::IntegerOnly;
+///
+/// fn returned_to_wasm<T: IntegerOnly>(value: T) -> T {
+///     value
+/// }
+/// returned_to_wasm(0.5_f64);
+/// ```
+///
+/// Nor can it be implemented outside of this module:
+///
+/// ```compile_fail
+/// use ic_embedders::wasmtime_embedder::integer_only::IntegerOnly;
+///
+/// struct Ratio(f64);
+/// impl IntegerOnly for Ratio {}
+/// ```
+pub trait IntegerOnly: private::Sealed {}
+
+macro_rules! impl_integer_only {
+    ($($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+            impl IntegerOnly for $t {}
+        )*
+    };
+}
+
+impl_integer_only!(
+    (),
+    bool,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    Time
+);
+
+/// Returns `true` iff none of the parameters or results of the given function
+/// type are floating point values.
+pub(crate) fn is_integer_only_signature(ty: &FuncType) -> bool {
+    !ty.params()
+        .chain(ty.results())
+        .any(|t| matches!(t, ValType::F32 | ValType::F64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmtime::Engine;
+
+    /// Lint-style audit of the System API dispatch layer: the code computing the
+    /// values returned to (or written into the memory of) a canister must not use
+    /// floating point types.
+    #[test]
+    fn system_api_dispatch_does_not_use_floats() {
+        let source = include_str!("system_api.rs");
+        let offenders: Vec<_> = source
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| {
+                // Ignore comments.
+                let code = line.split("//").next().unwrap();
+                code.split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .any(|token| token == "f32" || token == "f64")
+            })
+            .map(|(i, line)| format!("system_api.rs:{}: {}", i + 1, line.trim()))
+            .collect();
+        assert!(
+            offenders.is_empty(),
+            "Floating point usage in the System API dispatch layer:\n{}",
+            offenders.join("\n")
+        );
+    }
+
+    #[test]
+    fn integer_only_signatures() {
+        let engine = Engine::default();
+        assert!(is_integer_only_signature(&FuncType::new(
+            &engine,
+            [ValType::I32, ValType::I64],
+            [ValType::I64]
+        )));
+        assert!(is_integer_only_signature(&FuncType::new(&engine, [], [])));
+        assert!(!is_integer_only_signature(&FuncType::new(
+            &engine,
+            [ValType::I32],
+            [ValType::F64]
+        )));
+        assert!(!is_integer_only_signature(&FuncType::new(
+            &engine,
+            [ValType::F32],
+            []
+        )));
+    }
+}
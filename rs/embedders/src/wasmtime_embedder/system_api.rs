@@ -2,6 +2,7 @@ use crate::{
     wasm_utils::instrumentation::WasmMemoryType,
     wasmtime_embedder::{
         convert_backtrace,
+        integer_only::IntegerOnly,
         system_api_complexity::{overhead, overhead_native},
         StoreData, WASM_HEAP_BYTEMAP_MEMORY_NAME, WASM_HEAP_MEMORY_NAME,
     },
@@ -309,7 +310,9 @@ pub fn syscalls<
     <I as TryInto<u64>>::Error: std::fmt::Debug,
     <I as TryInto<u32>>::Error: std::fmt::Display,
 {
-    fn with_system_api<T>(
+    // All values returned to the canister go through one of the helpers below,
+    // which require them to be `IntegerOnly`.
+    fn with_system_api<T: IntegerOnly>(
         mut caller: &mut Caller<'_, StoreData>,
         f: impl Fn(&mut SystemApiImpl) -> HypervisorResult<T>,
     ) -> Result<T, anyhow::Error> {
@@ -320,14 +323,14 @@ pub fn syscalls<
             .map_err(|e| process_err(&mut caller, e))
     }
 
-    fn with_error_handling<T>(
+    fn with_error_handling<T: IntegerOnly>(
         caller: &mut Caller<'_, StoreData>,
         f: impl Fn(&mut Caller<'_, StoreData>) -> HypervisorResult<T>,
     ) -> Result<T, anyhow::Error> {
         f(caller).map_err(|e| process_err(caller, e))
     }
 
-    fn with_memory_and_system_api<T>(
+    fn with_memory_and_system_api<T: IntegerOnly>(
         mut caller: &mut Caller<'_, StoreData>,
         f: impl Fn(&mut SystemApiImpl, &mut [u8]) -> HypervisorResult<T>,
     ) -> Result<T, anyhow::Error> {
@@ -358,15 +361,10 @@ pub fn syscalls<
         caller: &mut Caller<'_, StoreData>,
         feature_flags: FeatureFlags,
     ) -> Result<bool, anyhow::Error> {
-        match (
-            feature_flags.rate_limiting_of_debug_prints,
-            with_system_api(caller, |s| Ok(s.subnet_type()))?,
-        ) {
-            // Debug print is enabled if rate limiting is off or for system subnets.
-            (FlagStatus::Disabled, _) | (_, SubnetType::System) => Ok(true),
-            // Disabled otherwise.
-            _ => Ok(false),
-        }
+        let is_system_subnet =
+            with_system_api(caller, |s| Ok(s.subnet_type() == SubnetType::System))?;
+        // Debug print is enabled if rate limiting is off or for system subnets.
+        Ok(feature_flags.rate_limiting_of_debug_prints == FlagStatus::Disabled || is_system_subnet)
     }
 
     /// Calculate logging charge bytes based on message size and remaining space in canister log.
@@ -0,0 +1,238 @@
+//! Performance regression gate for compiling and instrumenting a corpus of
+//! representative canister modules.
+//!
+//! For every module in `tests/compilation-corpus` (plus the asset canister,
+//! read from `ASSET_CANISTER_WASM_PATH`), the deterministic outputs of
+//! compilation (compilation cost, largest function instruction count and
+//! maximum complexity) must match `baselines.json` exactly.
+//!
+//! The wall time of validation, instrumentation and compilation is only
+//! reported when it exceeds `TIME_TOLERANCE` times the baseline: it depends on
+//! the machine the test runs on, which need not be the one that recorded the
+//! baselines.
+//!
+//! To update the baselines after an intentional change, run
+//!
+//! ```text
+//! EMBEDDERS_BLESS_COMPILATION_BASELINES=1 ASSET_CANISTER_WASM_PATH=<path to assetstorage.wasm.gz> \
+//!     cargo test -p ic-embedders --test compilation_regression
+//! ```
+//!
+//! and review the diff of `baselines.json`.
+use ic_config::embedders::Config as EmbeddersConfig;
+use ic_embedders::{
+    wasm_utils::{compile, decoding::decode_wasm},
+    WasmtimeEmbedder,
+};
+use ic_logger::replica_logger::no_op_logger;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+const BLESS_ENV_VAR: &str = "EMBEDDERS_BLESS_COMPILATION_BASELINES";
+
+/// Factor by which the compilation time may exceed its baseline before it is
+/// reported.
+const TIME_TOLERANCE: u64 = 2;
+
+/// Slack added to the allowed compilation time, so that very fast modules do
+/// not fail on scheduling noise.
+const TIME_SLACK: Duration = Duration::from_millis(50);
+
+/// Each module is compiled this many times and the fastest run is recorded.
+const TIME_SAMPLES: usize = 3;
+
+/// Where a corpus module is read from.
+enum Source {
+    /// A file in `tests/compilation-corpus`.
+    File(&'static str),
+    /// A file whose path is held by the given environment variable.
+    EnvVar(&'static str),
+}
+
+/// The modules of the corpus, by name, and where they are read from.
+const CORPUS: &[(&str, Source)] = &[
+    ("asset_canister", Source::EnvVar("ASSET_CANISTER_WASM_PATH")),
+    ("motoko_qr", Source::File("motoko_qr.wasm")),
+    (
+        "rust_cdk_icrc1_archive",
+        Source::File("rust_cdk_icrc1_archive.wasm.gz"),
+    ),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+struct Baseline {
+    compilation_time_ms: u64,
+    compilation_cost: u64,
+    largest_function_instruction_count: u64,
+    max_complexity: u64,
+}
+
+fn corpus_dir() -> PathBuf {
+    PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set"))
+        .join("tests/compilation-corpus")
+}
+
+fn path(source: &Source) -> PathBuf {
+    match source {
+        Source::File(file) => corpus_dir().join(file),
+        Source::EnvVar(var) => {
+            PathBuf::from(std::env::var(var).unwrap_or_else(|_| panic!("{} not set", var)))
+        }
+    }
+}
+
+fn measure(source: &Source) -> Baseline {
+    let config = EmbeddersConfig::default();
+    let path = path(source);
+    let file = path.display();
+    let bytes =
+        std::fs::read(&path).unwrap_or_else(|e| panic!("couldn't open file {}: {}", file, e));
+    let wasm = decode_wasm(config.wasm_max_size, Arc::new(bytes))
+        .unwrap_or_else(|e| panic!("couldn't decode {}: {:?}", file, e));
+
+    let samples: Vec<Baseline> = (0..TIME_SAMPLES)
+        .map(|_| {
+            let embedder = WasmtimeEmbedder::new(config.clone(), no_op_logger());
+            let (result, serialized_module) = compile(&embedder, &wasm)
+                .1
+                .unwrap_or_else(|e| panic!("couldn't compile {}: {:?}", file, e));
+            Baseline {
+                compilation_time_ms: result.compilation_time.as_millis() as u64,
+                compilation_cost: serialized_module.compilation_cost.get(),
+                largest_function_instruction_count: result.largest_function_instruction_count.get(),
+                max_complexity: result.max_complexity,
+            }
+        })
+        .collect();
+    let fastest = samples
+        .iter()
+        .min_by_key(|sample| sample.compilation_time_ms)
+        .unwrap()
+        .clone();
+    for sample in &samples {
+        assert_eq!(
+            Baseline {
+                compilation_time_ms: fastest.compilation_time_ms,
+                ..sample.clone()
+            },
+            fastest,
+            "compiling {} is not deterministic",
+            file
+        );
+    }
+    fastest
+}
+
+/// Returns a human-readable line for every deterministic metric of `actual`
+/// that violates its baseline.
+fn violations(name: &str, baseline: Option<&Baseline>, actual: &Baseline) -> Vec<String> {
+    let Some(baseline) = baseline else {
+        return vec![format!("{}: no baseline", name)];
+    };
+    let mut violations = vec![];
+    let mut check_exact = |metric: &str, baseline: u64, actual: u64| {
+        if baseline != actual {
+            violations.push(format!(
+                "{}.{}: expected {}, got {} ({:+})",
+                name,
+                metric,
+                baseline,
+                actual,
+                actual as i128 - baseline as i128
+            ));
+        }
+    };
+    check_exact(
+        "compilation_cost",
+        baseline.compilation_cost,
+        actual.compilation_cost,
+    );
+    check_exact(
+        "largest_function_instruction_count",
+        baseline.largest_function_instruction_count,
+        actual.largest_function_instruction_count,
+    );
+    check_exact(
+        "max_complexity",
+        baseline.max_complexity,
+        actual.max_complexity,
+    );
+    violations
+}
+
+/// Returns a human-readable line if the compilation time of `actual` exceeds
+/// its baseline by more than the tolerance.
+fn slowdown(name: &str, baseline: Option<&Baseline>, actual: &Baseline) -> Option<String> {
+    let baseline = baseline?;
+    let allowed_ms = TIME_TOLERANCE * baseline.compilation_time_ms + TIME_SLACK.as_millis() as u64;
+    (actual.compilation_time_ms > allowed_ms).then(|| {
+        format!(
+            "{}.compilation_time_ms: baseline {}, got {} (tolerated up to {})",
+            name, baseline.compilation_time_ms, actual.compilation_time_ms, allowed_ms
+        )
+    })
+}
+
+#[test]
+fn compilation_does_not_regress_on_corpus() {
+    let baselines_path = corpus_dir().join("baselines.json");
+    let baselines: BTreeMap<String, Baseline> = serde_json::from_str(
+        &std::fs::read_to_string(&baselines_path)
+            .unwrap_or_else(|e| panic!("couldn't open file {}: {}", baselines_path.display(), e)),
+    )
+    .expect("couldn't parse the compilation baselines");
+
+    let actual: BTreeMap<String, Baseline> = CORPUS
+        .iter()
+        .map(|(name, source)| (name.to_string(), measure(source)))
+        .collect();
+    let actual_json = format!(
+        "{}\n",
+        serde_json::to_string_pretty(&actual).expect("couldn't serialize baselines")
+    );
+
+    if std::env::var(BLESS_ENV_VAR).is_ok() {
+        std::fs::write(&baselines_path, &actual_json)
+            .unwrap_or_else(|e| panic!("couldn't write file {}: {}", baselines_path.display(), e));
+        println!("Updated {}:\n{}", baselines_path.display(), actual_json);
+        return;
+    }
+
+    let slowdowns: Vec<String> = actual
+        .iter()
+        .filter_map(|(name, actual)| slowdown(name, baselines.get(name), actual))
+        .collect();
+    if !slowdowns.is_empty() {
+        // Timings depend on the machine, so they are reported but never fail the
+        // test.
+        println!(
+            "Compilation of the corpus is slower than recorded in {}:\n  {}",
+            baselines_path.display(),
+            slowdowns.join("\n  ")
+        );
+    }
+
+    let mut all_violations: Vec<String> = actual
+        .iter()
+        .flat_map(|(name, actual)| violations(name, baselines.get(name), actual))
+        .collect();
+    all_violations.extend(
+        baselines
+            .keys()
+            .filter(|name| !actual.contains_key(*name))
+            .map(|name| format!("{}: baseline for a module that is not in the corpus", name)),
+    );
+    assert!(
+        all_violations.is_empty(),
+        "Compilation of the corpus deviates from {}:\n  {}\n\n\
+         Measured:\n{}\n\
+         If the change is intentional, rerun with {}=1 to update the baselines.",
+        baselines_path.display(),
+        all_violations.join("\n  "),
+        actual_json,
+        BLESS_ENV_VAR
+    );
+}
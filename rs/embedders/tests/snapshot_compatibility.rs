@@ -0,0 +1,288 @@
+//! Compatibility of persisted execution states across embedder versions.
+//!
+//! A replica upgrade resumes every canister from the globals and memories that
+//! the previous embedder version persisted. Each fixture in
+//! `tests/snapshot-compat-fixtures` holds such a state: the exported globals,
+//! including the ones injected by the instrumentation, and the non-zero parts
+//! of the Wasm and stable memory. The test loads every fixture into an instance
+//! of the current embedder, runs `VERIFY_METHOD`, which reads back specific
+//! addresses of both memories into globals, and compares the resulting globals
+//! and the number of executed instructions with the recorded expectations.
+//!
+//! Fixtures are written by the generator below and must never be edited by
+//! hand. When bumping the embedder version, keep the existing fixtures and add
+//! one for the new version by running
+//!
+//! ```text
+//! EMBEDDERS_GENERATE_SNAPSHOT_FIXTURE=<name> cargo test -p ic-embedders --test snapshot_compatibility
+//! ```
+//!
+//! and review the new file. A fixture may only be removed once no replica can
+//! resume from states of its version anymore.
+use ic_embedders::{wasm_executor::compute_page_delta, wasmtime_embedder::CanisterMemoryType};
+use ic_replicated_state::{Global, Memory, NumWasmPages, PageIndex, PageMap};
+use ic_sys::{PageBytes, PAGE_SIZE};
+use ic_test_utilities_embedders::{WasmtimeInstanceBuilder, DEFAULT_NUM_INSTRUCTIONS};
+use ic_types::methods::{FuncRef, WasmMethod};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+const GENERATE_ENV_VAR: &str = "EMBEDDERS_GENERATE_SNAPSHOT_FIXTURE";
+
+const SETUP_METHOD: &str = "setup";
+const VERIFY_METHOD: &str = "verify";
+
+/// Memories are stored in chunks of this many bytes, which divides the OS page
+/// size of all supported platforms.
+const CHUNK_SIZE: usize = 4096;
+
+/// The module of newly generated fixtures. `setup` produces the state, which
+/// `verify` reads back into the exported globals.
+const WAT: &str = r#"
+(module
+  (import "ic0" "stable64_grow" (func $stable64_grow (param i64) (result i64)))
+  (import "ic0" "stable64_write" (func $stable64_write (param i64 i64 i64)))
+  (import "ic0" "stable64_read" (func $stable64_read (param i64 i64 i64)))
+  (memory (export "memory") 4)
+  (global $runs (export "runs") (mut i64) (i64.const 0))
+  (global $marker (export "marker") (mut i32) (i32.const 0))
+  (global $heap_low (export "heap_low") (mut i64) (i64.const 0))
+  (global $heap_high (export "heap_high") (mut i64) (i64.const 0))
+  (global $stable (export "stable") (mut i64) (i64.const 0))
+  (func (export "canister_update setup")
+    ;; A value in the first and in the last Wasm page of the heap.
+    (i64.store (i32.const 16) (i64.const 0x0123456789abcdef))
+    (i64.store (i32.const 262136) (i64.const 0x7edcba9876543210))
+    ;; A value at the start of the second Wasm page of the stable memory.
+    (drop (call $stable64_grow (i64.const 2)))
+    (i64.store (i32.const 1024) (i64.const 0x5555aaaa5555aaaa))
+    (call $stable64_write (i64.const 65536) (i64.const 1024) (i64.const 8))
+    (i64.store (i32.const 1024) (i64.const 0))
+    (global.set $runs (i64.const 1))
+    (global.set $marker (i32.const -559038737))
+  )
+  (func (export "canister_update verify")
+    (global.set $heap_low (i64.load (i32.const 16)))
+    (global.set $heap_high (i64.load (i32.const 262136)))
+    (call $stable64_read (i64.const 2048) (i64.const 65536) (i64.const 8))
+    (global.set $stable (i64.load (i32.const 2048)))
+    (global.set $runs (i64.add (global.get $runs) (i64.const 1)))
+  )
+)
+"#;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Fixture {
+    /// The module that produced `state`, in the text format.
+    wat: String,
+    state: ExecutionSnapshot,
+    expected: Expectations,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct ExecutionSnapshot {
+    /// Including the globals injected by the instrumentation, such as the
+    /// instruction counter.
+    exported_globals: Vec<Global>,
+    wasm_memory: MemorySnapshot,
+    stable_memory: MemorySnapshot,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct MemorySnapshot {
+    size_in_wasm_pages: usize,
+    /// The hex-encoded chunks that are not all zeros, by byte offset.
+    chunks: BTreeMap<u64, String>,
+}
+
+/// The outcome of running `VERIFY_METHOD` on the state.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+struct Expectations {
+    exported_globals: Vec<Global>,
+    instructions_executed: u64,
+}
+
+impl MemorySnapshot {
+    fn capture(pages: &[(PageIndex, &PageBytes)], size: NumWasmPages) -> Self {
+        let mut chunks = BTreeMap::new();
+        for (page_index, bytes) in pages {
+            for (i, chunk) in bytes.chunks(CHUNK_SIZE).enumerate() {
+                if chunk.iter().any(|byte| *byte != 0) {
+                    let offset = page_index.get() as usize * PAGE_SIZE + i * CHUNK_SIZE;
+                    chunks.insert(offset as u64, hex::encode(chunk));
+                }
+            }
+        }
+        Self {
+            size_in_wasm_pages: size.get(),
+            chunks,
+        }
+    }
+
+    fn restore(&self) -> Memory {
+        let mut pages: BTreeMap<u64, Box<PageBytes>> = BTreeMap::new();
+        for (offset, chunk) in &self.chunks {
+            let chunk = hex::decode(chunk).expect("couldn't decode memory chunk");
+            assert_eq!(
+                chunk.len(),
+                CHUNK_SIZE,
+                "chunk at {offset} has a wrong size"
+            );
+            let offset = *offset as usize;
+            let page = pages
+                .entry((offset / PAGE_SIZE) as u64)
+                .or_insert_with(|| Box::new([0; PAGE_SIZE]));
+            let start = offset % PAGE_SIZE;
+            page[start..start + CHUNK_SIZE].copy_from_slice(&chunk);
+        }
+        let mut page_map = PageMap::new_for_testing();
+        let pages: Vec<(PageIndex, &PageBytes)> = pages
+            .iter()
+            .map(|(index, bytes)| (PageIndex::new(*index), &**bytes))
+            .collect();
+        page_map.update(&pages);
+        Memory::new(page_map, NumWasmPages::from(self.size_in_wasm_pages))
+    }
+}
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set"))
+        .join("tests/snapshot-compat-fixtures")
+}
+
+fn update(method: &str) -> FuncRef {
+    FuncRef::Method(WasmMethod::Update(method.to_string()))
+}
+
+/// Runs `VERIFY_METHOD` on `state` with the current embedder.
+fn verify(wat: &str, state: &ExecutionSnapshot) -> Expectations {
+    let mut instance = WasmtimeInstanceBuilder::new()
+        .with_wat(wat)
+        .with_globals(state.exported_globals.clone())
+        .with_wasm_memory(state.wasm_memory.restore())
+        .with_stable_memory(state.stable_memory.restore())
+        .build();
+    let result = instance
+        .run(update(VERIFY_METHOD))
+        .unwrap_or_else(|e| panic!("running {VERIFY_METHOD} failed: {e:?}"));
+    Expectations {
+        exported_globals: result.exported_globals,
+        instructions_executed: DEFAULT_NUM_INSTRUCTIONS.get()
+            - instance.instruction_counter() as u64,
+    }
+}
+
+/// Produces the state with `SETUP_METHOD`, the way the replica persists it
+/// (see `wasm_executor`), and records what `VERIFY_METHOD` observes on it.
+fn generate() -> Fixture {
+    let mut instance = WasmtimeInstanceBuilder::new().with_wat(WAT).build();
+    let result = instance
+        .run(update(SETUP_METHOD))
+        .unwrap_or_else(|e| panic!("running {SETUP_METHOD} failed: {e:?}"));
+    let wasm_memory_size = instance.heap_size(CanisterMemoryType::Heap);
+    let stable_memory_size = instance.heap_size(CanisterMemoryType::Stable);
+    let wasm_memory = MemorySnapshot::capture(
+        &compute_page_delta(
+            &mut instance,
+            &result.wasm_dirty_pages,
+            CanisterMemoryType::Heap,
+        ),
+        wasm_memory_size,
+    );
+    let stable_memory = MemorySnapshot::capture(
+        &compute_page_delta(
+            &mut instance,
+            &result.stable_memory_dirty_pages,
+            CanisterMemoryType::Stable,
+        ),
+        stable_memory_size,
+    );
+    let state = ExecutionSnapshot {
+        exported_globals: result.exported_globals,
+        wasm_memory,
+        stable_memory,
+    };
+    Fixture {
+        wat: WAT.to_string(),
+        expected: verify(WAT, &state),
+        state,
+    }
+}
+
+#[test]
+fn current_embedder_resumes_from_persisted_states() {
+    if let Ok(name) = std::env::var(GENERATE_ENV_VAR) {
+        let path = fixtures_dir().join(format!("{name}.json"));
+        let json = format!(
+            "{}\n",
+            serde_json::to_string_pretty(&generate()).expect("couldn't serialize fixture")
+        );
+        std::fs::write(&path, json)
+            .unwrap_or_else(|e| panic!("couldn't write file {}: {}", path.display(), e));
+        println!("Generated {}", path.display());
+        return;
+    }
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(fixtures_dir())
+        .expect("couldn't list the fixtures")
+        .map(|entry| entry.expect("couldn't list the fixtures").path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    paths.sort();
+    assert!(
+        !paths.is_empty(),
+        "No fixtures in {}, generate one with {}=<name>.",
+        fixtures_dir().display(),
+        GENERATE_ENV_VAR
+    );
+
+    let mut failures = vec![];
+    for path in &paths {
+        let fixture: Fixture = serde_json::from_str(
+            &std::fs::read_to_string(path)
+                .unwrap_or_else(|e| panic!("couldn't open file {}: {}", path.display(), e)),
+        )
+        .unwrap_or_else(|e| panic!("couldn't parse fixture {}: {}", path.display(), e));
+        let actual = verify(&fixture.wat, &fixture.state);
+        if actual != fixture.expected {
+            failures.push(format!(
+                "{}:\n    expected {:?}\n    got      {:?}",
+                path.display(),
+                fixture.expected,
+                actual
+            ));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "The current embedder does not resume from persisted states as before:\n  {}",
+        failures.join("\n  ")
+    );
+}
+
+/// Guards the generator itself, independently of the committed fixtures.
+#[test]
+fn generated_state_round_trips() {
+    let fixture = generate();
+    let json = serde_json::to_string(&fixture).expect("couldn't serialize fixture");
+    let fixture: Fixture = serde_json::from_str(&json).expect("couldn't parse fixture");
+
+    assert_eq!(verify(&fixture.wat, &fixture.state), fixture.expected);
+    let globals = &fixture.expected.exported_globals;
+    assert_eq!(
+        globals[..5],
+        [
+            Global::I64(2),
+            Global::I32(0xdeadbeef_u32 as i32),
+            Global::I64(0x0123456789abcdef),
+            Global::I64(0x7edcba9876543210),
+            Global::I64(0x5555aaaa5555aaaa),
+        ]
+    );
+    assert_eq!(fixture.state.wasm_memory.size_in_wasm_pages, 4);
+    assert_eq!(fixture.state.stable_memory.size_in_wasm_pages, 2);
+}
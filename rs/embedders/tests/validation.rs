@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::time::{Duration, Instant};
 
 use assert_matches::assert_matches;
 use ic_config::embedders::Config as EmbeddersConfig;
@@ -104,6 +105,10 @@ fn can_validate_valid_export_section() {
         Ok(WasmValidationDetails {
             largest_function_instruction_count: NumInstructions::new(1),
             max_complexity: Complexity(1),
+            function_count: 1,
+            // An empty function body consists of the number of local
+            // declarations and the `end` instruction.
+            largest_function_body_size: NumBytes::new(2),
             ..Default::default()
         })
     );
@@ -431,6 +436,8 @@ fn can_validate_many_exported_functions() {
         Ok(WasmValidationDetails {
             largest_function_instruction_count: NumInstructions::new(1),
             max_complexity: Complexity(1),
+            function_count: 1,
+            largest_function_body_size: NumBytes::new(2),
             ..Default::default()
         })
     );
@@ -467,6 +474,8 @@ fn can_validate_large_sum_exported_function_name_lengths() {
         Ok(WasmValidationDetails {
             largest_function_instruction_count: NumInstructions::new(1),
             max_complexity: Complexity(1),
+            function_count: 1,
+            largest_function_body_size: NumBytes::new(2),
             ..Default::default()
         })
     );
@@ -510,6 +519,8 @@ fn can_validate_canister_query_update_method_name_with_whitespace() {
         Ok(WasmValidationDetails {
             largest_function_instruction_count: NumInstructions::new(1),
             max_complexity: Complexity(1),
+            function_count: 1,
+            largest_function_body_size: NumBytes::new(2),
             ..Default::default()
         })
     );
@@ -660,6 +671,115 @@ fn can_reject_module_with_too_many_functions() {
     );
 }
 
+/// Generous upper bound on the time it takes to validate (and thus compile)
+/// a module that is just within the function limits.
+const VALIDATION_TIME_LIMIT: Duration = Duration::from_secs(60);
+
+/// Returns a module defining `count` empty functions.
+fn wasm_with_function_count(count: usize) -> BinaryEncodedWasm {
+    let mut wat = "(module".to_string();
+    for _ in 0..count {
+        wat.push_str(" (func)");
+    }
+    wat.push(')');
+    wat2wasm(&wat).unwrap()
+}
+
+/// Returns a module defining an empty function followed by a function whose
+/// body is exactly `body_size` bytes long.
+fn wasm_with_function_body_size(body_size: usize) -> BinaryEncodedWasm {
+    // The body consists of the number of local declarations (1 byte), the
+    // instructions and the final `end` (1 byte). Each `nop` is 1 byte.
+    let mut wat = "(module (func) (func".to_string();
+    for _ in 0..body_size - 2 {
+        wat.push_str(" nop");
+    }
+    wat.push_str("))");
+    wat2wasm(&wat).unwrap()
+}
+
+#[test]
+fn can_validate_module_with_max_functions() {
+    let config = EmbeddersConfig::default();
+    let wasm = wasm_with_function_count(config.max_functions);
+
+    let start = Instant::now();
+    let result = validate_wasm_binary(&wasm, &config);
+    let elapsed = start.elapsed();
+
+    assert_matches!(
+        result,
+        Ok(WasmValidationDetails { function_count, .. }) if function_count == config.max_functions
+    );
+    assert!(
+        elapsed < VALIDATION_TIME_LIMIT,
+        "validating {} functions took {:?}",
+        config.max_functions,
+        elapsed
+    );
+}
+
+#[test]
+fn can_reject_module_with_one_function_more_than_max_functions() {
+    let config = EmbeddersConfig::default();
+    let wasm = wasm_with_function_count(config.max_functions + 1);
+
+    assert_eq!(
+        validate_wasm_binary(&wasm, &config),
+        Err(WasmValidationError::TooManyFunctions {
+            defined: config.max_functions + 1,
+            allowed: config.max_functions
+        })
+    );
+}
+
+// The default body size limit cannot be reached with a function that stays
+// within the limit on the number of instructions, so use a smaller one.
+const MAX_FUNCTION_BODY_SIZE_BYTES_FOR_TEST: usize = 256 * KB as usize;
+
+#[test]
+fn can_validate_module_with_max_function_body_size() {
+    let config = EmbeddersConfig {
+        max_function_body_size_bytes: MAX_FUNCTION_BODY_SIZE_BYTES_FOR_TEST,
+        ..Default::default()
+    };
+    let wasm = wasm_with_function_body_size(MAX_FUNCTION_BODY_SIZE_BYTES_FOR_TEST);
+
+    let start = Instant::now();
+    let result = validate_wasm_binary(&wasm, &config);
+    let elapsed = start.elapsed();
+
+    assert_matches!(
+        result,
+        Ok(WasmValidationDetails { function_count: 2, largest_function_body_size, .. })
+            if largest_function_body_size.get() == MAX_FUNCTION_BODY_SIZE_BYTES_FOR_TEST as u64
+    );
+    assert!(
+        elapsed < VALIDATION_TIME_LIMIT,
+        "validating a function body of {} bytes took {:?}",
+        MAX_FUNCTION_BODY_SIZE_BYTES_FOR_TEST,
+        elapsed
+    );
+}
+
+#[test]
+fn can_reject_module_with_function_body_one_byte_too_large() {
+    let config = EmbeddersConfig {
+        max_function_body_size_bytes: MAX_FUNCTION_BODY_SIZE_BYTES_FOR_TEST,
+        ..Default::default()
+    };
+    let wasm = wasm_with_function_body_size(MAX_FUNCTION_BODY_SIZE_BYTES_FOR_TEST + 1);
+
+    assert_eq!(
+        validate_wasm_binary(&wasm, &config),
+        Err(WasmValidationError::FunctionBodyTooLarge {
+            index: 1,
+            size: MAX_FUNCTION_BODY_SIZE_BYTES_FOR_TEST + 1,
+            allowed: MAX_FUNCTION_BODY_SIZE_BYTES_FOR_TEST
+        })
+    );
+}
+
 #[test]
 fn can_validate_module_with_custom_sections() {
     let mut module = wasm_encoder::Module::new();
@@ -6,14 +6,16 @@ use ic_config::{
 use ic_embedders::{
     wasm_utils::instrumentation::instruction_to_cost,
     wasm_utils::instrumentation::WasmMemoryType,
-    wasmtime_embedder::{system_api_complexity, CanisterMemoryType},
+    wasmtime_embedder::{system_api_complexity, CanisterMemoryType, WasmtimeInstance},
 };
 use ic_interfaces::execution_environment::{
     CanisterBacktrace, ExecutionMode, HypervisorError, SystemApi, TrapCode,
 };
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::{canister_state::WASM_PAGE_SIZE_IN_BYTES, Global};
-use ic_test_utilities_embedders::{WasmtimeInstanceBuilder, DEFAULT_NUM_INSTRUCTIONS};
+use ic_test_utilities_embedders::{
+    run_twice_and_compare, CleanupViolation, WasmtimeInstanceBuilder, DEFAULT_NUM_INSTRUCTIONS,
+};
 use ic_test_utilities_types::ids::{call_context_test_id, user_test_id};
 use ic_types::{
     ingress::WasmResult,
@@ -278,6 +280,232 @@ fn correctly_report_performance_counter() {
     assert_eq!(instructions_used.get(), expected_instructions);
 }
 
+/// Runs the `canister_update test` method of the given module and returns the
+/// number of instructions it used.
+fn instructions_used_by_test_method(wat: &str) -> u64 {
+    let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
+    instance
+        .run(FuncRef::Method(WasmMethod::Update("test".to_string())))
+        .unwrap();
+    let instruction_counter = instance.instruction_counter();
+    let system_api = &instance.store_data().system_api().unwrap();
+    system_api
+        .slice_instructions_executed(instruction_counter)
+        .get()
+}
+
+// The expected values in the following tests are computed by hand from the
+// instructions on the executed path, using the costs of `instruction_to_cost`:
+// the function itself costs 1, `block`, `loop` and `end` cost 0, constants,
+// local accesses, arithmetic, `drop` and `return` cost 1 and all branch
+// instructions (`if`, `br`, `br_if` and `br_table`) cost 2.
+
+#[test]
+fn br_table_charges_only_the_taken_target() {
+    let wat = |index: u32| {
+        format!(
+            r#"
+                (module
+                    (func (export "canister_update test") (local i32)
+                        (block $b2
+                            (block $b1
+                                (block $b0
+                                    (br_table $b0 $b1 $b2 (i32.const {index}))
+                                )
+                                ;; target 0
+                                (local.set 0 (i32.const 10))
+                                (br $b2)
+                            )
+                            ;; target 1
+                            (local.set 0 (i32.const 20))
+                            (drop (i32.const 0))
+                            (br $b2)
+                        )
+                        ;; target 2 and default
+                        (drop (local.get 0))
+                    )
+                    (memory 0)
+                )
+            "#
+        )
+    };
+    // function + i32.const + br_table
+    let dispatch = 1 + 1 + 2;
+    // local.get + drop
+    let join = 1 + 1;
+
+    // i32.const + local.set + br
+    assert_eq!(
+        instructions_used_by_test_method(&wat(0)),
+        dispatch + (1 + 1 + 2) + join
+    );
+    // i32.const + local.set + i32.const + drop + br
+    assert_eq!(
+        instructions_used_by_test_method(&wat(1)),
+        dispatch + (1 + 1 + 1 + 1 + 2) + join
+    );
+    assert_eq!(instructions_used_by_test_method(&wat(2)), dispatch + join);
+    // Out of range indices take the default target.
+    assert_eq!(
+        instructions_used_by_test_method(&wat(1000)),
+        dispatch + join
+    );
+}
+
+#[test]
+fn br_table_cost_does_not_depend_on_number_of_targets() {
+    let wat = |num_targets: usize, index: u32| {
+        format!(
+            r#"
+                (module
+                    (func (export "canister_update test")
+                        (block
+                            (br_table {targets} 0 (i32.const {index}))
+                        )
+                    )
+                    (memory 0)
+                )
+            "#,
+            targets = "0 ".repeat(num_targets)
+        )
+    };
+    // function + i32.const + br_table
+    let expected = 1 + 1 + 2;
+
+    assert_eq!(instructions_used_by_test_method(&wat(1, 0)), expected);
+    assert_eq!(instructions_used_by_test_method(&wat(1000, 0)), expected);
+    assert_eq!(instructions_used_by_test_method(&wat(1000, 999)), expected);
+    assert_eq!(instructions_used_by_test_method(&wat(1000, 5000)), expected);
+}
+
+#[test]
+fn early_return_from_nested_blocks_skips_remaining_instructions() {
+    let wat = r#"
+        (module
+            (func (export "canister_update test")
+                (block
+                    (block
+                        (drop (i32.const 1))
+                        (return)
+                        (drop (i32.const 2))
+                    )
+                    (drop (i32.const 3))
+                )
+                (drop (i32.const 4))
+            )
+            (memory 0)
+        )
+    "#;
+    // function + i32.const + drop + return
+    assert_eq!(instructions_used_by_test_method(wat), 1 + 1 + 1 + 1);
+}
+
+#[test]
+fn conditional_return_charges_only_the_taken_path() {
+    let wat = |condition: u32| {
+        format!(
+            r#"
+                (module
+                    (func (export "canister_update test")
+                        (if (i32.const {condition}) (then (return)))
+                        (drop (i32.const 4))
+                    )
+                    (memory 0)
+                )
+            "#
+        )
+    };
+    // function + i32.const + if
+    let condition = 1 + 1 + 2;
+
+    // return
+    assert_eq!(instructions_used_by_test_method(&wat(1)), condition + 1);
+    // i32.const + drop
+    assert_eq!(instructions_used_by_test_method(&wat(0)), condition + 1 + 1);
+}
+
+#[test]
+fn br_out_of_nested_blocks_skips_remaining_instructions() {
+    let wat = r#"
+        (module
+            (func (export "canister_update test")
+                (block $outer
+                    (block
+                        (block
+                            (br $outer)
+                            (drop (i32.const 1))
+                        )
+                        (drop (i32.const 2))
+                    )
+                    (drop (i32.const 3))
+                )
+                (drop (i32.const 4))
+            )
+            (memory 0)
+        )
+    "#;
+    // function + br, then i32.const + drop after the outer block
+    assert_eq!(instructions_used_by_test_method(wat), 1 + 2 + 1 + 1);
+}
+
+#[test]
+fn loop_charges_every_iteration() {
+    let wat = |iterations: u32| {
+        format!(
+            r#"
+                (module
+                    (func (export "canister_update test") (local i32)
+                        (local.set 0 (i32.const {iterations}))
+                        (loop $l
+                            (local.set 0 (i32.sub (local.get 0) (i32.const 1)))
+                            (br_if $l (local.get 0))
+                        )
+                    )
+                    (memory 0)
+                )
+            "#
+        )
+    };
+    // function + i32.const + local.set
+    let init = 1 + 1 + 1;
+    // local.get + i32.const + i32.sub + local.set + local.get + br_if
+    let iteration = 1 + 1 + 1 + 1 + 1 + 2;
+
+    assert_eq!(instructions_used_by_test_method(&wat(1)), init + iteration);
+    assert_eq!(
+        instructions_used_by_test_method(&wat(10)),
+        init + 10 * iteration
+    );
+}
+
+#[test]
+fn br_out_of_loop_skips_rest_of_iteration() {
+    let wat = r#"
+        (module
+            (func (export "canister_update test") (local i32)
+                (block $exit
+                    (loop $l
+                        (br_if $exit (i32.eq (local.get 0) (i32.const 5)))
+                        (local.set 0 (i32.add (local.get 0) (i32.const 1)))
+                        (br $l)
+                    )
+                )
+            )
+            (memory 0)
+        )
+    "#;
+    // local.get + i32.const + i32.eq + br_if
+    let check = 1 + 1 + 1 + 2;
+    // local.get + i32.const + i32.add + local.set + br
+    let increment = 1 + 1 + 1 + 1 + 2;
+
+    // The function, five full iterations and the check of the sixth iteration.
+    assert_eq!(
+        instructions_used_by_test_method(wat),
+        1 + 5 * (check + increment) + check
+    );
+}
+
 #[test]
 fn stack_overflow_traps() {
     use std::thread;
@@ -640,6 +868,131 @@ fn read_before_write_stats() {
     assert_eq!(stats.wasm_read_before_write_count, 1);
 }
 
+#[test]
+fn engine_memory_report_reports_compiled_code_size() {
+    let small_wat = r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "canister_update test"))
+            )"#;
+    let large_wat = format!(
+        r#"
+            (module
+                (memory (export "memory") 1)
+                {}
+                (func (export "canister_update test"))
+            )"#,
+        (0..200)
+            .map(|i| {
+                format!(
+                    "(func (export \"f{i}\") (result i64) \
+                        (i64.mul (i64.add (i64.const {i}) (i64.const 7)) (i64.const 3)))"
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+
+    let small = WasmtimeInstanceBuilder::new()
+        .with_wat(small_wat)
+        .build()
+        .engine_memory_report();
+    let large = WasmtimeInstanceBuilder::new()
+        .with_wat(&large_wat)
+        .build()
+        .engine_memory_report();
+
+    assert!(small.compiled_code_bytes > 0);
+    assert!(large.compiled_code_bytes > small.compiled_code_bytes);
+    assert!(small.instance_bookkeeping_bytes > 0);
+    assert!(small.linear_memory_reserved_bytes > small.linear_memory_resident_bytes);
+    assert_eq!(
+        small.linear_memory_untouched_bytes(),
+        small.linear_memory_reserved_bytes - small.linear_memory_resident_bytes
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn engine_memory_report_resident_heap_matches_dirty_pages() {
+    const NUM_WRITTEN_PAGES: usize = 20;
+    // Writes one byte to each of the first `NUM_WRITTEN_PAGES` OS pages.
+    let wat = format!(
+        r#"
+            (module
+                (import "ic0" "msg_reply" (func $msg_reply))
+                (memory (export "memory") 2)
+                (func (export "canister_update write")
+                    (local $i i32)
+                    (loop $loop
+                        (i32.store8 (i32.mul (local.get $i) (i32.const 4096)) (i32.const 1))
+                        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                        (br_if $loop (i32.lt_u (local.get $i) (i32.const {NUM_WRITTEN_PAGES})))
+                    )
+                    (call $msg_reply)
+                )
+            )"#
+    );
+    let mut instance = WasmtimeInstanceBuilder::new()
+        .with_wat(&wat)
+        .with_api_type(ic_system_api::ApiType::update(
+            UNIX_EPOCH,
+            vec![],
+            Cycles::zero(),
+            PrincipalId::new_user_test_id(0),
+            0.into(),
+        ))
+        .build();
+    instance
+        .run(FuncRef::Method(WasmMethod::Update("write".to_string())))
+        .unwrap();
+
+    let stats = instance.get_stats();
+    assert_eq!(stats.wasm_dirty_pages, NUM_WRITTEN_PAGES);
+    let resident_pages = stats.linear_memory_resident_bytes / 4096;
+    // Pages accessed but not written, e.g. by prefetching, may be resident too.
+    assert!(
+        resident_pages >= stats.wasm_dirty_pages
+            && resident_pages <= stats.wasm_accessed_pages + stats.stable_accessed_pages,
+        "resident pages: {}, dirty pages: {}, accessed pages: {}",
+        resident_pages,
+        stats.wasm_dirty_pages,
+        stats.wasm_accessed_pages
+    );
+    assert_eq!(
+        stats.linear_memory_resident_bytes,
+        instance.engine_memory_report().linear_memory_resident_bytes
+    );
+}
+
+/// Asserts that `err` is a `CalledTrap` error carrying `expected` as message,
+/// or its length and hash if `redact_error_payloads` is enabled.
+fn assert_trap_message(
+    instance: &WasmtimeInstance,
+    err: HypervisorError,
+    redact_error_payloads: FlagStatus,
+    expected: &[u8],
+) {
+    let HypervisorError::CalledTrap { message, .. } = err else {
+        panic!("Expected CalledTrap error, but got {}.", err);
+    };
+    let raw_trap_message = instance
+        .store_data()
+        .system_api()
+        .unwrap()
+        .raw_trap_message();
+    match redact_error_payloads {
+        FlagStatus::Disabled => {
+            assert_eq!(message.as_bytes(), expected);
+            assert_eq!(raw_trap_message, None);
+        }
+        FlagStatus::Enabled => {
+            assert_eq!(message, ic_system_api::redact_error_payload(expected));
+            assert_eq!(raw_trap_message.as_deref(), Some(expected));
+        }
+    }
+}
+
 #[test]
 fn stable_write_and_read() {
     let wat = r#"
@@ -668,19 +1021,24 @@ fn stable_write_and_read() {
                 )
                 (memory (export "memory") 1)
             )"#;
-    let mut config = Config::default();
-    config.feature_flags.wasm_native_stable_memory = FlagStatus::Enabled;
-    let mut instance = WasmtimeInstanceBuilder::new()
-        .with_config(config)
-        .with_wat(wat)
-        .build();
-    let err = instance
-        .run(FuncRef::Method(WasmMethod::Update("test".to_string())))
-        .unwrap_err();
-    let HypervisorError::CalledTrap { message, .. } = err else {
-        panic!("Expected CalledTrap error, but got {}.", err);
-    };
-    assert_eq!(message, "Hello".to_string());
+    for redact_error_payloads in [FlagStatus::Disabled, FlagStatus::Enabled] {
+        let mut config = Config::default();
+        config.feature_flags.wasm_native_stable_memory = FlagStatus::Enabled;
+        config.feature_flags.redact_error_payloads = redact_error_payloads;
+        let mut instance = WasmtimeInstanceBuilder::new()
+            .with_config(config)
+            .with_wat(wat)
+            .build();
+        instance
+            .store_data_mut()
+            .system_api_mut()
+            .unwrap()
+            .enable_raw_trap_message_capture();
+        let err = instance
+            .run(FuncRef::Method(WasmMethod::Update("test".to_string())))
+            .unwrap_err();
+        assert_trap_message(&instance, err, redact_error_payloads, b"Hello");
+    }
 }
 
 #[test]
@@ -712,19 +1070,24 @@ fn stable64_write_and_read() {
                 (table funcref (elem $test))
                 (memory (export "memory") 1)
             )"#;
-    let mut config = Config::default();
-    config.feature_flags.wasm_native_stable_memory = FlagStatus::Enabled;
-    let mut instance = WasmtimeInstanceBuilder::new()
-        .with_config(config)
-        .with_wat(wat)
-        .build();
-    let err = instance
-        .run(FuncRef::Method(WasmMethod::Update("test".to_string())))
-        .unwrap_err();
-    let HypervisorError::CalledTrap { message, .. } = err else {
-        panic!("Expected CalledTrap error, but got {}.", err);
-    };
-    assert_eq!(message, "Hello".to_string());
+    for redact_error_payloads in [FlagStatus::Disabled, FlagStatus::Enabled] {
+        let mut config = Config::default();
+        config.feature_flags.wasm_native_stable_memory = FlagStatus::Enabled;
+        config.feature_flags.redact_error_payloads = redact_error_payloads;
+        let mut instance = WasmtimeInstanceBuilder::new()
+            .with_config(config)
+            .with_wat(wat)
+            .build();
+        instance
+            .store_data_mut()
+            .system_api_mut()
+            .unwrap()
+            .enable_raw_trap_message_capture();
+        let err = instance
+            .run(FuncRef::Method(WasmMethod::Update("test".to_string())))
+            .unwrap_err();
+        assert_trap_message(&instance, err, redact_error_payloads, b"Hello");
+    }
 }
 
 #[test]
@@ -3159,3 +3522,348 @@ fn wasm64_saturate_fun_index() {
         }
     }
 }
+
+#[test]
+fn call_on_cleanup_registration_is_charged() {
+    let wat = |register_cleanup: bool| {
+        let call_on_cleanup = if register_cleanup {
+            "(call $ic0_call_on_cleanup (i32.const 0) (i32.const 66))"
+        } else {
+            ""
+        };
+        format!(
+            r#"
+            (module
+                (import "ic0" "call_new"
+                    (func $ic0_call_new (param i32 i32 i32 i32 i32 i32 i32 i32)))
+                (import "ic0" "call_on_cleanup"
+                    (func $ic0_call_on_cleanup (param i32 i32)))
+                (func (export "canister_update test")
+                    (call $ic0_call_new
+                        (i32.const 100) (i32.const 10)  ;; callee canister id
+                        (i32.const 0) (i32.const 18)    ;; refers to "some_remote_method"
+                        (i32.const 0) (i32.const 22)    ;; on_reply closure
+                        (i32.const 0) (i32.const 44)    ;; on_reject closure
+                    )
+                    {call_on_cleanup}
+                )
+                (memory 1)
+                (data (i32.const 0) "some_remote_method")
+                (data (i32.const 100) "\09\03\00\00\00\00\00\00\ff\01")
+            )"#
+        )
+    };
+    let instructions_used = |register_cleanup: bool| {
+        let mut instance = WasmtimeInstanceBuilder::new()
+            .with_wat(&wat(register_cleanup))
+            .with_api_type(ic_system_api::ApiType::update(
+                UNIX_EPOCH,
+                vec![],
+                Cycles::zero(),
+                user_test_id(24).get(),
+                call_context_test_id(13),
+            ))
+            .build();
+        instance
+            .run(FuncRef::Method(WasmMethod::Update("test".to_string())))
+            .unwrap();
+        DEFAULT_NUM_INSTRUCTIONS.get() - instance.instruction_counter() as u64
+    };
+
+    let const_cost = instruction_to_cost(
+        &wasmparser::Operator::I32Const { value: 1 },
+        WasmMemoryType::Wasm32,
+    );
+    let call_cost = instruction_to_cost(
+        &wasmparser::Operator::Call { function_index: 0 },
+        WasmMemoryType::Wasm32,
+    );
+    assert_eq!(
+        instructions_used(true) - instructions_used(false),
+        2 * const_cost + call_cost + system_api_complexity::overhead::CALL_ON_CLEANUP.get()
+    );
+}
+
+/// A module with a trapping callback (table index 0) and cleanup closures
+/// that succeed (1), run out of instructions (2) and call a System API that is
+/// not available in cleanup mode (3).
+const CALL_ON_CLEANUP_WAT: &str = r#"
+    (module
+        (import "ic0" "trap" (func $ic0_trap (param i32 i32)))
+        (import "ic0" "call_perform" (func $ic0_call_perform (result i32)))
+        (func $trapping_callback (param i32)
+            (call $ic0_trap (i32.const 0) (i32.const 0))
+        )
+        (func $cleanup (param i32)
+            (i32.store (i32.const 0) (local.get 0))
+        )
+        (func $looping_cleanup (param i32)
+            (loop $loop (br $loop))
+        )
+        (func $calling_cleanup (param i32)
+            (drop (call $ic0_call_perform))
+        )
+        (table funcref (elem $trapping_callback $cleanup $looping_cleanup $calling_cleanup))
+        (memory (export "memory") 1)
+    )"#;
+
+fn call_on_cleanup_instance_builder(num_instructions: NumInstructions) -> WasmtimeInstanceBuilder {
+    WasmtimeInstanceBuilder::new()
+        .with_wat(CALL_ON_CLEANUP_WAT)
+        .with_api_type(ic_system_api::ApiType::reply_callback(
+            UNIX_EPOCH,
+            user_test_id(24).get(),
+            vec![],
+            Cycles::zero(),
+            call_context_test_id(13),
+            false,
+            ExecutionMode::Replicated,
+            NumInstructions::from(0),
+        ))
+        .with_num_instructions(num_instructions)
+}
+
+#[test]
+fn cleanup_runs_after_callback_traps() {
+    let limit = NumInstructions::from(1_000_000);
+    let run = call_on_cleanup_instance_builder(limit).run_callback_with_cleanup(
+        FuncRef::UpdateClosure(WasmClosure::new(0, 0)),
+        WasmClosure::new(1, 7),
+    );
+
+    assert_matches!(run.callback_result, Err(HypervisorError::CalledTrap { .. }));
+    let cleanup = run.cleanup.unwrap();
+    assert_eq!(cleanup.result, Ok(()));
+    // The cleanup gets whatever the callback left plus the reservation.
+    assert_eq!(run.callback_instructions_used + cleanup.budget, limit);
+    assert!(cleanup.instructions_used.get() > 0);
+    assert!(cleanup.instructions_used < cleanup.budget);
+}
+
+#[test]
+fn cleanup_does_not_run_after_callback_succeeds() {
+    let run = call_on_cleanup_instance_builder(NumInstructions::from(1_000_000))
+        .run_callback_with_cleanup(
+            FuncRef::UpdateClosure(WasmClosure::new(1, 7)),
+            WasmClosure::new(1, 7),
+        );
+
+    assert_eq!(run.callback_result, Ok(()));
+    assert_eq!(run.cleanup, None);
+}
+
+#[test]
+fn cleanup_is_limited_to_its_instruction_budget() {
+    let limit = NumInstructions::from(100_000);
+    let run = call_on_cleanup_instance_builder(limit).run_callback_with_cleanup(
+        FuncRef::UpdateClosure(WasmClosure::new(0, 0)),
+        WasmClosure::new(2, 0),
+    );
+
+    let cleanup = run.cleanup.unwrap();
+    assert_eq!(
+        cleanup.result,
+        Err(CleanupViolation::InstructionBudgetExhausted {
+            budget: cleanup.budget
+        })
+    );
+    assert_eq!(cleanup.instructions_used, cleanup.budget);
+    assert!(cleanup.budget < limit);
+}
+
+#[test]
+fn cleanup_cannot_call_perform() {
+    let run = call_on_cleanup_instance_builder(NumInstructions::from(1_000_000))
+        .run_callback_with_cleanup(
+            FuncRef::UpdateClosure(WasmClosure::new(0, 0)),
+            WasmClosure::new(3, 0),
+        );
+
+    assert_eq!(
+        run.cleanup.unwrap().result,
+        Err(CleanupViolation::ForbiddenSystemApiCall {
+            api: "ic0_call_perform".to_string()
+        })
+    );
+}
+
+const DETERMINISM_AUDIT_WAT: &str = r#"
+    (module
+        (import "ic0" "time" (func $time (result i64)))
+        (import "ic0" "stable64_grow" (func $stable64_grow (param i64) (result i64)))
+        (import "ic0" "stable64_write" (func $stable64_write (param i64 i64 i64)))
+        (memory 1)
+        (global $last_time (export "last_time") (mut i64) (i64.const 0))
+        (func (export "canister_update test")
+            (global.set $last_time (call $time))
+            (i64.store (i32.const 0) (global.get $last_time))
+            (drop (call $stable64_grow (i64.const 1)))
+            (call $stable64_write (i64.const 4096) (i64.const 0) (i64.const 8))
+        )
+    )"#;
+
+#[test]
+fn determinism_audit_passes_for_identical_executions() {
+    let report = WasmtimeInstanceBuilder::new()
+        .with_wat(DETERMINISM_AUDIT_WAT)
+        .run_twice_and_compare(FuncRef::Method(WasmMethod::Update("test".to_string())));
+
+    report.assert_deterministic();
+    assert!(report.first.result.is_ok());
+    assert!(!report.first.wasm_dirty_pages.is_empty());
+    assert!(report
+        .first
+        .syscall_trace
+        .iter()
+        .any(|call| call.starts_with("Time")));
+}
+
+#[test]
+fn determinism_audit_pinpoints_nondeterministic_host_function() {
+    // The mocked `ic0.time` returns a different value on every build.
+    let mut now = UNIX_EPOCH;
+    let report = run_twice_and_compare(
+        || {
+            now += std::time::Duration::from_nanos(1);
+            WasmtimeInstanceBuilder::new()
+                .with_wat(DETERMINISM_AUDIT_WAT)
+                .with_api_type(ic_system_api::ApiType::init(
+                    now,
+                    vec![],
+                    user_test_id(24).get(),
+                ))
+                .build()
+        },
+        FuncRef::Method(WasmMethod::Update("test".to_string())),
+    );
+
+    assert!(!report.is_deterministic());
+    let divergence = report.first_divergence().unwrap();
+    assert!(
+        divergence.starts_with("System API call #0 differs") && divergence.contains("Time"),
+        "Unexpected divergence: {divergence}"
+    );
+    // Everything except the time dependent state is still identical.
+    assert_eq!(
+        report.first.instructions_left,
+        report.second.instructions_left
+    );
+    assert_eq!(
+        report.first.wasm_dirty_pages,
+        report.second.wasm_dirty_pages
+    );
+    assert_ne!(
+        report.first.wasm_memory_hash,
+        report.second.wasm_memory_hash
+    );
+    assert_ne!(
+        report.first.exported_globals,
+        report.second.exported_globals
+    );
+}
+
+#[cfg(target_os = "linux")]
+mod page_access_trace {
+    use super::*;
+    use memory_tracker::DEFAULT_PAGE_ACCESS_TRACE_LEN;
+
+    /// Writes every OS page of a 16 MiB heap in order.
+    const SEQUENTIAL_SCAN_WAT: &str = r#"
+        (module
+            (memory (export "memory") 256)
+            (func (export "canister_update test")
+                (local $i i32)
+                (loop $loop
+                    (i32.store (i32.mul (local.get $i) (i32.const 4096)) (i32.const 1))
+                    (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                    (br_if $loop (i32.lt_u (local.get $i) (i32.const 4096)))
+                )
+            )
+        )"#;
+
+    /// Writes 1000 pseudo-randomly chosen OS pages of a 16 MiB heap.
+    const RANDOM_ACCESS_WAT: &str = r#"
+        (module
+            (memory (export "memory") 256)
+            (func (export "canister_update test")
+                (local $i i32)
+                (local $x i32)
+                (local.set $x (i32.const 42))
+                (loop $loop
+                    (local.set $x
+                        (i32.add (i32.mul (local.get $x) (i32.const 1103515245)) (i32.const 12345)))
+                    (i32.store
+                        (i32.mul
+                            (i32.and (i32.shr_u (local.get $x) (i32.const 16)) (i32.const 4095))
+                            (i32.const 4096))
+                        (i32.const 1))
+                    (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                    (br_if $loop (i32.lt_u (local.get $i) (i32.const 1000)))
+                )
+            )
+        )"#;
+
+    /// Maximum number of pages mapped by the signal handler on a single fault.
+    const PREFETCH_WINDOW: u64 = 128;
+
+    fn run_traced(wat: &str) -> memory_tracker::PageAccessTrace<CanisterMemoryType> {
+        let mut instance = WasmtimeInstanceBuilder::new()
+            .with_wat(wat)
+            .with_page_access_trace(DEFAULT_PAGE_ACCESS_TRACE_LEN)
+            .build();
+        instance
+            .run(FuncRef::Method(WasmMethod::Update("test".to_string())))
+            .unwrap();
+        instance.take_page_access_trace().unwrap()
+    }
+
+    #[test]
+    fn sequentiality_separates_sequential_and_random_access() {
+        let sequential = run_traced(SEQUENTIAL_SCAN_WAT);
+        let random = run_traced(RANDOM_ACCESS_WAT);
+        assert!(!sequential.is_truncated());
+        assert!(!random.is_truncated());
+        assert!(sequential
+            .accesses()
+            .iter()
+            .all(|access| access.memory == CanisterMemoryType::Heap));
+
+        let sequential = sequential.sequentiality(PREFETCH_WINDOW).unwrap();
+        let random = random.sequentiality(PREFETCH_WINDOW).unwrap();
+        assert!(sequential > 0.5, "sequential scan: {sequential}");
+        assert!(random < 0.2, "random access: {random}");
+    }
+
+    #[test]
+    fn page_access_trace_is_truncated() {
+        let mut instance = WasmtimeInstanceBuilder::new()
+            .with_wat(SEQUENTIAL_SCAN_WAT)
+            .with_page_access_trace(10)
+            .build();
+        instance
+            .run(FuncRef::Method(WasmMethod::Update("test".to_string())))
+            .unwrap();
+        let trace = instance.take_page_access_trace().unwrap();
+        assert_eq!(trace.accesses().len(), 10);
+        assert!(trace.is_truncated());
+    }
+
+    #[test]
+    fn page_access_trace_does_not_change_stats() {
+        let run = |builder: WasmtimeInstanceBuilder| {
+            let mut instance = builder.with_wat(RANDOM_ACCESS_WAT).build();
+            instance
+                .run(FuncRef::Method(WasmMethod::Update("test".to_string())))
+                .unwrap();
+            (instance.get_stats(), instance.take_page_access_trace())
+        };
+
+        let (untraced_stats, untraced) = run(WasmtimeInstanceBuilder::new());
+        let (traced_stats, traced) =
+            run(WasmtimeInstanceBuilder::new()
+                .with_page_access_trace(DEFAULT_PAGE_ACCESS_TRACE_LEN));
+        assert!(untraced.is_none());
+        assert!(!traced.unwrap().accesses().is_empty());
+        assert_eq!(untraced_stats, traced_stats);
+    }
+}
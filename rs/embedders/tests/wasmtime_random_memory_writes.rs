@@ -114,6 +114,9 @@ fn test_api_for_update(
             .feature_flags
             .wasm_native_stable_memory,
         EmbeddersConfig::default().feature_flags.canister_backtrace,
+        EmbeddersConfig::default()
+            .feature_flags
+            .redact_error_payloads,
         EmbeddersConfig::default().max_sum_exported_function_name_lengths,
         Memory::new_for_testing(),
         NumWasmPages::from(0),
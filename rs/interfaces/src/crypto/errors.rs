@@ -277,6 +277,8 @@ impl ErrorReproducibility for IDkgVerifyDealingPrivateError {
             }
             // false, as a transient error is not reproducible by definition
             IDkgVerifyDealingPrivateError::TransientInternalError { .. } => false,
+            // false, as a timeout is transient by definition
+            IDkgVerifyDealingPrivateError::Timeout { .. } => false,
             // true, as the dealing does not become valid through retrying
             IDkgVerifyDealingPrivateError::InvalidDealing(_) => true,
             // true, as validity checks of arguments are stable across replicas
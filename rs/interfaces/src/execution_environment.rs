@@ -86,6 +86,24 @@ pub struct InstanceStats {
 
     /// Number of pages loaded by copying the data in stable memory.
     pub stable_copy_page_count: usize,
+
+    /// Size in bytes of the compiled machine code of the module. Shared by all
+    /// instances of the module.
+    pub engine_compiled_code_bytes: usize,
+
+    /// Estimated size in bytes of the engine's bookkeeping of the instance
+    /// (tables, globals and store overhead). This is an estimate, not a
+    /// measurement.
+    pub engine_instance_bookkeeping_bytes: usize,
+
+    /// Address space in bytes reserved for the linear memories, excluding
+    /// guard regions. Most of it is typically untouched and not backed by
+    /// physical memory.
+    pub linear_memory_reserved_bytes: usize,
+
+    /// Bytes of the Wasm heap and stable memory backed by physical memory.
+    /// Measured on Linux, estimated from the accessed pages elsewhere.
+    pub linear_memory_resident_bytes: usize,
 }
 
 impl InstanceStats {
@@ -0,0 +1,83 @@
+//! Cycles fees of the update variants of the expensive holder endpoints.
+//!
+//! Canisters reading the holder store through inter-canister calls consume
+//! the ledger's cycles. The holder range endpoints (`get_top`,
+//! `get_holders_by_cursor`) and `get_holder_snapshots` therefore have update
+//! variants (`get_top_update`, `get_holders_by_cursor_update`,
+//! `get_holder_snapshots_update`) that require the caller to attach the fee of
+//! the endpoint's tier. The ledger accepts exactly the fee before serving the
+//! call; the excess is refunded to the caller when the call returns. A call
+//! with fewer cycles than the fee is rejected, and all attached cycles are
+//! refunded.
+//!
+//! The query variants remain free, for ingress and canister callers alike:
+//! their cost per call is bounded by the page size limits of the endpoints
+//! and by the instruction budget of queries. The fees are configured by the
+//! ledger's controllers and take effect immediately, without an upgrade. All
+//! fees are zero by default, i.e., the update variants are free as well.
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Cycles fees of the update variants of the holder endpoints, per tier.
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct HolderApiFees {
+    /// Cycles required by `get_top_update` and `get_holders_by_cursor_update`.
+    pub holder_range_cycles: u64,
+    /// Cycles required by `get_holder_snapshots_update`.
+    pub holder_snapshots_cycles: u64,
+}
+
+/// The tier of a holder endpoint, which determines its fee.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HolderApiTier {
+    Range,
+    Snapshots,
+}
+
+impl HolderApiFees {
+    pub fn fee(&self, tier: HolderApiTier) -> u64 {
+        match tier {
+            HolderApiTier::Range => self.holder_range_cycles,
+            HolderApiTier::Snapshots => self.holder_snapshots_cycles,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HolderApiFeeError {
+    InsufficientCycles { required: u64, available: u64 },
+}
+
+impl fmt::Display for HolderApiFeeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InsufficientCycles {
+                required,
+                available,
+            } => write!(
+                f,
+                "Insufficient cycles attached: the call requires {} cycles, but only {} were attached.",
+                required, available
+            ),
+        }
+    }
+}
+
+/// Returns the number of cycles to accept out of the `available` cycles
+/// attached to a call to an endpoint of `tier`. The cycles not accepted are
+/// refunded to the caller.
+pub fn cycles_to_accept(
+    fees: &HolderApiFees,
+    tier: HolderApiTier,
+    available: u64,
+) -> Result<u64, HolderApiFeeError> {
+    let required = fees.fee(tier);
+    if available < required {
+        return Err(HolderApiFeeError::InsufficientCycles {
+            required,
+            available,
+        });
+    }
+    Ok(required)
+}
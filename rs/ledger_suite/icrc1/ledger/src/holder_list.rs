@@ -1,11 +1,39 @@
+use crate::holder_tasks::{in_progress_holder_tasks, HolderTask};
 use crate::HOLDER_STORE;
 use candid::{CandidType, Nat};
+use ic_crypto_sha2::Sha256;
+use ic_stable_structures::memory_manager::VirtualMemory;
+use ic_stable_structures::{DefaultMemoryImpl, Memory, StableBTreeMap, Storable};
 use icrc_ledger_types::icrc1::account::Account;
 use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::ops::Bound;
+
+/// Maximum number of holders returned by a single `get_holders_by_cursor` call.
+/// Bounds the instructions spent per call, so that other canisters can page
+/// through holders from within a composite query.
+pub const MAX_HOLDERS_PAGE_SIZE: u32 = 1_000;
+
+thread_local! {
+    /// Key used to authenticate pagination cursors. Lives on the heap only, so
+    /// it is replaced on every upgrade, invalidating all outstanding cursors.
+    /// `None` until seeded with randomness after `init` or `post_upgrade`.
+    static CURSOR_SECRET: RefCell<Option<CursorSecret>> = const { RefCell::new(None) };
+
+    /// Byte accounting, budget and secondary index of `HOLDER_STORE`. Lives on
+    /// the heap and is recomputed from `HOLDER_STORE` on `init` and
+    /// `post_upgrade`.
+    static HOLDER_STORE_STATE: RefCell<HolderStoreState> = RefCell::new(HolderStoreState::default());
+}
 
 #[derive(CandidType, Deserialize, Debug, Clone, Serialize)]
 pub struct HolderListMetadata {
     pub total: u64,
+    /// Holder maintenance tasks in progress, during which the holders may not
+    /// reflect the ledger balances yet.
+    pub in_progress_tasks: Vec<HolderTask>,
 }
 
 #[derive(CandidType, Deserialize, Debug, Clone, Serialize)]
@@ -27,15 +55,281 @@ pub struct UpsertHolderInput {
     pub amount: u64,
 }
 
+/// Records the balances of the given holders. Updates that would exceed the
+/// holder store budget are skipped (and counted), never trapping the caller.
 pub fn upsert_holders(input: Vec<UpsertHolderInput>) {
     ic_cdk::print(format!("upsert_holders: {:?}", input));
     HOLDER_STORE.with_borrow_mut(|list| {
-        for holder in input {
-            list.insert(holder.account, holder.amount);
+        HOLDER_STORE_STATE.with_borrow_mut(|state| {
+            for holder in input {
+                if let Err(err) = state.upsert(list, holder.account, holder.amount) {
+                    ic_cdk::println!(
+                        "[ledger] skipped tracking holder {}: {:?}",
+                        holder.account,
+                        err
+                    );
+                }
+            }
+        })
+    })
+}
+
+/// Limits on the stable memory used by the holder store. Configurable by the
+/// ledger's controllers.
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct HolderStoreConfig {
+    /// Maximum number of bytes of holder store entries plus their balance-ordered
+    /// index; `None` for no limit.
+    pub budget_bytes: Option<u64>,
+    /// Whether to evict zero-balance holders to make room for new holders once
+    /// the budget is reached. If `false`, new holders are not tracked instead.
+    pub evict_zero_balance_holders: bool,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct HolderStoreStats {
+    /// Number of holders in the store.
+    pub entries: u64,
+    /// Bytes used by holder store entries (keys plus values).
+    pub store_bytes: u64,
+    /// Number of entries in the zero-balance holder index.
+    pub zero_balance_index_entries: u64,
+    /// Bytes used by the zero-balance holder index (keys only).
+    pub zero_balance_index_bytes: u64,
+    /// Number of entries in the balance-ordered holder index.
+    pub amount_index_entries: u64,
+    /// Bytes used by the balance-ordered holder index (keys only). Charged to
+    /// the budget along with the holder store entries.
+    pub amount_index_bytes: u64,
+    pub budget_bytes: Option<u64>,
+    pub evict_zero_balance_holders: bool,
+    /// Number of zero-balance holders evicted since the last upgrade.
+    pub evicted_total: u64,
+    /// Number of holder updates skipped since the last upgrade.
+    pub skipped_total: u64,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum HolderStoreError {
+    /// Tracking a new holder would exceed the holder store budget.
+    BudgetExceeded {
+        budget_bytes: u64,
+        used_bytes: u64,
+        holder_bytes: u64,
+    },
+}
+
+/// Holder store byte accounting and budget enforcement, maintained
+/// incrementally as holders are inserted, updated or evicted.
+#[derive(Debug, Default)]
+pub struct HolderStoreState {
+    config: HolderStoreConfig,
+    store_bytes: u64,
+    /// Secondary index of holders with a zero balance, i.e. eviction candidates.
+    zero_balance_holders: BTreeSet<Account>,
+    zero_balance_index_bytes: u64,
+    /// Secondary index of all holders ordered by balance, for reading the top
+    /// holders without scanning the store.
+    holders_by_amount: BTreeSet<(u64, Account)>,
+    amount_index_bytes: u64,
+    evicted_total: u64,
+    skipped_total: u64,
+}
+
+impl HolderStoreState {
+    /// Computes the state of the given holder store from scratch.
+    pub fn new<M: Memory>(
+        store: &StableBTreeMap<Account, u64, M>,
+        config: HolderStoreConfig,
+    ) -> Self {
+        let mut state = Self {
+            config,
+            ..Default::default()
+        };
+        for (account, amount) in store.iter() {
+            state.store_bytes += entry_bytes(&account);
+            state.holders_by_amount.insert((amount, account));
+            state.amount_index_bytes += amount_index_entry_bytes(&account);
+            if amount == 0 {
+                state.add_to_zero_balance_index(account);
+            }
         }
+        state
+    }
+
+    pub fn config(&self) -> &HolderStoreConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: HolderStoreConfig) {
+        self.config = config;
+    }
+
+    /// Records `amount` as the balance of `account`.
+    ///
+    /// Updating a tracked holder never changes the store's size. Tracking a new
+    /// holder (i.e. its entry and its balance index entry) beyond the budget
+    /// evicts zero-balance holders, if enabled and possible; else the update is
+    /// skipped, counted and an error is returned.
+    pub fn upsert<M: Memory>(
+        &mut self,
+        store: &mut StableBTreeMap<Account, u64, M>,
+        account: Account,
+        amount: u64,
+    ) -> Result<(), HolderStoreError> {
+        if !store.contains_key(&account) {
+            self.make_room(store, holder_bytes(&account))?;
+            self.store_bytes += entry_bytes(&account);
+            self.amount_index_bytes += amount_index_entry_bytes(&account);
+        }
+
+        if let Some(previous_amount) = store.insert(account, amount) {
+            self.holders_by_amount.remove(&(previous_amount, account));
+        }
+        self.holders_by_amount.insert((amount, account));
+        if amount == 0 {
+            self.add_to_zero_balance_index(account);
+        } else {
+            self.remove_from_zero_balance_index(&account);
+        }
+        Ok(())
+    }
+
+    /// Ensures that `needed_bytes` more bytes fit within the budget, evicting
+    /// zero-balance holders if enabled.
+    fn make_room<M: Memory>(
+        &mut self,
+        store: &mut StableBTreeMap<Account, u64, M>,
+        needed_bytes: u64,
+    ) -> Result<(), HolderStoreError> {
+        let Some(budget_bytes) = self.config.budget_bytes else {
+            return Ok(());
+        };
+        while self.used_bytes() + needed_bytes > budget_bytes {
+            let victim = match self.zero_balance_holders.first() {
+                Some(victim) if self.config.evict_zero_balance_holders => *victim,
+                _ => {
+                    self.skipped_total += 1;
+                    return Err(HolderStoreError::BudgetExceeded {
+                        budget_bytes,
+                        used_bytes: self.used_bytes(),
+                        holder_bytes: needed_bytes,
+                    });
+                }
+            };
+            store.remove(&victim);
+            self.remove_from_zero_balance_index(&victim);
+            self.holders_by_amount.remove(&(0, victim));
+            self.store_bytes -= entry_bytes(&victim);
+            self.amount_index_bytes -= amount_index_entry_bytes(&victim);
+            self.evicted_total += 1;
+        }
+        Ok(())
+    }
+
+    fn add_to_zero_balance_index(&mut self, account: Account) {
+        let key_bytes = account.to_bytes().len() as u64;
+        if self.zero_balance_holders.insert(account) {
+            self.zero_balance_index_bytes += key_bytes;
+        }
+    }
+
+    fn remove_from_zero_balance_index(&mut self, account: &Account) {
+        if self.zero_balance_holders.remove(account) {
+            self.zero_balance_index_bytes -= account.to_bytes().len() as u64;
+        }
+    }
+
+    /// Returns up to `k` holders with a non-zero balance, in descending order
+    /// of their balance. The cost is bounded by `k`, not by the number of
+    /// holders.
+    pub fn top_holders(&self, k: usize) -> Vec<(Account, u64)> {
+        self.holders_by_amount
+            .iter()
+            .rev()
+            .take_while(|(amount, _)| *amount > 0)
+            .take(k)
+            .map(|(amount, account)| (*account, *amount))
+            .collect()
+    }
+
+    /// Number of holders with a non-zero balance.
+    pub fn non_zero_holders(&self) -> u64 {
+        (self.holders_by_amount.len() - self.zero_balance_holders.len()) as u64
+    }
+
+    /// Bytes charged to the budget: the holder store entries plus their
+    /// balance-ordered index.
+    pub fn used_bytes(&self) -> u64 {
+        self.store_bytes + self.amount_index_bytes
+    }
+
+    pub fn stats(&self, entries: u64) -> HolderStoreStats {
+        HolderStoreStats {
+            entries,
+            store_bytes: self.store_bytes,
+            zero_balance_index_entries: self.zero_balance_holders.len() as u64,
+            zero_balance_index_bytes: self.zero_balance_index_bytes,
+            amount_index_entries: self.holders_by_amount.len() as u64,
+            amount_index_bytes: self.amount_index_bytes,
+            budget_bytes: self.config.budget_bytes,
+            evict_zero_balance_holders: self.config.evict_zero_balance_holders,
+            evicted_total: self.evicted_total,
+            skipped_total: self.skipped_total,
+        }
+    }
+}
+
+/// The number of bytes a holder store entry for `account` occupies: the
+/// encoded account plus the `u64` balance.
+fn entry_bytes(account: &Account) -> u64 {
+    (account.to_bytes().len() + std::mem::size_of::<u64>()) as u64
+}
+
+/// The number of bytes the balance-ordered index entry for `account` occupies:
+/// the `u64` balance plus the encoded account.
+fn amount_index_entry_bytes(account: &Account) -> u64 {
+    (std::mem::size_of::<u64>() + account.to_bytes().len()) as u64
+}
+
+/// The number of bytes charged to the budget for tracking `account`.
+fn holder_bytes(account: &Account) -> u64 {
+    entry_bytes(account) + amount_index_entry_bytes(account)
+}
+
+/// Recomputes the holder store state from `HOLDER_STORE`. Must be called on
+/// `init` and `post_upgrade`.
+pub fn reset_holder_store_state(config: HolderStoreConfig) {
+    HOLDER_STORE.with_borrow(|list| {
+        HOLDER_STORE_STATE.with_borrow_mut(|state| *state = HolderStoreState::new(list, config))
     })
 }
 
+/// Applies a new holder store configuration. A lowered budget only affects
+/// holders tracked from now on; existing holders are never removed.
+pub fn set_holder_store_config(config: HolderStoreConfig) {
+    HOLDER_STORE_STATE.with_borrow_mut(|state| state.set_config(config))
+}
+
+pub(crate) fn with_holder_store_state<R>(f: impl FnOnce(&HolderStoreState) -> R) -> R {
+    HOLDER_STORE_STATE.with_borrow(f)
+}
+
+pub(crate) fn with_holder_store_mut<R>(
+    f: impl FnOnce(
+        &mut StableBTreeMap<Account, u64, VirtualMemory<DefaultMemoryImpl>>,
+        &mut HolderStoreState,
+    ) -> R,
+) -> R {
+    HOLDER_STORE
+        .with_borrow_mut(|store| HOLDER_STORE_STATE.with_borrow_mut(|state| f(store, state)))
+}
+
+pub fn get_holder_store_stats() -> HolderStoreStats {
+    let entries = count_holders();
+    HOLDER_STORE_STATE.with_borrow(|state| state.stats(entries))
+}
+
 pub fn get_holders(offset: u32, limit: u32, total_supply: u64) -> HolderListResp {
     let mut data = vec![];
     let mut total = 0;
@@ -63,7 +357,10 @@ pub fn get_holders(offset: u32, limit: u32, total_supply: u64) -> HolderListResp
     });
 
     HolderListResp {
-        metadata: HolderListMetadata { total },
+        metadata: HolderListMetadata {
+            total,
+            in_progress_tasks: in_progress_holder_tasks(),
+        },
         data,
     }
 }
@@ -75,3 +372,193 @@ pub fn count_holders() -> u64 {
     });
     total
 }
+
+/// The secret key authenticating holder cursors, together with an epoch
+/// identifying it.
+struct CursorSecret {
+    key: [u8; 32],
+    epoch: u64,
+}
+
+impl CursorSecret {
+    fn new(seed: &[u8]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.write(b"ic-icrc1-ledger-holder-cursor");
+        hasher.write(seed);
+        let key = hasher.finish();
+        let epoch_bytes: [u8; 8] = Sha256::hash(&key)[..8].try_into().unwrap();
+        Self {
+            key,
+            epoch: u64::from_be_bytes(epoch_bytes),
+        }
+    }
+}
+
+/// Discards the cursor secret, invalidating all cursors issued before. Holder
+/// cursors are rejected until `set_cursor_secret()` is called. Must be called
+/// on `init` and `post_upgrade`.
+pub fn clear_cursor_secret() {
+    CURSOR_SECRET.with_borrow_mut(|secret| *secret = None);
+}
+
+/// Sets the cursor secret to one derived from `seed`, which must be
+/// unpredictable (e.g. the output of `raw_rand`): anyone able to reproduce the
+/// secret can forge cursors.
+pub fn set_cursor_secret(seed: &[u8]) {
+    CURSOR_SECRET.with_borrow_mut(|secret| *secret = Some(CursorSecret::new(seed)));
+}
+
+/// An opaque, self-authenticating pagination cursor for `get_holders_by_cursor`.
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Serialize)]
+pub struct HolderCursor {
+    /// The ledger's chain length when the first page was requested.
+    pub as_of_block: u64,
+    /// The last account returned on the previous page.
+    pub last_key: Account,
+    /// Identifies the secret the cursor was issued with.
+    pub epoch: u64,
+    /// HMAC-SHA256 over `(epoch, as_of_block, last_key)`.
+    pub mac: ByteBuf,
+}
+
+impl HolderCursor {
+    fn new(secret: &CursorSecret, as_of_block: u64, last_key: Account) -> Self {
+        let mac = cursor_mac(secret, as_of_block, &last_key);
+        Self {
+            as_of_block,
+            last_key,
+            epoch: secret.epoch,
+            mac: ByteBuf::from(mac.to_vec()),
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum HolderCursorError {
+    /// The cursor was not issued by this ledger or was tampered with.
+    InvalidCursor,
+    /// The cursor was issued before the last upgrade of the ledger.
+    CursorExpired { reason: String },
+    /// The ledger is not yet ready to issue or accept cursors, e.g. right after
+    /// an upgrade. Retry later.
+    TemporarilyUnavailable { reason: String },
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, Serialize)]
+pub struct HolderPage {
+    /// The ledger's chain length when the first page was requested.
+    pub as_of_block: u64,
+    pub data: Vec<HolderData>,
+    /// The cursor to pass to the next call; `None` if this is the last page.
+    pub next_cursor: Option<HolderCursor>,
+    /// Holder maintenance tasks in progress, see `HolderListMetadata`.
+    pub in_progress_tasks: Vec<HolderTask>,
+}
+
+/// Returns up to `limit` (capped at `MAX_HOLDERS_PAGE_SIZE`) holders in account
+/// order, starting after the account recorded in `cursor`; or from the first
+/// account if `cursor` is `None`, in which case `chain_length` is recorded as
+/// the page's `as_of_block`.
+///
+/// Performs no inter-canister calls and its cost is bounded by `limit`, so it
+/// is safe to call from composite queries.
+pub fn get_holders_by_cursor(
+    cursor: Option<HolderCursor>,
+    limit: u32,
+    total_supply: u64,
+    chain_length: u64,
+) -> Result<HolderPage, HolderCursorError> {
+    CURSOR_SECRET.with_borrow(|secret| {
+        let Some(secret) = secret else {
+            return Err(HolderCursorError::TemporarilyUnavailable {
+                reason: "The ledger is initializing its cursor secret. Retry shortly.".to_string(),
+            });
+        };
+        let (as_of_block, start) = match cursor {
+            None => (chain_length, Bound::Unbounded),
+            Some(cursor) => {
+                verify_cursor(secret, &cursor)?;
+                (cursor.as_of_block, Bound::Excluded(cursor.last_key))
+            }
+        };
+        let limit = limit.min(MAX_HOLDERS_PAGE_SIZE) as usize;
+
+        HOLDER_STORE.with_borrow(|list| {
+            let mut page: Vec<_> = list
+                .range((start, Bound::Unbounded))
+                .take(limit + 1)
+                .collect();
+            let has_more = page.len() > limit;
+            page.truncate(limit);
+
+            let next_cursor = match page.last() {
+                Some((account, _)) if has_more => {
+                    Some(HolderCursor::new(secret, as_of_block, *account))
+                }
+                _ => None,
+            };
+            let data = page
+                .into_iter()
+                .map(|(account, amount)| HolderData {
+                    account,
+                    amount: Nat::from(amount),
+                    percentage: (amount as f64) / (total_supply as f64),
+                })
+                .collect();
+
+            Ok(HolderPage {
+                as_of_block,
+                data,
+                next_cursor,
+                in_progress_tasks: in_progress_holder_tasks(),
+            })
+        })
+    })
+}
+
+fn verify_cursor(secret: &CursorSecret, cursor: &HolderCursor) -> Result<(), HolderCursorError> {
+    if cursor.epoch != secret.epoch {
+        return Err(HolderCursorError::CursorExpired {
+            reason: "The cursor was issued before the last ledger upgrade. \
+                     Restart pagination without a cursor."
+                .to_string(),
+        });
+    }
+    let expected_mac = cursor_mac(secret, cursor.as_of_block, &cursor.last_key);
+    if cursor.mac.as_slice() != expected_mac.as_slice() {
+        return Err(HolderCursorError::InvalidCursor);
+    }
+    Ok(())
+}
+
+fn cursor_mac(secret: &CursorSecret, as_of_block: u64, last_key: &Account) -> [u8; 32] {
+    let owner = last_key.owner.as_slice();
+    let mut message = Vec::with_capacity(8 + 8 + 1 + owner.len() + 32);
+    message.extend_from_slice(&secret.epoch.to_be_bytes());
+    message.extend_from_slice(&as_of_block.to_be_bytes());
+    message.push(owner.len() as u8);
+    message.extend_from_slice(owner);
+    message.extend_from_slice(last_key.effective_subaccount());
+    hmac_sha256(&secret.key, &message)
+}
+
+/// HMAC-SHA256 as per RFC 2104, for keys no longer than the block size.
+fn hmac_sha256(key: &[u8; 32], message: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+    let mut inner_pad = [0x36_u8; BLOCK_SIZE];
+    let mut outer_pad = [0x5c_u8; BLOCK_SIZE];
+    for (i, byte) in key.iter().enumerate() {
+        inner_pad[i] ^= byte;
+        outer_pad[i] ^= byte;
+    }
+
+    let mut inner = Sha256::new();
+    inner.write(&inner_pad);
+    inner.write(message);
+    let inner_hash = inner.finish();
+
+    let mut outer = Sha256::new();
+    outer.write(&outer_pad);
+    outer.write(&inner_hash);
+    outer.finish()
+}
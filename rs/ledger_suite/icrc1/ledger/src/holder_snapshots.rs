@@ -0,0 +1,251 @@
+//! Periodic snapshots of the top holders, for historical top-holder charts.
+//!
+//! Snapshots are taken by a timer at a controller-configured interval and
+//! kept in a ring buffer in stable memory: a `StableBTreeMap` keyed by
+//! consecutive sequence numbers, from which the oldest snapshots are evicted
+//! once the configured retention count is reached. Taking a snapshot only
+//! reads the top `HOLDER_SNAPSHOT_TOP_K` holders from the balance index of the
+//! holder store, so its cost does not depend on the number of holders.
+use crate::holder_list::{with_holder_store_state, HolderStoreState};
+use crate::HOLDER_SNAPSHOTS;
+use candid::CandidType;
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::{Memory, StableBTreeMap, Storable};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cell::RefCell;
+
+/// Number of holders recorded in every snapshot.
+pub const HOLDER_SNAPSHOT_TOP_K: usize = 100;
+
+/// Maximum number of snapshots returned by a single `get_holder_snapshots`
+/// call.
+pub const MAX_HOLDER_SNAPSHOTS_PAGE_SIZE: u32 = 10;
+
+thread_local! {
+    /// Byte accounting of `HOLDER_SNAPSHOTS`. Lives on the heap and is
+    /// recomputed from `HOLDER_SNAPSHOTS` on `init` and `post_upgrade`.
+    static HOLDER_SNAPSHOTS_STATE: RefCell<HolderSnapshotsState> = RefCell::new(HolderSnapshotsState::default());
+}
+
+/// Schedule of the holder snapshots. Configurable by the ledger's controllers.
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct HolderSnapshotConfig {
+    /// Seconds between two snapshots.
+    pub interval_seconds: u64,
+    /// Number of snapshots to keep. Older snapshots are evicted.
+    pub retention: u32,
+}
+
+impl HolderSnapshotConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_seconds == 0 {
+            return Err("interval_seconds must be positive".to_string());
+        }
+        if self.retention == 0 {
+            return Err("retention must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct HolderSnapshotEntry {
+    pub account: Account,
+    pub amount: u64,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct HolderSnapshot {
+    /// Time the snapshot was taken, in nanoseconds since the UNIX epoch.
+    pub timestamp: u64,
+    /// The ledger's chain length when the snapshot was taken.
+    pub block: u64,
+    /// Number of holders with a non-zero balance.
+    pub total_holders: u64,
+    /// Up to `HOLDER_SNAPSHOT_TOP_K` holders, in descending order of balance.
+    pub top_holders: Vec<HolderSnapshotEntry>,
+}
+
+impl Storable for HolderSnapshot {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        let mut buf = vec![];
+        ciborium::ser::into_writer(self, &mut buf).expect("failed to encode holder snapshot");
+        Cow::Owned(buf)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        ciborium::de::from_reader(&bytes[..]).expect("failed to decode holder snapshot")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, Serialize)]
+pub struct HolderSnapshotList {
+    /// Number of stored snapshots.
+    pub total: u64,
+    /// Snapshots, newest first.
+    pub snapshots: Vec<HolderSnapshot>,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum HolderSnapshotError {
+    /// Storing the snapshot would exceed the holder store budget.
+    BudgetExceeded {
+        budget_bytes: u64,
+        used_bytes: u64,
+        snapshot_bytes: u64,
+    },
+}
+
+/// Byte accounting of the stored snapshots, maintained incrementally as
+/// snapshots are taken or evicted.
+#[derive(Debug, Default)]
+pub struct HolderSnapshotsState {
+    snapshot_bytes: u64,
+    /// Number of snapshots skipped since the last upgrade.
+    skipped_total: u64,
+}
+
+impl HolderSnapshotsState {
+    /// Computes the state of the given snapshots from scratch.
+    pub fn new<M: Memory>(snapshots: &StableBTreeMap<u64, HolderSnapshot, M>) -> Self {
+        Self {
+            snapshot_bytes: snapshots
+                .iter()
+                .map(|(_, snapshot)| snapshot_bytes(&snapshot))
+                .sum(),
+            skipped_total: 0,
+        }
+    }
+
+    pub fn snapshot_bytes(&self) -> u64 {
+        self.snapshot_bytes
+    }
+
+    pub fn skipped_total(&self) -> u64 {
+        self.skipped_total
+    }
+
+    /// Stores a snapshot of the top holders of `holders` and evicts the oldest
+    /// snapshots beyond `retention`.
+    ///
+    /// Snapshots share the budget of the holder store (see
+    /// `HolderStoreConfig::budget_bytes`) with the holder entries: if the new
+    /// snapshot does not fit into what the holder entries leave, even after
+    /// evicting old snapshots, nothing is changed and an error is returned.
+    pub fn take_snapshot<M: Memory>(
+        &mut self,
+        holders: &HolderStoreState,
+        snapshots: &mut StableBTreeMap<u64, HolderSnapshot, M>,
+        retention: u32,
+        timestamp: u64,
+        block: u64,
+    ) -> Result<(), HolderSnapshotError> {
+        let snapshot = HolderSnapshot {
+            timestamp,
+            block,
+            total_holders: holders.non_zero_holders(),
+            top_holders: holders
+                .top_holders(HOLDER_SNAPSHOT_TOP_K)
+                .into_iter()
+                .map(|(account, amount)| HolderSnapshotEntry { account, amount })
+                .collect(),
+        };
+        let new_bytes = snapshot_bytes(&snapshot);
+
+        let num_evicted = (snapshots.len() + 1).saturating_sub(retention as u64);
+        let evicted: Vec<(u64, u64)> = snapshots
+            .iter()
+            .take(num_evicted as usize)
+            .map(|(index, snapshot)| (index, snapshot_bytes(&snapshot)))
+            .collect();
+        let evicted_bytes: u64 = evicted.iter().map(|(_, bytes)| bytes).sum();
+
+        if let Some(budget_bytes) = holders.config().budget_bytes {
+            let used_bytes = holders.used_bytes() + self.snapshot_bytes - evicted_bytes;
+            if used_bytes + new_bytes > budget_bytes {
+                self.skipped_total += 1;
+                return Err(HolderSnapshotError::BudgetExceeded {
+                    budget_bytes,
+                    used_bytes,
+                    snapshot_bytes: new_bytes,
+                });
+            }
+        }
+
+        for (index, _) in evicted {
+            snapshots.remove(&index);
+        }
+        let next_index = snapshots.last_key_value().map_or(0, |(index, _)| index + 1);
+        snapshots.insert(next_index, snapshot);
+        self.snapshot_bytes = self.snapshot_bytes - evicted_bytes + new_bytes;
+        Ok(())
+    }
+}
+
+fn snapshot_bytes(snapshot: &HolderSnapshot) -> u64 {
+    (std::mem::size_of::<u64>() + snapshot.to_bytes().len()) as u64
+}
+
+/// Returns up to `limit` (capped at `MAX_HOLDER_SNAPSHOTS_PAGE_SIZE`) snapshots,
+/// newest first, skipping the `offset` newest ones.
+pub fn holder_snapshots_page<M: Memory>(
+    snapshots: &StableBTreeMap<u64, HolderSnapshot, M>,
+    offset: u32,
+    limit: u32,
+) -> HolderSnapshotList {
+    let limit = limit.min(MAX_HOLDER_SNAPSHOTS_PAGE_SIZE) as u64;
+    let page = match (snapshots.first_key_value(), snapshots.last_key_value()) {
+        // Snapshots are stored under consecutive indices.
+        (Some((first, _)), Some((last, _))) => (0..limit)
+            .filter_map(|i| last.checked_sub(offset as u64 + i))
+            .take_while(|index| *index >= first)
+            .filter_map(|index| snapshots.get(&index))
+            .collect(),
+        _ => vec![],
+    };
+    HolderSnapshotList {
+        total: snapshots.len(),
+        snapshots: page,
+    }
+}
+
+/// Recomputes the snapshot state from `HOLDER_SNAPSHOTS`. Must be called on
+/// `init` and `post_upgrade`.
+pub fn reset_holder_snapshots_state() {
+    HOLDER_SNAPSHOTS.with_borrow(|snapshots| {
+        HOLDER_SNAPSHOTS_STATE
+            .with_borrow_mut(|state| *state = HolderSnapshotsState::new(snapshots))
+    })
+}
+
+/// Takes a snapshot of the current top holders, see
+/// `HolderSnapshotsState::take_snapshot`.
+pub fn take_holder_snapshot(
+    retention: u32,
+    timestamp: u64,
+    block: u64,
+) -> Result<(), HolderSnapshotError> {
+    with_holder_store_state(|holders| {
+        HOLDER_SNAPSHOTS.with_borrow_mut(|snapshots| {
+            HOLDER_SNAPSHOTS_STATE.with_borrow_mut(|state| {
+                state.take_snapshot(holders, snapshots, retention, timestamp, block)
+            })
+        })
+    })
+}
+
+pub fn get_holder_snapshots(offset: u32, limit: u32) -> HolderSnapshotList {
+    HOLDER_SNAPSHOTS.with_borrow(|snapshots| holder_snapshots_page(snapshots, offset, limit))
+}
+
+/// Returns the number of stored snapshots, the bytes they use and the number
+/// of snapshots skipped since the last upgrade.
+pub fn holder_snapshots_stats() -> (u64, u64, u64) {
+    let total = HOLDER_SNAPSHOTS.with_borrow(|snapshots| snapshots.len());
+    HOLDER_SNAPSHOTS_STATE
+        .with_borrow(|state| (total, state.snapshot_bytes(), state.skipped_total()))
+}
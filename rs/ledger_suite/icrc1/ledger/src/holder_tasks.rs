@@ -0,0 +1,191 @@
+//! Registry of the holder maintenance tasks that span several messages.
+//!
+//! Such a task records its progress in stable memory after every step: its
+//! kind, the time it was started and a checkpoint, i.e. the last account it
+//! processed. Timers do not survive upgrades, so `post_upgrade` re-schedules
+//! every registered task, which then resumes right after its checkpoint. At
+//! most one task of each kind is in progress at a time.
+//!
+//! The only such task is the holder backfill, which recomputes the holder
+//! store from the ledger balances in batches. Holder snapshots are taken
+//! within a single message and thus never are in progress during an upgrade.
+use crate::holder_list::{with_holder_store_mut, HolderStoreState};
+use crate::HOLDER_TASKS;
+use candid::CandidType;
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::{Memory, StableBTreeMap, Storable};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::ops::Bound as RangeBound;
+
+/// Number of ledger balances processed by a single step of the backfill.
+pub const HOLDER_BACKFILL_BATCH_SIZE: usize = 100;
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HolderTaskKind {
+    /// Recomputes the holder store from the ledger balances.
+    Backfill,
+}
+
+impl HolderTaskKind {
+    /// The key of the task in the registry.
+    fn key(self) -> u8 {
+        match self {
+            HolderTaskKind::Backfill => 0,
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct HolderTask {
+    pub kind: HolderTaskKind,
+    /// Time the task was started, in nanoseconds since the UNIX epoch.
+    pub started_at: u64,
+    /// The last account processed; `None` if the task has not made progress yet.
+    pub checkpoint: Option<Account>,
+    /// Number of accounts processed so far.
+    pub processed: u64,
+}
+
+impl Storable for HolderTask {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        let mut buf = vec![];
+        ciborium::ser::into_writer(self, &mut buf).expect("failed to encode holder task");
+        Cow::Owned(buf)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        ciborium::de::from_reader(&bytes[..]).expect("failed to decode holder task")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum HolderTaskError {
+    /// A task of the same kind is already in progress.
+    AlreadyRunning { task: HolderTask },
+}
+
+/// Registers a new task of the given kind, unless one is already in progress.
+pub fn start_task<M: Memory>(
+    tasks: &mut StableBTreeMap<u8, HolderTask, M>,
+    kind: HolderTaskKind,
+    started_at: u64,
+) -> Result<HolderTask, HolderTaskError> {
+    if let Some(task) = tasks.get(&kind.key()) {
+        return Err(HolderTaskError::AlreadyRunning { task });
+    }
+    let task = HolderTask {
+        kind,
+        started_at,
+        checkpoint: None,
+        processed: 0,
+    };
+    tasks.insert(kind.key(), task.clone());
+    Ok(task)
+}
+
+/// Returns the tasks in progress.
+pub fn in_progress<M: Memory>(tasks: &StableBTreeMap<u8, HolderTask, M>) -> Vec<HolderTask> {
+    tasks.iter().map(|(_, task)| task).collect()
+}
+
+/// Runs one step of the backfill, if in progress: records the balances of up
+/// to `batch_size` accounts after the checkpoint in the holder store, zeroes
+/// the holders in the same range that no longer have a balance, and then
+/// advances the checkpoint or, after the last batch, removes the task.
+///
+/// Returns whether the backfill needs further steps. The batch and the new
+/// checkpoint are applied in the same message, so resuming from the
+/// checkpoint never processes an account twice.
+pub fn backfill_step<T, M: Memory, N: Memory>(
+    tasks: &mut StableBTreeMap<u8, HolderTask, M>,
+    holders: &mut HolderStoreState,
+    store: &mut StableBTreeMap<Account, u64, N>,
+    balances: &BTreeMap<Account, T>,
+    amount: impl Fn(&T) -> u64,
+    batch_size: usize,
+) -> bool {
+    let key = HolderTaskKind::Backfill.key();
+    let Some(mut task) = tasks.get(&key) else {
+        return false;
+    };
+    let start = match task.checkpoint {
+        Some(checkpoint) => RangeBound::Excluded(checkpoint),
+        None => RangeBound::Unbounded,
+    };
+    let mut batch: Vec<(Account, u64)> = balances
+        .range((start, RangeBound::Unbounded))
+        .take(batch_size + 1)
+        .map(|(account, balance)| (*account, amount(balance)))
+        .collect();
+    let has_more = batch.len() > batch_size;
+    batch.truncate(batch_size);
+    let end = match batch.last() {
+        Some((last, _)) if has_more => RangeBound::Included(*last),
+        _ => RangeBound::Unbounded,
+    };
+
+    let stale: Vec<Account> = store
+        .range((start, end))
+        .filter(|(account, amount)| *amount > 0 && !balances.contains_key(account))
+        .map(|(account, _)| account)
+        .collect();
+    let processed = batch.len() as u64;
+    // Updates beyond the holder store budget are skipped and counted by the
+    // holder store.
+    for (account, amount) in batch
+        .into_iter()
+        .chain(stale.into_iter().map(|account| (account, 0)))
+    {
+        let _ = holders.upsert(store, account, amount);
+    }
+
+    match end {
+        RangeBound::Included(last) => {
+            task.checkpoint = Some(last);
+            task.processed += processed;
+            tasks.insert(key, task);
+            true
+        }
+        _ => {
+            tasks.remove(&key);
+            false
+        }
+    }
+}
+
+/// Starts a task of the given kind, see `start_task`.
+pub fn start_holder_task(
+    kind: HolderTaskKind,
+    started_at: u64,
+) -> Result<HolderTask, HolderTaskError> {
+    HOLDER_TASKS.with_borrow_mut(|tasks| start_task(tasks, kind, started_at))
+}
+
+pub fn in_progress_holder_tasks() -> Vec<HolderTask> {
+    HOLDER_TASKS.with_borrow(in_progress)
+}
+
+/// Runs one step of the holder backfill against the given ledger balances,
+/// see `backfill_step`.
+pub fn run_holder_backfill_step<T>(
+    balances: &BTreeMap<Account, T>,
+    amount: impl Fn(&T) -> u64,
+) -> bool {
+    HOLDER_TASKS.with_borrow_mut(|tasks| {
+        with_holder_store_mut(|store, holders| {
+            backfill_step(
+                tasks,
+                holders,
+                store,
+                balances,
+                amount,
+                HOLDER_BACKFILL_BATCH_SIZE,
+            )
+        })
+    })
+}
@@ -1,5 +1,8 @@
 pub mod cdk_runtime;
+pub mod holder_api_fees;
 pub mod holder_list;
+pub mod holder_snapshots;
+pub mod holder_tasks;
 
 #[cfg(test)]
 mod tests;
@@ -9,7 +12,10 @@ use candid::{
     types::number::{Int, Nat},
     CandidType, Principal,
 };
-use holder_list::upsert_holders;
+use holder_api_fees::HolderApiFees;
+use holder_list::{upsert_holders, HolderStoreConfig};
+use holder_snapshots::{HolderSnapshot, HolderSnapshotConfig};
+use holder_tasks::HolderTask;
 use ic_base_types::PrincipalId;
 use ic_canister_log::{log, Sink};
 use ic_certification::{
@@ -351,6 +357,10 @@ const UPGRADES_MEMORY_ID: MemoryId = MemoryId::new(0);
 
 pub const HOLDER_LIST_MEMORY_ID: MemoryId = MemoryId::new(1);
 
+pub const HOLDER_SNAPSHOTS_MEMORY_ID: MemoryId = MemoryId::new(2);
+
+pub const HOLDER_TASKS_MEMORY_ID: MemoryId = MemoryId::new(3);
+
 thread_local! {
     pub static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(
         MemoryManager::init(DefaultMemoryImpl::default())
@@ -365,6 +375,20 @@ thread_local! {
             MEMORY_MANAGER.with_borrow(|m| m.get(HOLDER_LIST_MEMORY_ID)),
         )
     );
+
+    // Ring buffer of holder snapshots, see `holder_snapshots`.
+    pub static HOLDER_SNAPSHOTS: RefCell<StableBTreeMap<u64, HolderSnapshot, VirtualMemory<DefaultMemoryImpl>>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with_borrow(|m| m.get(HOLDER_SNAPSHOTS_MEMORY_ID)),
+        )
+    );
+
+    // Holder maintenance tasks in progress, see `holder_tasks`.
+    pub static HOLDER_TASKS: RefCell<StableBTreeMap<u8, HolderTask, VirtualMemory<DefaultMemoryImpl>>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with_borrow(|m| m.get(HOLDER_TASKS_MEMORY_ID)),
+        )
+    );
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -401,6 +425,15 @@ pub struct Ledger<Tokens: TokensType> {
 
     #[serde(default = "default_ledger_version")]
     pub ledger_version: u64,
+
+    #[serde(default)]
+    holder_store_config: HolderStoreConfig,
+
+    #[serde(default)]
+    holder_snapshot_config: Option<HolderSnapshotConfig>,
+
+    #[serde(default)]
+    holder_api_fees: HolderApiFees,
 }
 
 fn default_maximum_number_of_accounts() -> usize {
@@ -498,6 +531,9 @@ impl<Tokens: TokensType> Ledger<Tokens> {
                 .try_into()
                 .unwrap(),
             ledger_version: LEDGER_VERSION,
+            holder_store_config: HolderStoreConfig::default(),
+            holder_snapshot_config: None,
+            holder_api_fees: HolderApiFees::default(),
         };
 
         for (account, balance) in initial_balances.into_iter() {
@@ -657,6 +693,30 @@ impl<Tokens: TokensType> Ledger<Tokens> {
         &self.feature_flags
     }
 
+    pub fn holder_store_config(&self) -> &HolderStoreConfig {
+        &self.holder_store_config
+    }
+
+    pub fn set_holder_store_config(&mut self, config: HolderStoreConfig) {
+        self.holder_store_config = config;
+    }
+
+    pub fn holder_snapshot_config(&self) -> Option<&HolderSnapshotConfig> {
+        self.holder_snapshot_config.as_ref()
+    }
+
+    pub fn set_holder_snapshot_config(&mut self, config: Option<HolderSnapshotConfig>) {
+        self.holder_snapshot_config = config;
+    }
+
+    pub fn holder_api_fees(&self) -> &HolderApiFees {
+        &self.holder_api_fees
+    }
+
+    pub fn set_holder_api_fees(&mut self, fees: HolderApiFees) {
+        self.holder_api_fees = fees;
+    }
+
     pub fn upgrade(&mut self, sink: impl Sink + Clone, args: UpgradeArgs) {
         if let Some(upgrade_metadata_args) = args.metadata {
             self.metadata = upgrade_metadata_args
@@ -10,12 +10,19 @@ use ic_cdk::api::stable::StableReader;
 #[cfg(not(feature = "canbench-rs"))]
 use ic_cdk_macros::init;
 use ic_cdk_macros::{post_upgrade, pre_upgrade, query, update};
+use ic_cdk_timers::TimerId;
 use ic_icrc1::{
     endpoints::{convert_transfer_error, StandardRecord},
     Operation, Transaction,
 };
 use ic_icrc1_ledger::{
-    holder_list::{self, upsert_holders, HolderListResp, UpsertHolderInput},
+    holder_api_fees::{self, HolderApiFees, HolderApiTier},
+    holder_list::{
+        self, upsert_holders, HolderCursor, HolderCursorError, HolderListResp, HolderPage,
+        HolderStoreConfig, HolderStoreStats, UpsertHolderInput,
+    },
+    holder_snapshots::{self, HolderSnapshotConfig, HolderSnapshotList},
+    holder_tasks::{self, HolderTask, HolderTaskError, HolderTaskKind},
     InitArgs, Ledger, LedgerArgument, HOLDER_LIST_MEMORY_ID, HOLDER_STORE, MEMORY_MANAGER,
 };
 use ic_icrc1_ledger::{LEDGER_VERSION, UPGRADES_MEMORY};
@@ -65,9 +72,14 @@ use num_traits::{bounds::Bounded, ToPrimitive};
 use serde_bytes::ByteBuf;
 use std::cell::RefCell;
 use std::io::{Read, Write};
+use std::time::Duration;
 
 const MAX_MESSAGE_SIZE: u64 = 1024 * 1024;
 
+/// Delay before retrying to seed the holder cursor secret after `raw_rand`
+/// failed.
+const HOLDER_CURSOR_SECRET_RETRY_DELAY: Duration = Duration::from_secs(10);
+
 #[cfg(not(feature = "u256-tokens"))]
 pub type Tokens = ic_icrc1_tokens_u64::U64;
 
@@ -78,6 +90,7 @@ thread_local! {
     static LEDGER: RefCell<Option<Ledger<Tokens>>> = const { RefCell::new(None) };
     static PRE_UPGRADE_INSTRUCTIONS_CONSUMED: RefCell<u64> = const { RefCell::new(0) };
     static POST_UPGRADE_INSTRUCTIONS_CONSUMED: RefCell<u64> = const { RefCell::new(0) };
+    static HOLDER_SNAPSHOT_TIMER: RefCell<Option<TimerId>> = const { RefCell::new(None) };
 }
 
 declare_log_buffer!(name = LOG, capacity = 1000);
@@ -115,9 +128,97 @@ fn init(args: LedgerArgument) {
             panic!("Cannot initialize the canister with an Upgrade argument. Please provide an Init argument.");
         }
     }
+    rotate_holder_cursor_secret();
+    reset_holder_store_state();
+    holder_snapshots::reset_holder_snapshots_state();
+    schedule_holder_snapshots();
     ic_cdk::api::set_certified_data(&Access::with_ledger(Ledger::root_hash));
 }
 
+/// Recomputes the holder store accounting, applying the persisted
+/// configuration.
+fn reset_holder_store_state() {
+    let config = Access::with_ledger(|ledger| ledger.holder_store_config().clone());
+    holder_list::reset_holder_store_state(config);
+}
+
+/// (Re)starts the holder snapshot timer according to the persisted
+/// configuration. Timers do not survive upgrades, so this must be called on
+/// `init`, `post_upgrade` and whenever the configuration changes.
+fn schedule_holder_snapshots() {
+    if let Some(timer_id) = HOLDER_SNAPSHOT_TIMER.with_borrow_mut(Option::take) {
+        ic_cdk_timers::clear_timer(timer_id);
+    }
+    let Some(config) = Access::with_ledger(|ledger| ledger.holder_snapshot_config().cloned())
+    else {
+        return;
+    };
+    let timer_id = ic_cdk_timers::set_timer_interval(
+        Duration::from_secs(config.interval_seconds),
+        move || take_holder_snapshot(config.retention),
+    );
+    HOLDER_SNAPSHOT_TIMER.with_borrow_mut(|timer| *timer = Some(timer_id));
+}
+
+fn take_holder_snapshot(retention: u32) {
+    let block = Access::with_ledger(|ledger| ledger.blockchain().chain_length());
+    if let Err(err) = holder_snapshots::take_holder_snapshot(retention, ic_cdk::api::time(), block)
+    {
+        ic_cdk::println!("[ledger] skipped holder snapshot: {:?}", err);
+    }
+}
+
+/// Re-schedules the holder tasks interrupted by the upgrade, which resume
+/// from their persisted checkpoints.
+fn resume_holder_tasks() {
+    for task in holder_tasks::in_progress_holder_tasks() {
+        ic_cdk::println!("[ledger] resuming holder task {:?}", task);
+        match task.kind {
+            HolderTaskKind::Backfill => schedule_holder_backfill_step(),
+        }
+    }
+}
+
+fn schedule_holder_backfill_step() {
+    ic_cdk_timers::set_timer(Duration::ZERO, run_holder_backfill_step);
+}
+
+fn run_holder_backfill_step() {
+    let more = Access::with_ledger(|ledger| {
+        holder_tasks::run_holder_backfill_step(&ledger.balances().store, |tokens| tokens.to_u64())
+    });
+    if more {
+        schedule_holder_backfill_step();
+    }
+}
+
+/// Invalidates all holder cursors issued before and schedules seeding a fresh
+/// cursor secret from `raw_rand`. Cursors are rejected until then: a secret
+/// derived from public inputs (time, canister version or ID) could be used to
+/// forge cursors.
+fn rotate_holder_cursor_secret() {
+    holder_list::clear_cursor_secret();
+    schedule_holder_cursor_secret_seeding(Duration::ZERO);
+}
+
+fn schedule_holder_cursor_secret_seeding(delay: Duration) {
+    ic_cdk_timers::set_timer(delay, || ic_cdk::spawn(seed_holder_cursor_secret()));
+}
+
+async fn seed_holder_cursor_secret() {
+    match ic_cdk::api::management_canister::main::raw_rand().await {
+        Ok((seed,)) => holder_list::set_cursor_secret(&seed),
+        Err((code, message)) => {
+            ic_cdk::println!(
+                "[ledger] failed to seed the holder cursor secret: {:?} {}",
+                code,
+                message
+            );
+            schedule_holder_cursor_secret_seeding(HOLDER_CURSOR_SECRET_RETRY_DELAY);
+        }
+    }
+}
+
 fn init_state(init_args: InitArgs) {
     let now = TimeStamp::from_nanos_since_unix_epoch(ic_cdk::api::time());
     LEDGER.with(|cell| {
@@ -231,6 +332,12 @@ fn post_upgrade(args: Option<LedgerArgument>) {
         }
     }
 
+    rotate_holder_cursor_secret();
+    reset_holder_store_state();
+    holder_snapshots::reset_holder_snapshots_state();
+    schedule_holder_snapshots();
+    resume_holder_tasks();
+
     PRE_UPGRADE_INSTRUCTIONS_CONSUMED.with(|n| *n.borrow_mut() = pre_upgrade_instructions_consumed);
 
     let end = ic_cdk::api::instruction_counter();
@@ -326,6 +433,61 @@ fn encode_metrics(w: &mut ic_metrics_encoder::MetricsEncoder<Vec<u8>>) -> std::i
             ledger.balances().store.len() as f64,
             "Total number of accounts in the balance store.",
         )?;
+        let holder_store_stats = holder_list::get_holder_store_stats();
+        w.encode_gauge(
+            "ledger_holder_store_entries",
+            holder_store_stats.entries as f64,
+            "Total number of accounts in the holder store.",
+        )?;
+        w.encode_gauge(
+            "ledger_holder_store_bytes",
+            holder_store_stats.store_bytes as f64,
+            "Bytes used by holder store entries.",
+        )?;
+        w.encode_gauge(
+            "ledger_holder_store_index_bytes",
+            holder_store_stats.zero_balance_index_bytes as f64,
+            "Bytes used by the zero-balance holder index.",
+        )?;
+        w.encode_gauge(
+            "ledger_holder_store_amount_index_bytes",
+            holder_store_stats.amount_index_bytes as f64,
+            "Bytes used by the balance-ordered holder index.",
+        )?;
+        if let Some(budget_bytes) = holder_store_stats.budget_bytes {
+            w.encode_gauge(
+                "ledger_holder_store_budget_bytes",
+                budget_bytes as f64,
+                "Maximum number of bytes of holder store entries and their balance-ordered index.",
+            )?;
+        }
+        w.encode_counter(
+            "holders_tracking_evicted_total",
+            holder_store_stats.evicted_total as f64,
+            "Number of zero-balance holders evicted from the holder store since the last upgrade.",
+        )?;
+        w.encode_counter(
+            "holders_tracking_skipped_total",
+            holder_store_stats.skipped_total as f64,
+            "Number of holder updates skipped due to the holder store budget since the last upgrade.",
+        )?;
+        let (snapshots, snapshot_bytes, snapshots_skipped) =
+            holder_snapshots::holder_snapshots_stats();
+        w.encode_gauge(
+            "ledger_holder_snapshots",
+            snapshots as f64,
+            "Number of stored holder snapshots.",
+        )?;
+        w.encode_gauge(
+            "ledger_holder_snapshots_bytes",
+            snapshot_bytes as f64,
+            "Bytes used by stored holder snapshots.",
+        )?;
+        w.encode_counter(
+            "ledger_holder_snapshots_skipped_total",
+            snapshots_skipped as f64,
+            "Number of holder snapshots skipped due to the holder store budget since the last upgrade.",
+        )?;
         w.encode_gauge(
             "ledger_most_recent_block_time_seconds",
             (ledger
@@ -892,6 +1054,14 @@ fn get_top(num: u32) -> HolderListResp {
     holder_list::get_holders(0, num, total_supply.to_u64())
 }
 
+/// Same as `get_top`, for canisters paying the `holder_range_cycles` fee.
+#[update]
+#[candid_method(update)]
+fn get_top_update(num: u32) -> HolderListResp {
+    accept_holder_api_fee(HolderApiTier::Range);
+    get_top(num)
+}
+
 #[query]
 #[candid_method(query)]
 fn get_top_100_holder() -> HolderListResp {
@@ -906,6 +1076,126 @@ fn get_total_holder() -> u64 {
     holder_list::count_holders()
 }
 
+#[query]
+#[candid_method(query)]
+fn get_holder_store_stats() -> HolderStoreStats {
+    holder_list::get_holder_store_stats()
+}
+
+#[update]
+#[candid_method(update)]
+fn set_holder_store_config(config: HolderStoreConfig) {
+    if !ic_cdk::api::is_controller(&ic_cdk::api::caller()) {
+        ic_cdk::trap("Only controllers can configure the holder store.");
+    }
+    Access::with_ledger_mut(|ledger| ledger.set_holder_store_config(config.clone()));
+    holder_list::set_holder_store_config(config);
+}
+
+#[update]
+#[candid_method(update)]
+fn set_holder_snapshot_config(config: Option<HolderSnapshotConfig>) {
+    if !ic_cdk::api::is_controller(&ic_cdk::api::caller()) {
+        ic_cdk::trap("Only controllers can configure holder snapshots.");
+    }
+    if let Some(Err(err)) = config.as_ref().map(HolderSnapshotConfig::validate) {
+        ic_cdk::trap(&format!("Invalid holder snapshot config: {}", err));
+    }
+    Access::with_ledger_mut(|ledger| ledger.set_holder_snapshot_config(config));
+    schedule_holder_snapshots();
+}
+
+#[query]
+#[candid_method(query)]
+fn get_holder_snapshots(offset: u32, limit: u32) -> HolderSnapshotList {
+    holder_snapshots::get_holder_snapshots(offset, limit)
+}
+
+/// Same as `get_holder_snapshots`, for canisters paying the
+/// `holder_snapshots_cycles` fee.
+#[update]
+#[candid_method(update)]
+fn get_holder_snapshots_update(offset: u32, limit: u32) -> HolderSnapshotList {
+    accept_holder_api_fee(HolderApiTier::Snapshots);
+    get_holder_snapshots(offset, limit)
+}
+
+/// Starts recomputing the holder store from the ledger balances, in batches
+/// of `HOLDER_BACKFILL_BATCH_SIZE` accounts.
+#[update]
+#[candid_method(update)]
+fn start_holder_backfill() -> Result<HolderTask, HolderTaskError> {
+    if !ic_cdk::api::is_controller(&ic_cdk::api::caller()) {
+        ic_cdk::trap("Only controllers can start a holder backfill.");
+    }
+    let task = holder_tasks::start_holder_task(HolderTaskKind::Backfill, ic_cdk::api::time())?;
+    schedule_holder_backfill_step();
+    Ok(task)
+}
+
+#[query]
+#[candid_method(query)]
+fn get_holder_tasks() -> Vec<HolderTask> {
+    holder_tasks::in_progress_holder_tasks()
+}
+
+#[query]
+#[candid_method(query)]
+fn get_holders_by_cursor(
+    cursor: Option<HolderCursor>,
+    limit: u32,
+) -> Result<HolderPage, HolderCursorError> {
+    let (total_supply, chain_length) = Access::with_ledger(|ledger| {
+        (
+            ledger.balances().total_supply(),
+            ledger.blockchain().chain_length(),
+        )
+    });
+    holder_list::get_holders_by_cursor(cursor, limit, total_supply.to_u64(), chain_length)
+}
+
+/// Same as `get_holders_by_cursor`, for canisters paying the
+/// `holder_range_cycles` fee.
+#[update]
+#[candid_method(update)]
+fn get_holders_by_cursor_update(
+    cursor: Option<HolderCursor>,
+    limit: u32,
+) -> Result<HolderPage, HolderCursorError> {
+    accept_holder_api_fee(HolderApiTier::Range);
+    get_holders_by_cursor(cursor, limit)
+}
+
+#[query]
+#[candid_method(query)]
+fn get_holder_api_fees() -> HolderApiFees {
+    Access::with_ledger(|ledger| ledger.holder_api_fees().clone())
+}
+
+#[update]
+#[candid_method(update)]
+fn set_holder_api_fees(fees: HolderApiFees) {
+    if !ic_cdk::api::is_controller(&ic_cdk::api::caller()) {
+        ic_cdk::trap("Only controllers can configure the holder API fees.");
+    }
+    Access::with_ledger_mut(|ledger| ledger.set_holder_api_fees(fees));
+}
+
+/// Accepts the fee of `tier` from the cycles attached to the call, or rejects
+/// the call if fewer cycles are attached. The cycles not accepted are refunded
+/// to the caller.
+fn accept_holder_api_fee(tier: HolderApiTier) {
+    let fees = Access::with_ledger(|ledger| ledger.holder_api_fees().clone());
+    let available = ic_cdk::api::call::msg_cycles_available();
+    match holder_api_fees::cycles_to_accept(&fees, tier, available) {
+        Ok(0) => {}
+        Ok(amount) => {
+            ic_cdk::api::call::msg_cycles_accept(amount);
+        }
+        Err(err) => ic_cdk::trap(&err.to_string()),
+    }
+}
+
 #[update]
 #[candid_method(update)]
 fn icrc21_canister_call_consent_message(
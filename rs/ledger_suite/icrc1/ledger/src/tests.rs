@@ -1,3 +1,12 @@
+use crate::holder_api_fees::{cycles_to_accept, HolderApiFeeError, HolderApiFees, HolderApiTier};
+use crate::holder_list::{HolderStoreConfig, HolderStoreError, HolderStoreState};
+use crate::holder_snapshots::{
+    holder_snapshots_page, HolderSnapshot, HolderSnapshotEntry, HolderSnapshotError,
+    HolderSnapshotsState,
+};
+use crate::holder_tasks::{
+    backfill_step, in_progress, start_task, HolderTask, HolderTaskError, HolderTaskKind,
+};
 use crate::{InitArgs, Ledger};
 use ic_base_types::PrincipalId;
 use ic_canister_log::Sink;
@@ -7,6 +16,7 @@ use ic_ledger_canister_core::ledger::{LedgerContext, LedgerTransaction, TxApplyE
 use ic_ledger_core::approvals::Allowance;
 use ic_ledger_core::timestamp::TimeStamp;
 use ic_ledger_core::Tokens;
+use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap, Storable};
 use icrc_ledger_types::icrc::generic_metadata_value::MetadataValue as Value;
 use icrc_ledger_types::icrc1::account::Account;
 
@@ -16,6 +26,7 @@ use ic_ledger_suite_state_machine_tests::{
     TEXT_META_VALUE, TOKEN_NAME, TOKEN_SYMBOL,
 };
 
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 #[derive(Clone)]
@@ -615,3 +626,450 @@ fn test_approval_burn_from() {
     assert_eq!(ctx.balances().account_balance(&spender), Tokens::ZERO);
     assert_eq!(ctx.balances().total_supply().get_e8s(), 90_000);
 }
+
+fn holder_entry_bytes(account: &Account) -> u64 {
+    account.to_bytes().len() as u64 + 8
+}
+
+/// The store entry plus the balance-ordered index entry, both charged to the budget.
+fn holder_bytes(account: &Account) -> u64 {
+    2 * holder_entry_bytes(account)
+}
+
+fn new_holder_store() -> StableBTreeMap<Account, u64, DefaultMemoryImpl> {
+    StableBTreeMap::init(DefaultMemoryImpl::default())
+}
+
+#[test]
+fn test_holder_store_skips_new_holders_beyond_budget() {
+    let entry_bytes = holder_entry_bytes(&test_account_id(1));
+    let holder_bytes = holder_bytes(&test_account_id(1));
+    let mut store = new_holder_store();
+    let mut state = HolderStoreState::new(
+        &store,
+        HolderStoreConfig {
+            budget_bytes: Some(3 * holder_bytes),
+            evict_zero_balance_holders: false,
+        },
+    );
+
+    for n in 1..=3 {
+        state.upsert(&mut store, test_account_id(n), n).unwrap();
+    }
+    assert_eq!(
+        state.upsert(&mut store, test_account_id(4), 4),
+        Err(HolderStoreError::BudgetExceeded {
+            budget_bytes: 3 * holder_bytes,
+            used_bytes: 3 * holder_bytes,
+            holder_bytes,
+        })
+    );
+    assert!(!store.contains_key(&test_account_id(4)));
+
+    // Updating tracked holders, even to a zero balance, is always possible.
+    state.upsert(&mut store, test_account_id(1), 0).unwrap();
+    state.upsert(&mut store, test_account_id(2), 20).unwrap();
+    assert_eq!(store.get(&test_account_id(2)), Some(20));
+
+    // Zero-balance holders are only evicted if enabled.
+    assert!(state.upsert(&mut store, test_account_id(4), 4).is_err());
+
+    let stats = state.stats(store.len());
+    assert_eq!(stats.entries, 3);
+    assert_eq!(stats.store_bytes, 3 * entry_bytes);
+    assert_eq!(stats.amount_index_entries, 3);
+    assert_eq!(stats.amount_index_bytes, 3 * entry_bytes);
+    assert_eq!(stats.evicted_total, 0);
+    assert_eq!(stats.skipped_total, 2);
+}
+
+#[test]
+fn test_holder_store_evicts_zero_balance_holders() {
+    let entry_bytes = holder_entry_bytes(&test_account_id(1));
+    let mut store = new_holder_store();
+    let mut state = HolderStoreState::new(
+        &store,
+        HolderStoreConfig {
+            budget_bytes: Some(3 * holder_bytes(&test_account_id(1))),
+            evict_zero_balance_holders: true,
+        },
+    );
+
+    for n in 1..=3 {
+        state.upsert(&mut store, test_account_id(n), n).unwrap();
+    }
+    state.upsert(&mut store, test_account_id(2), 0).unwrap();
+    assert_eq!(state.stats(store.len()).zero_balance_index_entries, 1);
+
+    state.upsert(&mut store, test_account_id(4), 4).unwrap();
+    assert!(!store.contains_key(&test_account_id(2)));
+    assert_eq!(store.get(&test_account_id(4)), Some(4));
+
+    // No zero-balance holders left to evict.
+    assert!(state.upsert(&mut store, test_account_id(5), 5).is_err());
+
+    let stats = state.stats(store.len());
+    assert_eq!(stats.entries, 3);
+    assert_eq!(stats.store_bytes, 3 * entry_bytes);
+    assert_eq!(stats.amount_index_entries, 3);
+    assert_eq!(stats.amount_index_bytes, 3 * entry_bytes);
+    assert_eq!(stats.zero_balance_index_entries, 0);
+    assert_eq!(stats.zero_balance_index_bytes, 0);
+    assert_eq!(stats.evicted_total, 1);
+    assert_eq!(stats.skipped_total, 1);
+}
+
+#[test]
+fn test_holder_store_stats_match_recomputation() {
+    let mut store = new_holder_store();
+    let mut state = HolderStoreState::new(&store, HolderStoreConfig::default());
+
+    for n in 1..=20 {
+        state.upsert(&mut store, test_account_id(n), n % 3).unwrap();
+    }
+    for n in (1..=20).step_by(4) {
+        state.upsert(&mut store, test_account_id(n), 0).unwrap();
+    }
+    for n in (2..=20).step_by(5) {
+        state.upsert(&mut store, test_account_id(n), 7).unwrap();
+    }
+
+    let recomputed = HolderStoreState::new(&store, HolderStoreConfig::default());
+    assert_eq!(state.stats(store.len()), recomputed.stats(store.len()));
+    assert_eq!(
+        state.stats(store.len()).store_bytes,
+        store
+            .iter()
+            .map(|(account, _)| holder_entry_bytes(&account))
+            .sum::<u64>()
+    );
+    assert_eq!(
+        state.stats(store.len()).amount_index_bytes,
+        state.stats(store.len()).store_bytes
+    );
+}
+
+#[test]
+fn test_holder_store_resumes_after_raising_budget() {
+    let holder_bytes = holder_bytes(&test_account_id(1));
+    let mut store = new_holder_store();
+    let mut state = HolderStoreState::new(
+        &store,
+        HolderStoreConfig {
+            budget_bytes: Some(holder_bytes),
+            evict_zero_balance_holders: false,
+        },
+    );
+
+    state.upsert(&mut store, test_account_id(1), 1).unwrap();
+    assert!(state.upsert(&mut store, test_account_id(2), 2).is_err());
+
+    state.set_config(HolderStoreConfig {
+        budget_bytes: Some(2 * holder_bytes),
+        evict_zero_balance_holders: false,
+    });
+    state.upsert(&mut store, test_account_id(2), 2).unwrap();
+    assert_eq!(store.len(), 2);
+
+    state.set_config(HolderStoreConfig::default());
+    for n in 3..=10 {
+        state.upsert(&mut store, test_account_id(n), n).unwrap();
+    }
+    let stats = state.stats(store.len());
+    assert_eq!(stats.entries, 10);
+    assert_eq!(stats.budget_bytes, None);
+    assert_eq!(stats.skipped_total, 1);
+}
+
+fn new_holder_snapshots() -> StableBTreeMap<u64, HolderSnapshot, DefaultMemoryImpl> {
+    StableBTreeMap::init(DefaultMemoryImpl::default())
+}
+
+#[test]
+fn test_holder_snapshots_record_top_holders() {
+    let mut store = new_holder_store();
+    let mut holders = HolderStoreState::new(&store, HolderStoreConfig::default());
+    let mut snapshots = new_holder_snapshots();
+    let mut state = HolderSnapshotsState::new(&snapshots);
+
+    holders.upsert(&mut store, test_account_id(1), 10).unwrap();
+    holders.upsert(&mut store, test_account_id(2), 30).unwrap();
+    holders.upsert(&mut store, test_account_id(3), 0).unwrap();
+    state
+        .take_snapshot(&holders, &mut snapshots, 10, 1_000, 5)
+        .unwrap();
+
+    holders.upsert(&mut store, test_account_id(1), 50).unwrap();
+    holders.upsert(&mut store, test_account_id(2), 0).unwrap();
+    holders.upsert(&mut store, test_account_id(3), 20).unwrap();
+    state
+        .take_snapshot(&holders, &mut snapshots, 10, 2_000, 8)
+        .unwrap();
+
+    let entry = |n, amount| HolderSnapshotEntry {
+        account: test_account_id(n),
+        amount,
+    };
+    assert_eq!(
+        snapshots.iter().collect::<Vec<_>>(),
+        vec![
+            (
+                0,
+                HolderSnapshot {
+                    timestamp: 1_000,
+                    block: 5,
+                    total_holders: 2,
+                    top_holders: vec![entry(2, 30), entry(1, 10)],
+                }
+            ),
+            (
+                1,
+                HolderSnapshot {
+                    timestamp: 2_000,
+                    block: 8,
+                    total_holders: 2,
+                    top_holders: vec![entry(1, 50), entry(3, 20)],
+                }
+            ),
+        ]
+    );
+    assert_eq!(
+        state.snapshot_bytes(),
+        HolderSnapshotsState::new(&snapshots).snapshot_bytes()
+    );
+}
+
+#[test]
+fn test_holder_snapshots_evict_beyond_retention() {
+    let mut store = new_holder_store();
+    let mut holders = HolderStoreState::new(&store, HolderStoreConfig::default());
+    let mut snapshots = new_holder_snapshots();
+    let mut state = HolderSnapshotsState::new(&snapshots);
+
+    for n in 1..=5 {
+        holders.upsert(&mut store, test_account_id(n), n).unwrap();
+        state
+            .take_snapshot(&holders, &mut snapshots, 3, n * 1_000, n)
+            .unwrap();
+    }
+    assert_eq!(
+        snapshots.iter().map(|(index, _)| index).collect::<Vec<_>>(),
+        vec![2, 3, 4]
+    );
+    assert_eq!(
+        snapshots
+            .iter()
+            .map(|(_, snapshot)| snapshot.block)
+            .collect::<Vec<_>>(),
+        vec![3, 4, 5]
+    );
+
+    // Lowering the retention evicts several snapshots at once.
+    state
+        .take_snapshot(&holders, &mut snapshots, 1, 6_000, 6)
+        .unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots.get(&5).unwrap().block, 6);
+    assert_eq!(
+        state.snapshot_bytes(),
+        HolderSnapshotsState::new(&snapshots).snapshot_bytes()
+    );
+}
+
+fn page_blocks(
+    snapshots: &StableBTreeMap<u64, HolderSnapshot, DefaultMemoryImpl>,
+    offset: u32,
+    limit: u32,
+) -> Vec<u64> {
+    let page = holder_snapshots_page(snapshots, offset, limit);
+    assert_eq!(page.total, snapshots.len());
+    page.snapshots
+        .into_iter()
+        .map(|snapshot| snapshot.block)
+        .collect()
+}
+
+#[test]
+fn test_holder_snapshots_page_newest_first() {
+    let store = new_holder_store();
+    let holders = HolderStoreState::new(&store, HolderStoreConfig::default());
+    let mut snapshots = new_holder_snapshots();
+    let mut state = HolderSnapshotsState::new(&snapshots);
+
+    assert_eq!(page_blocks(&snapshots, 0, 10), Vec::<u64>::new());
+
+    for block in 1..=15 {
+        state
+            .take_snapshot(&holders, &mut snapshots, 12, block, block)
+            .unwrap();
+    }
+    assert_eq!(page_blocks(&snapshots, 0, 3), vec![15, 14, 13]);
+    assert_eq!(page_blocks(&snapshots, 3, 2), vec![12, 11]);
+    // Only 12 snapshots are retained.
+    assert_eq!(page_blocks(&snapshots, 10, 5), vec![5, 4]);
+    assert_eq!(page_blocks(&snapshots, 12, 5), Vec::<u64>::new());
+    // The page size is capped.
+    assert_eq!(page_blocks(&snapshots, 0, 100).len(), 10);
+}
+
+#[test]
+fn test_holder_snapshots_skipped_beyond_budget() {
+    let holder_bytes = holder_bytes(&test_account_id(1));
+    let mut store = new_holder_store();
+    let mut holders = HolderStoreState::new(
+        &store,
+        HolderStoreConfig {
+            budget_bytes: Some(2 * holder_bytes),
+            evict_zero_balance_holders: false,
+        },
+    );
+    let mut snapshots = new_holder_snapshots();
+    let mut state = HolderSnapshotsState::new(&snapshots);
+
+    holders.upsert(&mut store, test_account_id(1), 1).unwrap();
+    holders.upsert(&mut store, test_account_id(2), 2).unwrap();
+    let Err(HolderSnapshotError::BudgetExceeded {
+        budget_bytes,
+        used_bytes,
+        ..
+    }) = state.take_snapshot(&holders, &mut snapshots, 10, 1_000, 2)
+    else {
+        panic!("expected the snapshot to exceed the budget");
+    };
+    assert_eq!(budget_bytes, 2 * holder_bytes);
+    assert_eq!(used_bytes, 2 * holder_bytes);
+    assert!(snapshots.is_empty());
+    assert_eq!(state.snapshot_bytes(), 0);
+    assert_eq!(state.skipped_total(), 1);
+
+    holders.set_config(HolderStoreConfig::default());
+    state
+        .take_snapshot(&holders, &mut snapshots, 10, 2_000, 2)
+        .unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(state.skipped_total(), 1);
+}
+
+fn new_holder_tasks() -> StableBTreeMap<u8, HolderTask, DefaultMemoryImpl> {
+    StableBTreeMap::init(DefaultMemoryImpl::default())
+}
+
+#[test]
+fn test_holder_tasks_reject_conflicting_task() {
+    let mut tasks = new_holder_tasks();
+
+    let task = start_task(&mut tasks, HolderTaskKind::Backfill, 1_000).unwrap();
+    assert_eq!(
+        task,
+        HolderTask {
+            kind: HolderTaskKind::Backfill,
+            started_at: 1_000,
+            checkpoint: None,
+            processed: 0,
+        }
+    );
+    assert_eq!(
+        start_task(&mut tasks, HolderTaskKind::Backfill, 2_000),
+        Err(HolderTaskError::AlreadyRunning { task: task.clone() })
+    );
+    assert_eq!(in_progress(&tasks), vec![task]);
+}
+
+#[test]
+fn test_holder_backfill_resumes_from_checkpoint() {
+    let balances: BTreeMap<Account, u64> = (1..=7).map(|n| (test_account_id(n), n)).collect();
+    let mut sorted_accounts: Vec<Account> = balances.keys().copied().collect();
+    sorted_accounts.sort();
+    let mut store = new_holder_store();
+    let mut holders = HolderStoreState::new(&store, HolderStoreConfig::default());
+    let mut tasks = new_holder_tasks();
+
+    // The holder store misses some holders, has stale and wrong balances.
+    holders.upsert(&mut store, test_account_id(1), 100).unwrap();
+    holders.upsert(&mut store, test_account_id(8), 8).unwrap();
+
+    // Without a backfill in progress, a step does nothing.
+    assert!(!backfill_step(
+        &mut tasks,
+        &mut holders,
+        &mut store,
+        &balances,
+        |amount| *amount,
+        3
+    ));
+    assert_eq!(store.len(), 2);
+
+    start_task(&mut tasks, HolderTaskKind::Backfill, 1_000).unwrap();
+    for (step, checkpoint) in [(1, 2), (2, 5)] {
+        assert!(backfill_step(
+            &mut tasks,
+            &mut holders,
+            &mut store,
+            &balances,
+            |amount| *amount,
+            3
+        ));
+        let task = in_progress(&tasks).pop().unwrap();
+        assert_eq!(task.checkpoint, Some(sorted_accounts[checkpoint]));
+        assert_eq!(task.processed, 3 * step);
+        assert_eq!(task.started_at, 1_000);
+    }
+    assert!(!backfill_step(
+        &mut tasks,
+        &mut holders,
+        &mut store,
+        &balances,
+        |amount| *amount,
+        3
+    ));
+    assert!(in_progress(&tasks).is_empty());
+
+    let mut expected: Vec<(Account, u64)> = balances.into_iter().collect();
+    expected.push((test_account_id(8), 0));
+    expected.sort();
+    assert_eq!(store.iter().collect::<Vec<_>>(), expected);
+    assert_eq!(holders.non_zero_holders(), 7);
+}
+
+#[test]
+fn test_holder_api_fees_accept_only_the_fee_of_the_tier() {
+    let fees = HolderApiFees {
+        holder_range_cycles: 1_000,
+        holder_snapshots_cycles: 5_000,
+    };
+
+    assert_eq!(
+        cycles_to_accept(&fees, HolderApiTier::Range, 1_000),
+        Ok(1_000)
+    );
+    assert_eq!(
+        cycles_to_accept(&fees, HolderApiTier::Range, 9_000),
+        Ok(1_000)
+    );
+    assert_eq!(
+        cycles_to_accept(&fees, HolderApiTier::Snapshots, 9_000),
+        Ok(5_000)
+    );
+    assert_eq!(
+        cycles_to_accept(&HolderApiFees::default(), HolderApiTier::Snapshots, 9_000),
+        Ok(0)
+    );
+}
+
+#[test]
+fn test_holder_api_fees_reject_insufficient_cycles_with_required_amount() {
+    let fees = HolderApiFees {
+        holder_range_cycles: 1_000,
+        holder_snapshots_cycles: 5_000,
+    };
+
+    let err = cycles_to_accept(&fees, HolderApiTier::Snapshots, 4_999).unwrap_err();
+    assert_eq!(
+        err,
+        HolderApiFeeError::InsufficientCycles {
+            required: 5_000,
+            available: 4_999,
+        }
+    );
+    assert!(err.to_string().contains("requires 5000 cycles"));
+}
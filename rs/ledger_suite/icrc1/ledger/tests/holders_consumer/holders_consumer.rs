@@ -0,0 +1,30 @@
+//! A canister paging through the holders of an ICRC-1 ledger from within a
+//! composite query, the way on-chain consumers of `get_holders_by_cursor` do.
+use candid::Principal;
+use ic_cdk::api::call::call;
+use ic_cdk_macros::query;
+use ic_icrc1_ledger::holder_list::{HolderCursor, HolderCursorError, HolderPage};
+use icrc_ledger_types::icrc1::account::Account;
+
+/// Returns all holders of `ledger`, fetched `page_size` at a time.
+#[query(composite = true)]
+async fn list_holders(ledger: Principal, page_size: u32) -> Result<Vec<Account>, String> {
+    let mut holders = vec![];
+    let mut cursor: Option<HolderCursor> = None;
+    loop {
+        let (result,): (Result<HolderPage, HolderCursorError>,) =
+            call(ledger, "get_holders_by_cursor", (cursor, page_size))
+                .await
+                .map_err(|(code, message)| {
+                    format!("get_holders_by_cursor rejected: {:?} {}", code, message)
+                })?;
+        let page = result.map_err(|err| format!("get_holders_by_cursor failed: {:?}", err))?;
+        holders.extend(page.data.into_iter().map(|holder| holder.account));
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => return Ok(holders),
+        }
+    }
+}
+
+fn main() {}
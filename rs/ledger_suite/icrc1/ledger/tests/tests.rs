@@ -1,7 +1,14 @@
+use assert_matches::assert_matches;
 use candid::{CandidType, Decode, Encode, Nat};
 use ic_agent::identity::Identity;
 use ic_base_types::{CanisterId, PrincipalId};
 use ic_icrc1::{Block, Operation, Transaction};
+use ic_icrc1_ledger::holder_api_fees::HolderApiFees;
+use ic_icrc1_ledger::holder_list::{HolderCursor, HolderCursorError, HolderListResp, HolderPage};
+use ic_icrc1_ledger::holder_snapshots::{HolderSnapshotConfig, HolderSnapshotList};
+use ic_icrc1_ledger::holder_tasks::{
+    HolderTask, HolderTaskError, HolderTaskKind, HOLDER_BACKFILL_BATCH_SIZE,
+};
 use ic_icrc1_ledger::{
     ChangeFeeCollector, FeatureFlags, InitArgs, InitArgsBuilder as LedgerInitArgsBuilder,
     LedgerArgument,
@@ -18,7 +25,9 @@ use ic_ledger_suite_state_machine_tests::{
     NAT_META_VALUE, NUM_BLOCKS_TO_ARCHIVE, TEXT_META_KEY, TEXT_META_VALUE, TOKEN_NAME,
     TOKEN_SYMBOL,
 };
-use ic_state_machine_tests::StateMachine;
+use ic_state_machine_tests::{StateMachine, WasmResult};
+use ic_types::Cycles;
+use ic_universal_canister::{call_args, wasm, UNIVERSAL_CANISTER_WASM};
 use icrc_ledger_types::icrc::generic_metadata_value::MetadataValue;
 use icrc_ledger_types::icrc::generic_value::Value;
 use icrc_ledger_types::icrc1::account::Account;
@@ -34,6 +43,7 @@ use num_traits::ToPrimitive;
 use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[cfg(not(feature = "u256-tokens"))]
 pub type Tokens = ic_icrc1_tokens_u64::U64;
@@ -2019,3 +2029,516 @@ mod incompatible_token_type_upgrade {
         );
     }
 }
+
+fn install_ledger_with_holders(env: &StateMachine, num_holders: u64) -> CanisterId {
+    let mut builder = LedgerInitArgsBuilder::with_symbol_and_name(TOKEN_SYMBOL, TOKEN_NAME)
+        .with_minting_account(MINTER)
+        .with_transfer_fee(FEE);
+    for n in 1..=num_holders {
+        builder = builder.with_initial_balance(account(n), 1_000_000 * n);
+    }
+    let init_args = Encode!(&LedgerArgument::Init(builder.build())).unwrap();
+    let ledger_id = env
+        .install_canister(ledger_wasm(), init_args, None)
+        .unwrap();
+    wait_for_holder_cursor_secret(env, ledger_id);
+    ledger_id
+}
+
+/// Executes rounds until the ledger has seeded its holder cursor secret, which
+/// it does asynchronously after `init` and `post_upgrade`.
+fn wait_for_holder_cursor_secret(env: &StateMachine, ledger_id: CanisterId) {
+    for _ in 0..10 {
+        match get_holders_by_cursor(env, ledger_id, None, 0) {
+            Err(HolderCursorError::TemporarilyUnavailable { .. }) => env.tick(),
+            _ => return,
+        }
+    }
+    panic!("The ledger did not seed its holder cursor secret");
+}
+
+fn get_holders_by_cursor(
+    env: &StateMachine,
+    ledger_id: CanisterId,
+    cursor: Option<HolderCursor>,
+    limit: u32,
+) -> Result<HolderPage, HolderCursorError> {
+    let args = Encode!(&cursor, &limit).unwrap();
+    let res = env
+        .query(ledger_id, "get_holders_by_cursor", args)
+        .expect("Unable to perform get_holders_by_cursor")
+        .bytes();
+    Decode!(&res, Result<HolderPage, HolderCursorError>).unwrap()
+}
+
+#[test]
+fn test_get_holders_by_cursor_pages_through_all_holders() {
+    let env = StateMachine::new();
+    let ledger_id = install_ledger_with_holders(&env, 7);
+
+    let mut holders = vec![];
+    let mut cursor = None;
+    let mut as_of_block = None;
+    loop {
+        let page = get_holders_by_cursor(&env, ledger_id, cursor, 3).unwrap();
+        assert!(page.data.len() <= 3);
+        // All pages report the chain length when pagination started.
+        assert_eq!(
+            *as_of_block.get_or_insert(page.as_of_block),
+            page.as_of_block
+        );
+        holders.extend(page.data.into_iter().map(|holder| holder.account));
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    let mut expected: Vec<_> = (1..=7).map(account).collect();
+    expected.sort();
+    assert_eq!(expected, holders);
+}
+
+#[test]
+fn test_get_holders_by_cursor_rejects_tampered_cursor() {
+    let env = StateMachine::new();
+    let ledger_id = install_ledger_with_holders(&env, 5);
+
+    let page = get_holders_by_cursor(&env, ledger_id, None, 2).unwrap();
+    let cursor = page.next_cursor.unwrap();
+    assert!(get_holders_by_cursor(&env, ledger_id, Some(cursor.clone()), 2).is_ok());
+
+    let mut tampered = cursor.clone();
+    tampered.last_key = account(1_000);
+    assert_eq!(
+        get_holders_by_cursor(&env, ledger_id, Some(tampered), 2).unwrap_err(),
+        HolderCursorError::InvalidCursor
+    );
+
+    let mut tampered = cursor.clone();
+    tampered.as_of_block += 1;
+    assert_eq!(
+        get_holders_by_cursor(&env, ledger_id, Some(tampered), 2).unwrap_err(),
+        HolderCursorError::InvalidCursor
+    );
+
+    let mut tampered = cursor;
+    tampered.mac[0] ^= 1;
+    assert_eq!(
+        get_holders_by_cursor(&env, ledger_id, Some(tampered), 2).unwrap_err(),
+        HolderCursorError::InvalidCursor
+    );
+}
+
+#[test]
+fn test_get_holders_by_cursor_invalidated_by_upgrade() {
+    let env = StateMachine::new();
+    let ledger_id = install_ledger_with_holders(&env, 5);
+
+    let cursor = get_holders_by_cursor(&env, ledger_id, None, 2)
+        .unwrap()
+        .next_cursor
+        .unwrap();
+
+    let upgrade_args = Encode!(&LedgerArgument::Upgrade(None)).unwrap();
+    env.upgrade_canister(ledger_id, ledger_wasm(), upgrade_args)
+        .unwrap();
+    wait_for_holder_cursor_secret(&env, ledger_id);
+
+    assert_matches!(
+        get_holders_by_cursor(&env, ledger_id, Some(cursor), 2),
+        Err(HolderCursorError::CursorExpired { reason }) if reason.contains("upgrade")
+    );
+    // Pagination can be restarted after the upgrade.
+    assert_eq!(
+        get_holders_by_cursor(&env, ledger_id, None, 10)
+            .unwrap()
+            .data
+            .len(),
+        5
+    );
+}
+
+#[test]
+fn test_get_holders_by_cursor_unavailable_until_secret_is_seeded() {
+    let env = StateMachine::new();
+    let init_args = Encode!(&LedgerArgument::Init(
+        LedgerInitArgsBuilder::with_symbol_and_name(TOKEN_SYMBOL, TOKEN_NAME)
+            .with_minting_account(MINTER)
+            .with_initial_balance(account(1), 1_000_000_u64)
+            .build()
+    ))
+    .unwrap();
+    let ledger_id = env
+        .install_canister(ledger_wasm(), init_args, None)
+        .unwrap();
+
+    // The secret is seeded from `raw_rand` in a later round, so neither pages nor
+    // cursors are served until then.
+    assert_matches!(
+        get_holders_by_cursor(&env, ledger_id, None, 10),
+        Err(HolderCursorError::TemporarilyUnavailable { .. })
+    );
+
+    wait_for_holder_cursor_secret(&env, ledger_id);
+    assert_eq!(
+        get_holders_by_cursor(&env, ledger_id, None, 10)
+            .unwrap()
+            .data
+            .len(),
+        1
+    );
+}
+
+fn holders_consumer_wasm() -> Vec<u8> {
+    std::fs::read(std::env::var("HOLDERS_CONSUMER_WASM_PATH").unwrap()).unwrap()
+}
+
+#[test]
+fn test_get_holders_by_cursor_from_composite_query() {
+    let env = StateMachine::new();
+    let ledger_id = install_ledger_with_holders(&env, 7);
+    let consumer_id = env
+        .install_canister(holders_consumer_wasm(), vec![], None)
+        .unwrap();
+
+    let res = env
+        .query(
+            consumer_id,
+            "list_holders",
+            Encode!(&PrincipalId::from(ledger_id).0, &2_u32).unwrap(),
+        )
+        .expect("Unable to perform list_holders")
+        .bytes();
+    let holders = Decode!(&res, Result<Vec<Account>, String>)
+        .unwrap()
+        .unwrap();
+
+    let mut expected: Vec<_> = (1..=7).map(account).collect();
+    expected.sort();
+    assert_eq!(expected, holders);
+}
+
+fn start_holder_backfill(
+    env: &StateMachine,
+    ledger_id: CanisterId,
+) -> Result<HolderTask, HolderTaskError> {
+    let res = env
+        .execute_ingress(ledger_id, "start_holder_backfill", Encode!().unwrap())
+        .expect("Unable to perform start_holder_backfill")
+        .bytes();
+    Decode!(&res, Result<HolderTask, HolderTaskError>).unwrap()
+}
+
+fn get_holder_tasks(env: &StateMachine, ledger_id: CanisterId) -> Vec<HolderTask> {
+    let res = env
+        .query(ledger_id, "get_holder_tasks", Encode!().unwrap())
+        .expect("Unable to perform get_holder_tasks")
+        .bytes();
+    Decode!(&res, Vec<HolderTask>).unwrap()
+}
+
+fn get_top(env: &StateMachine, ledger_id: CanisterId, num: u32) -> HolderListResp {
+    let res = env
+        .query(ledger_id, "get_top", Encode!(&num).unwrap())
+        .expect("Unable to perform get_top")
+        .bytes();
+    Decode!(&res, HolderListResp).unwrap()
+}
+
+/// Asserts that all holder queries report exactly `expected` as in progress.
+fn assert_holder_tasks_reported(
+    env: &StateMachine,
+    ledger_id: CanisterId,
+    expected: &[HolderTask],
+) {
+    assert_eq!(get_holder_tasks(env, ledger_id), expected);
+    assert_eq!(
+        get_top(env, ledger_id, 1).metadata.in_progress_tasks,
+        expected
+    );
+    assert_eq!(
+        get_holders_by_cursor(env, ledger_id, None, 1)
+            .unwrap()
+            .in_progress_tasks,
+        expected
+    );
+}
+
+#[test]
+fn test_holder_backfill_resumes_after_upgrade() {
+    const NUM_HOLDERS: u64 = 450;
+    let batch_size = HOLDER_BACKFILL_BATCH_SIZE as u64;
+    let env = StateMachine::new();
+    let ledger_id = install_ledger_with_holders(&env, NUM_HOLDERS);
+    let mut sorted_accounts: Vec<_> = (1..=NUM_HOLDERS).map(account).collect();
+    sorted_accounts.sort();
+    assert_holder_tasks_reported(&env, ledger_id, &[]);
+
+    let task = start_holder_backfill(&env, ledger_id).unwrap();
+    assert_eq!(task.kind, HolderTaskKind::Backfill);
+    assert_eq!(
+        start_holder_backfill(&env, ledger_id),
+        Err(HolderTaskError::AlreadyRunning {
+            task: get_holder_tasks(&env, ledger_id).pop().unwrap()
+        })
+    );
+
+    env.tick();
+    let before_upgrade = get_holder_tasks(&env, ledger_id).pop().unwrap();
+    assert!(before_upgrade.processed > 0 && before_upgrade.processed < NUM_HOLDERS);
+    assert_eq!(before_upgrade.started_at, task.started_at);
+    assert_holder_tasks_reported(&env, ledger_id, &[before_upgrade.clone()]);
+
+    let upgrade_args = Encode!(&LedgerArgument::Upgrade(None)).unwrap();
+    env.upgrade_canister(ledger_id, ledger_wasm(), upgrade_args)
+        .unwrap();
+    // The task survives the upgrade unchanged and still conflicts.
+    assert_holder_tasks_reported(&env, ledger_id, &[before_upgrade.clone()]);
+    assert_matches!(
+        start_holder_backfill(&env, ledger_id),
+        Err(HolderTaskError::AlreadyRunning { .. })
+    );
+
+    // Every step resumes right after the checkpoint and processes a full batch,
+    // so no account is processed twice.
+    let mut previous = before_upgrade;
+    for _ in 0..NUM_HOLDERS {
+        env.tick();
+        let Some(task) = get_holder_tasks(&env, ledger_id).pop() else {
+            break;
+        };
+        assert!(task.processed >= previous.processed);
+        assert_eq!(task.processed % batch_size, 0);
+        assert_eq!(
+            task.checkpoint,
+            Some(sorted_accounts[task.processed as usize - 1])
+        );
+        assert_holder_tasks_reported(&env, ledger_id, &[task.clone()]);
+        previous = task;
+    }
+    assert!(previous.processed > NUM_HOLDERS - batch_size);
+    assert_holder_tasks_reported(&env, ledger_id, &[]);
+
+    let top = get_top(&env, ledger_id, 1);
+    assert_eq!(top.metadata.total, NUM_HOLDERS);
+    assert_eq!(top.data[0].account, account(NUM_HOLDERS));
+    assert_eq!(top.data[0].amount, Nat::from(1_000_000 * NUM_HOLDERS));
+
+    // A new backfill can be started once the previous one completed.
+    assert_matches!(start_holder_backfill(&env, ledger_id), Ok(_));
+}
+
+fn get_holder_snapshots(env: &StateMachine, ledger_id: CanisterId) -> HolderSnapshotList {
+    let res = env
+        .query(
+            ledger_id,
+            "get_holder_snapshots",
+            Encode!(&0u32, &10u32).unwrap(),
+        )
+        .expect("Unable to perform get_holder_snapshots")
+        .bytes();
+    Decode!(&res, HolderSnapshotList).unwrap()
+}
+
+#[test]
+fn test_holder_snapshots_continue_after_upgrade() {
+    let env = StateMachine::new();
+    let ledger_id = install_ledger_with_holders(&env, 5);
+    let config = Some(HolderSnapshotConfig {
+        interval_seconds: 60,
+        retention: 10,
+    });
+    env.execute_ingress(
+        ledger_id,
+        "set_holder_snapshot_config",
+        Encode!(&config).unwrap(),
+    )
+    .expect("Unable to perform set_holder_snapshot_config");
+
+    env.advance_time(Duration::from_secs(60));
+    env.tick();
+    assert_eq!(get_holder_snapshots(&env, ledger_id).total, 1);
+
+    // Snapshots are taken within a single message, so an upgrade never
+    // interrupts one and snapshots are never reported as in progress.
+    let upgrade_args = Encode!(&LedgerArgument::Upgrade(None)).unwrap();
+    env.upgrade_canister(ledger_id, ledger_wasm(), upgrade_args)
+        .unwrap();
+    assert_holder_tasks_reported(&env, ledger_id, &[]);
+    assert_eq!(get_holder_snapshots(&env, ledger_id).total, 1);
+
+    env.advance_time(Duration::from_secs(60));
+    env.tick();
+    let snapshots = get_holder_snapshots(&env, ledger_id);
+    assert_eq!(snapshots.total, 2);
+    assert_eq!(snapshots.snapshots[0].total_holders, 5);
+}
+
+fn set_holder_api_fees(env: &StateMachine, ledger_id: CanisterId, fees: &HolderApiFees) {
+    env.execute_ingress(ledger_id, "set_holder_api_fees", Encode!(fees).unwrap())
+        .expect("Unable to perform set_holder_api_fees");
+}
+
+fn get_holder_api_fees(env: &StateMachine, ledger_id: CanisterId) -> HolderApiFees {
+    let res = env
+        .query(ledger_id, "get_holder_api_fees", Encode!().unwrap())
+        .expect("Unable to perform get_holder_api_fees")
+        .bytes();
+    Decode!(&res, HolderApiFees).unwrap()
+}
+
+/// Calls `method` of the ledger from the universal canister `caller`,
+/// attaching `cycles`.
+fn call_with_cycles(
+    env: &StateMachine,
+    caller: CanisterId,
+    ledger_id: CanisterId,
+    method: &str,
+    args: Vec<u8>,
+    cycles: u128,
+) -> Result<Vec<u8>, String> {
+    let payload = wasm()
+        .call_with_cycles(
+            ledger_id.get(),
+            method,
+            call_args()
+                .other_side(args)
+                .on_reject(wasm().reject_message().reject()),
+            Cycles::new(cycles),
+        )
+        .build();
+    match env.execute_ingress(caller, "update", payload).unwrap() {
+        WasmResult::Reply(bytes) => Ok(bytes),
+        WasmResult::Reject(reason) => Err(reason),
+    }
+}
+
+fn install_universal_canister(env: &StateMachine) -> CanisterId {
+    env.install_canister_with_cycles(
+        UNIVERSAL_CANISTER_WASM.clone(),
+        vec![],
+        None,
+        Cycles::new(100_000_000_000_000),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_holder_api_fees_are_charged_to_canister_callers() {
+    let env = StateMachine::new();
+    let ledger_id = install_ledger_with_holders(&env, 5);
+    let caller = install_universal_canister(&env);
+    assert_eq!(
+        get_holder_api_fees(&env, ledger_id),
+        HolderApiFees::default()
+    );
+    let fees = HolderApiFees {
+        holder_range_cycles: 1_000_000,
+        holder_snapshots_cycles: 2_000_000,
+    };
+    set_holder_api_fees(&env, ledger_id, &fees);
+    assert_eq!(get_holder_api_fees(&env, ledger_id), fees);
+
+    // Sufficient cycles: exactly the fee is accepted.
+    let balance_before = env.cycle_balance(ledger_id);
+    let res = call_with_cycles(
+        &env,
+        caller,
+        ledger_id,
+        "get_top_update",
+        Encode!(&3u32).unwrap(),
+        1_000_000,
+    )
+    .unwrap();
+    assert_eq!(Decode!(&res, HolderListResp).unwrap().data.len(), 3);
+    assert_eq!(env.cycle_balance(ledger_id) - balance_before, 1_000_000);
+
+    // Excess cycles: the ledger only keeps the fee and refunds the rest.
+    let balance_before = env.cycle_balance(ledger_id);
+    let res = call_with_cycles(
+        &env,
+        caller,
+        ledger_id,
+        "get_holder_snapshots_update",
+        Encode!(&0u32, &10u32).unwrap(),
+        50_000_000,
+    )
+    .unwrap();
+    assert_eq!(Decode!(&res, HolderSnapshotList).unwrap().total, 0);
+    assert_eq!(env.cycle_balance(ledger_id) - balance_before, 2_000_000);
+
+    // Insufficient cycles: the call is rejected with the required amount and
+    // no cycles are accepted.
+    let balance_before = env.cycle_balance(ledger_id);
+    let err = call_with_cycles(
+        &env,
+        caller,
+        ledger_id,
+        "get_holders_by_cursor_update",
+        Encode!(&None::<HolderCursor>, &3u32).unwrap(),
+        999_999,
+    )
+    .unwrap_err();
+    assert!(err.contains("requires 1000000 cycles"), "{}", err);
+    assert!(env.cycle_balance(ledger_id) <= balance_before);
+
+    // The query variants remain free.
+    assert!(get_holders_by_cursor(&env, ledger_id, None, 3).is_ok());
+}
+
+#[test]
+fn test_holder_api_fee_updates_take_effect_without_upgrade() {
+    let env = StateMachine::new();
+    let ledger_id = install_ledger_with_holders(&env, 5);
+    let caller = install_universal_canister(&env);
+    let get_top_update = |cycles| {
+        call_with_cycles(
+            &env,
+            caller,
+            ledger_id,
+            "get_top_update",
+            Encode!(&3u32).unwrap(),
+            cycles,
+        )
+    };
+
+    // No fees are charged by default.
+    assert!(get_top_update(0).is_ok());
+
+    set_holder_api_fees(
+        &env,
+        ledger_id,
+        &HolderApiFees {
+            holder_range_cycles: 1_000_000,
+            holder_snapshots_cycles: 0,
+        },
+    );
+    assert!(get_top_update(0).is_err());
+    assert!(get_top_update(1_000_000).is_ok());
+
+    set_holder_api_fees(&env, ledger_id, &HolderApiFees::default());
+    assert!(get_top_update(0).is_ok());
+}
+
+#[test]
+fn test_only_controllers_can_set_holder_api_fees() {
+    let env = StateMachine::new();
+    let ledger_id = install_ledger_with_holders(&env, 1);
+    let fees = HolderApiFees {
+        holder_range_cycles: 1,
+        holder_snapshots_cycles: 1,
+    };
+    assert!(env
+        .execute_ingress_as(
+            PrincipalId::new_user_test_id(1),
+            ledger_id,
+            "set_holder_api_fees",
+            Encode!(&fees).unwrap(),
+        )
+        .is_err());
+    assert_eq!(
+        get_holder_api_fees(&env, ledger_id),
+        HolderApiFees::default()
+    );
+}
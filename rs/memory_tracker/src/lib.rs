@@ -79,6 +79,107 @@ pub enum AccessKind {
     Write,
 }
 
+/// Default upper bound on the number of faults recorded by a `PageAccessTrace`.
+pub const DEFAULT_PAGE_ACCESS_TRACE_LEN: usize = 1 << 16;
+
+/// A single page fault recorded by a `PageAccessTrace`. `M` identifies the
+/// memory (e.g. Wasm heap or stable memory) the fault occurred in.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct PageAccess<M> {
+    pub memory: M,
+    pub page: PageIndex,
+    /// `None` if the signal handler in use cannot tell reads from writes.
+    pub access_kind: Option<AccessKind>,
+}
+
+/// The ordered sequence of page faults handled during an execution, used to
+/// tune the prefetching heuristics of the signal handler.
+///
+/// At most `max_len` faults are recorded; any further faults only set the
+/// `truncated` flag.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PageAccessTrace<M> {
+    accesses: Vec<PageAccess<M>>,
+    max_len: usize,
+    truncated: bool,
+}
+
+impl<M: Copy + Eq> PageAccessTrace<M> {
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            accesses: Vec::new(),
+            max_len,
+            truncated: false,
+        }
+    }
+
+    pub fn record(&mut self, access: PageAccess<M>) {
+        if self.accesses.len() < self.max_len {
+            self.accesses.push(access);
+        } else {
+            self.truncated = true;
+        }
+    }
+
+    pub fn accesses(&self) -> &[PageAccess<M>] {
+        &self.accesses
+    }
+
+    /// Whether faults were dropped because the trace reached its maximum length.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns the fraction of faults (after the first one) that hit the same
+    /// memory as the previous fault, at most `window` pages after it.
+    ///
+    /// The signal handler maps up to `MAX_PAGES_TO_MAP` pages per fault, so a
+    /// sequential scan produces faults that are `1..=MAX_PAGES_TO_MAP` pages
+    /// apart; `window` should be chosen accordingly. Returns `None` for traces
+    /// with fewer than two faults.
+    pub fn sequentiality(&self, window: u64) -> Option<f64> {
+        if self.accesses.len() < 2 {
+            return None;
+        }
+        let sequential = self
+            .accesses
+            .windows(2)
+            .filter(|pair| {
+                let (prev, next) = (&pair[0], &pair[1]);
+                prev.memory == next.memory
+                    && next.page > prev.page
+                    && next.page.get() - prev.page.get() <= window
+            })
+            .count();
+        Some(sequential as f64 / (self.accesses.len() - 1) as f64)
+    }
+}
+
+impl<M: std::fmt::Display> PageAccessTrace<M> {
+    /// Writes the trace in a compact text format: one `<memory> <page> <R|W|?>`
+    /// line per fault, followed by a `truncated` line if faults were dropped.
+    pub fn write_to<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        for access in self.accesses.iter() {
+            let access_kind = match access.access_kind {
+                Some(AccessKind::Read) => "R",
+                Some(AccessKind::Write) => "W",
+                None => "?",
+            };
+            writeln!(
+                writer,
+                "{} {} {}",
+                access.memory,
+                access.page.get(),
+                access_kind
+            )?;
+        }
+        if self.truncated {
+            writeln!(writer, "truncated")?;
+        }
+        Ok(())
+    }
+}
+
 /// Bitmap tracking which pages on the memory were accessed during an execution.
 pub struct PageBitmap {
     pages: BitVec,
@@ -12,8 +12,8 @@ use nix::sys::mman::{mmap, MapFlags, ProtFlags};
 use std::sync::Arc;
 
 use crate::{
-    new_signal_handler_available, AccessKind, DirtyPageTracking, PageBitmap, SigsegvMemoryTracker,
-    MAX_PAGES_TO_MAP,
+    new_signal_handler_available, AccessKind, DirtyPageTracking, PageAccess, PageAccessTrace,
+    PageBitmap, SigsegvMemoryTracker, MAX_PAGES_TO_MAP,
 };
 
 /// Sets up the SigsegvMemoryTracker to track accesses to a region of memory. Returns:
@@ -1052,3 +1052,52 @@ mod random_ops {
         }
     }
 }
+
+#[test]
+fn page_access_trace_is_truncated_at_max_len() {
+    let mut trace = PageAccessTrace::new(2);
+    for page in 0..3 {
+        trace.record(PageAccess {
+            memory: 0,
+            page: PageIndex::new(page),
+            access_kind: Some(AccessKind::Read),
+        });
+    }
+    assert_eq!(2, trace.accesses().len());
+    assert!(trace.is_truncated());
+
+    let mut out = vec![];
+    trace.write_to(&mut out).unwrap();
+    assert_eq!("0 0 R\n0 1 R\ntruncated\n", String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn page_access_trace_sequentiality() {
+    let trace_of = |accesses: &[(u8, u64)]| {
+        let mut trace = PageAccessTrace::new(100);
+        for (memory, page) in accesses {
+            trace.record(PageAccess {
+                memory: *memory,
+                page: PageIndex::new(*page),
+                access_kind: None,
+            });
+        }
+        trace
+    };
+
+    assert_eq!(None, trace_of(&[(0, 7)]).sequentiality(1));
+    // Forward within the window, in the same memory.
+    assert_eq!(
+        Some(1.0),
+        trace_of(&[(0, 0), (0, 1), (0, 3), (0, 7)]).sequentiality(4)
+    );
+    // Backward, too far and in a different memory.
+    assert_eq!(
+        Some(0.0),
+        trace_of(&[(0, 10), (0, 9), (0, 20), (1, 21)]).sequentiality(4)
+    );
+    assert_eq!(
+        Some(0.5),
+        trace_of(&[(0, 0), (0, 1), (0, 100)]).sequentiality(4)
+    );
+}
@@ -31,7 +31,8 @@ use ic_registry_provisional_whitelist::ProvisionalWhitelist;
 use ic_registry_subnet_features::{ChainKeyConfig, SubnetFeatures};
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::{
-    metadata_state::ApiBoundaryNodeEntry, NetworkTopology, ReplicatedState, SubnetTopology,
+    metadata_state::ApiBoundaryNodeEntry, MessageDropStats, NetworkTopology, ReplicatedState,
+    SubnetTopology,
 };
 use ic_types::{
     batch::{Batch, BatchSummary},
@@ -77,6 +78,11 @@ const STATUS_IGNORED: &str = "ignored";
 const STATUS_QUEUE_FULL: &str = "queue_full";
 const STATUS_SUCCESS: &str = "success";
 
+const LABEL_REASON: &str = "reason";
+const REASON_EXPIRED_REQUEST: &str = "expired_request";
+const REASON_EXPIRED_RESPONSE: &str = "expired_response";
+const REASON_SHED: &str = "shed";
+
 const PHASE_LOAD_STATE: &str = "load_state";
 const PHASE_COMMIT: &str = "commit";
 
@@ -87,6 +93,8 @@ const METRIC_TIMED_OUT_MESSAGES_TOTAL: &str = "mr_timed_out_messages_total";
 const METRIC_TIMED_OUT_CALLBACKS_TOTAL: &str = "mr_timed_out_callbacks_total";
 const METRIC_SHED_MESSAGES_TOTAL: &str = "mr_shed_messages_total";
 const METRIC_SHED_MESSAGE_BYTES_TOTAL: &str = "mr_shed_message_bytes_total";
+const METRIC_DROPPED_MESSAGES_TOTAL: &str = "mr_dropped_messages_total";
+const METRIC_DROPPED_MESSAGE_BYTES_TOTAL: &str = "mr_dropped_message_bytes_total";
 const METRIC_SUBNET_SPLIT_HEIGHT: &str = "mr_subnet_split_height";
 const BLOCKS_PROPOSED_TOTAL: &str = "mr_blocks_proposed_total";
 const BLOCKS_NOT_PROPOSED_TOTAL: &str = "mr_blocks_not_proposed_total";
@@ -285,6 +293,12 @@ pub(crate) struct MessageRoutingMetrics {
     pub(crate) shed_messages_total: IntCounter,
     /// Byte size of shed best-effort messages.
     pub(crate) shed_message_bytes_total: IntCounter,
+    /// Number of messages dropped from canister and subnet queues by any means
+    /// (including shedding outside of `enforce_best_effort_message_limit()`), by
+    /// reason.
+    dropped_messages_total: IntCounterVec,
+    /// Byte size of messages dropped from canister and subnet queues, by reason.
+    dropped_message_bytes_total: IntCounterVec,
     /// Height at which the subnet last split (if during the lifetime of this
     /// replica process; otherwise zero).
     pub(crate) subnet_split_height: IntGaugeVec,
@@ -397,6 +411,16 @@ impl MessageRoutingMetrics {
                 METRIC_SHED_MESSAGE_BYTES_TOTAL,
                 "Total byte size of shed messages.",
             ),
+            dropped_messages_total: metrics_registry.int_counter_vec(
+                METRIC_DROPPED_MESSAGES_TOTAL,
+                "Count of messages dropped from canister and subnet queues, by reason.",
+                &[LABEL_REASON],
+            ),
+            dropped_message_bytes_total: metrics_registry.int_counter_vec(
+                METRIC_DROPPED_MESSAGE_BYTES_TOTAL,
+                "Total byte size of messages dropped from canister and subnet queues, by reason.",
+                &[LABEL_REASON],
+            ),
             subnet_split_height: metrics_registry.int_gauge_vec(
                 METRIC_SUBNET_SPLIT_HEIGHT,
                 "Height at which the subnet last split (if during the lifetime of this replica process).",
@@ -453,6 +477,29 @@ impl MessageRoutingMetrics {
         }
     }
 
+    /// Rolls up the counts of messages dropped from canister and subnet queues
+    /// (as returned by `ReplicatedState::take_message_drop_stats()`).
+    pub fn observe_dropped_messages(&self, stats: MessageDropStats) {
+        let MessageDropStats {
+            expired_request_count,
+            expired_response_count,
+            shed_message_count,
+            shed_message_bytes,
+        } = stats;
+        for (reason, count) in [
+            (REASON_EXPIRED_REQUEST, expired_request_count),
+            (REASON_EXPIRED_RESPONSE, expired_response_count),
+            (REASON_SHED, shed_message_count),
+        ] {
+            self.dropped_messages_total
+                .with_label_values(&[reason])
+                .inc_by(count as u64);
+        }
+        self.dropped_message_bytes_total
+            .with_label_values(&[REASON_SHED])
+            .inc_by(shed_message_bytes as u64);
+    }
+
     pub fn observe_no_canister_allocation_range(&self, log: &ReplicaLogger, message: String) {
         self.critical_error_no_canister_allocation_range.inc();
         warn!(
@@ -10,7 +10,7 @@ use ic_interfaces::messaging::{
     LABEL_VALUE_CANISTER_OUT_OF_CYCLES, LABEL_VALUE_CANISTER_STOPPED,
     LABEL_VALUE_CANISTER_STOPPING, LABEL_VALUE_INVALID_MANAGEMENT_PAYLOAD,
 };
-use ic_logger::{debug, error, info, trace, ReplicaLogger};
+use ic_logger::{debug, error, info, trace, warn, ReplicaLogger};
 use ic_metrics::{
     buckets::{add_bucket, decimal_buckets},
     MetricsRegistry,
@@ -829,6 +829,16 @@ impl StreamHandlerImpl {
                                         RejectReason::CanisterStopping
                                     }
                                     StateError::QueueFull { .. } => RejectReason::QueueFull,
+                                    StateError::MessagePoolFull { capacity } => {
+                                        warn!(
+                                            every_n_seconds => 5,
+                                            self.log,
+                                            "Rejecting request, message pool at its capacity of {} messages: {:?}",
+                                            capacity,
+                                            request
+                                        );
+                                        RejectReason::QueueFull
+                                    }
                                     StateError::OutOfMemory { .. } => RejectReason::OutOfMemory,
                                     // Unreachable.
                                     StateError::NonMatchingResponse { .. }
@@ -13,7 +13,7 @@ use ic_registry_subnet_features::SubnetFeatures;
 use ic_replicated_state::{NetworkTopology, ReplicatedState};
 use ic_types::batch::Batch;
 use ic_types::{ExecutionRound, NumBytes};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 #[cfg(test)]
 mod tests;
@@ -41,6 +41,7 @@ pub(crate) struct StateMachineImpl {
     demux: Box<dyn Demux>,
     stream_builder: Box<dyn StreamBuilder>,
     best_effort_message_memory_capacity: NumBytes,
+    request_lifetime: Duration,
     log: ReplicaLogger,
     metrics: MessageRoutingMetrics,
 }
@@ -59,6 +60,7 @@ impl StateMachineImpl {
             demux,
             stream_builder,
             best_effort_message_memory_capacity: hypervisor_config.subnet_message_memory_capacity,
+            request_lifetime: hypervisor_config.guaranteed_response_request_lifetime,
             log,
             metrics,
         }
@@ -118,6 +120,11 @@ impl StateMachine for StateMachineImpl {
                 .observe_no_canister_allocation_range(&self.log, message);
         }
 
+        // Date the messages enqueued this round and apply the configured request
+        // lifetime to the requests enqueued this round.
+        state.observe_message_time();
+        state.set_request_lifetime(self.request_lifetime);
+
         // Time out expired messages.
         let timed_out_messages = state.time_out_messages();
         self.metrics
@@ -203,6 +210,10 @@ impl StateMachine for StateMachineImpl {
             .inc_by(shed_message_bytes.get());
         self.observe_phase_duration(PHASE_SHED_MESSAGES, &since);
 
+        // Roll up all messages dropped from canister and subnet queues this round.
+        self.metrics
+            .observe_dropped_messages(state_after_stream_builder.take_message_drop_stats());
+
         state_after_stream_builder
     }
 }
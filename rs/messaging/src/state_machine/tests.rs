@@ -4,6 +4,7 @@ use crate::{
     routing::demux::MockDemux, routing::stream_builder::MockStreamBuilder,
     state_machine::StateMachineImpl,
 };
+use ic_base_types::NumSeconds;
 use ic_interfaces::execution_environment::Scheduler;
 use ic_interfaces_state_manager::StateManager;
 use ic_management_canister_types::MasterPublicKeyId;
@@ -15,13 +16,17 @@ use ic_test_utilities::state_manager::FakeStateManager;
 use ic_test_utilities_execution_environment::test_registry_settings;
 use ic_test_utilities_logger::with_test_replica_logger;
 use ic_test_utilities_metrics::fetch_int_counter_vec;
+use ic_test_utilities_state::{new_canister_state, register_callback};
 use ic_test_utilities_types::{
-    batch::BatchBuilder, ids::subnet_test_id, messages::SignedIngressBuilder,
+    batch::BatchBuilder,
+    ids::{canister_test_id, subnet_test_id, user_test_id},
+    messages::{RequestBuilder, SignedIngressBuilder},
 };
 use ic_types::consensus::idkg::PreSigId;
 use ic_types::messages::SignedIngress;
+use ic_types::time::{CoarseTime, UNIX_EPOCH};
 use ic_types::{batch::BatchMessages, crypto::canister_threshold_sig::MasterPublicKey};
-use ic_types::{Height, PrincipalId, ReplicaVersion, SubnetId, Time};
+use ic_types::{CountBytes, Cycles, Height, NumBytes, PrincipalId, ReplicaVersion, SubnetId, Time};
 use maplit::btreemap;
 use mockall::{mock, predicate::*, Sequence};
 use std::collections::{BTreeMap, BTreeSet};
@@ -323,6 +328,78 @@ fn test_batch_time_impl(
     });
 }
 
+/// Tests that messages dropped from canister queues during the round are rolled
+/// up into the `mr_dropped_messages_total` and `mr_dropped_message_bytes_total`
+/// metrics.
+#[test]
+fn test_dropped_messages_are_observed() {
+    let provided_batch = BatchBuilder::new()
+        .batch_number(Height::new(1))
+        .time(Time::from_nanos_since_unix_epoch(2))
+        .build();
+    let mut fixture = test_fixture(&provided_batch);
+
+    // A canister with a best-effort request in an output queue.
+    let canister_id = canister_test_id(1);
+    let mut canister_state = new_canister_state(
+        canister_id,
+        user_test_id(1).get(),
+        Cycles::new(1 << 36),
+        NumSeconds::from(100_000),
+    );
+    let deadline = CoarseTime::from_secs_since_unix_epoch(1000);
+    let callback_id = register_callback(&mut canister_state, canister_id, canister_id, deadline);
+    let request = RequestBuilder::default()
+        .sender(canister_id)
+        .receiver(canister_test_id(2))
+        .sender_reply_callback(callback_id)
+        .deadline(deadline)
+        .build();
+    let request_bytes = request.count_bytes() as u64;
+    canister_state
+        .push_output_request(request.into(), UNIX_EPOCH)
+        .unwrap();
+    fixture.initial_state.put_canister_state(canister_state);
+
+    with_test_replica_logger(|log| {
+        // No room for best-effort messages, so the request is shed.
+        let hypervisor_config = HypervisorConfig {
+            subnet_message_memory_capacity: NumBytes::new(0),
+            ..Default::default()
+        };
+        let state_machine = StateMachineImpl::new(
+            fixture.scheduler,
+            fixture.demux,
+            fixture.stream_builder,
+            hypervisor_config,
+            log,
+            fixture.metrics,
+        );
+
+        state_machine.execute_round(
+            fixture.initial_state,
+            fixture.network_topology.clone(),
+            provided_batch,
+            Default::default(),
+            &test_registry_settings(),
+            Default::default(),
+            Default::default(),
+        );
+    });
+
+    let reason = |reason: &str| btreemap! { "reason".to_string() => reason.to_string() };
+    let dropped_messages =
+        fetch_int_counter_vec(&fixture.metrics_registry, "mr_dropped_messages_total");
+    assert_eq!(Some(&1), dropped_messages.get(&reason("shed")));
+    assert_eq!(Some(&0), dropped_messages.get(&reason("expired_request")));
+    assert_eq!(Some(&0), dropped_messages.get(&reason("expired_response")));
+    assert_eq!(
+        Some(&request_bytes),
+        fetch_int_counter_vec(&fixture.metrics_registry, "mr_dropped_message_bytes_total")
+            .get(&reason("shed"))
+    );
+}
+
 fn fetch_critical_error_non_increasing_batch_time_count(
     metrics_registry: &MetricsRegistry,
 ) -> Option<u64> {
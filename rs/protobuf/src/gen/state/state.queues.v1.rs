@@ -152,6 +152,18 @@ pub struct MessagePool {
     /// IDs.
     #[prost(uint64, tag = "3")]
     pub message_id_generator: u64,
+    /// Lifetime of guaranteed response call requests in output queues. Only
+    /// recorded if different from the default of 300 seconds.
+    #[prost(uint64, optional, tag = "5")]
+    pub request_lifetime_nanos: ::core::option::Option<u64>,
+    /// Load shedding priorities of best-effort messages, where different from the
+    /// default (normal) priority.
+    #[prost(message, repeated, tag = "6")]
+    pub priorities: ::prost::alloc::vec::Vec<message_pool::MessagePriority>,
+    /// Running message stats, so they need not be recomputed on load. Absent in
+    /// checkpoints written before they were persisted.
+    #[prost(message, optional, tag = "7")]
+    pub message_stats: ::core::option::Option<message_pool::MessageStats>,
 }
 /// Nested message and enum types in `MessagePool`.
 pub mod message_pool {
@@ -174,6 +186,41 @@ pub mod message_pool {
         #[prost(uint32, tag = "2")]
         pub deadline_seconds: u32,
     }
+    /// A (non-default) load shedding priority of a best-effort message.
+    #[derive(Clone, Copy, PartialEq, ::prost::Message)]
+    pub struct MessagePriority {
+        #[prost(uint64, tag = "1")]
+        pub id: u64,
+        #[prost(uint32, tag = "2")]
+        pub priority: u32,
+    }
+    /// Running stats of the messages in the pool. Guaranteed response memory
+    /// reservations are persisted separately, by `CanisterQueues`.
+    #[derive(Clone, Copy, PartialEq, ::prost::Message)]
+    pub struct MessageStats {
+        #[prost(uint64, tag = "1")]
+        pub size_bytes: u64,
+        #[prost(uint64, tag = "2")]
+        pub best_effort_message_bytes: u64,
+        #[prost(uint64, tag = "3")]
+        pub best_effort_message_count: u64,
+        #[prost(uint64, tag = "4")]
+        pub guaranteed_responses_size_bytes: u64,
+        #[prost(uint64, tag = "5")]
+        pub oversized_guaranteed_requests_extra_bytes: u64,
+        #[prost(uint64, tag = "6")]
+        pub inbound_size_bytes: u64,
+        #[prost(uint64, tag = "7")]
+        pub inbound_message_count: u64,
+        #[prost(uint64, tag = "8")]
+        pub inbound_response_count: u64,
+        #[prost(uint64, tag = "9")]
+        pub inbound_guaranteed_request_count: u64,
+        #[prost(uint64, tag = "10")]
+        pub inbound_guaranteed_response_count: u64,
+        #[prost(uint64, tag = "11")]
+        pub outbound_message_count: u64,
+    }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterQueue {
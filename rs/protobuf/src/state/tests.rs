@@ -42,6 +42,9 @@ fn huge_proto_encoding_roundtrip() {
         messages: vec![entry; 2 << 10],
         outbound_guaranteed_request_deadlines: vec![],
         message_id_generator: 42,
+        request_lifetime_nanos: None,
+        priorities: vec![],
+        message_stats: None,
     };
 
     let mut buf = vec![];
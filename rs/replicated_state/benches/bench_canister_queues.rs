@@ -0,0 +1,72 @@
+//! Benchmarks cloning (as done when snapshotting the replicated state)
+//! `CanisterQueues` holding large numbers of pooled messages; and the first
+//! mutation after such a clone, which has to copy the mutated collections.
+
+use criterion::{black_box, BatchSize, BenchmarkId, Criterion};
+use criterion_time::ProcessTime;
+use ic_replicated_state::testing::CanisterQueuesTesting;
+use ic_replicated_state::{CanisterQueues, InputQueueType};
+use ic_test_utilities_types::messages::RequestBuilder;
+use ic_types::messages::RequestOrResponse;
+use ic_types::time::CoarseTime;
+use ic_types::CanisterId;
+
+/// Number of senders that messages are spread across, in order to stay well
+/// within the per-queue capacity.
+const SENDER_COUNT: u64 = 1000;
+
+/// Returns a best-effort request from the given sender.
+fn request(sender: u64, deadline: u32) -> RequestOrResponse {
+    RequestBuilder::default()
+        .sender(CanisterId::from_u64(sender))
+        .receiver(CanisterId::from_u64(SENDER_COUNT))
+        .deadline(CoarseTime::from_secs_since_unix_epoch(deadline))
+        .build()
+        .into()
+}
+
+/// Returns `CanisterQueues` holding `message_count` best-effort requests.
+fn canister_queues(message_count: u64) -> CanisterQueues {
+    let mut queues = CanisterQueues::default();
+    for i in 0..message_count {
+        queues
+            .push_input(
+                request(i % SENDER_COUNT, 1000 + i as u32),
+                InputQueueType::RemoteSubnet,
+            )
+            .unwrap();
+    }
+    queues
+}
+
+fn bench_canister_queues(c: &mut Criterion<ProcessTime>) {
+    let mut group = c.benchmark_group("Clone");
+    for n in [1_000u64, 10_000, 100_000].iter().cloned() {
+        let queues = canister_queues(n);
+        group.bench_function(BenchmarkId::new("clone", n), |b| {
+            b.iter(|| black_box(queues.clone()));
+        });
+        group.bench_function(BenchmarkId::new("clone_then_push", n), |b| {
+            b.iter_batched(
+                || queues.clone(),
+                |mut clone| {
+                    clone
+                        .push_input(request(0, u32::MAX), InputQueueType::RemoteSubnet)
+                        .unwrap();
+                    black_box(clone)
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+fn main() {
+    let mut c = Criterion::default()
+        .with_measurement(ProcessTime::UserTime)
+        .sample_size(20)
+        .configure_from_args();
+    bench_canister_queues(&mut c);
+    c.final_summary();
+}
@@ -23,7 +23,11 @@ use ic_types::{LongExecutionMode, NumInstructions};
 use ic_validate_eq::ValidateEq;
 use ic_validate_eq_derive::ValidateEq;
 use phantom_newtype::AmountOf;
-pub use queues::{CanisterQueues, DEFAULT_QUEUE_CAPACITY};
+pub use queues::{
+    CanisterQueues, LoadSheddingPolicyKind, MessageClass, MessageContext, MessageDropStats,
+    MessageKind, MessagePriority, MessageRemovalReason, MessageRemovalRecord,
+    DEFAULT_QUEUE_CAPACITY,
+};
 use std::collections::BTreeSet;
 use std::convert::From;
 use std::sync::Arc;
@@ -6,8 +6,16 @@ mod tests;
 
 pub use self::input_schedule::CanisterQueuesLoopDetector;
 use self::input_schedule::InputSchedule;
+pub use self::message_pool::LoadSheddingPolicyKind;
+pub use self::message_pool::MessageDropStats;
+pub use self::message_pool::Priority as MessagePriority;
+pub use self::message_pool::{
+    Class as MessageClass, Context as MessageContext, Kind as MessageKind,
+    RemovalReason as MessageRemovalReason, RemovalRecord as MessageRemovalRecord,
+};
 use self::message_pool::{
-    Context, InboundReference, Kind, MessagePool, OutboundReference, SomeReference,
+    Context, InboundReference, Kind, MessagePool, OutboundReference, PoolFullError, SomeReference,
+    REQUEST_LIFETIME,
 };
 use self::queue::{CanisterQueue, IngressQueue, InputQueue, OutputQueue};
 use crate::replicated_state::MR_SYNTHETIC_REJECT_MESSAGE_MAX_LEN;
@@ -31,6 +39,7 @@ use prost::Message;
 use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::convert::{From, TryFrom};
 use std::sync::Arc;
+use std::time::Duration;
 use strum::EnumCount;
 
 pub const DEFAULT_QUEUE_CAPACITY: usize = 500;
@@ -122,9 +131,10 @@ pub const DEFAULT_QUEUE_CAPACITY: usize = 500;
 ///
 ///  # External invariants
 ///
-///  * `QueueStats`' slot and memory reservation stats are consistent with
-///    `CallContextManager`'s callbacks and non-responded call contexts (see
-///    `SystemState::check_invariants()` for details).
+///  * `QueueStats`' slot reservation stats and `MessageStats`' memory
+///    reservation stats are consistent with `CallContextManager`'s callbacks and
+///    non-responded call contexts (see `SystemState::check_invariants()` for
+///    details).
 #[derive(Clone, Eq, PartialEq, Debug, Default, ValidateEq)]
 pub struct CanisterQueues {
     /// Queue of ingress (user) messages.
@@ -375,7 +385,10 @@ struct MessageStoreImpl {
 
 impl MessageStoreImpl {
     /// Inserts an inbound message into the pool.
-    fn insert_inbound(&mut self, msg: RequestOrResponse) -> InboundReference {
+    fn insert_inbound(
+        &mut self,
+        msg: RequestOrResponse,
+    ) -> Result<InboundReference, PoolFullError> {
         self.pool.insert_inbound(msg)
     }
 
@@ -419,7 +432,7 @@ impl MessageStoreImpl {
     /// Tests whether the message store contains neither pooled messages nor compact
     /// responses.
     fn is_empty(&self) -> bool {
-        self.pool.len() == 0 && self.expired_callbacks.is_empty() && self.shed_responses.is_empty()
+        self.pool.is_empty() && self.expired_callbacks.is_empty() && self.shed_responses.is_empty()
     }
 
     /// Helper function for concisely validating the hard invariant that a canister
@@ -719,6 +732,10 @@ impl CanisterQueues {
     ///  * `QueueFull` if pushing a `Request` and the corresponding input or output
     ///    queues are full.
     ///
+    ///  * `MessagePoolFull` if pushing a `Request` and the message pool is at its
+    ///    configured capacity. Responses fill reserved slots, so they are exempt
+    ///    from the capacity limit.
+    ///
     ///  * `NonMatchingResponse` if pushing a guaranteed `Response` and the
     ///    corresponding input queue does not have a reserved slot; or it is a
     ///    duplicate.
@@ -730,6 +747,10 @@ impl CanisterQueues {
         let sender = msg.sender();
         let input_queue = match msg {
             RequestOrResponse::Request(_) => {
+                // Reject the request before making any changes if the pool is full.
+                if let Err(PoolFullError { capacity }) = self.store.pool.check_capacity() {
+                    return Err((StateError::MessagePoolFull { capacity }, msg));
+                }
                 let (input_queue, output_queue) =
                     get_or_insert_queues(&mut self.canister_queues, &sender);
                 if let Err(e) = input_queue.check_has_request_slot() {
@@ -792,8 +813,19 @@ impl CanisterQueues {
             }
         };
 
+        if msg.deadline() == NO_DEADLINE {
+            // A guaranteed response request makes a memory reservation, a guaranteed
+            // response consumes one.
+            match &msg {
+                RequestOrResponse::Request(_) => self.store.pool.reserve_guaranteed_response_slot(),
+                RequestOrResponse::Response(_) => self.store.pool.release_reservation(),
+            }
+        }
         self.queue_stats.on_push(&msg, Context::Inbound);
-        let reference = self.store.insert_inbound(msg);
+        let reference = self
+            .store
+            .insert_inbound(msg)
+            .expect("Requests were checked against the pool capacity above");
         match reference.kind() {
             Kind::Request => input_queue.push_request(reference),
             Kind::Response => input_queue.push_response(reference),
@@ -1056,7 +1088,8 @@ impl CanisterQueues {
     /// # Errors
     ///
     /// Returns a `QueueFull` error along with the provided message if either
-    /// the output queue or the matching input queue is full.
+    /// the output queue or the matching input queue is full; or a
+    /// `MessagePoolFull` error if the message pool is at its configured capacity.
     //
     // NOTE: DO NOT CHANGE THE VISIBILITY OF THIS METHOD. IT IS ONLY SUPPOSED TO BE
     // CALLED FOR CANISTERS (I.E. NOT FOR THE SUBNET QUEUES).
@@ -1065,6 +1098,26 @@ impl CanisterQueues {
         request: Arc<Request>,
         time: Time,
     ) -> Result<(), (StateError, Arc<Request>)> {
+        self.push_output_request_with_priority(request, time, MessagePriority::default())
+    }
+
+    /// Pushes a `Request` into the relevant output queue, with the given load
+    /// shedding priority (ignored for guaranteed response call requests). Lower
+    /// priority best-effort messages are shed first. See `push_output_request()`.
+    //
+    // NOTE: DO NOT CHANGE THE VISIBILITY OF THIS METHOD. IT IS ONLY SUPPOSED TO BE
+    // CALLED FOR CANISTERS (I.E. NOT FOR THE SUBNET QUEUES).
+    pub(super) fn push_output_request_with_priority(
+        &mut self,
+        request: Arc<Request>,
+        time: Time,
+        priority: MessagePriority,
+    ) -> Result<(), (StateError, Arc<Request>)> {
+        // Reject the request before making any changes if the pool is full.
+        if let Err(PoolFullError { capacity }) = self.store.pool.check_capacity() {
+            return Err((StateError::MessagePoolFull { capacity }, request));
+        }
+
         let (input_queue, output_queue) =
             get_or_insert_queues(&mut self.canister_queues, &request.receiver);
 
@@ -1075,10 +1128,16 @@ impl CanisterQueues {
             return Err((e, request));
         }
 
-        self.queue_stats
-            .on_push_request(&request, Context::Outbound);
+        if request.deadline == NO_DEADLINE {
+            self.store.pool.reserve_guaranteed_response_slot();
+        }
+        self.queue_stats.on_push_request(Context::Outbound);
 
-        let reference = self.store.pool.insert_outbound_request(request, time);
+        let reference = self
+            .store
+            .pool
+            .insert_outbound_request_with_priority(request, time, priority)
+            .expect("Pool capacity was checked above");
         output_queue.push_request(reference);
 
         debug_assert_eq!(Ok(()), self.test_invariants());
@@ -1109,8 +1168,10 @@ impl CanisterQueues {
         let (input_queue, _output_queue) =
             get_or_insert_queues(&mut self.canister_queues, &request.receiver);
         input_queue.try_reserve_response_slot()?;
-        self.queue_stats
-            .on_push_request(&request, Context::Outbound);
+        if request.deadline == NO_DEADLINE {
+            self.store.pool.reserve_guaranteed_response_slot();
+        }
+        self.queue_stats.on_push_request(Context::Outbound);
         debug_assert_eq!(Ok(()), self.test_invariants());
 
         let response = RequestOrResponse::Response(Arc::new(Response {
@@ -1154,8 +1215,10 @@ impl CanisterQueues {
     /// Panics if the queue does not already exist or there is no reserved slot
     /// to push the `Response` into.
     pub fn push_output_response(&mut self, response: Arc<Response>) {
-        self.queue_stats
-            .on_push_response(&response, Context::Outbound);
+        if response.deadline == NO_DEADLINE {
+            self.store.pool.release_reservation();
+        }
+        self.queue_stats.on_push_response(Context::Outbound);
 
         // Since we reserve an output queue slot whenever we induct a request; and
         // we would never garbage collect a non-empty queue (including one with just a
@@ -1165,7 +1228,13 @@ impl CanisterQueues {
             .get_mut(&response.originator)
             .expect("pushing response into inexistent output queue")
             .1;
-        let reference = self.store.pool.insert_outbound_response(response);
+        // Responses have reserved slots and are exempt from the pool capacity, so
+        // this cannot fail.
+        let reference = self
+            .store
+            .pool
+            .insert_outbound_response(response)
+            .expect("Failed to insert response into pool");
         output_queue.push_response(reference);
 
         debug_assert_eq!(Ok(()), self.test_invariants());
@@ -1179,6 +1248,37 @@ impl CanisterQueues {
         Some(self.store.get(output_queue.peek()?))
     }
 
+    /// Replaces the message at the front of the output queue to `canister_id` with
+    /// `msg`, preserving its position in the queue (e.g. in order to swap an
+    /// oversized payload for a truncated one). Returns the replaced message.
+    ///
+    /// Fails, returning `msg`, if the output queue is empty; or if `msg` differs
+    /// from the message it would replace in kind, class or deadline.
+    ///
+    /// The caller is responsible for accounting for any change in memory usage.
+    pub fn replace_output_message(
+        &mut self,
+        canister_id: &CanisterId,
+        msg: RequestOrResponse,
+    ) -> Result<RequestOrResponse, RequestOrResponse> {
+        let Some(reference) = self
+            .canister_queues
+            .get(canister_id)
+            .and_then(|(_, output_queue)| output_queue.peek())
+        else {
+            return Err(msg);
+        };
+
+        // `replace()` consumes `msg`, so keep a cheap (`Arc`) copy to return on failure.
+        match self.store.pool.replace(reference, msg.clone()) {
+            Some(replaced) => {
+                debug_assert_eq!(Ok(()), self.test_invariants());
+                Ok(replaced)
+            }
+            None => Err(msg),
+        }
+    }
+
     /// Tries to induct a message from the output queue to `own_canister_id`
     /// into the input queue from `own_canister_id`. Returns `Err(())` if there
     /// was no message to induct or the input queue was full.
@@ -1277,6 +1377,12 @@ impl CanisterQueues {
         self.message_stats().best_effort_message_bytes
     }
 
+    /// Returns the memory usage of all best-effort messages of the given load
+    /// shedding priority.
+    pub fn best_effort_message_memory_usage_by_priority(&self, priority: MessagePriority) -> usize {
+        self.store.pool.best_effort_message_bytes(priority)
+    }
+
     /// Returns the memory usage of all guaranteed response messages.
     pub fn guaranteed_response_memory_usage(&self) -> usize {
         self.queue_stats.guaranteed_response_memory_usage()
@@ -1295,7 +1401,7 @@ impl CanisterQueues {
     /// Note that this is different from slots reserved for responses (whether
     /// best effort or guaranteed) which are used to implement backpressure.
     pub fn guaranteed_response_memory_reservations(&self) -> usize {
-        self.queue_stats.guaranteed_response_memory_reservations
+        self.message_stats().guaranteed_response_reservations
     }
 
     /// Returns the sum total of bytes above `MAX_RESPONSE_COUNT_BYTES` per
@@ -1332,14 +1438,25 @@ impl CanisterQueues {
         if self.canister_queues.is_empty() && self.ingress_queue.is_empty() {
             // The schedules and stats will already have default (zero) values, only `store`
             // and `input_schedule` must be reset explicitly.
+            // A non-default request lifetime, the message capacity and the load
+            // shedding policy are retained.
             debug_assert!(self.store.is_empty());
+            let request_lifetime = self.store.pool.request_lifetime();
+            let max_messages = self.store.pool.max_messages();
+            let load_shedding_policy = *self.store.pool.load_shedding_policy();
             self.store = MessageStoreImpl::default();
+            self.store.pool.set_request_lifetime(request_lifetime);
+            self.store.pool.set_max_messages(max_messages);
+            self.store
+                .pool
+                .set_load_shedding_policy(load_shedding_policy);
             self.input_schedule = InputSchedule::default();
 
-            // Trust but verify. Ensure that the `CanisterQueues` now encodes to zero bytes.
-            debug_assert_eq!(
-                0,
-                pb_queues::CanisterQueues::from(self as &Self).encoded_len()
+            // Trust but verify. Ensure that the `CanisterQueues` now encodes to zero bytes
+            // (unless it must persist a non-default request lifetime).
+            debug_assert!(
+                request_lifetime != REQUEST_LIFETIME
+                    || pb_queues::CanisterQueues::from(self as &Self).encoded_len() == 0
             );
         }
     }
@@ -1366,6 +1483,32 @@ impl CanisterQueues {
         self.store.pool.has_expired_deadlines(current_time)
     }
 
+    /// Returns the earliest deadline of any message in the pool; or `None` if no
+    /// message has a deadline. Messages expire once the current time is past
+    /// their deadline.
+    ///
+    /// Time complexity: `O(log(n))`.
+    pub fn next_message_expiry(&self) -> Option<Time> {
+        self.store.pool.next_expiry()
+    }
+
+    /// Records `current_time` as the earliest possible insertion time of all
+    /// messages enqueued from here on, so that `oldest_message_age()` can date
+    /// them. Expected to be called once per round.
+    pub fn observe_time(&mut self, current_time: Time) {
+        self.store.pool.observe_time(current_time);
+    }
+
+    /// Returns an upper bound for the time the oldest message has been enqueued
+    /// for at `current_time`; or `None` if there are no messages or the oldest
+    /// message was enqueued before any time was observed (e.g. because it was
+    /// loaded from a checkpoint).
+    ///
+    /// Time complexity: `O(log(n))`.
+    pub fn oldest_message_age(&self, current_time: Time) -> Option<Duration> {
+        self.store.pool.oldest_message_age(current_time)
+    }
+
     /// Drops expired messages given a current time, enqueueing a reject response
     /// for own requests into the matching reverse queue (input or output).
     ///
@@ -1393,6 +1536,13 @@ impl CanisterQueues {
         expired_message_count
     }
 
+    /// Returns the byte size of the best-effort message that
+    /// `shed_largest_message()` would shed next, without shedding it; or `None`
+    /// if there are no best-effort messages.
+    pub fn largest_best_effort_message_size(&self) -> Option<usize> {
+        self.store.pool.largest_best_effort_size()
+    }
+
     /// Removes the largest best-effort message in the underlying pool. Returns
     /// `true` if a message was removed; `false` otherwise.
     ///
@@ -1418,6 +1568,115 @@ impl CanisterQueues {
         false
     }
 
+    /// Sheds the largest best-effort messages in the underlying pool until at
+    /// most `max_best_effort` best-effort messages remain. Returns the number of
+    /// messages that were shed.
+    ///
+    /// Updates the stats for the dropped messages and (where applicable) the
+    /// generated responses. Reject responses generated for shed outbound requests
+    /// are best-effort messages themselves, so more than `max_best_effort`
+    /// best-effort messages may remain. `own_canister_id` and `local_canisters`
+    /// are required to determine the correct input queue schedule to update (if
+    /// applicable).
+    ///
+    /// Time complexity per shed message: `O(log(n))`.
+    pub fn shed_down_to_best_effort_count(
+        &mut self,
+        max_best_effort: usize,
+        own_canister_id: &CanisterId,
+        local_canisters: &BTreeMap<CanisterId, CanisterState>,
+    ) -> usize {
+        let shed_messages = self.store.pool.shed_down_to_count(max_best_effort);
+        let shed_message_count = shed_messages.len();
+
+        let input_queue_type_fn = input_queue_type_fn(own_canister_id, local_canisters);
+        for (reference, msg) in shed_messages.into_iter() {
+            self.on_message_dropped(reference, msg, &input_queue_type_fn);
+        }
+
+        debug_assert_eq!(Ok(()), self.test_invariants());
+        debug_assert_eq!(Ok(()), self.schedules_ok(&input_queue_type_fn));
+        shed_message_count
+    }
+
+    /// Sheds all best-effort messages in the underlying pool at once, e.g. under
+    /// heavy memory pressure. Returns the number of messages that were shed.
+    ///
+    /// Guaranteed response messages are left untouched. Reject responses generated
+    /// for shed outbound requests are enqueued (and are best-effort messages
+    /// themselves). `own_canister_id` and `local_canisters` are required to
+    /// determine the correct input queue schedule to update (if applicable).
+    pub fn shed_all_best_effort_messages(
+        &mut self,
+        own_canister_id: &CanisterId,
+        local_canisters: &BTreeMap<CanisterId, CanisterState>,
+    ) -> usize {
+        let shed_messages = self.store.pool.clear_best_effort();
+        let shed_message_count = shed_messages.len();
+
+        let input_queue_type_fn = input_queue_type_fn(own_canister_id, local_canisters);
+        for (reference, msg) in shed_messages.into_iter() {
+            self.on_message_dropped(reference, msg, &input_queue_type_fn);
+        }
+
+        debug_assert_eq!(Ok(()), self.test_invariants());
+        debug_assert_eq!(Ok(()), self.schedules_ok(&input_queue_type_fn));
+        shed_message_count
+    }
+
+    /// Limits the number of messages held by the queues to `max_messages` (or
+    /// lifts the limit, if `None`). Pushing requests beyond the limit fails with
+    /// `StateError::MessagePoolFull`; responses fill reserved slots, so they are
+    /// exempt.
+    ///
+    /// This is configuration, so it is not persisted and must be re-applied after
+    /// loading the queues from a checkpoint.
+    pub fn set_message_capacity(&mut self, max_messages: Option<usize>) {
+        self.store.pool.set_max_messages(max_messages);
+    }
+
+    /// Sets the policy selecting the best-effort message to shed next (by
+    /// default, `LoadSheddingPolicyKind::LargestFirst`).
+    ///
+    /// This is configuration, so it is not persisted and must be re-applied after
+    /// loading the queues from a checkpoint.
+    pub fn set_load_shedding_policy(&mut self, load_shedding_policy: LoadSheddingPolicyKind) {
+        self.store
+            .pool
+            .set_load_shedding_policy(load_shedding_policy);
+    }
+
+    /// Sets the lifetime of guaranteed response call requests subsequently
+    /// enqueued into output queues (by default, `REQUEST_LIFETIME`).
+    ///
+    /// A non-default lifetime is persisted, so that all replicas compute the same
+    /// deadlines, also after a restart.
+    pub fn set_request_lifetime(&mut self, request_lifetime: Duration) {
+        self.store.pool.set_request_lifetime(request_lifetime);
+    }
+
+    /// Returns the counts of messages expired or shed (by `time_out_messages()`
+    /// and `shed_largest_message()`) since the previous call, resetting them.
+    /// Meant to be rolled up into replica metrics once per round.
+    pub fn take_message_drop_stats(&mut self) -> MessageDropStats {
+        self.store.pool.take_drop_stats()
+    }
+
+    /// Sets how many removed messages `recent_message_removals()` returns,
+    /// dropping the oldest records in excess of `capacity`. Zero (the default)
+    /// disables the removal log.
+    ///
+    /// This is a debugging aid, so neither the capacity nor the log are persisted.
+    pub fn set_removal_log_capacity(&mut self, capacity: usize) {
+        self.store.pool.set_removal_log_capacity(capacity);
+    }
+
+    /// Returns records of the most recently expired, shed or consumed messages
+    /// (up to the capacity of the removal log), oldest first.
+    pub fn recent_message_removals(&self) -> Vec<MessageRemovalRecord> {
+        self.store.pool.recent_removals()
+    }
+
     /// Handles the timing out or shedding of a message from the pool.
     ///
     /// Updates the stats, replaces shed inbound responses with compact reject
@@ -1515,13 +1774,20 @@ impl CanisterQueues {
                 let response = generate_timeout_response(&request);
 
                 // Update stats for the generated response.
-                self.queue_stats
-                    .on_push_response(&response, Context::Inbound);
+                if response.deadline == NO_DEADLINE {
+                    self.store.pool.release_reservation();
+                }
+                self.queue_stats.on_push_response(Context::Inbound);
 
                 assert!(self
                     .callbacks_with_enqueued_response
                     .insert(response.originator_reply_callback));
-                let reference = self.store.insert_inbound(response.into());
+                // Timeout responses fill reserved slots and are exempt from the pool
+                // capacity, so this cannot fail.
+                let reference = self
+                    .store
+                    .insert_inbound(response.into())
+                    .expect("Failed to insert timeout response into pool");
                 input_queue.push_response(reference);
 
                 // If the input queue is not already in a sender schedule, add it.
@@ -1598,7 +1864,6 @@ impl CanisterQueues {
         // Reserved slot stats match the actual number of reserved slots.
         let calculated_stats = Self::calculate_queue_stats(
             &self.canister_queues,
-            self.queue_stats.guaranteed_response_memory_reservations,
             self.queue_stats
                 .transient_stream_guaranteed_responses_size_bytes,
         );
@@ -1625,14 +1890,12 @@ impl CanisterQueues {
     }
 
     /// Computes stats for the given canister queues. Used when deserializing and in
-    /// `debug_assert!()` checks. Takes the number of memory reservations from the
-    /// caller, as the queues have no need to track memory reservations, so it
-    /// cannot be computed. Same with the size of guaranteed responses in streams.
+    /// `debug_assert!()` checks. Takes the size of guaranteed responses in streams
+    /// from the caller, as it cannot be computed from the queues.
     ///
     /// Time complexity: `O(canister_queues.len())`.
     fn calculate_queue_stats(
         canister_queues: &BTreeMap<CanisterId, (InputQueue, OutputQueue)>,
-        guaranteed_response_memory_reservations: usize,
         transient_stream_guaranteed_responses_size_bytes: usize,
     ) -> QueueStats {
         let (input_queues_reserved_slots, output_queues_reserved_slots) = canister_queues
@@ -1642,7 +1905,6 @@ impl CanisterQueues {
                 (acc0 + item0, acc1 + item1)
             });
         QueueStats {
-            guaranteed_response_memory_reservations,
             input_queues_reserved_slots,
             output_queues_reserved_slots,
             transient_stream_guaranteed_responses_size_bytes,
@@ -1735,9 +1997,7 @@ impl From<&CanisterQueues> for pb_queues::CanisterQueues {
             next_input_source,
             local_sender_schedule,
             remote_sender_schedule,
-            guaranteed_response_memory_reservations: item
-                .queue_stats
-                .guaranteed_response_memory_reservations
+            guaranteed_response_memory_reservations: item.guaranteed_response_memory_reservations()
                 as u64,
         }
     }
@@ -1748,7 +2008,10 @@ impl TryFrom<(pb_queues::CanisterQueues, &dyn CheckpointLoadingMetrics)> for Can
     fn try_from(
         (item, metrics): (pb_queues::CanisterQueues, &dyn CheckpointLoadingMetrics),
     ) -> Result<Self, Self::Error> {
-        let pool = MessagePool::try_from(item.pool.unwrap_or_default())?;
+        let pool = MessagePool::try_from((
+            item.pool.unwrap_or_default(),
+            item.guaranteed_response_memory_reservations as usize,
+        ))?;
 
         fn callback_references_try_from_proto(
             callback_references: Vec<pb_queues::canister_queues::CallbackReference>,
@@ -1810,11 +2073,7 @@ impl TryFrom<(pb_queues::CanisterQueues, &dyn CheckpointLoadingMetrics)> for Can
             ));
         }
 
-        let queue_stats = Self::calculate_queue_stats(
-            &canister_queues,
-            item.guaranteed_response_memory_reservations as usize,
-            0,
-        );
+        let queue_stats = Self::calculate_queue_stats(&canister_queues, 0);
 
         let input_schedule = InputSchedule::try_from((
             item.next_input_source,
@@ -1851,29 +2110,16 @@ impl TryFrom<(pb_queues::CanisterQueues, &dyn CheckpointLoadingMetrics)> for Can
     }
 }
 
-/// Tracks slot and guaranteed response memory reservations across input and
-/// output queues; and holds a (transient) byte size of responses already routed
-/// into streams (tracked separately, at the replicated state level, as messages
-/// are routed to and GC-ed from streams).
+/// Tracks slot reservations across input and output queues; and holds a
+/// (transient) byte size of responses already routed into streams (tracked
+/// separately, at the replicated state level, as messages are routed to and
+/// GC-ed from streams).
 ///
 /// Stats for the enqueued messages themselves (counts and sizes by kind,
-/// context and class) are tracked separately in `message_pool::MessageStats`.
+/// context and class) and guaranteed response memory reservations are tracked
+/// separately in `message_pool::MessageStats`.
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
 struct QueueStats {
-    /// Count of guaranteed response memory reservations across input and output
-    /// queues. This is equivalent to the number of outstanding (inbound or outbound)
-    /// guaranteed response calls and is used for computing message memory
-    /// usage (as `MAX_RESPONSE_COUNT_BYTES` per request).
-    ///
-    /// Note that this is different from slots reserved for responses (whether
-    /// best effort or guaranteed), which are used to implement backpressure.
-    ///
-    /// This is a counter maintained by `CanisterQueues` / `QueueStats`, but not
-    /// computed from the queues themselves. Rather, it is validated against the
-    /// number of unresponded guaranteed response callbacks and call contexts in the
-    /// `CallContextManager`.
-    guaranteed_response_memory_reservations: usize,
-
     /// Count of slots reserved in input queues. Note that this is different from
     /// memory reservations for guaranteed responses.
     input_queues_reserved_slots: usize,
@@ -1892,30 +2138,23 @@ struct QueueStats {
 }
 
 impl QueueStats {
-    /// Returns the memory usage of reservations for guaranteed responses plus
-    /// guaranteed responses in streans.
+    /// Returns the memory usage of guaranteed responses in streams.
     pub fn guaranteed_response_memory_usage(&self) -> usize {
-        self.guaranteed_response_memory_reservations * MAX_RESPONSE_COUNT_BYTES
-            + self.transient_stream_guaranteed_responses_size_bytes
+        self.transient_stream_guaranteed_responses_size_bytes
     }
 
     /// Updates the stats to reflect the enqueueing of the given message in the given
     /// context.
     fn on_push(&mut self, msg: &RequestOrResponse, context: Context) {
         match msg {
-            RequestOrResponse::Request(request) => self.on_push_request(request, context),
-            RequestOrResponse::Response(response) => self.on_push_response(response, context),
+            RequestOrResponse::Request(_) => self.on_push_request(context),
+            RequestOrResponse::Response(_) => self.on_push_response(context),
         }
     }
 
-    /// Updates the stats to reflect the enqueueing of the given request in the
-    /// given context.
-    fn on_push_request(&mut self, request: &Request, context: Context) {
-        // If pushing a guaranteed response request, make a memory reservation.
-        if request.deadline == NO_DEADLINE {
-            self.guaranteed_response_memory_reservations += 1;
-        }
-
+    /// Updates the stats to reflect the enqueueing of a request in the given
+    /// context.
+    fn on_push_request(&mut self, context: Context) {
         if context == Context::Outbound {
             // If pushing a request into an output queue, reserve an input queue slot.
             self.input_queues_reserved_slots += 1;
@@ -1925,17 +2164,9 @@ impl QueueStats {
         }
     }
 
-    /// Updates the stats to reflect the enqueueing of the given response in the
-    /// given context.
-    fn on_push_response(&mut self, response: &Response, context: Context) {
-        // If pushing a guaranteed response, consume a memory reservation.
-        if response.deadline == NO_DEADLINE {
-            debug_assert!(self.guaranteed_response_memory_reservations > 0);
-            self.guaranteed_response_memory_reservations = self
-                .guaranteed_response_memory_reservations
-                .saturating_sub(1);
-        }
-
+    /// Updates the stats to reflect the enqueueing of a response in the given
+    /// context.
+    fn on_push_response(&mut self, context: Context) {
         if context == Context::Inbound {
             // If pushing a response into an input queue, consume an input queue slot.
             debug_assert!(self.input_queues_reserved_slots > 0);
@@ -199,7 +199,9 @@ fn test_invariants() {
             .map(|(canister_id, size)| {
                 let mut queue = InputQueue::new(500);
                 for _ in 0..size {
-                    let id = pool.insert_inbound(RequestBuilder::default().build().into());
+                    let id = pool
+                        .insert_inbound(RequestBuilder::default().build().into())
+                        .unwrap();
                     queue.push_request(id);
                 }
                 (canister_id, queue)
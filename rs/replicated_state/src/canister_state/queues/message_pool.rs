@@ -8,23 +8,25 @@ use ic_types::time::CoarseTime;
 use ic_types::{CountBytes, Time};
 use ic_validate_eq::ValidateEq;
 use ic_validate_eq_derive::ValidateEq;
-use std::collections::{BTreeMap, BTreeSet};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::marker::PhantomData;
 use std::ops::{AddAssign, SubAssign};
 use std::sync::Arc;
 use std::time::Duration;
+use strum::EnumCount;
 
 #[cfg(test)]
 pub(super) mod tests;
 
-/// The lifetime of a guaranteed response call request in an output queue, from
-/// which its deadline is computed (as `now + REQUEST_LIFETIME`).
+/// The default lifetime of a guaranteed response call request in an output
+/// queue, from which its deadline is computed (as `now + request_lifetime`).
 pub const REQUEST_LIFETIME: Duration = Duration::from_secs(300);
 
 /// Bit encoding the message kind (request or response).
 #[repr(u64)]
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
-pub(super) enum Kind {
+pub enum Kind {
     Request = 0,
     Response = Self::BIT,
 }
@@ -46,7 +48,7 @@ impl From<&RequestOrResponse> for Kind {
 /// Bit encoding the message context (inbound or outbound).
 #[repr(u64)]
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
-pub(super) enum Context {
+pub enum Context {
     Inbound = 0,
     Outbound = Self::BIT,
 }
@@ -59,7 +61,7 @@ impl Context {
 /// Bit encoding the message class (guaranteed response vs best-effort).
 #[repr(u64)]
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
-pub(super) enum Class {
+pub enum Class {
     GuaranteedResponse = 0,
     BestEffort = Self::BIT,
 }
@@ -79,6 +81,39 @@ impl From<&RequestOrResponse> for Class {
     }
 }
 
+/// Load shedding priority of a best-effort message (2 bits). Lower priority
+/// messages are shed first, regardless of size; within a priority level,
+/// messages are shed largest first.
+///
+/// Provided by the caller on insertion and recorded by the `MessagePool`
+/// alongside (not within) the message's `Id`.
+#[repr(u8)]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Default, EnumCount)]
+pub enum Priority {
+    /// E.g. telemetry.
+    Low = 0,
+    #[default]
+    Normal = 1,
+    High = 2,
+    Highest = 3,
+}
+
+impl TryFrom<u32> for Priority {
+    type Error = ProxyDecodeError;
+    fn try_from(item: u32) -> Result<Self, Self::Error> {
+        match item {
+            0 => Ok(Priority::Low),
+            1 => Ok(Priority::Normal),
+            2 => Ok(Priority::High),
+            3 => Ok(Priority::Highest),
+            _ => Err(ProxyDecodeError::Other(format!(
+                "Invalid message priority: {}",
+                item
+            ))),
+        }
+    }
+}
+
 /// A generated identifier for a message held in a `MessagePool` that also
 /// encodes the message kind (request or response), context (incoming or
 /// outgoing) and class (guaranteed response or best-effort).
@@ -91,9 +126,6 @@ impl Id {
     /// Number of `Id` bits used as flags.
     const BITMASK_LEN: u32 = 3;
 
-    /// The minimum `Id` value, for use in e.g. `BTreeSet::split_off()` calls.
-    const MIN: Self = Self(0);
-
     fn kind(&self) -> Kind {
         if self.0 & Kind::BIT == Kind::Request as u64 {
             Kind::Request
@@ -118,6 +150,11 @@ impl Id {
         }
     }
 
+    /// Returns the value of the message ID generator this `Id` was created from.
+    fn generator(&self) -> u64 {
+        self.0 >> Self::BITMASK_LEN
+    }
+
     /// Tests whether this `Id` represents an inbound best-effort response.
     fn is_inbound_best_effort_response(&self) -> bool {
         self.0 & (Context::BIT | Class::BIT | Kind::BIT)
@@ -159,7 +196,7 @@ impl<T> Reference<T> {
         Id::from(self).context()
     }
 
-    #[allow(dead_code)]
+    #[cfg(test)]
     fn class(&self) -> Class {
         Id::from(self).class()
     }
@@ -202,6 +239,20 @@ impl<T> Ord for Reference<T> {
     }
 }
 
+/// Every combination of the `Id` flag bits is a valid kind / context / class,
+/// so any `u64` (e.g. a protobuf `uint64`) decodes to a valid `Id`.
+impl From<u64> for Id {
+    fn from(item: u64) -> Id {
+        Id(item)
+    }
+}
+
+impl From<Id> for u64 {
+    fn from(id: Id) -> u64 {
+        id.0
+    }
+}
+
 impl<T> From<&Reference<T>> for Id {
     fn from(reference: &Reference<T>) -> Id {
         Id(reference.0)
@@ -254,13 +305,22 @@ impl From<Id> for SomeReference {
     }
 }
 
+impl From<&SomeReference> for Id {
+    fn from(reference: &SomeReference) -> Id {
+        match reference {
+            SomeReference::Inbound(reference) => reference.into(),
+            SomeReference::Outbound(reference) => reference.into(),
+        }
+    }
+}
+
 impl<T> TryFrom<u64> for Reference<T>
 where
     T: ToContext,
 {
     type Error = ProxyDecodeError;
     fn try_from(item: u64) -> Result<Self, Self::Error> {
-        let id = Id(item);
+        let id = Id::from(item);
         if id.context() == T::context() {
             Ok(Reference(item, PhantomData))
         } else {
@@ -305,6 +365,160 @@ impl TryFrom<pb_queues::canister_queues::CallbackReference> for CallbackReferenc
     }
 }
 
+/// Deadline priority queue, with messages bucketed by `CoarseTime` deadline.
+///
+/// Outbound guaranteed response requests all get a deadline of `now +
+/// REQUEST_LIFETIME`, so under heavy traffic most messages in the queue share a
+/// handful of deadlines. Bucketing them means that an insert usually just adds
+/// to an existing bucket; and that expiration drains whole buckets.
+///
+/// Within a bucket, `Id`s are kept in an ordered set, so iteration order is
+/// deterministic: by deadline, then by `Id`. Empty buckets are never retained,
+/// so equality does not depend on the order of operations.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+struct DeadlineQueue {
+    /// Message `Ids` by deadline.
+    buckets: BTreeMap<CoarseTime, BTreeSet<Id>>,
+}
+
+impl DeadlineQueue {
+    /// Records the given message deadline.
+    ///
+    /// Time complexity: `O(log(n))`.
+    fn insert(&mut self, deadline: CoarseTime, id: Id) {
+        self.buckets.entry(deadline).or_default().insert(id);
+    }
+
+    /// Removes the given message deadline. Returns `true` if it was present.
+    ///
+    /// Time complexity: `O(log(n))`.
+    fn remove(&mut self, deadline: CoarseTime, id: Id) -> bool {
+        let Some(bucket) = self.buckets.get_mut(&deadline) else {
+            return false;
+        };
+        if !bucket.remove(&id) {
+            return false;
+        }
+        if bucket.is_empty() {
+            self.buckets.remove(&deadline);
+        }
+        true
+    }
+
+    /// Returns the earliest deadline in the queue, if any.
+    fn first_deadline(&self) -> Option<CoarseTime> {
+        self.buckets
+            .first_key_value()
+            .map(|(deadline, _)| *deadline)
+    }
+
+    /// Removes and returns all buckets with deadlines before `now`, in deadline
+    /// order.
+    fn split_off_expired(&mut self, now: CoarseTime) -> BTreeMap<CoarseTime, BTreeSet<Id>> {
+        let mut expired = self.buckets.split_off(&now);
+        std::mem::swap(&mut expired, &mut self.buckets);
+        expired
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// Returns the number of entries across all buckets.
+    ///
+    /// Time complexity: `O(number of buckets)`.
+    fn len(&self) -> usize {
+        self.buckets.values().map(BTreeSet::len).sum()
+    }
+
+    /// Iterates over all `(deadline, id)` pairs, in deadline order, then `Id`
+    /// order.
+    fn iter(&self) -> impl Iterator<Item = (CoarseTime, Id)> + '_ {
+        self.buckets
+            .iter()
+            .flat_map(|(deadline, ids)| ids.iter().map(move |id| (*deadline, *id)))
+    }
+}
+
+/// Ensures that the given expired `(deadline, id)` pairs are strictly ordered
+/// ascending, as returned by `MessagePool::expire_messages()`.
+///
+/// `DeadlineQueue` yields this order by construction, so a violation is a bug:
+/// debug builds panic, release builds sort the pairs.
+///
+/// Time complexity: `O(n)` if the pairs are ordered (always the case, barring
+/// bugs).
+fn ensure_expiry_order(expired: &mut [(CoarseTime, Id)]) {
+    let ordered = expired.windows(2).all(|pair| pair[0] < pair[1]);
+    debug_assert!(
+        ordered,
+        "Expired messages not ordered by (deadline, Id): {:?}",
+        expired
+    );
+    if !ordered {
+        expired.sort_unstable();
+    }
+}
+
+/// Strategy for selecting the best-effort message to shed when a
+/// `MessagePool` needs to make room.
+pub(super) trait LoadSheddingPolicy: Clone + Eq + std::fmt::Debug + Default {
+    /// Returns the best-effort message to shed next, if any. Must never select
+    /// a guaranteed response message.
+    fn select_victim<P: LoadSheddingPolicy>(&self, pool: &MessagePool<P>) -> Option<SomeReference>;
+}
+
+/// Sheds the lowest priority best-effort message first; and, within a priority
+/// level, the largest one; message IDs breaking ties.
+///
+/// Time complexity: `O(log(N))`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub(super) struct LargestFirst;
+
+impl LoadSheddingPolicy for LargestFirst {
+    fn select_victim<P: LoadSheddingPolicy>(&self, pool: &MessagePool<P>) -> Option<SomeReference> {
+        pool.size_queue.last().map(|(_, _, id)| (*id).into())
+    }
+}
+
+/// Sheds the oldest (lowest message ID) best-effort message first.
+///
+/// Time complexity: `O(N)` in the worst case (many guaranteed response messages
+/// older than the oldest best-effort message).
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub(super) struct OldestFirst;
+
+impl LoadSheddingPolicy for OldestFirst {
+    fn select_victim<P: LoadSheddingPolicy>(&self, pool: &MessagePool<P>) -> Option<SomeReference> {
+        pool.messages
+            .keys()
+            .find(|id| id.class() == Class::BestEffort)
+            .map(|id| (*id).into())
+    }
+}
+
+/// The load shedding policy of the `MessagePool` backing `CanisterQueues`.
+/// Configured via `CanisterQueues::set_load_shedding_policy()`; by default,
+/// `LargestFirst`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum LoadSheddingPolicyKind {
+    /// See `LargestFirst`.
+    #[default]
+    LargestFirst,
+
+    /// See `OldestFirst`.
+    OldestFirst,
+}
+
+impl LoadSheddingPolicy for LoadSheddingPolicyKind {
+    fn select_victim<P: LoadSheddingPolicy>(&self, pool: &MessagePool<P>) -> Option<SomeReference> {
+        match self {
+            Self::LargestFirst => LargestFirst.select_victim(pool),
+            Self::OldestFirst => OldestFirst.select_victim(pool),
+        }
+    }
+}
+
 /// A pool of canister messages, guaranteed response and best effort, with
 /// built-in support for time-based expiration and load shedding.
 ///
@@ -320,14 +534,24 @@ impl TryFrom<pb_queues::canister_queues::CallbackReference> for CallbackReferenc
 /// queues. All best-effort messages (and only best-effort messages) are added
 /// to the load shedding queue.
 ///
+/// Which best-effort message gets shed is decided by the `LoadSheddingPolicy`
+/// `P`, by default `LoadSheddingPolicyKind` (itself `LargestFirst` by default).
+///
 /// All pool operations except `expire_messages()` and
-/// `calculate_message_stats()` (only called during deserialization) execute in
-/// at most `O(log(N))` time.
-#[derive(Clone, Eq, PartialEq, Debug, Default, ValidateEq)]
-pub(super) struct MessagePool {
+/// `calculate_message_stats()` (only called when deserializing checkpoints and
+/// in debug assertions) execute in at most `O(log(N))` time
+/// (plus the time complexity of the load shedding policy, for
+/// `shed_largest_message()`).
+///
+/// The pool's collections are copy-on-write (`Arc`s updated via
+/// `Arc::make_mut()`), so cloning a pool (e.g. when snapshotting the replicated
+/// state) is `O(1)`. The first mutation of a collection shared with a clone
+/// copies that collection only.
+#[derive(Clone, Eq, Debug, ValidateEq)]
+pub(super) struct MessagePool<P: LoadSheddingPolicy = LoadSheddingPolicyKind> {
     /// Pool contents.
     #[validate_eq(CompareWithValidateEq)]
-    messages: BTreeMap<Id, RequestOrResponse>,
+    messages: Arc<BTreeMap<Id, RequestOrResponse>>,
 
     /// Records the (implicit) deadlines of all the outbound guaranteed response
     /// requests (only).
@@ -336,31 +560,266 @@ pub(super) struct MessagePool {
     ///  * Contains all outbound guaranteed requests:
     ///    `outbound_guaranteed_request_deadlines.keys().collect() == messages.keys().filter(|id| (id.context(), id.class(), id.kind()) == (Context::Outbound, Class::GuaranteedResponse, Kind::Request)).collect()`
     ///  * The deadline matches the one recorded in `deadline_queue`:
-    ///    `outbound_guaranteed_request_deadlines.iter().all(|(id, deadline)| deadline_queue.contains(&(deadline, id)))`
-    outbound_guaranteed_request_deadlines: BTreeMap<Id, CoarseTime>,
+    ///    `outbound_guaranteed_request_deadlines.iter().all(|(id, deadline)| deadline_queue.iter().any(|entry| entry == (*deadline, *id)))`
+    outbound_guaranteed_request_deadlines: Arc<BTreeMap<Id, CoarseTime>>,
 
     /// Running message stats for the pool.
     message_stats: MessageStats,
 
     /// Deadline priority queue. Holds all best-effort messages except responses in
     /// input queues (which we don't want to expire); plus guaranteed response call
-    /// requests in output queues (which expire after `REQUEST_LIFETIME`); ordered
+    /// requests in output queues (which expire after `request_lifetime`); ordered
     /// by deadline.
     ///
-    /// Message IDs break ties, ensuring deterministic ordering.
-    deadline_queue: BTreeSet<(CoarseTime, Id)>,
+    /// Messages with the same deadline are bucketed together; message IDs order
+    /// messages within a bucket, ensuring deterministic ordering.
+    deadline_queue: Arc<DeadlineQueue>,
 
     /// Load shedding priority queue. Holds all best-effort messages, ordered by
+    /// priority (descending, so the lowest priority messages come last), then by
     /// size.
     ///
     /// Message IDs break ties, ensuring deterministic ordering.
-    size_queue: BTreeSet<(usize, Id)>,
+    size_queue: Arc<BTreeSet<(Reverse<Priority>, usize, Id)>>,
+
+    /// Load shedding priorities of best-effort messages, where different from
+    /// `Priority::Normal`.
+    ///
+    /// Invariants:
+    ///  * Only holds best-effort messages in the pool.
+    ///  * Holds no `Priority::Normal` entries.
+    priorities: Arc<BTreeMap<Id, Priority>>,
+
+    /// Total byte size of all best-effort messages in the pool, by priority.
+    best_effort_message_bytes_by_priority: [usize; Priority::COUNT],
 
     /// A monotonically increasing counter used to generate unique message IDs.
     message_id_generator: u64,
+
+    /// Times observed by the pool (on outbound request insertion or explicitly
+    /// via `observe_time()`), keyed by the value of
+    /// `message_id_generator` at the time. Because message IDs are monotonically
+    /// increasing, every message with a generator value of at least `key` was
+    /// inserted no earlier than the respective time.
+    ///
+    /// Only the checkpoints still needed to date the pool's messages are retained.
+    /// Observability only: neither persisted nor compared, so messages loaded from
+    /// a checkpoint are not dated.
+    #[validate_eq(Ignore)]
+    time_checkpoints: Arc<BTreeMap<u64, Time>>,
+
+    /// The lifetime of guaranteed response call requests in output queues, from
+    /// which their deadline is computed. Defaults to `REQUEST_LIFETIME`.
+    ///
+    /// Persisted iff different from the default, so that all replicas compute the
+    /// same deadlines, also after a restart.
+    request_lifetime: Duration,
+
+    /// Maximum number of messages the pool may hold; unlimited if `None`.
+    ///
+    /// This is configuration rather than state, so it is neither persisted nor
+    /// compared.
+    #[validate_eq(Ignore)]
+    max_messages: Option<usize>,
+
+    /// Selects the best-effort message to shed. Configuration, not persisted.
+    #[validate_eq(Ignore)]
+    load_shedding_policy: P,
+
+    /// Counts of messages expired or shed since the last `take_drop_stats()`
+    /// call. Observability only: neither persisted nor compared.
+    #[validate_eq(Ignore)]
+    drop_stats: MessageDropStats,
+
+    /// The most recently removed messages and why they were removed, for
+    /// debugging. Disabled by default. Observability only: neither persisted
+    /// nor compared.
+    #[validate_eq(Ignore)]
+    removal_log: RemovalLog,
 }
 
-impl MessagePool {
+// Implemented by hand in order to exclude the observability-only `drop_stats`,
+// `removal_log` and `time_checkpoints`; and the non-persisted `max_messages` and
+// `load_shedding_policy`, so that a pool equals its encode-decode roundtrip.
+impl<P: LoadSheddingPolicy> PartialEq for MessagePool<P> {
+    fn eq(&self, rhs: &Self) -> bool {
+        // Destructuring on purpose, so that adding a field to `MessagePool` results
+        // in a compiler error here.
+        let MessagePool {
+            messages,
+            outbound_guaranteed_request_deadlines,
+            message_stats,
+            deadline_queue,
+            size_queue,
+            priorities,
+            best_effort_message_bytes_by_priority,
+            message_id_generator,
+            time_checkpoints: _,
+            request_lifetime,
+            max_messages: _,
+            load_shedding_policy: _,
+            drop_stats: _,
+            removal_log: _,
+        } = rhs;
+
+        (
+            &self.messages,
+            &self.outbound_guaranteed_request_deadlines,
+            &self.message_stats,
+            &self.deadline_queue,
+            &self.size_queue,
+            &self.priorities,
+            &self.best_effort_message_bytes_by_priority,
+            &self.message_id_generator,
+            &self.request_lifetime,
+        ) == (
+            messages,
+            outbound_guaranteed_request_deadlines,
+            message_stats,
+            deadline_queue,
+            size_queue,
+            priorities,
+            best_effort_message_bytes_by_priority,
+            message_id_generator,
+            request_lifetime,
+        )
+    }
+}
+
+/// Counts of messages dropped from a `MessagePool`, by reason (expiration or
+/// load shedding). Returned (and reset) by `MessagePool::take_drop_stats()`,
+/// to be rolled up into replica metrics.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct MessageDropStats {
+    /// Count of expired requests.
+    pub expired_request_count: usize,
+
+    /// Count of expired responses.
+    pub expired_response_count: usize,
+
+    /// Count of shed messages.
+    pub shed_message_count: usize,
+
+    /// Total byte size of shed messages.
+    pub shed_message_bytes: usize,
+}
+
+impl AddAssign<MessageDropStats> for MessageDropStats {
+    fn add_assign(&mut self, rhs: MessageDropStats) {
+        let MessageDropStats {
+            expired_request_count,
+            expired_response_count,
+            shed_message_count,
+            shed_message_bytes,
+        } = rhs;
+        self.expired_request_count += expired_request_count;
+        self.expired_response_count += expired_response_count;
+        self.shed_message_count += shed_message_count;
+        self.shed_message_bytes += shed_message_bytes;
+    }
+}
+
+/// Why a message was removed from a `MessagePool`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum RemovalReason {
+    /// The message expired (`expire_messages()`).
+    Expired,
+    /// The message was shed (`shed_largest_message()` or `clear_best_effort()`).
+    Shed,
+    /// The message was consumed (`take()`).
+    Taken,
+}
+
+/// A message removed from a `MessagePool`. See `MessagePool::recent_removals()`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct RemovalRecord {
+    /// The `Id` of the message, including the bits encoding its kind, context
+    /// and class.
+    pub id: u64,
+    pub kind: Kind,
+    pub context: Context,
+    pub class: Class,
+
+    /// Byte size of the message.
+    pub size_bytes: usize,
+
+    /// The deadline of the message; the implicit one for outbound guaranteed
+    /// response requests; `NO_DEADLINE` for all other guaranteed response
+    /// messages.
+    pub deadline: CoarseTime,
+
+    pub reason: RemovalReason,
+
+    /// When the message was removed: `now` for expired messages; else the
+    /// latest time observed by the pool (i.e. a lower bound), if any.
+    pub removed_at: Option<Time>,
+}
+
+/// Bounded ring buffer of `RemovalRecord`s: once at capacity, the oldest
+/// record is dropped for every new one. Records nothing at capacity zero.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+struct RemovalLog {
+    capacity: usize,
+    records: Arc<VecDeque<RemovalRecord>>,
+}
+
+impl RemovalLog {
+    fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    fn push(&mut self, record: RemovalRecord) {
+        if !self.is_enabled() {
+            return;
+        }
+        let records = Arc::make_mut(&mut self.records);
+        if records.len() >= self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Sets the capacity, dropping the oldest records in excess of it.
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        if self.records.len() > capacity {
+            let records = Arc::make_mut(&mut self.records);
+            let excess = records.len() - capacity;
+            records.drain(..excess);
+        }
+    }
+}
+
+/// Error returned when trying to insert a message into a `MessagePool` that is
+/// at capacity.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(super) struct PoolFullError {
+    /// The maximum number of messages the pool may hold.
+    pub(super) capacity: usize,
+}
+
+impl<P: LoadSheddingPolicy> MessagePool<P> {
+    /// Creates an empty pool that sheds messages according to the given
+    /// `LoadSheddingPolicy`.
+    pub(super) fn with_load_shedding_policy(load_shedding_policy: P) -> Self {
+        Self {
+            messages: Default::default(),
+            outbound_guaranteed_request_deadlines: Default::default(),
+            message_stats: Default::default(),
+            deadline_queue: Default::default(),
+            size_queue: Default::default(),
+            priorities: Default::default(),
+            best_effort_message_bytes_by_priority: Default::default(),
+            message_id_generator: 0,
+            time_checkpoints: Default::default(),
+            request_lifetime: REQUEST_LIFETIME,
+            max_messages: None,
+            load_shedding_policy,
+            drop_stats: Default::default(),
+            removal_log: Default::default(),
+        }
+    }
+
     /// Inserts an inbound message (one that is to be enqueued in an input queue)
     /// into the pool. Returns the ID assigned to the message.
     ///
@@ -368,7 +827,27 @@ impl MessagePool {
     /// (best effort responses that already made it into an input queue should not
     /// expire). It is added to the load shedding queue if it is a best-effort
     /// message.
-    pub(super) fn insert_inbound(&mut self, msg: RequestOrResponse) -> InboundReference {
+    ///
+    /// Fails with `PoolFullError` if `msg` is a request and the pool is at
+    /// capacity. Responses fill previously reserved slots, so they are never
+    /// rejected.
+    pub(super) fn insert_inbound(
+        &mut self,
+        msg: RequestOrResponse,
+    ) -> Result<InboundReference, PoolFullError> {
+        self.insert_inbound_with_priority(msg, Priority::Normal)
+    }
+
+    /// Inserts an inbound message with the given load shedding `Priority`. See
+    /// `insert_inbound()`.
+    ///
+    /// The priority is ignored for guaranteed response messages, which are never
+    /// shed.
+    pub(super) fn insert_inbound_with_priority(
+        &mut self,
+        msg: RequestOrResponse,
+        priority: Priority,
+    ) -> Result<InboundReference, PoolFullError> {
         let actual_deadline = match &msg {
             RequestOrResponse::Request(request) => request.deadline,
 
@@ -376,7 +855,7 @@ impl MessagePool {
             RequestOrResponse::Response(_) => NO_DEADLINE,
         };
 
-        self.insert_impl(msg, actual_deadline, Context::Inbound)
+        self.insert_impl(msg, actual_deadline, Context::Inbound, priority)
     }
 
     /// Reserves an `InboundReference` for a timeout reject response for a
@@ -387,22 +866,40 @@ impl MessagePool {
         self.next_reference(Class::BestEffort, Kind::Response)
     }
 
+    /// Inserts an outbound request with the default (normal) load shedding
+    /// priority. See `insert_outbound_request_with_priority()`.
+    #[cfg(test)]
+    pub(super) fn insert_outbound_request(
+        &mut self,
+        request: Arc<Request>,
+        now: Time,
+    ) -> Result<OutboundReference, PoolFullError> {
+        self.insert_outbound_request_with_priority(request, now, Priority::Normal)
+    }
+
     /// Inserts an outbound request (one that is to be enqueued in an output queue)
-    /// into the pool. Returns the reference assigned to the request.
+    /// with the given load shedding `Priority` into the pool. Returns the
+    /// reference assigned to the request.
     ///
     /// The request is always added to the deadline queue: if it is a best-effort
     /// request, with its explicit deadline; if it is a guaranteed response call
-    /// request, with a deadline of `now + REQUEST_LIFETIME`. It is added to the
-    /// load shedding queue iff it is a best-effort request.
-    pub(super) fn insert_outbound_request(
+    /// request, with a deadline of `now + request_lifetime`. It is added to the
+    /// load shedding queue iff it is a best-effort request. The priority is ignored
+    /// for guaranteed response call requests, which are never shed.
+    ///
+    /// Fails with `PoolFullError` if the pool is at capacity.
+    pub(super) fn insert_outbound_request_with_priority(
         &mut self,
         request: Arc<Request>,
         now: Time,
-    ) -> OutboundReference {
+        priority: Priority,
+    ) -> Result<OutboundReference, PoolFullError> {
+        self.observe_time(now);
+
         let actual_deadline = if request.deadline == NO_DEADLINE {
             // Guaranteed response call requests in canister output queues expire after
-            // `REQUEST_LIFETIME`.
-            CoarseTime::floor(now + REQUEST_LIFETIME)
+            // `request_lifetime`.
+            CoarseTime::floor(now + self.request_lifetime)
         } else {
             // Best-effort requests expire as per their specified deadline.
             request.deadline
@@ -412,6 +909,7 @@ impl MessagePool {
             RequestOrResponse::Request(request),
             actual_deadline,
             Context::Outbound,
+            priority,
         )
     }
 
@@ -420,15 +918,31 @@ impl MessagePool {
     ///
     /// The response is added to both the deadline queue and the load shedding queue
     /// iff it is a best-effort response.
+    ///
+    /// Responses fill previously reserved slots, so they are never rejected,
+    /// regardless of the pool capacity.
     pub(super) fn insert_outbound_response(
         &mut self,
         response: Arc<Response>,
-    ) -> OutboundReference {
+    ) -> Result<OutboundReference, PoolFullError> {
+        self.insert_outbound_response_with_priority(response, Priority::Normal)
+    }
+
+    /// Inserts an outbound response with the given load shedding `Priority`. See
+    /// `insert_outbound_response()`.
+    ///
+    /// The priority is ignored for guaranteed responses, which are never shed.
+    pub(super) fn insert_outbound_response_with_priority(
+        &mut self,
+        response: Arc<Response>,
+        priority: Priority,
+    ) -> Result<OutboundReference, PoolFullError> {
         let actual_deadline = response.deadline;
         self.insert_impl(
             RequestOrResponse::Response(response),
             actual_deadline,
             Context::Outbound,
+            priority,
         )
     }
 
@@ -439,17 +953,27 @@ impl MessagePool {
     /// `actual_deadline` iff it is non-zero (as opposed to the message's nominal
     /// deadline; this is so we can expire outgoing guaranteed response requests;
     /// and not expire incoming best-effort responses). It is recorded in the load
-    /// shedding priority queue iff it is a best-effort message.
+    /// shedding priority queue (with the given `priority`) iff it is a best-effort
+    /// message.
+    ///
+    /// Fails with `PoolFullError`, leaving the pool unchanged, if `msg` is a
+    /// request and the pool is at capacity. Responses are exempt from the
+    /// capacity limit, as they fill slots reserved by their requests.
     fn insert_impl<T>(
         &mut self,
         msg: RequestOrResponse,
         actual_deadline: CoarseTime,
         context: Context,
-    ) -> Reference<T>
+        priority: Priority,
+    ) -> Result<Reference<T>, PoolFullError>
     where
         T: ToContext,
     {
         let kind = Kind::from(&msg);
+        if kind == Kind::Request {
+            self.check_capacity()?;
+        }
+
         let class = Class::from(&msg);
         let reference = self.next_reference(class, kind);
         let id = reference.into();
@@ -460,33 +984,43 @@ impl MessagePool {
         self.message_stats += MessageStats::stats_delta(&msg, context);
 
         // Insert.
-        assert!(self.messages.insert(id, msg).is_none());
-        debug_assert_eq!(
-            Self::calculate_message_stats(&self.messages),
-            self.message_stats
-        );
+        assert!(Arc::make_mut(&mut self.messages).insert(id, msg).is_none());
 
         // Record in deadline queue iff `actual_deadline` is non-zero. This applies to
         // all best-effort messages except responses in input queues; plus guaranteed
         // response requests in output queues
         if actual_deadline != NO_DEADLINE {
-            self.deadline_queue.insert((actual_deadline, id));
+            Arc::make_mut(&mut self.deadline_queue).insert(actual_deadline, id);
 
             // Record in the outbound guaranteed response deadline map, iff it's an outbound
             // guaranteed response request.
             if class == Class::GuaranteedResponse {
                 debug_assert_eq!((Context::Outbound, Kind::Request), (context, kind));
-                self.outbound_guaranteed_request_deadlines
+                Arc::make_mut(&mut self.outbound_guaranteed_request_deadlines)
                     .insert(id, actual_deadline);
             }
         }
 
         // Record in load shedding queue iff it's a best-effort message.
         if class == Class::BestEffort {
-            self.size_queue.insert((size_bytes, id));
+            Arc::make_mut(&mut self.size_queue).insert((Reverse(priority), size_bytes, id));
+            if priority != Priority::Normal {
+                Arc::make_mut(&mut self.priorities).insert(id, priority);
+            }
+            self.best_effort_message_bytes_by_priority[priority as usize] += size_bytes;
         }
 
-        reference
+        debug_assert_eq!(Ok(()), self.check_invariants());
+        Ok(reference)
+    }
+
+    /// Returns `Err(PoolFullError)` iff the pool is at capacity, i.e. inserting
+    /// another request would fail.
+    pub(super) fn check_capacity(&self) -> Result<(), PoolFullError> {
+        match self.max_messages {
+            Some(capacity) if self.messages.len() >= capacity => Err(PoolFullError { capacity }),
+            _ => Ok(()),
+        }
     }
 
     /// Reserves and returns a new message reference.
@@ -510,6 +1044,7 @@ impl MessagePool {
     pub(super) fn take<T>(&mut self, reference: Reference<T>) -> Option<RequestOrResponse> {
         let id = reference.into();
         let msg = self.take_impl(id)?;
+        self.record_removal(id, &msg, RemovalReason::Taken, None);
 
         self.remove_from_deadline_queue(id, &msg);
         self.remove_from_size_queue(id, &msg);
@@ -518,11 +1053,61 @@ impl MessagePool {
         Some(msg)
     }
 
+    /// Replaces the message with the given `Reference` with `msg`, preserving
+    /// the reference (and thus the message's position in any queue holding it).
+    /// Returns the replaced message.
+    ///
+    /// Returns `None` and leaves the pool unchanged if there is no message with
+    /// the given `Reference`; or if `msg` is of a different kind or class; or
+    /// has a different deadline than the message it would replace.
+    ///
+    /// Updates the stats; and the load shedding queue, if the byte size of the
+    /// message changed. The deadline queue is left unchanged.
+    ///
+    /// Time complexity: `O(log(self.len()))`.
+    pub(super) fn replace<T>(
+        &mut self,
+        reference: Reference<T>,
+        msg: RequestOrResponse,
+    ) -> Option<RequestOrResponse> {
+        let id: Id = reference.into();
+        if (id.class(), id.kind()) != (Class::from(&msg), Kind::from(&msg)) {
+            return None;
+        }
+        // Check before unsharing the map, so a rejected replacement leaves it shared.
+        if self.messages.get(&id)?.deadline() != msg.deadline() {
+            return None;
+        }
+        let old_msg = Arc::make_mut(&mut self.messages).get_mut(&id).unwrap();
+        let (old_size, new_size) = (old_msg.count_bytes(), msg.count_bytes());
+        let old_msg = std::mem::replace(old_msg, msg);
+
+        self.message_stats -= MessageStats::stats_delta(&old_msg, id.context());
+        self.message_stats += MessageStats::stats_delta(&self.messages[&id], id.context());
+
+        if id.class() == Class::BestEffort && old_size != new_size {
+            let priority = self.priority(id);
+            let size_queue = Arc::make_mut(&mut self.size_queue);
+            let removed = size_queue.remove(&(Reverse(priority), old_size, id));
+            debug_assert!(removed);
+            size_queue.insert((Reverse(priority), new_size, id));
+            self.best_effort_message_bytes_by_priority[priority as usize] -= old_size;
+            self.best_effort_message_bytes_by_priority[priority as usize] += new_size;
+        }
+
+        debug_assert_eq!(Ok(()), self.check_invariants());
+        Some(old_msg)
+    }
+
     /// Removes the message with the given `Reference` from the pool.
     ///
     /// Updates the stats, but not the priority queues.
     fn take_impl(&mut self, id: Id) -> Option<RequestOrResponse> {
-        let msg = self.messages.remove(&id)?;
+        if !self.messages.contains_key(&id) {
+            // Stale reference, don't unshare the map.
+            return None;
+        }
+        let msg = Arc::make_mut(&mut self.messages).remove(&id).unwrap();
         // Sanity check.
         debug_assert_eq!(
             (id.class(), id.kind()),
@@ -531,13 +1116,57 @@ impl MessagePool {
 
         self.message_stats -= MessageStats::stats_delta(&msg, id.context());
         debug_assert_eq!(
-            Self::calculate_message_stats(&self.messages),
+            MessagePool::calculate_message_stats(
+                &self.messages,
+                self.message_stats.guaranteed_response_reservations
+            ),
             self.message_stats
         );
 
         Some(msg)
     }
 
+    /// Records the removal of the given message in the removal log, if enabled.
+    /// Must be called before the message is removed from
+    /// `self.outbound_guaranteed_request_deadlines`.
+    ///
+    /// `now` is the time of removal, if known; else the latest time observed by
+    /// the pool is recorded.
+    fn record_removal(
+        &mut self,
+        id: Id,
+        msg: &RequestOrResponse,
+        reason: RemovalReason,
+        now: Option<Time>,
+    ) {
+        if !self.removal_log.is_enabled() {
+            return;
+        }
+        let deadline = if id.is_outbound_guaranteed_request() {
+            self.outbound_guaranteed_request_deadlines
+                .get(&id)
+                .copied()
+                .unwrap_or(NO_DEADLINE)
+        } else {
+            msg.deadline()
+        };
+        let removed_at = now.or_else(|| {
+            self.time_checkpoints
+                .last_key_value()
+                .map(|(_, time)| *time)
+        });
+        self.removal_log.push(RemovalRecord {
+            id: id.0,
+            kind: id.kind(),
+            context: id.context(),
+            class: id.class(),
+            size_bytes: msg.count_bytes(),
+            deadline,
+            reason,
+            removed_at,
+        });
+    }
+
     /// Removes the given message from the deadline queue and from
     /// `self.outbound_guaranteed_request_deadlines`, if applicable.
     fn remove_from_deadline_queue(&mut self, id: Id, msg: &RequestOrResponse) {
@@ -548,11 +1177,10 @@ impl MessagePool {
         match (id.context(), id.class(), id.kind()) {
             // Outbound guaranteed response requests have (separately recorded) deadlines.
             (Outbound, GuaranteedResponse, Request) => {
-                let deadline = self
-                    .outbound_guaranteed_request_deadlines
+                let deadline = Arc::make_mut(&mut self.outbound_guaranteed_request_deadlines)
                     .remove(&id)
                     .unwrap();
-                let removed = self.deadline_queue.remove(&(deadline, id));
+                let removed = Arc::make_mut(&mut self.deadline_queue).remove(deadline, id);
                 debug_assert!(removed);
             }
 
@@ -564,36 +1192,85 @@ impl MessagePool {
 
             // All other best-effort messages do expire.
             (_, BestEffort, _) => {
-                let removed = self.deadline_queue.remove(&(msg.deadline(), id));
+                let removed = Arc::make_mut(&mut self.deadline_queue).remove(msg.deadline(), id);
                 debug_assert!(removed);
             }
         }
     }
 
-    /// Removes the given message from the load shedding queue.
+    /// Removes the given message from the load shedding queue; and drops its
+    /// priority.
     fn remove_from_size_queue(&mut self, id: Id, msg: &RequestOrResponse) {
         if id.class() == Class::BestEffort {
-            let removed = self.size_queue.remove(&(msg.count_bytes(), id));
+            let priority = Arc::make_mut(&mut self.priorities)
+                .remove(&id)
+                .unwrap_or_default();
+            let size_bytes = msg.count_bytes();
+            let removed =
+                Arc::make_mut(&mut self.size_queue).remove(&(Reverse(priority), size_bytes, id));
             debug_assert!(removed);
+            self.best_effort_message_bytes_by_priority[priority as usize] -= size_bytes;
         }
     }
 
+    /// Returns the load shedding priority of the message with the given `Id`.
+    fn priority(&self, id: Id) -> Priority {
+        self.priorities.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Returns the load shedding priority of the message with the given
+    /// `Reference`; `Priority::Normal` for guaranteed response messages.
+    #[cfg(test)]
+    pub(super) fn priority_of<T>(&self, reference: Reference<T>) -> Priority {
+        self.priority(reference.into())
+    }
+
+    /// Returns the total byte size of all best-effort messages of the given
+    /// priority.
+    ///
+    /// Time complexity: `O(1)`.
+    pub(super) fn best_effort_message_bytes(&self, priority: Priority) -> usize {
+        self.best_effort_message_bytes_by_priority[priority as usize]
+    }
+
     /// Queries whether any message's deadline has expired.
     ///
     /// Time complexity: `O(log(self.len()))`.
     pub(super) fn has_expired_deadlines(&self, now: Time) -> bool {
-        if let Some((deadline, _)) = self.deadline_queue.first() {
+        if let Some(deadline) = self.deadline_queue.first_deadline() {
             let now = CoarseTime::floor(now);
-            if *deadline < now {
+            if deadline < now {
                 return true;
             }
         }
         false
     }
 
+    /// Returns the earliest deadline of any message in the pool (explicit or, for
+    /// outbound guaranteed response requests, implicit); or `None` if no message
+    /// has a deadline.
+    ///
+    /// Messages expire as soon as the current time (rounded down to the second)
+    /// is past their deadline, i.e. `has_expired_deadlines(now)` returns `true`
+    /// for any `now` at least one second after the returned time.
+    ///
+    /// Time complexity: `O(log(self.len()))`.
+    pub(super) fn next_expiry(&self) -> Option<Time> {
+        self.deadline_queue
+            .first_deadline()
+            .map(|deadline| deadline.into())
+    }
+
     /// Removes and returns all messages with expired deadlines (i.e. `deadline <
     /// now`). Updates the stats; and the priority queues, where applicable.
     ///
+    /// Expired messages are returned ordered by `(deadline, Id)` ascending. This
+    /// order is part of the contract: callers generate reject responses (and
+    /// thus message IDs) from the output, so it must be identical across
+    /// replicas regardless of how the pool was built or decoded. The order is
+    /// verified on every call (and fully restored, should it ever be violated in
+    /// a release build).
+    ///
     /// Time complexity per expired message: `O(log(self.len()))`.
     pub(super) fn expire_messages(&mut self, now: Time) -> Vec<(SomeReference, RequestOrResponse)> {
         if self.deadline_queue.is_empty() {
@@ -601,25 +1278,35 @@ impl MessagePool {
             return Vec::new();
         }
 
+        let removed_at = now;
         let now = CoarseTime::floor(now);
-        if self.deadline_queue.first().unwrap().0 >= now {
+        if self.deadline_queue.first_deadline().unwrap() >= now {
             // No expired messages, bail out.
             return Vec::new();
         }
 
-        // Split the deadline queue at `now`.
-        let mut temp = self.deadline_queue.split_off(&(now, Id::MIN));
-        std::mem::swap(&mut temp, &mut self.deadline_queue);
+        // Drain all buckets with deadlines before `now`.
+        let mut expired_ids: Vec<(CoarseTime, Id)> = Arc::make_mut(&mut self.deadline_queue)
+            .split_off_expired(now)
+            .into_iter()
+            .flat_map(|(deadline, ids)| ids.into_iter().map(move |id| (deadline, id)))
+            .collect();
+        ensure_expiry_order(&mut expired_ids);
 
         // Take and return all expired messages.
-        let expired = temp
+        let expired = expired_ids
             .into_iter()
             .map(|(_, id)| {
                 let msg = self.take_impl(id).unwrap();
+                self.record_removal(id, &msg, RemovalReason::Expired, Some(removed_at));
                 if id.is_outbound_guaranteed_request() {
-                    self.outbound_guaranteed_request_deadlines.remove(&id);
+                    Arc::make_mut(&mut self.outbound_guaranteed_request_deadlines).remove(&id);
                 }
                 self.remove_from_size_queue(id, &msg);
+                match id.kind() {
+                    Kind::Request => self.drop_stats.expired_request_count += 1,
+                    Kind::Response => self.drop_stats.expired_response_count += 1,
+                }
                 (id.into(), msg)
             })
             .collect();
@@ -628,138 +1315,508 @@ impl MessagePool {
         expired
     }
 
-    /// Removes and returns the largest best-effort message in the pool, if any.
-    /// Updates the stats; and the priority queues, where applicable.
+    /// Removes and returns the best-effort message selected by the pool's
+    /// `LoadSheddingPolicy` (by default, the largest one), if any. Updates the
+    /// stats; and the priority queues, where applicable.
     ///
-    /// Time complexity: `O(log(self.len()))`.
+    /// Time complexity: `O(log(self.len()))`, plus the time complexity of the
+    /// load shedding policy.
     pub(super) fn shed_largest_message(&mut self) -> Option<(SomeReference, RequestOrResponse)> {
-        if let Some((_, id)) = self.size_queue.pop_last() {
+        if let Some(victim) = self.load_shedding_policy.select_victim(self) {
+            let id = Id::from(&victim);
             debug_assert_eq!(Class::BestEffort, id.class());
 
             let msg = self.take_impl(id).unwrap();
+            self.record_removal(id, &msg, RemovalReason::Shed, None);
             self.remove_from_deadline_queue(id, &msg);
+            self.remove_from_size_queue(id, &msg);
+            self.drop_stats.shed_message_count += 1;
+            self.drop_stats.shed_message_bytes += msg.count_bytes();
 
             debug_assert_eq!(Ok(()), self.check_invariants());
-            return Some((id.into(), msg));
+            return Some((victim, msg));
         }
 
         // Nothing to shed.
         None
     }
 
+    /// Returns the byte size of the best-effort message that
+    /// `shed_largest_message()` would remove next, without removing it; or
+    /// `None` if the pool holds no best-effort messages.
+    ///
+    /// Size queue entries are removed together with their messages, so there
+    /// are no stale entries to skip.
+    ///
+    /// Time complexity: `O(log(self.len()))`, plus the time complexity of the
+    /// load shedding policy.
+    pub(super) fn largest_best_effort_size(&self) -> Option<usize> {
+        let id = Id::from(&self.load_shedding_policy.select_victim(self)?);
+        debug_assert_eq!(Class::BestEffort, id.class());
+
+        self.messages.get(&id).map(|msg| msg.count_bytes())
+    }
+
+    /// Sheds the largest best-effort messages in the pool until at most
+    /// `max_best_effort` best-effort messages remain. Returns the shed messages,
+    /// largest first. Updates the stats; and the priority queues, where
+    /// applicable.
+    ///
+    /// Time complexity per shed message: `O(log(self.len()))`.
+    pub(super) fn shed_down_to_count(
+        &mut self,
+        max_best_effort: usize,
+    ) -> Vec<(SomeReference, RequestOrResponse)> {
+        let mut shed = Vec::new();
+        while self.best_effort_message_count() > max_best_effort {
+            match self.shed_largest_message() {
+                Some(shed_message) => shed.push(shed_message),
+                None => break,
+            }
+        }
+        shed
+    }
+
+    /// Removes and returns all best-effort messages in the pool, in load shedding
+    /// order (lowest priority, largest first). Guaranteed response messages are
+    /// left untouched. Updates the stats; and the priority queues, where
+    /// applicable. Removed messages are counted as shed.
+    ///
+    /// Cheaper than repeated `shed_largest_message()` calls, as the load shedding
+    /// queue and priorities are dropped wholesale.
+    ///
+    /// Time complexity: `O(k * log(self.len()))`, where `k` is the number of
+    /// best-effort messages.
+    pub(super) fn clear_best_effort(&mut self) -> Vec<(SomeReference, RequestOrResponse)> {
+        let guaranteed_message_count = self.guaranteed_message_count();
+        let size_queue = Arc::unwrap_or_clone(std::mem::take(&mut self.size_queue));
+        self.priorities = Default::default();
+        self.best_effort_message_bytes_by_priority = Default::default();
+
+        let cleared: Vec<_> = size_queue
+            .into_iter()
+            .rev()
+            .map(|(_, size_bytes, id)| {
+                let msg = self.take_impl(id).unwrap();
+                self.record_removal(id, &msg, RemovalReason::Shed, None);
+                self.remove_from_deadline_queue(id, &msg);
+                self.drop_stats.shed_message_count += 1;
+                self.drop_stats.shed_message_bytes += size_bytes;
+                (id.into(), msg)
+            })
+            .collect();
+
+        debug_assert_eq!(0, self.best_effort_message_count());
+        debug_assert_eq!(guaranteed_message_count, self.guaranteed_message_count());
+        debug_assert_eq!(Ok(()), self.check_invariants());
+        cleared
+    }
+
+    /// Records `now` as the earliest possible insertion time of all messages
+    /// inserted from here on. Times earlier than the latest observed time are
+    /// ignored.
+    ///
+    /// Time complexity: `O(log(self.len()))` amortized.
+    pub(super) fn observe_time(&mut self, now: Time) {
+        if let Some((_, last_time)) = self.time_checkpoints.last_key_value() {
+            if *last_time >= now {
+                return;
+            }
+        }
+        // Any existing checkpoint with the same key is superseded: no messages were
+        // inserted since it was recorded.
+        Arc::make_mut(&mut self.time_checkpoints).insert(self.message_id_generator, now);
+
+        // Drop all checkpoints older than the one dating the oldest message.
+        let oldest_generator = self
+            .messages
+            .first_key_value()
+            .map_or(self.message_id_generator, |(id, _)| id.generator());
+        while let Some(second) = self.time_checkpoints.keys().nth(1) {
+            if *second > oldest_generator {
+                break;
+            }
+            Arc::make_mut(&mut self.time_checkpoints).pop_first();
+        }
+    }
+
+    /// Returns the age of the oldest message in the pool, i.e. how long it has been
+    /// in the pool at `now`; or `None` if the pool is empty or the oldest message
+    /// was inserted before any time was observed.
+    ///
+    /// Messages are dated by the latest time observed before their insertion, so
+    /// the returned age is an upper bound, accurate to the frequency with which
+    /// the pool observes time.
+    ///
+    /// Time complexity: `O(log(self.len()))`.
+    pub(super) fn oldest_message_age(&self, now: Time) -> Option<Duration> {
+        // Message IDs are monotonically increasing, so the lowest one is the oldest.
+        let (oldest, _) = self.messages.first_key_value()?;
+        let (_, inserted_no_earlier_than) = self
+            .time_checkpoints
+            .range(..=oldest.generator())
+            .next_back()?;
+        Some(now.saturating_duration_since(*inserted_no_earlier_than))
+    }
+
     /// Returns the number of messages in the pool.
     pub(super) fn len(&self) -> usize {
         self.messages.len()
     }
 
+    /// Returns `true` if the pool holds no messages.
+    ///
+    /// Time complexity: `O(1)`.
+    pub(super) fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Returns the number of best-effort messages in the pool.
+    ///
+    /// Time complexity: `O(1)`.
+    pub(super) fn best_effort_message_count(&self) -> usize {
+        self.message_stats.best_effort_message_count
+    }
+
+    /// Returns the number of guaranteed response messages in the pool.
+    ///
+    /// Time complexity: `O(1)`.
+    pub(super) fn guaranteed_message_count(&self) -> usize {
+        self.messages.len() - self.message_stats.best_effort_message_count
+    }
+
     /// Returns a reference to the pool's message stats.
     pub(super) fn message_stats(&self) -> &MessageStats {
         &self.message_stats
     }
 
-    /// Computes message stats from scratch. Used when deserializing and in
-    /// `debug_assert!()` checks.
+    /// Returns the lifetime of guaranteed response call requests in output queues.
+    pub(super) fn request_lifetime(&self) -> Duration {
+        self.request_lifetime
+    }
+
+    /// Sets the lifetime of guaranteed response call requests in output queues.
+    /// Only applies to requests inserted from now on: the deadlines of requests
+    /// already in the pool are unchanged.
+    pub(super) fn set_request_lifetime(&mut self, request_lifetime: Duration) {
+        self.request_lifetime = request_lifetime;
+    }
+
+    /// Returns the maximum number of messages the pool may hold; `None` if
+    /// unlimited.
+    pub(super) fn max_messages(&self) -> Option<usize> {
+        self.max_messages
+    }
+
+    /// Returns the policy selecting the best-effort message to shed next.
+    pub(super) fn load_shedding_policy(&self) -> &P {
+        &self.load_shedding_policy
+    }
+
+    /// Limits the pool to at most `max_messages` messages (or lifts the limit,
+    /// if `None`). Request inserts into a full pool fail with a `PoolFullError`;
+    /// responses fill reserved slots, so they are exempt. Messages already in
+    /// the pool are retained, even if over the limit.
+    pub(super) fn set_max_messages(&mut self, max_messages: Option<usize>) {
+        self.max_messages = max_messages;
+    }
+
+    /// Sets the policy selecting the best-effort message to shed next.
+    pub(super) fn set_load_shedding_policy(&mut self, load_shedding_policy: P) {
+        self.load_shedding_policy = load_shedding_policy;
+    }
+
+    /// Returns the counts of messages expired or shed since the previous call,
+    /// resetting them.
+    pub(super) fn take_drop_stats(&mut self) -> MessageDropStats {
+        std::mem::take(&mut self.drop_stats)
+    }
+
+    /// Sets how many removed messages `recent_removals()` returns, dropping the
+    /// oldest records in excess of `capacity`. Zero (the default) disables the
+    /// removal log.
     ///
-    /// Time complexity: `O(n)`.
-    fn calculate_message_stats(messages: &BTreeMap<Id, RequestOrResponse>) -> MessageStats {
-        let mut stats = MessageStats::default();
-        for (id, msg) in messages.iter() {
-            stats += MessageStats::stats_delta(msg, id.context());
-        }
-        stats
+    /// Like the removal log itself, this is neither persisted nor compared.
+    pub(super) fn set_removal_log_capacity(&mut self, capacity: usize) {
+        self.removal_log.set_capacity(capacity);
     }
 
-    /// Invariant check for use at loading time and in `debug_asserts`.
+    /// Returns records of the most recently removed messages (up to the
+    /// capacity of the removal log), oldest first. Meant for debugging, e.g.
+    /// whether a message behind a timeout reject expired or was shed.
+    pub(super) fn recent_removals(&self) -> Vec<RemovalRecord> {
+        self.removal_log.records.iter().copied().collect()
+    }
+
+    /// Makes a memory reservation for a guaranteed response. See
+    /// `MessageStats::reserve_guaranteed_response_slot()`.
+    pub(super) fn reserve_guaranteed_response_slot(&mut self) {
+        self.message_stats.reserve_guaranteed_response_slot();
+    }
+
+    /// Releases a guaranteed response memory reservation. See
+    /// `MessageStats::release_reservation()`.
+    pub(super) fn release_reservation(&mut self) {
+        self.message_stats.release_reservation();
+    }
+
+    /// Invariant check for use at loading time, in `debug_asserts` and in tests.
+    /// Returns all violations found by `validate_invariants()`, joined into a
+    /// single error message.
+    ///
+    /// Time complexity: `O(n * log(n))`.
+    pub(crate) fn check_invariants(&self) -> Result<(), String> {
+        self.validate_invariants()
+            .map_err(|violations| violations.join("; "))
+    }
+
+    /// Validates all pool invariants, returning every violation found (rather
+    /// than just the first one). Verifies that:
+    ///
+    ///  * the message stats match stats computed from scratch;
+    ///  * every message's `Id` matches its kind and class;
+    ///  * the deadline and load shedding queues hold exactly the expected entries
+    ///    (i.e. all best-effort messages are in the load shedding queue and all
+    ///    messages that should expire are in the deadline queue) and no more
+    ///    entries than there are messages;
+    ///  * every deadline queue entry of a message in the pool records the
+    ///    message's (possibly implicit) deadline;
+    ///  * every load shedding queue entry of a message in the pool records the
+    ///    message's size;
+    ///  * explicit priorities are only recorded for best-effort messages in the
+    ///    pool; and per-priority byte counts match the messages;
+    ///  * no message, queue entry or time checkpoint references an `Id` at or
+    ///    beyond `message_id_generator`.
+    ///
+    /// Available in release builds, so it can also be used e.g. by checkpoint
+    /// verification tools.
     ///
     /// Time complexity: `O(n * log(n))`.
-    fn check_invariants(&self) -> Result<(), String> {
+    pub(crate) fn validate_invariants(&self) -> Result<(), Vec<String>> {
+        let mut violations = Vec::new();
+
+        // Running stats must match stats computed from scratch.
+        let expected_message_stats = MessagePool::calculate_message_stats(
+            &self.messages,
+            self.message_stats.guaranteed_response_reservations,
+        );
+        if self.message_stats != expected_message_stats {
+            violations.push(format!(
+                "Unexpected message stats: expected {:?}, actual {:?}",
+                expected_message_stats, self.message_stats
+            ));
+        }
+
         // `Id` kind and class must match those of the message.
-        self.messages.iter().try_for_each(|(id, msg)| {
+        for (id, msg) in self.messages.iter() {
             if id.kind() != Kind::from(msg) {
-                return Err(format!(
+                violations.push(format!(
                     "Message kind mismatch: message {:?}, Id {:?}",
                     Kind::from(msg),
                     id.kind()
                 ));
             }
             if id.class() != Class::from(msg) {
-                return Err(format!(
+                violations.push(format!(
                     "Message class mismatch: message {:?}, Id {:?}",
                     Class::from(msg),
                     id.class()
                 ));
             }
-            Ok(())
-        })?;
+        }
 
         // Validate the priority queues.
-        let (expected_deadline_queue, expected_size_queue) = Self::calculate_priority_queues(
+        let (expected_deadline_queue, expected_size_queue) = MessagePool::calculate_priority_queues(
             &self.messages,
             &self.outbound_guaranteed_request_deadlines,
+            &self.priorities,
         );
-        if self.deadline_queue != expected_deadline_queue {
-            return Err(format!(
+        if *self.deadline_queue != expected_deadline_queue {
+            violations.push(format!(
                 "Unexpected deadline queue: expected {:?}, actual {:?}",
                 expected_deadline_queue, self.deadline_queue
             ));
         }
-        if self.size_queue != expected_size_queue {
-            return Err(format!(
+        if *self.size_queue != expected_size_queue {
+            violations.push(format!(
                 "Unexpected load shedding queue: expected {:?}, actual {:?}",
                 expected_size_queue, self.size_queue
             ));
         }
 
+        // Neither priority queue may hold more entries than there are messages.
+        if self.deadline_queue.len() > self.messages.len()
+            || self.size_queue.len() > self.messages.len()
+        {
+            violations.push(format!(
+                "Priority queues larger than the pool: {} messages, deadline queue {}, load shedding queue {}",
+                self.messages.len(),
+                self.deadline_queue.len(),
+                self.size_queue.len()
+            ));
+        }
+
+        // Deadline queue entries of live messages record the message's deadline (the
+        // implicit one, for outbound guaranteed response requests).
+        for (deadline, id) in self.deadline_queue.iter() {
+            let Some(msg) = self.messages.get(&id) else {
+                continue;
+            };
+            let expected_deadline = if id.is_outbound_guaranteed_request() {
+                self.outbound_guaranteed_request_deadlines.get(&id).copied()
+            } else {
+                Some(msg.deadline())
+            };
+            if expected_deadline != Some(deadline) {
+                violations.push(format!(
+                    "Deadline queue entry mismatch: `Id` {}, queue deadline {:?}, message deadline {:?}",
+                    id.0, deadline, expected_deadline
+                ));
+            }
+        }
+
+        // Only best-effort messages in the pool may have explicit priorities; and
+        // `Priority::Normal` is never recorded explicitly.
+        for (id, priority) in self.priorities.iter() {
+            if !self.messages.contains_key(id)
+                || id.class() != Class::BestEffort
+                || *priority == Priority::Normal
+            {
+                violations.push(format!(
+                    "Unexpected message priority: `Id` {}, priority {:?}",
+                    id.0, priority
+                ));
+            }
+        }
+        let expected_bytes_by_priority =
+            MessagePool::calculate_bytes_by_priority(&self.messages, &self.priorities);
+        if self.best_effort_message_bytes_by_priority != expected_bytes_by_priority {
+            violations.push(format!(
+                "Unexpected best-effort message bytes by priority: expected {:?}, actual {:?}",
+                expected_bytes_by_priority, self.best_effort_message_bytes_by_priority
+            ));
+        }
+
+        // Load shedding queue entries of live messages record the message's size.
+        for (_, size_bytes, id) in self.size_queue.iter() {
+            let Some(msg) = self.messages.get(id) else {
+                continue;
+            };
+            if *size_bytes != msg.count_bytes() {
+                violations.push(format!(
+                    "Load shedding queue entry mismatch: `Id` {}, queue size {}, message size {}",
+                    id.0,
+                    size_bytes,
+                    msg.count_bytes()
+                ));
+            }
+        }
+
+        // All best-effort messages (and only best-effort messages) are in the load
+        // shedding queue.
+        if self.message_stats.best_effort_message_count != self.size_queue.len() {
+            violations.push(format!(
+                "Best-effort message count mismatch: stats {}, load shedding queue {}",
+                self.message_stats.best_effort_message_count,
+                self.size_queue.len()
+            ));
+        }
+
         // Validate that `outbound_guaranteed_request_deadlines` holds all outbound
         // guaranteed response requests (and nothing else).
-        let mut expected_outbound_guaranteed_request_ids = BTreeSet::new();
-        self.messages.keys().for_each(|id| {
-            if id.is_outbound_guaranteed_request() {
-                expected_outbound_guaranteed_request_ids.insert(id);
-            }
-        });
+        let expected_outbound_guaranteed_request_ids = self
+            .messages
+            .keys()
+            .filter(|id| id.is_outbound_guaranteed_request())
+            .collect::<BTreeSet<_>>();
         if self
             .outbound_guaranteed_request_deadlines
             .keys()
             .collect::<BTreeSet<_>>()
             != expected_outbound_guaranteed_request_ids
         {
-            return Err(format!(
+            violations.push(format!(
                 "Unexpected outbound guaranteed request deadlines: expected keys {:?}, actual {:?}",
                 expected_outbound_guaranteed_request_ids,
                 self.outbound_guaranteed_request_deadlines
             ));
         }
 
-        if !self.messages.is_empty() {
-            // Validate `message_id_generator` against the largest seen `Id`.
-            let mut max_message_id = 0;
-            self.messages.keys().for_each(|id| {
-                max_message_id = max_message_id.max(id.0);
-            });
+        // Validate `message_id_generator` against the largest seen `Id`.
+        if let Some(max_message_id) = self.messages.keys().map(|id| id.0).max() {
             if max_message_id >> Id::BITMASK_LEN >= self.message_id_generator {
-                return Err(format!(
+                violations.push(format!(
                     "`Id` out of bounds: max `Id`: {}, message_id_generator: {}",
                     max_message_id, self.message_id_generator
                 ));
             }
         }
 
-        Ok(())
+        // No queue entry may reference an `Id` that was not yet generated.
+        let out_of_bounds = self
+            .deadline_queue
+            .iter()
+            .map(|(_, id)| id)
+            .chain(self.size_queue.iter().map(|(_, _, id)| *id))
+            .chain(self.outbound_guaranteed_request_deadlines.keys().copied())
+            .find(|id| id.generator() >= self.message_id_generator);
+        if let Some(id) = out_of_bounds {
+            violations.push(format!(
+                "Queue entry `Id` out of bounds: `Id`: {}, message_id_generator: {}",
+                id.0, self.message_id_generator
+            ));
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+impl Default for MessagePool {
+    fn default() -> Self {
+        Self::with_load_shedding_policy(LoadSheddingPolicyKind::default())
+    }
+}
+
+impl MessagePool {
+    /// Computes message stats from scratch. Used when deserializing checkpoints
+    /// (to validate the persisted stats or, for checkpoints that predate them,
+    /// in their stead) and in `debug_assert!()` checks. Takes the number of
+    /// guaranteed response memory reservations from the caller, as it cannot be
+    /// computed from the messages.
+    ///
+    /// Time complexity: `O(n)`.
+    fn calculate_message_stats(
+        messages: &BTreeMap<Id, RequestOrResponse>,
+        guaranteed_response_reservations: usize,
+    ) -> MessageStats {
+        let mut stats = MessageStats {
+            guaranteed_response_reservations,
+            ..Default::default()
+        };
+        for (id, msg) in messages.iter() {
+            stats += MessageStats::stats_delta(msg, id.context());
+        }
+        stats
     }
 
     /// Calculates the deadline and load shedding priority queues for the given
-    /// messages and outbound guaranteed response request (implicit) deadlines.
+    /// messages, outbound guaranteed response request (implicit) deadlines and
+    /// (non-default) message priorities.
     ///
     /// Time complexity: `O(n * log(n))`.
-    #[allow(clippy::type_complexity)]
     fn calculate_priority_queues(
         messages: &BTreeMap<Id, RequestOrResponse>,
         outbound_guaranteed_request_deadlines: &BTreeMap<Id, CoarseTime>,
-    ) -> (BTreeSet<(CoarseTime, Id)>, BTreeSet<(usize, Id)>) {
-        let mut expected_deadline_queue = BTreeSet::new();
+        priorities: &BTreeMap<Id, Priority>,
+    ) -> (DeadlineQueue, BTreeSet<(Reverse<Priority>, usize, Id)>) {
+        let mut expected_deadline_queue = DeadlineQueue::default();
         let mut expected_size_queue = BTreeSet::new();
         messages.iter().for_each(|(id, msg)| {
+            let priority = Reverse(priorities.get(id).copied().unwrap_or_default());
             use Class::*;
             use Context::*;
             use Kind::*;
@@ -767,7 +1824,7 @@ impl MessagePool {
                 // Outbound guaranteed response requests have (separately recorded) deadlines.
                 (Outbound, GuaranteedResponse, Request) => {
                     let deadline = outbound_guaranteed_request_deadlines.get(id).unwrap();
-                    expected_deadline_queue.insert((*deadline, *id));
+                    expected_deadline_queue.insert(*deadline, *id);
                 }
 
                 // All other guaranteed response messages neither expire nor can be shed.
@@ -776,18 +1833,35 @@ impl MessagePool {
                 // Inbound best-effort responses don't have expiration deadlines, but can be
                 // shed.
                 (Inbound, BestEffort, Response) => {
-                    expected_size_queue.insert((msg.count_bytes(), *id));
+                    expected_size_queue.insert((priority, msg.count_bytes(), *id));
                 }
 
                 // All other best-effort messages are enqueued in both priority queues.
                 (_, BestEffort, _) => {
-                    expected_deadline_queue.insert((msg.deadline(), *id));
-                    expected_size_queue.insert((msg.count_bytes(), *id));
+                    expected_deadline_queue.insert(msg.deadline(), *id);
+                    expected_size_queue.insert((priority, msg.count_bytes(), *id));
                 }
             }
         });
         (expected_deadline_queue, expected_size_queue)
     }
+
+    /// Calculates the total byte size of best-effort messages by priority.
+    ///
+    /// Time complexity: `O(n * log(n))`.
+    fn calculate_bytes_by_priority(
+        messages: &BTreeMap<Id, RequestOrResponse>,
+        priorities: &BTreeMap<Id, Priority>,
+    ) -> [usize; Priority::COUNT] {
+        let mut bytes_by_priority = [0; Priority::COUNT];
+        for (id, msg) in messages.iter() {
+            if id.class() == Class::BestEffort {
+                let priority = priorities.get(id).copied().unwrap_or_default();
+                bytes_by_priority[priority as usize] += msg.count_bytes();
+            }
+        }
+        bytes_by_priority
+    }
 }
 
 impl From<&MessagePool> for pb_queues::MessagePool {
@@ -799,7 +1873,7 @@ impl From<&MessagePool> for pb_queues::MessagePool {
                 .messages
                 .iter()
                 .map(|(id, message)| Entry {
-                    id: id.0,
+                    id: (*id).into(),
                     message: Some(message.into()),
                 })
                 .collect(),
@@ -808,24 +1882,84 @@ impl From<&MessagePool> for pb_queues::MessagePool {
                 .iter()
                 .map(|(id, deadline)| MessageDeadline {
                     deadline_seconds: deadline.as_secs_since_unix_epoch(),
-                    id: id.0,
+                    id: (*id).into(),
                 })
                 .collect(),
             message_id_generator: item.message_id_generator,
+            request_lifetime_nanos: (item.request_lifetime != REQUEST_LIFETIME)
+                .then_some(item.request_lifetime.as_nanos() as u64),
+            priorities: item
+                .priorities
+                .iter()
+                .map(|(id, priority)| MessagePriority {
+                    id: (*id).into(),
+                    priority: *priority as u32,
+                })
+                .collect(),
+            message_stats: Some((&item.message_stats).into()),
         }
     }
 }
 
-impl TryFrom<pb_queues::MessagePool> for MessagePool {
+impl From<&MessageStats> for pb_queues::message_pool::MessageStats {
+    fn from(item: &MessageStats) -> Self {
+        Self {
+            size_bytes: item.size_bytes as u64,
+            best_effort_message_bytes: item.best_effort_message_bytes as u64,
+            best_effort_message_count: item.best_effort_message_count as u64,
+            guaranteed_responses_size_bytes: item.guaranteed_responses_size_bytes as u64,
+            oversized_guaranteed_requests_extra_bytes: item
+                .oversized_guaranteed_requests_extra_bytes
+                as u64,
+            inbound_size_bytes: item.inbound_size_bytes as u64,
+            inbound_message_count: item.inbound_message_count as u64,
+            inbound_response_count: item.inbound_response_count as u64,
+            inbound_guaranteed_request_count: item.inbound_guaranteed_request_count as u64,
+            inbound_guaranteed_response_count: item.inbound_guaranteed_response_count as u64,
+            outbound_message_count: item.outbound_message_count as u64,
+        }
+    }
+}
+
+/// Decodes `MessageStats` from their protobuf representation plus the number of
+/// guaranteed response memory reservations (persisted by `CanisterQueues`).
+impl From<(pb_queues::message_pool::MessageStats, usize)> for MessageStats {
+    fn from(
+        (item, guaranteed_response_reservations): (pb_queues::message_pool::MessageStats, usize),
+    ) -> Self {
+        Self {
+            size_bytes: item.size_bytes as usize,
+            best_effort_message_bytes: item.best_effort_message_bytes as usize,
+            best_effort_message_count: item.best_effort_message_count as usize,
+            guaranteed_responses_size_bytes: item.guaranteed_responses_size_bytes as usize,
+            oversized_guaranteed_requests_extra_bytes: item
+                .oversized_guaranteed_requests_extra_bytes
+                as usize,
+            inbound_size_bytes: item.inbound_size_bytes as usize,
+            inbound_message_count: item.inbound_message_count as usize,
+            inbound_response_count: item.inbound_response_count as usize,
+            inbound_guaranteed_request_count: item.inbound_guaranteed_request_count as usize,
+            inbound_guaranteed_response_count: item.inbound_guaranteed_response_count as usize,
+            outbound_message_count: item.outbound_message_count as usize,
+            guaranteed_response_reservations,
+        }
+    }
+}
+
+/// Decodes a `MessagePool` from its protobuf representation plus the number of
+/// guaranteed response memory reservations (persisted by `CanisterQueues`).
+impl TryFrom<(pb_queues::MessagePool, usize)> for MessagePool {
     type Error = ProxyDecodeError;
-    fn try_from(item: pb_queues::MessagePool) -> Result<Self, Self::Error> {
+    fn try_from(
+        (item, guaranteed_response_reservations): (pb_queues::MessagePool, usize),
+    ) -> Result<Self, Self::Error> {
         let message_count = item.messages.len();
 
         let messages: BTreeMap<_, _> = item
             .messages
             .into_iter()
             .map(|entry| {
-                let id = Id(entry.id);
+                let id = Id::from(entry.id);
                 let message = try_from_option_field(entry.message, "MessagePool::Entry::message")?;
                 Ok((id, message))
             })
@@ -833,44 +1967,99 @@ impl TryFrom<pb_queues::MessagePool> for MessagePool {
         if messages.len() != message_count {
             return Err(ProxyDecodeError::Other("Duplicate Id".to_string()));
         }
-        let message_stats = Self::calculate_message_stats(&messages);
+        let message_stats = match item.message_stats {
+            Some(message_stats) => {
+                MessageStats::from((message_stats, guaranteed_response_reservations))
+            }
+            // Checkpoint written before message stats were persisted.
+            None => Self::calculate_message_stats(&messages, guaranteed_response_reservations),
+        };
 
-        let outbound_guaranteed_request_deadlines = item
+        let outbound_guaranteed_request_deadlines: BTreeMap<_, _> = item
             .outbound_guaranteed_request_deadlines
             .into_iter()
             .map(|entry| {
-                let id = Id(entry.id);
+                let id = Id::from(entry.id);
+                if !id.is_outbound_guaranteed_request() {
+                    return Err(ProxyDecodeError::Other(format!(
+                        "Outbound guaranteed request deadline for non outbound guaranteed request: {}",
+                        entry.id
+                    )));
+                }
                 let deadline = CoarseTime::from_secs_since_unix_epoch(entry.deadline_seconds);
-                (id, deadline)
+                Ok((id, deadline))
             })
-            .collect();
+            .collect::<Result<_, Self::Error>>()?;
 
-        let (deadline_queue, size_queue) =
-            Self::calculate_priority_queues(&messages, &outbound_guaranteed_request_deadlines);
+        let priority_count = item.priorities.len();
+        let priorities: BTreeMap<_, _> = item
+            .priorities
+            .into_iter()
+            .map(|entry| {
+                let id = Id::from(entry.id);
+                if id.class() != Class::BestEffort || !messages.contains_key(&id) {
+                    return Err(ProxyDecodeError::Other(format!(
+                        "Priority for non best-effort or missing message: {}",
+                        entry.id
+                    )));
+                }
+                Ok((id, Priority::try_from(entry.priority)?))
+            })
+            .collect::<Result<_, Self::Error>>()?;
+        if priorities.len() != priority_count {
+            return Err(ProxyDecodeError::Other(
+                "Duplicate message priority".to_string(),
+            ));
+        }
+
+        let (deadline_queue, size_queue) = Self::calculate_priority_queues(
+            &messages,
+            &outbound_guaranteed_request_deadlines,
+            &priorities,
+        );
+        let best_effort_message_bytes_by_priority =
+            Self::calculate_bytes_by_priority(&messages, &priorities);
 
         let res = Self {
-            messages,
-            outbound_guaranteed_request_deadlines,
+            messages: Arc::new(messages),
+            outbound_guaranteed_request_deadlines: Arc::new(outbound_guaranteed_request_deadlines),
             message_stats,
-            deadline_queue,
-            size_queue,
+            deadline_queue: Arc::new(deadline_queue),
+            size_queue: Arc::new(size_queue),
+            priorities: Arc::new(priorities),
+            best_effort_message_bytes_by_priority,
             message_id_generator: item.message_id_generator,
+            time_checkpoints: Default::default(),
+            request_lifetime: item
+                .request_lifetime_nanos
+                .map_or(REQUEST_LIFETIME, Duration::from_nanos),
+            max_messages: None,
+            load_shedding_policy: Default::default(),
+            drop_stats: Default::default(),
+            removal_log: Default::default(),
         };
 
-        // Ensure that we've built a valid `MessagePool`.
-        res.check_invariants().map_err(ProxyDecodeError::Other)?;
+        // Ensure that we've built a valid `MessagePool`, including persisted message
+        // stats that match the messages: the `O(n)` recomputation is dwarfed by
+        // rebuilding the priority queues above.
+        res.validate_invariants()
+            .map_err(|violations| ProxyDecodeError::Other(violations.join("; ")))?;
 
         Ok(res)
     }
 }
 
-/// Running stats for all messages in a `MessagePool`.
+/// Running stats for all messages in a `MessagePool`, plus the memory
+/// reservations for guaranteed responses.
 ///
-/// Slot reservations and memory reservations for guaranteed responses, being
-/// queue metrics, are tracked separately by `CanisterQueues`.
+/// Slot reservations, being queue metrics, are tracked separately by
+/// `CanisterQueues`.
 ///
 /// All operations (computing stats deltas and retrieving the stats) are
 /// constant time.
+///
+/// Persisted in checkpoints along with the pool's messages and validated
+/// against them on load.
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
 pub(super) struct MessageStats {
     /// Total byte size of all messages in the pool.
@@ -880,6 +2069,9 @@ pub(super) struct MessageStats {
     /// contains zero best-effort messages.
     pub(super) best_effort_message_bytes: usize,
 
+    /// Count of best-effort messages in the pool.
+    pub(super) best_effort_message_count: usize,
+
     /// Total byte size of all guaranteed responses in the pool.
     pub(super) guaranteed_responses_size_bytes: usize,
 
@@ -913,15 +2105,45 @@ pub(super) struct MessageStats {
 
     /// Count of messages in output queues.
     pub(super) outbound_message_count: usize,
+
+    /// Count of guaranteed response memory reservations across input and output
+    /// queues. This is equivalent to the number of outstanding (inbound or
+    /// outbound) guaranteed response calls and is used for computing message
+    /// memory usage (as `MAX_RESPONSE_COUNT_BYTES` per request).
+    ///
+    /// Note that this is different from slots reserved for responses (whether
+    /// best effort or guaranteed), which are used to implement backpressure.
+    ///
+    /// Unlike all other stats, this is not computed from the pool contents (a
+    /// reservation outlives the request that made it), but maintained explicitly
+    /// by `CanisterQueues`. It is validated against the number of unresponded
+    /// guaranteed response callbacks and call contexts in the `CallContextManager`.
+    pub(super) guaranteed_response_reservations: usize,
 }
 
 impl MessageStats {
     /// Returns the memory usage of the guaranteed response messages in the pool,
-    /// excluding memory reservations for guaranteed responses.
+    /// plus that of memory reservations for guaranteed responses.
     ///
     /// Complexity: `O(1)`.
     pub fn guaranteed_response_memory_usage(&self) -> usize {
-        self.guaranteed_responses_size_bytes + self.oversized_guaranteed_requests_extra_bytes
+        self.guaranteed_response_reservations * MAX_RESPONSE_COUNT_BYTES
+            + self.guaranteed_responses_size_bytes
+            + self.oversized_guaranteed_requests_extra_bytes
+    }
+
+    /// Makes a memory reservation for a guaranteed response, to be consumed by
+    /// said response via `release_reservation()`.
+    pub(super) fn reserve_guaranteed_response_slot(&mut self) {
+        self.guaranteed_response_reservations += 1;
+    }
+
+    /// Releases a guaranteed response memory reservation, e.g. when enqueueing
+    /// the guaranteed response that it was made for.
+    pub(super) fn release_reservation(&mut self) {
+        debug_assert!(self.guaranteed_response_reservations > 0);
+        self.guaranteed_response_reservations =
+            self.guaranteed_response_reservations.saturating_sub(1);
     }
 
     /// Calculates the change in stats caused by pushing (+) or popping (-) the
@@ -950,11 +2172,14 @@ impl MessageStats {
         let guaranteed_responses_size_bytes = 0;
         let inbound_response_count = 0;
         let inbound_guaranteed_response_count = 0;
+        // Memory reservations are managed explicitly.
+        let guaranteed_response_reservations = 0;
 
         match (context, class) {
             (Inbound, GuaranteedResponse) => MessageStats {
                 size_bytes,
                 best_effort_message_bytes: 0,
+                best_effort_message_count: 0,
                 guaranteed_responses_size_bytes,
                 oversized_guaranteed_requests_extra_bytes: size_bytes
                     .saturating_sub(MAX_RESPONSE_COUNT_BYTES),
@@ -964,10 +2189,12 @@ impl MessageStats {
                 inbound_guaranteed_request_count: 1,
                 inbound_guaranteed_response_count,
                 outbound_message_count: 0,
+                guaranteed_response_reservations,
             },
             (Inbound, BestEffort) => MessageStats {
                 size_bytes,
                 best_effort_message_bytes: size_bytes,
+                best_effort_message_count: 1,
                 guaranteed_responses_size_bytes,
                 oversized_guaranteed_requests_extra_bytes: 0,
                 inbound_size_bytes: size_bytes,
@@ -976,10 +2203,12 @@ impl MessageStats {
                 inbound_guaranteed_request_count: 0,
                 inbound_guaranteed_response_count,
                 outbound_message_count: 0,
+                guaranteed_response_reservations,
             },
             (Outbound, GuaranteedResponse) => MessageStats {
                 size_bytes,
                 best_effort_message_bytes: 0,
+                best_effort_message_count: 0,
                 guaranteed_responses_size_bytes,
                 oversized_guaranteed_requests_extra_bytes: size_bytes
                     .saturating_sub(MAX_RESPONSE_COUNT_BYTES),
@@ -989,10 +2218,12 @@ impl MessageStats {
                 inbound_guaranteed_request_count: 0,
                 inbound_guaranteed_response_count,
                 outbound_message_count: 1,
+                guaranteed_response_reservations,
             },
             (Outbound, BestEffort) => MessageStats {
                 size_bytes,
                 best_effort_message_bytes: size_bytes,
+                best_effort_message_count: 1,
                 guaranteed_responses_size_bytes,
                 oversized_guaranteed_requests_extra_bytes: 0,
                 inbound_size_bytes: 0,
@@ -1001,6 +2232,7 @@ impl MessageStats {
                 inbound_guaranteed_request_count: 0,
                 inbound_guaranteed_response_count,
                 outbound_message_count: 1,
+                guaranteed_response_reservations,
             },
         }
     }
@@ -1021,11 +2253,14 @@ impl MessageStats {
         // This is a response, request stats are all unaffected.
         let oversized_guaranteed_requests_extra_bytes = 0;
         let inbound_guaranteed_request_count = 0;
+        // Memory reservations are managed explicitly.
+        let guaranteed_response_reservations = 0;
 
         match (context, class) {
             (Inbound, GuaranteedResponse) => MessageStats {
                 size_bytes,
                 best_effort_message_bytes: 0,
+                best_effort_message_count: 0,
                 guaranteed_responses_size_bytes: size_bytes,
                 oversized_guaranteed_requests_extra_bytes,
                 inbound_size_bytes: size_bytes,
@@ -1034,10 +2269,12 @@ impl MessageStats {
                 inbound_guaranteed_request_count,
                 inbound_guaranteed_response_count: 1,
                 outbound_message_count: 0,
+                guaranteed_response_reservations,
             },
             (Inbound, BestEffort) => MessageStats {
                 size_bytes,
                 best_effort_message_bytes: size_bytes,
+                best_effort_message_count: 1,
                 guaranteed_responses_size_bytes: 0,
                 oversized_guaranteed_requests_extra_bytes,
                 inbound_size_bytes: size_bytes,
@@ -1046,10 +2283,12 @@ impl MessageStats {
                 inbound_guaranteed_request_count,
                 inbound_guaranteed_response_count: 0,
                 outbound_message_count: 0,
+                guaranteed_response_reservations,
             },
             (Outbound, GuaranteedResponse) => MessageStats {
                 size_bytes,
                 best_effort_message_bytes: 0,
+                best_effort_message_count: 0,
                 guaranteed_responses_size_bytes: size_bytes,
                 oversized_guaranteed_requests_extra_bytes,
                 inbound_size_bytes: 0,
@@ -1058,10 +2297,12 @@ impl MessageStats {
                 inbound_guaranteed_request_count,
                 inbound_guaranteed_response_count: 0,
                 outbound_message_count: 1,
+                guaranteed_response_reservations,
             },
             (Outbound, BestEffort) => MessageStats {
                 size_bytes,
                 best_effort_message_bytes: size_bytes,
+                best_effort_message_count: 1,
                 guaranteed_responses_size_bytes: 0,
                 oversized_guaranteed_requests_extra_bytes,
                 inbound_size_bytes: 0,
@@ -1070,6 +2311,7 @@ impl MessageStats {
                 inbound_guaranteed_request_count,
                 inbound_guaranteed_response_count: 0,
                 outbound_message_count: 1,
+                guaranteed_response_reservations,
             },
         }
     }
@@ -1080,6 +2322,7 @@ impl AddAssign<MessageStats> for MessageStats {
         let MessageStats {
             size_bytes,
             best_effort_message_bytes,
+            best_effort_message_count,
             guaranteed_responses_size_bytes,
             oversized_guaranteed_requests_extra_bytes,
             inbound_size_bytes,
@@ -1088,9 +2331,11 @@ impl AddAssign<MessageStats> for MessageStats {
             inbound_guaranteed_request_count,
             inbound_guaranteed_response_count,
             outbound_message_count,
+            guaranteed_response_reservations,
         } = rhs;
         self.size_bytes += size_bytes;
         self.best_effort_message_bytes += best_effort_message_bytes;
+        self.best_effort_message_count += best_effort_message_count;
         self.guaranteed_responses_size_bytes += guaranteed_responses_size_bytes;
         self.oversized_guaranteed_requests_extra_bytes += oversized_guaranteed_requests_extra_bytes;
         self.inbound_size_bytes += inbound_size_bytes;
@@ -1099,14 +2344,21 @@ impl AddAssign<MessageStats> for MessageStats {
         self.inbound_guaranteed_request_count += inbound_guaranteed_request_count;
         self.inbound_guaranteed_response_count += inbound_guaranteed_response_count;
         self.outbound_message_count += outbound_message_count;
+        self.guaranteed_response_reservations += guaranteed_response_reservations;
     }
 }
 
+/// Subtracts `rhs` from `self`, field by field.
+///
+/// Every subtracted delta must correspond to a message previously added to the
+/// stats (persisted stats are validated against the messages on load), so this
+/// panics on underflow in debug builds.
 impl SubAssign<MessageStats> for MessageStats {
     fn sub_assign(&mut self, rhs: MessageStats) {
         let MessageStats {
             size_bytes,
             best_effort_message_bytes,
+            best_effort_message_count,
             guaranteed_responses_size_bytes,
             oversized_guaranteed_requests_extra_bytes,
             inbound_size_bytes,
@@ -1115,9 +2367,11 @@ impl SubAssign<MessageStats> for MessageStats {
             inbound_guaranteed_request_count,
             inbound_guaranteed_response_count,
             outbound_message_count,
+            guaranteed_response_reservations,
         } = rhs;
         self.size_bytes -= size_bytes;
         self.best_effort_message_bytes -= best_effort_message_bytes;
+        self.best_effort_message_count -= best_effort_message_count;
         self.guaranteed_responses_size_bytes -= guaranteed_responses_size_bytes;
         self.oversized_guaranteed_requests_extra_bytes -= oversized_guaranteed_requests_extra_bytes;
         self.inbound_size_bytes -= inbound_size_bytes;
@@ -1126,5 +2380,6 @@ impl SubAssign<MessageStats> for MessageStats {
         self.inbound_guaranteed_request_count -= inbound_guaranteed_request_count;
         self.inbound_guaranteed_response_count -= inbound_guaranteed_response_count;
         self.outbound_message_count -= outbound_message_count;
+        self.guaranteed_response_reservations -= guaranteed_response_reservations;
     }
 }
@@ -4,6 +4,8 @@ use ic_test_utilities_types::messages::{RequestBuilder, ResponseBuilder};
 use ic_types::messages::{Payload, MAX_INTER_CANISTER_PAYLOAD_IN_BYTES_U64};
 use ic_types::time::UNIX_EPOCH;
 use maplit::btreeset;
+use proptest::prelude::*;
+use prost::Message;
 use std::collections::BTreeSet;
 use std::time::Duration;
 
@@ -16,42 +18,58 @@ fn test_insert() {
     let mut pool = MessagePool::default();
 
     // Insert one message of each kind / class / context.
-    let id1: Id = pool.insert_inbound(request(NO_DEADLINE).into()).into();
+    let id1: Id = pool
+        .insert_inbound(request(NO_DEADLINE).into())
+        .unwrap()
+        .into();
     assert_eq!(Request, id1.kind());
     assert_eq!(Inbound, id1.context());
     assert_eq!(GuaranteedResponse, id1.class());
-    let id2: Id = pool.insert_inbound(request(time(20)).into()).into();
+    let id2: Id = pool
+        .insert_inbound(request(time(20)).into())
+        .unwrap()
+        .into();
     assert_eq!(Request, id2.kind());
     assert_eq!(Inbound, id2.context());
     assert_eq!(BestEffort, id2.class());
-    let id3: Id = pool.insert_inbound(response(NO_DEADLINE).into()).into();
+    let id3: Id = pool
+        .insert_inbound(response(NO_DEADLINE).into())
+        .unwrap()
+        .into();
     assert_eq!(Response, id3.kind());
     assert_eq!(Inbound, id3.context());
     assert_eq!(GuaranteedResponse, id3.class());
-    let id4: Id = pool.insert_inbound(response(time(40)).into()).into();
+    let id4: Id = pool
+        .insert_inbound(response(time(40)).into())
+        .unwrap()
+        .into();
     assert_eq!(Response, id4.kind());
     assert_eq!(Inbound, id4.context());
     assert_eq!(BestEffort, id4.class());
     let id5: Id = pool
         .insert_outbound_request(request(NO_DEADLINE).into(), time(50).into())
+        .unwrap()
         .into();
     assert_eq!(Request, id5.kind());
     assert_eq!(Outbound, id5.context());
     assert_eq!(GuaranteedResponse, id5.class());
     let id6: Id = pool
         .insert_outbound_request(request(time(60)).into(), time(65).into())
+        .unwrap()
         .into();
     assert_eq!(Request, id6.kind());
     assert_eq!(Outbound, id6.context());
     assert_eq!(BestEffort, id6.class());
     let id7: Id = pool
         .insert_outbound_response(response(NO_DEADLINE).into())
+        .unwrap()
         .into();
     assert_eq!(Response, id7.kind());
     assert_eq!(Outbound, id7.context());
     assert_eq!(GuaranteedResponse, id7.class());
     let id8: Id = pool
         .insert_outbound_response(response(time(80)).into())
+        .unwrap()
         .into();
     assert_eq!(Response, id8.kind());
     assert_eq!(Outbound, id8.context());
@@ -69,7 +87,7 @@ fn test_insert() {
             (time(80), id8),
             (time(50 + REQUEST_LIFETIME.as_secs() as u32), id5)
         },
-        pool.deadline_queue
+        pool.deadline_queue.iter().collect::<BTreeSet<_>>()
     );
 
     // All best-effort messages should be in the load shedding queue.
@@ -77,6 +95,7 @@ fn test_insert() {
     // We don't want to predict message sizes, so we only test which messages are in
     // the load shedding queue.
     assert_exact_messages_in_queue(btreeset! {id2, id4, id6, id8}, &pool.size_queue);
+    assert_invariants(&pool);
 }
 
 #[test]
@@ -100,9 +119,13 @@ fn test_insert_outbound_request_deadline_rounding() {
     let expected_deadline =
         CoarseTime::from_secs_since_unix_epoch(13 + REQUEST_LIFETIME.as_secs() as u32);
 
-    pool.insert_outbound_request(request(NO_DEADLINE).into(), current_time);
+    pool.insert_outbound_request(request(NO_DEADLINE).into(), current_time)
+        .unwrap();
 
-    assert_eq!(expected_deadline, pool.deadline_queue.first().unwrap().0);
+    assert_eq!(
+        Some(expected_deadline),
+        pool.deadline_queue.first_deadline()
+    );
 }
 
 #[test]
@@ -117,7 +140,7 @@ fn test_get() {
             } else {
                 response(time(i)).into()
             };
-            let id = pool.insert_inbound(msg.clone());
+            let id = pool.insert_inbound(msg.clone()).unwrap();
             (id, msg)
         })
         .collect();
@@ -171,14 +194,17 @@ fn test_take() {
 
             match context {
                 Context::Inbound => {
-                    let request_id = pool.insert_inbound(request.clone().into());
-                    let response_id = pool.insert_inbound(response.clone().into());
+                    let request_id = pool.insert_inbound(request.clone().into()).unwrap();
+                    let response_id = pool.insert_inbound(response.clone().into()).unwrap();
                     test_take_impl(request_id, response_id, request, response, &mut pool);
                 }
                 Context::Outbound => {
-                    let request_id =
-                        pool.insert_outbound_request(request.clone().into(), time(14).into());
-                    let response_id = pool.insert_outbound_response(response.clone().into());
+                    let request_id = pool
+                        .insert_outbound_request(request.clone().into(), time(14).into())
+                        .unwrap();
+                    let response_id = pool
+                        .insert_outbound_response(response.clone().into())
+                        .unwrap();
                     test_take_impl(request_id, response_id, request, response, &mut pool);
                 }
             }
@@ -211,16 +237,20 @@ fn test_expiration() {
 
     // Insert one of each kind / class of message that expires.
     let msg1 = request(time(10));
-    let ref1 = pool.insert_inbound(msg1.clone().into());
+    let ref1 = pool.insert_inbound(msg1.clone().into()).unwrap();
     let id1 = ref1.into();
     let msg2 = request(time(20));
-    let ref2 = pool.insert_outbound_request(msg2.clone().into(), time(25).into());
+    let ref2 = pool
+        .insert_outbound_request(msg2.clone().into(), time(25).into())
+        .unwrap();
     let id2 = ref2.into();
     let msg3 = response(time(30));
-    let ref3 = pool.insert_outbound_response(msg3.clone().into());
+    let ref3 = pool.insert_outbound_response(msg3.clone().into()).unwrap();
     let id3 = ref3.into();
     let msg4 = request(NO_DEADLINE);
-    let ref4 = pool.insert_outbound_request(msg4.clone().into(), time(40).into());
+    let ref4 = pool
+        .insert_outbound_request(msg4.clone().into(), time(40).into())
+        .unwrap();
     let id4 = ref4.into();
 
     // Sanity check.
@@ -232,7 +262,7 @@ fn test_expiration() {
             (time(30), id3),
             (time(40 + REQUEST_LIFETIME.as_secs() as u32), id4)
         },
-        pool.deadline_queue
+        pool.deadline_queue.iter().collect::<BTreeSet<_>>()
     );
     // There are expiring messages.
     assert!(pool.has_expired_deadlines(t_max));
@@ -301,6 +331,132 @@ fn test_expiration() {
     // And no messages are expiring.
     assert!(!pool.has_expired_deadlines(t_max));
     assert_eq!(empty_vec, pool.expire_messages(t_max));
+    assert_invariants(&pool);
+}
+
+#[test]
+fn test_expiration_bucketed_deadlines() {
+    let mut pool = MessagePool::default();
+
+    // Outbound guaranteed response requests inserted within the same second share
+    // a deadline bucket; best-effort messages have distinct deadlines, one of
+    // them coinciding with the bucketed one.
+    let t0 = Time::from_nanos_since_unix_epoch(10_000_000_000);
+    let bucket_deadline = time(10 + REQUEST_LIFETIME.as_secs() as u32);
+    let mut expected = Vec::new();
+    for i in 0..3 {
+        let msg = request(NO_DEADLINE);
+        let now = t0 + Duration::from_millis(100 * i);
+        let reference = pool
+            .insert_outbound_request(msg.clone().into(), now)
+            .unwrap();
+        expected.push((bucket_deadline, Id::from(reference), msg));
+    }
+    for deadline in [time(5), bucket_deadline, time(1000)] {
+        let msg = request(deadline);
+        let reference = pool
+            .insert_outbound_request(msg.clone().into(), t0)
+            .unwrap();
+        expected.push((deadline, Id::from(reference), msg));
+    }
+    // And one more request in the shared bucket, inserted last.
+    let msg = request(NO_DEADLINE);
+    let reference = pool
+        .insert_outbound_request(msg.clone().into(), t0)
+        .unwrap();
+    expected.push((bucket_deadline, Id::from(reference), msg));
+
+    // 3 distinct deadlines, 7 messages.
+    assert_eq!(3, pool.deadline_queue.buckets.len());
+    assert_eq!(5, pool.deadline_queue.buckets[&bucket_deadline].len());
+    assert_eq!(7, pool.deadline_queue.iter().count());
+
+    // Expected expiration order: by deadline, then by ID.
+    expected.sort_by_key(|(deadline, id, _)| (*deadline, *id));
+    let expected: Vec<(SomeReference, RequestOrResponse)> = expected
+        .into_iter()
+        .map(|(_, id, msg)| (id.into(), msg.into()))
+        .collect();
+
+    // Taking a message from the middle of a bucket leaves the others in place.
+    let (taken_ref, _) = &expected[2];
+    let SomeReference::Outbound(taken_ref) = taken_ref else {
+        panic!("Expected an outbound reference");
+    };
+    assert!(pool.take(*taken_ref).is_some());
+    assert_eq!(4, pool.deadline_queue.buckets[&bucket_deadline].len());
+
+    // Everything but the latest deadline expires, in order.
+    let mut expected_expired = expected;
+    expected_expired.remove(2);
+    let not_expired = expected_expired.pop().unwrap();
+    assert_eq!(
+        expected_expired,
+        pool.expire_messages(Time::from(bucket_deadline) + Duration::from_secs(1))
+    );
+    assert_eq!(1, pool.deadline_queue.buckets.len());
+
+    // And the last one expires on its own.
+    assert_eq!(
+        vec![not_expired],
+        pool.expire_messages(Time::from(time(1001)))
+    );
+    assert!(pool.deadline_queue.is_empty());
+    assert_eq!(0, pool.len());
+}
+
+#[test]
+fn test_expiration_many_requests_few_buckets() {
+    // The pool's `debug_assert!()` checks recompute the stats on every operation,
+    // making them `O(n)`; so only go for the full 100k requests in release builds.
+    const REQUEST_COUNT: u64 = if cfg!(debug_assertions) {
+        1_000
+    } else {
+        100_000
+    };
+    const BUCKET_COUNT: u64 = 4;
+
+    let mut pool = MessagePool::default();
+
+    // Insert lots of outbound guaranteed response requests, spread over 4 seconds,
+    // so they all end up in one of 4 deadline buckets.
+    let t0 = Time::from_nanos_since_unix_epoch(1_000_000_000_000);
+    let step = Duration::from_nanos(BUCKET_COUNT * 1_000_000_000 / REQUEST_COUNT);
+    let msg: Arc<Request> = request(NO_DEADLINE).into();
+    let references: Vec<_> = (0..REQUEST_COUNT)
+        .map(|i| {
+            pool.insert_outbound_request(msg.clone(), t0 + step * i as u32)
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(REQUEST_COUNT as usize, pool.len());
+    assert_eq!(BUCKET_COUNT as usize, pool.deadline_queue.buckets.len());
+
+    // Take every other request (i.e. from the middle of the buckets).
+    for reference in references.iter().step_by(2) {
+        assert!(pool.take(*reference).is_some());
+    }
+    assert_eq!(REQUEST_COUNT as usize / 2, pool.len());
+
+    // Expire the first two buckets, then the rest.
+    let t_expire = Time::from(CoarseTime::floor(t0 + REQUEST_LIFETIME)) + Duration::from_secs(2);
+    let first = pool.expire_messages(t_expire);
+    let rest = pool.expire_messages(t_expire + Duration::from_secs(BUCKET_COUNT));
+    assert_eq!(REQUEST_COUNT as usize / 2, first.len() + rest.len());
+    assert!(!first.is_empty());
+    assert!(!rest.is_empty());
+    assert_eq!(0, pool.len());
+    assert!(pool.deadline_queue.is_empty());
+
+    // Messages expired in ID order (all the remaining references, in order).
+    let expired_references: Vec<_> = first.into_iter().chain(rest).map(|(r, _)| r).collect();
+    let expected_references: Vec<_> = references
+        .into_iter()
+        .skip(1)
+        .step_by(2)
+        .map(SomeReference::Outbound)
+        .collect();
+    assert_eq!(expected_references, expired_references);
 }
 
 #[test]
@@ -308,10 +464,11 @@ fn test_expiration_of_non_expiring_messages() {
     let mut pool = MessagePool::default();
 
     // Insert one message of each kind / class / context.
-    pool.insert_inbound(request(NO_DEADLINE).into());
-    pool.insert_inbound(response(NO_DEADLINE).into());
-    pool.insert_inbound(response(time(30)).into());
-    pool.insert_outbound_response(response(NO_DEADLINE).into());
+    pool.insert_inbound(request(NO_DEADLINE).into()).unwrap();
+    pool.insert_inbound(response(NO_DEADLINE).into()).unwrap();
+    pool.insert_inbound(response(time(30)).into()).unwrap();
+    pool.insert_outbound_response(response(NO_DEADLINE).into())
+        .unwrap();
 
     // Sanity check.
     assert_eq!(4, pool.len());
@@ -326,6 +483,46 @@ fn test_expiration_of_non_expiring_messages() {
     assert_eq!(4, pool.len());
 }
 
+#[test]
+fn test_next_expiry() {
+    let mut pool = MessagePool::default();
+    let t = time(100);
+    let t_plus = |seconds| time(t.as_secs_since_unix_epoch() + seconds);
+
+    // No messages, no expiry.
+    assert_eq!(None, pool.next_expiry());
+
+    // Messages without deadlines do not expire.
+    pool.insert_inbound(request(NO_DEADLINE).into()).unwrap();
+    pool.insert_inbound(response(t_plus(1)).into()).unwrap();
+    pool.insert_outbound_response(response(NO_DEADLINE).into())
+        .unwrap();
+    assert_eq!(None, pool.next_expiry());
+
+    // Insert best-effort messages with deadlines at `t + 10s` and `t + 5s`.
+    pool.insert_outbound_request(request(t_plus(10)).into(), t.into())
+        .unwrap();
+    let reference = pool.insert_inbound(request(t_plus(5)).into()).unwrap();
+    assert_eq!(Some(t_plus(5).into()), pool.next_expiry());
+
+    // The message does not expire at its deadline, only one second later.
+    assert!(!pool.has_expired_deadlines(t_plus(5).into()));
+    assert!(pool.has_expired_deadlines(t_plus(6).into()));
+
+    // Remove the earliest expiring message, `next_expiry()` moves on.
+    assert!(pool.take(reference).is_some());
+    assert_eq!(Some(t_plus(10).into()), pool.next_expiry());
+
+    // An outbound guaranteed response request has an implicit deadline.
+    pool.insert_outbound_request(request(NO_DEADLINE).into(), t.into())
+        .unwrap();
+    assert_eq!(Some(t_plus(10).into()), pool.next_expiry());
+
+    // Expire the best-effort request, only the implicit deadline is left.
+    assert_eq!(1, pool.expire_messages(t_plus(11).into()).len());
+    assert_eq!(Some(Time::from(t) + REQUEST_LIFETIME), pool.next_expiry());
+}
+
 #[test]
 fn test_shed_message() {
     let mut pool = MessagePool::default();
@@ -335,14 +532,16 @@ fn test_shed_message() {
 
     // Insert one best-effort message of each kind / context.
     let msg1 = request_with_payload(1000, time(10));
-    let ref1 = pool.insert_inbound(msg1.clone().into());
+    let ref1 = pool.insert_inbound(msg1.clone().into()).unwrap();
     let msg2 = response_with_payload(4000, time(20));
-    let ref2 = pool.insert_inbound(msg2.clone().into());
+    let ref2 = pool.insert_inbound(msg2.clone().into()).unwrap();
     let id2: Id = ref2.into();
     let msg3 = request_with_payload(3000, time(30));
-    let ref3 = pool.insert_outbound_request(msg3.clone().into(), time(35).into());
+    let ref3 = pool
+        .insert_outbound_request(msg3.clone().into(), time(35).into())
+        .unwrap();
     let msg4 = response_with_payload(2000, time(40));
-    let ref4 = pool.insert_outbound_response(msg4.clone().into());
+    let ref4 = pool.insert_outbound_response(msg4.clone().into()).unwrap();
     let id4: Id = ref4.into();
 
     // Sanity check.
@@ -366,6 +565,205 @@ fn test_shed_message() {
     assert_eq!(None, pool.shed_largest_message());
     assert_eq!(0, pool.len());
     assert_eq!(0, pool.size_queue.len());
+    assert_invariants(&pool);
+}
+
+#[test]
+fn test_drop_stats() {
+    let mut pool = MessagePool::default();
+    assert_eq!(MessageDropStats::default(), pool.take_drop_stats());
+
+    // Insert one best-effort message of each kind / context.
+    let msg1 = request_with_payload(1000, time(10));
+    pool.insert_inbound(msg1.into()).unwrap();
+    let msg2 = response_with_payload(4000, time(20));
+    pool.insert_inbound(msg2.clone().into()).unwrap();
+    let msg3 = request_with_payload(3000, time(30));
+    pool.insert_outbound_request(msg3.into(), time(35).into())
+        .unwrap();
+    let msg4 = response_with_payload(2000, time(40));
+    pool.insert_outbound_response(msg4.into()).unwrap();
+
+    // Expire the two requests, shed the inbound response (which doesn't expire).
+    assert_eq!(2, pool.expire_messages(time(31).into()).len());
+    assert!(pool.shed_largest_message().is_some());
+
+    // Drop stats do not affect equality.
+    let mut pool_without_drop_stats = pool.clone();
+    pool_without_drop_stats.take_drop_stats();
+    assert_eq!(pool, pool_without_drop_stats);
+
+    assert_eq!(
+        MessageDropStats {
+            expired_request_count: 2,
+            expired_response_count: 0,
+            shed_message_count: 1,
+            shed_message_bytes: msg2.count_bytes(),
+        },
+        pool.take_drop_stats()
+    );
+    // `take_drop_stats()` resets the stats.
+    assert_eq!(MessageDropStats::default(), pool.take_drop_stats());
+
+    // Expire the outbound response.
+    assert_eq!(1, pool.expire_messages(time(41).into()).len());
+    assert_eq!(0, pool.len());
+    assert_eq!(
+        MessageDropStats {
+            expired_request_count: 0,
+            expired_response_count: 1,
+            shed_message_count: 0,
+            shed_message_bytes: 0,
+        },
+        pool.take_drop_stats()
+    );
+    assert_invariants(&pool);
+}
+
+#[test]
+fn test_removal_log() {
+    let mut pool = MessagePool::default();
+    pool.set_removal_log_capacity(10);
+
+    // A best-effort inbound request, to be expired.
+    let msg1 = request_with_payload(1000, time(10));
+    let id1: Id = pool.insert_inbound(msg1.clone().into()).unwrap().into();
+    // A best-effort inbound response (which doesn't expire), to be shed.
+    let msg2 = response_with_payload(4000, time(20));
+    let id2: Id = pool.insert_inbound(msg2.clone().into()).unwrap().into();
+    // An outbound guaranteed response request, to be taken.
+    let msg3 = request_with_payload(3000, NO_DEADLINE);
+    let ref3 = pool
+        .insert_outbound_request(msg3.clone().into(), time(35).into())
+        .unwrap();
+    let id3: Id = ref3.into();
+
+    assert_eq!(1, pool.expire_messages(time(11).into()).len());
+    assert!(pool.shed_largest_message().is_some());
+    assert!(pool.take(ref3).is_some());
+    assert_eq!(0, pool.len());
+
+    assert_eq!(
+        vec![
+            RemovalRecord {
+                id: id1.0,
+                kind: Kind::Request,
+                context: Context::Inbound,
+                class: Class::BestEffort,
+                size_bytes: RequestOrResponse::from(msg1).count_bytes(),
+                deadline: time(10),
+                reason: RemovalReason::Expired,
+                removed_at: Some(time(11).into()),
+            },
+            RemovalRecord {
+                id: id2.0,
+                kind: Kind::Response,
+                context: Context::Inbound,
+                class: Class::BestEffort,
+                size_bytes: RequestOrResponse::from(msg2).count_bytes(),
+                deadline: time(20),
+                reason: RemovalReason::Shed,
+                // The latest time observed by the pool.
+                removed_at: Some(time(35).into()),
+            },
+            RemovalRecord {
+                id: id3.0,
+                kind: Kind::Request,
+                context: Context::Outbound,
+                class: Class::GuaranteedResponse,
+                size_bytes: RequestOrResponse::from(msg3).count_bytes(),
+                // The implicit deadline.
+                deadline: time(35 + REQUEST_LIFETIME.as_secs() as u32),
+                reason: RemovalReason::Taken,
+                removed_at: Some(time(35).into()),
+            },
+        ],
+        pool.recent_removals()
+    );
+    assert_invariants(&pool);
+}
+
+#[test]
+fn test_removal_log_capacity() {
+    let mut pool = MessagePool::default();
+
+    // Disabled by default.
+    let reference = pool.insert_inbound(request(NO_DEADLINE).into()).unwrap();
+    pool.take(reference).unwrap();
+    assert!(pool.recent_removals().is_empty());
+
+    // Only the latest `capacity` removals are retained.
+    pool.set_removal_log_capacity(2);
+    let ids: Vec<Id> = (0..5)
+        .map(|_| {
+            let reference = pool.insert_inbound(request(NO_DEADLINE).into()).unwrap();
+            pool.take(reference).unwrap();
+            reference.into()
+        })
+        .collect();
+    let recorded_ids = |pool: &MessagePool| {
+        pool.recent_removals()
+            .iter()
+            .map(|record| record.id)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(vec![ids[3].0, ids[4].0], recorded_ids(&pool));
+
+    // Shrinking the capacity drops the oldest records.
+    pool.set_removal_log_capacity(1);
+    assert_eq!(vec![ids[4].0], recorded_ids(&pool));
+
+    // A capacity of zero disables the removal log.
+    pool.set_removal_log_capacity(0);
+    assert!(pool.recent_removals().is_empty());
+    let reference = pool.insert_inbound(request(NO_DEADLINE).into()).unwrap();
+    pool.take(reference).unwrap();
+    assert!(pool.recent_removals().is_empty());
+}
+
+#[test]
+fn test_largest_best_effort_size() {
+    let mut pool = MessagePool::default();
+
+    // No messages.
+    assert_eq!(None, pool.largest_best_effort_size());
+
+    // Only guaranteed response messages.
+    pool.insert_inbound(request(NO_DEADLINE).into()).unwrap();
+    pool.insert_outbound_response(response(NO_DEADLINE).into())
+        .unwrap();
+    assert_eq!(None, pool.largest_best_effort_size());
+
+    // Best-effort messages of various sizes.
+    pool.insert_inbound(request_with_payload(1000, time(10)).into())
+        .unwrap();
+    pool.insert_inbound(response_with_payload(4000, time(20)).into())
+        .unwrap();
+    pool.insert_outbound_request(request_with_payload(2000, time(30)).into(), time(5).into())
+        .unwrap();
+    pool.insert_outbound_response(response_with_payload(3000, time(40)).into())
+        .unwrap();
+
+    // Peeking does not remove anything.
+    let len = pool.len();
+    let size = pool.largest_best_effort_size();
+    assert!(size.is_some());
+    assert_eq!(size, pool.largest_best_effort_size());
+    assert_eq!(len, pool.len());
+
+    // The peeked size always matches the next shed message.
+    loop {
+        let size = pool.largest_best_effort_size();
+        let shed = pool.shed_largest_message();
+        assert_eq!(size, shed.as_ref().map(|(_, msg)| msg.count_bytes()));
+        if shed.is_none() {
+            break;
+        }
+        assert_invariants(&pool);
+    }
+
+    // Only the guaranteed response messages are left.
+    assert_eq!(2, pool.len());
 }
 
 #[test]
@@ -373,10 +771,12 @@ fn test_shed_message_guaranteed_response() {
     let mut pool = MessagePool::default();
 
     // Insert one guaranteed response message of each kind / context.
-    pool.insert_inbound(request(NO_DEADLINE).into());
-    pool.insert_inbound(response(NO_DEADLINE).into());
-    pool.insert_outbound_request(request(NO_DEADLINE).into(), time(30).into());
-    pool.insert_outbound_response(response(NO_DEADLINE).into());
+    pool.insert_inbound(request(NO_DEADLINE).into()).unwrap();
+    pool.insert_inbound(response(NO_DEADLINE).into()).unwrap();
+    pool.insert_outbound_request(request(NO_DEADLINE).into(), time(30).into())
+        .unwrap();
+    pool.insert_outbound_response(response(NO_DEADLINE).into())
+        .unwrap();
 
     assert_eq!(4, pool.len());
 
@@ -385,19 +785,525 @@ fn test_shed_message_guaranteed_response() {
     assert_eq!(0, pool.size_queue.len());
 }
 
+#[test]
+fn test_load_shedding_policy_largest_first() {
+    let mut pool = MessagePool::default();
+
+    // A guaranteed response request and three best-effort messages.
+    pool.insert_inbound(request_with_payload(5000, NO_DEADLINE).into())
+        .unwrap();
+    let ref1 = pool
+        .insert_inbound(request_with_payload(2000, time(10)).into())
+        .unwrap();
+    let ref2 = pool
+        .insert_outbound_response(response_with_payload(3000, time(20)).into())
+        .unwrap();
+    pool.insert_inbound(response_with_payload(1000, time(30)).into())
+        .unwrap();
+
+    assert_eq!(
+        Some(SomeReference::Outbound(ref2)),
+        LargestFirst.select_victim(&pool)
+    );
+    assert_eq!(
+        Some(SomeReference::Outbound(ref2)),
+        pool.shed_largest_message().map(|(reference, _)| reference)
+    );
+    assert_eq!(
+        Some(SomeReference::Inbound(ref1)),
+        LargestFirst.select_victim(&pool)
+    );
+}
+
+#[test]
+fn test_load_shedding_policy_oldest_first() {
+    let mut pool = MessagePool::with_load_shedding_policy(OldestFirst);
+
+    // Nothing to shed.
+    assert_eq!(None, OldestFirst.select_victim(&pool));
+
+    // A guaranteed response request (oldest overall) and three best-effort
+    // messages, the oldest of which is the smallest.
+    pool.insert_inbound(request_with_payload(5000, NO_DEADLINE).into())
+        .unwrap();
+    let msg1 = response_with_payload(1000, time(30));
+    let ref1 = pool.insert_inbound(msg1.clone().into()).unwrap();
+    let msg2 = request_with_payload(3000, time(10));
+    let ref2 = pool
+        .insert_outbound_request(msg2.clone().into(), time(5).into())
+        .unwrap();
+    pool.insert_inbound(request_with_payload(2000, time(20)).into())
+        .unwrap();
+
+    // The oldest best-effort message is selected, regardless of size.
+    assert_eq!(
+        Some(SomeReference::Inbound(ref1)),
+        OldestFirst.select_victim(&pool)
+    );
+    assert_eq!(
+        Some((SomeReference::Inbound(ref1), msg1.into())),
+        pool.shed_largest_message()
+    );
+    assert_eq!(
+        Some((SomeReference::Outbound(ref2), msg2.into())),
+        pool.shed_largest_message()
+    );
+    assert_invariants(&pool);
+
+    // Shed the last best-effort message, the guaranteed response is left.
+    assert!(pool.shed_largest_message().is_some());
+    assert_eq!(None, pool.shed_largest_message());
+    assert_eq!(1, pool.len());
+    assert_invariants(&pool);
+}
+
+#[test]
+fn test_set_load_shedding_policy() {
+    let mut pool = MessagePool::default();
+    let small = pool
+        .insert_inbound(request_with_payload(1000, time(10)).into())
+        .unwrap();
+    let large = pool
+        .insert_inbound(request_with_payload(3000, time(20)).into())
+        .unwrap();
+    let encoded = pb_queues::MessagePool::from(&pool);
+
+    // `LargestFirst` by default.
+    assert_eq!(
+        Some(SomeReference::Inbound(large)),
+        pool.load_shedding_policy.select_victim(&pool)
+    );
+
+    pool.set_load_shedding_policy(LoadSheddingPolicyKind::OldestFirst);
+    assert_eq!(
+        Some(SomeReference::Inbound(small)),
+        pool.load_shedding_policy.select_victim(&pool)
+    );
+
+    // The policy is configuration: neither persisted nor compared.
+    assert_eq!(MessagePool::try_from((encoded, 0)).unwrap(), pool);
+}
+
+#[test]
+fn test_shed_down_to_count() {
+    let mut pool = MessagePool::default();
+
+    // Insert 10 small best-effort messages of increasing size, plus a guaranteed
+    // response request.
+    for i in 0..10 {
+        pool.insert_inbound(request_with_payload(10 + i, time(20)).into())
+            .unwrap();
+    }
+    pool.insert_inbound(request(NO_DEADLINE).into()).unwrap();
+    assert_eq!(10, pool.message_stats.best_effort_message_count);
+
+    // Shed down to 5 best-effort messages.
+    let shed = pool.shed_down_to_count(5);
+    assert_eq!(5, shed.len());
+    assert_eq!(5, pool.message_stats.best_effort_message_count);
+    assert_eq!(6, pool.len());
+
+    // The largest messages were shed, largest first.
+    let shed_sizes: Vec<_> = shed.iter().map(|(_, msg)| msg.count_bytes()).collect();
+    let mut expected_sizes = shed_sizes.clone();
+    expected_sizes.sort_by(|a, b| b.cmp(a));
+    assert_eq!(expected_sizes, shed_sizes);
+    assert!(pool
+        .size_queue
+        .iter()
+        .all(|(_, size, _)| *size < *shed_sizes.last().unwrap()));
+
+    // Shedding down to a higher count is a no-op.
+    assert!(pool.shed_down_to_count(7).is_empty());
+    assert_eq!(5, pool.message_stats.best_effort_message_count);
+
+    // Shedding down to zero leaves only the guaranteed response request.
+    assert_eq!(5, pool.shed_down_to_count(0).len());
+    assert_eq!(0, pool.message_stats.best_effort_message_count);
+    assert_eq!(1, pool.len());
+    assert!(pool.shed_down_to_count(0).is_empty());
+    assert_invariants(&pool);
+}
+
+#[test]
+fn test_clear_best_effort() {
+    let mut pool = MessagePool::default();
+
+    // One best-effort and one guaranteed response message of each context / kind.
+    let be_in_req = pool
+        .insert_inbound(request_with_payload(1000, time(10)).into())
+        .unwrap();
+    let be_in_rep = pool
+        .insert_inbound_with_priority(response_with_payload(2000, time(20)).into(), Priority::Low)
+        .unwrap();
+    let be_out_req = pool
+        .insert_outbound_request(request_with_payload(3000, time(30)).into(), time(5).into())
+        .unwrap();
+    let be_out_rep = pool
+        .insert_outbound_response(response_with_payload(4000, time(40)).into())
+        .unwrap();
+    let gr_in_req = pool.insert_inbound(request(NO_DEADLINE).into()).unwrap();
+    let gr_in_rep = pool.insert_inbound(response(NO_DEADLINE).into()).unwrap();
+    let gr_out_req = pool
+        .insert_outbound_request(request(NO_DEADLINE).into(), time(5).into())
+        .unwrap();
+    let gr_out_rep = pool
+        .insert_outbound_response(response(NO_DEADLINE).into())
+        .unwrap();
+    let guaranteed_messages: Vec<_> = [gr_in_req, gr_in_rep]
+        .iter()
+        .map(|reference| pool.get(*reference).unwrap().clone())
+        .chain(
+            [gr_out_req, gr_out_rep]
+                .iter()
+                .map(|reference| pool.get(*reference).unwrap().clone()),
+        )
+        .collect();
+    let stats_before = pool.message_stats.clone();
+
+    let cleared = pool.clear_best_effort();
+
+    // All best-effort messages were returned, in load shedding order.
+    assert_eq!(
+        stats_before.best_effort_message_bytes,
+        cleared
+            .iter()
+            .map(|(_, msg)| msg.count_bytes())
+            .sum::<usize>()
+    );
+    assert_eq!(
+        vec![
+            SomeReference::Inbound(be_in_rep),
+            SomeReference::Outbound(be_out_rep),
+            SomeReference::Outbound(be_out_req),
+            SomeReference::Inbound(be_in_req),
+        ],
+        cleared
+            .into_iter()
+            .map(|(reference, _)| reference)
+            .collect::<Vec<_>>()
+    );
+
+    // The load shedding queue is empty, no best-effort messages are accounted for.
+    assert!(pool.size_queue.is_empty());
+    assert!(pool.priorities.is_empty());
+    assert_eq!(0, pool.message_stats.best_effort_message_bytes);
+    assert_eq!(0, pool.message_stats.best_effort_message_count);
+    assert_eq!(
+        stats_before.size_bytes - stats_before.best_effort_message_bytes,
+        pool.message_stats.size_bytes
+    );
+    assert_eq!(4, pool.drop_stats.shed_message_count);
+
+    // All guaranteed response messages are still there (and the outbound request
+    // can still expire).
+    assert_eq!(4, pool.len());
+    assert_eq!(
+        guaranteed_messages,
+        vec![
+            pool.get(gr_in_req).unwrap().clone(),
+            pool.get(gr_in_rep).unwrap().clone(),
+            pool.get(gr_out_req).unwrap().clone(),
+            pool.get(gr_out_rep).unwrap().clone(),
+        ]
+    );
+    assert_eq!(1, pool.deadline_queue.len());
+    assert_invariants(&pool);
+
+    // Clearing a pool without best-effort messages is a no-op.
+    assert!(pool.clear_best_effort().is_empty());
+    assert_eq!(4, pool.len());
+}
+
+#[test]
+fn test_replace() {
+    let mut pool = MessagePool::default();
+
+    // Insert a best-effort request and a guaranteed response request.
+    let msg1 = request_with_payload(1000, time(10));
+    let ref1 = pool.insert_inbound(msg1.clone().into()).unwrap();
+    let msg2 = request_with_payload(2000, NO_DEADLINE);
+    let ref2 = pool.insert_inbound(msg2.clone().into()).unwrap();
+
+    // Replace both with messages of different sizes.
+    let msg3 = request_with_payload(500, time(10));
+    assert_eq!(Some(msg1.into()), pool.replace(ref1, msg3.clone().into()));
+    let msg4 = request_with_payload(3000, NO_DEADLINE);
+    assert_eq!(Some(msg2.into()), pool.replace(ref2, msg4.clone().into()));
+
+    // The references now point to the new messages.
+    assert_eq!(Some(&msg3.clone().into()), pool.get(ref1));
+    assert_eq!(Some(&msg4.clone().into()), pool.get(ref2));
+    assert_eq!(2, pool.len());
+
+    // And the stats and priority queues are consistent with the new messages.
+    assert_eq!(
+        MessagePool::calculate_message_stats(
+            &pool.messages,
+            pool.message_stats.guaranteed_response_reservations
+        ),
+        pool.message_stats
+    );
+    assert_exact_messages_in_queue(btreeset! {ref1.into()}, &pool.size_queue);
+    assert_eq!(
+        Some(&(
+            Reverse(Priority::Normal),
+            msg3.count_bytes(),
+            Id::from(ref1)
+        )),
+        pool.size_queue.first()
+    );
+    assert_invariants(&pool);
+
+    // Both messages can still be taken.
+    assert_eq!(Some(msg3.into()), pool.take(ref1));
+    assert_eq!(Some(msg4.into()), pool.take(ref2));
+    assert_eq!(MessageStats::default(), pool.message_stats);
+}
+
+#[test]
+fn test_replace_rejected() {
+    let mut pool = MessagePool::default();
+
+    let msg = request_with_payload(1000, time(10));
+    let reference = pool.insert_inbound(msg.clone().into()).unwrap();
+    let stats = pool.message_stats.clone();
+    let shared = pool.clone();
+
+    // Different kind.
+    assert_eq!(
+        None,
+        pool.replace(reference, response_with_payload(10, time(10)).into())
+    );
+    // Different class.
+    assert_eq!(
+        None,
+        pool.replace(reference, request_with_payload(10, NO_DEADLINE).into())
+    );
+    // Different deadline.
+    assert_eq!(
+        None,
+        pool.replace(reference, request_with_payload(10, time(20)).into())
+    );
+    // No such message.
+    let missing_reference = new_request_reference(13, Class::BestEffort);
+    assert_eq!(
+        None,
+        pool.replace(missing_reference, request_with_payload(10, time(10)).into())
+    );
+
+    // The pool is unchanged and still shares its messages with the clone.
+    assert_eq!(Some(&msg.into()), pool.get(reference));
+    assert_eq!(stats, pool.message_stats);
+    assert!(Arc::ptr_eq(&shared.messages, &pool.messages));
+    assert_invariants(&pool);
+}
+
+#[test]
+fn test_replace_then_shed() {
+    let mut pool = MessagePool::default();
+
+    // Insert two best-effort messages, `msg1` smaller than `msg2`.
+    let ref1 = pool
+        .insert_inbound(request_with_payload(1000, time(10)).into())
+        .unwrap();
+    let msg2 = response_with_payload(2000, time(20));
+    let ref2 = pool.insert_outbound_response(msg2.clone().into()).unwrap();
+
+    // Grow `msg1` so that it becomes the largest message.
+    let msg3 = request_with_payload(3000, time(10));
+    assert!(pool.replace(ref1, msg3.clone().into()).is_some());
+
+    // Shedding picks up the new sizes.
+    assert_eq!(
+        Some((SomeReference::Inbound(ref1), msg3.into())),
+        pool.shed_largest_message()
+    );
+
+    // Shrink `msg2` and check that it is shed with its new size.
+    let msg4 = response_with_payload(10, time(20));
+    assert_eq!(Some(msg2.into()), pool.replace(ref2, msg4.clone().into()));
+    assert_eq!(
+        Some(&(
+            Reverse(Priority::Normal),
+            msg4.count_bytes(),
+            Id::from(ref2)
+        )),
+        pool.size_queue.last()
+    );
+    assert_eq!(
+        Some((SomeReference::Outbound(ref2), msg4.into())),
+        pool.shed_largest_message()
+    );
+    assert_eq!(0, pool.len());
+    assert_eq!(MessageStats::default(), pool.message_stats);
+}
+
+#[test]
+fn test_oldest_message_age() {
+    let t0 = UNIX_EPOCH + Duration::from_secs(1000);
+    let epsilon = Duration::from_millis(1);
+    let assert_age = |pool: &MessagePool, now: Time, expected: Duration| {
+        let age = pool.oldest_message_age(now).unwrap();
+        assert!(
+            age.abs_diff(expected) <= epsilon,
+            "Expected age {:?}, got {:?}",
+            expected,
+            age
+        );
+    };
+
+    let mut pool = MessagePool::default();
+    assert_eq!(None, pool.oldest_message_age(t0));
+
+    // Messages inserted before any time was observed cannot be dated.
+    let ref0 = pool.insert_inbound(request(NO_DEADLINE).into()).unwrap();
+    assert_eq!(None, pool.oldest_message_age(t0));
+    assert!(pool.take(ref0).is_some());
+
+    // Insert an outbound request at `t0`; and an inbound request 5 seconds later.
+    let ref1 = pool
+        .insert_outbound_request(request(NO_DEADLINE).into(), t0)
+        .unwrap();
+    pool.observe_time(t0 + Duration::from_secs(5));
+    let ref2 = pool.insert_inbound(request(time(2000)).into()).unwrap();
+
+    // The outbound request is the oldest message.
+    assert_age(&pool, t0, Duration::ZERO);
+    assert_age(&pool, t0 + Duration::from_secs(12), Duration::from_secs(12));
+
+    // Once it is gone, the inbound request is the oldest message.
+    assert!(pool.take(ref1).is_some());
+    assert_age(&pool, t0 + Duration::from_secs(12), Duration::from_secs(7));
+
+    // Observing time does not affect the age of existing messages; and obsolete
+    // checkpoints are dropped.
+    pool.observe_time(t0 + Duration::from_secs(20));
+    assert_age(&pool, t0 + Duration::from_secs(30), Duration::from_secs(25));
+    assert_eq!(2, pool.time_checkpoints.len());
+
+    // Observing an earlier time is a no-op.
+    let before = pool.time_checkpoints.clone();
+    pool.observe_time(t0);
+    assert_eq!(before, pool.time_checkpoints);
+
+    // An empty pool has no oldest message.
+    assert!(pool.take(ref2).is_some());
+    assert_eq!(None, pool.oldest_message_age(t0 + Duration::from_secs(30)));
+}
+
+#[test]
+fn test_oldest_message_age_encode_roundtrip() {
+    let t0 = UNIX_EPOCH + Duration::from_secs(1000);
+    let mut pool = MessagePool::default();
+    pool.insert_outbound_request(request(NO_DEADLINE).into(), t0)
+        .unwrap();
+    let now = t0 + Duration::from_secs(42);
+
+    assert_eq!(Some(Duration::from_secs(42)), pool.oldest_message_age(now));
+
+    // Observed times are not persisted, so the decoded pool is equal to the
+    // original, but cannot date the message.
+    let encoded: pb_queues::MessagePool = (&pool).into();
+    let mut decoded = MessagePool::try_from((encoded, 0)).unwrap();
+    assert_eq!(pool, decoded);
+    assert_eq!(None, decoded.oldest_message_age(now));
+
+    // Not even after observing a time.
+    decoded.observe_time(now);
+    assert_eq!(None, decoded.oldest_message_age(now));
+}
+
+#[test]
+fn test_max_messages() {
+    const CAPACITY: usize = 5;
+    let mut pool = MessagePool::default();
+    pool.set_max_messages(Some(CAPACITY));
+
+    // Fill the pool to capacity, with a mix of messages.
+    let mut references = Vec::new();
+    for i in 0..CAPACITY {
+        let reference = match i % 3 {
+            0 => SomeReference::Inbound(pool.insert_inbound(request(time(10)).into()).unwrap()),
+            1 => SomeReference::Outbound(
+                pool.insert_outbound_request(request(NO_DEADLINE).into(), time(20).into())
+                    .unwrap(),
+            ),
+            _ => SomeReference::Outbound(
+                pool.insert_outbound_response(response(time(30)).into())
+                    .unwrap(),
+            ),
+        };
+        references.push(reference);
+    }
+    assert_eq!(CAPACITY, pool.len());
+
+    // The (capacity + 1)-th request insert fails.
+    let full = PoolFullError { capacity: CAPACITY };
+    let before = pool.clone();
+    assert_eq!(Err(full), pool.insert_inbound(request(NO_DEADLINE).into()));
+    assert_eq!(
+        Err(full),
+        pool.insert_outbound_request(request(time(40)).into(), time(40).into())
+    );
+    assert_eq!(Err(full), pool.check_capacity());
+    // And leaves the pool unchanged.
+    assert_eq!(before, pool);
+
+    // But responses, which fill reserved slots, are exempt from the limit.
+    let inbound_response = pool.insert_inbound(response(NO_DEADLINE).into()).unwrap();
+    let outbound_response = pool
+        .insert_outbound_response(response(NO_DEADLINE).into())
+        .unwrap();
+    assert_eq!(CAPACITY + 2, pool.len());
+    assert!(pool.take(inbound_response).is_some());
+    assert!(pool.take(outbound_response).is_some());
+    assert_eq!(CAPACITY, pool.len());
+
+    // Removing a message makes room for another one.
+    match references.pop().unwrap() {
+        SomeReference::Inbound(reference) => assert!(pool.take(reference).is_some()),
+        SomeReference::Outbound(reference) => assert!(pool.take(reference).is_some()),
+    }
+    assert_eq!(Ok(()), pool.check_capacity());
+    assert!(pool.insert_inbound(request(time(10)).into()).is_ok());
+    assert!(pool.insert_inbound(request(time(10)).into()).is_err());
+
+    // The limit is configuration: it is not persisted, but neither does it affect
+    // equality.
+    let encoded: pb_queues::MessagePool = (&pool).into();
+    let decoded = MessagePool::try_from((encoded, 0)).unwrap();
+    assert_eq!(None, decoded.max_messages());
+    assert_eq!(pool, decoded);
+
+    // Lifting the limit allows further inserts.
+    pool.set_max_messages(None);
+    assert!(pool.insert_inbound(request(time(10)).into()).is_ok());
+}
+
 #[test]
 fn test_equality() {
     let mut pool = MessagePool::default();
 
     // Insert one message of each kind / class / context.
-    let ref1 = pool.insert_inbound(request(NO_DEADLINE).into());
-    let ref2 = pool.insert_inbound(request_with_payload(2000, time(20)).into());
-    let _ref3 = pool.insert_inbound(response(NO_DEADLINE).into());
-    let _ref4 = pool.insert_inbound(response(time(40)).into());
-    let _ref5 = pool.insert_outbound_request(request(NO_DEADLINE).into(), time(50).into());
-    let _ref6 = pool.insert_outbound_request(request(time(60)).into(), time(65).into());
-    let _ref7 = pool.insert_outbound_response(response(NO_DEADLINE).into());
-    let ref8 = pool.insert_outbound_response(response(time(80)).into());
+    let ref1 = pool.insert_inbound(request(NO_DEADLINE).into()).unwrap();
+    let ref2 = pool
+        .insert_inbound(request_with_payload(2000, time(20)).into())
+        .unwrap();
+    let _ref3 = pool.insert_inbound(response(NO_DEADLINE).into()).unwrap();
+    let _ref4 = pool.insert_inbound(response(time(40)).into()).unwrap();
+    let _ref5 = pool
+        .insert_outbound_request(request(NO_DEADLINE).into(), time(50).into())
+        .unwrap();
+    let _ref6 = pool
+        .insert_outbound_request(request(time(60)).into(), time(65).into())
+        .unwrap();
+    let _ref7 = pool
+        .insert_outbound_response(response(NO_DEADLINE).into())
+        .unwrap();
+    let ref8 = pool
+        .insert_outbound_response(response(time(80)).into())
+        .unwrap();
 
     // Make a clone.
     let mut other_pool = pool.clone();
@@ -659,6 +1565,67 @@ fn test_reference_roundtrip_encode() {
     }
 }
 
+/// Tests that any valid `Id` (of any kind, context and class, with any
+/// generator value) survives a roundtrip through `u64`, as used for protobuf
+/// `uint64` fields.
+#[test_strategy::proptest]
+fn id_u64_roundtrip(
+    outbound: bool,
+    best_effort: bool,
+    response: bool,
+    #[strategy(0..u64::MAX >> Id::BITMASK_LEN)] generator: u64,
+) {
+    let class = if best_effort {
+        Class::BestEffort
+    } else {
+        Class::GuaranteedResponse
+    };
+    let kind = if response {
+        Kind::Response
+    } else {
+        Kind::Request
+    };
+    let id = if outbound {
+        Id::from(OutboundReference::new(class, kind, generator))
+    } else {
+        Id::from(InboundReference::new(class, kind, generator))
+    };
+
+    let encoded = u64::from(id);
+    let decoded = Id::from(encoded);
+
+    prop_assert_eq!(id, decoded);
+    prop_assert_eq!(
+        (id.context(), id.class(), id.kind(), id.generator()),
+        (
+            decoded.context(),
+            decoded.class(),
+            decoded.kind(),
+            generator
+        )
+    );
+}
+
+/// Tests that decoding fails if an outbound guaranteed request deadline is
+/// keyed by an `Id` that does not represent an outbound guaranteed request.
+#[test]
+fn decode_with_invalid_outbound_guaranteed_request_deadline_fails() {
+    let mut pool = MessagePool::default();
+    let reference = pool
+        .insert_outbound_request(request(NO_DEADLINE).into(), time(50).into())
+        .unwrap();
+    let mut encoded: pb_queues::MessagePool = (&pool).into();
+
+    // Flip the message kind bit, turning the request `Id` into a response `Id`.
+    let invalid_id = u64::from(&reference) | Kind::BIT;
+    encoded.outbound_guaranteed_request_deadlines[0].id = invalid_id;
+
+    assert_matches!(
+        MessagePool::try_from((encoded, 0)),
+        Err(ProxyDecodeError::Other(msg)) if msg.contains("non outbound guaranteed request")
+    );
+}
+
 #[test]
 fn test_callback_reference_roundtip_encode() {
     let callback_reference = CallbackReference(
@@ -724,13 +1691,17 @@ fn test_message_stats_best_effort() {
     let response = response(time(20));
     let response_size_bytes = response.count_bytes();
 
-    let _ = pool.insert_inbound(request.clone().into());
+    let _ = pool.insert_inbound(request.clone().into()).unwrap();
     stats.adjust_and_check(&pool, Push, Inbound, request.clone().into());
-    let inbound_response_id = pool.insert_inbound(response.clone().into());
+    let inbound_response_id = pool.insert_inbound(response.clone().into()).unwrap();
     stats.adjust_and_check(&pool, Push, Inbound, response.clone().into());
-    let outbound_request_id = pool.insert_outbound_request(request.clone().into(), UNIX_EPOCH);
+    let outbound_request_id = pool
+        .insert_outbound_request(request.clone().into(), UNIX_EPOCH)
+        .unwrap();
     stats.adjust_and_check(&pool, Push, Outbound, request.clone().into());
-    let _ = pool.insert_outbound_response(response.clone().into());
+    let _ = pool
+        .insert_outbound_response(response.clone().into())
+        .unwrap();
     stats.adjust_and_check(&pool, Push, Outbound, response.clone().into());
 
     // Sanity check the absolute values.
@@ -738,6 +1709,7 @@ fn test_message_stats_best_effort() {
         MessageStats {
             size_bytes: 2 * (request_size_bytes + response_size_bytes),
             best_effort_message_bytes: 2 * (request_size_bytes + response_size_bytes),
+            best_effort_message_count: 4,
             guaranteed_responses_size_bytes: 0,
             oversized_guaranteed_requests_extra_bytes: 0,
             inbound_size_bytes: request_size_bytes + response_size_bytes,
@@ -745,7 +1717,8 @@ fn test_message_stats_best_effort() {
             inbound_response_count: 1,
             inbound_guaranteed_request_count: 0,
             inbound_guaranteed_response_count: 0,
-            outbound_message_count: 2
+            outbound_message_count: 2,
+            guaranteed_response_reservations: 0,
         },
         pool.message_stats
     );
@@ -803,13 +1776,17 @@ fn test_message_stats_guaranteed_response() {
     let response = response(NO_DEADLINE);
     let response_size_bytes = response.count_bytes();
 
-    let inbound_request_id = pool.insert_inbound(request.clone().into());
+    let inbound_request_id = pool.insert_inbound(request.clone().into()).unwrap();
     stats.adjust_and_check(&pool, Push, Inbound, request.clone().into());
-    let inbound_response_id = pool.insert_inbound(response.clone().into());
+    let inbound_response_id = pool.insert_inbound(response.clone().into()).unwrap();
     stats.adjust_and_check(&pool, Push, Inbound, response.clone().into());
-    let _ = pool.insert_outbound_request(request.clone().into(), UNIX_EPOCH);
+    let _ = pool
+        .insert_outbound_request(request.clone().into(), UNIX_EPOCH)
+        .unwrap();
     stats.adjust_and_check(&pool, Push, Outbound, request.clone().into());
-    let outbound_response_id = pool.insert_outbound_response(response.clone().into());
+    let outbound_response_id = pool
+        .insert_outbound_response(response.clone().into())
+        .unwrap();
     stats.adjust_and_check(&pool, Push, Outbound, response.clone().into());
 
     // Sanity check the absolute values.
@@ -817,6 +1794,7 @@ fn test_message_stats_guaranteed_response() {
         MessageStats {
             size_bytes: 2 * (request_size_bytes + response_size_bytes),
             best_effort_message_bytes: 0,
+            best_effort_message_count: 0,
             guaranteed_responses_size_bytes: 2 * response_size_bytes,
             oversized_guaranteed_requests_extra_bytes: 0,
             inbound_size_bytes: request_size_bytes + response_size_bytes,
@@ -824,7 +1802,8 @@ fn test_message_stats_guaranteed_response() {
             inbound_response_count: 1,
             inbound_guaranteed_request_count: 1,
             inbound_guaranteed_response_count: 1,
-            outbound_message_count: 2
+            outbound_message_count: 2,
+            guaranteed_response_reservations: 0,
         },
         pool.message_stats
     );
@@ -897,14 +1876,17 @@ fn test_message_stats_oversized_requests() {
     // `Response` structs, so better to compute it
     let guaranteed_extra_bytes = guaranteed_size_bytes - MAX_RESPONSE_COUNT_BYTES;
 
-    let _ = pool.insert_inbound(best_effort.clone().into());
+    let _ = pool.insert_inbound(best_effort.clone().into()).unwrap();
     stats.adjust_and_check(&pool, Push, Inbound, best_effort.clone().into());
-    let inbound_guaranteed_id = pool.insert_inbound(guaranteed.clone().into());
+    let inbound_guaranteed_id = pool.insert_inbound(guaranteed.clone().into()).unwrap();
     stats.adjust_and_check(&pool, Push, Inbound, guaranteed.clone().into());
-    let outbound_best_effort_id =
-        pool.insert_outbound_request(best_effort.clone().into(), UNIX_EPOCH);
+    let outbound_best_effort_id = pool
+        .insert_outbound_request(best_effort.clone().into(), UNIX_EPOCH)
+        .unwrap();
     stats.adjust_and_check(&pool, Push, Outbound, best_effort.clone().into());
-    let _ = pool.insert_outbound_request(guaranteed.clone().into(), UNIX_EPOCH);
+    let _ = pool
+        .insert_outbound_request(guaranteed.clone().into(), UNIX_EPOCH)
+        .unwrap();
     stats.adjust_and_check(&pool, Push, Outbound, guaranteed.clone().into());
 
     // Sanity check the absolute values.
@@ -912,6 +1894,7 @@ fn test_message_stats_oversized_requests() {
         MessageStats {
             size_bytes: 2 * (best_effort_size_bytes + guaranteed_size_bytes),
             best_effort_message_bytes: 2 * best_effort_size_bytes,
+            best_effort_message_count: 2,
             guaranteed_responses_size_bytes: 0,
             oversized_guaranteed_requests_extra_bytes: 2 * guaranteed_extra_bytes,
             inbound_size_bytes: best_effort_size_bytes + guaranteed_size_bytes,
@@ -919,7 +1902,8 @@ fn test_message_stats_oversized_requests() {
             inbound_response_count: 0,
             inbound_guaranteed_request_count: 1,
             inbound_guaranteed_response_count: 0,
-            outbound_message_count: 2
+            outbound_message_count: 2,
+            guaranteed_response_reservations: 0,
         },
         pool.message_stats
     );
@@ -961,6 +1945,253 @@ fn test_message_stats_oversized_requests() {
     assert_eq!(MessageStats::default(), pool.message_stats);
 }
 
+#[test]
+fn test_message_counts() {
+    let mut pool = MessagePool::default();
+    assert!(pool.is_empty());
+    assert_message_counts(&pool, 0, 0);
+
+    // Insert one message of each kind / class / context, via every insertion API.
+    let inbound_guaranteed_request = pool.insert_inbound(request(NO_DEADLINE).into()).unwrap();
+    pool.insert_inbound_with_priority(request(time(20)).into(), Priority::High)
+        .unwrap();
+    pool.insert_inbound(response(NO_DEADLINE).into()).unwrap();
+    let inbound_best_effort_response = pool.insert_inbound(response(time(40)).into()).unwrap();
+    assert_message_counts(&pool, 2, 2);
+    let outbound_guaranteed_request = pool
+        .insert_outbound_request(request(NO_DEADLINE).into(), time(50).into())
+        .unwrap();
+    pool.insert_outbound_request_with_priority(
+        request(time(60)).into(),
+        time(65).into(),
+        Priority::Low,
+    )
+    .unwrap();
+    pool.insert_outbound_response(response(NO_DEADLINE).into())
+        .unwrap();
+    pool.insert_outbound_response_with_priority(response(time(80)).into(), Priority::Highest)
+        .unwrap();
+    assert!(!pool.is_empty());
+    assert_message_counts(&pool, 4, 4);
+
+    // Replacing a message leaves the counts unchanged.
+    pool.replace(
+        inbound_best_effort_response,
+        response_with_payload(10, time(40)).into(),
+    )
+    .unwrap();
+    assert_message_counts(&pool, 4, 4);
+
+    // The counts are recomputed on deserialization.
+    let encoded: pb_queues::MessagePool = (&pool).into();
+    let decoded = MessagePool::try_from((encoded, 0)).unwrap();
+    assert_eq!(pool, decoded);
+    assert_message_counts(&decoded, 4, 4);
+
+    // Drain the pool via a mix of `take()`, `expire_messages()` and
+    // `shed_largest_message()`.
+    assert!(pool.take(inbound_guaranteed_request).is_some());
+    assert!(pool.take(inbound_best_effort_response).is_some());
+    assert_message_counts(&pool, 3, 3);
+
+    // Expires the best-effort requests, but not the outbound guaranteed request.
+    assert_eq!(2, pool.expire_messages(time(70).into()).len());
+    assert_message_counts(&pool, 1, 3);
+
+    while pool.shed_largest_message().is_some() {}
+    assert_message_counts(&pool, 0, 3);
+
+    // Guaranteed response messages are not shed, take them explicitly.
+    assert!(pool.take(outbound_guaranteed_request).is_some());
+    let remaining: Vec<_> = pool.messages.keys().cloned().collect();
+    for id in remaining {
+        let reference: SomeReference = id.into();
+        match reference {
+            SomeReference::Inbound(reference) => assert!(pool.take(reference).is_some()),
+            SomeReference::Outbound(reference) => assert!(pool.take(reference).is_some()),
+        }
+    }
+
+    assert!(pool.is_empty());
+    assert_message_counts(&pool, 0, 0);
+}
+
+/// Tests that a small low priority message is shed before a huge normal
+/// priority one; and that within a priority level, the largest message is shed
+/// first.
+#[test]
+fn test_shed_lowest_priority_message_first() {
+    let mut pool = MessagePool::default();
+
+    let huge = pool
+        .insert_inbound(request_with_payload(100_000, time(10)).into())
+        .unwrap();
+    let small_low = pool
+        .insert_outbound_response_with_priority(
+            response_with_payload(10, time(20)).into(),
+            Priority::Low,
+        )
+        .unwrap();
+    let large_low = pool
+        .insert_inbound_with_priority(request_with_payload(1000, time(30)).into(), Priority::Low)
+        .unwrap();
+    let medium_high = pool
+        .insert_outbound_request_with_priority(
+            request_with_payload(500, time(40)).into(),
+            time(5).into(),
+            Priority::High,
+        )
+        .unwrap();
+    // Priorities are ignored for guaranteed response messages.
+    let guaranteed = pool
+        .insert_inbound_with_priority(request(NO_DEADLINE).into(), Priority::Low)
+        .unwrap();
+    assert_eq!(Priority::Normal, pool.priority_of(guaranteed));
+    assert_invariants(&pool);
+
+    let shed_order: Vec<_> = std::iter::from_fn(|| pool.shed_largest_message())
+        .map(|(reference, _)| reference)
+        .collect();
+    assert_eq!(
+        vec![
+            SomeReference::Inbound(large_low),
+            SomeReference::Outbound(small_low),
+            SomeReference::Inbound(huge),
+            SomeReference::Outbound(medium_high),
+        ],
+        shed_order
+    );
+
+    // Only the guaranteed response request is left, no priorities.
+    assert_eq!(1, pool.len());
+    assert!(pool.priorities.is_empty());
+    assert_invariants(&pool);
+}
+
+/// Tests that per-priority best-effort message byte counts are maintained
+/// across inserts, replacements, takes, expiration and shedding.
+#[test]
+fn test_best_effort_message_bytes_by_priority() {
+    let mut pool = MessagePool::default();
+    let bytes_by_priority = |pool: &MessagePool| {
+        [
+            Priority::Low,
+            Priority::Normal,
+            Priority::High,
+            Priority::Highest,
+        ]
+        .map(|priority| pool.best_effort_message_bytes(priority))
+    };
+
+    let msg1 = request_with_payload(100, time(10));
+    let msg2 = response_with_payload(200, time(20));
+    let msg3 = request_with_payload(300, time(30));
+    let ref1 = pool
+        .insert_inbound_with_priority(msg1.clone().into(), Priority::Low)
+        .unwrap();
+    let ref2 = pool
+        .insert_outbound_response_with_priority(msg2.clone().into(), Priority::Highest)
+        .unwrap();
+    let ref3 = pool.insert_inbound(msg3.clone().into()).unwrap();
+    pool.insert_inbound_with_priority(request(NO_DEADLINE).into(), Priority::High)
+        .unwrap();
+    assert_eq!(
+        [
+            msg1.count_bytes(),
+            msg3.count_bytes(),
+            0,
+            msg2.count_bytes()
+        ],
+        bytes_by_priority(&pool)
+    );
+    assert_eq!(
+        pool.message_stats.best_effort_message_bytes,
+        bytes_by_priority(&pool).iter().sum::<usize>()
+    );
+
+    // Replace `msg1` with a larger request.
+    let msg4 = request_with_payload(400, time(10));
+    pool.replace(ref1, msg4.clone().into()).unwrap();
+    assert_eq!(Priority::Low, pool.priority_of(ref1));
+    assert_eq!(
+        [
+            msg4.count_bytes(),
+            msg3.count_bytes(),
+            0,
+            msg2.count_bytes()
+        ],
+        bytes_by_priority(&pool)
+    );
+
+    // Take `msg3`.
+    pool.take(ref3).unwrap();
+    assert_eq!(
+        [msg4.count_bytes(), 0, 0, msg2.count_bytes()],
+        bytes_by_priority(&pool)
+    );
+
+    // Shed the low priority `msg4`.
+    assert_eq!(
+        Some((SomeReference::Inbound(ref1), msg4.into())),
+        pool.shed_largest_message()
+    );
+    assert_eq!([0, 0, 0, msg2.count_bytes()], bytes_by_priority(&pool));
+
+    // Expire `msg2`.
+    assert_eq!(
+        vec![(SomeReference::Outbound(ref2), msg2.into())],
+        pool.expire_messages(time(21).into())
+    );
+    assert_eq!([0; Priority::COUNT], bytes_by_priority(&pool));
+    assert!(pool.priorities.is_empty());
+    assert_invariants(&pool);
+}
+
+/// Tests that priorities survive an encode-decode roundtrip; and that pools
+/// differing only in message priorities are not equal.
+#[test]
+fn encode_roundtrip_priorities() {
+    let mut pool = MessagePool::default();
+    let mut same_messages_pool = MessagePool::default();
+    pool.insert_inbound_with_priority(request(time(10)).into(), Priority::Low)
+        .unwrap();
+    same_messages_pool
+        .insert_inbound(request(time(10)).into())
+        .unwrap();
+    pool.insert_outbound_response_with_priority(response(time(20)).into(), Priority::High)
+        .unwrap();
+    same_messages_pool
+        .insert_outbound_response(response(time(20)).into())
+        .unwrap();
+
+    assert_ne!(pool, same_messages_pool);
+    assert!(pool.validate_eq(&same_messages_pool).is_err());
+
+    let encoded: pb_queues::MessagePool = (&pool).into();
+    assert_eq!(2, encoded.priorities.len());
+    let decoded: MessagePool = (encoded, 0).try_into().unwrap();
+    assert_eq!(pool, decoded);
+
+    // Default priorities are not persisted.
+    let encoded: pb_queues::MessagePool = (&same_messages_pool).into();
+    assert!(encoded.priorities.is_empty());
+}
+
+/// Tests that decoding fails on an invalid priority value.
+#[test]
+fn decode_with_invalid_priority_fails() {
+    let mut pool = MessagePool::default();
+    pool.insert_inbound_with_priority(request(time(10)).into(), Priority::Low)
+        .unwrap();
+    let mut encoded: pb_queues::MessagePool = (&pool).into();
+    encoded.priorities[0].priority = Priority::COUNT as u32;
+
+    assert_matches!(
+        MessagePool::try_from((encoded, 0)),
+        Err(ProxyDecodeError::Other(msg)) if msg.contains("Invalid message priority")
+    );
+}
+
 /// Tests that an encode-decode roundtrip yields a result equal to the original
 /// (and that the stats and priority queues of an organically constructed
 /// `MessagePool` match those of a deserialized one).
@@ -969,39 +2200,720 @@ fn encode_roundtrip() {
     let mut pool = MessagePool::default();
 
     // Insert one message of each kind / class / context.
-    pool.insert_inbound(request_with_payload(100, NO_DEADLINE).into());
-    pool.insert_inbound(request_with_payload(200, time(20)).into());
-    pool.insert_inbound(response_with_payload(300, NO_DEADLINE).into());
-    pool.insert_inbound(response_with_payload(400, time(40)).into());
+    pool.insert_inbound(request_with_payload(100, NO_DEADLINE).into())
+        .unwrap();
+    pool.insert_inbound(request_with_payload(200, time(20)).into())
+        .unwrap();
+    pool.insert_inbound(response_with_payload(300, NO_DEADLINE).into())
+        .unwrap();
+    pool.insert_inbound(response_with_payload(400, time(40)).into())
+        .unwrap();
     pool.insert_outbound_request(
         request_with_payload(500, NO_DEADLINE).into(),
         time(50).into(),
-    );
-    pool.insert_outbound_request(request_with_payload(600, time(60)).into(), time(65).into());
-    pool.insert_outbound_response(response_with_payload(700, NO_DEADLINE).into());
-    pool.insert_outbound_response(response_with_payload(800, time(80)).into());
+    )
+    .unwrap();
+    pool.insert_outbound_request(request_with_payload(600, time(60)).into(), time(65).into())
+        .unwrap();
+    pool.insert_outbound_response(response_with_payload(700, NO_DEADLINE).into())
+        .unwrap();
+    pool.insert_outbound_response(response_with_payload(800, time(80)).into())
+        .unwrap();
+    // Plus a couple of memory reservations, passed in explicitly when decoding.
+    pool.reserve_guaranteed_response_slot();
+    pool.reserve_guaranteed_response_slot();
 
     let encoded: pb_queues::MessagePool = (&pool).into();
-    let decoded = encoded.try_into().unwrap();
+    assert!(encoded.message_stats.is_some());
+    let decoded = (encoded, 2).try_into().unwrap();
+
+    assert_eq!(pool, decoded);
+
+    // Stats are recomputed from scratch when decoding a checkpoint that predates
+    // persisted stats, with the same result.
+    let mut encoded: pb_queues::MessagePool = (&pool).into();
+    encoded.message_stats = None;
+    let decoded = (encoded, 2).try_into().unwrap();
 
     assert_eq!(pool, decoded);
 }
 
+/// Tests that decoding a checkpoint whose persisted stats do not match its
+/// messages fails, in release builds too.
+#[test]
+fn decode_with_inconsistent_message_stats_fails() {
+    let mut pool = MessagePool::default();
+    pool.insert_inbound(request_with_payload(100, time(10)).into())
+        .unwrap();
+    pool.insert_outbound_response(response_with_payload(200, NO_DEADLINE).into())
+        .unwrap();
+
+    let mut encoded: pb_queues::MessagePool = (&pool).into();
+    encoded
+        .message_stats
+        .as_mut()
+        .unwrap()
+        .inbound_message_count += 1;
+
+    assert_matches!(
+        MessagePool::try_from((encoded, 0)),
+        Err(ProxyDecodeError::Other(msg)) if msg.contains("Unexpected message stats")
+    );
+}
+
 /// Tests an encode-decode roundtrip of an empty `MessagePool`.
 #[test]
 fn encode_roundtrip_empty() {
     let pool = MessagePool::default();
 
     let encoded: pb_queues::MessagePool = (&pool).into();
-    let decoded = encoded.try_into().unwrap();
+    let decoded = (encoded, 0).try_into().unwrap();
+
+    assert_eq!(pool, decoded);
+}
+
+/// Tests that a non-default request lifetime survives an encode-decode
+/// roundtrip; and that the default request lifetime is not persisted.
+#[test]
+fn encode_roundtrip_request_lifetime() {
+    let mut pool = MessagePool::default();
+    let encoded: pb_queues::MessagePool = (&pool).into();
+    assert_eq!(None, encoded.request_lifetime_nanos);
+
+    pool.set_request_lifetime(Duration::from_secs(1));
+    pool.insert_outbound_request(request(NO_DEADLINE).into(), time(10).into())
+        .unwrap();
+
+    let encoded: pb_queues::MessagePool = (&pool).into();
+    assert_eq!(Some(1_000_000_000), encoded.request_lifetime_nanos);
+    let decoded: MessagePool = (encoded, 0).try_into().unwrap();
 
+    assert_eq!(Duration::from_secs(1), decoded.request_lifetime());
     assert_eq!(pool, decoded);
 }
 
+/// Tests that guaranteed response requests in a pool with a 1 second request
+/// lifetime expire after 2 seconds, whereas with the default lifetime they don't.
+#[test]
+fn test_expire_messages_with_custom_request_lifetime() {
+    let mut short_lifetime_pool = MessagePool::default();
+    short_lifetime_pool.set_request_lifetime(Duration::from_secs(1));
+    let mut default_pool = MessagePool::default();
+
+    let reference = short_lifetime_pool
+        .insert_outbound_request(request(NO_DEADLINE).into(), time(10).into())
+        .unwrap();
+    default_pool
+        .insert_outbound_request(request(NO_DEADLINE).into(), time(10).into())
+        .unwrap();
+
+    let now = time(12).into();
+    assert!(short_lifetime_pool.has_expired_deadlines(now));
+    let expired = short_lifetime_pool.expire_messages(now);
+    assert_eq!(1, expired.len());
+    assert_eq!(SomeReference::Outbound(reference), expired[0].0);
+    assert_eq!(0, short_lifetime_pool.len());
+
+    assert!(!default_pool.has_expired_deadlines(now));
+    assert!(default_pool.expire_messages(now).is_empty());
+    assert_eq!(1, default_pool.len());
+
+    assert_invariants(&short_lifetime_pool);
+    assert_invariants(&default_pool);
+}
+
+/// Tests that enqueueing a guaranteed response consumes exactly the memory
+/// reservation made for it, without any change in guaranteed response memory
+/// usage (for a response of size `MAX_RESPONSE_COUNT_BYTES`).
+#[test]
+fn test_guaranteed_response_reservations() {
+    let mut pool = MessagePool::default();
+
+    // A guaranteed response of exactly `MAX_RESPONSE_COUNT_BYTES`.
+    let empty_response_size_bytes = response_with_payload(0, NO_DEADLINE).count_bytes();
+    let response = response_with_payload(
+        MAX_RESPONSE_COUNT_BYTES - empty_response_size_bytes,
+        NO_DEADLINE,
+    );
+    assert_eq!(MAX_RESPONSE_COUNT_BYTES, response.count_bytes());
+
+    // Induct a request and reserve memory for its response.
+    let request_ref = pool.insert_inbound(request(NO_DEADLINE).into()).unwrap();
+    pool.reserve_guaranteed_response_slot();
+    assert_eq!(1, pool.message_stats.guaranteed_response_reservations);
+    assert_eq!(
+        MAX_RESPONSE_COUNT_BYTES,
+        pool.message_stats.guaranteed_response_memory_usage()
+    );
+    assert_invariants(&pool);
+
+    // The reservation survives stats updates from consuming the request.
+    assert!(pool.take(request_ref).is_some());
+    assert_eq!(1, pool.message_stats.guaranteed_response_reservations);
+    assert_eq!(
+        MAX_RESPONSE_COUNT_BYTES,
+        pool.message_stats.guaranteed_response_memory_usage()
+    );
+    assert_invariants(&pool);
+
+    // Enqueueing the response releases exactly one reservation.
+    pool.insert_outbound_response(response.into()).unwrap();
+    pool.release_reservation();
+    assert_eq!(0, pool.message_stats.guaranteed_response_reservations);
+    // And memory usage stays flat.
+    assert_eq!(
+        MAX_RESPONSE_COUNT_BYTES,
+        pool.message_stats.guaranteed_response_memory_usage()
+    );
+    assert_invariants(&pool);
+}
+
+/// Releasing a reservation that was never made is a bug.
+#[test]
+#[should_panic(expected = "assertion failed: self.guaranteed_response_reservations > 0")]
+fn test_release_reservation_without_reservation() {
+    let mut pool = MessagePool::default();
+    pool.release_reservation();
+}
+
+#[test]
+fn test_check_invariants_detects_corruption() {
+    let mut pool = MessagePool::default();
+    pool.insert_inbound(request(time(10)).into()).unwrap();
+    pool.insert_outbound_request(request(NO_DEADLINE).into(), time(20).into())
+        .unwrap();
+    assert_invariants(&pool);
+
+    // Stats out of sync with the messages.
+    let mut corrupted = pool.clone();
+    corrupted.message_stats.size_bytes += 1;
+    assert_matches!(corrupted.check_invariants(), Err(msg) if msg.contains("message stats"));
+
+    // Best-effort message missing from the load shedding queue.
+    let mut corrupted = pool.clone();
+    Arc::make_mut(&mut corrupted.size_queue).pop_first();
+    assert_matches!(corrupted.check_invariants(), Err(msg) if msg.contains("load shedding queue"));
+
+    // Message missing from the deadline queue.
+    let mut corrupted = pool.clone();
+    let (deadline, id) = corrupted.deadline_queue.iter().next().unwrap();
+    Arc::make_mut(&mut corrupted.deadline_queue).remove(deadline, id);
+    assert_matches!(corrupted.check_invariants(), Err(msg) if msg.contains("deadline queue"));
+
+    // `Id` generator behind the pool's messages.
+    let mut corrupted = pool.clone();
+    corrupted.message_id_generator = 1;
+    assert_matches!(corrupted.check_invariants(), Err(msg) if msg.contains("out of bounds"));
+}
+
+/// Tests that `validate_invariants()` reports every violation, not just the first.
+#[test]
+fn test_validate_invariants_reports_all_violations() {
+    let mut pool = MessagePool::default();
+    pool.insert_inbound(request(time(10)).into()).unwrap();
+    pool.insert_outbound_request(request(NO_DEADLINE).into(), time(20).into())
+        .unwrap();
+    pool.insert_outbound_response(response(time(30)).into())
+        .unwrap();
+    assert_invariants(&pool);
+
+    // Corrupt the stats, a load shedding queue entry and the `Id` generator.
+    let mut corrupted = pool.clone();
+    corrupted.message_stats.size_bytes += 1;
+    let size_queue = Arc::make_mut(&mut corrupted.size_queue);
+    let (priority, size_bytes, id) = size_queue.pop_first().unwrap();
+    size_queue.insert((priority, size_bytes + 1, id));
+    corrupted.message_id_generator = 1;
+
+    let violations = corrupted.validate_invariants().unwrap_err();
+    assert!(violations.iter().any(|v| v.contains("message stats")));
+    assert!(violations
+        .iter()
+        .any(|v| v.contains("Load shedding queue entry mismatch")));
+    assert!(violations.iter().any(|v| v.contains("out of bounds")));
+
+    // `check_invariants()` reports the same violations, as a single message.
+    assert_eq!(Err(violations.join("; ")), corrupted.check_invariants());
+}
+
+/// Tests that `validate_invariants()` detects deadline queue entries that don't
+/// match the (possibly implicit) deadline of the message.
+#[test]
+fn test_validate_invariants_deadline_mismatch() {
+    let mut pool = MessagePool::default();
+    let id: Id = pool
+        .insert_outbound_request(request(NO_DEADLINE).into(), time(20).into())
+        .unwrap()
+        .into();
+    assert_invariants(&pool);
+
+    // Record a different implicit deadline for the outbound guaranteed request.
+    let mut corrupted = pool.clone();
+    let deadline = corrupted.outbound_guaranteed_request_deadlines[&id];
+    let deadline_queue = Arc::make_mut(&mut corrupted.deadline_queue);
+    deadline_queue.remove(deadline, id);
+    deadline_queue.insert(time(u32::MAX), id);
+
+    let violations = corrupted.validate_invariants().unwrap_err();
+    assert!(violations
+        .iter()
+        .any(|v| v.contains("Deadline queue entry mismatch")));
+}
+
+/// Tests that a cloned pool shares all storage with the original, until either
+/// of them is mutated; and that mutations only copy the collections they touch.
+#[test]
+fn test_clone_shares_storage_until_mutated() {
+    fn shares_storage(a: &MessagePool, b: &MessagePool) -> [bool; 6] {
+        [
+            Arc::ptr_eq(&a.messages, &b.messages),
+            Arc::ptr_eq(
+                &a.outbound_guaranteed_request_deadlines,
+                &b.outbound_guaranteed_request_deadlines,
+            ),
+            Arc::ptr_eq(&a.deadline_queue, &b.deadline_queue),
+            Arc::ptr_eq(&a.size_queue, &b.size_queue),
+            Arc::ptr_eq(&a.priorities, &b.priorities),
+            Arc::ptr_eq(&a.time_checkpoints, &b.time_checkpoints),
+        ]
+    }
+
+    let mut pool = MessagePool::default();
+    pool.insert_inbound(request(time(10)).into()).unwrap();
+    pool.insert_outbound_request(request(NO_DEADLINE).into(), time(20).into())
+        .unwrap();
+    let reference = pool.insert_inbound(response(NO_DEADLINE).into()).unwrap();
+    assert_invariants(&pool);
+
+    let snapshot = pool.clone();
+    assert_eq!([true; 6], shares_storage(&pool, &snapshot));
+
+    // Taking a guaranteed response only copies the messages.
+    let msg = pool.take(reference).unwrap();
+    assert_eq!(
+        [false, true, true, true, true, true],
+        shares_storage(&pool, &snapshot)
+    );
+    assert_eq!(3, snapshot.len());
+    assert_eq!(Some(&msg), snapshot.get(reference));
+    assert_invariants(&snapshot);
+
+    // Taking a stale reference does not copy anything.
+    let mut clone = pool.clone();
+    assert_eq!(None, clone.take(reference));
+    assert_eq!([true; 6], shares_storage(&pool, &clone));
+
+    // Expiring a best-effort request copies the deadline and load shedding
+    // queues, but not the unrelated outbound guaranteed request deadlines.
+    assert_eq!(1, clone.expire_messages(time(11).into()).len());
+    assert_eq!(
+        [false, true, false, false, false, true],
+        shares_storage(&pool, &clone)
+    );
+    assert_eq!(2, pool.len());
+    assert_invariants(&pool);
+    assert_invariants(&clone);
+}
+
+/// An operation applied to a `MessagePool` by `check_invariants_under_random_operations`.
+#[derive(Clone, Debug)]
+enum PoolOp {
+    InsertInbound {
+        response: bool,
+        best_effort: bool,
+        payload_size: usize,
+    },
+    InsertOutboundRequest {
+        best_effort: bool,
+        payload_size: usize,
+    },
+    InsertOutboundResponse {
+        best_effort: bool,
+        payload_size: usize,
+    },
+    /// Takes the message at the given index (modulo the number of references
+    /// handed out so far), if still present.
+    Take(usize),
+    ExpireMessages,
+    ShedLargestMessage,
+    AdvanceTime(u32),
+}
+
+fn arb_pool_op() -> impl Strategy<Value = PoolOp> {
+    prop_oneof![
+        (any::<bool>(), any::<bool>(), 0..2000_usize).prop_map(
+            |(response, best_effort, payload_size)| PoolOp::InsertInbound {
+                response,
+                best_effort,
+                payload_size
+            }
+        ),
+        (any::<bool>(), 0..2000_usize).prop_map(|(best_effort, payload_size)| {
+            PoolOp::InsertOutboundRequest {
+                best_effort,
+                payload_size,
+            }
+        }),
+        (any::<bool>(), 0..2000_usize).prop_map(|(best_effort, payload_size)| {
+            PoolOp::InsertOutboundResponse {
+                best_effort,
+                payload_size,
+            }
+        }),
+        any::<usize>().prop_map(PoolOp::Take),
+        Just(PoolOp::ExpireMessages),
+        Just(PoolOp::ShedLargestMessage),
+        (1..400_u32).prop_map(PoolOp::AdvanceTime),
+    ]
+}
+
+/// Applies `op` to `pool`, recording any newly handed out references into
+/// `references` and advancing `now` as requested.
+fn apply_pool_op(
+    pool: &mut MessagePool,
+    op: &PoolOp,
+    now: &mut CoarseTime,
+    references: &mut Vec<SomeReference>,
+) {
+    // Best-effort messages expire within a minute of `now`.
+    let deadline = |now: CoarseTime, best_effort: bool| {
+        if best_effort {
+            CoarseTime::from_secs_since_unix_epoch(now.as_secs_since_unix_epoch() + 60)
+        } else {
+            NO_DEADLINE
+        }
+    };
+
+    match *op {
+        PoolOp::InsertInbound {
+            response,
+            best_effort,
+            payload_size,
+        } => {
+            let msg: RequestOrResponse = if response {
+                response_with_payload(payload_size, deadline(*now, best_effort)).into()
+            } else {
+                request_with_payload(payload_size, deadline(*now, best_effort)).into()
+            };
+            references.push(SomeReference::Inbound(pool.insert_inbound(msg).unwrap()));
+        }
+        PoolOp::InsertOutboundRequest {
+            best_effort,
+            payload_size,
+        } => {
+            let request = request_with_payload(payload_size, deadline(*now, best_effort));
+            references.push(SomeReference::Outbound(
+                pool.insert_outbound_request(request.into(), (*now).into())
+                    .unwrap(),
+            ));
+        }
+        PoolOp::InsertOutboundResponse {
+            best_effort,
+            payload_size,
+        } => {
+            let response = response_with_payload(payload_size, deadline(*now, best_effort));
+            references.push(SomeReference::Outbound(
+                pool.insert_outbound_response(response.into()).unwrap(),
+            ));
+        }
+        PoolOp::Take(index) => {
+            if !references.is_empty() {
+                match references.swap_remove(index % references.len()) {
+                    SomeReference::Inbound(reference) => pool.take(reference),
+                    SomeReference::Outbound(reference) => pool.take(reference),
+                };
+            }
+        }
+        PoolOp::ExpireMessages => {
+            pool.expire_messages((*now).into());
+        }
+        PoolOp::ShedLargestMessage => {
+            pool.shed_largest_message();
+        }
+        PoolOp::AdvanceTime(seconds) => {
+            *now = CoarseTime::from_secs_since_unix_epoch(now.as_secs_since_unix_epoch() + seconds);
+        }
+    }
+}
+
+/// Applies random sequences of inserts, takes, expirations and load shedding to
+/// a `MessagePool`, validating all of its invariants after every operation.
+#[test_strategy::proptest(ProptestConfig { cases: 1000, ..ProptestConfig::default() })]
+fn check_invariants_under_random_operations(
+    #[strategy(proptest::collection::vec(arb_pool_op(), 0..200))] ops: Vec<PoolOp>,
+) {
+    let mut pool = MessagePool::default();
+    let mut now = time(1000);
+    let mut references = Vec::new();
+
+    for op in ops {
+        apply_pool_op(&mut pool, &op, &mut now, &mut references);
+        prop_assert_eq!(Ok(()), pool.validate_invariants(), "after {:?}", op);
+    }
+
+    // Invariants also hold after expiring and shedding everything possible.
+    pool.expire_messages(time(u32::MAX).into());
+    while pool.shed_largest_message().is_some() {}
+    assert_invariants(&pool);
+}
+
+/// Returns a copy of `pool` that shares no storage with it, by round-tripping
+/// it through its protobuf representation.
+fn deep_copy(pool: &MessagePool) -> MessagePool {
+    let encoded: pb_queues::MessagePool = pool.into();
+    let copy =
+        MessagePool::try_from((encoded, pool.message_stats.guaranteed_response_reservations))
+            .unwrap();
+    assert_eq!(pool, &copy);
+    copy
+}
+
+/// Tests that mutating a pool after cloning it leaves the clone unchanged, and
+/// the other way around.
+#[test_strategy::proptest]
+fn clone_is_isolated_from_subsequent_mutations(
+    #[strategy(proptest::collection::vec(arb_pool_op(), 0..100))] before: Vec<PoolOp>,
+    #[strategy(proptest::collection::vec(arb_pool_op(), 0..100))] after: Vec<PoolOp>,
+) {
+    let mut pool = MessagePool::default();
+    let mut now = time(1000);
+    let mut references = Vec::new();
+    for op in &before {
+        apply_pool_op(&mut pool, op, &mut now, &mut references);
+    }
+
+    let mut snapshot = pool.clone();
+    let expected_snapshot = deep_copy(&snapshot);
+
+    // Mutate the original; the snapshot is unaffected.
+    for op in &after {
+        apply_pool_op(&mut pool, op, &mut now, &mut references);
+    }
+    prop_assert_eq!(&expected_snapshot, &snapshot);
+    prop_assert_eq!(Ok(()), snapshot.validate_invariants());
+
+    // Mutate the snapshot; the original is unaffected.
+    let expected_pool = deep_copy(&pool);
+    snapshot.expire_messages(time(u32::MAX).into());
+    while snapshot.shed_largest_message().is_some() {}
+    prop_assert_eq!(&expected_pool, &pool);
+    prop_assert_eq!(Ok(()), pool.validate_invariants());
+}
+
+/// Tests that enabling the removal log changes neither pool equality nor the
+/// encoded pool, under random sequences of operations.
+#[test_strategy::proptest]
+fn removal_log_does_not_affect_equality_or_encoding(
+    #[strategy(proptest::collection::vec(arb_pool_op(), 0..200))] ops: Vec<PoolOp>,
+) {
+    const CAPACITY: usize = 5;
+    let mut pool = MessagePool::default();
+    let mut logging_pool = MessagePool::default();
+    logging_pool.set_removal_log_capacity(CAPACITY);
+    let (mut now, mut logging_now) = (time(1000), time(1000));
+    let (mut references, mut logging_references) = (Vec::new(), Vec::new());
+
+    for op in ops {
+        apply_pool_op(&mut pool, &op, &mut now, &mut references);
+        apply_pool_op(
+            &mut logging_pool,
+            &op,
+            &mut logging_now,
+            &mut logging_references,
+        );
+
+        prop_assert_eq!(&pool, &logging_pool, "after {:?}", op);
+        prop_assert_eq!(
+            pb_queues::MessagePool::from(&pool).encode_to_vec(),
+            pb_queues::MessagePool::from(&logging_pool).encode_to_vec(),
+            "after {:?}",
+            op
+        );
+        prop_assert!(logging_pool.recent_removals().len() <= CAPACITY);
+        prop_assert!(pool.recent_removals().is_empty());
+    }
+}
+
+/// Tests that the running stats of a pool produced via random insertions and
+/// takes always match stats computed from scratch.
+#[test_strategy::proptest]
+fn arb_pool_stats_match_recomputation(#[strategy(arb_message_pool(50))] pool: MessagePool) {
+    prop_assert_eq!(
+        &MessagePool::calculate_message_stats(
+            &pool.messages,
+            pool.message_stats.guaranteed_response_reservations
+        ),
+        pool.message_stats()
+    );
+    prop_assert_eq!(Ok(()), pool.check_invariants());
+}
+
+/// Tests that expiring messages at the latest possible time leaves no deadline
+/// queue entries for live messages.
+#[test_strategy::proptest]
+fn arb_pool_expire_all_empties_deadline_queue(#[strategy(arb_message_pool(50))] pool: MessagePool) {
+    let mut pool = pool;
+    let len_before = pool.len();
+    let expired = pool.expire_messages(time(u32::MAX).into());
+
+    prop_assert_eq!(len_before, pool.len() + expired.len());
+    prop_assert!(pool
+        .deadline_queue
+        .iter()
+        .all(|(_, id)| !pool.messages.contains_key(&id)));
+    // Only messages without (explicit or implicit) deadlines are left: guaranteed
+    // response messages other than outbound requests; and inbound responses.
+    for id in pool.messages.keys() {
+        prop_assert!(!id.is_outbound_guaranteed_request());
+        prop_assert!(
+            id.class() == Class::GuaranteedResponse
+                || (id.context() == Context::Inbound && id.kind() == Kind::Response)
+        );
+    }
+    prop_assert_eq!(Ok(()), pool.check_invariants());
+}
+
+/// Tests that `expire_messages()` returns the expired messages ordered by
+/// `(deadline, Id)` ascending.
+#[test_strategy::proptest]
+fn expire_messages_ordered_by_deadline_then_id(
+    #[strategy(arb_message_pool(50))] pool: MessagePool,
+    #[strategy(0..400_u64)] seconds_after_now: u64,
+) {
+    let mut pool = pool;
+    let now = Time::from(ARB_POOL_NOW) + Duration::from_secs(seconds_after_now);
+    let expected: Vec<(CoarseTime, Id)> = pool
+        .deadline_queue
+        .iter()
+        .filter(|(deadline, _)| *deadline < CoarseTime::floor(now))
+        .collect();
+    prop_assert!(expected.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let expired: Vec<Id> = pool
+        .expire_messages(now)
+        .iter()
+        .map(|(reference, _)| reference.into())
+        .collect();
+
+    prop_assert_eq!(
+        expected.into_iter().map(|(_, id)| id).collect::<Vec<_>>(),
+        expired
+    );
+}
+
+/// Tests that two pools holding the same messages, but whose deadline queues
+/// were built by inserting the same entries in different orders, expire the
+/// same messages in the same order.
+#[test_strategy::proptest]
+fn expire_messages_order_independent_of_insertion_order(
+    #[strategy(arb_message_pool(50))] pool: MessagePool,
+    #[strategy(Just(#pool.deadline_queue.iter().collect::<Vec<_>>()).prop_shuffle())]
+    shuffled_entries: Vec<(CoarseTime, Id)>,
+    #[strategy(0..400_u64)] seconds_after_now: u64,
+) {
+    let mut pool = pool;
+    let mut shuffled_pool = pool.clone();
+    let mut shuffled_deadline_queue = DeadlineQueue::default();
+    for (deadline, id) in shuffled_entries {
+        shuffled_deadline_queue.insert(deadline, id);
+    }
+    shuffled_pool.deadline_queue = Arc::new(shuffled_deadline_queue);
+    prop_assert_eq!(&pool, &shuffled_pool);
+
+    let now = Time::from(ARB_POOL_NOW) + Duration::from_secs(seconds_after_now);
+    prop_assert_eq!(
+        pool.expire_messages(now),
+        shuffled_pool.expire_messages(now)
+    );
+}
+
+/// Tests that a pool with pending expirations expires the same messages in the
+/// same order after a roundtrip through its protobuf representation.
+#[test_strategy::proptest]
+fn expire_messages_order_preserved_by_encode_roundtrip(
+    #[strategy(arb_message_pool(50))] pool: MessagePool,
+    #[strategy(0..400_u64)] seconds_after_now: u64,
+) {
+    let mut pool = pool;
+    let mut decoded = deep_copy(&pool);
+
+    let now = Time::from(ARB_POOL_NOW) + Duration::from_secs(seconds_after_now);
+    prop_assert_eq!(pool.expire_messages(now), decoded.expire_messages(now));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Expired messages not ordered by (deadline, Id)")]
+fn ensure_expiry_order_panics_on_unordered_pairs_in_debug_builds() {
+    let id1 = Id::from(new_request_reference(1, Class::BestEffort));
+    let id2 = Id::from(new_request_reference(2, Class::BestEffort));
+
+    ensure_expiry_order(&mut [(time(10), id2), (time(10), id1)]);
+}
+
+#[test]
+#[cfg(not(debug_assertions))]
+fn ensure_expiry_order_sorts_unordered_pairs_in_release_builds() {
+    let id1 = Id::from(new_request_reference(1, Class::BestEffort));
+    let id2 = Id::from(new_request_reference(2, Class::BestEffort));
+    let mut expired = [(time(20), id1), (time(10), id2), (time(10), id1)];
+
+    ensure_expiry_order(&mut expired);
+
+    assert_eq!([(time(10), id1), (time(10), id2), (time(20), id1)], expired);
+}
+
+/// Tests that `shed_largest_message()` never returns a guaranteed response
+/// message and sheds all best-effort messages.
+#[test_strategy::proptest]
+fn arb_pool_never_sheds_guaranteed_messages(#[strategy(arb_message_pool(50))] pool: MessagePool) {
+    let mut pool = pool;
+    let guaranteed_count = pool.guaranteed_message_count();
+
+    while let Some((reference, msg)) = pool.shed_largest_message() {
+        let id = Id::from(&reference);
+        prop_assert_eq!(Class::BestEffort, id.class());
+        prop_assert_ne!(NO_DEADLINE, msg.deadline());
+    }
+
+    prop_assert_eq!(0, pool.best_effort_message_count());
+    prop_assert_eq!(guaranteed_count, pool.guaranteed_message_count());
+    prop_assert_eq!(Ok(()), pool.check_invariants());
+}
+
 //
 // Fixtures and helper functions.
 //
 
+/// Asserts that all of `pool`'s invariants hold.
+fn assert_invariants<P: LoadSheddingPolicy>(pool: &MessagePool<P>) {
+    assert_eq!(Ok(()), pool.validate_invariants());
+}
+
+/// Asserts that `pool`'s constant time message counts match the expected values
+/// and the counts computed from scratch.
+fn assert_message_counts(
+    pool: &MessagePool,
+    expected_best_effort_count: usize,
+    expected_guaranteed_count: usize,
+) {
+    let actual_best_effort_count = pool
+        .messages
+        .keys()
+        .filter(|id| id.class() == Class::BestEffort)
+        .count();
+    assert_eq!(expected_best_effort_count, actual_best_effort_count);
+    assert_eq!(
+        expected_guaranteed_count,
+        pool.messages.len() - actual_best_effort_count
+    );
+
+    assert_eq!(expected_best_effort_count, pool.best_effort_message_count());
+    assert_eq!(expected_guaranteed_count, pool.guaranteed_message_count());
+    assert_eq!(pool.messages.is_empty(), pool.is_empty());
+    assert_invariants(pool);
+}
+
 fn request(deadline: CoarseTime) -> Request {
     RequestBuilder::new().deadline(deadline).build()
 }
@@ -1028,9 +2940,102 @@ fn time(seconds_since_unix_epoch: u32) -> CoarseTime {
     CoarseTime::from_secs_since_unix_epoch(seconds_since_unix_epoch)
 }
 
-fn assert_exact_messages_in_queue<T>(messages: BTreeSet<Id>, queue: &BTreeSet<(T, Id)>) {
+//
+// Proptest strategies, also used by `CanisterQueues` tests.
+//
+
+/// The time at which `arb_message_pool()` inserts outbound requests.
+pub(crate) const ARB_POOL_NOW: CoarseTime = CoarseTime::from_secs_since_unix_epoch(1000);
+
+/// Generates either `NO_DEADLINE` or a best-effort deadline shortly after
+/// `ARB_POOL_NOW`.
+pub(crate) fn arb_deadline() -> impl Strategy<Value = CoarseTime> {
+    let now = ARB_POOL_NOW.as_secs_since_unix_epoch();
+    prop_oneof![
+        Just(NO_DEADLINE),
+        (now + 1..now + 100).prop_map(CoarseTime::from_secs_since_unix_epoch),
+    ]
+}
+
+/// Generates a request with a random payload and a deadline drawn from the
+/// given strategy.
+pub(crate) fn arb_request(
+    deadline: impl Strategy<Value = CoarseTime>,
+) -> impl Strategy<Value = Request> {
+    (0..2000_usize, deadline)
+        .prop_map(|(payload_size, deadline)| request_with_payload(payload_size, deadline))
+}
+
+/// Generates a response with a random payload and a deadline drawn from the
+/// given strategy.
+pub(crate) fn arb_response(
+    deadline: impl Strategy<Value = CoarseTime>,
+) -> impl Strategy<Value = Response> {
+    (0..2000_usize, deadline)
+        .prop_map(|(payload_size, deadline)| response_with_payload(payload_size, deadline))
+}
+
+/// An operation applied by `arb_message_pool()` when building a pool.
+#[derive(Clone, Debug)]
+enum ArbPoolOp {
+    InsertInbound(RequestOrResponse),
+    InsertOutboundRequest(Request),
+    InsertOutboundResponse(Response),
+    /// Takes the message at the given index (modulo the number of messages
+    /// still in the pool).
+    Take(usize),
+}
+
+/// Generates a `MessagePool` holding up to `max_messages` inbound and outbound,
+/// guaranteed response and best-effort messages. The pool is built via the
+/// regular insertion APIs, with random `take()`s interleaved.
+pub(crate) fn arb_message_pool(max_messages: usize) -> impl Strategy<Value = MessagePool> {
+    let op = prop_oneof![
+        3 => prop_oneof![
+            arb_request(arb_deadline()).prop_map(RequestOrResponse::from),
+            arb_response(arb_deadline()).prop_map(RequestOrResponse::from),
+        ]
+        .prop_map(ArbPoolOp::InsertInbound),
+        2 => arb_request(arb_deadline()).prop_map(ArbPoolOp::InsertOutboundRequest),
+        2 => arb_response(arb_deadline()).prop_map(ArbPoolOp::InsertOutboundResponse),
+        1 => any::<usize>().prop_map(ArbPoolOp::Take),
+    ];
+
+    proptest::collection::vec(op, 0..=max_messages).prop_map(|ops| {
+        let mut pool = MessagePool::default();
+        let mut references = Vec::new();
+        for op in ops {
+            match op {
+                ArbPoolOp::InsertInbound(msg) => {
+                    references.push(SomeReference::Inbound(pool.insert_inbound(msg).unwrap()));
+                }
+                ArbPoolOp::InsertOutboundRequest(request) => {
+                    references.push(SomeReference::Outbound(
+                        pool.insert_outbound_request(request.into(), ARB_POOL_NOW.into())
+                            .unwrap(),
+                    ));
+                }
+                ArbPoolOp::InsertOutboundResponse(response) => {
+                    references.push(SomeReference::Outbound(
+                        pool.insert_outbound_response(response.into()).unwrap(),
+                    ));
+                }
+                ArbPoolOp::Take(index) if !references.is_empty() => {
+                    match references.swap_remove(index % references.len()) {
+                        SomeReference::Inbound(reference) => pool.take(reference),
+                        SomeReference::Outbound(reference) => pool.take(reference),
+                    };
+                }
+                ArbPoolOp::Take(_) => {}
+            }
+        }
+        pool
+    })
+}
+
+fn assert_exact_messages_in_queue<T, U>(messages: BTreeSet<Id>, queue: &BTreeSet<(T, U, Id)>) {
     assert_eq!(messages.len(), queue.len());
-    assert_eq!(messages, queue.iter().map(|(_, id)| *id).collect())
+    assert_eq!(messages, queue.iter().map(|(_, _, id)| *id).collect())
 }
 
 /// Generates an `InboundReference` for a request of the given class.
@@ -1097,9 +3102,13 @@ fn request_stats_delta2(req: &Request, context: Context) -> MessageStats {
     };
 
     let size_bytes = req.count_bytes();
-    let (best_effort_message_bytes, oversized_guaranteed_requests_extra_bytes) = match class {
-        GuaranteedResponse => (0, size_bytes.saturating_sub(MAX_RESPONSE_COUNT_BYTES)),
-        BestEffort => (size_bytes, 0),
+    let (
+        best_effort_message_bytes,
+        best_effort_message_count,
+        oversized_guaranteed_requests_extra_bytes,
+    ) = match class {
+        GuaranteedResponse => (0, 0, size_bytes.saturating_sub(MAX_RESPONSE_COUNT_BYTES)),
+        BestEffort => (size_bytes, 1, 0),
     };
     let (inbound_size_bytes, inbound_message_count, outbound_message_count) = if context == Inbound
     {
@@ -1120,6 +3129,7 @@ fn request_stats_delta2(req: &Request, context: Context) -> MessageStats {
     MessageStats {
         size_bytes,
         best_effort_message_bytes,
+        best_effort_message_count,
         guaranteed_responses_size_bytes,
         oversized_guaranteed_requests_extra_bytes,
         inbound_size_bytes,
@@ -1128,6 +3138,7 @@ fn request_stats_delta2(req: &Request, context: Context) -> MessageStats {
         inbound_guaranteed_request_count,
         inbound_guaranteed_response_count,
         outbound_message_count,
+        guaranteed_response_reservations: 0,
     }
 }
 
@@ -1144,10 +3155,11 @@ fn response_stats_delta2(rep: &Response, context: Context) -> MessageStats {
     };
 
     let size_bytes = rep.count_bytes();
-    let (best_effort_message_bytes, guaranteed_responses_size_bytes) = match class {
-        GuaranteedResponse => (0, size_bytes),
-        BestEffort => (size_bytes, 0),
-    };
+    let (best_effort_message_bytes, best_effort_message_count, guaranteed_responses_size_bytes) =
+        match class {
+            GuaranteedResponse => (0, 0, size_bytes),
+            BestEffort => (size_bytes, 1, 0),
+        };
     let (inbound_size_bytes, inbound_message_count, inbound_response_count, outbound_message_count) =
         if context == Inbound {
             (size_bytes, 1, 1, 0)
@@ -1167,6 +3179,7 @@ fn response_stats_delta2(rep: &Response, context: Context) -> MessageStats {
     MessageStats {
         size_bytes,
         best_effort_message_bytes,
+        best_effort_message_count,
         guaranteed_responses_size_bytes,
         oversized_guaranteed_requests_extra_bytes,
         inbound_size_bytes,
@@ -1175,5 +3188,6 @@ fn response_stats_delta2(rep: &Response, context: Context) -> MessageStats {
         inbound_guaranteed_request_count,
         inbound_guaranteed_response_count,
         outbound_message_count,
+        guaranteed_response_reservations: 0,
     }
 }
@@ -204,7 +204,7 @@ fn can_push_output_request() {
 /// Cannot push guaranteed response to output queues without having pushed an
 /// input request first.
 #[test]
-#[should_panic(expected = "assertion failed: self.guaranteed_response_memory_reservations > 0")]
+#[should_panic(expected = "assertion failed: self.guaranteed_response_reservations > 0")]
 fn cannot_push_output_response_guaranteed_without_input_request() {
     let mut fixture = CanisterQueuesFixture::new();
     fixture.push_output_response();
@@ -627,6 +627,50 @@ fn test_shed_largest_message() {
     assert!(!queues.shed_largest_message(&this, &local_canisters));
 }
 
+/// Tests that a low priority best-effort output request is shed before a much
+/// larger normal priority one.
+#[test]
+fn test_shed_low_priority_output_request_first() {
+    let mut queues = CanisterQueues::default();
+
+    let low_priority_request = request(1, SOME_DEADLINE);
+    let low_priority_request_size = low_priority_request.count_bytes();
+    queues
+        .push_output_request_with_priority(
+            low_priority_request.into(),
+            UNIX_EPOCH,
+            MessagePriority::Low,
+        )
+        .unwrap();
+    queues
+        .push_output_request(
+            request_with_payload(1000, 2, SOME_DEADLINE).into(),
+            UNIX_EPOCH,
+        )
+        .unwrap();
+    assert_eq!(
+        low_priority_request_size,
+        queues.best_effort_message_memory_usage_by_priority(MessagePriority::Low)
+    );
+
+    // The low priority request is shed first, despite being smaller.
+    let this = canister_test_id(13);
+    const NO_LOCAL_CANISTERS: BTreeMap<CanisterId, CanisterState> = BTreeMap::new();
+    assert!(queues.shed_largest_message(&this, &NO_LOCAL_CANISTERS));
+    assert_eq!(
+        0,
+        queues.best_effort_message_memory_usage_by_priority(MessagePriority::Low)
+    );
+    assert_matches!(
+        queues.pop_input(),
+        Some(CanisterInput::Response(response)) if response.originator_reply_callback.get() == 1
+    );
+    assert_matches!(
+        queues.output_into_iter().next(),
+        Some(RequestOrResponse::Request(request)) if request.sender_reply_callback.get() == 2
+    );
+}
+
 #[test]
 fn test_shed_inbound_response() {
     let mut queues = CanisterQueues::default();
@@ -656,11 +700,12 @@ fn test_shed_inbound_response() {
     let this = canister_test_id(13);
     const NO_LOCAL_CANISTERS: BTreeMap<CanisterId, CanisterState> = BTreeMap::new();
 
-    // Shed the largest response (callback ID 3).
+    // Shed the largest response (callback ID 3), after peeking at its size.
     let memory_usage3 = queues.best_effort_message_memory_usage();
+    let largest_size = queues.largest_best_effort_message_size().unwrap();
     assert!(queues.shed_largest_message(&this, &NO_LOCAL_CANISTERS));
     let memory_usage2 = queues.best_effort_message_memory_usage();
-    assert!(memory_usage2 < memory_usage3);
+    assert_eq!(memory_usage3 - largest_size, memory_usage2);
 
     // Shed the next largest response (callback ID 2).
     assert!(queues.shed_largest_message(&this, &NO_LOCAL_CANISTERS));
@@ -673,6 +718,7 @@ fn test_shed_inbound_response() {
     assert_eq!(0, queues.best_effort_message_memory_usage());
 
     // There's nothing else to shed.
+    assert_eq!(None, queues.largest_best_effort_message_size());
     assert!(!queues.shed_largest_message(&this, &NO_LOCAL_CANISTERS));
 
     // Peek then pop the response for callback ID 2.
@@ -694,6 +740,116 @@ fn test_shed_inbound_response() {
     assert_eq!(0, queues.input_queues_response_count());
 }
 
+#[test]
+fn test_shed_down_to_best_effort_count() {
+    let this = canister_test_id(13);
+    let other = canister_test_id(11);
+    const NO_LOCAL_CANISTERS: BTreeMap<CanisterId, CanisterState> = BTreeMap::new();
+
+    let mut queues = CanisterQueues::default();
+
+    // Push 3 best-effort input requests of increasing size and one guaranteed
+    // response input request.
+    for callback in 1..=3 {
+        queues
+            .push_input(
+                RequestBuilder::default()
+                    .sender(other)
+                    .receiver(this)
+                    .method_payload(vec![13; 1000 * callback])
+                    .deadline(SOME_DEADLINE)
+                    .build()
+                    .into(),
+                RemoteSubnet,
+            )
+            .unwrap();
+    }
+    queues
+        .push_input(
+            RequestBuilder::default()
+                .sender(other)
+                .receiver(this)
+                .build()
+                .into(),
+            RemoteSubnet,
+        )
+        .unwrap();
+    assert_eq!(4, queues.input_queues_message_count());
+
+    // Nothing to shed if already within the limit.
+    assert_eq!(
+        0,
+        queues.shed_down_to_best_effort_count(3, &this, &NO_LOCAL_CANISTERS)
+    );
+
+    // Shedding down to one best-effort message sheds the two largest ones.
+    assert_eq!(
+        2,
+        queues.shed_down_to_best_effort_count(1, &this, &NO_LOCAL_CANISTERS)
+    );
+    assert_eq!(2, queues.input_queues_message_count());
+    assert_eq!(2, queues.output_queues_reserved_slots());
+    assert_matches!(
+        queues.pop_input(),
+        Some(CanisterInput::Request(request)) if request.method_payload.len() == 1000
+    );
+
+    // The guaranteed response request is never shed.
+    assert_eq!(
+        0,
+        queues.shed_down_to_best_effort_count(0, &this, &NO_LOCAL_CANISTERS)
+    );
+    assert_matches!(
+        queues.pop_input(),
+        Some(CanisterInput::Request(request)) if request.deadline == NO_DEADLINE
+    );
+    assert!(!queues.has_input());
+}
+
+#[test]
+fn test_shed_all_best_effort_messages() {
+    let this = canister_test_id(13);
+    let other = canister_test_id(11);
+    const NO_LOCAL_CANISTERS: BTreeMap<CanisterId, CanisterState> = BTreeMap::new();
+
+    let mut queues = CanisterQueues::default();
+
+    // Push 2 best-effort input requests and one guaranteed response input request.
+    for deadline in [SOME_DEADLINE, SOME_DEADLINE, NO_DEADLINE] {
+        queues
+            .push_input(
+                RequestBuilder::default()
+                    .sender(other)
+                    .receiver(this)
+                    .deadline(deadline)
+                    .build()
+                    .into(),
+                RemoteSubnet,
+            )
+            .unwrap();
+    }
+    assert_eq!(3, queues.input_queues_message_count());
+
+    // Both best-effort requests are shed, the guaranteed response one is retained.
+    assert_eq!(
+        2,
+        queues.shed_all_best_effort_messages(&this, &NO_LOCAL_CANISTERS)
+    );
+    assert_eq!(0, queues.best_effort_message_memory_usage());
+    assert_eq!(1, queues.input_queues_message_count());
+    assert_matches!(
+        queues.pop_input(),
+        Some(CanisterInput::Request(request)) if request.deadline == NO_DEADLINE
+    );
+    assert!(!queues.has_input());
+
+    // Nothing left to shed.
+    assert_eq!(
+        0,
+        queues.shed_all_best_effort_messages(&this, &NO_LOCAL_CANISTERS)
+    );
+}
+
 /// Enqueues 3 requests for the same canister and consumes them.
 #[test]
 fn test_message_picking_round_robin_on_one_queue() {
@@ -714,6 +870,36 @@ fn test_message_picking_round_robin_on_one_queue() {
     assert!(fixture.pop_input().is_none());
 }
 
+/// Tests that inbound messages are dated by the time last observed by the
+/// queues, as are outbound requests.
+#[test]
+fn test_oldest_message_age() {
+    let mut fixture = CanisterQueuesFixture::new();
+    let t0 = UNIX_EPOCH + Duration::from_secs(1000);
+
+    // Nothing to date.
+    assert_eq!(None, fixture.queues.oldest_message_age(t0));
+
+    // An input request enqueued after observing `t0` is dated `t0`.
+    fixture.queues.observe_time(t0);
+    fixture.push_input_request().unwrap();
+    assert_eq!(
+        Some(Duration::from_secs(10)),
+        fixture
+            .queues
+            .oldest_message_age(t0 + Duration::from_secs(10))
+    );
+
+    // Once it is consumed, there is nothing left to date.
+    assert!(fixture.pop_input().is_some());
+    assert_eq!(
+        None,
+        fixture
+            .queues
+            .oldest_message_age(t0 + Duration::from_secs(10))
+    );
+}
+
 /// Enqueues 10 ingress messages and pops them.
 #[test]
 fn test_message_picking_ingress_only() {
@@ -2216,7 +2402,6 @@ fn test_stats_best_effort() {
 
     // One input queue slot, one output queue slot, zero memory reservations.
     expected_queue_stats = QueueStats {
-        guaranteed_response_memory_reservations: 0,
         input_queues_reserved_slots: 1,
         output_queues_reserved_slots: 1,
         transient_stream_guaranteed_responses_size_bytes: 0,
@@ -2227,6 +2412,7 @@ fn test_stats_best_effort() {
         &MessageStats {
             size_bytes: 2 * (request_size_bytes + response_size_bytes),
             best_effort_message_bytes: 2 * (request_size_bytes + response_size_bytes),
+            best_effort_message_count: 4,
             guaranteed_responses_size_bytes: 0,
             oversized_guaranteed_requests_extra_bytes: 0,
             inbound_size_bytes: request_size_bytes + response_size_bytes,
@@ -2235,6 +2421,7 @@ fn test_stats_best_effort() {
             inbound_guaranteed_request_count: 0,
             inbound_guaranteed_response_count: 0,
             outbound_message_count: 2,
+            guaranteed_response_reservations: 0,
         },
         queues.message_stats()
     );
@@ -2256,6 +2443,7 @@ fn test_stats_best_effort() {
         &MessageStats {
             size_bytes: request_size_bytes + response_size_bytes,
             best_effort_message_bytes: request_size_bytes + response_size_bytes,
+            best_effort_message_count: 2,
             guaranteed_responses_size_bytes: 0,
             oversized_guaranteed_requests_extra_bytes: 0,
             inbound_size_bytes: response_size_bytes,
@@ -2264,6 +2452,7 @@ fn test_stats_best_effort() {
             inbound_guaranteed_request_count: 0,
             inbound_guaranteed_response_count: 0,
             outbound_message_count: 1,
+            guaranteed_response_reservations: 0,
         },
         queues.message_stats()
     );
@@ -2278,7 +2467,6 @@ fn test_stats_best_effort() {
 
     // Input queue slot reservation was consumed by reject response.
     expected_queue_stats = QueueStats {
-        guaranteed_response_memory_reservations: 0,
         input_queues_reserved_slots: 0,
         output_queues_reserved_slots: 1,
         transient_stream_guaranteed_responses_size_bytes: 0,
@@ -2292,6 +2480,7 @@ fn test_stats_best_effort() {
         &message_pool::MessageStats {
             size_bytes: reject_response_size_bytes,
             best_effort_message_bytes: reject_response_size_bytes,
+            best_effort_message_count: 1,
             guaranteed_responses_size_bytes: 0,
             oversized_guaranteed_requests_extra_bytes: 0,
             inbound_size_bytes: reject_response_size_bytes,
@@ -2300,6 +2489,7 @@ fn test_stats_best_effort() {
             inbound_guaranteed_request_count: 0,
             inbound_guaranteed_response_count: 0,
             outbound_message_count: 0,
+            guaranteed_response_reservations: 0,
         },
         queues.message_stats()
     );
@@ -2372,7 +2562,6 @@ fn test_stats_guaranteed_response() {
 
     // One input queue slot, one output queue slot, two memory reservations.
     expected_queue_stats = QueueStats {
-        guaranteed_response_memory_reservations: 2,
         input_queues_reserved_slots: 1,
         output_queues_reserved_slots: 1,
         transient_stream_guaranteed_responses_size_bytes: 0,
@@ -2383,6 +2572,7 @@ fn test_stats_guaranteed_response() {
         &MessageStats {
             size_bytes: 2 * (request_size_bytes + response_size_bytes),
             best_effort_message_bytes: 0,
+            best_effort_message_count: 0,
             guaranteed_responses_size_bytes: 2 * response_size_bytes,
             oversized_guaranteed_requests_extra_bytes: 0,
             inbound_size_bytes: request_size_bytes + response_size_bytes,
@@ -2391,6 +2581,7 @@ fn test_stats_guaranteed_response() {
             inbound_guaranteed_request_count: 1,
             inbound_guaranteed_response_count: 1,
             outbound_message_count: 2,
+            guaranteed_response_reservations: 2,
         },
         queues.message_stats()
     );
@@ -2412,6 +2603,7 @@ fn test_stats_guaranteed_response() {
         &MessageStats {
             size_bytes: request_size_bytes + response_size_bytes,
             best_effort_message_bytes: 0,
+            best_effort_message_count: 0,
             guaranteed_responses_size_bytes: response_size_bytes,
             oversized_guaranteed_requests_extra_bytes: 0,
             inbound_size_bytes: response_size_bytes,
@@ -2420,6 +2612,7 @@ fn test_stats_guaranteed_response() {
             inbound_guaranteed_request_count: 0,
             inbound_guaranteed_response_count: 1,
             outbound_message_count: 1,
+            guaranteed_response_reservations: 2,
         },
         queues.message_stats()
     );
@@ -2442,14 +2635,19 @@ fn test_stats_guaranteed_response() {
 
     // Input queue slot and memory reservations were consumed.
     expected_queue_stats = QueueStats {
-        guaranteed_response_memory_reservations: 1,
         input_queues_reserved_slots: 0,
         output_queues_reserved_slots: 1,
         transient_stream_guaranteed_responses_size_bytes: 0,
     };
     assert_eq!(expected_queue_stats, queues.queue_stats);
-    // And we have all-zero message stats.
-    assert_eq!(&MessageStats::default(), queues.message_stats());
+    // And we have all-zero message stats, except for the one memory reservation.
+    assert_eq!(
+        &MessageStats {
+            guaranteed_response_reservations: 1,
+            ..Default::default()
+        },
+        queues.message_stats()
+    );
 
     // Consume the output queue slot reservation.
     queues.push_output_response(response4_.clone().into());
@@ -2502,7 +2700,6 @@ fn test_stats_oversized_requests() {
 
     // Two input queue slots, two output queue slots, two memory reservations.
     expected_queue_stats = QueueStats {
-        guaranteed_response_memory_reservations: 2,
         input_queues_reserved_slots: 2,
         output_queues_reserved_slots: 2,
         transient_stream_guaranteed_responses_size_bytes: 0,
@@ -2513,6 +2710,7 @@ fn test_stats_oversized_requests() {
         &MessageStats {
             size_bytes: 2 * (best_effort_size_bytes + guaranteed_size_bytes),
             best_effort_message_bytes: 2 * best_effort_size_bytes,
+            best_effort_message_count: 2,
             guaranteed_responses_size_bytes: 0,
             oversized_guaranteed_requests_extra_bytes: 2 * guaranteed_extra_bytes,
             inbound_size_bytes: best_effort_size_bytes + guaranteed_size_bytes,
@@ -2521,6 +2719,7 @@ fn test_stats_oversized_requests() {
             inbound_guaranteed_request_count: 1,
             inbound_guaranteed_response_count: 0,
             outbound_message_count: 2,
+            guaranteed_response_reservations: 2,
         },
         queues.message_stats()
     );
@@ -2542,6 +2741,7 @@ fn test_stats_oversized_requests() {
         &MessageStats {
             size_bytes: best_effort_size_bytes + guaranteed_size_bytes,
             best_effort_message_bytes: best_effort_size_bytes,
+            best_effort_message_count: 1,
             guaranteed_responses_size_bytes: 0,
             oversized_guaranteed_requests_extra_bytes: guaranteed_extra_bytes,
             inbound_size_bytes: 0,
@@ -2550,6 +2750,7 @@ fn test_stats_oversized_requests() {
             inbound_guaranteed_request_count: 0,
             inbound_guaranteed_response_count: 0,
             outbound_message_count: 2,
+            guaranteed_response_reservations: 2,
         },
         queues.message_stats()
     );
@@ -2567,7 +2768,6 @@ fn test_stats_oversized_requests() {
 
     // Input queue slots and the input queue memory reservation were consumed.
     expected_queue_stats = QueueStats {
-        guaranteed_response_memory_reservations: 1,
         input_queues_reserved_slots: 0,
         output_queues_reserved_slots: 2,
         transient_stream_guaranteed_responses_size_bytes: 0,
@@ -2580,10 +2780,139 @@ fn test_stats_oversized_requests() {
 
     // No change in slot and memory reservations.
     assert_eq!(expected_queue_stats, queues.queue_stats);
-    // But back to all-zero message stats.
+    // But back to all-zero message stats, except for the one memory reservation.
+    assert_eq!(
+        &MessageStats {
+            guaranteed_response_reservations: 1,
+            ..Default::default()
+        },
+        queues.message_stats()
+    );
+}
+
+/// Tests that enqueueing a guaranteed response consumes exactly the memory
+/// reservation made for it, and that (for a response of exactly
+/// `MAX_RESPONSE_COUNT_BYTES`) guaranteed response memory usage stays flat.
+#[test]
+fn test_guaranteed_response_reservation_released_on_push() {
+    let mut queues = CanisterQueues::default();
+
+    let empty_response_size_bytes = response_with_payload(0, 1, NO_DEADLINE).count_bytes();
+    let response = response_with_payload(
+        MAX_RESPONSE_COUNT_BYTES - empty_response_size_bytes,
+        1,
+        NO_DEADLINE,
+    );
+    assert_eq!(MAX_RESPONSE_COUNT_BYTES, response.count_bytes());
+
+    // Induct and consume a guaranteed response request, making a reservation.
+    queues
+        .push_input(request(1, NO_DEADLINE).into(), LocalSubnet)
+        .unwrap();
+    queues.pop_input().unwrap();
+    assert_eq!(1, queues.guaranteed_response_memory_reservations());
+    assert_eq!(
+        MAX_RESPONSE_COUNT_BYTES,
+        queues.guaranteed_response_memory_usage()
+    );
+
+    // Enqueueing the response consumes the reservation.
+    queues.push_output_response(response.into());
+    assert_eq!(0, queues.guaranteed_response_memory_reservations());
+    assert_eq!(
+        MAX_RESPONSE_COUNT_BYTES,
+        queues.guaranteed_response_memory_usage()
+    );
+
+    // And routing it leaves no memory usage behind.
+    queues.output_into_iter().next().unwrap();
+    assert_eq!(0, queues.guaranteed_response_memory_usage());
     assert_eq!(&MessageStats::default(), queues.message_stats());
 }
 
+/// Tests that pushing into `CanisterQueues` backed by a full pool fails with
+/// `MessagePoolFull` and leaves the queues unchanged.
+#[test]
+fn test_push_into_full_pool() {
+    let this = canister_test_id(1);
+    let other = canister_test_id(2);
+
+    let mut queues = CanisterQueues::default();
+    queues.set_message_capacity(Some(1));
+
+    let request = RequestBuilder::default()
+        .sender(other)
+        .receiver(this)
+        .build();
+    queues
+        .push_input(request.clone().into(), LocalSubnet)
+        .unwrap();
+
+    let before = queues.clone();
+    assert_matches!(
+        queues.push_input(request.into(), LocalSubnet),
+        Err((StateError::MessagePoolFull { capacity: 1 }, _))
+    );
+    let request = RequestBuilder::default()
+        .sender(this)
+        .receiver(other)
+        .build();
+    assert_matches!(
+        queues.push_output_request(request.into(), UNIX_EPOCH),
+        Err((StateError::MessagePoolFull { capacity: 1 }, _))
+    );
+    assert_eq!(before, queues);
+}
+
+/// Tests that responses (including timeout responses) are exempt from the
+/// message pool capacity, as they fill previously reserved slots.
+#[test]
+fn test_push_responses_into_full_pool() {
+    let this = canister_test_id(13);
+    let mut queues = CanisterQueues::default();
+    queues.set_message_capacity(Some(2));
+
+    // Two outgoing guaranteed response requests fill the pool.
+    queues
+        .push_output_request(request(1, NO_DEADLINE).into(), UNIX_EPOCH)
+        .unwrap();
+    queues
+        .push_output_request(request(2, NO_DEADLINE).into(), UNIX_EPOCH)
+        .unwrap();
+    assert_matches!(
+        queues.push_input(request(3, NO_DEADLINE).into(), LocalSubnet),
+        Err((StateError::MessagePoolFull { capacity: 2 }, _))
+    );
+
+    // Route the first request and induct an incoming request in its place.
+    queues.output_into_iter().next().unwrap();
+    queues
+        .push_input(request(3, NO_DEADLINE).into(), LocalSubnet)
+        .unwrap();
+
+    // The response to the first request is inducted into the full pool.
+    queues
+        .push_input(response(1, NO_DEADLINE).into(), LocalSubnet)
+        .unwrap();
+
+    // The response to the incoming request is enqueued into the full pool.
+    assert_matches!(queues.pop_input(), Some(CanisterInput::Request(_)));
+    queues.push_output_response(response(3, NO_DEADLINE).into());
+
+    // And so is the timeout response for the second request.
+    assert_eq!(
+        1,
+        queues.time_out_messages(
+            UNIX_EPOCH + REQUEST_LIFETIME + Duration::from_secs(1),
+            &this,
+            &BTreeMap::new()
+        )
+    );
+    assert_matches!(queues.pop_input(), Some(CanisterInput::Response(_)));
+    assert_matches!(queues.pop_input(), Some(CanisterInput::Response(_)));
+    assert_eq!(None, queues.pop_input());
+}
+
 /// Simulates sending an outgoing request and receiving an incoming response,
 /// calling `garbage_collect()` throughout. This is always a no-op, until after
 /// the response was consumed, when the queue pair is GC-ed and all fields are
@@ -2677,6 +3006,29 @@ fn test_garbage_collect_restores_defaults() {
     assert_eq!(0, pb_queues::CanisterQueues::from(&queues).encoded_len());
 }
 
+/// Tests that `garbage_collect()` retains the message capacity and the load
+/// shedding policy, which are configuration rather than state.
+#[test]
+fn test_garbage_collect_retains_configuration() {
+    let this = canister_test_id(1);
+
+    let mut queues = CanisterQueues::default();
+    queues.set_message_capacity(Some(10));
+    queues.set_load_shedding_policy(LoadSheddingPolicyKind::OldestFirst);
+
+    // Push and pop an ingress message, then garbage collect.
+    queues.push_ingress(IngressBuilder::default().receiver(this).build());
+    assert!(queues.pop_input().is_some());
+    queues.garbage_collect();
+
+    assert_eq!(CanisterQueues::default(), queues);
+    assert_eq!(Some(10), queues.store.pool.max_messages());
+    assert_eq!(
+        LoadSheddingPolicyKind::OldestFirst,
+        *queues.store.pool.load_shedding_policy()
+    );
+}
+
 #[test]
 fn test_reject_subnet_output_request() {
     let this = canister_test_id(1);
@@ -2853,6 +3205,72 @@ fn test_peek_output_with_stale_references() {
     assert!(queues.store.pool.len() == 2);
 }
 
+/// Tests that replacing an output message preserves its queue position and
+/// updates the queue sizes; and that mismatched replacements are rejected.
+#[test]
+fn test_replace_output_message() {
+    let mut queues = CanisterQueues::default();
+    let this = canister_test_id(1);
+    let other = canister_test_id(2);
+
+    let request = |payload_size, deadline| {
+        RequestBuilder::default()
+            .sender(this)
+            .receiver(other)
+            .method_payload(vec![13; payload_size])
+            .deadline(deadline)
+            .build()
+    };
+    let request1 = request(1000, SOME_DEADLINE);
+    let request2 = request(10, SOME_DEADLINE);
+    queues
+        .push_output_request(request1.clone().into(), UNIX_EPOCH)
+        .unwrap();
+    queues
+        .push_output_request(request2.clone().into(), UNIX_EPOCH)
+        .unwrap();
+    let best_effort_memory_usage = queues.best_effort_message_memory_usage();
+
+    // No output queue to replace into.
+    let truncated: RequestOrResponse = request(100, SOME_DEADLINE).into();
+    assert_eq!(
+        Err(truncated.clone()),
+        queues.replace_output_message(&this, truncated.clone())
+    );
+    // Different kind, class or deadline.
+    let mismatched: [RequestOrResponse; 3] = [
+        ResponseBuilder::default()
+            .respondent(this)
+            .originator(other)
+            .deadline(SOME_DEADLINE)
+            .build()
+            .into(),
+        request(100, NO_DEADLINE).into(),
+        request(100, coarse_time(13)).into(),
+    ];
+    for msg in mismatched {
+        assert_eq!(Err(msg.clone()), queues.replace_output_message(&other, msg));
+    }
+    assert_eq!(
+        best_effort_memory_usage,
+        queues.best_effort_message_memory_usage()
+    );
+
+    // Replacing the front message with a truncated one keeps it at the front.
+    assert_eq!(
+        Ok(request1.into()),
+        queues.replace_output_message(&other, truncated.clone())
+    );
+    assert_eq!(
+        best_effort_memory_usage - 900,
+        queues.best_effort_message_memory_usage()
+    );
+    assert_eq!(Some(&truncated), queues.peek_output(&other));
+    assert_eq!(Some(truncated), queues.pop_canister_output(&other));
+    assert_eq!(Some(request2.into()), queues.pop_canister_output(&other));
+    assert!(!queues.has_output());
+}
+
 // Must be duplicated here, because the `ic_test_utilities` one pulls in the
 // `CanisterQueues` defined by its `ic_replicated_state`, not the ones from
 // `crate` and we wouldn't have access to its non-public methods.
@@ -3199,6 +3617,81 @@ fn has_expired_deadlines_reports_correctly() {
     assert!(canister_queues.has_expired_deadlines(time101));
 }
 
+/// Tests that `next_message_expiry()` returns the earliest (explicit or implicit)
+/// deadline of any enqueued message.
+#[test]
+fn next_message_expiry_returns_earliest_deadline() {
+    let mut canister_queues = CanisterQueues::default();
+    assert_eq!(None, canister_queues.next_message_expiry());
+
+    // An outbound guaranteed response request expires after `REQUEST_LIFETIME`.
+    let time0 = Time::from_secs_since_unix_epoch(0).unwrap();
+    canister_queues
+        .push_output_request(request(1, NO_DEADLINE).into(), time0)
+        .unwrap();
+    assert_eq!(
+        Some(time0 + REQUEST_LIFETIME),
+        canister_queues.next_message_expiry()
+    );
+
+    // An inbound best-effort request with an earlier deadline expires first.
+    canister_queues
+        .push_input(request(2, coarse_time(100)).into(), LocalSubnet)
+        .unwrap();
+    assert_eq!(
+        Some(Time::from_secs_since_unix_epoch(100).unwrap()),
+        canister_queues.next_message_expiry()
+    );
+
+    // Once it is consumed, the outbound request is again the next to expire.
+    assert_matches!(canister_queues.pop_input(), Some(CanisterInput::Request(_)));
+    assert_eq!(
+        Some(time0 + REQUEST_LIFETIME),
+        canister_queues.next_message_expiry()
+    );
+}
+
+#[test]
+fn recent_message_removals_records_consumed_and_expired_messages() {
+    let mut canister_queues = CanisterQueues::default();
+    canister_queues.set_removal_log_capacity(10);
+
+    // A consumed message is logged.
+    canister_queues
+        .push_input(request(1, coarse_time(100)).into(), LocalSubnet)
+        .unwrap();
+    assert_matches!(canister_queues.pop_input(), Some(CanisterInput::Request(_)));
+
+    // So is an expired message.
+    canister_queues
+        .push_input(request(2, coarse_time(100)).into(), LocalSubnet)
+        .unwrap();
+    assert_eq!(
+        1,
+        canister_queues.time_out_messages(
+            coarse_time(200).into(),
+            &canister_test_id(13),
+            &BTreeMap::new()
+        )
+    );
+
+    let removals = canister_queues.recent_message_removals();
+    assert_eq!(
+        vec![MessageRemovalReason::Taken, MessageRemovalReason::Expired],
+        removals.iter().map(|r| r.reason).collect::<Vec<_>>()
+    );
+    for removal in &removals {
+        assert_eq!(MessageKind::Request, removal.kind);
+        assert_eq!(MessageContext::Inbound, removal.context);
+        assert_eq!(MessageClass::BestEffort, removal.class);
+        assert_eq!(coarse_time(100), removal.deadline);
+    }
+
+    // A capacity of zero disables (and clears) the removal log.
+    canister_queues.set_removal_log_capacity(0);
+    assert!(canister_queues.recent_message_removals().is_empty());
+}
+
 /// Tests `time_out_messages` on an instance of `CanisterQueues` that contains exactly 4 output messages.
 /// - A guaranteed response output request addressed to self.
 /// - A best-effort output request addressed to a local canister.
@@ -9,7 +9,8 @@ use crate::metadata_state::subnet_call_context_manager::InstallCodeCallId;
 use crate::page_map::PageAllocatorFileDescriptor;
 use crate::replicated_state::MR_SYNTHETIC_REJECT_MESSAGE_MAX_LEN;
 use crate::{
-    CanisterQueues, CanisterState, CheckpointLoadingMetrics, InputQueueType, PageMap, StateError,
+    CanisterQueues, CanisterState, CheckpointLoadingMetrics, InputQueueType, MessageDropStats,
+    MessagePriority, PageMap, StateError,
 };
 pub use call_context_manager::{CallContext, CallContextAction, CallContextManager, CallOrigin};
 use ic_base_types::NumSeconds;
@@ -47,6 +48,7 @@ use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::convert::{TryFrom, TryInto};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use strum_macros::EnumIter;
 
 lazy_static! {
@@ -1486,6 +1488,26 @@ impl SystemState {
         self.queues.push_output_request(msg, time)
     }
 
+    /// Pushes a `Request` into the relevant output queue, with the given load
+    /// shedding priority. Lower priority best-effort requests are shed before
+    /// higher priority ones, regardless of size.
+    ///
+    /// See [`Self::push_output_request`] for further details.
+    pub fn push_output_request_with_priority(
+        &mut self,
+        msg: Arc<Request>,
+        time: Time,
+        priority: MessagePriority,
+    ) -> Result<(), (StateError, Arc<Request>)> {
+        assert_eq!(
+            msg.sender, self.canister_id,
+            "Expected `Request` to have been sent by canister ID {}, but instead got {}",
+            self.canister_id, msg.sender
+        );
+        self.queues
+            .push_output_request_with_priority(msg, time, priority)
+    }
+
     /// See documentation for [`CanisterQueues::reject_subnet_output_request`].
     pub fn reject_subnet_output_request(
         &mut self,
@@ -2007,6 +2029,30 @@ impl SystemState {
         self.queues.has_expired_deadlines(current_time)
     }
 
+    /// Sets the lifetime of guaranteed response call requests subsequently
+    /// enqueued into the canister's output queues.
+    ///
+    /// See [`CanisterQueues::set_request_lifetime`] for further details.
+    pub fn set_queues_request_lifetime(&mut self, request_lifetime: Duration) {
+        self.queues.set_request_lifetime(request_lifetime);
+    }
+
+    /// Returns the counts of messages dropped from the canister queues since the
+    /// previous call, resetting them.
+    ///
+    /// See [`CanisterQueues::take_message_drop_stats`] for further details.
+    pub fn take_message_drop_stats(&mut self) -> MessageDropStats {
+        self.queues.take_message_drop_stats()
+    }
+
+    /// Records the current time with the canister queues, for dating enqueued
+    /// messages.
+    ///
+    /// See [`CanisterQueues::observe_time`] for further details.
+    pub fn observe_queues_time(&mut self, current_time: Time) {
+        self.queues.observe_time(current_time);
+    }
+
     /// Drops expired messages given a current time. Returns the number of messages
     /// that were timed out.
     ///
@@ -74,7 +74,8 @@ pub use canister_state::{
         CallOrigin, CanisterMetrics, CanisterStatus, ExecutionTask, SystemState,
     },
     CanisterQueues, CanisterState, EmbedderCache, ExecutionState, ExportedFunctions, Global,
-    NumWasmPages, SchedulerState,
+    LoadSheddingPolicyKind, MessageClass, MessageContext, MessageDropStats, MessageKind,
+    MessagePriority, MessageRemovalReason, MessageRemovalRecord, NumWasmPages, SchedulerState,
 };
 pub use metadata_state::{
     IngressHistoryState, NetworkTopology, Stream, SubnetTopology, SystemMetadata,
@@ -12,7 +12,7 @@ use crate::{
         subnet_call_context_manager::{IDkgDealingsContext, SignWithThresholdContext},
         StreamMap,
     },
-    CanisterQueues,
+    CanisterQueues, MessageDropStats,
 };
 use ic_base_types::PrincipalId;
 use ic_btc_replica_types::BitcoinAdapterResponse;
@@ -37,6 +37,7 @@ use rand::{Rng, SeedableRng};
 use rand_chacha::ChaChaRng;
 use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::sync::Arc;
+use std::time::Duration;
 use strum_macros::{EnumCount, EnumIter};
 
 /// Maximum message length of a synthetic reject response produced by message
@@ -101,6 +102,10 @@ pub enum StateError {
     /// Message enqueuing failed due to full in/out queue.
     QueueFull { capacity: usize },
 
+    /// Message enqueuing failed because the canister's message pool is at its
+    /// configured capacity.
+    MessagePoolFull { capacity: usize },
+
     /// Message enqueuing would have caused the canister or subnet to run over
     /// their memory limit.
     OutOfMemory { requested: NumBytes, available: i64 },
@@ -254,6 +259,7 @@ impl PeekableOutputIterator for OutputIterator<'_> {
 }
 
 pub const LABEL_VALUE_QUEUE_FULL: &str = "QueueFull";
+pub const LABEL_VALUE_MESSAGE_POOL_FULL: &str = "MessagePoolFull";
 pub const LABEL_VALUE_OUT_OF_MEMORY: &str = "OutOfMemory";
 pub const LABEL_VALUE_INVALID_RESPONSE: &str = "InvalidResponse";
 pub const LABEL_VALUE_BITCOIN_NON_MATCHING_RESPONSE: &str = "BitcoinNonMatchingResponse";
@@ -267,6 +273,7 @@ impl StateError {
             StateError::CanisterStopped(_) => LABEL_VALUE_CANISTER_STOPPED,
             StateError::CanisterStopping(_) => LABEL_VALUE_CANISTER_STOPPING,
             StateError::QueueFull { .. } => LABEL_VALUE_QUEUE_FULL,
+            StateError::MessagePoolFull { .. } => LABEL_VALUE_MESSAGE_POOL_FULL,
             StateError::OutOfMemory { .. } => LABEL_VALUE_OUT_OF_MEMORY,
             StateError::NonMatchingResponse { .. } => LABEL_VALUE_INVALID_RESPONSE,
             StateError::BitcoinNonMatchingResponse { .. } => {
@@ -305,6 +312,9 @@ impl std::fmt::Display for StateError {
             StateError::QueueFull { capacity } => {
                 write!(f, "Maximum queue capacity {} reached", capacity)
             }
+            StateError::MessagePoolFull { capacity } => {
+                write!(f, "Maximum message pool capacity {} reached", capacity)
+            }
             StateError::OutOfMemory {
                 requested,
                 available,
@@ -920,6 +930,43 @@ impl ReplicatedState {
         crate::bitcoin::push_response(self, response)
     }
 
+    /// Records the state time with all canister and subnet queues, so that the
+    /// messages enqueued from here on can be dated.
+    ///
+    /// See `CanisterQueues::observe_time` for further details.
+    pub fn observe_message_time(&mut self) {
+        let current_time = self.metadata.time();
+        for canister in self.canister_states.values_mut() {
+            canister.system_state.observe_queues_time(current_time);
+        }
+        self.subnet_queues.observe_time(current_time);
+    }
+
+    /// Sets the lifetime of guaranteed response call requests subsequently
+    /// enqueued into all canister and subnet output queues.
+    ///
+    /// Applied every round (canisters created during a round use the default until
+    /// the next one); a non-default lifetime is also persisted with the queues.
+    pub fn set_request_lifetime(&mut self, request_lifetime: Duration) {
+        for canister in self.canister_states.values_mut() {
+            canister
+                .system_state
+                .set_queues_request_lifetime(request_lifetime);
+        }
+        self.subnet_queues.set_request_lifetime(request_lifetime);
+    }
+
+    /// Returns the counts of messages expired or shed from all canister and subnet
+    /// queues since the previous call, resetting them. Meant to be rolled up into
+    /// replica metrics once per round.
+    pub fn take_message_drop_stats(&mut self) -> MessageDropStats {
+        let mut stats = self.subnet_queues.take_message_drop_stats();
+        for canister in self.canister_states.values_mut() {
+            stats += canister.system_state.take_message_drop_stats();
+        }
+        stats
+    }
+
     /// Times out all messages with expired deadlines (given the state time) in all
     /// canister (but not subnet) queues. Returns the number of timed out messages.
     ///
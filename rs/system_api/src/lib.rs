@@ -1,6 +1,7 @@
 use ic_base_types::PrincipalIdBlobParseError;
 use ic_config::embedders::StableMemoryPageLimit;
 use ic_config::flag_status::FlagStatus;
+use ic_crypto_sha2::Sha256;
 use ic_cycles_account_manager::ResourceSaturation;
 use ic_error_types::RejectCode;
 use ic_interfaces::execution_environment::{
@@ -31,6 +32,7 @@ use sandbox_safe_system_state::{CanisterStatusView, SandboxSafeSystemState, Syst
 use serde::{Deserialize, Serialize};
 use stable_memory::StableMemory;
 use std::{
+    cell::RefCell,
     convert::{From, TryFrom},
     rc::Rc,
 };
@@ -61,6 +63,14 @@ pub const MAX_CALL_TIMEOUT_SECONDS: u32 = 300;
 // This macro is used in system calls for tracing.
 macro_rules! trace_syscall {
     ($self:ident, $name:ident, $result:expr $( , $args:expr )*) => {{
+        if let Some(trace) = $self.syscall_trace.borrow_mut().as_mut() {
+            trace.push(format!(
+                "{}: {:?} => {:?}",
+                stringify!($name),
+                ($(&$args, )*),
+                &$result
+            ));
+        }
         if TRACE_SYSCALLS {
             // Output to both logger and stderr to simplify debugging.
             error!(
@@ -83,21 +93,35 @@ macro_rules! trace_syscall {
 }
 
 // This helper is used in system calls for displaying a summary hash of a heap region.
+// It is only evaluated as an argument of `trace_syscall!`, i.e. when tracing or
+// recording of system calls is enabled.
 #[inline]
 fn summarize(heap: &[u8], start: usize, size: usize) -> u64 {
-    if TRACE_SYSCALLS {
-        let start = start.min(heap.len());
-        let end = (start + size).min(heap.len());
-        // The actual hash function doesn't matter much as long as it is
-        // cheap to compute and maps the input to u64 reasonably well.
-        let mut sum = 0;
-        for (i, byte) in heap[start..end].iter().enumerate() {
-            sum += (i + 1) as u64 * *byte as u64
-        }
-        sum
-    } else {
-        0
-    }
+    let start = start.min(heap.len());
+    let end = (start + size).min(heap.len());
+    // The actual hash function doesn't matter much as long as it is
+    // cheap to compute and maps the input to u64 reasonably well.
+    let mut sum = 0;
+    for (i, byte) in heap[start..end].iter().enumerate() {
+        sum = ((i + 1) as u64)
+            .wrapping_mul(*byte as u64)
+            .wrapping_add(sum)
+    }
+    sum
+}
+
+/// Renders the length and the SHA-256 hash of a payload taken from canister
+/// memory, to be included in error messages instead of the payload itself
+/// when `redact_error_payloads` is enabled.
+pub fn redact_error_payload(payload: &[u8]) -> String {
+    use std::fmt::Write;
+    let hash = Sha256::hash(payload)
+        .iter()
+        .fold(String::new(), |mut hash, byte| {
+            write!(hash, "{:02x}", byte).expect("writing to a String cannot fail");
+            hash
+        });
+    format!("(redacted {} bytes with sha256 {})", payload.len(), hash)
 }
 
 /// Keeps the message instruction limit and the maximum slice instruction limit.
@@ -1032,6 +1056,15 @@ pub struct SystemApiImpl {
     #[allow(unused)]
     canister_backtrace: FlagStatus,
 
+    /// Payloads taken from canister memory, such as the message of
+    /// `ic0.trap`, are replaced by their length and hash in errors.
+    redact_error_payloads: FlagStatus,
+
+    /// The message passed to `ic0.trap` if it was redacted from the error and
+    /// capturing it was enabled. See [`Self::enable_raw_trap_message_capture`].
+    raw_trap_message: RefCell<Option<Vec<u8>>>,
+    capture_raw_trap_message: bool,
+
     /// The maximum sum of `<name>` lengths in exported functions called `canister_update <name>`,
     /// `canister_query <name>`, or `canister_composite_query <name>`.
     max_sum_exported_function_name_lengths: usize,
@@ -1062,6 +1095,10 @@ pub struct SystemApiImpl {
 
     /// How many times each tracked System API call was invoked.
     call_counters: SystemApiCallCounters,
+
+    /// If enabled, a record of every System API call with its arguments and
+    /// result, in invocation order. Used to compare executions in tests.
+    syscall_trace: RefCell<Option<Vec<String>>>,
 }
 
 impl SystemApiImpl {
@@ -1075,6 +1112,7 @@ impl SystemApiImpl {
         subnet_available_memory: SubnetAvailableMemory,
         wasm_native_stable_memory: FlagStatus,
         canister_backtrace: FlagStatus,
+        redact_error_payloads: FlagStatus,
         max_sum_exported_function_name_lengths: usize,
         stable_memory: Memory,
         wasm_memory_size: NumWasmPages,
@@ -1115,6 +1153,9 @@ impl SystemApiImpl {
             execution_parameters,
             wasm_native_stable_memory,
             canister_backtrace,
+            redact_error_payloads,
+            raw_trap_message: RefCell::new(None),
+            capture_raw_trap_message: false,
             max_sum_exported_function_name_lengths,
             stable_memory,
             sandbox_safe_system_state,
@@ -1123,6 +1164,7 @@ impl SystemApiImpl {
             current_slice_instruction_limit: i64::try_from(slice_limit).unwrap_or(i64::MAX),
             instructions_executed_before_current_slice: 0,
             call_counters: SystemApiCallCounters::default(),
+            syscall_trace: RefCell::new(None),
         }
     }
 
@@ -1567,6 +1609,30 @@ impl SystemApiImpl {
         self.call_counters.clone()
     }
 
+    /// Starts recording every subsequent System API call together with its
+    /// arguments and result. Meant for tests and debugging only.
+    pub fn enable_syscall_trace(&mut self) {
+        self.syscall_trace.get_mut().get_or_insert_with(Vec::new);
+    }
+
+    /// Returns the recorded System API calls if recording is enabled.
+    pub fn syscall_trace(&self) -> Option<Vec<String>> {
+        self.syscall_trace.borrow().clone()
+    }
+
+    /// Starts keeping the message passed to `ic0.trap` whenever it is redacted
+    /// from the `CalledTrap` error, so it can be inspected via
+    /// `raw_trap_message()`. Meant for tests only.
+    pub fn enable_raw_trap_message_capture(&mut self) {
+        self.capture_raw_trap_message = true;
+    }
+
+    /// Returns the message passed to `ic0.trap` if it was redacted from the
+    /// `CalledTrap` error and capturing it was enabled.
+    pub fn raw_trap_message(&self) -> Option<Vec<u8>> {
+        self.raw_trap_message.borrow().clone()
+    }
+
     /// Appends the specified bytes on the heap as a string to the canister's logs.
     pub fn save_log_message(&mut self, src: usize, size: usize, heap: &[u8]) {
         self.sandbox_safe_system_state.append_canister_log(
@@ -3231,7 +3297,15 @@ impl SystemApi for SystemApiImpl {
         let size = size.min(MAX_ERROR_MESSAGE_SIZE);
         let result = {
             let message = valid_subslice("trap", src, size, heap)
-                .map(|bytes| String::from_utf8_lossy(bytes).to_string())
+                .map(|bytes| match self.redact_error_payloads {
+                    FlagStatus::Enabled => {
+                        if self.capture_raw_trap_message {
+                            *self.raw_trap_message.borrow_mut() = Some(bytes.to_vec());
+                        }
+                        redact_error_payload(bytes)
+                    }
+                    FlagStatus::Disabled => String::from_utf8_lossy(bytes).to_string(),
+                })
                 .unwrap_or_else(|_| "(trap message out of memory bounds)".to_string());
             CalledTrap {
                 message,
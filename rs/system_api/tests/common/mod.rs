@@ -181,6 +181,22 @@ pub fn get_system_api(
     api_type: ApiType,
     system_state: &SystemState,
     cycles_account_manager: CyclesAccountManager,
+) -> SystemApiImpl {
+    get_system_api_with_config(
+        api_type,
+        system_state,
+        cycles_account_manager,
+        EmbeddersConfig::default(),
+    )
+}
+
+// Not used in all test crates
+#[allow(dead_code)]
+pub fn get_system_api_with_config(
+    api_type: ApiType,
+    system_state: &SystemState,
+    cycles_account_manager: CyclesAccountManager,
+    embedders_config: EmbeddersConfig,
 ) -> SystemApiImpl {
     let execution_mode = api_type.execution_mode();
     let sandbox_safe_system_state = SandboxSafeSystemState::new_for_testing(
@@ -205,11 +221,10 @@ pub fn get_system_api(
             SUBNET_MEMORY_CAPACITY,
             SUBNET_MEMORY_CAPACITY,
         ),
-        EmbeddersConfig::default()
-            .feature_flags
-            .wasm_native_stable_memory,
-        EmbeddersConfig::default().feature_flags.canister_backtrace,
-        EmbeddersConfig::default().max_sum_exported_function_name_lengths,
+        embedders_config.feature_flags.wasm_native_stable_memory,
+        embedders_config.feature_flags.canister_backtrace,
+        embedders_config.feature_flags.redact_error_payloads,
+        embedders_config.max_sum_exported_function_name_lengths,
         Memory::new_for_testing(),
         NumWasmPages::from(0),
         Rc::new(DefaultOutOfInstructionsHandler::default()),
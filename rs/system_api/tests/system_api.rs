@@ -18,8 +18,8 @@ use ic_replicated_state::{
     CallOrigin, Memory, NetworkTopology, SystemState,
 };
 use ic_system_api::{
-    sandbox_safe_system_state::SandboxSafeSystemState, ApiType, DefaultOutOfInstructionsHandler,
-    SystemApiImpl,
+    redact_error_payload, sandbox_safe_system_state::SandboxSafeSystemState, ApiType,
+    DefaultOutOfInstructionsHandler, SystemApiImpl,
 };
 use ic_test_utilities::cycles_account_manager::CyclesAccountManagerBuilder;
 use ic_test_utilities_state::SystemStateBuilder;
@@ -1345,6 +1345,9 @@ fn growing_wasm_memory_updates_subnet_available_memory() {
             .feature_flags
             .wasm_native_stable_memory,
         EmbeddersConfig::default().feature_flags.canister_backtrace,
+        EmbeddersConfig::default()
+            .feature_flags
+            .redact_error_payloads,
         EmbeddersConfig::default().max_sum_exported_function_name_lengths,
         Memory::new_for_testing(),
         NumWasmPages::from(0),
@@ -1424,6 +1427,9 @@ fn helper_test_on_low_wasm_memory(
             .feature_flags
             .wasm_native_stable_memory,
         EmbeddersConfig::default().feature_flags.canister_backtrace,
+        EmbeddersConfig::default()
+            .feature_flags
+            .redact_error_payloads,
         EmbeddersConfig::default().max_sum_exported_function_name_lengths,
         Memory::new_for_testing(),
         NumWasmPages::from(0),
@@ -1659,6 +1665,9 @@ fn push_output_request_respects_memory_limits() {
             .feature_flags
             .wasm_native_stable_memory,
         EmbeddersConfig::default().feature_flags.canister_backtrace,
+        EmbeddersConfig::default()
+            .feature_flags
+            .redact_error_payloads,
         EmbeddersConfig::default().max_sum_exported_function_name_lengths,
         Memory::new_for_testing(),
         NumWasmPages::from(0),
@@ -1773,6 +1782,9 @@ fn push_output_request_oversized_request_memory_limits() {
             .feature_flags
             .wasm_native_stable_memory,
         EmbeddersConfig::default().feature_flags.canister_backtrace,
+        EmbeddersConfig::default()
+            .feature_flags
+            .redact_error_payloads,
         EmbeddersConfig::default().max_sum_exported_function_name_lengths,
         Memory::new_for_testing(),
         NumWasmPages::from(0),
@@ -2055,3 +2067,69 @@ fn test_save_log_message_keeps_total_log_size_limited() {
     assert_eq!(log.records().len(), initial_records_number + 1);
     assert_le!(log.used_space(), MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE);
 }
+
+#[test]
+fn test_redact_error_payload_renders_length_and_hash() {
+    assert_eq!(
+        redact_error_payload(b"Hello"),
+        "(redacted 5 bytes with sha256 185f8db32271fe25f561a6fc938b2e264306ec304eda518007d1764826381969)"
+    );
+    assert_eq!(
+        redact_error_payload(&[]),
+        "(redacted 0 bytes with sha256 e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855)"
+    );
+}
+
+#[test]
+fn test_trap_message_is_redacted_only_if_enabled() {
+    let heap = b"...Hello...";
+    for redact_error_payloads in [FlagStatus::Disabled, FlagStatus::Enabled] {
+        let mut config = EmbeddersConfig::default();
+        config.feature_flags.redact_error_payloads = redact_error_payloads;
+        let api = get_system_api_with_config(
+            ApiTypeBuilder::build_update_api(),
+            &SystemStateBuilder::default().build(),
+            CyclesAccountManagerBuilder::new().build(),
+            config,
+        );
+
+        let err = api.ic0_trap(3, 5, heap).unwrap_err();
+
+        let HypervisorError::CalledTrap { message, .. } = err else {
+            panic!("Expected CalledTrap error, but got {}.", err);
+        };
+        match redact_error_payloads {
+            FlagStatus::Disabled => {
+                assert_eq!(message, "Hello");
+                assert_eq!(api.raw_trap_message(), None);
+            }
+            FlagStatus::Enabled => {
+                assert_eq!(message, redact_error_payload(b"Hello"));
+                assert!(!message.contains("Hello"), "{}", message);
+                assert_eq!(api.raw_trap_message(), Some(b"Hello".to_vec()));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_redacted_trap_message_is_truncated_like_unredacted_one() {
+    let heap = vec![b'x'; 32 * 1024];
+    let mut config = EmbeddersConfig::default();
+    config.feature_flags.redact_error_payloads = FlagStatus::Enabled;
+    let api = get_system_api_with_config(
+        ApiTypeBuilder::build_update_api(),
+        &SystemStateBuilder::default().build(),
+        CyclesAccountManagerBuilder::new().build(),
+        config,
+    );
+
+    let err = api.ic0_trap(0, heap.len(), &heap).unwrap_err();
+
+    let HypervisorError::CalledTrap { message, .. } = err else {
+        panic!("Expected CalledTrap error, but got {}.", err);
+    };
+    let truncated = &heap[..16 * 1024];
+    assert_eq!(message, redact_error_payload(truncated));
+    assert_eq!(api.raw_trap_message(), Some(truncated.to_vec()));
+}
@@ -0,0 +1,139 @@
+//! Execution of a response callback followed by its `call_on_cleanup` closure.
+//!
+//! [WasmtimeInstanceBuilder::run_callback_with_cleanup] mirrors how the
+//! execution environment handles responses: a share of the message instruction
+//! limit is reserved for the cleanup closure, the callback runs with the rest
+//! and, if it traps, the cleanup closure runs on the clean state in cleanup
+//! mode with the instructions left by the callback plus the reservation.
+//! Instruction usage of the callback and the cleanup closure is reported
+//! separately in [CleanupRun].
+
+use ic_interfaces::execution_environment::HypervisorError;
+use ic_system_api::ApiType;
+use ic_test_utilities_types::ids::user_test_id;
+use ic_types::{
+    methods::{FuncRef, WasmClosure},
+    time::UNIX_EPOCH,
+    NumInstructions,
+};
+
+use crate::WasmtimeInstanceBuilder;
+
+/// The percentage of the message instruction limit reserved for the cleanup
+/// closure. Must match the reservation in the execution environment.
+pub const RESERVED_CLEANUP_INSTRUCTIONS_IN_PERCENT: u64 = 5;
+
+/// The reasons for which a cleanup closure may fail.
+#[derive(Clone, PartialEq, Debug)]
+pub enum CleanupViolation {
+    /// The cleanup closure used up its instruction budget.
+    InstructionBudgetExhausted { budget: NumInstructions },
+    /// The cleanup closure called a System API that is not available in
+    /// cleanup mode, e.g. `ic0_call_perform`.
+    ForbiddenSystemApiCall { api: String },
+    /// The cleanup closure failed for any other reason.
+    Trapped(HypervisorError),
+}
+
+impl CleanupViolation {
+    fn from_error(err: HypervisorError, budget: NumInstructions) -> Self {
+        match err {
+            HypervisorError::InstructionLimitExceeded(_) => {
+                CleanupViolation::InstructionBudgetExhausted { budget }
+            }
+            HypervisorError::UserContractViolation { ref error, .. } => {
+                match forbidden_api_name(error) {
+                    Some(api) => CleanupViolation::ForbiddenSystemApiCall { api },
+                    None => CleanupViolation::Trapped(err),
+                }
+            }
+            err => CleanupViolation::Trapped(err),
+        }
+    }
+}
+
+/// Extracts the name of the System API from errors of the form
+/// `"ic0_call_perform" cannot be executed in cleanup mode`.
+fn forbidden_api_name(error: &str) -> Option<String> {
+    let api = error
+        .strip_suffix(" cannot be executed in cleanup mode")?
+        .strip_prefix('"')?
+        .strip_suffix('"')?;
+    Some(api.to_string())
+}
+
+/// The execution of a cleanup closure.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CleanupExecution {
+    /// Instructions left by the callback plus the reservation.
+    pub budget: NumInstructions,
+    pub instructions_used: NumInstructions,
+    pub result: Result<(), CleanupViolation>,
+}
+
+/// The execution of a response callback and, if it trapped, of its cleanup
+/// closure.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CleanupRun {
+    pub callback_result: Result<(), HypervisorError>,
+    pub callback_instructions_used: NumInstructions,
+    /// `None` if the callback succeeded, in which case the cleanup closure is
+    /// not executed.
+    pub cleanup: Option<CleanupExecution>,
+}
+
+impl WasmtimeInstanceBuilder {
+    /// Runs `callback` with the configured API type and instruction limit
+    /// minus the cleanup reservation, and runs `cleanup` if the callback fails.
+    pub fn run_callback_with_cleanup(self, callback: FuncRef, cleanup: WasmClosure) -> CleanupRun {
+        let message_limit = self.num_instructions;
+        let reserved = (message_limit * RESERVED_CLEANUP_INSTRUCTIONS_IN_PERCENT) / 100;
+        let callback_limit = message_limit - reserved;
+        let caller = self
+            .api_type
+            .caller()
+            .unwrap_or_else(|| user_test_id(24).get());
+        let execution_mode = self.api_type.execution_mode();
+
+        let mut instance = self.clone().with_num_instructions(callback_limit).build();
+        let callback_result = instance.run(callback).map(|_| ());
+        let callback_left = instructions_left(instance.instruction_counter(), callback_limit);
+        let callback_instructions_used = callback_limit - callback_left;
+
+        let cleanup = callback_result.as_ref().err().map(|_| {
+            let budget = callback_left + reserved;
+            // The cleanup closure runs on the state from before the callback,
+            // i.e. on a freshly built instance.
+            let mut instance = self
+                .with_api_type(ApiType::Cleanup {
+                    caller,
+                    time: UNIX_EPOCH,
+                    execution_mode,
+                    call_context_instructions_executed: callback_instructions_used,
+                })
+                .with_num_instructions(budget)
+                .build();
+            let result = instance
+                .run(FuncRef::UpdateClosure(cleanup))
+                .map(|_| ())
+                .map_err(|err| CleanupViolation::from_error(err, budget));
+            CleanupExecution {
+                budget,
+                instructions_used: budget
+                    - instructions_left(instance.instruction_counter(), budget),
+                result,
+            }
+        });
+
+        CleanupRun {
+            callback_result,
+            callback_instructions_used,
+            cleanup,
+        }
+    }
+}
+
+/// The instruction counter becomes negative when the limit is exceeded.
+fn instructions_left(instruction_counter: i64, limit: NumInstructions) -> NumInstructions {
+    NumInstructions::new((instruction_counter.max(0) as u64).min(limit.get()))
+}
@@ -0,0 +1,204 @@
+//! A strict determinism audit for the embedder.
+//!
+//! [run_twice_and_compare] executes the same message on two freshly built
+//! instances within one process and records everything that must not differ
+//! between the two executions: the result, the number of instructions left,
+//! the dirty pages and their contents, the exported globals and the sequence
+//! of System API calls. The returned [DeterminismReport] points at the first
+//! divergence, which is usually much easier to debug than a state hash
+//! mismatch between replicas.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+
+use ic_embedders::wasmtime_embedder::{CanisterMemoryType, WasmtimeInstance};
+use ic_interfaces::execution_environment::HypervisorError;
+use ic_replicated_state::{Global, PageIndex};
+use ic_sys::PAGE_SIZE;
+use ic_types::methods::FuncRef;
+
+use crate::WasmtimeInstanceBuilder;
+
+/// Everything observable about a single execution of a message.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ExecutionRecord {
+    pub result: Result<(), HypervisorError>,
+    pub instructions_left: i64,
+    pub wasm_dirty_pages: Vec<PageIndex>,
+    pub stable_memory_dirty_pages: Vec<PageIndex>,
+    /// Hash of the contents of `wasm_dirty_pages` after execution.
+    pub wasm_memory_hash: u64,
+    /// Hash of the contents of `stable_memory_dirty_pages` after execution.
+    pub stable_memory_hash: u64,
+    pub exported_globals: Vec<Global>,
+    /// The System API calls with their arguments and results, in order.
+    pub syscall_trace: Vec<String>,
+}
+
+/// The records of two executions of the same message.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DeterminismReport {
+    pub first: ExecutionRecord,
+    pub second: ExecutionRecord,
+}
+
+impl DeterminismReport {
+    pub fn is_deterministic(&self) -> bool {
+        self.first_divergence().is_none()
+    }
+
+    /// Describes the first difference between the two executions, if any.
+    ///
+    /// System API calls are compared first because a diverging call is the
+    /// most likely cause of any other difference.
+    pub fn first_divergence(&self) -> Option<String> {
+        let (a, b) = (&self.first, &self.second);
+        let trace_len = a.syscall_trace.len().max(b.syscall_trace.len());
+        for i in 0..trace_len {
+            let (call_a, call_b) = (a.syscall_trace.get(i), b.syscall_trace.get(i));
+            if call_a != call_b {
+                return Some(format!(
+                    "System API call #{i} differs: {call_a:?} vs {call_b:?} \
+                     (previous call: {:?})",
+                    i.checked_sub(1).and_then(|j| a.syscall_trace.get(j)),
+                ));
+            }
+        }
+        if a.result != b.result {
+            return Some(format!("Result differs: {:?} vs {:?}", a.result, b.result));
+        }
+        if a.instructions_left != b.instructions_left {
+            return Some(format!(
+                "Instructions left differ: {} vs {}",
+                a.instructions_left, b.instructions_left
+            ));
+        }
+        if a.wasm_dirty_pages != b.wasm_dirty_pages {
+            return Some(format!(
+                "Wasm dirty pages differ: {:?} vs {:?}",
+                a.wasm_dirty_pages, b.wasm_dirty_pages
+            ));
+        }
+        if a.stable_memory_dirty_pages != b.stable_memory_dirty_pages {
+            return Some(format!(
+                "Stable memory dirty pages differ: {:?} vs {:?}",
+                a.stable_memory_dirty_pages, b.stable_memory_dirty_pages
+            ));
+        }
+        if a.wasm_memory_hash != b.wasm_memory_hash {
+            return Some("Contents of the dirty Wasm memory pages differ".to_string());
+        }
+        if a.stable_memory_hash != b.stable_memory_hash {
+            return Some("Contents of the dirty stable memory pages differ".to_string());
+        }
+        if a.exported_globals != b.exported_globals {
+            return Some(format!(
+                "Exported globals differ: {:?} vs {:?}",
+                a.exported_globals, b.exported_globals
+            ));
+        }
+        None
+    }
+
+    /// Panics with the first divergence if the executions differ.
+    pub fn assert_deterministic(&self) {
+        if let Some(divergence) = self.first_divergence() {
+            panic!("Execution is not deterministic: {divergence}");
+        }
+    }
+}
+
+/// Builds two instances with `build`, executes `func_ref` on both and compares
+/// the executions.
+///
+/// `build` is expected to return identical instances, so any divergence in the
+/// report indicates nondeterminism in the embedder or the System API.
+pub fn run_twice_and_compare(
+    mut build: impl FnMut() -> WasmtimeInstance,
+    func_ref: FuncRef,
+) -> DeterminismReport {
+    DeterminismReport {
+        first: execute_and_record(build(), func_ref.clone()),
+        second: execute_and_record(build(), func_ref),
+    }
+}
+
+impl WasmtimeInstanceBuilder {
+    /// Runs the determinism audit on two instances built from this builder.
+    pub fn run_twice_and_compare(self, func_ref: FuncRef) -> DeterminismReport {
+        run_twice_and_compare(|| self.clone().build(), func_ref)
+    }
+}
+
+fn execute_and_record(mut instance: WasmtimeInstance, func_ref: FuncRef) -> ExecutionRecord {
+    instance
+        .store_data_mut()
+        .system_api_mut()
+        .expect("The instance has no System API")
+        .enable_syscall_trace();
+    let result = instance.run(func_ref);
+    let instructions_left = instance.instruction_counter();
+    let syscall_trace = instance
+        .store_data()
+        .system_api()
+        .expect("The instance has no System API")
+        .syscall_trace()
+        .unwrap_or_default();
+    let (result, run_result) = match result {
+        Ok(run_result) => (Ok(()), Some(run_result)),
+        Err(err) => (Err(err), None),
+    };
+    let (wasm_dirty_pages, stable_memory_dirty_pages, exported_globals) = match run_result {
+        Some(r) => (
+            r.wasm_dirty_pages,
+            r.stable_memory_dirty_pages,
+            r.exported_globals,
+        ),
+        None => (vec![], vec![], vec![]),
+    };
+    let wasm_memory_hash =
+        hash_dirty_pages(&mut instance, CanisterMemoryType::Heap, &wasm_dirty_pages);
+    let stable_memory_hash = hash_dirty_pages(
+        &mut instance,
+        CanisterMemoryType::Stable,
+        &stable_memory_dirty_pages,
+    );
+    ExecutionRecord {
+        result,
+        instructions_left,
+        wasm_dirty_pages,
+        stable_memory_dirty_pages,
+        wasm_memory_hash,
+        stable_memory_hash,
+        exported_globals,
+        syscall_trace,
+    }
+}
+
+/// Hashes the contents of the given pages. Only dirty pages are read because
+/// they are guaranteed to be accessible after execution.
+fn hash_dirty_pages(
+    instance: &mut WasmtimeInstance,
+    memory_type: CanisterMemoryType,
+    pages: &[PageIndex],
+) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    if pages.is_empty() {
+        return hasher.finish();
+    }
+    let memory: &[u8] = unsafe {
+        let addr = instance.heap_addr(memory_type);
+        if addr.is_null() {
+            return hasher.finish();
+        }
+        let size_in_bytes = instance.heap_size(memory_type).get()
+            * ic_replicated_state::canister_state::WASM_PAGE_SIZE_IN_BYTES;
+        std::slice::from_raw_parts(addr, size_in_bytes)
+    };
+    for page in pages {
+        let start = page.get() as usize * PAGE_SIZE;
+        hasher.write_u64(page.get());
+        hasher.write(&memory[start..start + PAGE_SIZE]);
+    }
+    hasher.finish()
+}
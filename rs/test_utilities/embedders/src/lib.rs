@@ -1,4 +1,4 @@
-use std::{convert::TryFrom, rc::Rc};
+use std::{convert::TryFrom, path::Path, rc::Rc};
 
 use ic_base_types::NumBytes;
 use ic_config::execution_environment::Config as HypervisorConfig;
@@ -10,8 +10,7 @@ use ic_interfaces::execution_environment::{
 };
 use ic_logger::replica_logger::no_op_logger;
 use ic_registry_subnet_type::SubnetType;
-use ic_replicated_state::NumWasmPages;
-use ic_replicated_state::{Global, Memory, NetworkTopology, PageMap};
+use ic_replicated_state::{Global, Memory, NetworkTopology};
 use ic_system_api::{
     sandbox_safe_system_state::SandboxSafeSystemState, ExecutionParameters, InstructionLimits,
     ModificationTracking, SystemApiImpl,
@@ -25,18 +24,27 @@ use ic_types::{
 };
 use ic_wasm_types::BinaryEncodedWasm;
 
+pub mod cleanup;
+pub mod determinism;
+pub use cleanup::{CleanupExecution, CleanupRun, CleanupViolation};
+pub use determinism::{run_twice_and_compare, DeterminismReport, ExecutionRecord};
+
 pub const DEFAULT_NUM_INSTRUCTIONS: NumInstructions = NumInstructions::new(5_000_000_000);
 
+#[derive(Clone)]
 pub struct WasmtimeInstanceBuilder {
     wasm: Vec<u8>,
     wat: String,
     globals: Option<Vec<Global>>,
+    wasm_memory: Memory,
+    stable_memory: Memory,
     api_type: ic_system_api::ApiType,
     num_instructions: NumInstructions,
     subnet_type: SubnetType,
     network_topology: NetworkTopology,
     config: ic_config::embedders::Config,
     canister_memory_limit: NumBytes,
+    page_access_trace_len: Option<usize>,
 }
 
 impl Default for WasmtimeInstanceBuilder {
@@ -45,12 +53,15 @@ impl Default for WasmtimeInstanceBuilder {
             wasm: vec![],
             wat: "".to_string(),
             globals: None,
+            wasm_memory: Memory::new_for_testing(),
+            stable_memory: Memory::new_for_testing(),
             api_type: ic_system_api::ApiType::init(UNIX_EPOCH, vec![], user_test_id(24).get()),
             num_instructions: DEFAULT_NUM_INSTRUCTIONS,
             subnet_type: SubnetType::Application,
             network_topology: NetworkTopology::default(),
             config: ic_config::embedders::Config::default(),
             canister_memory_limit: NumBytes::from(4 << 30), // Set to 4 GiB by default
+            page_access_trace_len: None,
         }
     }
 }
@@ -82,6 +93,24 @@ impl WasmtimeInstanceBuilder {
         }
     }
 
+    /// Starts the instance with the given Wasm memory instead of an empty one,
+    /// e.g., to resume from a previously persisted state.
+    pub fn with_wasm_memory(self, wasm_memory: Memory) -> Self {
+        Self {
+            wasm_memory,
+            ..self
+        }
+    }
+
+    /// Starts the instance with the given stable memory instead of an empty
+    /// one, see `with_wasm_memory()`.
+    pub fn with_stable_memory(self, stable_memory: Memory) -> Self {
+        Self {
+            stable_memory,
+            ..self
+        }
+    }
+
     pub fn with_api_type(self, api_type: ic_system_api::ApiType) -> Self {
         Self { api_type, ..self }
     }
@@ -107,6 +136,15 @@ impl WasmtimeInstanceBuilder {
         }
     }
 
+    /// Records up to `max_len` page faults of the built instance, retrievable
+    /// via `WasmtimeInstance::take_page_access_trace()`.
+    pub fn with_page_access_trace(self, max_len: usize) -> Self {
+        Self {
+            page_access_trace_len: Some(max_len),
+            ..self
+        }
+    }
+
     pub fn try_build(self) -> Result<WasmtimeInstance, (HypervisorError, SystemApiImpl)> {
         let log = no_op_logger();
 
@@ -173,33 +211,32 @@ impl WasmtimeInstanceBuilder {
             ),
             embedder.config().feature_flags.wasm_native_stable_memory,
             embedder.config().feature_flags.canister_backtrace,
+            embedder.config().feature_flags.redact_error_payloads,
             embedder.config().max_sum_exported_function_name_lengths,
-            Memory::new_for_testing(),
-            NumWasmPages::from(0),
+            self.stable_memory.clone(),
+            self.wasm_memory.size,
             Rc::new(ic_system_api::DefaultOutOfInstructionsHandler::new(
                 self.num_instructions,
             )),
             log,
         );
         let instruction_limit = api.slice_instruction_limit();
+        let page_access_trace_len = self.page_access_trace_len;
         let instance = embedder
             .new_instance(
                 canister_test_id(1),
                 &compiled,
                 self.globals.as_deref(),
-                &Memory::new(
-                    PageMap::new_for_testing(),
-                    ic_replicated_state::NumWasmPages::from(0),
-                ),
-                &Memory::new(
-                    PageMap::new_for_testing(),
-                    ic_replicated_state::NumWasmPages::from(0),
-                ),
+                &self.wasm_memory,
+                &self.stable_memory,
                 ModificationTracking::Track,
                 Some(api),
             )
             .map(|mut result| {
                 result.set_instruction_counter(i64::try_from(instruction_limit.get()).unwrap());
+                if let Some(max_len) = page_access_trace_len {
+                    result.enable_page_access_trace(max_len);
+                }
                 result
             });
         instance.map_err(|(h, s)| (h, s.unwrap()))
@@ -212,3 +249,19 @@ impl WasmtimeInstanceBuilder {
             .expect("Failed to create instance")
     }
 }
+
+/// Takes the page access trace recorded by `instance` (see
+/// `WasmtimeInstanceBuilder::with_page_access_trace()`) and writes it to
+/// `path`, for offline analysis of the prefetching heuristics.
+pub fn write_page_access_trace(
+    instance: &mut WasmtimeInstance,
+    path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let trace = instance.take_page_access_trace().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "page access tracing was not enabled",
+        )
+    })?;
+    trace.write_to(std::io::BufWriter::new(std::fs::File::create(path)?))
+}
@@ -314,6 +314,11 @@ GRAFANA_RUNTIME_DEPS = UNIVERSAL_VM_RUNTIME_DEPS + [
     "//rs/tests:grafana_dashboards",
 ]
 
+VECTOR_RUNTIME_DEPS = UNIVERSAL_VM_RUNTIME_DEPS + [
+    "//rs/tests:python3.tar",
+    "//rs/tests:vector_aggregator.py",
+]
+
 BOUNDARY_NODE_GUESTOS_RUNTIME_DEPS = [
     "//ic-os/boundary-guestos/envs/dev:disk-img.tar.zst.cas-url",
     "//ic-os/boundary-guestos/envs/dev:disk-img.tar.zst.sha256",
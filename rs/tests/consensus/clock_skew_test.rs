@@ -0,0 +1,110 @@
+/* tag::catalog[]
+Title:: Clock skew test
+
+Goal:: Ensure that a subnet keeps finalizing blocks if the clock of one of its
+nodes is off, and that the driver can skew and restore node clocks.
+
+Runbook::
+. Set up a subnet with 4 nodes.
+. Measure the clock offset of one node.
+. Skew the clock of that node by +30s and measure its offset.
+. Wait until all nodes certify a number of further heights.
+. Restore the clock of the node and measure its offset.
+
+Success::
+. The measured offset is close to 0 before skewing and after restoring the
+  clock, and close to 30s in between.
+. All nodes make progress while the clock of one node is skewed.
+
+end::catalog[] */
+
+use anyhow::Result;
+use chrono::Duration;
+use ic_consensus_system_test_utils::node::{
+    await_node_certified_height, get_node_certified_height,
+};
+use ic_registry_subnet_type::SubnetType;
+use ic_system_test_driver::driver::clock_skew::ClockSkew;
+use ic_system_test_driver::driver::group::SystemTestGroup;
+use ic_system_test_driver::driver::ic::{InternetComputer, Subnet};
+use ic_system_test_driver::driver::test_env::TestEnv;
+use ic_system_test_driver::driver::test_env_api::{
+    HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer, IcNodeSnapshot,
+};
+use ic_system_test_driver::systest;
+use ic_types::Height;
+use slog::info;
+
+const SUBNET_SIZE: usize = 4;
+const CLOCK_OFFSET_SECS: i64 = 30;
+/// Maximum deviation of a measured clock offset from the expected one.
+const MAX_MEASUREMENT_ERROR_MILLIS: i64 = 2_000;
+/// Number of heights all nodes have to certify while a clock is skewed.
+const PROGRESS_HEIGHTS: u64 = 20;
+
+fn setup(env: TestEnv) {
+    InternetComputer::new()
+        .add_subnet(Subnet::new(SubnetType::System).add_nodes(SUBNET_SIZE))
+        .setup_and_start(&env)
+        .expect("failed to setup IC under test");
+    env.topology_snapshot().subnets().for_each(|subnet| {
+        subnet
+            .nodes()
+            .for_each(|node| node.await_status_is_healthy().unwrap())
+    });
+}
+
+fn assert_clock_offset(node: &IcNodeSnapshot, expected: Duration) {
+    let measured = node
+        .clock_offset()
+        .expect("failed to measure the clock offset");
+    assert!(
+        (measured - expected).num_milliseconds().abs() <= MAX_MEASUREMENT_ERROR_MILLIS,
+        "measured clock offset of {}ms, expected {}ms",
+        measured.num_milliseconds(),
+        expected.num_milliseconds()
+    );
+}
+
+fn test(env: TestEnv) {
+    let log = env.logger();
+    let nodes: Vec<_> = env.topology_snapshot().root_subnet().nodes().collect();
+    assert_eq!(nodes.len(), SUBNET_SIZE);
+    let skewed_node = &nodes[0];
+
+    info!(
+        log,
+        "Measuring the clock offset of node {}", skewed_node.node_id
+    );
+    assert_clock_offset(skewed_node, Duration::zero());
+
+    let offset = Duration::seconds(CLOCK_OFFSET_SECS);
+    skewed_node
+        .set_clock_offset(offset)
+        .expect("failed to set the clock offset");
+    assert_clock_offset(skewed_node, offset);
+
+    info!(log, "Checking that all nodes make progress");
+    for node in nodes.iter() {
+        let height = get_node_certified_height(node, log.clone());
+        await_node_certified_height(
+            node,
+            Height::from(height.get() + PROGRESS_HEIGHTS),
+            log.clone(),
+        );
+    }
+
+    skewed_node
+        .clear_clock_offset()
+        .expect("failed to clear the clock offset");
+    assert_clock_offset(skewed_node, Duration::zero());
+}
+
+fn main() -> Result<()> {
+    SystemTestGroup::new()
+        .with_setup(setup)
+        .add_test(systest!(test))
+        .execute_from_args()?;
+
+    Ok(())
+}
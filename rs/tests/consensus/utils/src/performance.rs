@@ -120,7 +120,7 @@ pub fn test_with_rt_handle(
 
     info!(log, "Reporting workload execution results ...");
     if report {
-        env.emit_report(format!("{}", test_metrics));
+        env.emit_benchmark_report(format!("{}", test_metrics));
     } else {
         info!(log, "{}", test_metrics);
     }
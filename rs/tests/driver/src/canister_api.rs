@@ -1057,6 +1057,15 @@ impl SnsRequestProvider {
             .icrc1_metadata_request(mode)
     }
 
+    pub fn icrc1_balance_of(
+        &self,
+        account: Account,
+        mode: CallMode,
+    ) -> impl Request<Icrc1BalanceOfResponse> + std::fmt::Debug + Clone + Sync + Send {
+        self.sns_ledger_request_provider
+            .icrc1_balance_of_request(account, mode)
+    }
+
     pub fn metadata(
         &self,
         mode: CallMode,
@@ -217,6 +217,24 @@ impl<T: TaskIdT> ActionGraph<T> {
                                 Self::effect_when_finished(graph, *idx, myself, Decrease);
                             }
                         }
+                        EvalOrder::Bounded(max_parallel) => {
+                            // The child `i` starts when the child `i - max_parallel`
+                            // in the same lane finishes.
+                            let lanes = max_parallel.max(1);
+                            for (i, idx) in child_idcs.iter().enumerate() {
+                                if i < lanes {
+                                    Self::start_when_started(graph, myself, *idx);
+                                }
+                                match child_idcs.get(i + lanes) {
+                                    Some(next_idx) => {
+                                        Self::effect_when_finished(graph, *idx, *next_idx, Start)
+                                    }
+                                    None => {
+                                        Self::effect_when_finished(graph, *idx, myself, Decrease)
+                                    }
+                                }
+                            }
+                        }
                     }
                 }
             }
@@ -400,3 +418,57 @@ impl<T: TaskIdT> NodeHandle<T> {
         self.0.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(name: &'static str) -> Plan<&'static str> {
+        Plan::Leaf { task: name }
+    }
+
+    fn running(graph: &ActionGraph<&'static str>) -> Vec<&'static str> {
+        graph
+            .task_iter()
+            .filter_map(|(node, task_id)| match node {
+                Node::Running { active, .. } if active > 0 => task_id,
+                _ => None,
+            })
+            .filter(|task_id| *task_id != "root")
+            .collect()
+    }
+
+    fn node_idx(graph: &ActionGraph<&'static str>, name: &str) -> usize {
+        graph
+            .task_iter()
+            .position(|(_, task_id)| task_id == Some(name))
+            .unwrap()
+    }
+
+    #[test]
+    fn bounded_order_runs_at_most_max_parallel_children() {
+        let mut graph = ActionGraph::from_plan(Plan::Supervised {
+            supervisor: "root",
+            ordering: EvalOrder::Bounded(2),
+            children: vec![leaf("a"), leaf("b"), leaf("c"), leaf("d"), leaf("e")],
+        });
+        graph.start();
+        assert_eq!(vec!["a", "b"], running(&graph));
+
+        graph.stop(node_idx(&graph, "b"), "ok".to_string());
+        assert_eq!(vec!["a", "d"], running(&graph));
+        // A failing child does not hold up its lane.
+        graph.fail(node_idx(&graph, "a"), "boom".to_string());
+        assert_eq!(vec!["c", "d"], running(&graph));
+        graph.stop(node_idx(&graph, "c"), "ok".to_string());
+        assert_eq!(vec!["d", "e"], running(&graph));
+        graph.stop(node_idx(&graph, "d"), "ok".to_string());
+        graph.stop(node_idx(&graph, "e"), "ok".to_string());
+        assert!(running(&graph).is_empty());
+        // The scope stops once the last child of every lane has finished.
+        assert!(matches!(
+            graph.task_iter().next().unwrap().0,
+            Node::Running { active: 0, .. }
+        ));
+    }
+}
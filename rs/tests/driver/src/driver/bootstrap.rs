@@ -1,8 +1,8 @@
 use crate::driver::{
-    config::NODES_INFO,
+    config::{NODES_INFO, NODES_INFO_SCHEMA_VERSION},
     driver_setup::SSH_AUTHORIZED_PUB_KEYS_DIR,
     farm::{AttachImageSpec, Farm, FarmResult, FileId},
-    ic::{InternetComputer, Node},
+    ic::{InternetComputer, Node, Subnet},
     nested::{NestedNode, NestedVms, NESTED_CONFIGURED_IMAGE_PATH},
     node_software_version::NodeSoftwareVersion,
     port_allocator::AddrType,
@@ -140,28 +140,11 @@ pub fn init_ic(
 
         ic_topology.insert_subnet(
             subnet_index,
-            SubnetConfig::new(
+            subnet_config(
                 subnet_index,
+                subnet,
                 nodes,
                 initial_replica.replica_version.clone(),
-                subnet.max_ingress_bytes_per_message,
-                subnet.max_ingress_messages_per_block,
-                subnet.max_block_payload_size,
-                subnet.unit_delay,
-                subnet.initial_notary_delay,
-                subnet.dkg_interval_length,
-                subnet.dkg_dealings_per_block,
-                subnet.subnet_type,
-                subnet.max_instructions_per_message,
-                subnet.max_instructions_per_round,
-                subnet.max_instructions_per_install_code,
-                subnet.features,
-                subnet.chain_key_config.clone().map(|c| c.into()),
-                subnet.max_number_of_canisters,
-                subnet.ssh_readonly_access.clone(),
-                subnet.ssh_backup_access.clone(),
-                subnet.running_state,
-                Some(subnet.initial_height),
             ),
         );
     }
@@ -328,7 +311,7 @@ pub fn setup_and_start_vms(
     }
     // In the tests we may need to identify, which node/s have malicious behavior.
     // We dump this info into a file.
-    env.write_json_object(NODES_INFO, &nodes_info)?;
+    env.write_versioned(NODES_INFO, NODES_INFO_SCHEMA_VERSION, &nodes_info)?;
 
     let mut result = Ok(());
     // Wait for all threads to finish and return an error if any of them fails.
@@ -360,11 +343,14 @@ pub fn setup_and_start_nested_vms(
         let t_env = env.to_owned();
         let t_group_name = group_name.to_owned();
         let t_vm_name = node.name.to_owned();
+        let t_setupos_image = node.setupos_image.clone();
         let t_nns_url = nns_url.to_owned();
         let t_nns_public_key = nns_public_key.to_owned();
         join_handles.push(thread::spawn(move || {
-            let configured_image =
-                configure_setupos_image(&t_env, &t_vm_name, &t_nns_url, &t_nns_public_key)?;
+            let configured_image = match t_setupos_image {
+                Some(setupos_image) => setupos_image,
+                None => configure_setupos_image(&t_env, &t_vm_name, &t_nns_url, &t_nns_public_key)?,
+            };
 
             let configured_image_spec = AttachImageSpec::new(t_farm.upload_file(
                 &t_group_name,
@@ -378,6 +364,10 @@ pub fn setup_and_start_nested_vms(
                 vec![configured_image_spec],
             )?;
             t_farm.start_vm(&t_group_name, &t_vm_name)?;
+            t_env.write_nested_vm_console_url(
+                &t_vm_name,
+                &t_farm.vm_console_url(&t_group_name, &t_vm_name),
+            )?;
 
             Ok(())
         }));
@@ -571,6 +561,39 @@ fn create_config_disk_image(
     Ok(())
 }
 
+/// Translates the `subnet` builder into the ic-prep configuration from which
+/// its initial registry record is generated.
+fn subnet_config(
+    subnet_index: u64,
+    subnet: &Subnet,
+    nodes: BTreeMap<NodeIndex, NodeConfiguration>,
+    replica_version: ReplicaVersion,
+) -> SubnetConfig {
+    SubnetConfig::new(
+        subnet_index,
+        nodes,
+        replica_version,
+        subnet.max_ingress_bytes_per_message,
+        subnet.max_ingress_messages_per_block,
+        subnet.max_block_payload_size,
+        subnet.unit_delay,
+        subnet.initial_notary_delay,
+        subnet.dkg_interval_length,
+        subnet.dkg_dealings_per_block,
+        subnet.subnet_type,
+        subnet.max_instructions_per_message,
+        subnet.max_instructions_per_round,
+        subnet.max_instructions_per_install_code,
+        subnet.features,
+        subnet.chain_key_config.clone().map(|c| c.into()),
+        subnet.max_number_of_canisters,
+        subnet.ssh_readonly_access.clone(),
+        subnet.ssh_backup_access.clone(),
+        subnet.running_state,
+        Some(subnet.initial_height),
+    )
+}
+
 fn node_to_config(node: &Node) -> NodeConfiguration {
     let ipv6_addr = IpAddr::V6(node.ipv6.expect("missing ip_addr"));
     let public_api = SocketAddr::new(ipv6_addr, AddrType::PublicApi.into());
@@ -692,3 +715,158 @@ fn configure_setupos_image(
 
     Ok(configured_image)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_types::Height;
+    use std::{str::FromStr, time::Duration};
+
+    #[test]
+    fn subnet_record_carries_consensus_overrides() {
+        let ic = InternetComputer::new().add_subnet(
+            Subnet::new(SubnetType::System)
+                .with_dkg_interval_length(Height::from(19))
+                .with_initial_notary_delay(Duration::from_millis(100))
+                .add_nodes(1),
+        );
+        let subnet = &ic.subnets[0];
+        subnet.check_consensus_config().unwrap();
+
+        let nodes = BTreeMap::from([(
+            0,
+            NodeConfiguration {
+                xnet_api: SocketAddr::from_str("1.2.3.4:8080").unwrap(),
+                public_api: SocketAddr::from_str("1.2.3.4:8081").unwrap(),
+                node_operator_principal_id: None,
+                secret_key_store: None,
+                domain: None,
+            },
+        )]);
+        let tempdir = tempfile::tempdir().unwrap();
+        let initialized_subnet = subnet_config(0, subnet, nodes, ReplicaVersion::default())
+            .initialize(tempdir.path())
+            .unwrap();
+
+        let subnet_record = initialized_subnet.subnet_record;
+        assert_eq!(subnet_record.dkg_interval_length, 19);
+        assert_eq!(subnet_record.initial_notary_delay_millis, 100);
+    }
+
+    #[test]
+    fn dkg_interval_shorter_than_initial_notary_delay_is_rejected() {
+        let subnet = Subnet::new(SubnetType::System)
+            .with_unit_delay(Duration::from_millis(200))
+            .with_dkg_interval_length(Height::from(4))
+            .with_initial_notary_delay(Duration::from_secs(2))
+            .add_nodes(1);
+
+        let err = subnet.check_consensus_config().unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("lasts 1s, which is shorter than the initial notary delay of 2s"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn allocates_and_starts_nested_vm() {
+        use crate::driver::ic::{NrOfVCPUs, VmResources};
+        use crate::driver::resource::{
+            vm_spec_from_nested_node, DiskImage, FarmVmAllocator, ImageType, VmAllocator,
+        };
+        use mockito::Matcher;
+        use serde_json::json;
+
+        let mut server = mockito::Server::new();
+        let create_vm = server
+            .mock("POST", "/group/test-group/vm/host-1")
+            .match_body(Matcher::PartialJson(json!({"type": "nested", "vCPUs": 16})))
+            .with_status(200)
+            .with_body(
+                json!({
+                    "ipv6": "2a0b:21c0:4003:2:5000:ff:fe00:1",
+                    "mac6": "52:00:00:00:00:01",
+                    "hostname": "host-1",
+                    "spec": {"vCPUs": 16, "memoryKiB": 25165824},
+                })
+                .to_string(),
+            )
+            .create();
+        let upload_setupos = server
+            .mock("POST", "/group/test-group/file")
+            .with_status(200)
+            .with_body(json!({"imageIds": {NESTED_CONFIGURED_IMAGE_PATH: "setupos"}}).to_string())
+            .create();
+        let attach_setupos = server
+            .mock(
+                "PUT",
+                "/group/test-group/vm/host-1/drive-templates/usb-storage",
+            )
+            .with_status(200)
+            .create();
+        let start_vm = server
+            .mock("PUT", "/group/test-group/vm/host-1/start")
+            .with_status(200)
+            .create();
+        let logger = Logger::root(slog::Discard, slog::o!());
+        let farm = Farm::new(
+            Url::parse(&format!("{}/", server.url())).unwrap(),
+            logger.clone(),
+        );
+        let tempdir = tempfile::tempdir().unwrap();
+        let env = TestEnv::new_without_duplicating_logger(tempdir.path(), logger);
+        let setupos_image = tempdir.path().join("setupos.img.zst");
+        std::fs::write(&setupos_image, b"SetupOS").unwrap();
+        let node = NestedNode::new("host-1".to_string())
+            .with_vm_resources(VmResources {
+                vcpus: Some(NrOfVCPUs::new(16)),
+                ..VmResources::default()
+            })
+            .with_setupos_image(setupos_image);
+
+        let allocator = FarmVmAllocator {
+            farm: &farm,
+            group_name: "test-group",
+            primary_image: &DiskImage {
+                image_type: ImageType::IcOsImage,
+                url: Url::parse("https://example.com/disk-img.tar.zst").unwrap(),
+                sha256: "na".to_string(),
+            },
+            env: &env,
+        };
+        let empty_image: FileId = serde_json::from_value(json!("empty")).unwrap();
+        let vm = allocator
+            .allocate_vm(&vm_spec_from_nested_node(&node, None, empty_image))
+            .unwrap();
+        env.write_nested_vm(&node.name, &vm).unwrap();
+        setup_and_start_nested_vms(
+            &[node],
+            &env,
+            &farm,
+            "test-group",
+            &Url::parse("http://[::1]:8080/").unwrap(),
+            "nns-public-key",
+        )
+        .unwrap();
+
+        create_vm.assert();
+        upload_setupos.assert();
+        attach_setupos.assert();
+        start_vm.assert();
+        let nested_vm = env.get_nested_vm("host-1").unwrap();
+        assert_eq!(
+            nested_vm.get_console_url().unwrap(),
+            farm.vm_console_url("test-group", "host-1")
+        );
+        assert!(nested_vm
+            .get_console_url()
+            .unwrap()
+            .as_str()
+            .ends_with("/group/test-group/vm/host-1/console/"));
+        assert_eq!(
+            nested_vm.get_ipv6().unwrap(),
+            nested_vm.get_vm().unwrap().ipv6
+        );
+    }
+}
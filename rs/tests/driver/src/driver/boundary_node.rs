@@ -80,6 +80,15 @@ pub struct BoundaryNodeCustomDomainsConfig {
     pub peek_sleep_sec: Option<u64>,
 }
 
+/// Static IPv4 configuration written into the config image of a boundary node.
+/// Without one, the boundary node obtains its IPv4 address via DHCP.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct Ipv4Config {
+    pub address: Ipv4Addr,
+    pub prefix_length: u8,
+    pub gateway: Ipv4Addr,
+}
+
 /// A builder for the initial configuration of an IC boundary node.
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Deserialize)]
 pub struct BoundaryNode {
@@ -103,6 +112,7 @@ pub struct BoundaryNodeWithVm {
     pub nns_public_key: Option<PathBuf>,
     pub replica_ipv6_rule: String,
     pub has_ipv4: bool,
+    pub ipv4_config: Option<Ipv4Config>,
     pub custom_domains_config: Option<BoundaryNodeCustomDomainsConfig>,
 }
 
@@ -110,6 +120,15 @@ impl BoundaryNodeWithVm {
     pub fn ipv6(&self) -> Ipv6Addr {
         self.allocated_vm.ipv6
     }
+
+    /// The IPv4 address of this BN, if known before it boots: either the
+    /// statically configured one or the one assigned during allocation.
+    pub fn ipv4(&self) -> Option<Ipv4Addr> {
+        self.ipv4_config
+            .map(|ipv4_config| ipv4_config.address)
+            .or(self.allocated_vm.ipv4)
+    }
+
     /// Acquire a playnet certificate (or fail if all have been acquired already)
     /// for the domain `ic{ix}.farm.dfinity.systems`
     /// where `ix` is the index of the acquired playnet.
@@ -165,6 +184,13 @@ impl BoundaryNodeWithVm {
         self
     }
 
+    /// Configure the IPv4 address and gateway of this BN statically instead
+    /// of via DHCP. Requires IPv4 to be enabled (see [BoundaryNode::with_ipv4]).
+    pub fn with_ipv4_config(mut self, ipv4_config: Ipv4Config) -> Self {
+        self.ipv4_config = Some(ipv4_config);
+        self
+    }
+
     pub fn with_custom_domains(
         mut self,
         custom_domains_config: BoundaryNodeCustomDomainsConfig,
@@ -207,6 +233,9 @@ impl BoundaryNodeWithVm {
                 "cannot set use_real_certs_and_dns and use_ipv6_certs at the same time",
             ));
         }
+        if self.ipv4_config.is_some() && !self.has_ipv4 {
+            bail!("cannot set an IPv4 config on a boundary node without IPv4");
+        }
         let logger = env.logger();
         let pot_setup = GroupSetup::read_attribute(env);
         let farm_url = env.get_farm_url()?;
@@ -314,9 +343,15 @@ impl BoundaryNodeWithVm {
             "debug: existing playnet {:?}", opt_existing_playnet
         );
 
+        // Record the statically configured IPv4 address, so that it can be
+        // retrieved without connecting to the BN.
+        let vm = VMCreateResponse {
+            ipv4: self.ipv4(),
+            ..self.allocated_vm.clone()
+        };
         env.write_boundary_node_vm(
             &self.name,
-            &self.allocated_vm,
+            &vm,
             opt_existing_playnet_cert
                 .as_ref()
                 .map(|existing_playnet_cert| existing_playnet_cert.playnet.clone()),
@@ -375,17 +410,20 @@ impl BoundaryNodeWithVm {
 
                 existing_playnet.a_records.push(ipv4_address);
 
-                if InfraProvider::read_attribute(env) == InfraProvider::Farm {
-                    let bn_fqdn = env.create_playnet_dns_records(vec![DnsRecord {
-                        name: "".to_string(),
-                        record_type: DnsRecordType::A,
-                        records: existing_playnet.a_records.clone(),
-                    }]);
-                    info!(
-                        &logger,
-                        "Created A record {} to {:?}", bn_fqdn, existing_playnet.a_records
-                    );
-                }
+                let name = if InfraProvider::read_attribute(env) == InfraProvider::Farm {
+                    "".to_string()
+                } else {
+                    existing_playnet.playnet_cert.playnet.clone()
+                };
+                let bn_fqdn = env.create_playnet_dns_records(vec![DnsRecord {
+                    name,
+                    record_type: DnsRecordType::A,
+                    records: existing_playnet.a_records.clone(),
+                }]);
+                info!(
+                    &logger,
+                    "Created A record {} to {:?}", bn_fqdn, existing_playnet.a_records
+                );
             }
         }
 
@@ -430,6 +468,19 @@ impl BoundaryNode {
         self
     }
 
+    /// Request an IPv4 address from the infrastructure provider during
+    /// allocation, in addition to the IPv6 address. This is the default.
+    pub fn with_ipv4(mut self) -> Self {
+        self.has_ipv4 = true;
+        self
+    }
+
+    /// Make the boundary node reachable over IPv6 only.
+    pub fn without_ipv4(mut self) -> Self {
+        self.has_ipv4 = false;
+        self
+    }
+
     pub fn allocate_vm(self, env: &TestEnv) -> Result<BoundaryNodeWithVm> {
         let farm = Farm::from_test_env(env, "boundary node");
         let pot_setup = GroupSetup::read_attribute(env);
@@ -503,11 +554,40 @@ impl BoundaryNode {
             use_real_certs_and_dns: false,
             use_ipv6_certs: false,
             has_ipv4: self.has_ipv4,
+            ipv4_config: Default::default(),
             custom_domains_config: Default::default(),
         })
     }
 }
 
+/// Arguments of the config image script that determine the network config
+/// (`network.conf`) of the boundary node.
+fn network_config_args(boundary_node: &BoundaryNodeWithVm) -> Vec<String> {
+    let mut args = vec!["--ipv6_name_servers".to_string()];
+    if !boundary_node.ipv6_nameservers.is_empty() {
+        args.push(boundary_node.ipv6_nameservers.join(" "));
+    } else {
+        // Cloudflare DNS servers
+        args.push("2606:4700:4700::1111 2606:4700:4700::1001".to_string());
+    }
+
+    if !boundary_node.ipv4_nameservers.is_empty() {
+        args.push("--ipv4_name_servers".to_string());
+        args.push(boundary_node.ipv4_nameservers.join(" "));
+    }
+
+    if let Some(ipv4_config) = boundary_node.ipv4_config {
+        args.push("--ipv4_address".to_string());
+        args.push(format!(
+            "{}/{}",
+            ipv4_config.address, ipv4_config.prefix_length
+        ));
+        args.push("--ipv4_gateway".to_string());
+        args.push(ipv4_config.gateway.to_string());
+    }
+    args
+}
+
 /// side-effectful function that creates the config disk images
 /// in the boundary node directories.
 fn create_config_disk_image(
@@ -554,19 +634,7 @@ fn create_config_disk_image(
             .arg(format!("https://{}", elasticsearch_host));
     }
 
-    // add custom nameservers
-    cmd.arg("--ipv6_name_servers");
-    if !boundary_node.ipv6_nameservers.is_empty() {
-        cmd.arg(boundary_node.ipv6_nameservers.join(" "));
-    } else {
-        // Cloudflare DNS servers
-        cmd.arg("2606:4700:4700::1111 2606:4700:4700::1001");
-    }
-
-    if !boundary_node.ipv4_nameservers.is_empty() {
-        cmd.arg("--ipv4_name_servers");
-        cmd.arg(boundary_node.ipv6_nameservers.join(" "));
-    }
+    cmd.args(network_config_args(boundary_node));
 
     if !boundary_node.nns_node_urls.is_empty() {
         cmd.arg("--nns_url").arg({
@@ -796,6 +864,13 @@ impl BoundaryNodeSnapshot {
         self.vm.ipv6
     }
 
+    /// The IPv4 address of this BN, if it was known when the BN was started.
+    /// Use [RetrieveIpv4Addr::block_on_ipv4] to also retrieve addresses
+    /// obtained via DHCP.
+    pub fn ipv4(&self) -> Option<Ipv4Addr> {
+        self.vm.ipv4
+    }
+
     pub fn get_playnet(&self) -> Option<String> {
         self.playnet.clone()
     }
@@ -889,6 +964,9 @@ impl RetrieveIpv4Addr for BoundaryNodeSnapshot {
             InfraProvider::K8s => Ok(self.vm.ipv4.expect("ipv4 should be present")),
             InfraProvider::Farm => {
                 use anyhow::Context;
+                if let Some(ipv4) = self.ipv4() {
+                    return Ok(ipv4);
+                }
                 let ipv4_string = self.block_on_bash_script(IPV4_RETRIEVE_SH_SCRIPT)?;
                 ipv4_string
                     .trim()
@@ -921,3 +999,118 @@ pub fn emit_bn_aaaa_records_event(log: &slog::Logger, bn_fqdn: &str, aaaa_record
     );
     event.emit_log(log);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::farm::VmSpec;
+    use slog::{o, Logger};
+
+    fn vm(ipv4: Option<Ipv4Addr>) -> VMCreateResponse {
+        VMCreateResponse {
+            ipv6: "2001:db8::1".parse().unwrap(),
+            ipv4,
+            mac6: "00:00:00:00:00:01".to_string(),
+            hostname: "bn".to_string(),
+            spec: VmSpec {
+                v_cpus: 6,
+                memory_ki_b: 25165824,
+            },
+            placement: None,
+        }
+    }
+
+    fn boundary_node_with_vm(allocated_vm: VMCreateResponse) -> BoundaryNodeWithVm {
+        BoundaryNodeWithVm {
+            name: "bn".to_string(),
+            allocated_vm,
+            ipv4_nameservers: vec![],
+            ipv6_nameservers: vec![],
+            use_real_certs_and_dns: false,
+            use_ipv6_certs: false,
+            nns_node_urls: vec![],
+            nns_public_key: None,
+            replica_ipv6_rule: String::new(),
+            has_ipv4: true,
+            ipv4_config: None,
+            custom_domains_config: None,
+        }
+    }
+
+    const IPV4_CONFIG: Ipv4Config = Ipv4Config {
+        address: Ipv4Addr::new(192, 0, 2, 10),
+        prefix_length: 24,
+        gateway: Ipv4Addr::new(192, 0, 2, 1),
+    };
+
+    fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+        args.iter()
+            .position(|arg| arg == name)
+            .map(|i| args[i + 1].as_str())
+    }
+
+    #[test]
+    fn network_config_args_contain_static_ipv4_settings() {
+        let bn = boundary_node_with_vm(vm(None))
+            .with_ipv4_config(IPV4_CONFIG)
+            .with_ipv4_nameservers(vec!["1.1.1.1".to_string(), "8.8.8.8".to_string()]);
+
+        let args = network_config_args(&bn);
+
+        assert_eq!(arg_value(&args, "--ipv4_address"), Some("192.0.2.10/24"));
+        assert_eq!(arg_value(&args, "--ipv4_gateway"), Some("192.0.2.1"));
+        assert_eq!(
+            arg_value(&args, "--ipv4_name_servers"),
+            Some("1.1.1.1 8.8.8.8")
+        );
+    }
+
+    #[test]
+    fn network_config_args_leave_ipv4_to_dhcp_without_config() {
+        let args = network_config_args(&boundary_node_with_vm(vm(None)));
+
+        assert_eq!(arg_value(&args, "--ipv4_address"), None);
+        assert_eq!(arg_value(&args, "--ipv4_gateway"), None);
+        assert_eq!(
+            arg_value(&args, "--ipv6_name_servers"),
+            Some("2606:4700:4700::1111 2606:4700:4700::1001")
+        );
+    }
+
+    #[test]
+    fn ipv4_prefers_static_config_over_allocated_address() {
+        let allocated = Ipv4Addr::new(198, 51, 100, 7);
+
+        assert_eq!(boundary_node_with_vm(vm(None)).ipv4(), None);
+        assert_eq!(
+            boundary_node_with_vm(vm(Some(allocated))).ipv4(),
+            Some(allocated)
+        );
+        assert_eq!(
+            boundary_node_with_vm(vm(Some(allocated)))
+                .with_ipv4_config(IPV4_CONFIG)
+                .ipv4(),
+            Some(IPV4_CONFIG.address)
+        );
+    }
+
+    #[test]
+    fn ipv4_is_retrievable_from_deployed_boundary_node() {
+        let dir = tempfile::tempdir().unwrap();
+        let env =
+            TestEnv::new_without_duplicating_logger(dir.path(), Logger::root(slog::Discard, o!()));
+        env.write_boundary_node_vm("with-ipv4", &vm(Some(IPV4_CONFIG.address)), None)
+            .unwrap();
+        env.write_boundary_node_vm("without-ipv4", &vm(None), None)
+            .unwrap();
+
+        let snapshot = |name: &str| {
+            env.get_deployed_boundary_node(name)
+                .unwrap()
+                .get_snapshot()
+                .unwrap()
+        };
+        assert_eq!(snapshot("with-ipv4").ipv4(), Some(IPV4_CONFIG.address));
+        assert_eq!(snapshot("without-ipv4").ipv4(), None);
+    }
+}
@@ -0,0 +1,283 @@
+//! A small corpus of trivial canisters that is versioned together with the
+//! test driver.
+//!
+//! Many system tests only need a canister that counts, echoes its argument or
+//! touches stable memory. Instead of building or vendoring their own Wasm,
+//! such tests can install one of the [Corpus] canisters and talk to it via the
+//! typed client wrappers defined in this module:
+//!
+//! ```ignore
+//! let subnet = env.topology_snapshot().subnets().next().unwrap();
+//! let canister_id = env.install_corpus_canister(Corpus::Counter, &subnet);
+//! let counter = CounterCanister::new(agent, canister_id);
+//! assert_eq!(counter.inc().await?, 1);
+//! ```
+//!
+//! The canisters are embedded as WAT sources and compiled on demand. The
+//! SHA-256 hash of every source is committed below, so any change to the corpus
+//! shows up in review together with the updated hash.
+
+use crate::driver::test_env::TestEnv;
+use crate::driver::test_env_api::{IcNodeContainer, SubnetSnapshot};
+use anyhow::{bail, Result};
+use candid::Principal;
+use ic_agent::Agent;
+use ic_utils::interfaces::{management_canister::builders::InstallMode, ManagementCanister};
+use slog::info;
+
+/// Upper bound on the size of every compiled corpus module, in bytes.
+pub const MAX_CORPUS_WASM_SIZE: usize = 4 * 1024;
+
+/// Size of a Wasm (and stable memory) page, in bytes.
+const WASM_PAGE_SIZE: u64 = 64 * 1024;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Corpus {
+    /// Counter that keeps its value across upgrades.
+    Counter,
+    /// Replies with its argument or the caller's principal.
+    Echo,
+    /// Grows stable memory by a configurable number of pages.
+    StableWriter,
+}
+
+impl Corpus {
+    pub const ALL: [Corpus; 3] = [Corpus::Counter, Corpus::Echo, Corpus::StableWriter];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Corpus::Counter => "counter",
+            Corpus::Echo => "echo",
+            Corpus::StableWriter => "stable_writer",
+        }
+    }
+
+    /// The embedded WAT source of the canister.
+    pub fn wat(&self) -> &'static str {
+        match self {
+            Corpus::Counter => include_str!("canisters/counter.wat"),
+            Corpus::Echo => include_str!("canisters/echo.wat"),
+            Corpus::StableWriter => include_str!("canisters/stable_writer.wat"),
+        }
+    }
+
+    /// The hex-encoded SHA-256 hash of [Corpus::wat] that was reviewed.
+    pub fn expected_sha256(&self) -> &'static str {
+        match self {
+            Corpus::Counter => "36d1795b03179b58149805f0cdb33a3724638b91a1aaeb8687ed6597e9d96884",
+            Corpus::Echo => "a48f3165119355565c6ecb4a9ff1656f0d684a3d6408d7862b3c580d5abb78da",
+            Corpus::StableWriter => {
+                "d353f8add41c622d4dad1fa73eef6b22a2ce5bfccd52f84048caee98a206c2e6"
+            }
+        }
+    }
+
+    /// Compiles the embedded source to a Wasm binary.
+    pub fn wasm(&self) -> Vec<u8> {
+        wat::parse_str(self.wat())
+            .unwrap_or_else(|e| panic!("Could not compile corpus canister {}: {e}", self.name()))
+    }
+}
+
+pub trait InstallCorpusCanister {
+    /// Creates a canister on the first node of `subnet` and installs the given
+    /// corpus canister into it.
+    fn install_corpus_canister(&self, corpus: Corpus, subnet: &SubnetSnapshot) -> Principal;
+}
+
+impl InstallCorpusCanister for TestEnv {
+    fn install_corpus_canister(&self, corpus: Corpus, subnet: &SubnetSnapshot) -> Principal {
+        let node = subnet
+            .nodes()
+            .next()
+            .unwrap_or_else(|| panic!("Subnet {} has no nodes", subnet.subnet_id));
+        let canister_id = node.create_and_install_canister_with_bytes(corpus.wasm(), None);
+        info!(
+            self.logger(),
+            "Installed corpus canister {} with id {} on subnet {}",
+            corpus.name(),
+            canister_id,
+            subnet.subnet_id
+        );
+        canister_id
+    }
+}
+
+fn decode_u32(bytes: Vec<u8>) -> Result<u32> {
+    match <[u8; 4]>::try_from(bytes.as_slice()) {
+        Ok(bytes) => Ok(u32::from_le_bytes(bytes)),
+        Err(_) => bail!("Expected a 4 byte reply, got {} bytes", bytes.len()),
+    }
+}
+
+fn decode_u64(bytes: Vec<u8>) -> Result<u64> {
+    match <[u8; 8]>::try_from(bytes.as_slice()) {
+        Ok(bytes) => Ok(u64::from_le_bytes(bytes)),
+        Err(_) => bail!("Expected an 8 byte reply, got {} bytes", bytes.len()),
+    }
+}
+
+/// Client for [Corpus::Counter].
+#[derive(Clone)]
+pub struct CounterCanister {
+    agent: Agent,
+    pub canister_id: Principal,
+}
+
+impl CounterCanister {
+    pub fn new(agent: Agent, canister_id: Principal) -> Self {
+        Self { agent, canister_id }
+    }
+
+    pub async fn read(&self) -> Result<u32> {
+        let res = self.agent.query(&self.canister_id, "read").call().await?;
+        decode_u32(res)
+    }
+
+    /// Increments the counter and returns its new value.
+    pub async fn inc(&self) -> Result<u32> {
+        let res = self
+            .agent
+            .update(&self.canister_id, "inc")
+            .call_and_wait()
+            .await?;
+        decode_u32(res)
+    }
+
+    /// Upgrades the canister to the same corpus module, which preserves the
+    /// counter value.
+    pub async fn upgrade(&self) -> Result<()> {
+        ManagementCanister::create(&self.agent)
+            .install_code(&self.canister_id, &Corpus::Counter.wasm())
+            .with_mode(InstallMode::Upgrade(None))
+            .call_and_wait()
+            .await?;
+        Ok(())
+    }
+}
+
+/// Client for [Corpus::Echo].
+#[derive(Clone)]
+pub struct EchoCanister {
+    agent: Agent,
+    pub canister_id: Principal,
+}
+
+impl EchoCanister {
+    pub fn new(agent: Agent, canister_id: Principal) -> Self {
+        Self { agent, canister_id }
+    }
+
+    pub async fn echo(&self, payload: Vec<u8>) -> Result<Vec<u8>> {
+        Ok(self
+            .agent
+            .query(&self.canister_id, "echo")
+            .with_arg(payload)
+            .call()
+            .await?)
+    }
+
+    pub async fn echo_update(&self, payload: Vec<u8>) -> Result<Vec<u8>> {
+        Ok(self
+            .agent
+            .update(&self.canister_id, "echo_update")
+            .with_arg(payload)
+            .call_and_wait()
+            .await?)
+    }
+
+    /// Returns the principal the canister sees as the caller.
+    pub async fn whoami(&self) -> Result<Principal> {
+        let res = self.agent.query(&self.canister_id, "whoami").call().await?;
+        Ok(Principal::try_from_slice(&res)?)
+    }
+}
+
+/// Client for [Corpus::StableWriter].
+#[derive(Clone)]
+pub struct StableWriterCanister {
+    agent: Agent,
+    pub canister_id: Principal,
+}
+
+impl StableWriterCanister {
+    /// Marker byte written at the start of every page grown by
+    /// [StableWriterCanister::grow_and_write].
+    pub const PAGE_MARKER: u8 = 0xAB;
+
+    pub fn new(agent: Agent, canister_id: Principal) -> Self {
+        Self { agent, canister_id }
+    }
+
+    /// Returns the size of stable memory in pages.
+    pub async fn size(&self) -> Result<u64> {
+        let res = self.agent.query(&self.canister_id, "size").call().await?;
+        decode_u64(res)
+    }
+
+    /// Grows stable memory by `pages` pages, marks each of them and returns
+    /// the new size of stable memory in pages.
+    pub async fn grow_and_write(&self, pages: u64) -> Result<u64> {
+        let res = self
+            .agent
+            .update(&self.canister_id, "grow_and_write")
+            .with_arg(pages.to_le_bytes().to_vec())
+            .call_and_wait()
+            .await?;
+        decode_u64(res)
+    }
+
+    /// Returns the first byte of the given stable memory page.
+    pub async fn read_page_marker(&self, page: u64) -> Result<u8> {
+        let res = self
+            .agent
+            .query(&self.canister_id, "read_page_marker")
+            .with_arg(page.to_le_bytes().to_vec())
+            .call()
+            .await?;
+        match res.as_slice() {
+            [marker] => Ok(*marker),
+            _ => bail!("Expected a 1 byte reply, got {} bytes", res.len()),
+        }
+    }
+
+    /// Returns the size of stable memory in bytes.
+    pub async fn size_in_bytes(&self) -> Result<u64> {
+        Ok(self.size().await? * WASM_PAGE_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+
+    #[test]
+    fn embedded_sources_match_committed_hashes() {
+        for corpus in Corpus::ALL {
+            let hash = hex::encode(Sha256::digest(corpus.wat().as_bytes()));
+            assert_eq!(
+                hash,
+                corpus.expected_sha256(),
+                "The source of corpus canister {} changed. If this is intended, \
+                 update its hash in `Corpus::expected_sha256`.",
+                corpus.name()
+            );
+        }
+    }
+
+    #[test]
+    fn corpus_canisters_compile_within_size_budget() {
+        for corpus in Corpus::ALL {
+            let wasm = corpus.wasm();
+            assert!(wasm.starts_with(b"\0asm"));
+            assert!(
+                wasm.len() <= MAX_CORPUS_WASM_SIZE,
+                "Corpus canister {} is {} bytes, more than {} bytes",
+                corpus.name(),
+                wasm.len(),
+                MAX_CORPUS_WASM_SIZE
+            );
+        }
+    }
+}
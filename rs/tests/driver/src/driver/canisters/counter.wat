@@ -0,0 +1,43 @@
+;; Counter canister with upgrade support.
+;;
+;; The counter lives in a global and is persisted to the first four bytes of
+;; stable memory in `canister_pre_upgrade`, so its value survives upgrades.
+(module
+  (import "ic0" "msg_reply" (func $msg_reply))
+  (import "ic0" "msg_reply_data_append"
+    (func $msg_reply_data_append (param i32 i32)))
+  (import "ic0" "stable64_size" (func $stable64_size (result i64)))
+  (import "ic0" "stable64_grow" (func $stable64_grow (param i64) (result i64)))
+  (import "ic0" "stable64_read" (func $stable64_read (param i64 i64 i64)))
+  (import "ic0" "stable64_write" (func $stable64_write (param i64 i64 i64)))
+
+  ;; Replies with the current value of the counter as a little-endian u32.
+  (func $read
+    (i32.store (i32.const 0) (global.get $counter))
+    (call $msg_reply_data_append (i32.const 0) (i32.const 4))
+    (call $msg_reply))
+
+  ;; Increments the counter and replies with its new value.
+  (func $inc
+    (global.set $counter (i32.add (global.get $counter) (i32.const 1)))
+    (call $read))
+
+  (func $pre_upgrade
+    (if (i64.eqz (call $stable64_size))
+      (then (drop (call $stable64_grow (i64.const 1)))))
+    (i32.store (i32.const 0) (global.get $counter))
+    (call $stable64_write (i64.const 0) (i64.const 0) (i64.const 4)))
+
+  (func $post_upgrade
+    (if (i64.ne (call $stable64_size) (i64.const 0))
+      (then
+        (call $stable64_read (i64.const 0) (i64.const 0) (i64.const 4))
+        (global.set $counter (i32.load (i32.const 0))))))
+
+  (memory $memory 1)
+  (global $counter (mut i32) (i32.const 0))
+  (export "canister_query read" (func $read))
+  (export "canister_update inc" (func $inc))
+  (export "canister_pre_upgrade" (func $pre_upgrade))
+  (export "canister_post_upgrade" (func $post_upgrade))
+)
@@ -0,0 +1,31 @@
+;; Echo / reflection canister.
+;;
+;; `echo` and `echo_update` reply with their argument unchanged, `whoami`
+;; replies with the raw bytes of the caller's principal.
+(module
+  (import "ic0" "msg_reply" (func $msg_reply))
+  (import "ic0" "msg_reply_data_append"
+    (func $msg_reply_data_append (param i32 i32)))
+  (import "ic0" "msg_arg_data_size" (func $msg_arg_data_size (result i32)))
+  (import "ic0" "msg_arg_data_copy"
+    (func $msg_arg_data_copy (param i32 i32 i32)))
+  (import "ic0" "msg_caller_size" (func $msg_caller_size (result i32)))
+  (import "ic0" "msg_caller_copy"
+    (func $msg_caller_copy (param i32 i32 i32)))
+
+  (func $echo
+    (call $msg_arg_data_copy (i32.const 0) (i32.const 0) (call $msg_arg_data_size))
+    (call $msg_reply_data_append (i32.const 0) (call $msg_arg_data_size))
+    (call $msg_reply))
+
+  (func $whoami
+    (call $msg_caller_copy (i32.const 0) (i32.const 0) (call $msg_caller_size))
+    (call $msg_reply_data_append (i32.const 0) (call $msg_caller_size))
+    (call $msg_reply))
+
+  ;; 4 MiB, enough to hold the largest ingress message payload.
+  (memory $memory 64)
+  (export "canister_query echo" (func $echo))
+  (export "canister_update echo_update" (func $echo))
+  (export "canister_query whoami" (func $whoami))
+)
@@ -0,0 +1,62 @@
+;; Stable memory writer canister.
+;;
+;; `grow_and_write` takes a little-endian u64 page count, grows stable memory
+;; by that many 64 KiB pages and writes a marker byte (0xAB) at the start of
+;; every new page. It replies with the new stable memory size in pages.
+;; `size` replies with the current stable memory size in pages and
+;; `read_page_marker` takes a little-endian u64 page index and replies with
+;; the first byte of that page.
+(module
+  (import "ic0" "msg_reply" (func $msg_reply))
+  (import "ic0" "msg_reply_data_append"
+    (func $msg_reply_data_append (param i32 i32)))
+  (import "ic0" "msg_arg_data_copy"
+    (func $msg_arg_data_copy (param i32 i32 i32)))
+  (import "ic0" "trap" (func $trap (param i32 i32)))
+  (import "ic0" "stable64_size" (func $stable64_size (result i64)))
+  (import "ic0" "stable64_grow" (func $stable64_grow (param i64) (result i64)))
+  (import "ic0" "stable64_read" (func $stable64_read (param i64 i64 i64)))
+  (import "ic0" "stable64_write" (func $stable64_write (param i64 i64 i64)))
+
+  (func $reply_size
+    (i64.store (i32.const 0) (call $stable64_size))
+    (call $msg_reply_data_append (i32.const 0) (i32.const 8))
+    (call $msg_reply))
+
+  (func $grow_and_write
+    (local $pages i64)
+    (local $page i64)
+    (local $end i64)
+    (call $msg_arg_data_copy (i32.const 0) (i32.const 0) (i32.const 8))
+    (local.set $pages (i64.load (i32.const 0)))
+    (local.set $page (call $stable64_grow (local.get $pages)))
+    (if (i64.eq (local.get $page) (i64.const -1))
+      (then (call $trap (i32.const 16) (i32.const 18))))
+    (local.set $end (i64.add (local.get $page) (local.get $pages)))
+    (i32.store8 (i32.const 8) (i32.const 0xAB))
+    (block $done
+      (loop $next
+        (br_if $done (i64.ge_u (local.get $page) (local.get $end)))
+        (call $stable64_write
+          (i64.mul (local.get $page) (i64.const 65536))
+          (i64.const 8)
+          (i64.const 1))
+        (local.set $page (i64.add (local.get $page) (i64.const 1)))
+        (br $next)))
+    (call $reply_size))
+
+  (func $read_page_marker
+    (call $msg_arg_data_copy (i32.const 0) (i32.const 0) (i32.const 8))
+    (call $stable64_read
+      (i64.const 8)
+      (i64.mul (i64.load (i32.const 0)) (i64.const 65536))
+      (i64.const 1))
+    (call $msg_reply_data_append (i32.const 8) (i32.const 1))
+    (call $msg_reply))
+
+  (memory $memory 1)
+  (data (i32.const 16) "stable_grow failed")
+  (export "canister_query size" (func $reply_size))
+  (export "canister_update grow_and_write" (func $grow_and_write))
+  (export "canister_query read_page_marker" (func $read_page_marker))
+)
@@ -0,0 +1,229 @@
+//! Records which driver capabilities a test exercised.
+//!
+//! Helpers in `test_env_api` and friends record a [`Capability`] when they are
+//! used, e.g. when an agent is created or an SSH session is opened. Records are
+//! scoped to the [`TestEnv`] of the test: every environment owns a
+//! [`CapabilityRecorder`] that persists the set of recorded capabilities to
+//! `capabilities.json` in the environment directory, from where the parent
+//! process picks them up when creating the report.
+//!
+//! Helpers that have no access to a `TestEnv` (e.g. proposal submission) record
+//! via [`record`] into the recorder installed for the current thread with
+//! [`with_recorder`]. The driver installs the recorder of the test environment
+//! for the duration of each test function. Calls made on other threads, e.g.
+//! from tasks spawned on a multi-threaded runtime, are not recorded.
+//!
+//! Recording never fails and never changes the behavior of the helper: errors
+//! while persisting are ignored, as the records are informational only.
+//!
+//! [`TestEnv`]: crate::driver::test_env::TestEnv
+
+use ic_sys::fs::write_atomically;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Name of the file the capabilities are persisted to, relative to the
+/// directory of the test environment.
+pub const CAPABILITIES_FILE: &str = "capabilities.json";
+
+/// A driver API or system feature that a test may exercise.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// An agent was created to talk to a node or boundary node.
+    AgentCreated,
+    /// An SSH session was opened to a VM.
+    SshSession,
+    /// An NNS proposal was submitted.
+    ProposalSubmitted,
+    /// A deployed universal VM was looked up.
+    UniversalVmTouched,
+    /// Metrics were fetched from nodes.
+    MetricsFetched,
+}
+
+/// Records the capabilities used within a single test environment.
+#[derive(Debug)]
+pub struct CapabilityRecorder {
+    path: PathBuf,
+    recorded: Mutex<BTreeSet<Capability>>,
+}
+
+impl CapabilityRecorder {
+    /// Creates a recorder persisting to `CAPABILITIES_FILE` in `dir`.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            path: dir.as_ref().join(CAPABILITIES_FILE),
+            recorded: Default::default(),
+        }
+    }
+
+    /// Records `capability`. Only the first record of every capability touches
+    /// the file system, all subsequent ones are a lookup in the in-memory set.
+    pub fn record(&self, capability: Capability) {
+        let mut recorded = self.recorded.lock().unwrap();
+        if !recorded.insert(capability) {
+            return;
+        }
+        // Other recorders (e.g. of a `TestEnv` created separately for the same
+        // directory) may have persisted capabilities in the meantime.
+        let mut persisted: BTreeSet<_> = read_capabilities(self.path.parent().unwrap())
+            .into_iter()
+            .collect();
+        persisted.extend(recorded.iter().copied());
+        let _ = write_atomically(&self.path, |buf| {
+            serde_json::to_writer(buf, &persisted)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+        });
+    }
+
+    /// Returns the capabilities recorded by this recorder.
+    pub fn recorded(&self) -> BTreeSet<Capability> {
+        self.recorded.lock().unwrap().clone()
+    }
+}
+
+/// Reads the capabilities persisted in `dir`. Returns an empty list if none
+/// were recorded or the file cannot be read.
+pub fn read_capabilities<P: AsRef<Path>>(dir: P) -> Vec<Capability> {
+    File::open(dir.as_ref().join(CAPABILITIES_FILE))
+        .ok()
+        .and_then(|file| serde_json::from_reader(file).ok())
+        .unwrap_or_default()
+}
+
+thread_local! {
+    static CURRENT_RECORDER: RefCell<Option<Arc<CapabilityRecorder>>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with `recorder` installed as the recorder of the current thread.
+/// The previously installed recorder (if any) is restored afterwards, also if
+/// `f` panics.
+pub fn with_recorder<R>(recorder: Arc<CapabilityRecorder>, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Arc<CapabilityRecorder>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CURRENT_RECORDER.with(|current| *current.borrow_mut() = self.0.take());
+        }
+    }
+
+    let _restore = Restore(CURRENT_RECORDER.with(|current| current.replace(Some(recorder))));
+    f()
+}
+
+/// Records `capability` with the recorder installed for the current thread.
+/// Does nothing if no recorder is installed.
+pub fn record(capability: Capability) {
+    CURRENT_RECORDER.with(|current| {
+        if let Some(recorder) = current.borrow().as_ref() {
+            recorder.record(capability);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::test_env::TestEnv;
+    use crate::driver::universal_vm::UniversalVms;
+    use slog::{o, Logger};
+    use std::thread;
+
+    fn test_env(dir: &Path) -> TestEnv {
+        TestEnv::new_without_duplicating_logger(dir, Logger::root(slog::Discard, o!()))
+    }
+
+    #[test]
+    fn records_helper_calls_into_test_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = test_env(dir.path());
+        std::fs::create_dir_all(env.get_deployed_universal_vm_dir("uvm")).unwrap();
+
+        // A failed lookup does not count as touching a universal VM.
+        assert!(env.get_deployed_universal_vm("missing").is_err());
+        assert!(read_capabilities(dir.path()).is_empty());
+
+        env.get_deployed_universal_vm("uvm").unwrap();
+        env.get_deployed_universal_vm("uvm").unwrap();
+        with_recorder(env.capability_recorder(), || {
+            record(Capability::ProposalSubmitted)
+        });
+
+        assert_eq!(
+            read_capabilities(dir.path()),
+            vec![
+                Capability::ProposalSubmitted,
+                Capability::UniversalVmTouched
+            ]
+        );
+    }
+
+    #[test]
+    fn recorders_for_the_same_dir_do_not_overwrite_each_other() {
+        let dir = tempfile::tempdir().unwrap();
+        test_env(dir.path()).record_capability(Capability::SshSession);
+        test_env(dir.path()).record_capability(Capability::AgentCreated);
+
+        assert_eq!(
+            read_capabilities(dir.path()),
+            vec![Capability::AgentCreated, Capability::SshSession]
+        );
+    }
+
+    #[test]
+    fn parallel_tests_do_not_cross_contaminate() {
+        let dirs = [tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap()];
+        let capabilities = [Capability::ProposalSubmitted, Capability::MetricsFetched];
+
+        thread::scope(|s| {
+            for (dir, capability) in dirs.iter().zip(capabilities) {
+                let env = test_env(dir.path());
+                s.spawn(move || {
+                    with_recorder(env.capability_recorder(), || {
+                        for _ in 0..100 {
+                            record(capability);
+                            thread::yield_now();
+                        }
+                    })
+                });
+            }
+        });
+
+        for (dir, capability) in dirs.iter().zip(capabilities) {
+            assert_eq!(read_capabilities(dir.path()), vec![capability]);
+        }
+    }
+
+    #[test]
+    fn record_outside_of_scope_is_a_noop() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = test_env(dir.path());
+
+        with_recorder(env.capability_recorder(), || {});
+        record(Capability::ProposalSubmitted);
+
+        assert!(env.capability_recorder().recorded().is_empty());
+        assert!(read_capabilities(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn forked_env_does_not_inherit_capabilities() {
+        let setup_dir = tempfile::tempdir().unwrap();
+        let test_dir = tempfile::tempdir().unwrap();
+        let setup_env = test_env(setup_dir.path());
+        setup_env.record_capability(Capability::AgentCreated);
+
+        let env = TestEnv::fork_from(
+            setup_dir.path(),
+            test_dir.path(),
+            Logger::root(slog::Discard, o!()),
+        )
+        .unwrap();
+
+        assert!(read_capabilities(env.base_path()).is_empty());
+    }
+}
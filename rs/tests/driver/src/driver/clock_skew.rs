@@ -0,0 +1,292 @@
+//! Clock skew injection into IC nodes, for tests of behavior that depends on
+//! the clocks of the nodes, e.g. consensus and certification under clock skew.
+//!
+//! `ClockSkew::set_clock_offset()` disables the time synchronization of a node
+//! and steps its clock via SSH. The offset is recorded in the `TestEnv` of the
+//! node, from which the driver restores the clocks of all skewed nodes once the
+//! test function returns, whether it succeeded or not (see
+//! `restore_clock_offsets()`).
+use crate::driver::config::{NODES_INFO, NODES_INFO_SCHEMA_VERSION};
+use crate::driver::test_env::TestEnv;
+use crate::driver::test_env_api::{
+    get_ssh_session_from_env, HasTestEnv, IcNodeSnapshot, NodesInfo, SshSession,
+};
+use anyhow::{bail, Context, Result};
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use slog::{info, warn};
+use ssh2::Session;
+use std::io::{Read, Write};
+use std::net::IpAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The largest offset, in either direction, that may be set on the clock of a
+/// node.
+pub const MAX_CLOCK_OFFSET_SECS: i64 = 10 * 60;
+
+/// Number of SSH round trips `ClockSkew::clock_offset()` samples, of which the
+/// one with the lowest latency is used.
+const CLOCK_OFFSET_SAMPLES: usize = 5;
+
+/// Directory of the `TestEnv` holding a `ClockOffsetRecord` for each node whose
+/// clock is skewed, named after the node ID.
+const CLOCK_OFFSETS_DIR: &str = "clock_offsets";
+
+/// Disables the time synchronization via NTP. Nodes run either
+/// systemd-timesyncd or chrony, so both are tried.
+const DISABLE_NTP_SCRIPT: &str = r#"set -e
+disabled=0
+sudo timedatectl set-ntp false 2> /dev/null && disabled=1
+sudo systemctl stop chrony.service 2> /dev/null && disabled=1
+if [ "$disabled" != 1 ]; then
+  echo "no NTP service to disable" >&2
+  exit 1
+fi
+"#;
+
+const ENABLE_NTP_SCRIPT: &str = r#"
+sudo systemctl start chrony.service 2> /dev/null || true
+sudo timedatectl set-ntp true 2> /dev/null || true
+"#;
+
+/// The clock offset of a node, as recorded in the `TestEnv` to restore the
+/// clock after the test.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+struct ClockOffsetRecord {
+    ip: IpAddr,
+    offset_nanos: i64,
+}
+
+pub trait ClockSkew {
+    /// Disables NTP on the node and steps its clock such that it is `offset`
+    /// ahead of the true time (or behind, if `offset` is negative). Replaces
+    /// any offset set before.
+    ///
+    /// Fails if the absolute offset exceeds `MAX_CLOCK_OFFSET_SECS`, or if the
+    /// node was not deployed by the `TestEnv` of the node.
+    fn set_clock_offset(&self, offset: Duration) -> Result<()>;
+
+    /// Steps the clock of the node back to the true time and re-enables NTP.
+    /// Does nothing if no offset is set.
+    fn clear_clock_offset(&self) -> Result<()>;
+
+    /// Measures the offset of the node clock against the clock of the test
+    /// host. The SSH round trip with the lowest latency is used, assuming the
+    /// node read its clock halfway through it, such that the error is bounded
+    /// by half the round trip time.
+    fn clock_offset(&self) -> Result<Duration>;
+}
+
+impl ClockSkew for IcNodeSnapshot {
+    fn set_clock_offset(&self, offset: Duration) -> Result<()> {
+        let env = self.test_env();
+        check_clock_offset(offset)?;
+        ensure_deployed_by(&env, self)?;
+        let record_path = clock_offset_record_path(&self.node_id.to_string());
+        let session = self.block_on_ssh_session()?;
+        let current_offset_nanos = match env.read_json_object::<ClockOffsetRecord, _>(&record_path)
+        {
+            Ok(record) => record.offset_nanos,
+            Err(_) => {
+                self.block_on_bash_script_from_session(&session, DISABLE_NTP_SCRIPT)?;
+                0
+            }
+        };
+        // Recorded before stepping the clock, such that NTP is re-enabled even
+        // if stepping fails.
+        let ip = self.get_ip_addr();
+        env.write_json_object(
+            &record_path,
+            &ClockOffsetRecord {
+                ip,
+                offset_nanos: current_offset_nanos,
+            },
+        )?;
+        let offset_nanos = offset.num_nanoseconds().unwrap();
+        self.block_on_bash_script_from_session(
+            &session,
+            &step_clock_script(offset_nanos - current_offset_nanos),
+        )?;
+        env.write_json_object(&record_path, &ClockOffsetRecord { ip, offset_nanos })?;
+        info!(
+            env.logger(),
+            "Set clock offset of node {} to {}ms",
+            self.node_id,
+            offset.num_milliseconds()
+        );
+        Ok(())
+    }
+
+    fn clear_clock_offset(&self) -> Result<()> {
+        let env = self.test_env();
+        let record_path = clock_offset_record_path(&self.node_id.to_string());
+        let Ok(record) = env.read_json_object::<ClockOffsetRecord, _>(&record_path) else {
+            return Ok(());
+        };
+        let session = self.block_on_ssh_session()?;
+        self.block_on_bash_script_from_session(&session, &restore_clock_script(&record))?;
+        std::fs::remove_file(env.get_json_path(&record_path))?;
+        info!(
+            env.logger(),
+            "Cleared clock offset of node {}", self.node_id
+        );
+        Ok(())
+    }
+
+    fn clock_offset(&self) -> Result<Duration> {
+        let session = self.block_on_ssh_session()?;
+        let mut best: Option<(std::time::Duration, i128)> = None;
+        for _ in 0..CLOCK_OFFSET_SAMPLES {
+            let before = SystemTime::now();
+            let output = self.block_on_bash_script_from_session(&session, "date +%s%N")?;
+            let after = SystemTime::now();
+            let node_nanos: i128 = output
+                .trim()
+                .parse()
+                .with_context(|| format!("unexpected output of date: {output:?}"))?;
+            let round_trip = after.duration_since(before).unwrap_or_default();
+            let midpoint_nanos = before.duration_since(UNIX_EPOCH)?.as_nanos() as i128
+                + round_trip.as_nanos() as i128 / 2;
+            let offset_nanos = node_nanos - midpoint_nanos;
+            if best.map_or(true, |(best_round_trip, _)| round_trip < best_round_trip) {
+                best = Some((round_trip, offset_nanos));
+            }
+        }
+        let (_, offset_nanos) = best.unwrap();
+        Ok(Duration::nanoseconds(offset_nanos as i64))
+    }
+}
+
+/// Restores the clocks of all nodes skewed via `ClockSkew::set_clock_offset()`
+/// in `env` and re-enables NTP on them. Tries all nodes, even if restoring
+/// some of them fails.
+pub fn restore_clock_offsets(env: &TestEnv) -> Result<()> {
+    let dir = env.get_path(CLOCK_OFFSETS_DIR);
+    if !dir.exists() {
+        return Ok(());
+    }
+    let mut failed = vec![];
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let node_id = path.file_stem().unwrap().to_string_lossy().to_string();
+        let record_path = clock_offset_record_path(&node_id);
+        let result = env
+            .read_json_object::<ClockOffsetRecord, _>(&record_path)
+            .and_then(|record| {
+                let session = get_ssh_session_from_env(env, record.ip)?;
+                run_bash_script(&session, &restore_clock_script(&record))
+            })
+            .and_then(|_| Ok(std::fs::remove_file(&path)?));
+        match result {
+            Ok(()) => info!(env.logger(), "Restored the clock of node {node_id}"),
+            Err(e) => {
+                warn!(
+                    env.logger(),
+                    "Failed to restore the clock of node {node_id}: {e:?}"
+                );
+                failed.push(node_id);
+            }
+        }
+    }
+    if !failed.is_empty() {
+        bail!("Failed to restore the clocks of nodes {failed:?}");
+    }
+    Ok(())
+}
+
+fn check_clock_offset(offset: Duration) -> Result<()> {
+    if offset.num_seconds().abs() > MAX_CLOCK_OFFSET_SECS {
+        bail!(
+            "Clock offset of {}s exceeds the maximum of {}s",
+            offset.num_seconds(),
+            MAX_CLOCK_OFFSET_SECS
+        );
+    }
+    Ok(())
+}
+
+/// Refuses to skew nodes that were not deployed by `env`, e.g. nodes of an IC
+/// the test only talks to.
+fn ensure_deployed_by(env: &TestEnv, node: &IcNodeSnapshot) -> Result<()> {
+    let nodes_info: NodesInfo = env.read_versioned(NODES_INFO, NODES_INFO_SCHEMA_VERSION)?;
+    if !nodes_info.contains_key(&node.node_id) {
+        bail!(
+            "Refusing to skew the clock of node {} which was not deployed by this test",
+            node.node_id
+        );
+    }
+    Ok(())
+}
+
+fn clock_offset_record_path(node_id: &str) -> String {
+    format!("{CLOCK_OFFSETS_DIR}/{node_id}.json")
+}
+
+/// Renders a script that steps the clock by `delta_nanos`.
+fn step_clock_script(delta_nanos: i64) -> String {
+    format!(
+        r#"set -e
+now=$(date +%s%N)
+new=$((now + ({delta_nanos})))
+sudo date --set "@${{new:0:-9}}.${{new: -9}}" > /dev/null
+"#
+    )
+}
+
+/// Renders a script that steps the clock back by the recorded offset and
+/// re-enables NTP.
+fn restore_clock_script(record: &ClockOffsetRecord) -> String {
+    format!(
+        "{}{}",
+        step_clock_script(-record.offset_nanos),
+        ENABLE_NTP_SCRIPT
+    )
+}
+
+fn run_bash_script(session: &Session, script: &str) -> Result<String> {
+    let mut channel = session.channel_session()?;
+    channel.exec("bash")?;
+    channel.write_all(script.as_bytes())?;
+    channel.flush()?;
+    channel.send_eof()?;
+    let mut out = String::new();
+    channel.read_to_string(&mut out)?;
+    let exit_status = channel.exit_status()?;
+    if exit_status != 0 {
+        bail!("exit_status = {exit_status:?}. Output: {out}");
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::{o, Logger};
+
+    #[test]
+    fn offsets_beyond_the_maximum_are_rejected() {
+        assert!(check_clock_offset(Duration::seconds(30)).is_ok());
+        assert!(check_clock_offset(Duration::seconds(-MAX_CLOCK_OFFSET_SECS)).is_ok());
+        assert!(check_clock_offset(Duration::seconds(MAX_CLOCK_OFFSET_SECS + 1)).is_err());
+        assert!(check_clock_offset(Duration::seconds(-MAX_CLOCK_OFFSET_SECS - 1)).is_err());
+    }
+
+    #[test]
+    fn restore_script_steps_the_clock_back() {
+        let record = ClockOffsetRecord {
+            ip: "::1".parse().unwrap(),
+            offset_nanos: 30_000_000_000,
+        };
+        let script = restore_clock_script(&record);
+        assert!(script.contains("new=$((now + (-30000000000)))"));
+        assert!(script.contains("sudo timedatectl set-ntp true"));
+    }
+
+    #[test]
+    fn nothing_to_restore_without_skewed_nodes() {
+        let dir = tempfile::tempdir().unwrap();
+        let env =
+            TestEnv::new_without_duplicating_logger(dir.path(), Logger::root(slog::Discard, o!()));
+        restore_clock_offsets(&env).unwrap();
+    }
+}
@@ -1,2 +1,420 @@
+use crate::driver::constants::{DEFAULT_FARM_BASE_URL, GROUP_TTL, KEEPALIVE_INTERVAL};
+use crate::driver::ic::{AmountOfMemoryKiB, ImageSizeGiB, NrOfVCPUs, VmResources};
+use crate::driver::test_env::TestEnvAttribute;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use url::Url;
+
 // Constants used in the test-driver.
 pub const NODES_INFO: &str = "nodes_info.json";
+/// The schema version of the `NodesInfo` stored in `NODES_INFO`.
+pub const NODES_INFO_SCHEMA_VERSION: u32 = 1;
+
+/// Configuration of the task that keeps the Farm group of a test alive by
+/// periodically extending its TTL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FarmKeepaliveConfig {
+    /// How often the group's TTL is extended.
+    pub interval: Duration,
+    /// The TTL (counted from the time of the extension) set on every extension.
+    /// Must be larger than `interval`, or the group may expire in between.
+    pub group_ttl: Duration,
+    /// Stop extending the TTL after this long, letting the group expire. `None`
+    /// keeps the group alive for as long as the test runs.
+    pub max_lifetime: Option<Duration>,
+    /// Number of attempts per extension before giving up until the next
+    /// interval.
+    pub max_attempts: usize,
+    /// Pause between failed attempts.
+    pub retry_backoff: Duration,
+}
+
+impl Default for FarmKeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            interval: KEEPALIVE_INTERVAL,
+            group_ttl: GROUP_TTL,
+            max_lifetime: None,
+            max_attempts: 3,
+            retry_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Retry policy of the HTTP calls to Farm.
+///
+/// Connection errors and server errors (5xx) are retried with exponential
+/// backoff and jitter; client errors (4xx) are not retried.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FarmRetryConfig {
+    /// Number of attempts per call before giving up.
+    pub max_attempts: usize,
+    /// Pause after the first failed attempt, doubled after every further one.
+    pub initial_backoff: Duration,
+    /// Upper bound of the pause between attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for FarmRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Prefix of the environment variables overriding the driver configuration,
+/// e.g. `IC_TEST_DRIVER_FARM_BASE_URL`.
+pub const DRIVER_CONFIG_ENV_VAR_PREFIX: &str = "IC_TEST_DRIVER_";
+
+/// Environment variable holding the path of a driver config file. The file is
+/// only read if no config file is passed on the command line.
+pub const DRIVER_CONFIG_FILE_ENV_VAR: &str = "IC_TEST_DRIVER_CONFIG_FILE";
+
+/// Severity threshold of the log messages printed by the driver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Critical,
+    Error,
+    Warning,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "critical" => Ok(LogLevel::Critical),
+            "error" => Ok(LogLevel::Error),
+            "warning" | "warn" => Ok(LogLevel::Warning),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            _ => Err(format!("invalid log level {s:?}")),
+        }
+    }
+}
+
+impl From<LogLevel> for slog::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Critical => slog::Level::Critical,
+            LogLevel::Error => slog::Level::Error,
+            LogLevel::Warning => slog::Level::Warning,
+            LogLevel::Info => slog::Level::Info,
+            LogLevel::Debug => slog::Level::Debug,
+            LogLevel::Trace => slog::Level::Trace,
+        }
+    }
+}
+
+/// The effective configuration of the driver, resolved from (in increasing
+/// order of precedence) the defaults, a config file, `IC_TEST_DRIVER_*`
+/// environment variables and the command line. See `DriverConfigLayer`.
+///
+/// The effective configuration is written to the root environment, from which
+/// it is copied to the environments of the setup and all tests.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DriverConfig {
+    pub farm_base_url: Url,
+    /// Resources of the VMs that neither the VM itself nor its subnet specify.
+    /// Unset fields fall back to the built-in defaults.
+    pub default_vm_resources: VmResources,
+    pub log_level: LogLevel,
+    pub k8s: bool,
+}
+
+impl TestEnvAttribute for DriverConfig {
+    fn attribute_name() -> String {
+        "driver_config".to_string()
+    }
+}
+
+/// A partial driver configuration, as read from a single source. Layers are
+/// merged with `DriverConfigLayer::merge()` and resolved into a
+/// `DriverConfig` with `DriverConfigLayer::validate()`.
+///
+/// The values are kept unparsed where parsing can fail, such that `validate()`
+/// can report all errors at once.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DriverConfigLayer {
+    pub farm_base_url: Option<String>,
+    pub default_vcpus: Option<u64>,
+    pub default_memory_kibibytes: Option<u64>,
+    pub default_boot_image_minimal_size_gibibytes: Option<u64>,
+    pub log_level: Option<String>,
+    pub k8s: Option<bool>,
+    /// Errors that occurred while reading this layer, e.g. environment
+    /// variables whose values could not be parsed.
+    #[serde(skip)]
+    pub errors: Vec<String>,
+}
+
+impl DriverConfigLayer {
+    /// Reads a layer from a JSON config file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read driver config file {path:?}"))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse driver config file {path:?}"))
+    }
+
+    /// Reads a layer from the `IC_TEST_DRIVER_*` variables among `vars`, e.g.
+    /// `IC_TEST_DRIVER_DEFAULT_VCPUS` for `default_vcpus`. Variables with
+    /// invalid values or unknown names are recorded as errors of the layer.
+    pub fn from_env_vars(vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        let mut layer = Self::default();
+        for (name, value) in vars {
+            let Some(key) = name.strip_prefix(DRIVER_CONFIG_ENV_VAR_PREFIX) else {
+                continue;
+            };
+            match key {
+                "CONFIG_FILE" => {}
+                "FARM_BASE_URL" => layer.farm_base_url = Some(value),
+                "DEFAULT_VCPUS" => layer.default_vcpus = layer.parse_env_var(&name, &value),
+                "DEFAULT_MEMORY_KIBIBYTES" => {
+                    layer.default_memory_kibibytes = layer.parse_env_var(&name, &value)
+                }
+                "DEFAULT_BOOT_IMAGE_MINIMAL_SIZE_GIBIBYTES" => {
+                    layer.default_boot_image_minimal_size_gibibytes =
+                        layer.parse_env_var(&name, &value)
+                }
+                "LOG_LEVEL" => layer.log_level = Some(value),
+                "K8S" => layer.k8s = layer.parse_env_var(&name, &value),
+                _ => layer
+                    .errors
+                    .push(format!("unknown environment variable {name}")),
+            }
+        }
+        layer
+    }
+
+    fn parse_env_var<T: FromStr>(&mut self, name: &str, value: &str) -> Option<T>
+    where
+        T::Err: fmt::Display,
+    {
+        match value.parse() {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.errors
+                    .push(format!("invalid value {value:?} of {name}: {e}"));
+                None
+            }
+        }
+    }
+
+    /// Overrides the values of `self` with those set in `other`.
+    pub fn merge(self, other: DriverConfigLayer) -> Self {
+        Self {
+            farm_base_url: other.farm_base_url.or(self.farm_base_url),
+            default_vcpus: other.default_vcpus.or(self.default_vcpus),
+            default_memory_kibibytes: other
+                .default_memory_kibibytes
+                .or(self.default_memory_kibibytes),
+            default_boot_image_minimal_size_gibibytes: other
+                .default_boot_image_minimal_size_gibibytes
+                .or(self.default_boot_image_minimal_size_gibibytes),
+            log_level: other.log_level.or(self.log_level),
+            k8s: other.k8s.or(self.k8s),
+            errors: self.errors.into_iter().chain(other.errors).collect(),
+        }
+    }
+
+    /// Resolves the layer into a `DriverConfig`, filling in the defaults for
+    /// unset values. Returns all errors found, rather than just the first one.
+    pub fn validate(self) -> Result<DriverConfig, DriverConfigErrors> {
+        let mut errors = self.errors;
+
+        let farm_base_url = match self.farm_base_url.as_deref() {
+            Some(url) => Url::parse(url)
+                .map_err(|e| errors.push(format!("invalid Farm base URL {url:?}: {e}")))
+                .ok(),
+            None => Some(Url::parse(DEFAULT_FARM_BASE_URL).unwrap()),
+        };
+        let log_level = match self.log_level.as_deref() {
+            Some(level) => level.parse().map_err(|e| errors.push(e)).ok(),
+            None => Some(LogLevel::Info),
+        };
+        for (name, value) in [
+            ("default_vcpus", self.default_vcpus),
+            ("default_memory_kibibytes", self.default_memory_kibibytes),
+            (
+                "default_boot_image_minimal_size_gibibytes",
+                self.default_boot_image_minimal_size_gibibytes,
+            ),
+        ] {
+            if value == Some(0) {
+                errors.push(format!("{name} must not be zero"));
+            }
+        }
+        let k8s = self.k8s.unwrap_or(false);
+        if k8s && self.farm_base_url.is_some() {
+            errors.push("a Farm base URL is set, but k8s is used as infra provider".to_string());
+        }
+
+        match (farm_base_url, log_level) {
+            (Some(farm_base_url), Some(log_level)) if errors.is_empty() => Ok(DriverConfig {
+                farm_base_url,
+                default_vm_resources: VmResources {
+                    vcpus: self.default_vcpus.map(NrOfVCPUs::new),
+                    memory_kibibytes: self.default_memory_kibibytes.map(AmountOfMemoryKiB::new),
+                    boot_image_minimal_size_gibibytes: self
+                        .default_boot_image_minimal_size_gibibytes
+                        .map(ImageSizeGiB::new),
+                },
+                log_level,
+                k8s,
+            }),
+            _ => Err(DriverConfigErrors(errors)),
+        }
+    }
+}
+
+/// All errors found in a driver configuration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DriverConfigErrors(pub Vec<String>);
+
+impl fmt::Display for DriverConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid driver configuration:")?;
+        for error in self.0.iter() {
+            write!(f, "\n  - {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DriverConfigErrors {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn defaults_apply_if_nothing_is_set() {
+        let config = DriverConfigLayer::default().validate().unwrap();
+        assert_eq!(
+            config,
+            DriverConfig {
+                farm_base_url: Url::parse(DEFAULT_FARM_BASE_URL).unwrap(),
+                default_vm_resources: VmResources::default(),
+                log_level: LogLevel::Info,
+                k8s: false,
+            }
+        );
+    }
+
+    #[test]
+    fn later_layers_take_precedence() {
+        let file = DriverConfigLayer {
+            farm_base_url: Some("https://file.example.com/".to_string()),
+            default_vcpus: Some(2),
+            default_memory_kibibytes: Some(1024),
+            log_level: Some("debug".to_string()),
+            ..Default::default()
+        };
+        let env = DriverConfigLayer::from_env_vars(env_vars(&[
+            ("IC_TEST_DRIVER_FARM_BASE_URL", "https://env.example.com/"),
+            ("IC_TEST_DRIVER_DEFAULT_VCPUS", "4"),
+            ("UNRELATED_VAR", "1"),
+        ]));
+        let cli = DriverConfigLayer {
+            farm_base_url: Some("https://cli.example.com/".to_string()),
+            ..Default::default()
+        };
+
+        let config = DriverConfigLayer::default()
+            .merge(file)
+            .merge(env)
+            .merge(cli)
+            .validate()
+            .unwrap();
+
+        assert_eq!(config.farm_base_url.as_str(), "https://cli.example.com/");
+        assert_eq!(config.default_vm_resources.vcpus, Some(NrOfVCPUs::new(4)));
+        assert_eq!(
+            config.default_vm_resources.memory_kibibytes,
+            Some(AmountOfMemoryKiB::new(1024))
+        );
+        assert_eq!(
+            config
+                .default_vm_resources
+                .boot_image_minimal_size_gibibytes,
+            None
+        );
+        assert_eq!(config.log_level, LogLevel::Debug);
+    }
+
+    #[test]
+    fn config_file_is_parsed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("driver_config.json");
+        std::fs::write(&path, r#"{"log_level": "warn", "default_vcpus": 8}"#).unwrap();
+
+        let layer = DriverConfigLayer::from_file(&path).unwrap();
+
+        assert_eq!(layer.log_level.as_deref(), Some("warn"));
+        assert_eq!(layer.default_vcpus, Some(8));
+        assert_eq!(layer.validate().unwrap().log_level, LogLevel::Warning);
+    }
+
+    #[test]
+    fn config_file_with_unknown_fields_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("driver_config.json");
+        std::fs::write(&path, r#"{"farm_url": "https://farm.example.com/"}"#).unwrap();
+
+        assert!(DriverConfigLayer::from_file(&path).is_err());
+    }
+
+    #[test]
+    fn validation_reports_all_errors() {
+        let env = DriverConfigLayer::from_env_vars(env_vars(&[
+            ("IC_TEST_DRIVER_DEFAULT_MEMORY_KIBIBYTES", "lots"),
+            ("IC_TEST_DRIVER_FARM_BASEURL", "https://farm.example.com/"),
+        ]));
+        let cli = DriverConfigLayer {
+            farm_base_url: Some("not a url".to_string()),
+            default_vcpus: Some(0),
+            log_level: Some("loud".to_string()),
+            k8s: Some(true),
+            ..Default::default()
+        };
+
+        let errors = DriverConfigLayer::default()
+            .merge(env)
+            .merge(cli)
+            .validate()
+            .unwrap_err();
+
+        let expected = [
+            "invalid value \"lots\" of IC_TEST_DRIVER_DEFAULT_MEMORY_KIBIBYTES",
+            "unknown environment variable IC_TEST_DRIVER_FARM_BASEURL",
+            "invalid Farm base URL \"not a url\"",
+            "invalid log level \"loud\"",
+            "default_vcpus must not be zero",
+            "a Farm base URL is set, but k8s is used as infra provider",
+        ];
+        assert_eq!(errors.0.len(), expected.len(), "{errors}");
+        for (error, expected) in errors.0.iter().zip(expected) {
+            assert!(error.starts_with(expected), "{error:?} vs. {expected:?}");
+        }
+    }
+}
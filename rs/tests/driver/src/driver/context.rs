@@ -9,7 +9,7 @@ use std::{
     time::SystemTime,
 };
 
-use crate::driver::{constants, event::TaskId, subprocess_ipc::LogSender};
+use crate::driver::{constants, event::TaskId, group::TestFilter, subprocess_ipc::LogSender};
 
 use slog::debug;
 
@@ -17,13 +17,17 @@ use slog::debug;
 pub struct GroupContext {
     pub exec_path: PathBuf,
     pub group_dir: PathBuf,
-    pub filter_tests: Option<String>,
+    pub test_filter: TestFilter,
     logger: Logger,
     pub sock_id: u64,
     pub debug_keepalive: bool,
     pub no_farm_keepalive: bool,
     pub group_base_name: String,
     pub k8s: bool,
+    pub with_jumphost: bool,
+    /// Whether the setup environment was copied from a previous invocation,
+    /// in which case the setup function is not run.
+    pub reuse_env: bool,
 }
 
 impl GroupContext {
@@ -32,19 +36,25 @@ impl GroupContext {
     ///
     /// XXX: The task id technically should be part of the ProcessContext. However, we need it here
     /// to create the log channel in case we are in a subprocess.
+    ///
+    /// Log messages below `log_level` are dropped by the parent process, including those received
+    /// from subprocesses.
     pub fn new(
         group_dir: PathBuf,
         subproc_info: Option<(TaskId, u64)>,
-        filter_tests: Option<String>,
+        test_filter: TestFilter,
         debug_keepalive: bool,
         no_farm_keepalive: bool,
         group_base_name: String,
         k8s: bool,
+        with_jumphost: bool,
+        reuse_env: bool,
+        log_level: slog::Level,
     ) -> Result<Self> {
         let task_id = subproc_info.as_ref().map(|t| t.0.clone());
         let sock_id = subproc_info.map(|t| t.1).unwrap_or_default();
         let socket_path = Self::log_socket_path(sock_id);
-        let logger = Self::create_logger(socket_path, task_id)?;
+        let logger = Self::create_logger(socket_path, task_id, log_level)?;
 
         let exec_path = std::env::current_exe().expect("could not acquire parent process path");
         if !exec_path.is_file() {
@@ -57,13 +67,15 @@ impl GroupContext {
         Ok(Self {
             exec_path,
             group_dir,
-            filter_tests,
+            test_filter,
             logger,
             sock_id,
             debug_keepalive,
             no_farm_keepalive,
             group_base_name,
             k8s,
+            with_jumphost,
+            reuse_env,
         })
     }
 
@@ -108,8 +120,14 @@ impl GroupContext {
     /// ensuring that the directory actually exists and
     /// contains a copy of the configuration files from root_env
     fn ensure_setup_dir(&self) -> Result<PathBuf> {
+        self.ensure_named_setup_dir(constants::GROUP_SETUP_DIR)
+    }
+
+    /// Like [Self::ensure_setup_dir], but for the setup directory `setup_name`
+    /// (e.g. that of a test matrix cell).
+    fn ensure_named_setup_dir(&self, setup_name: &str) -> Result<PathBuf> {
         let root_env_path = self.group_dir.join(constants::ROOT_ENV_DIR);
-        let setup_path = self.group_dir.join(constants::GROUP_SETUP_DIR);
+        let setup_path = self.group_dir.join(setup_name);
         debug!(
             self.logger,
             "Ensuring directory {:?} exists ...", setup_path
@@ -119,7 +137,7 @@ impl GroupContext {
                 self.logger,
                 "Directory {:?} doesn't exist, creating ...", setup_path
             );
-            let setup_path_tmp = self.group_dir.join("setup_tmp");
+            let setup_path_tmp = self.group_dir.join(format!("{setup_name}_tmp"));
             debug!(
                 self.logger,
                 "Copying configuration from {:?} to {:?} ...", root_env_path, setup_path_tmp
@@ -138,7 +156,12 @@ impl GroupContext {
 
     /// Returns the path to the setup artifact directory, if it exists.
     fn get_setup_dir(&self) -> Option<PathBuf> {
-        let setup_path = self.group_dir.join(constants::GROUP_SETUP_DIR);
+        self.get_named_setup_dir(constants::GROUP_SETUP_DIR)
+    }
+
+    /// Returns the path to the setup directory `setup_name`, if it exists.
+    fn get_named_setup_dir(&self, setup_name: &str) -> Option<PathBuf> {
+        let setup_path = self.group_dir.join(setup_name);
         if setup_path.is_dir() {
             Some(setup_path)
         } else {
@@ -159,9 +182,22 @@ impl GroupContext {
         TestEnv::new(setup_dir, self.logger.clone())
     }
 
+    /// Returns the environment of the setup task `setup_name` of a test matrix
+    /// cell, creating it (as a copy of the root environment) if necessary.
+    pub fn ensure_named_setup_env(&self, setup_name: &str) -> Result<TestEnv> {
+        let setup_dir = self.ensure_named_setup_dir(setup_name)?;
+        TestEnv::new(setup_dir, self.logger.clone())
+    }
+
     pub fn create_test_env(&self, test_name: &str) -> Result<TestEnv> {
+        self.create_test_env_from_setup(constants::GROUP_SETUP_DIR, test_name)
+    }
+
+    /// Creates the environment of `test_name` by forking that of the setup task
+    /// `setup_name`.
+    pub fn create_test_env_from_setup(&self, setup_name: &str, test_name: &str) -> Result<TestEnv> {
         let target_dir = self.ensure_test_dir(test_name)?;
-        if let Some(setup_dir) = self.get_setup_dir() {
+        if let Some(setup_dir) = self.get_named_setup_dir(setup_name) {
             TestEnv::fork_from(
                 setup_dir.as_path(),
                 target_dir.as_path(),
@@ -184,14 +220,18 @@ impl GroupContext {
     }
 
     /// Create a logger for this process.
-    fn create_logger(sock_path: PathBuf, subproc_id: Option<TaskId>) -> Result<Logger> {
+    fn create_logger(
+        sock_path: PathBuf,
+        subproc_id: Option<TaskId>,
+        log_level: slog::Level,
+    ) -> Result<Logger> {
         if let Some(task_id) = subproc_id {
             let sender = LogSender::new(task_id, sock_path.clone())
                 .with_context(|| format!("Log socket path: {sock_path:?}"))?;
             let logger = Logger::root(sender, slog::o!());
             Ok(logger)
         } else {
-            let logger = crate::driver::logger::new_stdout_logger();
+            let logger = crate::driver::logger::new_stdout_logger(log_level);
             Ok(logger)
         }
     }
@@ -1,2 +1,671 @@
+//! Pre-flight check of the files a system test depends on.
+//!
+//! A group declares the files it needs from the runfiles, e.g. canister WASMs
+//! or the GuestOS image, with [`SystemTestGroup::with_dependencies`] and via
+//! [`SetupFragment::dependencies`]. Before allocating any resources, the
+//! driver resolves and stats all of them, and verifies the hashes of those
+//! declared with one. All problems are reported at once, such that a broken
+//! test target fails within seconds instead of deep into the setup.
+//!
+//! Tests access the declared files with [`TestEnv::require_dependency`].
+//!
+//! For local debugging, the setup environment of a previous invocation whose
+//! Farm group is still alive can be reused with `--reuse-env`, see
+//! [`reuse_setup_env`]. The driver then skips the setup and runs the selected
+//! tests against the system deployed back then.
+//!
+//! [`SystemTestGroup::with_dependencies`]: crate::driver::group::SystemTestGroup::with_dependencies
+//! [`SetupFragment::dependencies`]: crate::driver::setup_fragments::SetupFragment::dependencies
+use crate::driver::farm::{Farm, FarmError};
+use crate::driver::group::SetupResult;
+use crate::driver::test_env::{HasIcPrepDir, TestEnv, TestEnvAttribute};
+use crate::driver::test_env_api::FarmBaseUrl;
+use crate::driver::test_setup::{GroupSetup, InfraProvider};
+use ic_crypto_sha2::Sha256;
+use serde::{Deserialize, Serialize};
+use slog::{info, Logger};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
 pub const SSH_AUTHORIZED_PUB_KEYS_DIR: &str = "ssh/authorized_pub_keys";
 pub const SSH_AUTHORIZED_PRIV_KEYS_DIR: &str = "ssh/authorized_priv_keys";
+
+/// The kind of a file a system test depends on.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
+pub enum DependencyKind {
+    CanisterWasm,
+    GuestOsImage,
+    SetupOsImage,
+    ConfigTemplate,
+    /// Any other file.
+    File,
+}
+
+/// Where the path of a dependency, relative to the runfiles, comes from.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
+pub enum DependencySource {
+    Path(PathBuf),
+    /// The environment variable with the given name.
+    Env(String),
+}
+
+/// A file a system test depends on.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
+pub struct Dependency {
+    pub kind: DependencyKind,
+    pub source: DependencySource,
+    /// The expected SHA-256 hash of the file, hex-encoded.
+    pub sha256: Option<String>,
+}
+
+impl Dependency {
+    pub fn new<P: Into<PathBuf>>(kind: DependencyKind, path: P) -> Self {
+        Self {
+            kind,
+            source: DependencySource::Path(path.into()),
+            sha256: None,
+        }
+    }
+
+    /// A dependency whose path is given by the environment variable `var`.
+    pub fn from_env(kind: DependencyKind, var: &str) -> Self {
+        Self {
+            kind,
+            source: DependencySource::Env(var.to_string()),
+            sha256: None,
+        }
+    }
+
+    pub fn canister_wasm<P: Into<PathBuf>>(path: P) -> Self {
+        Self::new(DependencyKind::CanisterWasm, path)
+    }
+
+    pub fn file<P: Into<PathBuf>>(path: P) -> Self {
+        Self::new(DependencyKind::File, path)
+    }
+
+    pub fn with_sha256(mut self, sha256: &str) -> Self {
+        self.sha256 = Some(sha256.to_lowercase());
+        self
+    }
+
+    /// Resolves the path of the dependency in `runfiles`, without checking
+    /// that it exists.
+    fn resolve(&self, runfiles: &Path) -> Result<PathBuf, DependencyProblem> {
+        match &self.source {
+            DependencySource::Path(path) => Ok(runfiles.join(path)),
+            DependencySource::Env(var) => std::env::var(var)
+                .map(|path| runfiles.join(path))
+                .map_err(|_| DependencyProblem::EnvVarNotSet {
+                    dependency: self.clone(),
+                    var: var.clone(),
+                }),
+        }
+    }
+}
+
+impl fmt::Display for DependencySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencySource::Path(path) => write!(f, "{}", path.display()),
+            DependencySource::Env(var) => write!(f, "${var}"),
+        }
+    }
+}
+
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            DependencySource::Path(path) => write!(f, "{:?} {}", self.kind, path.display()),
+            DependencySource::Env(var) => write!(f, "{:?} from ${}", self.kind, var),
+        }
+    }
+}
+
+/// The dependencies declared by a group, without duplicates.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DeclaredDependencies(BTreeSet<Dependency>);
+
+impl DeclaredDependencies {
+    pub fn add(&mut self, dependency: Dependency) {
+        self.0.insert(dependency);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Dependency> {
+        self.0.iter()
+    }
+
+    /// Resolves all dependencies in `runfiles`, checks that they exist and
+    /// verifies their hashes, if declared. Returns every problem found.
+    pub fn verify(&self, runfiles: &Path) -> Result<ResolvedDependencies, HermeticityReport> {
+        let mut resolved = BTreeMap::new();
+        let mut problems = vec![];
+        for dependency in self.0.iter() {
+            match verify_dependency(dependency, runfiles) {
+                Ok(path) => {
+                    resolved.insert(dependency.source.clone(), path);
+                }
+                Err(problem) => problems.push(problem),
+            }
+        }
+        if problems.is_empty() {
+            Ok(ResolvedDependencies(resolved.into_iter().collect()))
+        } else {
+            Err(HermeticityReport { problems })
+        }
+    }
+}
+
+impl<I: IntoIterator<Item = Dependency>> From<I> for DeclaredDependencies {
+    fn from(dependencies: I) -> Self {
+        Self(dependencies.into_iter().collect())
+    }
+}
+
+fn verify_dependency(
+    dependency: &Dependency,
+    runfiles: &Path,
+) -> Result<PathBuf, DependencyProblem> {
+    let path = dependency.resolve(runfiles)?;
+    let problem = |reason: String| DependencyProblem::Unusable {
+        dependency: dependency.clone(),
+        path: path.clone(),
+        reason,
+    };
+    let metadata = std::fs::metadata(&path).map_err(|e| problem(e.to_string()))?;
+    if !metadata.is_file() {
+        return Err(problem("not a file".to_string()));
+    }
+    if let Some(expected) = &dependency.sha256 {
+        let actual = sha256_of_file(&path).map_err(|e| problem(e.to_string()))?;
+        if &actual != expected {
+            return Err(DependencyProblem::HashMismatch {
+                dependency: dependency.clone(),
+                path,
+                expected: expected.clone(),
+                actual,
+            });
+        }
+    }
+    Ok(path)
+}
+
+pub(crate) fn sha256_of_file(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 1 << 20];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.write(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finish()))
+}
+
+/// A problem with a declared dependency.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DependencyProblem {
+    EnvVarNotSet {
+        dependency: Dependency,
+        var: String,
+    },
+    /// The file is missing or cannot be read.
+    Unusable {
+        dependency: Dependency,
+        path: PathBuf,
+        reason: String,
+    },
+    HashMismatch {
+        dependency: Dependency,
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl fmt::Display for DependencyProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EnvVarNotSet { dependency, var } => {
+                write!(f, "{dependency}: environment variable {var} is not set")
+            }
+            Self::Unusable {
+                dependency,
+                path,
+                reason,
+            } => write!(f, "{dependency}: {}: {reason}", path.display()),
+            Self::HashMismatch {
+                dependency,
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{dependency}: {}: expected sha256 {expected}, got {actual}",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// All problems found by [`DeclaredDependencies::verify`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HermeticityReport {
+    pub problems: Vec<DependencyProblem>,
+}
+
+impl fmt::Display for HermeticityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} declared dependencies are unusable:",
+            self.problems.len()
+        )?;
+        for problem in self.problems.iter() {
+            write!(f, "\n  {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The paths of the verified dependencies, recorded in the root environment
+/// of the group for [`TestEnv::require_dependency`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ResolvedDependencies(Vec<(DependencySource, PathBuf)>);
+
+impl TestEnvAttribute for ResolvedDependencies {
+    fn attribute_name() -> String {
+        "resolved_dependencies".to_string()
+    }
+}
+
+impl ResolvedDependencies {
+    pub fn get(&self, dependency: &Dependency) -> Option<&PathBuf> {
+        self.0
+            .iter()
+            .find(|(source, _)| source == &dependency.source)
+            .map(|(_, path)| path)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(DependencySource, PathBuf)> {
+        self.0.iter()
+    }
+}
+
+impl TestEnv {
+    /// Returns the path of `dependency`, which must have been declared by the
+    /// group, see the module documentation.
+    ///
+    /// # Panics
+    ///
+    /// If `dependency` was not declared.
+    pub fn require_dependency(&self, dependency: &Dependency) -> PathBuf {
+        ResolvedDependencies::try_read_attribute(self)
+            .unwrap_or_default()
+            .get(dependency)
+            .cloned()
+            .unwrap_or_else(|| {
+                panic!("Dependency {dependency} was not declared by the SystemTestGroup")
+            })
+    }
+}
+
+/// Why the setup environment of a previous invocation cannot be reused.
+#[derive(Debug, Error)]
+pub enum ReuseEnvError {
+    #[error("{path:?} is not a directory")]
+    NotADirectory { path: PathBuf },
+
+    #[error("{path:?} is not the environment of a group set up on Farm")]
+    NotOnFarm { path: PathBuf },
+
+    #[error("the setup of {path:?} did not succeed")]
+    SetupNotSucceeded { path: PathBuf },
+
+    #[error("{path:?} does not contain the topology of an Internet Computer")]
+    NoTopology { path: PathBuf },
+
+    #[error("Farm group {group_name} has expired or was deleted")]
+    GroupNotAlive { group_name: String },
+
+    #[error("the setup directory {path:?} already exists")]
+    SetupDirExists { path: PathBuf },
+
+    #[error("failed to check the Farm group {group_name}: {source}")]
+    Farm {
+        group_name: String,
+        source: FarmError,
+    },
+
+    #[error("failed to copy the environment: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Verifies that `env`, the setup environment of a previous invocation, can be
+/// reused: its setup succeeded on Farm and its group is still alive on `farm`.
+/// Returns the group of `env`.
+pub fn verify_reusable_env(env: &TestEnv, farm: &Farm) -> Result<GroupSetup, ReuseEnvError> {
+    let path = env.base_path();
+    if !path.is_dir() {
+        return Err(ReuseEnvError::NotADirectory { path });
+    }
+    let group_setup = match (
+        InfraProvider::try_read_attribute(env),
+        GroupSetup::try_read_attribute(env),
+    ) {
+        (Ok(InfraProvider::Farm), Ok(group_setup)) => group_setup,
+        _ => return Err(ReuseEnvError::NotOnFarm { path }),
+    };
+    if SetupResult::try_read_attribute(env).is_err() {
+        return Err(ReuseEnvError::SetupNotSucceeded { path });
+    }
+    let group_name = group_setup.infra_group_name.clone();
+    match farm.group_exists(&group_name) {
+        Ok(true) => Ok(group_setup),
+        Ok(false) => Err(ReuseEnvError::GroupNotAlive { group_name }),
+        Err(source) => Err(ReuseEnvError::Farm { group_name, source }),
+    }
+}
+
+/// Copies the reusable environment `env` to `setup_dir`, the setup directory
+/// of this invocation, such that the tests find the topology and the deployed
+/// VMs of the previous invocation there.
+pub fn rehydrate_setup_env(
+    env: &TestEnv,
+    setup_dir: &Path,
+    logger: Logger,
+) -> Result<TestEnv, ReuseEnvError> {
+    if env.prep_dir("").is_none() {
+        return Err(ReuseEnvError::NoTopology {
+            path: env.base_path(),
+        });
+    }
+    if setup_dir.exists() {
+        return Err(ReuseEnvError::SetupDirExists {
+            path: setup_dir.to_path_buf(),
+        });
+    }
+    TestEnv::fork_from(env.base_path().as_path(), setup_dir, logger)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("{e:#}")).into())
+}
+
+/// Verifies the setup environment at `reused_env_dir` with Farm and copies it
+/// to `setup_dir`, see [`verify_reusable_env`] and [`rehydrate_setup_env`].
+pub fn reuse_setup_env(
+    reused_env_dir: &Path,
+    setup_dir: &Path,
+    logger: Logger,
+) -> Result<TestEnv, ReuseEnvError> {
+    if !reused_env_dir.is_dir() {
+        return Err(ReuseEnvError::NotADirectory {
+            path: reused_env_dir.to_path_buf(),
+        });
+    }
+    let env = TestEnv::new_without_duplicating_logger(reused_env_dir, logger.clone());
+    let farm_base_url =
+        FarmBaseUrl::try_read_attribute(&env).map_err(|_| ReuseEnvError::NotOnFarm {
+            path: reused_env_dir.to_path_buf(),
+        })?;
+    let farm = Farm::new(farm_base_url.into(), logger.clone());
+    let group_setup = verify_reusable_env(&env, &farm)?;
+    info!(
+        logger,
+        "Reusing Farm group {} set up in {:?}", group_setup.infra_group_name, reused_env_dir
+    );
+    rehydrate_setup_env(&env, setup_dir, logger)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::setup_fragments::{SetupCapability, SetupFragment, SetupFragments};
+    use slog::{o, Logger};
+
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn runfiles_with(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    struct FragmentWithDependencies(&'static str, Vec<Dependency>);
+
+    impl SetupFragment for FragmentWithDependencies {
+        fn name(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn capabilities(&self) -> Vec<SetupCapability> {
+            vec![]
+        }
+
+        fn dependencies(&self) -> Vec<Dependency> {
+            self.1.clone()
+        }
+    }
+
+    #[test]
+    fn declarations_are_aggregated_without_duplicates() {
+        let fragments = SetupFragments::new()
+            .with(FragmentWithDependencies(
+                "nns",
+                vec![
+                    Dependency::canister_wasm("nns/registry.wasm.gz"),
+                    Dependency::new(DependencyKind::GuestOsImage, "guestos/disk-img.tar.zst"),
+                ],
+            ))
+            .with(FragmentWithDependencies(
+                "app",
+                vec![
+                    Dependency::canister_wasm("app/counter.wasm"),
+                    Dependency::new(DependencyKind::GuestOsImage, "guestos/disk-img.tar.zst"),
+                ],
+            ));
+        let mut declared = DeclaredDependencies::default();
+        for dependency in fragments.dependencies() {
+            declared.add(dependency);
+        }
+        declared.add(Dependency::canister_wasm("app/counter.wasm"));
+
+        assert_eq!(
+            declared,
+            DeclaredDependencies::from([
+                Dependency::canister_wasm("app/counter.wasm"),
+                Dependency::canister_wasm("nns/registry.wasm.gz"),
+                Dependency::new(DependencyKind::GuestOsImage, "guestos/disk-img.tar.zst"),
+            ])
+        );
+    }
+
+    #[test]
+    fn all_missing_files_are_reported() {
+        let runfiles = runfiles_with(&[("present.wasm", "wasm")]);
+        let declared = DeclaredDependencies::from([
+            Dependency::canister_wasm("present.wasm"),
+            Dependency::canister_wasm("missing.wasm"),
+            Dependency::new(DependencyKind::ConfigTemplate, "templates/missing.toml"),
+            Dependency::from_env(DependencyKind::File, "DRIVER_SETUP_TEST_UNSET_VAR"),
+        ]);
+
+        let report = declared.verify(runfiles.path()).unwrap_err();
+
+        assert_eq!(report.problems.len(), 3, "{report}");
+        let report = report.to_string();
+        assert!(report.starts_with("3 declared dependencies are unusable:"));
+        assert!(report.contains("CanisterWasm missing.wasm"), "{report}");
+        assert!(
+            report.contains("ConfigTemplate templates/missing.toml"),
+            "{report}"
+        );
+        assert!(
+            report.contains("DRIVER_SETUP_TEST_UNSET_VAR is not set"),
+            "{report}"
+        );
+        assert!(!report.contains("present.wasm"), "{report}");
+    }
+
+    #[test]
+    fn hashes_are_verified() {
+        let runfiles = runfiles_with(&[("hello", "hello"), ("other", "other")]);
+        let declared = DeclaredDependencies::from([
+            Dependency::file("hello").with_sha256(&HELLO_SHA256.to_uppercase()),
+            Dependency::file("other").with_sha256(HELLO_SHA256),
+        ]);
+
+        let report = declared.verify(runfiles.path()).unwrap_err();
+
+        assert_eq!(report.problems.len(), 1);
+        assert!(matches!(
+            &report.problems[0],
+            DependencyProblem::HashMismatch { dependency, expected, .. }
+                if dependency == &Dependency::file("other").with_sha256(HELLO_SHA256)
+                    && expected == HELLO_SHA256
+        ));
+    }
+
+    #[test]
+    fn only_declared_dependencies_can_be_required() {
+        let runfiles = runfiles_with(&[("counter.wasm", "wasm")]);
+        let declared = Dependency::canister_wasm("counter.wasm");
+        let resolved = DeclaredDependencies::from([declared.clone()])
+            .verify(runfiles.path())
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let env =
+            TestEnv::new_without_duplicating_logger(dir.path(), Logger::root(slog::Discard, o!()));
+        resolved.write_attribute(&env);
+
+        assert_eq!(
+            env.require_dependency(&declared),
+            runfiles.path().join("counter.wasm")
+        );
+        let undeclared = Dependency::canister_wasm("other.wasm");
+        assert!(std::panic::catch_unwind(|| env.require_dependency(&undeclared)).is_err());
+    }
+
+    fn env_in(dir: &Path) -> TestEnv {
+        TestEnv::new_without_duplicating_logger(dir, Logger::root(slog::Discard, o!()))
+    }
+
+    fn reusable_env(dir: &Path, group_name: &str) -> TestEnv {
+        let env = env_in(dir);
+        InfraProvider::Farm.write_attribute(&env);
+        GroupSetup {
+            group_base_name: "group".to_string(),
+            infra_group_name: group_name.to_string(),
+            ..Default::default()
+        }
+        .write_attribute(&env);
+        SetupResult {}.write_attribute(&env);
+        env
+    }
+
+    fn farm(server: &mockito::Server) -> Farm {
+        let base_url = url::Url::parse(&format!("{}/", server.url())).unwrap();
+        Farm::new(base_url, Logger::root(slog::Discard, o!()))
+    }
+
+    #[test]
+    fn topology_is_rehydrated_from_an_existing_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = reusable_env(&dir.path().join("old"), "group-1234");
+        let prep_dir = env.create_prep_dir("").unwrap();
+        std::fs::write(prep_dir.prep_dir.join("registry.proto"), "registry").unwrap();
+
+        let setup_dir = dir.path().join("setup");
+        let setup_env =
+            rehydrate_setup_env(&env, &setup_dir, Logger::root(slog::Discard, o!())).unwrap();
+
+        assert_eq!(setup_env.base_path(), setup_dir);
+        assert_eq!(
+            GroupSetup::read_attribute(&setup_env).infra_group_name,
+            "group-1234"
+        );
+        let rehydrated_prep_dir = setup_env.prep_dir("").expect("topology was not copied");
+        assert_eq!(
+            std::fs::read_to_string(rehydrated_prep_dir.prep_dir.join("registry.proto")).unwrap(),
+            "registry"
+        );
+        // The environment is not rehydrated over an existing setup.
+        assert!(matches!(
+            rehydrate_setup_env(&env, &setup_dir, Logger::root(slog::Discard, o!())),
+            Err(ReuseEnvError::SetupDirExists { .. })
+        ));
+    }
+
+    #[test]
+    fn env_without_topology_cannot_be_rehydrated() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = reusable_env(&dir.path().join("old"), "group-1234");
+
+        assert!(matches!(
+            rehydrate_setup_env(
+                &env,
+                &dir.path().join("setup"),
+                Logger::root(slog::Discard, o!())
+            ),
+            Err(ReuseEnvError::NoTopology { .. })
+        ));
+    }
+
+    #[test]
+    fn env_with_live_group_is_reusable() {
+        let mut server = mockito::Server::new();
+        let _live = server
+            .mock("GET", "/group/group-1234")
+            .with_status(200)
+            .with_body("{}")
+            .create();
+        let dir = tempfile::tempdir().unwrap();
+        let env = reusable_env(dir.path(), "group-1234");
+
+        let group_setup = verify_reusable_env(&env, &farm(&server)).unwrap();
+
+        assert_eq!(group_setup.infra_group_name, "group-1234");
+    }
+
+    #[test]
+    fn env_with_expired_group_is_rejected() {
+        let mut server = mockito::Server::new();
+        let _expired = server
+            .mock("GET", "/group/group-1234")
+            .with_status(404)
+            .with_body("no such group")
+            .create();
+        let dir = tempfile::tempdir().unwrap();
+        let env = reusable_env(dir.path(), "group-1234");
+
+        assert!(matches!(
+            verify_reusable_env(&env, &farm(&server)),
+            Err(ReuseEnvError::GroupNotAlive { group_name }) if group_name == "group-1234"
+        ));
+    }
+
+    #[test]
+    fn env_without_successful_setup_is_rejected() {
+        let server = mockito::Server::new();
+        let dir = tempfile::tempdir().unwrap();
+        let env = reusable_env(dir.path(), "group-1234");
+        std::fs::remove_file(env.get_json_path(SetupResult::attribute_name())).unwrap();
+
+        assert!(matches!(
+            verify_reusable_env(&env, &farm(&server)),
+            Err(ReuseEnvError::SetupNotSucceeded { .. })
+        ));
+    }
+}
@@ -5,6 +5,7 @@ use std::{
     collections::BTreeMap,
     panic::UnwindSafe,
     path::{Path},
+    time::Duration,
 };
 
 use crate::driver::{
@@ -28,6 +29,8 @@ pub struct SystemTestGroup {
 pub struct TestFunction {
     name: String,
     f: Box<dyn SysTestFn>,
+    timeout: Option<Duration>,
+    serial: bool,
 }
 
 impl TestFunction {
@@ -35,13 +38,39 @@ impl TestFunction {
         Self {
             name: name.to_string(),
             f: Box::new(f),
+            timeout: None,
+            serial: false,
         }
     }
 
+    /// Overrides the group's timeout per test for this test only. The test is
+    /// cancelled and reported as timed out once `timeout` has elapsed. The
+    /// overall timeout of the group, if any, still applies.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Runs this test alone, even in a group with parallelism: it starts once
+    /// all tests added before it have finished, and the tests added after it
+    /// start once it has finished.
+    pub fn serial(mut self) -> Self {
+        self.serial = true;
+        self
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    pub fn is_serial(&self) -> bool {
+        self.serial
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
     pub fn f(self) -> Box<dyn SysTestFn> {
         self.f
     }
@@ -2,24 +2,29 @@ use std::{
     collections::HashMap,
     net::{Ipv4Addr, Ipv6Addr},
     path::{Path, PathBuf},
+    sync::{Arc, Condvar, Mutex},
+    thread::JoinHandle,
     time::{Duration, Instant},
 };
 
-use crate::driver::ic::{AmountOfMemoryKiB, NrOfVCPUs, VmAllocationStrategy};
+use crate::driver::config::{FarmKeepaliveConfig, FarmRetryConfig};
+use crate::driver::ic::{AmountOfMemoryKiB, NrOfVCPUs, VmAllocationStrategy, VmClass, VmTopology};
 use crate::driver::log_events;
 use crate::driver::test_env::{RequiredHostFeaturesFromCmdLine, TestEnvAttribute};
 use crate::driver::test_env_api::{read_dependency_to_string, HasIcDependencies};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use ic_crypto_sha2::Sha256;
+use rand::Rng;
 use reqwest::blocking::{multipart, Client, RequestBuilder};
 use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use slog::{error, warn, Logger};
+use slog::{debug, error, info, warn, Logger};
 use std::fmt;
 use std::io::Write;
 use thiserror::Error;
 use url::Url;
+use uuid::Uuid;
 
 use crate::driver::{ic::ImageSizeGiB, test_env::TestEnv};
 
@@ -30,15 +35,18 @@ const TIMEOUT_SETTINGS_LONG: TimeoutSettings = TimeoutSettings {
     retry_timeout: Duration::from_secs(500),
     min_http_timeout: Duration::from_secs(20),
     max_http_timeout: Duration::from_secs(400),
-    linear_backoff: Duration::from_secs(10),
 };
 
 const TIMEOUT_SETTINGS: TimeoutSettings = TimeoutSettings {
     retry_timeout: Duration::from_secs(120),
     min_http_timeout: Duration::from_secs(5),
     max_http_timeout: Duration::from_secs(60),
-    linear_backoff: Duration::from_secs(5),
 };
+
+/// Header carrying a unique id of every attempt of a request, to correlate
+/// failures with the logs of Farm.
+const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
 // Be mindful when modifying these constants, as the events can be consumed by other parties.
 const FARM_VM_CREATED_EVENT_NAME: &str = "farm_vm_created_event";
 const VM_CONSOLE_LINK_CREATED_EVENT_NAME: &str = "vm_console_link_created_event";
@@ -52,6 +60,7 @@ pub struct Farm {
     pub logger: Logger,
     client: Client,
     pub override_host_features: Option<Vec<HostFeature>>,
+    retry_config: FarmRetryConfig,
 }
 
 impl Farm {
@@ -65,6 +74,7 @@ impl Farm {
             logger,
             client,
             override_host_features: None,
+            retry_config: FarmRetryConfig::default(),
         }
     }
 
@@ -78,9 +88,15 @@ impl Farm {
             logger: env.logger(),
             client,
             override_host_features: env.read_host_features(context),
+            retry_config: FarmRetryConfig::default(),
         }
     }
 
+    pub fn with_retry_config(mut self, retry_config: FarmRetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
     pub fn acquire_playnet_certificate(&self, group_name: &str) -> FarmResult<PlaynetCertificate> {
         let path = format!("group/{}/playnet/certificate", group_name);
         let rb = self.post(&path);
@@ -101,14 +117,29 @@ impl Farm {
             .override_host_features
             .clone()
             .unwrap_or_else(|| spec.required_host_features.clone());
-        let path = format!("group/{}", group_name);
         let ttl = ttl.map(|ttl| ttl.as_secs() as u32);
         let spec = spec.add_meta(group_base_name);
-        let body = CreateGroupRequest { ttl, spec };
-        let rb = Self::json(self.post(&path), &body);
+        self.create_group_(group_name, &CreateGroupRequest { ttl, spec })
+    }
+
+    fn create_group_(&self, group_name: &str, body: &CreateGroupRequest) -> FarmResult<()> {
+        let path = format!("group/{}", group_name);
+        let rb = Self::json(self.post(&path), body);
         let rbb = || rb.try_clone().expect("could not clone a request builder");
-        let _resp = self.retry_until_success(rbb)?;
-        Ok(())
+        match self.retry_until_success(rbb) {
+            Ok(_resp) => Ok(()),
+            // An attempt whose response got lost may have created the group.
+            Err(FarmError::AlreadyExists { message }) => {
+                warn!(
+                    self.logger,
+                    "Group {} already exists, assuming it was created by an earlier attempt: {}",
+                    group_name,
+                    message
+                );
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
     }
 
     /// creates a vm under the group `group_name` and returns the associated
@@ -125,7 +156,25 @@ impl Farm {
         let path = format!("group/{}/vm/{}", group_name, &vm.name);
         let rb = Self::json(self.post(&path), &vm);
         let rbb = || rb.try_clone().expect("could not clone a request builder");
-        let resp = self.retry_until_success_long(rbb)?;
+        let resp = self
+            .retry_until_success_long(rbb)
+            .map_err(|err| match err {
+                // Farm rejects placements and topologies it cannot satisfy.
+                FarmError::BadRequest { message } if !vm.vm_class.is_standard() => {
+                    FarmError::PerformancePlacementDenied {
+                        vm_name: vm.name.clone(),
+                        message,
+                    }
+                }
+                FarmError::BadRequest { message } if !vm.topology.is_unspecified() => {
+                    FarmError::VmTopologyRejected {
+                        vm_name: vm.name.clone(),
+                        topology: vm.topology,
+                        message,
+                    }
+                }
+                err => err,
+            })?;
         let created_vm = resp.json::<VMCreateResponse>()?;
         // Emit a json log event, to be consumed by log post-processing tools.
         let ipv6 = created_vm.ipv6;
@@ -212,11 +261,16 @@ impl Farm {
         let rb = self.put(&path);
         let rbb = || rb.try_clone().expect("could not clone a request builder");
         let _resp = self.retry_until_success(rbb)?;
-        let url = self.url_from_path(&format!("group/{}/vm/{}/console/", group_name, vm_name)[..]);
+        let url = self.vm_console_url(group_name, vm_name);
         emit_vm_console_link_event(&self.logger, url, vm_name);
         Ok(())
     }
 
+    /// The URL of the VNC and serial console of a VM in the browser.
+    pub fn vm_console_url(&self, group_name: &str, vm_name: &str) -> Url {
+        self.url_from_path(&format!("group/{}/vm/{}/console/", group_name, vm_name)[..])
+    }
+
     pub fn destroy_vm(&self, group_name: &str, vm_name: &str) -> FarmResult<()> {
         let path = format!("group/{}/vm/{}/destroy", group_name, vm_name);
         let rb = self.put(&path);
@@ -298,6 +352,47 @@ impl Farm {
         Ok(())
     }
 
+    /// Returns whether the given group is alive, i.e., has been neither deleted
+    /// nor garbage collected after its TTL expired.
+    pub fn group_exists(&self, group_name: &str) -> FarmResult<bool> {
+        let path = format!("group/{}", group_name);
+        let rb = self.get(&path);
+        let rbb = || rb.try_clone().expect("could not clone a request builder");
+        match self.retry_until_success(rbb) {
+            Ok(_) => Ok(true),
+            Err(FarmError::NotFound { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Extends the TTL of the given group to `duration` from now.
+    ///
+    /// Unlike `set_group_ttl()`, makes a single, short attempt and leaves any
+    /// retries to the caller (see `GroupKeepalive`).
+    pub fn extend_group_ttl(&self, group_name: &str, duration: Duration) -> FarmResult<()> {
+        let path = format!("group/{}/ttl/{}", group_name, duration.as_secs());
+        let resp = self
+            .put(&path)
+            .timeout(TIMEOUT_SETTINGS.min_http_timeout)
+            .send()?;
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let body = resp.text().unwrap_or_default();
+        if status.as_u16() == 404 {
+            return Err(FarmError::NotFound { message: body });
+        }
+        Err(FarmError::InvalidResponse {
+            message: format!("{}: {}", status, body),
+        })
+    }
+
+    fn get(&self, path: &str) -> RequestBuilder {
+        let url = self.url_from_path(path);
+        self.client.get(url)
+    }
+
     fn post(&self, path: &str) -> RequestBuilder {
         let url = self.url_from_path(path);
         self.client.post(url)
@@ -343,6 +438,10 @@ impl Farm {
         self.retry_until_success_(rbb, TIMEOUT_SETTINGS)
     }
 
+    /// Sends the request built by `rbb` until it succeeds, as configured by
+    /// the `retry_config` of this client and bounded by `t_settings`.
+    ///
+    /// Client errors (4xx) are returned right away, as retrying won't help.
     fn retry_until_success_<F: Fn() -> RequestBuilder>(
         &self,
         rbb: F,
@@ -350,36 +449,67 @@ impl Farm {
     ) -> FarmResult<reqwest::blocking::Response> {
         let started_at = Instant::now();
         let mut req_sent_successfully = false;
-        loop {
-            let mut req = rbb();
+        for attempt in 1..=self.retry_config.max_attempts {
             let http_timeout = match t_settings.retry_timeout.checked_sub(started_at.elapsed()) {
                 Some(t) if t > t_settings.min_http_timeout => t.min(t_settings.max_http_timeout),
                 _ => break,
             };
             // cond: MIN_HTTP_REQ_TIMEOUT < http_timeout <= MAX_HTTP_REQ_TIMEOUT
-            req = req.timeout(http_timeout);
+            let request_id = Uuid::new_v4().to_string();
+            let req = rbb()
+                .timeout(http_timeout)
+                .header(REQUEST_ID_HEADER, &request_id);
             match req.send() {
                 Err(e) => {
                     req_sent_successfully = false;
-                    error!(self.logger, "sending a request to Farm failed: {:?}", e);
+                    error!(
+                        self.logger,
+                        "sending request {} to Farm failed: {:?}", request_id, e
+                    );
                 }
                 Ok(r) => {
                     req_sent_successfully = true;
-                    if r.status().is_success() {
+                    let status = r.status();
+                    if status.is_success() {
                         return Ok(r);
                     };
-                    if r.status().as_u16() == 404 {
+                    if status.is_client_error() {
                         let body = r.text().unwrap_or_default();
-                        return Err(FarmError::NotFound { message: body });
+                        debug!(
+                            self.logger,
+                            "Farm rejected request {}: {}: {}", request_id, status, body
+                        );
+                        return Err(match status.as_u16() {
+                            400 => FarmError::BadRequest { message: body },
+                            404 => FarmError::NotFound { message: body },
+                            409 => FarmError::AlreadyExists { message: body },
+                            _ => FarmError::InvalidResponse {
+                                message: format!("{}: {}", status, body),
+                            },
+                        });
                     }
-                    if r.status().is_server_error() {
-                        error!(self.logger, "unexpected response from Farm: {:?}", r.text());
+                    if status.is_server_error() {
+                        error!(
+                            self.logger,
+                            "unexpected response from Farm to request {}: {}: {:?}",
+                            request_id,
+                            status,
+                            r.text()
+                        );
                     } else {
-                        warn!(self.logger, "unexpected response from Farm: {:?}", r.text());
+                        warn!(
+                            self.logger,
+                            "unexpected response from Farm to request {}: {}: {:?}",
+                            request_id,
+                            status,
+                            r.text()
+                        );
                     }
                 }
             }
-            std::thread::sleep(t_settings.linear_backoff);
+            if attempt < self.retry_config.max_attempts {
+                std::thread::sleep(backoff(&self.retry_config, attempt));
+            }
         }
         Err(FarmError::TooManyRetries {
             message: String::from(if req_sent_successfully {
@@ -391,6 +521,145 @@ impl Farm {
     }
 }
 
+/// The pause after the given failed attempt: exponential in the number of
+/// attempts and capped by `max_backoff`, of which a random share of up to a
+/// half is skipped, so that clients failing at the same time spread out.
+fn backoff(config: &FarmRetryConfig, attempt: usize) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(16) as u32;
+    let backoff = config
+        .initial_backoff
+        .saturating_mul(1 << exponent)
+        .min(config.max_backoff);
+    let jitter = backoff.mul_f64(rand::thread_rng().gen_range(0.0..0.5));
+    backoff - jitter
+}
+
+/// Background task keeping a Farm group alive by periodically extending its TTL,
+/// as configured by a `FarmKeepaliveConfig`.
+///
+/// Failed extensions are logged and retried, never panicking. The task stops
+/// when the handle is dropped (or explicitly stopped); or on its own, once the
+/// configured maximum lifetime is exceeded.
+pub struct GroupKeepalive {
+    stopped: Arc<(Mutex<bool>, Condvar)>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl GroupKeepalive {
+    /// Spawns a thread extending the TTL of `group_name`, starting immediately.
+    pub fn spawn(farm: Farm, group_name: String, config: FarmKeepaliveConfig) -> Self {
+        let stopped = Arc::new((Mutex::new(false), Condvar::new()));
+        let handle = std::thread::spawn({
+            let stopped = stopped.clone();
+            move || Self::run(&farm, &group_name, &config, &stopped)
+        });
+        Self {
+            stopped,
+            handle: Some(handle),
+        }
+    }
+
+    /// Blocks until the task stops on its own, i.e. until the maximum lifetime is
+    /// exceeded. Never returns if no maximum lifetime is configured.
+    pub fn join(mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.join().expect("keepalive thread panicked");
+        }
+    }
+
+    /// Stops the task and waits for it to finish. Equivalent to dropping the
+    /// handle.
+    pub fn stop(self) {}
+
+    fn run(
+        farm: &Farm,
+        group_name: &str,
+        config: &FarmKeepaliveConfig,
+        stopped: &(Mutex<bool>, Condvar),
+    ) {
+        let started_at = Instant::now();
+        loop {
+            if let Some(max_lifetime) = config.max_lifetime {
+                if started_at.elapsed() >= max_lifetime {
+                    info!(
+                        farm.logger,
+                        "Group {} kept alive for {:?}, no longer extending its TTL.",
+                        group_name,
+                        max_lifetime
+                    );
+                    return;
+                }
+            }
+            if !Self::extend_with_retries(farm, group_name, config, stopped)
+                || Self::wait_for_stop(stopped, config.interval)
+            {
+                return;
+            }
+        }
+    }
+
+    /// Tries to extend the group's TTL up to `config.max_attempts` times. Returns
+    /// `false` iff the task was stopped in the meantime.
+    fn extend_with_retries(
+        farm: &Farm,
+        group_name: &str,
+        config: &FarmKeepaliveConfig,
+        stopped: &(Mutex<bool>, Condvar),
+    ) -> bool {
+        for attempt in 1..=config.max_attempts {
+            match farm.extend_group_ttl(group_name, config.group_ttl) {
+                Ok(()) => {
+                    debug!(
+                        farm.logger,
+                        "Group {} TTL set to +{:?} from now (Farm endpoint: {:?})",
+                        group_name,
+                        config.group_ttl,
+                        farm.base_url
+                    );
+                    return true;
+                }
+                Err(e) => warn!(
+                    farm.logger,
+                    "Failed to extend TTL of group {} (attempt {}/{}): {:?}",
+                    group_name,
+                    attempt,
+                    config.max_attempts,
+                    e
+                ),
+            }
+            if attempt < config.max_attempts && Self::wait_for_stop(stopped, config.retry_backoff) {
+                return false;
+            }
+        }
+        error!(
+            farm.logger,
+            "Failed to extend TTL of group {}, retrying in {:?}.", group_name, config.interval
+        );
+        true
+    }
+
+    /// Waits for up to `timeout` for the task to be stopped. Returns `true` iff
+    /// it was.
+    fn wait_for_stop(stopped: &(Mutex<bool>, Condvar), timeout: Duration) -> bool {
+        let (lock, cvar) = stopped;
+        let (stopped, _) = cvar
+            .wait_timeout_while(lock.lock().unwrap(), timeout, |stopped| !*stopped)
+            .unwrap();
+        *stopped
+    }
+}
+
+impl Drop for GroupKeepalive {
+    fn drop(&mut self) {
+        let (lock, cvar) = &*self.stopped;
+        *lock.lock().unwrap() = true;
+        cvar.notify_all();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
 pub enum ClaimResult {
     FileNotFound,
     FileClaimed(FileExpiration),
@@ -425,7 +694,6 @@ struct TimeoutSettings {
     min_http_timeout: Duration,
     /// The minimum http request timeout.
     max_http_timeout: Duration,
-    linear_backoff: Duration,
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Deserialize, Serialize)]
@@ -597,6 +865,16 @@ pub struct CreateVmRequest {
     pub vm_allocation: Option<VmAllocationStrategy>,
     #[serde(rename = "requiredHostFeatures")]
     pub required_host_features: Vec<HostFeature>,
+    /// Omitted from the request if unspecified.
+    #[serde(flatten)]
+    pub topology: VmTopology,
+    /// Omitted from the request for standard VMs.
+    #[serde(
+        rename = "vmClass",
+        skip_serializing_if = "VmClass::is_standard",
+        default
+    )]
+    pub vm_class: VmClass,
 }
 
 impl CreateVmRequest {
@@ -623,8 +901,20 @@ impl CreateVmRequest {
             has_ipv4,
             vm_allocation,
             required_host_features,
+            topology: VmTopology::default(),
+            vm_class: VmClass::default(),
         }
     }
+
+    pub fn with_topology(mut self, topology: VmTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn with_vm_class(mut self, vm_class: VmClass) -> Self {
+        self.vm_class = vm_class;
+        self
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Deserialize, Serialize)]
@@ -664,6 +954,22 @@ pub enum FarmError {
     #[error("Invalid response: {message}")]
     InvalidResponse { message: String },
 
+    #[error("Bad request: {message}")]
+    BadRequest { message: String },
+
+    #[error("Already exists: {message}")]
+    AlreadyExists { message: String },
+
+    #[error("Farm rejected the CPU topology {topology:?} of VM '{vm_name}': {message}")]
+    VmTopologyRejected {
+        vm_name: String,
+        topology: VmTopology,
+        message: String,
+    },
+
+    #[error("Farm cannot give VM '{vm_name}' a performance placement: {message}")]
+    PerformancePlacementDenied { vm_name: String, message: String },
+
     #[error("Retried too many times: {message}")]
     TooManyRetries { message: String },
 
@@ -679,6 +985,27 @@ pub struct VMCreateResponse {
     pub mac6: String,
     pub hostname: String,
     pub spec: VmSpec,
+    /// How Farm placed the VM; only reported for performance VMs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<VmPlacement>,
+}
+
+/// The placement Farm granted to a VM.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
+pub struct VmPlacement {
+    /// The VM is the only one on its host.
+    #[serde(rename = "dedicatedHost", default)]
+    pub dedicated_host: bool,
+    /// The vCPUs of the VM are pinned to host cores not shared with other VMs.
+    #[serde(rename = "cpuPinned", default)]
+    pub cpu_pinned: bool,
+}
+
+impl VmPlacement {
+    /// Whether the VM is shielded from noisy neighbours.
+    pub fn is_isolated(&self) -> bool {
+        self.dedicated_host || self.cpu_pinned
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
@@ -817,3 +1144,348 @@ pub fn emit_vm_created_event(
     );
     event.emit_log(log);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn farm(server: &mockito::Server) -> Farm {
+        let base_url = Url::parse(&format!("{}/", server.url())).unwrap();
+        Farm::new(base_url, Logger::root(slog::Discard, slog::o!()))
+    }
+
+    fn config(interval: Duration) -> FarmKeepaliveConfig {
+        FarmKeepaliveConfig {
+            interval,
+            group_ttl: Duration::from_secs(90),
+            max_lifetime: None,
+            max_attempts: 3,
+            retry_backoff: Duration::from_millis(10),
+        }
+    }
+
+    #[test]
+    fn keepalive_extends_ttl_periodically() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("PUT", "/group/test-group/ttl/90")
+            .with_status(200)
+            .expect_at_least(3)
+            .expect_at_most(6)
+            .create();
+
+        let keepalive = GroupKeepalive::spawn(
+            farm(&server),
+            "test-group".to_string(),
+            config(Duration::from_millis(100)),
+        );
+        std::thread::sleep(Duration::from_millis(350));
+        keepalive.stop();
+
+        mock.assert();
+    }
+
+    #[test]
+    fn keepalive_stops_promptly() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("PUT", "/group/test-group/ttl/90")
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        // The first extension happens immediately, the next one only in an hour.
+        let keepalive = GroupKeepalive::spawn(
+            farm(&server),
+            "test-group".to_string(),
+            config(Duration::from_secs(3600)),
+        );
+        std::thread::sleep(Duration::from_millis(100));
+        let stopping_at = Instant::now();
+        drop(keepalive);
+
+        assert!(stopping_at.elapsed() < Duration::from_secs(10));
+        mock.assert();
+    }
+
+    #[test]
+    fn keepalive_retries_failed_extensions_without_panicking() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("PUT", "/group/test-group/ttl/90")
+            .with_status(500)
+            .expect(3)
+            .create();
+
+        let keepalive = GroupKeepalive::spawn(
+            farm(&server),
+            "test-group".to_string(),
+            config(Duration::from_secs(3600)),
+        );
+        std::thread::sleep(Duration::from_millis(300));
+        keepalive.stop();
+
+        mock.assert();
+    }
+
+    #[test]
+    fn keepalive_stops_after_max_lifetime() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("PUT", "/group/test-group/ttl/90")
+            .with_status(200)
+            .expect_at_least(1)
+            .create();
+
+        let keepalive = GroupKeepalive::spawn(
+            farm(&server),
+            "test-group".to_string(),
+            FarmKeepaliveConfig {
+                max_lifetime: Some(Duration::from_millis(100)),
+                ..config(Duration::from_millis(20))
+            },
+        );
+        // Returns on its own.
+        keepalive.join();
+
+        mock.assert();
+    }
+
+    #[test]
+    fn extend_group_ttl_reports_errors() {
+        let mut server = mockito::Server::new();
+        let _missing = server
+            .mock("PUT", "/group/missing-group/ttl/60")
+            .with_status(404)
+            .with_body("no such group")
+            .create();
+        let _broken = server
+            .mock("PUT", "/group/broken-group/ttl/60")
+            .with_status(500)
+            .create();
+        let farm = farm(&server);
+
+        assert!(matches!(
+            farm.extend_group_ttl("missing-group", Duration::from_secs(60)),
+            Err(FarmError::NotFound { message }) if message == "no such group"
+        ));
+        assert!(matches!(
+            farm.extend_group_ttl("broken-group", Duration::from_secs(60)),
+            Err(FarmError::InvalidResponse { .. })
+        ));
+    }
+
+    #[test]
+    fn group_exists_distinguishes_live_and_expired_groups() {
+        let mut server = mockito::Server::new();
+        let _live = server
+            .mock("GET", "/group/live-group")
+            .with_status(200)
+            .with_body("{}")
+            .create();
+        let _expired = server
+            .mock("GET", "/group/expired-group")
+            .with_status(404)
+            .with_body("no such group")
+            .create();
+        let farm = farm(&server);
+
+        assert!(farm.group_exists("live-group").unwrap());
+        assert!(!farm.group_exists("expired-group").unwrap());
+    }
+
+    fn create_vm_request() -> CreateVmRequest {
+        CreateVmRequest::new(
+            "vm-1".to_string(),
+            VmType::Production,
+            NrOfVCPUs::new(16),
+            AmountOfMemoryKiB::new(1024),
+            vec![],
+            ImageLocation::ImageViaUrl {
+                url: Url::parse("http://example.com/img.zst").unwrap(),
+                sha256: "abc".to_string(),
+            },
+            None,
+            false,
+            None,
+            vec![],
+        )
+    }
+
+    #[test]
+    fn create_vm_request_without_topology_keeps_its_shape() {
+        assert_eq!(
+            serde_json::to_string(&create_vm_request()).unwrap(),
+            r#"{"type":"production","vCPUs":16,"memoryKiB":1024,"qemuCliArgs":[],"primaryImage":{"_tag":"imageViaUrl","url":"http://example.com/img.zst","sha256":"abc"},"primaryImageMinimalSizeGiB":null,"hasIPv4":false,"vmAllocation":null,"requiredHostFeatures":[]}"#
+        );
+    }
+
+    #[test]
+    fn create_vm_request_carries_topology() {
+        let request =
+            create_vm_request().with_topology(VmTopology::new(2, 8).with_pin_to_numa_node(1));
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["sockets"], 2);
+        assert_eq!(json["coresPerSocket"], 8);
+        assert_eq!(json["pinToNumaNode"], 1);
+
+        let partial = create_vm_request().with_topology(VmTopology {
+            pin_to_numa_node: Some(0),
+            ..Default::default()
+        });
+        let json = serde_json::to_value(&partial).unwrap();
+        assert_eq!(json["pinToNumaNode"], 0);
+        assert!(json.get("sockets").is_none());
+        assert!(json.get("coresPerSocket").is_none());
+    }
+
+    #[test]
+    fn create_vm_fails_if_topology_is_rejected() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/group/test-group/vm/vm-1")
+            .with_status(400)
+            .with_body("unsupported topology")
+            .expect(1)
+            .create();
+
+        let request = create_vm_request().with_topology(VmTopology::new(4, 8));
+        let err = farm(&server).create_vm("test-group", request).unwrap_err();
+        match err {
+            FarmError::VmTopologyRejected {
+                vm_name,
+                topology,
+                message,
+            } => {
+                assert_eq!(vm_name, "vm-1");
+                assert_eq!(topology, VmTopology::new(4, 8));
+                assert_eq!(message, "unsupported topology");
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+        mock.assert();
+    }
+
+    fn farm_with_fast_retries(server: &mockito::Server) -> Farm {
+        farm(server).with_retry_config(FarmRetryConfig {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+        })
+    }
+
+    fn request_id() -> mockito::Matcher {
+        mockito::Matcher::Regex("^[0-9a-f]{8}-([0-9a-f]{4}-){3}[0-9a-f]{12}$".to_string())
+    }
+
+    #[test]
+    fn retries_server_errors_until_success() {
+        let mut server = mockito::Server::new();
+        let unavailable = server
+            .mock("PUT", "/group/test-group/ttl/60")
+            .match_header(REQUEST_ID_HEADER, request_id())
+            .with_status(503)
+            .expect(2)
+            .create();
+        let available = server
+            .mock("PUT", "/group/test-group/ttl/60")
+            .match_header(REQUEST_ID_HEADER, request_id())
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let result =
+            farm_with_fast_retries(&server).set_group_ttl("test-group", Duration::from_secs(60));
+
+        assert!(result.is_ok(), "unexpected error: {result:?}");
+        unavailable.assert();
+        available.assert();
+    }
+
+    #[test]
+    fn does_not_retry_client_errors() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("PUT", "/group/test-group/ttl/60")
+            .with_status(400)
+            .with_body("invalid ttl")
+            .expect(1)
+            .create();
+
+        let result =
+            farm_with_fast_retries(&server).set_group_ttl("test-group", Duration::from_secs(60));
+
+        assert!(matches!(
+            result,
+            Err(FarmError::BadRequest { message }) if message == "invalid ttl"
+        ));
+        mock.assert();
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("PUT", "/group/test-group/ttl/60")
+            .with_status(502)
+            .expect(5)
+            .create();
+
+        let result =
+            farm_with_fast_retries(&server).set_group_ttl("test-group", Duration::from_secs(60));
+
+        assert!(matches!(result, Err(FarmError::TooManyRetries { .. })));
+        mock.assert();
+    }
+
+    #[test]
+    fn group_creation_is_idempotent_across_retries() {
+        let mut server = mockito::Server::new();
+        let unavailable = server
+            .mock("POST", "/group/test-group")
+            .with_status(503)
+            .expect(1)
+            .create();
+        let conflict = server
+            .mock("POST", "/group/test-group")
+            .with_status(409)
+            .with_body("group already exists")
+            .expect(1)
+            .create();
+        let request = CreateGroupRequest {
+            ttl: Some(60),
+            spec: GroupSpec {
+                vm_allocation: None,
+                required_host_features: vec![],
+                preferred_network: None,
+                metadata: None,
+            },
+        };
+
+        let result = farm_with_fast_retries(&server).create_group_("test-group", &request);
+
+        assert!(result.is_ok(), "unexpected error: {result:?}");
+        unavailable.assert();
+        conflict.assert();
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_with_jitter_up_to_max() {
+        let config = FarmRetryConfig {
+            max_attempts: 10,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        };
+        for (attempt, full_backoff) in [(1, 1), (2, 2), (3, 4), (5, 16), (6, 30), (100, 30)] {
+            let full_backoff = Duration::from_secs(full_backoff);
+            for _ in 0..100 {
+                let backoff = backoff(&config, attempt);
+                assert!(backoff <= full_backoff, "{backoff:?} > {full_backoff:?}");
+                assert!(
+                    backoff > full_backoff / 2,
+                    "{backoff:?} <= {full_backoff:?} / 2"
+                );
+            }
+        }
+    }
+}
@@ -3,26 +3,37 @@
 use walkdir::WalkDir;
 use crate::driver::constants;
 use crate::driver::{
-    farm::{Farm, HostFeature},
+    capabilities, clock_skew,
+    farm::{Farm, GroupKeepalive, HostFeature},
     resource::AllocatedVm,
     task_scheduler::TaskScheduler,
-    test_env_api::{FarmBaseUrl, HasGroupSetup, HasIcDependencies},
-    universal_vm::UNIVERSAL_VMS_DIR,
+    test_env_api::{
+        get_dependency_path, CollectNodeLogs, FarmBaseUrl, HasGroupSetup, HasIcDependencies,
+        NODE_LOGS_WINDOW_ON_FAILURE,
+    },
+    universal_vm::{UniversalVms, UNIVERSAL_VMS_DIR},
     {
         action_graph::ActionGraph,
         context::{GroupContext, ProcessContext},
         dsl::{SubprocessFn, TestFunction},
         event::TaskId,
         plan::{EvalOrder, Plan},
-        report::Outcome,
+        report::{DriverExitCode, Outcome, ResultLine, SystemGroupSummary, TaskReport},
         task::{DebugKeepaliveTask, EmptyTask},
         task_scheduler::TaskTable,
     },
 };
 use crate::driver::{
+    driver_setup::{reuse_setup_env, DeclaredDependencies, Dependency},
+    jumphost::HasJumphost,
+    log_budget::{self, LogBudgetStartTime, LogBudgets, Severity},
     log_events,
-    pot_dsl::{PotSetupFn, SysTestFn},
+    pot_dsl::{Matrix, MatrixCell, PotSetupFn, SysTestFn, TestDependencies, TestDependency},
+    prometheus_vm::{PrometheusVm, PROMETHEUS_VM_NAME},
+    repro::{write_repro_bundle, DriverInvocation},
+    setup_fragments::SetupFragments,
     test_env::{TestEnv, TestEnvAttribute},
+    test_events::TestEventSink,
     test_setup::{GroupSetup, InfraProvider},
 };
 use crate::k8s::tnet::TNet;
@@ -33,6 +44,7 @@ use std::path::PathBuf;
 
 use anyhow::{bail, Context, Result};
 use clap::Parser;
+use regex::Regex;
 use tokio::{
     io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
     net::TcpSocket,
@@ -40,17 +52,21 @@ use tokio::{
 };
 
 use crate::driver::{
-    constants::{GROUP_TTL, KEEPALIVE_INTERVAL},
+    config::{
+        DriverConfig, DriverConfigErrors, DriverConfigLayer, FarmKeepaliveConfig,
+        DRIVER_CONFIG_FILE_ENV_VAR,
+    },
     report::SystemTestGroupError,
     subprocess_task::SubprocessTask,
-    task::{SkipTestTask, Task},
+    task::{dependency_failure_message, SkipTestTask, Task},
     timeout::TimeoutTask,
 };
 use slog::{debug, error, info, trace, warn, Logger};
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     iter::once,
     net::Ipv6Addr,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
     time::Duration,
 };
 
@@ -65,7 +81,7 @@ const DEBUG_KEEPALIVE_TASK_NAME: &str = "debug_keepalive";
 const REPORT_TASK_NAME: &str = "report";
 const KEEPALIVE_TASK_NAME: &str = "keepalive";
 const UVMS_LOGS_STREAM_TASK_NAME: &str = "uvms_logs_stream";
-const SETUP_TASK_NAME: &str = "setup";
+pub(crate) const SETUP_TASK_NAME: &str = "setup";
 const LIFETIME_GUARD_TASK_PREFIX: &str = "lifetime_guard_";
 pub const COLOCATE_CONTAINER_NAME: &str = "system_test";
 
@@ -89,6 +105,31 @@ pub struct CliArgs {
     )]
     pub no_delete_farm_group: bool,
 
+    #[clap(
+        long = "keep-env",
+        help = "If set, the Farm group or k8s tnet is not deleted in the tear down, such that the setup directory can be passed to --reuse-env later on."
+    )]
+    pub keep_env: bool,
+
+    #[clap(
+        long = "reuse-env",
+        help = "Setup directory of a previous invocation whose Farm group is still alive, e.g. one run with --keep-env. The setup is skipped and the selected tests run against the system deployed back then. The reused Farm group or k8s tnet is not deleted in the tear down, unless --teardown-reused-env is set."
+    )]
+    pub reuse_env: Option<PathBuf>,
+
+    #[clap(
+        long = "teardown-reused-env",
+        requires = "reuse_env",
+        help = "If set, the Farm group or k8s tnet reused via --reuse-env is deleted in the tear down."
+    )]
+    pub teardown_reused_env: bool,
+
+    #[clap(
+        long = "download-prometheus-data",
+        help = "If set, a snapshot of the TSDB of the Prometheus VM, if any, is downloaded to the setup directory in the tear down."
+    )]
+    pub download_prometheus_data: bool,
+
     #[clap(
         long = "no-group-ttl",
         help = "If set, The group won't have a Time-To-Live set and thus won't be garbage collected"
@@ -113,9 +154,41 @@ pub struct CliArgs {
     )]
     pub filter_tests: Option<String>,
 
+    #[clap(
+        long = "include-pattern",
+        help = "Execute only those tests whose qualified name <group>::<test> matches this regex and skip all the others.",
+        value_parser = CliArgs::parse_regex
+    )]
+    pub include_pattern: Option<Regex>,
+
+    #[clap(
+        long = "skip-pattern",
+        help = "Skip those tests whose qualified name <group>::<test> matches this regex.",
+        value_parser = CliArgs::parse_regex
+    )]
+    pub skip_pattern: Option<Regex>,
+
+    #[clap(
+        long = "list",
+        help = "Print the qualified names of the tests which would be executed and exit, without running any setup or allocating any resources."
+    )]
+    pub list: bool,
+
     #[clap(long = "k8s", help = "Use k8s as infra provider instead of Farm.")]
     pub k8s: bool,
 
+    #[clap(
+        long = "driver-config",
+        help = "JSON file with driver settings, overridden by IC_TEST_DRIVER_* environment variables and the command line. Defaults to the file given by IC_TEST_DRIVER_CONFIG_FILE, if any."
+    )]
+    pub driver_config: Option<PathBuf>,
+
+    #[clap(
+        long = "log-level",
+        help = "Minimum level (critical, error, warning, info, debug or trace) of the log messages printed by the driver."
+    )]
+    pub log_level: Option<String>,
+
     #[clap(long = "group-base-name", help = "Group base name.")]
     pub group_base_name: String,
 
@@ -131,6 +204,18 @@ pub struct CliArgs {
         value_parser = CliArgs::parse_host_feature
     )]
     pub required_host_features: Option<Vec<HostFeature>>,
+
+    #[clap(
+        long = "test-events-socket",
+        help = "Unix socket to which test lifecycle events are written as newline-delimited JSON, in addition to the test events file in the group directory."
+    )]
+    pub test_events_socket: Option<PathBuf>,
+
+    #[clap(
+        long = "with-jumphost",
+        help = "If set, a jumphost is deployed once the setup has succeeded, e.g., to debug the system under test kept alive by --debug-keepalive."
+    )]
+    pub with_jumphost: bool,
 }
 
 impl CliArgs {
@@ -139,6 +224,41 @@ impl CliArgs {
         Ok(self)
     }
 
+    /// Resolves the effective driver configuration from the defaults, the
+    /// config file, the `IC_TEST_DRIVER_*` variables among `env_vars` and the
+    /// command line, in increasing order of precedence.
+    fn driver_config(
+        &self,
+        env_vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<DriverConfig, DriverConfigErrors> {
+        let env_vars: Vec<_> = env_vars.into_iter().collect();
+        let config_file = self.driver_config.clone().or_else(|| {
+            env_vars
+                .iter()
+                .find(|(name, _)| name == DRIVER_CONFIG_FILE_ENV_VAR)
+                .map(|(_, path)| PathBuf::from(path))
+        });
+        let file_layer = match config_file {
+            Some(path) => {
+                DriverConfigLayer::from_file(&path).unwrap_or_else(|e| DriverConfigLayer {
+                    errors: vec![format!("{e:#}")],
+                    ..Default::default()
+                })
+            }
+            None => DriverConfigLayer::default(),
+        };
+        let cli_layer = DriverConfigLayer {
+            farm_base_url: self.farm_base_url.as_ref().map(|url| url.to_string()),
+            log_level: self.log_level.clone(),
+            k8s: self.k8s.then_some(true),
+            ..Default::default()
+        };
+        file_layer
+            .merge(DriverConfigLayer::from_env_vars(env_vars))
+            .merge(cli_layer)
+            .validate()
+    }
+
     /// A convenience method to get the task id of this subprocess, *if* it is in fact a
     /// subprocess.
     fn subproc_id(&self) -> Option<(TaskId, u64)> {
@@ -150,6 +270,19 @@ impl CliArgs {
         }
     }
 
+    fn parse_regex(s: &str) -> Result<Regex, regex::Error> {
+        Regex::new(s)
+    }
+
+    /// The filter selecting the tests to execute.
+    fn test_filter(&self) -> TestFilter {
+        TestFilter {
+            include_tests: self.filter_tests.clone(),
+            include_pattern: self.include_pattern.clone(),
+            skip_pattern: self.skip_pattern.clone(),
+        }
+    }
+
     /// Convert stringified HostFeatures to HostFeatures.
     fn parse_host_feature(s: &str) -> Result<HostFeature, serde_json::Error> {
         let quoted_feature = format!("\"{}\"", s.trim());
@@ -179,12 +312,16 @@ pub enum SystemTestsSubcommand {
     /// Not yet implemented!
     InteractiveMode,
 
+    /// Deploy a jumphost into the group of a running invocation (e.g., one
+    /// kept alive by --debug-keepalive) sharing the same working directory.
+    Jumphost,
+
     #[clap(hide = true)]
     SpawnChild { task_id: TaskId, sock_id: u64 },
 }
 
 #[derive(Deserialize, Serialize)]
-struct SetupResult;
+pub(crate) struct SetupResult;
 
 impl TestEnvAttribute for SetupResult {
     fn attribute_name() -> String {
@@ -202,6 +339,9 @@ pub struct ComposeContext<'a> {
     empty_task_counter: u64,
     logger: Logger,
     timeout_per_test: Duration,
+    /// Selected tests that are skipped since one of their prerequisites is
+    /// not run, along with the reason.
+    dependency_skips: BTreeMap<String, String>,
 }
 
 fn subproc(
@@ -286,6 +426,147 @@ fn compose_seq(
     compose(root_task, EvalOrder::Sequential, vec![first, second], ctx)
 }
 
+/// Turns the top-level sub-groups of a group into the plans that run one
+/// after the other. Without `parallelism`, every sub-group is its own plan.
+/// Otherwise, consecutive tests added with `add_test()` are batched into a
+/// plan running up to `parallelism` of them at the same time. A batch ends
+/// before a serial test, a sub-group other than a single test, or a test whose
+/// prerequisite is part of the batch.
+fn compose_tests(
+    tests: Vec<SystemTestSubGroup>,
+    parallelism: Option<usize>,
+    serial_tests: &BTreeSet<String>,
+    prerequisites: &BTreeMap<TaskId, BTreeSet<TaskId>>,
+    ctx: &mut ComposeContext,
+) -> Vec<Plan<Box<dyn Task>>> {
+    let Some(max_parallel) = parallelism else {
+        return tests
+            .into_iter()
+            .map(|sub_group| sub_group.into_plan(ctx))
+            .collect();
+    };
+    let mut plans = vec![];
+    let mut batch: Vec<(TaskId, Plan<Box<dyn Task>>)> = vec![];
+    for sub_group in tests {
+        let task_id = match &sub_group {
+            SystemTestSubGroup::Singleton {
+                task_id: task_id @ TaskId::Test(name),
+                ..
+            } if !serial_tests.contains(name) => Some(task_id.clone()),
+            _ => None,
+        };
+        match task_id {
+            Some(task_id) => {
+                let depends_on_batch = prerequisites.get(&task_id).is_some_and(|prerequisites| {
+                    batch.iter().any(|(id, _)| prerequisites.contains(id))
+                });
+                if depends_on_batch {
+                    flush_batch(max_parallel, &mut batch, &mut plans, ctx);
+                }
+                let plan = sub_group.into_plan(ctx);
+                batch.push((task_id, plan));
+            }
+            None => {
+                flush_batch(max_parallel, &mut batch, &mut plans, ctx);
+                plans.push(sub_group.into_plan(ctx));
+            }
+        }
+    }
+    flush_batch(max_parallel, &mut batch, &mut plans, ctx);
+    plans
+}
+
+fn flush_batch(
+    max_parallel: usize,
+    batch: &mut Vec<(TaskId, Plan<Box<dyn Task>>)>,
+    plans: &mut Vec<Plan<Box<dyn Task>>>,
+    ctx: &mut ComposeContext,
+) {
+    let mut children: Vec<_> = batch.drain(..).map(|(_, plan)| plan).collect();
+    match children.len() {
+        0 => {}
+        1 => plans.push(children.pop().unwrap()),
+        _ => plans.push(compose(
+            None,
+            EvalOrder::Bounded(max_parallel),
+            children,
+            ctx,
+        )),
+    }
+}
+
+/// The name of test `test_name` of group `group_name` as matched by the
+/// `--include-pattern` and `--skip-pattern` filters and printed by `--list`.
+pub fn qualified_test_name(group_name: &str, test_name: &str) -> String {
+    format!("{group_name}::{test_name}")
+}
+
+/// Selects the tests of a group to execute. Tests that are not selected are
+/// skipped.
+#[derive(Clone, Debug, Default)]
+pub struct TestFilter {
+    /// Selects tests whose name contains this substring (`--include-tests`).
+    pub include_tests: Option<String>,
+    /// Selects tests whose qualified name matches this regex (`--include-pattern`).
+    pub include_pattern: Option<Regex>,
+    /// Deselects tests whose qualified name matches this regex (`--skip-pattern`),
+    /// even if they are selected by the filters above.
+    pub skip_pattern: Option<Regex>,
+}
+
+impl TestFilter {
+    pub fn matches(&self, group_name: &str, test_name: &str) -> bool {
+        let qualified_name = qualified_test_name(group_name, test_name);
+        self.include_tests
+            .as_ref()
+            .map_or(true, |filter| test_name.contains(filter.as_str()))
+            && self
+                .include_pattern
+                .as_ref()
+                .map_or(true, |pattern| pattern.is_match(&qualified_name))
+            && !self
+                .skip_pattern
+                .as_ref()
+                .is_some_and(|pattern| pattern.is_match(&qualified_name))
+    }
+}
+
+/// Runs the test function `test_fn` of the test `test_name` in `env`. If the
+/// test fails, the logs of all nodes and a reproduction bundle are written
+/// into `env` before the failure is propagated. Node clocks skewed by the test
+/// are restored in either case.
+fn run_test_fn(
+    env: TestEnv,
+    group_ctx: &GroupContext,
+    test_name: &str,
+    test_fn: Box<dyn SysTestFn>,
+) {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        capabilities::with_recorder(env.capability_recorder(), || test_fn(env.clone()))
+    }));
+    if let Err(e) = clock_skew::restore_clock_offsets(&env) {
+        warn!(env.logger(), "Failed to restore clock offsets: {e:?}");
+    }
+    if let Err(panic) = result {
+        // Failing to collect the logs must not mask the failure of the test.
+        match catch_unwind(AssertUnwindSafe(|| {
+            env.collect_node_logs(NODE_LOGS_WINDOW_ON_FAILURE)
+        })) {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => warn!(env.logger(), "Failed to collect node logs: {e:?}"),
+            Err(_) => warn!(env.logger(), "Collecting node logs panicked"),
+        }
+        match catch_unwind(AssertUnwindSafe(|| {
+            write_repro_bundle(&env, group_ctx, test_name)
+        })) {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => warn!(env.logger(), "Failed to write the repro bundle: {e:?}"),
+            Err(_) => warn!(env.logger(), "Writing the repro bundle panicked"),
+        }
+        resume_unwind(panic);
+    }
+}
+
 fn ensure_setup_env(gctx: GroupContext) -> TestEnv {
     trace!(gctx.log(), "get_setup_env()");
     let process_ctx = ProcessContext::new(gctx, String::from(SETUP_TASK_NAME)).unwrap();
@@ -306,7 +587,12 @@ pub enum SystemTestSubGroup {
     Singleton {
         task_fn: Box<dyn SysTestFn>,
         task_id: TaskId,
+        /// Overrides the timeout per test of the group, if set.
+        timeout: Option<Duration>,
     },
+    /// One cell of a test matrix: a setup task followed by a test, with their
+    /// own environment (forked from the root environment, not the group setup).
+    MatrixCell(MatrixCell),
 }
 
 impl Default for SystemTestSubGroup {
@@ -325,9 +611,11 @@ impl SystemTestSubGroup {
 
     pub fn add_test(self, test: TestFunction) -> Self {
         let task_is = TaskId::Test(String::from(test.name()));
+        let timeout = test.timeout();
         let singleton = Self::Singleton {
             task_fn: test.f(),
             task_id: task_is,
+            timeout,
         };
         match self {
             Self::Multiple { tasks, .. } if tasks.is_empty() => {
@@ -338,7 +626,7 @@ impl SystemTestSubGroup {
                 tasks: tasks.into_iter().chain(once(singleton)).collect(),
                 ordering,
             },
-            sub_group @ Self::Singleton { .. } => {
+            sub_group @ (Self::Singleton { .. } | Self::MatrixCell(_)) => {
                 Self::Multiple {
                     tasks: once(sub_group).chain(once(singleton)).collect(),
                     ordering: EvalOrder::Parallel, // TODO: generalize this
@@ -347,6 +635,22 @@ impl SystemTestSubGroup {
         }
     }
 
+    /// The names of the tests of this sub-group, along with whether they run
+    /// in an environment forked from the group setup, unlike matrix cells,
+    /// which have their own setup.
+    fn test_names(&self) -> Vec<(&str, bool)> {
+        match self {
+            SystemTestSubGroup::Multiple { tasks, .. } => {
+                tasks.iter().flat_map(|task| task.test_names()).collect()
+            }
+            SystemTestSubGroup::Singleton { task_id, .. } => match task_id {
+                TaskId::Test(name) => vec![(name.as_str(), true)],
+                _ => vec![],
+            },
+            SystemTestSubGroup::MatrixCell(cell) => vec![(cell.test_name.as_str(), false)],
+        }
+    }
+
     pub fn into_plan(self, ctx: &mut ComposeContext) -> Plan<Box<dyn Task>> {
         match self {
             SystemTestSubGroup::Multiple { tasks, ordering } => compose(
@@ -358,18 +662,31 @@ impl SystemTestSubGroup {
                     .collect(),
                 ctx,
             ),
-            // If filtering flag `--include-tests` is set, then for all
-            // skipped test function we execute a SkipTestTask
-            SystemTestSubGroup::Singleton { task_fn, task_id } => {
+            // For all test functions not selected by the test filter we
+            // execute a SkipTestTask
+            SystemTestSubGroup::Singleton {
+                task_fn,
+                task_id,
+                timeout,
+            } => {
                 let logger = ctx.logger.clone();
                 let group_ctx = ctx.group_ctx.clone();
-                if let Some(ref filter) = group_ctx.filter_tests {
-                    if let TaskId::Test(ref name) = task_id {
-                        if !name.contains(filter) {
-                            return Plan::Leaf {
-                                task: Box::from(SkipTestTask::new(task_id.clone())),
-                            };
-                        }
+                if let TaskId::Test(ref name) = task_id {
+                    if !group_ctx
+                        .test_filter
+                        .matches(&group_ctx.group_base_name, name)
+                    {
+                        return Plan::Leaf {
+                            task: Box::from(SkipTestTask::new(task_id.clone())),
+                        };
+                    }
+                    if let Some(reason) = ctx.dependency_skips.get(name) {
+                        return Plan::Leaf {
+                            task: Box::from(SkipTestTask::due_to_dependency_failure(
+                                task_id.clone(),
+                                reason,
+                            )),
+                        };
                     }
                 }
                 let closure = {
@@ -377,23 +694,90 @@ impl SystemTestSubGroup {
                     let group_ctx = ctx.group_ctx.clone();
                     move || {
                         debug!(logger, ">>> test_fn({})", &task_id);
-                        let env = get_or_create_env(group_ctx, task_id).unwrap();
+                        let env = get_or_create_env(group_ctx.clone(), task_id.clone()).unwrap();
                         // This function will only be called after setup finishes
                         if SetupResult::try_read_attribute(&env).is_err() {
                             panic!("Failed to find SetupResult attribute after setup. Cancelling test function.");
                         }
-                        task_fn(env)
+                        run_test_fn(env, &group_ctx, &task_id.name(), task_fn)
                     }
                 };
+                let timeout = timeout.unwrap_or(ctx.timeout_per_test);
                 timed(
                     Plan::Leaf {
                         task: Box::from(subproc(task_id, closure, ctx)),
                     },
-                    ctx.timeout_per_test,
+                    timeout,
                     None,
                     ctx,
                 )
             }
+            // Matrix cells are filtered by their qualified test name. The setup of a
+            // skipped cell is not run at all.
+            SystemTestSubGroup::MatrixCell(MatrixCell {
+                test_name,
+                setup_name,
+                setup_fn,
+                test_fn,
+            }) => {
+                let task_id = TaskId::Test(test_name.clone());
+                if !ctx
+                    .group_ctx
+                    .test_filter
+                    .matches(&ctx.group_ctx.group_base_name, &test_name)
+                {
+                    return Plan::Leaf {
+                        task: Box::from(SkipTestTask::new(task_id)),
+                    };
+                }
+                let setup_plan = {
+                    let logger = ctx.logger.clone();
+                    let group_ctx = ctx.group_ctx.clone();
+                    let setup_name = setup_name.clone();
+                    let closure = move || {
+                        debug!(logger, ">>> setup_fn({})", &setup_name);
+                        let env = group_ctx.ensure_named_setup_env(&setup_name).unwrap();
+                        capabilities::with_recorder(env.capability_recorder(), || {
+                            setup_fn(env.clone())
+                        });
+                        SetupResult {}.write_attribute(&env);
+                    };
+                    let setup_task = subproc(TaskId::Test(setup_name.clone()), closure, ctx);
+                    timed(
+                        Plan::Leaf {
+                            task: Box::from(setup_task),
+                        },
+                        ctx.timeout_per_test,
+                        None,
+                        ctx,
+                    )
+                };
+                let test_plan = {
+                    let logger = ctx.logger.clone();
+                    let group_ctx = ctx.group_ctx.clone();
+                    let task_id = task_id.clone();
+                    let closure = move || {
+                        debug!(logger, ">>> test_fn({})", &task_id);
+                        let env = group_ctx
+                            .create_test_env_from_setup(&setup_name, &test_name)
+                            .unwrap();
+                        // This function will only be called after the cell's setup finishes
+                        if SetupResult::try_read_attribute(&env).is_err() {
+                            panic!("Failed to find SetupResult attribute after setup. Cancelling test function.");
+                        }
+                        run_test_fn(env, &group_ctx, &test_name, test_fn)
+                    };
+                    timed(
+                        Plan::Leaf {
+                            task: Box::from(subproc(task_id, closure, ctx)),
+                        },
+                        ctx.timeout_per_test,
+                        None,
+                        ctx,
+                    )
+                };
+                compose_seq(None, setup_plan, test_plan, ctx)
+            }
         }
     }
 }
@@ -404,6 +788,12 @@ pub struct SystemTestGroup {
     timeout_per_test: Option<Duration>,
     overall_timeout: Option<Duration>,
     with_farm: bool,
+    farm_keepalive: FarmKeepaliveConfig,
+    log_budgets: LogBudgets,
+    dependencies: TestDependencies,
+    declared_dependencies: DeclaredDependencies,
+    parallelism: Option<usize>,
+    serial_tests: BTreeSet<String>,
 }
 
 impl Default for SystemTestGroup {
@@ -429,6 +819,12 @@ impl SystemTestGroup {
             timeout_per_test: None,
             overall_timeout: None,
             with_farm: true,
+            farm_keepalive: Default::default(),
+            log_budgets: Default::default(),
+            dependencies: Default::default(),
+            declared_dependencies: Default::default(),
+            parallelism: None,
+            serial_tests: Default::default(),
         }
     }
 
@@ -441,6 +837,12 @@ impl SystemTestGroup {
         self
     }
 
+    /// Configures how the Farm group is kept alive while the tests are running.
+    pub fn with_farm_keepalive(mut self, config: FarmKeepaliveConfig) -> Self {
+        self.farm_keepalive = config;
+        self
+    }
+
     pub fn with_overall_timeout(mut self, overall_timeout: Duration) -> Self {
         self.overall_timeout = Some(overall_timeout);
         self
@@ -451,15 +853,149 @@ impl SystemTestGroup {
         self
     }
 
+    /// Sets up the group by applying the given fragments, see
+    /// [`SetupFragments::apply`].
+    ///
+    /// Panics if two of the fragments declare conflicting capabilities.
+    pub fn with_setup_fragments(self, fragments: SetupFragments) -> Self {
+        if let Err(conflict) = fragments.check_conflicts() {
+            panic!("{}", conflict);
+        }
+        self.with_dependencies(fragments.dependencies())
+            .with_setup(move |env| fragments.apply(env))
+    }
+
+    /// Declares files from the runfiles the group depends on. They are
+    /// verified before any resources are allocated, see
+    /// [`crate::driver::driver_setup`].
+    pub fn with_dependencies<I: IntoIterator<Item = Dependency>>(
+        mut self,
+        dependencies: I,
+    ) -> Self {
+        for dependency in dependencies {
+            self.declared_dependencies.add(dependency);
+        }
+        self
+    }
+
     pub fn add_test(mut self, test: TestFunction) -> Self {
         let task_id = TaskId::Test(String::from(test.name()));
+        let timeout = test.timeout();
+        if test.is_serial() {
+            self.serial_tests.insert(test.name().to_string());
+        }
         self.tests.push(SystemTestSubGroup::Singleton {
             task_fn: test.f(),
             task_id,
+            timeout,
         });
         self
     }
 
+    /// The names of the tests of this group, see `SystemTestSubGroup::test_names()`.
+    fn test_names(&self) -> Vec<(&str, bool)> {
+        self.tests
+            .iter()
+            .flat_map(|sub_group| sub_group.test_names())
+            .collect()
+    }
+
+    /// The names of the tests of this group which are selected by `test_filter`
+    /// and not skipped due to a prerequisite that is not selected.
+    fn selected_test_names(&self, group_name: &str, test_filter: &TestFilter) -> Vec<String> {
+        let dependency_skips = self.dependency_skips(group_name, test_filter);
+        self.test_names()
+            .into_iter()
+            .filter(|(name, _)| {
+                test_filter.matches(group_name, name) && !dependency_skips.contains_key(*name)
+            })
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    /// The name of each test added with `add_test()`, or `None` for sub-groups
+    /// and matrices, in the order of `self.tests`.
+    fn top_level_test_names(&self) -> Vec<Option<&str>> {
+        self.tests
+            .iter()
+            .map(|sub_group| match sub_group {
+                SystemTestSubGroup::Singleton {
+                    task_id: TaskId::Test(name),
+                    ..
+                } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The tests selected by `test_filter` which are skipped since one of their
+    /// prerequisites is not selected, along with the reason.
+    fn dependency_skips(
+        &self,
+        group_name: &str,
+        test_filter: &TestFilter,
+    ) -> BTreeMap<String, String> {
+        let tests = self.dependencies.sort(
+            self.top_level_test_names()
+                .into_iter()
+                .flatten()
+                .map(|name| (Some(name.to_string()), name))
+                .collect(),
+        );
+        self.dependencies
+            .skipped_dependents(&tests, |name| test_filter.matches(group_name, name))
+    }
+
+    /// The prerequisites of each test that has any.
+    fn test_prerequisites(&self) -> BTreeMap<TaskId, BTreeSet<TaskId>> {
+        self.top_level_test_names()
+            .into_iter()
+            .flatten()
+            .map(|name| {
+                let prerequisites: BTreeSet<_> = self
+                    .dependencies
+                    .prerequisites_of(name)
+                    .map(|prerequisite| TaskId::Test(prerequisite.to_string()))
+                    .collect();
+                (TaskId::Test(name.to_string()), prerequisites)
+            })
+            .filter(|(_, prerequisites)| !prerequisites.is_empty())
+            .collect()
+    }
+
+    /// Records the infrastructure settings in the root environment and creates
+    /// the group on the infrastructure provider. The group is only created if
+    /// any tests are selected, such that an empty selection does not allocate
+    /// any resources.
+    fn prepare_root_env(
+        &self,
+        root_env: &TestEnv,
+        args: &CliArgs,
+        config: &DriverConfig,
+        any_test_selected: bool,
+    ) {
+        config.write_attribute(root_env);
+        FarmBaseUrl::new_or_default(Some(config.farm_base_url.clone())).write_attribute(root_env);
+        if let Some(required_args) = args.required_host_features.clone() {
+            required_args.write_attribute(root_env);
+        }
+        if config.k8s {
+            InfraProvider::K8s.write_attribute(root_env);
+        } else {
+            InfraProvider::Farm.write_attribute(root_env);
+        }
+        let with_farm = self.with_farm && !config.k8s;
+        // The group of a reused environment already exists.
+        if any_test_selected && args.reuse_env.is_none() && (with_farm || config.k8s) {
+            root_env.create_group_setup(args.group_base_name.clone(), args.no_group_ttl);
+            if config.default_vm_resources != Default::default() {
+                let mut group_setup = GroupSetup::read_attribute(root_env);
+                group_setup.default_vm_resources = Some(config.default_vm_resources);
+                group_setup.write_attribute(root_env);
+            }
+        }
+    }
+
     fn add_group(mut self, sub_group: SystemTestSubGroup, ordering: EvalOrder) -> Self {
         self.tests.push(match sub_group {
             SystemTestSubGroup::Multiple { tasks, .. } => {
@@ -506,14 +1042,16 @@ impl SystemTestGroup {
             lifetime_guard_task,
         );
         let lifetime_guard_sub_group = match sub_group {
-            SystemTestSubGroup::Singleton { .. } => sub_group.add_test(lifetime_guard_task),
+            SystemTestSubGroup::Singleton { .. } | SystemTestSubGroup::MatrixCell(_) => {
+                sub_group.add_test(lifetime_guard_task)
+            }
             SystemTestSubGroup::Multiple {
                 tasks: _,
                 ordering: EvalOrder::Parallel,
             } => sub_group.add_test(lifetime_guard_task),
             SystemTestSubGroup::Multiple {
                 tasks: _,
-                ordering: EvalOrder::Sequential,
+                ordering: EvalOrder::Sequential | EvalOrder::Bounded(_),
             } => {
                 todo!()
             }
@@ -533,20 +1071,145 @@ impl SystemTestGroup {
         self.add_group_with_minimal_lifetime(sub_group, min_lifetime)
     }
 
+    /// Adds a test [Matrix]: the same test body run against several IC
+    /// configurations. Each configuration is set up in its own environment, with
+    /// its own resources, in parallel with the other cells of the matrix.
+    ///
+    /// A group consisting only of matrices needs no `with_setup()`.
+    pub fn add_matrix(mut self, matrix: Matrix) -> Self {
+        if let Err(err) = matrix.validate() {
+            panic!("{}", err);
+        }
+        if self.setup.is_none() {
+            self.setup = Some(Box::new(|_env| {}));
+        }
+        self.tests.push(SystemTestSubGroup::Multiple {
+            tasks: matrix
+                .expand()
+                .into_iter()
+                .map(SystemTestSubGroup::MatrixCell)
+                .collect(),
+            ordering: EvalOrder::Parallel,
+        });
+        self
+    }
+
+    /// Declares that a test builds on the state created by other tests, e.g.
+    /// `add_dependency(t("upgrade").after("install"))`. The tests run in an
+    /// order where every test comes after its prerequisites, and a test is
+    /// skipped if any of its prerequisites does not pass. Tests without a
+    /// dependency between them are ordered as added.
+    ///
+    /// Dependencies can only be declared between tests that have already been
+    /// added with `add_test()`. Panics if the dependency introduces a cycle.
+    pub fn add_dependency(mut self, dependency: TestDependency) -> Self {
+        let test_names = self.top_level_test_names();
+        for name in
+            once(dependency.test()).chain(dependency.prerequisites().iter().map(String::as_str))
+        {
+            if !test_names.contains(&Some(name)) {
+                panic!(
+                    "Cannot declare a dependency on or of test {name}, which was not added with add_test()"
+                );
+            }
+        }
+        if let Err(err) = self.dependencies.add(dependency) {
+            panic!("{}", err);
+        }
+        self
+    }
+
+    /// Runs up to `max_parallel` of the tests added with `add_test()` at the
+    /// same time, each in its own environment forked from the setup. The
+    /// report lists the tests in the order they were added regardless.
+    ///
+    /// A test runs alone, i.e. no other test is started while it runs, if it
+    /// is marked with `TestFunction::serial()`, and it only starts once its
+    /// prerequisites have finished. Sub-groups and matrices run alone as well.
+    /// Panics if `max_parallel` is zero.
+    pub fn with_parallelism(mut self, max_parallel: usize) -> Self {
+        assert!(
+            max_parallel > 0,
+            "The parallelism of a group must be positive"
+        );
+        self.parallelism = Some(max_parallel);
+        self
+    }
+
     pub fn with_timeout_per_test(mut self, t: Duration) -> Self {
         self.timeout_per_test = Some(t);
         self
     }
 
-    fn make_plan(self, rh: &Handle, group_ctx: GroupContext) -> Result<Plan<Box<dyn Task>>> {
+    /// Fails the group if any IC node logs more than `max_entries` entries at
+    /// `severity` or above between the start of the setup and the end of the
+    /// last test. E.g. `with_log_budget(Severity::Critical, 0)`.
+    pub fn with_log_budget(mut self, severity: Severity, max_entries: usize) -> Self {
+        self.log_budgets = self.log_budgets.with_budget(severity, max_entries);
+        self
+    }
+
+    /// Excludes known-benign log messages matching the regular expression
+    /// `pattern` from all log budgets.
+    pub fn with_log_allowlist(mut self, pattern: &str) -> Self {
+        self.log_budgets = self.log_budgets.with_allowlist_pattern(pattern);
+        self
+    }
+
+    /// Reports exceeded log budgets without failing the group. The `reason` is
+    /// recorded in the log budget report.
+    pub fn with_log_budget_waiver(mut self, reason: &str) -> Self {
+        self.log_budgets = self.log_budgets.with_waiver(reason);
+        self
+    }
+
+    fn make_plan(mut self, rh: &Handle, group_ctx: GroupContext) -> Result<Plan<Box<dyn Task>>> {
         debug!(group_ctx.log(), "SystemTestGroup.make_plan");
 
+        // The tests are executed such that every test comes after its prerequisites.
+        let dependency_skips =
+            self.dependency_skips(&group_ctx.group_base_name, &group_ctx.test_filter);
+        let test_names: Vec<_> = self
+            .top_level_test_names()
+            .into_iter()
+            .map(|name| name.map(String::from))
+            .collect();
+        self.tests = self
+            .dependencies
+            .sort(test_names.into_iter().zip(self.tests).collect());
+        let prerequisites = self.test_prerequisites();
+
+        // The group setup is only run if any of the selected tests depends on it.
+        let run_setup = self
+            .test_names()
+            .into_iter()
+            .any(|(name, uses_group_setup)| {
+                uses_group_setup
+                    && group_ctx
+                        .test_filter
+                        .matches(&group_ctx.group_base_name, name)
+                    && !dependency_skips.contains_key(name)
+            });
+
+        // The log budgets are checked once all tests have finished.
+        let check_log_budgets = run_setup && !self.log_budgets.is_empty();
+        if check_log_budgets {
+            let log_budgets = self.log_budgets.clone();
+            self = self.add_test(
+                TestFunction::new(log_budget::LOG_BUDGET_TASK_NAME, move |env| {
+                    log_budget::check_log_budgets(env, log_budgets)
+                })
+                .serial(),
+            );
+        }
+
         let mut compose_ctx = ComposeContext {
             rh,
             group_ctx: group_ctx.clone(),
             empty_task_counter: 0,
             logger: group_ctx.logger().clone(),
             timeout_per_test: self.effective_timeout_per_test(),
+            dependency_skips,
         };
 
         let uvms_logs_stream_task_id = TaskId::Test(String::from(UVMS_LOGS_STREAM_TASK_NAME));
@@ -619,6 +1282,7 @@ impl SystemTestGroup {
                 {
                     let logger = group_ctx.logger().clone();
                     let group_ctx = group_ctx.clone();
+                    let farm_keepalive = self.farm_keepalive;
                     move || {
                         let group_ctx = group_ctx.clone();
                         debug!(logger, ">>> keepalive");
@@ -632,31 +1296,23 @@ impl SystemTestGroup {
                                 );
                                 if let Ok(group_setup) = GroupSetup::try_read_attribute(&env) {
                                     let farm_url = env.get_farm_url().unwrap();
-                                    let farm = Farm::new(farm_url.clone(), env.logger());
-                                    let group_name = group_setup.infra_group_name;
-                                    if let Err(e) = farm.set_group_ttl(&group_name, GROUP_TTL) {
-                                        panic!(
-                                            "{}",
-                                            format!(
-                                                "Failed to keep group {} alive via endpoint {:?}: {:?}",
-                                                group_name, farm_url, e
-                                            )
-                                        )
-                                    };
-                                    debug!(
-                                        logger,
-                                        "Group {} TTL set to +{:?} from now (Farm endpoint: {:?})",
-                                        group_name,
-                                        GROUP_TTL,
-                                        farm_url
-                                    );
+                                    let farm = Farm::new(farm_url, env.logger());
+                                    // Runs until the task is killed at the end of the
+                                    // group; or until the maximum lifetime is exceeded.
+                                    GroupKeepalive::spawn(
+                                        farm,
+                                        group_setup.infra_group_name,
+                                        farm_keepalive,
+                                    )
+                                    .join();
+                                    return;
                                 } else {
                                     info!(logger, "Farm group not created yet.");
                                 }
                             } else {
                                 info!(logger, "Setup directory not created yet.");
                             }
-                            std::thread::sleep(KEEPALIVE_INTERVAL);
+                            std::thread::sleep(farm_keepalive.interval);
                         }
                     }
                 },
@@ -666,9 +1322,17 @@ impl SystemTestGroup {
             Box::from(EmptyTask::new(keepalive_task_id)) as Box<dyn Task>
         };
 
-        let setup_plan = {
+        let setup_plan = if !run_setup {
+            Plan::Leaf {
+                task: Box::from(SkipTestTask::new(TaskId::Test(String::from(
+                    SETUP_TASK_NAME,
+                )))),
+            }
+        } else {
             let logger = group_ctx.logger().clone();
             let group_ctx = group_ctx.clone();
+            let with_jumphost = group_ctx.with_jumphost;
+            let reuse_env = group_ctx.reuse_env;
             let setup_fn = self
                 .setup
                 .unwrap_or_else(|| panic!("setup function not specified for SystemTestGroup."));
@@ -677,7 +1341,20 @@ impl SystemTestGroup {
                 move || {
                     debug!(logger, ">>> setup_fn");
                     let env = ensure_setup_env(group_ctx);
-                    setup_fn(env.clone());
+                    if check_log_budgets {
+                        LogBudgetStartTime::now().write_attribute(&env);
+                    }
+                    if reuse_env {
+                        info!(logger, "Skipping the setup of the reused environment.");
+                    } else {
+                        capabilities::with_recorder(env.capability_recorder(), || {
+                            setup_fn(env.clone())
+                        });
+                    }
+                    // A reused environment keeps the jumphost it was set up with, if any.
+                    if with_jumphost && !reuse_env {
+                        env.deploy_jumphost().expect("Failed to deploy jumphost");
+                    }
                     SetupResult {}.write_attribute(&env);
                 },
                 &mut compose_ctx,
@@ -692,6 +1369,14 @@ impl SystemTestGroup {
             )
         };
 
+        let test_plans = compose_tests(
+            self.tests,
+            self.parallelism,
+            &self.serial_tests,
+            &prerequisites,
+            &mut compose_ctx,
+        );
+
         // TODO: k8s
         // normal case: no debugkeepalive, overall timeout is active
         if !group_ctx.debug_keepalive {
@@ -701,13 +1386,7 @@ impl SystemTestGroup {
                 vec![compose(
                     None,
                     EvalOrder::Sequential,
-                    std::iter::once(setup_plan)
-                        .chain(
-                            self.tests
-                                .into_iter()
-                                .map(|sub_group| sub_group.into_plan(&mut compose_ctx)),
-                        )
-                        .collect(),
+                    std::iter::once(setup_plan).chain(test_plans).collect(),
                     &mut compose_ctx,
                 )],
                 &mut compose_ctx,
@@ -769,13 +1448,7 @@ impl SystemTestGroup {
             vec![compose(
                 None,
                 EvalOrder::Sequential,
-                std::iter::once(setup_plan)
-                    .chain(
-                        self.tests
-                            .into_iter()
-                            .map(|sub_group| sub_group.into_plan(&mut compose_ctx)),
-                    )
-                    .collect(),
+                std::iter::once(setup_plan).chain(test_plans).collect(),
                 &mut compose_ctx,
             )],
             &mut compose_ctx,
@@ -799,36 +1472,104 @@ impl SystemTestGroup {
         // Preconditions that are already being checked:
         // 1. CLI arguments are sane
         // 2. Test / setup functions are not specified more than once in the group
-        let args = CliArgs::parse().validate()?;
+        let args = match CliArgs::try_parse() {
+            Ok(args) => args.validate()?,
+            // --help and --version are not errors.
+            Err(e) if !e.use_stderr() => e.exit(),
+            Err(e) => bail!(SystemTestGroupError::InvalidInvocation {
+                message: e.to_string()
+            }),
+        };
+        let config = args.driver_config(std::env::vars()).map_err(|e| {
+            SystemTestGroupError::InvalidInvocation {
+                message: e.to_string(),
+            }
+        })?;
         let is_parent_process = matches!(args.action, SystemTestsSubcommand::Run);
 
+        let test_filter = args.test_filter();
+        let selected_tests = self.selected_test_names(&args.group_base_name, &test_filter);
+        if args.list {
+            for test_name in selected_tests.iter() {
+                println!("{}", qualified_test_name(&args.group_base_name, test_name));
+            }
+            return Ok(Outcome::FromSubProcess);
+        }
+
         let group_ctx = GroupContext::new(
             args.group_dir.path.clone(),
             args.subproc_id(),
-            args.filter_tests,
+            test_filter,
             args.debug_keepalive,
             args.no_farm_keepalive || args.no_group_ttl,
-            args.group_base_name,
-            args.k8s,
+            args.group_base_name.clone(),
+            config.k8s,
+            args.with_jumphost,
+            args.reuse_env.is_some(),
+            config.log_level.into(),
         )?;
 
-        let with_farm = self.with_farm && !args.k8s;
+        let with_farm = self.with_farm && !config.k8s;
 
         if is_parent_process {
             let root_env = group_ctx.get_root_env().unwrap();
-            FarmBaseUrl::new_or_default(args.farm_base_url).write_attribute(&root_env);
-            if let Some(required_args) = args.required_host_features {
-                required_args.write_attribute(&root_env);
+            DriverInvocation::current().write_attribute(&root_env);
+            if !selected_tests.is_empty() && !self.declared_dependencies.is_empty() {
+                let resolved = self
+                    .declared_dependencies
+                    .verify(&get_dependency_path(""))
+                    .map_err(|report| SystemTestGroupError::PreconditionViolation {
+                        condition: "All declared dependencies are usable".to_string(),
+                        counterexample: report.to_string(),
+                    })?;
+                resolved.write_attribute(&root_env);
             }
-            if args.k8s {
-                InfraProvider::K8s.write_attribute(&root_env);
-            } else {
-                InfraProvider::Farm.write_attribute(&root_env);
-            }
-            if with_farm || args.k8s {
-                root_env.create_group_setup(group_ctx.group_base_name.clone(), args.no_group_ttl);
+            if let (Some(reused_env_dir), false) = (&args.reuse_env, selected_tests.is_empty()) {
+                reuse_setup_env(
+                    reused_env_dir,
+                    &group_ctx.group_dir.join(constants::GROUP_SETUP_DIR),
+                    group_ctx.logger(),
+                )
+                .map_err(|e| SystemTestGroupError::PreconditionViolation {
+                    condition: "The reused environment has a live Farm group".to_string(),
+                    counterexample: e.to_string(),
+                })?;
             }
+            self.prepare_root_env(&root_env, &args, &config, !selected_tests.is_empty());
             debug!(group_ctx.log(), "Created group context: {:?}", group_ctx);
+            if selected_tests.is_empty() {
+                info!(
+                    group_ctx.log(),
+                    "No tests selected, skipping the setup and all tests."
+                );
+                let dependency_skips =
+                    self.dependency_skips(&args.group_base_name, &group_ctx.test_filter);
+                let report = SystemGroupSummary {
+                    test_name: group_ctx.group_base_name.clone(),
+                    success: vec![],
+                    failure: vec![],
+                    timeout: vec![],
+                    skipped: once(SETUP_TASK_NAME)
+                        .chain(self.test_names().into_iter().map(|(name, _)| name))
+                        .map(|name| TaskReport {
+                            name: name.to_string(),
+                            runtime: 0.0,
+                            message: Some(dependency_skips.get(name).map_or_else(
+                                || "Task skipped.".to_string(),
+                                |reason| dependency_failure_message(reason),
+                            )),
+                            capabilities: vec![],
+                        })
+                        .collect(),
+                    jumphost: None,
+                };
+                if !args.no_summary_report {
+                    let event: log_events::LogEvent<_> = report.clone().into();
+                    event.emit_log(group_ctx.log());
+                    info!(group_ctx.log(), "Report:\n{}", report.pretty_print());
+                }
+                return Ok(Outcome::FromParentProcess(report));
+            }
         }
 
         // create the runtime that lives until this variable is dropped.
@@ -852,6 +1593,7 @@ impl SystemTestGroup {
                 .unwrap()
         };
 
+        let prerequisites = self.test_prerequisites();
         let plan = self.make_plan(runtime.handle(), group_ctx.clone())?;
         if is_parent_process {
             info!(group_ctx.log(), "Generated plan: {:?}", plan);
@@ -897,6 +1639,17 @@ impl SystemTestGroup {
                 let action_graph = ActionGraph::from_plan(static_plan);
                 info!(group_ctx.log(), "Generated action_graph");
 
+                let event_sink = match TestEventSink::new(
+                    &group_ctx.group_dir(),
+                    args.test_events_socket.as_deref(),
+                    group_ctx.logger(),
+                ) {
+                    Ok(sink) => Some(sink),
+                    Err(e) => {
+                        warn!(group_ctx.log(), "Test events are not recorded: {e}");
+                        None
+                    }
+                };
                 let mut task_scheduler = TaskScheduler {
                     scheduled_tasks: table,
                     action_graph,
@@ -905,6 +1658,9 @@ impl SystemTestGroup {
                     end_times: BTreeMap::new(),
                     log: group_ctx.logger(),
                     test_name: group_ctx.group_base_name.clone(),
+                    group_dir: group_ctx.group_dir(),
+                    event_sink,
+                    prerequisites,
                 };
                 info!(group_ctx.log(), "Generated task_scheduler");
                 task_scheduler.execute(args.debug_keepalive);
@@ -926,13 +1682,19 @@ impl SystemTestGroup {
                     info!(group_ctx.log(), "Report:\n{}", report.pretty_print());
                 }
 
-                if with_farm && !args.no_delete_farm_group {
+                if args.download_prometheus_data {
+                    Self::download_prometheus_data(group_ctx.clone());
+                }
+                // A reused environment may still be needed by later invocations.
+                let keep_env =
+                    args.keep_env || (args.reuse_env.is_some() && !args.teardown_reused_env);
+                if with_farm && !args.no_delete_farm_group && !keep_env {
                     Self::delete_farm_group(group_ctx.clone());
                 }
-                if args.k8s && !args.debug_keepalive {
+                if config.k8s && !args.debug_keepalive && !keep_env {
                     Self::delete_tnet(group_ctx.clone());
                 }
-                if report.failure.is_empty() {
+                if report.failure.is_empty() && report.timeout.is_empty() {
                     Ok(Outcome::FromParentProcess(report))
                 } else {
                     bail!(SystemTestGroupError::SystemTestFailure(report))
@@ -941,6 +1703,12 @@ impl SystemTestGroup {
             SystemTestsSubcommand::InteractiveMode => {
                 todo!()
             }
+            SystemTestsSubcommand::Jumphost => {
+                let env = group_ctx.get_setup_env()?;
+                let info = env.deploy_jumphost()?;
+                println!("{}", info.ssh_command());
+                Ok(Outcome::FromSubProcess)
+            }
             SystemTestsSubcommand::SpawnChild { task_id, .. } => {
                 info!(group_ctx.log(), "Executing sub-process-specific code ...");
                 let my_task = table.get(&task_id).unwrap();
@@ -950,18 +1718,57 @@ impl SystemTestGroup {
         }
     }
 
+    /// Runs the test group and terminates the process with a [DriverExitCode],
+    /// after printing a final [ResultLine] for consumption by CI.
     pub fn execute_from_args(self) -> Result<()> {
+        let start = std::time::Instant::now();
         let outcome = self.execute();
 
-        match outcome {
-            Ok(Outcome::FromSubProcess) => Ok(()),
-            Ok(Outcome::FromParentProcess(_)) => Ok(()),
-            Err(e) => {
-                // TODO: also print Kibana link in case of failure. This requires that the dyncamic
-                // group name (e.g., distributed_mainnet_test_bin--1673213252002) is made available
-                // to all SystemTestGroup instances, not only those used with Farm.
-                bail!("Tests failed: {e:?}");
+        let (exit_code, summary) = match &outcome {
+            Ok(Outcome::FromSubProcess) => return Ok(()),
+            Ok(Outcome::FromParentProcess(summary)) => {
+                (DriverExitCode::from_summary(summary), Some(summary))
+            }
+            Err(e) => match e.downcast_ref::<SystemTestGroupError>() {
+                Some(SystemTestGroupError::SystemTestFailure(summary)) => {
+                    (DriverExitCode::from_summary(summary), Some(summary))
+                }
+                _ => (DriverExitCode::from_anyhow(e), None),
+            },
+        };
+        println!(
+            "{}",
+            ResultLine {
+                summary,
+                duration: start.elapsed(),
             }
+        );
+
+        if let Err(e) = outcome {
+            // TODO: also print Kibana link in case of failure. This requires that the dyncamic
+            // group name (e.g., distributed_mainnet_test_bin--1673213252002) is made available
+            // to all SystemTestGroup instances, not only those used with Farm.
+            eprintln!("Tests failed: {e:?}");
+        }
+        if exit_code != DriverExitCode::Passed {
+            std::process::exit(exit_code.code());
+        }
+        Ok(())
+    }
+
+    /// Downloads a snapshot of the TSDB of the Prometheus VM, if one was
+    /// deployed by the setup. Failures are logged but do not fail the group.
+    fn download_prometheus_data(ctx: GroupContext) {
+        let env = ensure_setup_env(ctx);
+        if env.get_deployed_universal_vm(PROMETHEUS_VM_NAME).is_err() {
+            info!(
+                env.logger(),
+                "No prometheus VM deployed, skipping the download of its data."
+            );
+            return;
+        }
+        if let Err(e) = PrometheusVm::download_prometheus_data_dir(&env) {
+            warn!(env.logger(), "Failed to download prometheus data: {e:?}");
         }
     }
 
@@ -1132,3 +1939,206 @@ async fn stream_journald_from_cursor(
     }
     (cursor, Ok(()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::config::LogLevel;
+    use crate::driver::ic::NrOfVCPUs;
+    use crate::driver::pot_dsl::{matrix, t, ConfigFn};
+    use tempfile::TempDir;
+
+    const GROUP: &str = "my_group";
+
+    fn filter(include_pattern: Option<&str>, skip_pattern: Option<&str>) -> TestFilter {
+        TestFilter {
+            include_tests: None,
+            include_pattern: include_pattern.map(|p| Regex::new(p).unwrap()),
+            skip_pattern: skip_pattern.map(|p| Regex::new(p).unwrap()),
+        }
+    }
+
+    fn group() -> SystemTestGroup {
+        SystemTestGroup::new()
+            .with_setup(|_env: TestEnv| {})
+            .add_test(TestFunction::new("upgrade_test", |_env: TestEnv| {}))
+            .add_test(TestFunction::new("downgrade_test", |_env: TestEnv| {}))
+            .add_matrix(matrix(
+                "counter_smoke",
+                vec![("system", Box::new(|_env: TestEnv| {}) as ConfigFn)],
+                |_env| {},
+            ))
+    }
+
+    #[test]
+    fn patterns_match_qualified_test_names() {
+        let group = group();
+        let selected = |filter: TestFilter| group.selected_test_names(GROUP, &filter);
+        assert_eq!(
+            vec!["upgrade_test", "downgrade_test", "counter_smoke[system]"],
+            selected(TestFilter::default())
+        );
+        assert_eq!(
+            vec!["upgrade_test"],
+            selected(filter(Some("^my_group::up"), None))
+        );
+        assert_eq!(
+            vec!["upgrade_test", "downgrade_test"],
+            selected(filter(Some("_test$"), None))
+        );
+        assert!(selected(filter(Some("^other_group::"), None)).is_empty());
+        assert_eq!(
+            vec!["counter_smoke[system]"],
+            selected(filter(None, Some("grade")))
+        );
+        // Skipping takes precedence over inclusion.
+        assert_eq!(
+            vec!["downgrade_test"],
+            selected(filter(Some("grade"), Some("::up")))
+        );
+    }
+
+    #[test]
+    fn patterns_combine_with_substring_filter() {
+        let test_filter = TestFilter {
+            include_tests: Some("grade".to_string()),
+            ..filter(None, Some("upgrade"))
+        };
+        assert!(test_filter.matches(GROUP, "downgrade_test"));
+        assert!(!test_filter.matches(GROUP, "upgrade_test"));
+        assert!(!test_filter.matches(GROUP, "counter_smoke[system]"));
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        let args = CliArgs::try_parse_from([
+            "driver",
+            "--working-dir",
+            "/tmp",
+            "--group-base-name",
+            GROUP,
+            "--include-pattern",
+            "(unclosed",
+            "run",
+        ]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn command_line_overrides_driver_env_vars() {
+        let args = CliArgs::try_parse_from([
+            "driver",
+            "--working-dir",
+            "/tmp",
+            "--group-base-name",
+            GROUP,
+            "--log-level",
+            "debug",
+            "run",
+        ])
+        .unwrap();
+        let config = args
+            .driver_config(vec![
+                ("IC_TEST_DRIVER_LOG_LEVEL".to_string(), "error".to_string()),
+                ("IC_TEST_DRIVER_DEFAULT_VCPUS".to_string(), "2".to_string()),
+            ])
+            .unwrap();
+        assert_eq!(LogLevel::Debug, config.log_level);
+        assert_eq!(Some(NrOfVCPUs::new(2)), config.default_vm_resources.vcpus);
+    }
+
+    #[test]
+    fn empty_selection_allocates_no_resources() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", mockito::Matcher::Any)
+            .expect(0)
+            .create();
+        let farm_base_url = format!("{}/", server.url());
+        let args = CliArgs::try_parse_from([
+            "driver",
+            "--working-dir",
+            "/tmp",
+            "--group-base-name",
+            GROUP,
+            "--farm-base-url",
+            &farm_base_url,
+            "--include-pattern",
+            "non_existing_test",
+            "run",
+        ])
+        .unwrap();
+        let group = group();
+        let selected_tests = group.selected_test_names(GROUP, &args.test_filter());
+        assert!(selected_tests.is_empty());
+
+        let root_dir = TempDir::new().unwrap();
+        let root_env = TestEnv::new_without_duplicating_logger(
+            root_dir.path(),
+            slog::Logger::root(slog::Discard, slog::o!()),
+        );
+        let config = args.driver_config(vec![]).unwrap();
+        group.prepare_root_env(&root_env, &args, &config, !selected_tests.is_empty());
+
+        mock.assert();
+        assert_eq!(
+            InfraProvider::Farm,
+            InfraProvider::read_attribute(&root_env)
+        );
+        assert!(GroupSetup::try_read_attribute(&root_env).is_err());
+        assert_eq!(config, DriverConfig::read_attribute(&root_env));
+    }
+
+    fn group_with_dependencies() -> SystemTestGroup {
+        SystemTestGroup::new()
+            .with_setup(|_env: TestEnv| {})
+            .add_test(TestFunction::new("upgrade_test", |_env: TestEnv| {}))
+            .add_test(TestFunction::new("install_test", |_env: TestEnv| {}))
+            .add_test(TestFunction::new("smoke_test", |_env: TestEnv| {}))
+            .add_dependency(t("upgrade_test").after("install_test"))
+    }
+
+    #[test]
+    fn filtering_out_a_prerequisite_skips_its_dependents() {
+        let group = group_with_dependencies();
+        let test_filter = TestFilter {
+            include_tests: Some("upgrade".to_string()),
+            ..Default::default()
+        };
+        assert!(group.selected_test_names(GROUP, &test_filter).is_empty());
+        assert_eq!(
+            BTreeMap::from([(
+                "upgrade_test".to_string(),
+                "prerequisite install_test is not selected by the test filter".to_string()
+            )]),
+            group.dependency_skips(GROUP, &test_filter)
+        );
+        assert_eq!(
+            vec!["upgrade_test", "install_test"],
+            group.selected_test_names(GROUP, &filter(None, Some("smoke")))
+        );
+        assert_eq!(
+            vec!["smoke_test"],
+            group.selected_test_names(GROUP, &filter(None, Some("install")))
+        );
+        assert_eq!(
+            BTreeMap::from([(
+                TaskId::Test("upgrade_test".to_string()),
+                BTreeSet::from([TaskId::Test("install_test".to_string())])
+            )]),
+            group.test_prerequisites()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "dependency cycle install_test -> upgrade_test -> install_test")]
+    fn rejects_dependency_cycles_at_registration() {
+        group_with_dependencies().add_dependency(t("install_test").after("upgrade_test"));
+    }
+
+    #[test]
+    #[should_panic(expected = "which was not added with add_test()")]
+    fn rejects_dependencies_on_unknown_tests() {
+        group_with_dependencies().add_dependency(t("smoke_test").after("counter_smoke[system]"));
+    }
+}
@@ -2,16 +2,22 @@ use crate::driver::{
     bootstrap::{init_ic, setup_and_start_vms},
     farm::{Farm, HostFeature},
     node_software_version::NodeSoftwareVersion,
-    resource::{allocate_resources, get_resource_request, ResourceGroup},
+    resource::{
+        allocate_resources, allocate_subnets, get_resource_request, FarmVmAllocator, ResourceGroup,
+        ResourceRequest, SubnetRequest, VmSpec,
+    },
     test_env::{TestEnv, TestEnvAttribute},
     test_env_api::{HasRegistryLocalStore, HasTopologySnapshot},
     test_setup::{GroupSetup, InfraProvider},
 };
 use crate::k8s::tnet::TNet;
 use crate::util::block_on;
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use ic_prep_lib::prep_state_directory::IcPrepStateDir;
-use ic_prep_lib::{node::NodeSecretKeyStore, subnet_configuration::SubnetRunningState};
+use ic_prep_lib::{
+    node::NodeSecretKeyStore,
+    subnet_configuration::{get_default_config_params, SubnetRunningState},
+};
 use ic_regedit;
 use ic_registry_canister_api::IPv4Config;
 use ic_registry_subnet_features::{ChainKeyConfig, SubnetFeatures};
@@ -20,11 +26,13 @@ use ic_types::malicious_behaviour::MaliciousBehaviour;
 use ic_types::{Height, NodeId, PrincipalId};
 use phantom_newtype::AmountOf;
 use serde::{Deserialize, Serialize};
-use slog::info;
+use slog::{info, warn};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::net::{Ipv6Addr, SocketAddr};
 use std::path::Path;
+use std::sync::Mutex;
 use std::time::Duration;
 
 /// Builder object to declare a topology of an InternetComputer.
@@ -49,8 +57,12 @@ pub struct InternetComputer {
     /// GuestOS disk images of the latest-deployed mainnet version.
     pub with_mainnet_config: bool,
     pub api_boundary_nodes: Vec<Node>,
+    subnet_provisioning_retries: Option<usize>,
 }
 
+/// How often the VMs of a subnet are allocated anew if some fail to come up.
+pub const DEFAULT_SUBNET_PROVISIONING_RETRIES: usize = 1;
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Deserialize, Serialize)]
 pub enum VmAllocationStrategy {
     #[serde(rename = "distributeToArbitraryHost")]
@@ -222,7 +234,21 @@ impl InternetComputer {
         self
     }
 
+    /// Set how often the VMs of a subnet (or of the unassigned or API boundary
+    /// nodes) are allocated anew if some of them fail to come up. Defaults to
+    /// `DEFAULT_SUBNET_PROVISIONING_RETRIES`.
+    pub fn with_subnet_provisioning_retries(mut self, retries: usize) -> Self {
+        self.subnet_provisioning_retries = Some(retries);
+        self
+    }
+
     pub fn setup_and_start(&mut self, env: &TestEnv) -> Result<()> {
+        for (index, subnet) in self.subnets.iter().enumerate() {
+            subnet
+                .check_consensus_config()
+                .with_context(|| format!("Invalid configuration of subnet {index}"))?;
+        }
+
         // propagate required host features and resource settings to all vms
         let farm = Farm::from_test_env(env, "Internet Computer");
         for node in self
@@ -257,7 +283,12 @@ impl InternetComputer {
             tnet.write_attribute(env);
         }
 
-        let res_group = allocate_resources(&farm, &res_request, env)?;
+        let res_group = match InfraProvider::read_attribute(env) {
+            InfraProvider::Farm => {
+                self.allocate_subnets(&farm, &res_request, env, tempdir.path())?
+            }
+            InfraProvider::K8s => allocate_resources(&farm, &res_request, env)?,
+        };
         self.propagate_ip_addrs(&res_group);
         let init_ic = init_ic(
             self,
@@ -288,6 +319,98 @@ impl InternetComputer {
         Ok(())
     }
 
+    /// Allocates the VMs subnet by subnet, retrying subnets whose VMs fail to
+    /// come up with fresh nodes. Retried nodes get new secret key stores, and
+    /// hence new node IDs and VM names.
+    fn allocate_subnets(
+        &mut self,
+        farm: &Farm,
+        res_request: &ResourceRequest,
+        env: &TestEnv,
+        tempdir: &Path,
+    ) -> Result<ResourceGroup> {
+        // The VM configs are ordered like the nodes, see `get_resource_request`.
+        let mut vm_configs = res_request.vm_configs.iter().cloned();
+        let mut subnets: Vec<SubnetRequest> = self
+            .subnets
+            .iter()
+            .enumerate()
+            .map(|(index, subnet)| SubnetRequest {
+                name: format!("subnet {index}"),
+                vm_configs: vm_configs.by_ref().take(subnet.nodes.len()).collect(),
+            })
+            .collect();
+        subnets.push(SubnetRequest {
+            name: "unassigned nodes".to_string(),
+            vm_configs: vm_configs
+                .by_ref()
+                .take(self.unassigned_nodes.len())
+                .collect(),
+        });
+        subnets.push(SubnetRequest {
+            name: "API boundary nodes".to_string(),
+            vm_configs: vm_configs.collect(),
+        });
+        subnets.retain(|subnet| !subnet.vm_configs.is_empty());
+
+        // Maps the ID of every replaced node to the secret key store of its
+        // replacement.
+        let replacements: Mutex<HashMap<String, NodeSecretKeyStore>> = Default::default();
+        let respec = |vm_config: &VmSpec| -> Result<VmSpec> {
+            let mut replacements = replacements.lock().unwrap();
+            let sks = NodeSecretKeyStore::new(
+                tempdir.join(format!("node-replacement-{}", replacements.len())),
+            )?;
+            let fresh_vm_config = VmSpec {
+                name: sks.node_id.to_string(),
+                ..vm_config.clone()
+            };
+            replacements.insert(vm_config.name.clone(), sks);
+            Ok(fresh_vm_config)
+        };
+        let allocator = FarmVmAllocator {
+            farm,
+            group_name: &res_request.group_name,
+            primary_image: &res_request.primary_image,
+            env,
+        };
+        let (res_group, report) = allocate_subnets(
+            &allocator,
+            &res_request.group_name,
+            subnets,
+            self.subnet_provisioning_retries
+                .unwrap_or(DEFAULT_SUBNET_PROVISIONING_RETRIES),
+            &respec,
+            &env.logger(),
+        );
+        report.write_attribute(env);
+        for subnet in report.retried() {
+            warn!(
+                env.logger(),
+                "Provisioning {} took {} attempts: {:?}",
+                subnet.name,
+                subnet.attempts,
+                subnet.failures
+            );
+        }
+        report.check()?;
+
+        let mut replacements = replacements.into_inner().unwrap();
+        for node in self
+            .subnets
+            .iter_mut()
+            .flat_map(|subnet| subnet.nodes.iter_mut())
+            .chain(self.unassigned_nodes.iter_mut())
+            .chain(self.api_boundary_nodes.iter_mut())
+        {
+            // A node may have been replaced on several retries.
+            while let Some(sks) = replacements.remove(&node.id().to_string()) {
+                node.secret_key_store = Some(sks);
+            }
+        }
+        Ok(res_group)
+    }
+
     fn create_secret_key_stores(&mut self, tempdir: &Path) -> Result<()> {
         for node in self
             .subnets
@@ -432,6 +555,10 @@ impl InternetComputer {
 #[derive(Clone, PartialEq, Debug, Deserialize)]
 pub struct Subnet {
     pub default_vm_resources: VmResources,
+    #[serde(default)]
+    pub default_vm_topology: VmTopology,
+    #[serde(default)]
+    pub default_vm_class: VmClass,
     pub vm_allocation: Option<VmAllocationStrategy>,
     pub required_host_features: Vec<HostFeature>,
     pub nodes: Vec<Node>,
@@ -464,6 +591,8 @@ impl Subnet {
     pub fn new(subnet_type: SubnetType) -> Self {
         Self {
             default_vm_resources: Default::default(),
+            default_vm_topology: Default::default(),
+            default_vm_class: Default::default(),
             vm_allocation: Default::default(),
             required_host_features: vec![],
             nodes: vec![],
@@ -499,6 +628,26 @@ impl Subnet {
         self
     }
 
+    /// Set the CPU topology of the VMs of all implicitly constructed nodes,
+    /// e.g. `VmTopology::new(2, 8)` for 2 sockets with 8 cores each.
+    ///
+    /// Setting the CPU topology for explicitly constructed nodes has to be
+    /// via `Node::with_vm_topology`.
+    pub fn with_vm_topology(mut self, vm_topology: VmTopology) -> Self {
+        self.default_vm_topology = vm_topology;
+        self
+    }
+
+    /// Set the placement class of the VMs of all implicitly constructed nodes,
+    /// e.g. `VmClass::Performance` for benchmarks.
+    ///
+    /// Setting the class for explicitly constructed nodes has to be via
+    /// `Node::with_vm_class`.
+    pub fn with_vm_class(mut self, vm_class: VmClass) -> Self {
+        self.default_vm_class = vm_class;
+        self
+    }
+
     pub fn with_vm_allocation(mut self, vm_allocation: VmAllocationStrategy) -> Self {
         self.vm_allocation = Some(vm_allocation);
         self
@@ -576,11 +725,17 @@ impl Subnet {
             let default_vm_resources = subnet.default_vm_resources;
             let vm_allocation = subnet.vm_allocation.clone();
             let required_host_features = subnet.required_host_features.clone();
-            subnet.add_node(Node::new_with_settings(
-                default_vm_resources,
-                vm_allocation,
-                required_host_features,
-            ))
+            let vm_topology = subnet.default_vm_topology;
+            let vm_class = subnet.default_vm_class;
+            subnet.add_node(
+                Node::new_with_settings(
+                    default_vm_resources,
+                    vm_allocation,
+                    required_host_features,
+                )
+                .with_vm_topology(vm_topology)
+                .with_vm_class(vm_class),
+            )
         })
     }
 
@@ -604,16 +759,50 @@ impl Subnet {
         self
     }
 
+    /// Overrides the initial notary delay of the subnet record. Together with
+    /// `with_dkg_interval_length` this allows tests to produce catch-up
+    /// packages within minutes.
     pub fn with_initial_notary_delay(mut self, initial_notary_delay: Duration) -> Self {
         self.initial_notary_delay = Some(initial_notary_delay);
         self
     }
 
+    /// Overrides the DKG interval length of the subnet record, i.e. the
+    /// number of blocks between two summary blocks minus one.
     pub fn with_dkg_interval_length(mut self, dkg_interval_length: Height) -> Self {
         self.dkg_interval_length = Some(dkg_interval_length);
         self
     }
 
+    /// Checks that the consensus parameters of this subnet are compatible,
+    /// using the defaults of ic-prep for the ones that are not overridden.
+    ///
+    /// A DKG interval of `dkg_interval_length + 1` rounds must last at least
+    /// one initial notary delay when rounds progress at the unit delay.
+    /// Otherwise, the notary delay alone would stretch every interval and the
+    /// subnet would not make catch-up packages at the expected pace.
+    pub fn check_consensus_config(&self) -> Result<()> {
+        let defaults = get_default_config_params(self.subnet_type, self.nodes.len());
+        let unit_delay = self.unit_delay.unwrap_or(defaults.unit_delay);
+        let initial_notary_delay = self
+            .initial_notary_delay
+            .unwrap_or(defaults.initial_notary_delay);
+        let dkg_interval_length = self
+            .dkg_interval_length
+            .unwrap_or(defaults.dkg_interval_length);
+        let rounds = u32::try_from(dkg_interval_length.get().saturating_add(1)).unwrap_or(u32::MAX);
+        let dkg_interval = unit_delay.saturating_mul(rounds);
+        if dkg_interval < initial_notary_delay {
+            bail!(
+                "A DKG interval of {rounds} rounds at a unit delay of {unit_delay:?} lasts \
+                 {dkg_interval:?}, which is shorter than the initial notary delay of \
+                 {initial_notary_delay:?}. Increase the DKG interval length or decrease the \
+                 initial notary delay."
+            );
+        }
+        Ok(())
+    }
+
     pub fn with_features(mut self, features: SubnetFeatures) -> Self {
         self.features = Some(features);
         self
@@ -659,12 +848,16 @@ impl Subnet {
             let default_vm_resources = subnet.default_vm_resources;
             let vm_allocation = subnet.vm_allocation.clone();
             let required_host_features = subnet.required_host_features.clone();
+            let vm_topology = subnet.default_vm_topology;
+            let vm_class = subnet.default_vm_class;
             subnet.add_node(
                 Node::new_with_settings(
                     default_vm_resources,
                     vm_allocation,
                     required_host_features,
                 )
+                .with_vm_topology(vm_topology)
+                .with_vm_class(vm_class)
                 .with_malicious_behaviour(malicious_behaviour.clone()),
             )
         })
@@ -674,8 +867,12 @@ impl Subnet {
         let default_vm_resources = self.default_vm_resources;
         let vm_allocation = self.vm_allocation.clone();
         let required_host_features = self.required_host_features.clone();
+        let vm_topology = self.default_vm_topology;
+        let vm_class = self.default_vm_class;
         self.add_node(
             Node::new_with_settings(default_vm_resources, vm_allocation, required_host_features)
+                .with_vm_topology(vm_topology)
+                .with_vm_class(vm_class)
                 .with_ipv4_config(ipv4_config),
         )
     }
@@ -695,6 +892,8 @@ impl Default for Subnet {
     fn default() -> Self {
         Self {
             default_vm_resources: Default::default(),
+            default_vm_topology: Default::default(),
+            default_vm_class: Default::default(),
             vm_allocation: Default::default(),
             required_host_features: vec![],
             nodes: vec![],
@@ -750,10 +949,75 @@ impl VmResources {
     }
 }
 
+/// CPU topology of a VM. Unset fields are left to Farm.
+///
+/// Pinning a VM to a NUMA node keeps its vCPUs and memory on the cores of a
+/// single host socket, which reduces the noise in performance tests.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Default, Deserialize, Serialize)]
+pub struct VmTopology {
+    #[serde(rename = "sockets", skip_serializing_if = "Option::is_none", default)]
+    pub sockets: Option<u32>,
+    #[serde(
+        rename = "coresPerSocket",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub cores_per_socket: Option<u32>,
+    #[serde(
+        rename = "pinToNumaNode",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub pin_to_numa_node: Option<u32>,
+}
+
+impl VmTopology {
+    /// `sockets` sockets with `cores_per_socket` cores each.
+    pub fn new(sockets: u32, cores_per_socket: u32) -> Self {
+        Self {
+            sockets: Some(sockets),
+            cores_per_socket: Some(cores_per_socket),
+            pin_to_numa_node: None,
+        }
+    }
+
+    pub fn with_pin_to_numa_node(mut self, numa_node: u32) -> Self {
+        self.pin_to_numa_node = Some(numa_node);
+        self
+    }
+
+    pub fn is_unspecified(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Placement class of a VM on the Farm hosts.
+///
+/// `Performance` VMs get a dedicated host or pinned CPUs, so that benchmarks
+/// are not disturbed by noisy neighbours. What happens if Farm cannot provide
+/// this is decided by the `PerformancePlacementPolicy` of the group.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VmClass {
+    #[default]
+    Standard,
+    Performance,
+}
+
+impl VmClass {
+    pub fn is_standard(&self) -> bool {
+        *self == VmClass::Standard
+    }
+}
+
 /// A builder for the initial configuration of a node.
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Default, Deserialize)]
 pub struct Node {
     pub vm_resources: VmResources,
+    #[serde(default)]
+    pub vm_topology: VmTopology,
+    #[serde(default)]
+    pub vm_class: VmClass,
     pub vm_allocation: Option<VmAllocationStrategy>,
     pub required_host_features: Vec<HostFeature>,
     pub secret_key_store: Option<NodeSecretKeyStore>,
@@ -801,4 +1065,16 @@ impl Node {
         self.domain = Some(domain);
         self
     }
+
+    /// Requests the given CPU topology for the VM of this node.
+    pub fn with_vm_topology(mut self, vm_topology: VmTopology) -> Self {
+        self.vm_topology = vm_topology;
+        self
+    }
+
+    /// Requests the given placement class for the VM of this node.
+    pub fn with_vm_class(mut self, vm_class: VmClass) -> Self {
+        self.vm_class = vm_class;
+        self
+    }
 }
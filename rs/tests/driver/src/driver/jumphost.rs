@@ -0,0 +1,424 @@
+//! A jumphost is a universal VM attached to the group of a running test which
+//! engineers can SSH into to debug the IC under test from within its network.
+//!
+//! The jumphost comes with the tools shipped via its config directory (e.g.,
+//! `ic-admin`) installed in `~/bin` of the SSH user and a kubeconfig-style
+//! context file, describing the topology of the IC under test, in
+//! `~/.ic/config.yaml`. Tools not shipped by the driver (`dig`, `curl`, ...)
+//! are expected to be part of the primary image of the jumphost.
+use std::{
+    collections::BTreeMap,
+    fs,
+    net::Ipv6Addr,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Result};
+use ic_nns_constants::{
+    CYCLES_MINTING_CANISTER_ID, GOVERNANCE_CANISTER_ID, LEDGER_CANISTER_ID, LIFELINE_CANISTER_ID,
+    REGISTRY_CANISTER_ID, ROOT_CANISTER_ID, SNS_WASM_CANISTER_ID,
+};
+use ic_registry_subnet_type::SubnetType;
+use serde::{Deserialize, Serialize};
+use slog::{info, warn};
+
+use crate::driver::{
+    constants::SSH_USERNAME,
+    driver_setup::SSH_AUTHORIZED_PRIV_KEYS_DIR,
+    log_events,
+    resource::DiskImage,
+    test_env::{TestEnv, TestEnvAttribute},
+    test_env_api::{
+        get_dependency_path, HasPublicApiUrl, HasRegistryVersion, HasTopologySnapshot,
+        IcNodeContainer, IcNodeSnapshot, TopologySnapshot,
+    },
+    test_setup::GroupSetup,
+    universal_vm::{insert_file_to_config, UniversalVm, UniversalVms},
+};
+
+pub const JUMPHOST_VM_NAME: &str = "jumphost";
+
+/// Name of the context file in the config directory of the jumphost.
+const JUMPHOST_CONTEXT_FILE: &str = "ic-context.yaml";
+/// Path of the context file on the jumphost, relative to the home of the SSH user.
+const JUMPHOST_CONTEXT_PATH: &str = ".ic/config.yaml";
+const JUMPHOST_BIN_DIR: &str = "bin";
+
+const CONTEXT_API_VERSION: &str = "v1";
+const CONTEXT_KIND: &str = "IcConfig";
+
+// Be mindful when modifying this constant, as the event can be consumed by other parties.
+const JUMPHOST_CREATED_EVENT_NAME: &str = "jumphost_created_event";
+
+/// A builder for a jumphost.
+pub struct Jumphost {
+    universal_vm: UniversalVm,
+    tools: Vec<PathBuf>,
+}
+
+impl Default for Jumphost {
+    fn default() -> Self {
+        Jumphost::new(String::from(JUMPHOST_VM_NAME))
+    }
+}
+
+impl Jumphost {
+    pub fn new(name: String) -> Self {
+        Self {
+            universal_vm: UniversalVm::new(name),
+            tools: vec![],
+        }
+    }
+
+    /// Boots the jumphost from the given (tooling) image instead of the
+    /// default universal VM image.
+    pub fn with_primary_image(mut self, primary_image: DiskImage) -> Self {
+        self.universal_vm = self.universal_vm.with_primary_image(primary_image);
+        self
+    }
+
+    /// Installs the given executable in `~/bin` on the jumphost.
+    pub fn with_tool<P: AsRef<Path>>(mut self, tool: P) -> Self {
+        self.tools.push(tool.as_ref().to_path_buf());
+        self
+    }
+
+    /// Starts the jumphost, describing the topology of the IC under test in
+    /// its context file, and returns how to connect to it.
+    pub fn start(&self, env: &TestEnv) -> Result<JumphostInfo> {
+        let vm_name = &self.universal_vm.name;
+        let log = env.logger();
+        let config_dir = env.single_activate_script_config_dir(
+            vm_name,
+            &format!(
+                r#"#!/bin/sh
+HOME_DIR="/home/{SSH_USERNAME}"
+mkdir -p "$HOME_DIR/bin" "$(dirname "$HOME_DIR/{JUMPHOST_CONTEXT_PATH}")"
+for tool in /config/{JUMPHOST_BIN_DIR}/*; do
+  if [ -f "$tool" ]; then
+    cp "$tool" "$HOME_DIR/bin/"
+    chmod +x "$HOME_DIR/bin/$(basename "$tool")"
+  fi
+done
+cp /config/{JUMPHOST_CONTEXT_FILE} "$HOME_DIR/{JUMPHOST_CONTEXT_PATH}"
+chown -R {SSH_USERNAME} "$HOME_DIR/bin" "$(dirname "$HOME_DIR/{JUMPHOST_CONTEXT_PATH}")"
+"#
+            ),
+        )?;
+
+        let bin_dir = config_dir.join(JUMPHOST_BIN_DIR);
+        fs::create_dir_all(&bin_dir)?;
+        for tool in self.tools.iter() {
+            let Some(file_name) = tool.file_name() else {
+                bail!("Tool {tool:?} is not a file");
+            };
+            fs::copy(tool, bin_dir.join(file_name))?;
+        }
+
+        let group_name = GroupSetup::read_attribute(env).infra_group_name;
+        let context = JumphostContext::new(
+            group_name,
+            IcContext::from_topology(&env.topology_snapshot()),
+        );
+        insert_file_to_config(
+            config_dir.clone(),
+            JUMPHOST_CONTEXT_FILE,
+            context.to_yaml()?.as_bytes(),
+        )?;
+
+        self.universal_vm
+            .clone()
+            .with_config_dir(config_dir)
+            .start(env)?;
+
+        let vm = env.get_deployed_universal_vm(vm_name)?.get_vm()?;
+        let info = JumphostInfo {
+            name: vm_name.clone(),
+            ssh_user: SSH_USERNAME.to_string(),
+            ipv6: vm.ipv6,
+            ssh_priv_key_path: env
+                .get_path(SSH_AUTHORIZED_PRIV_KEYS_DIR)
+                .join(SSH_USERNAME),
+            context_path: format!("/home/{SSH_USERNAME}/{JUMPHOST_CONTEXT_PATH}"),
+        };
+        let event =
+            log_events::LogEvent::new(JUMPHOST_CREATED_EVENT_NAME.to_string(), info.ssh_command());
+        event.emit_log(&log);
+        info!(
+            log,
+            "Jumphost {} is reachable via: {}",
+            info.name,
+            info.ssh_command()
+        );
+        Ok(info)
+    }
+}
+
+/// How to connect to a deployed jumphost.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct JumphostInfo {
+    pub name: String,
+    pub ssh_user: String,
+    pub ipv6: Ipv6Addr,
+    pub ssh_priv_key_path: PathBuf,
+    /// Path of the context file on the jumphost.
+    pub context_path: String,
+}
+
+impl JumphostInfo {
+    pub fn ssh_command(&self) -> String {
+        format!(
+            "ssh -i {} {}@{}",
+            self.ssh_priv_key_path.display(),
+            self.ssh_user,
+            self.ipv6
+        )
+    }
+}
+
+impl TestEnvAttribute for JumphostInfo {
+    fn attribute_name() -> String {
+        String::from("jumphost")
+    }
+}
+
+/// Reads how to connect to the jumphost deployed in the environment at
+/// `env_dir`, if any.
+pub fn read_jumphost_info<P: AsRef<Path>>(env_dir: P) -> Option<JumphostInfo> {
+    let path = env_dir
+        .as_ref()
+        .join(JumphostInfo::attribute_name())
+        .with_extension("json");
+    let file = fs::File::open(path).ok()?;
+    serde_json::from_reader(file).ok()
+}
+
+pub trait HasJumphost {
+    /// Deploys a jumphost with `ic-admin` preinstalled, records how to connect
+    /// to it in this environment and logs the SSH connection details.
+    fn deploy_jumphost(&self) -> Result<JumphostInfo>;
+}
+
+impl HasJumphost for TestEnv {
+    fn deploy_jumphost(&self) -> Result<JumphostInfo> {
+        let mut jumphost = Jumphost::default();
+        if std::env::var_os("RUNFILES").is_some() {
+            let ic_admin = get_dependency_path("rs/tests/recovery/binaries/ic-admin");
+            if ic_admin.is_file() {
+                jumphost = jumphost.with_tool(fs::canonicalize(ic_admin)?);
+            } else {
+                warn!(
+                    self.logger(),
+                    "ic-admin not found at {ic_admin:?}, it is not installed on the jumphost"
+                );
+            }
+        }
+        let info = jumphost.start(self)?;
+        info.write_attribute(self);
+        Ok(info)
+    }
+}
+
+/// A kubeconfig-style description of the ICs reachable from a jumphost.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct JumphostContext {
+    #[serde(rename = "apiVersion")]
+    pub api_version: String,
+    pub kind: String,
+    pub current_context: String,
+    pub contexts: Vec<NamedIcContext>,
+}
+
+impl JumphostContext {
+    /// A context file containing the single context `name`, which is also the
+    /// current one.
+    pub fn new(name: String, context: IcContext) -> Self {
+        Self {
+            api_version: CONTEXT_API_VERSION.to_string(),
+            kind: CONTEXT_KIND.to_string(),
+            current_context: name.clone(),
+            contexts: vec![NamedIcContext { name, context }],
+        }
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct NamedIcContext {
+    pub name: String,
+    pub context: IcContext,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct IcContext {
+    pub registry_version: u64,
+    pub nns_subnet_id: String,
+    /// The URLs of the nodes of the NNS subnet, e.g., to be passed to
+    /// `ic-admin --nns-urls`.
+    pub nns_urls: Vec<String>,
+    /// NNS canister ids by canister name.
+    pub nns_canisters: BTreeMap<String, String>,
+    pub subnets: Vec<SubnetContext>,
+    pub unassigned_nodes: Vec<NodeContext>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SubnetContext {
+    pub id: String,
+    pub subnet_type: SubnetType,
+    pub nodes: Vec<NodeContext>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct NodeContext {
+    pub id: String,
+    pub url: String,
+}
+
+impl From<IcNodeSnapshot> for NodeContext {
+    fn from(node: IcNodeSnapshot) -> Self {
+        Self {
+            id: node.node_id.to_string(),
+            url: node.get_public_url().to_string(),
+        }
+    }
+}
+
+impl IcContext {
+    pub fn new(
+        registry_version: u64,
+        nns_subnet_id: String,
+        subnets: Vec<SubnetContext>,
+        unassigned_nodes: Vec<NodeContext>,
+    ) -> Self {
+        let nns_urls = subnets
+            .iter()
+            .filter(|subnet| subnet.id == nns_subnet_id)
+            .flat_map(|subnet| subnet.nodes.iter().map(|node| node.url.clone()))
+            .collect();
+        let nns_canisters = [
+            ("registry", REGISTRY_CANISTER_ID),
+            ("governance", GOVERNANCE_CANISTER_ID),
+            ("ledger", LEDGER_CANISTER_ID),
+            ("root", ROOT_CANISTER_ID),
+            ("cycles-minting", CYCLES_MINTING_CANISTER_ID),
+            ("lifeline", LIFELINE_CANISTER_ID),
+            ("sns-wasm", SNS_WASM_CANISTER_ID),
+        ]
+        .into_iter()
+        .map(|(name, id)| (name.to_string(), id.to_string()))
+        .collect();
+        Self {
+            registry_version,
+            nns_subnet_id,
+            nns_urls,
+            nns_canisters,
+            subnets,
+            unassigned_nodes,
+        }
+    }
+
+    pub fn from_topology(topology: &TopologySnapshot) -> Self {
+        let subnets = topology
+            .subnets()
+            .map(|subnet| SubnetContext {
+                id: subnet.subnet_id.to_string(),
+                subnet_type: subnet.subnet_type(),
+                nodes: subnet.nodes().map(NodeContext::from).collect(),
+            })
+            .collect();
+        Self::new(
+            topology.get_registry_version().get(),
+            topology.root_subnet_id().to_string(),
+            subnets,
+            topology.unassigned_nodes().map(NodeContext::from).collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, ip: &str) -> NodeContext {
+        NodeContext {
+            id: id.to_string(),
+            url: format!("http://[{ip}]:8080/"),
+        }
+    }
+
+    fn fabricated_context() -> JumphostContext {
+        let subnets = vec![
+            SubnetContext {
+                id: "nns-subnet".to_string(),
+                subnet_type: SubnetType::System,
+                nodes: vec![node("node-1", "2001:db8::1"), node("node-2", "2001:db8::2")],
+            },
+            SubnetContext {
+                id: "app-subnet".to_string(),
+                subnet_type: SubnetType::Application,
+                nodes: vec![node("node-3", "2001:db8::3")],
+            },
+        ];
+        JumphostContext::new(
+            "my-group".to_string(),
+            IcContext::new(
+                7,
+                "nns-subnet".to_string(),
+                subnets,
+                vec![node("node-4", "2001:db8::4")],
+            ),
+        )
+    }
+
+    #[test]
+    fn nns_urls_are_the_urls_of_the_nns_nodes() {
+        let context = &fabricated_context().contexts[0].context;
+        assert_eq!(
+            context.nns_urls,
+            vec!["http://[2001:db8::1]:8080/", "http://[2001:db8::2]:8080/"]
+        );
+        assert_eq!(
+            context.nns_canisters["registry"],
+            REGISTRY_CANISTER_ID.to_string()
+        );
+        assert_eq!(
+            context.nns_canisters["governance"],
+            GOVERNANCE_CANISTER_ID.to_string()
+        );
+    }
+
+    #[test]
+    fn context_file_is_kubeconfig_style_yaml() {
+        let yaml = fabricated_context().to_yaml().unwrap();
+        let value: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(value["apiVersion"], "v1");
+        assert_eq!(value["kind"], "IcConfig");
+        assert_eq!(value["current-context"], "my-group");
+        let context = &value["contexts"][0];
+        assert_eq!(context["name"], "my-group");
+        assert_eq!(context["context"]["registry-version"], 7);
+        assert_eq!(context["context"]["nns-subnet-id"], "nns-subnet");
+        assert_eq!(
+            context["context"]["subnets"][1]["subnet-type"],
+            "application"
+        );
+        assert_eq!(
+            context["context"]["unassigned-nodes"][0]["url"],
+            "http://[2001:db8::4]:8080/"
+        );
+    }
+
+    #[test]
+    fn context_file_roundtrips() {
+        let context = fabricated_context();
+        let parsed: JumphostContext = serde_yaml::from_str(&context.to_yaml().unwrap()).unwrap();
+        assert_eq!(parsed, context);
+    }
+}
@@ -0,0 +1,496 @@
+//! Log-level budgets for system tests.
+//!
+//! A test group can declare how many log entries of a given severity its nodes
+//! may emit, e.g. `SystemTestGroup::with_log_budget(Severity::Critical, 0)`.
+//! After all tests finished, the driver reads the journal of every IC node,
+//! tallies the entries emitted since the setup started and fails the
+//! `log_budget` task if any node exceeded a budget. Known-benign messages can
+//! be excluded via allowlist patterns, and a group can waive the check, in
+//! which case the reason is recorded in the budget report instead.
+//!
+//! Only entries written by IC components are counted: their severity is parsed
+//! from the message itself (`CRIT`, `ERRO`, ... in the text format and
+//! `"level":"CRIT"` in the JSON format), not from the journald priority.
+
+use crate::driver::test_env::{TestEnv, TestEnvAttribute};
+use crate::driver::test_env_api::{HasTopologySnapshot, IcNodeContainer, IcNodeSnapshot};
+use crate::util::block_on;
+use anyhow::{Context, Result};
+use regex::RegexSet;
+use serde::{Deserialize, Serialize};
+use slog::{info, warn};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Name of the task that checks the log budgets.
+pub const LOG_BUDGET_TASK_NAME: &str = "log_budget";
+
+/// Maximum number of offending lines kept per node.
+pub const MAX_OFFENDING_LINES_PER_NODE: usize = 5;
+
+const JOURNAL_GATEWAY_PORT: u16 = 19531;
+const JOURNAL_FETCH_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize, Serialize)]
+pub enum Severity {
+    Trace,
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl Severity {
+    /// Parses the severity of a log line emitted by an IC component.
+    ///
+    /// The first token that looks like a log level wins, so that a message
+    /// mentioning e.g. "ERROR" in an `INFO` entry is not miscounted.
+    pub fn from_log_line(line: &str) -> Option<Self> {
+        line.split(|c: char| !c.is_ascii_alphabetic())
+            .find_map(|token| match token {
+                "TRCE" | "TRACE" => Some(Severity::Trace),
+                "DEBG" | "DEBUG" => Some(Severity::Debug),
+                "INFO" => Some(Severity::Info),
+                "WARN" | "WARNING" => Some(Severity::Warning),
+                "ERRO" | "ERROR" => Some(Severity::Error),
+                "CRIT" | "CRITICAL" => Some(Severity::Critical),
+                _ => None,
+            })
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Severity::Trace => "TRACE",
+            Severity::Debug => "DEBUG",
+            Severity::Info => "INFO",
+            Severity::Warning => "WARNING",
+            Severity::Error => "ERROR",
+            Severity::Critical => "CRITICAL",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// The log budgets declared by a test group.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct LogBudgets {
+    /// Maximum number of entries per node at the given severity or above.
+    budgets: BTreeMap<Severity, usize>,
+    /// Patterns of known-benign messages, which are not counted.
+    allowlist: Vec<String>,
+    /// If set, exceeded budgets are reported but do not fail the group.
+    waiver: Option<String>,
+}
+
+impl LogBudgets {
+    pub fn is_empty(&self) -> bool {
+        self.budgets.is_empty()
+    }
+
+    /// Allows at most `max_entries` entries at `severity` or above per node.
+    pub fn with_budget(mut self, severity: Severity, max_entries: usize) -> Self {
+        self.budgets.insert(severity, max_entries);
+        self
+    }
+
+    /// Excludes messages matching the regular expression `pattern`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pattern` is not a valid regular expression.
+    pub fn with_allowlist_pattern(mut self, pattern: &str) -> Self {
+        if let Err(e) = regex::Regex::new(pattern) {
+            panic!("Invalid log allowlist pattern {pattern:?}: {e}");
+        }
+        self.allowlist.push(pattern.to_string());
+        self
+    }
+
+    /// Turns exceeded budgets into a warning, recording `reason` in the report.
+    pub fn with_waiver(mut self, reason: &str) -> Self {
+        self.waiver = Some(reason.to_string());
+        self
+    }
+
+    /// Tallies the given `(node, message)` entries per node.
+    pub fn tally<I, N, M>(&self, entries: I) -> LogTally
+    where
+        I: IntoIterator<Item = (N, M)>,
+        N: Into<String>,
+        M: AsRef<str>,
+    {
+        let allowlist = RegexSet::new(&self.allowlist).expect("Patterns were validated");
+        let min_budgeted = self.budgets.keys().next().copied();
+
+        let mut tally = LogTally::default();
+        for (node, message) in entries {
+            let message = message.as_ref();
+            let Some(severity) = Severity::from_log_line(message) else {
+                continue;
+            };
+            let node_tally = tally.nodes.entry(node.into()).or_default();
+            if allowlist.is_match(message) {
+                node_tally.allowlisted += 1;
+                continue;
+            }
+            *node_tally.counts.entry(severity).or_default() += 1;
+            if min_budgeted.is_some_and(|min| severity >= min)
+                && node_tally.offending_lines.len() < MAX_OFFENDING_LINES_PER_NODE
+            {
+                node_tally.offending_lines.push(message.to_string());
+            }
+        }
+        tally
+    }
+
+    /// Checks `tally` against the budgets.
+    pub fn evaluate(&self, tally: LogTally) -> LogBudgetReport {
+        let mut violations = vec![];
+        for (node, node_tally) in tally.nodes.iter() {
+            for (&severity, &max_entries) in self.budgets.iter() {
+                let actual = node_tally.count_at_least(severity);
+                if actual > max_entries {
+                    violations.push(BudgetViolation {
+                        node: node.clone(),
+                        severity,
+                        max_entries,
+                        actual,
+                    });
+                }
+            }
+        }
+        LogBudgetReport {
+            tally,
+            violations,
+            waiver: self.waiver.clone(),
+        }
+    }
+}
+
+/// Per-node counts of log entries.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct LogTally {
+    pub nodes: BTreeMap<String, NodeLogTally>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct NodeLogTally {
+    /// Number of (non-allowlisted) entries per severity.
+    pub counts: BTreeMap<Severity, usize>,
+    /// Number of entries that matched the allowlist.
+    pub allowlisted: usize,
+    /// The first few entries at a budgeted severity or above.
+    pub offending_lines: Vec<String>,
+}
+
+impl NodeLogTally {
+    pub fn count_at_least(&self, severity: Severity) -> usize {
+        self.counts.range(severity..).map(|(_, count)| count).sum()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct BudgetViolation {
+    pub node: String,
+    pub severity: Severity,
+    pub max_entries: usize,
+    pub actual: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct LogBudgetReport {
+    pub tally: LogTally,
+    pub violations: Vec<BudgetViolation>,
+    pub waiver: Option<String>,
+}
+
+impl LogBudgetReport {
+    pub fn is_within_budget(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Whether the check passes, i.e. is within budget or explicitly waived.
+    pub fn passes(&self) -> bool {
+        self.is_within_budget() || self.waiver.is_some()
+    }
+}
+
+impl Display for LogBudgetReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_within_budget() {
+            writeln!(f, "All nodes are within their log budgets.")?;
+        }
+        for v in self.violations.iter() {
+            writeln!(
+                f,
+                "Node {} logged {} entries at {} or above (budget: {})",
+                v.node, v.actual, v.severity, v.max_entries
+            )?;
+        }
+        for (node, node_tally) in self.tally.nodes.iter() {
+            if node_tally.offending_lines.is_empty() {
+                continue;
+            }
+            writeln!(f, "First offending entries of node {node}:")?;
+            for line in node_tally.offending_lines.iter() {
+                writeln!(f, "  {line}")?;
+            }
+        }
+        if let Some(reason) = &self.waiver {
+            writeln!(f, "Log budgets are waived: {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+impl TestEnvAttribute for LogBudgetReport {
+    fn attribute_name() -> String {
+        String::from("log_budget_report")
+    }
+}
+
+/// The time from which log entries count against the budgets.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LogBudgetStartTime {
+    pub micros_since_unix_epoch: u64,
+}
+
+impl LogBudgetStartTime {
+    pub fn now() -> Self {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("System time is before the Unix epoch");
+        Self {
+            micros_since_unix_epoch: since_epoch.as_micros() as u64,
+        }
+    }
+}
+
+impl TestEnvAttribute for LogBudgetStartTime {
+    fn attribute_name() -> String {
+        String::from("log_budget_start_time")
+    }
+}
+
+/// Reads the journals of all IC nodes and checks them against `budgets`.
+///
+/// The report is written to `env` as [LogBudgetReport]. Panics, failing the
+/// task, if a budget is exceeded and the budgets are not waived.
+pub fn check_log_budgets(env: TestEnv, budgets: LogBudgets) {
+    let logger = env.logger();
+    let since = LogBudgetStartTime::read_attribute(&env).micros_since_unix_epoch;
+    let topology = env.topology_snapshot();
+    let nodes: Vec<IcNodeSnapshot> = topology
+        .subnets()
+        .flat_map(|subnet| subnet.nodes())
+        .chain(topology.unassigned_nodes())
+        .chain(topology.api_boundary_nodes())
+        .collect();
+
+    let mut entries = vec![];
+    for node in nodes {
+        let messages =
+            block_on(fetch_journal_messages(node.get_ip_addr(), since)).unwrap_or_else(|e| {
+                panic!("Could not read the journal of node {}: {e:?}", node.node_id)
+            });
+        info!(
+            logger,
+            "Read {} journal entries of node {}",
+            messages.len(),
+            node.node_id
+        );
+        let node_id = node.node_id.to_string();
+        entries.extend(messages.into_iter().map(|m| (node_id.clone(), m)));
+    }
+
+    let report = budgets.evaluate(budgets.tally(entries));
+    report.write_attribute(&env);
+    if report.is_within_budget() {
+        info!(logger, "{report}");
+    } else if report.passes() {
+        warn!(logger, "{report}");
+    } else {
+        panic!("Log budgets exceeded:\n{report}");
+    }
+}
+
+#[derive(Deserialize)]
+struct JournalEntry {
+    #[serde(rename = "__REALTIME_TIMESTAMP")]
+    realtime_timestamp: String,
+    #[serde(rename = "MESSAGE")]
+    message: Option<serde_json::Value>,
+}
+
+/// Returns the messages of all journal entries of the node at `ip` that were
+/// written at or after `since` (in microseconds since the Unix epoch).
+async fn fetch_journal_messages(ip: IpAddr, since: u64) -> Result<Vec<String>> {
+    let url = format!("http://[{ip}]:{JOURNAL_GATEWAY_PORT}/entries");
+    let body = reqwest::Client::new()
+        .get(url)
+        .header("Accept", "application/json")
+        .timeout(JOURNAL_FETCH_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    let mut messages = vec![];
+    for line in body.lines().filter(|line| !line.trim().is_empty()) {
+        let entry: JournalEntry =
+            serde_json::from_str(line).with_context(|| format!("Invalid entry: {line}"))?;
+        if entry.realtime_timestamp.parse::<u64>()? < since {
+            continue;
+        }
+        match entry.message {
+            Some(serde_json::Value::String(message)) => messages.push(message),
+            // Non-UTF-8 messages are encoded as arrays of bytes.
+            Some(serde_json::Value::Array(bytes)) => {
+                let bytes: Vec<u8> = bytes
+                    .iter()
+                    .filter_map(|b| b.as_u64().map(|b| b as u8))
+                    .collect();
+                messages.push(String::from_utf8_lossy(&bytes).into_owned());
+            }
+            _ => {}
+        }
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANNED_LOG: &[(&str, &str)] = &[
+        (
+            "node-1",
+            "2024-10-16 12:00:00.000 INFO s:/n:node-1 Starting replica",
+        ),
+        (
+            "node-1",
+            "2024-10-16 12:00:01.000 WARN s:/n:node-1 Slow block",
+        ),
+        (
+            "node-1",
+            "2024-10-16 12:00:02.000 CRIT s:/n:node-1 State divergence",
+        ),
+        (
+            "node-1",
+            "2024-10-16 12:00:03.000 ERRO s:/n:node-1 Failed to connect to peer",
+        ),
+        (
+            "node-2",
+            "2024-10-16 12:00:00.000 INFO s:/n:node-2 Saw an ERROR, ignoring it",
+        ),
+        (
+            "node-2",
+            r#"{"msg":"Out of memory","level":"CRIT","ts":"2024-10-16"}"#,
+        ),
+        ("node-2", "kernel: eth0 link up"),
+    ];
+
+    #[test]
+    fn parses_severities() {
+        assert_eq!(
+            Some(Severity::Critical),
+            Severity::from_log_line("Oct 16 12:00:00.000 CRIT s:/n:x Boom")
+        );
+        assert_eq!(
+            Some(Severity::Critical),
+            Severity::from_log_line(r#"{"level":"CRIT","msg":"Boom"}"#)
+        );
+        assert_eq!(
+            Some(Severity::Info),
+            Severity::from_log_line("Oct 16 12:00:00.000 INFO An ERROR occurred elsewhere")
+        );
+        assert_eq!(None, Severity::from_log_line("kernel: eth0 link up"));
+    }
+
+    #[test]
+    fn tallies_per_node() {
+        let budgets = LogBudgets::default().with_budget(Severity::Error, 0);
+        let tally = budgets.tally(CANNED_LOG.iter().copied());
+
+        let node1 = &tally.nodes["node-1"];
+        assert_eq!(
+            BTreeMap::from([
+                (Severity::Info, 1),
+                (Severity::Warning, 1),
+                (Severity::Error, 1),
+                (Severity::Critical, 1)
+            ]),
+            node1.counts
+        );
+        assert_eq!(2, node1.count_at_least(Severity::Error));
+        assert_eq!(2, node1.offending_lines.len());
+
+        let node2 = &tally.nodes["node-2"];
+        assert_eq!(1, node2.count_at_least(Severity::Critical));
+        assert_eq!(1, node2.counts[&Severity::Info]);
+        assert_eq!(vec![CANNED_LOG[5].1.to_string()], node2.offending_lines);
+    }
+
+    #[test]
+    fn exceeded_budget_fails() {
+        let budgets = LogBudgets::default()
+            .with_budget(Severity::Critical, 0)
+            .with_budget(Severity::Error, 5);
+        let report = budgets.evaluate(budgets.tally(CANNED_LOG.iter().copied()));
+
+        assert!(!report.passes());
+        assert_eq!(
+            vec!["node-1", "node-2"],
+            report
+                .violations
+                .iter()
+                .map(|v| v.node.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert!(report
+            .violations
+            .iter()
+            .all(|v| v.severity == Severity::Critical && v.actual == 1));
+        assert!(report.to_string().contains("State divergence"));
+    }
+
+    #[test]
+    fn allowlisted_entries_are_not_counted() {
+        let budgets = LogBudgets::default()
+            .with_budget(Severity::Error, 0)
+            .with_allowlist_pattern("Failed to connect to peer")
+            .with_allowlist_pattern("State divergence|Out of memory");
+        let report = budgets.evaluate(budgets.tally(CANNED_LOG.iter().copied()));
+
+        assert!(report.is_within_budget());
+        assert_eq!(2, report.tally.nodes["node-1"].allowlisted);
+        assert_eq!(1, report.tally.nodes["node-2"].allowlisted);
+        assert!(report.tally.nodes["node-1"].offending_lines.is_empty());
+    }
+
+    #[test]
+    fn waiver_is_recorded_in_report() {
+        let budgets = LogBudgets::default()
+            .with_budget(Severity::Critical, 0)
+            .with_waiver("Known issue, see the linked ticket");
+        let report = budgets.evaluate(budgets.tally(CANNED_LOG.iter().copied()));
+
+        assert!(!report.is_within_budget());
+        assert!(report.passes());
+        assert!(report
+            .to_string()
+            .contains("Log budgets are waived: Known issue, see the linked ticket"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid log allowlist pattern")]
+    fn invalid_allowlist_pattern_panics() {
+        LogBudgets::default().with_allowlist_pattern("(unclosed");
+    }
+}
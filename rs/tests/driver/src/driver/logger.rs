@@ -43,11 +43,15 @@ fn multiplex_logger(l1: Logger, l2: Logger) -> Logger {
     slog::Logger::root(slog::Duplicate(l1, l2).fuse(), o!())
 }
 
-/// creates a slog::Logger that prints to standard out using an asynchronous drain
-pub fn new_stdout_logger() -> Logger {
+/// creates a slog::Logger that prints messages of at least `level` to standard out using an
+/// asynchronous drain
+pub fn new_stdout_logger(level: slog::Level) -> Logger {
     let decorator = slog_term::TermDecorator::new().build();
     let drain = SysTestLogFormatter::new(decorator).fuse();
-    slog::Logger::root(async_drain(drain), o!())
+    slog::Logger::root(
+        slog::LevelFilter::new(async_drain(drain), level).fuse(),
+        o!(),
+    )
 }
 
 struct SysTestLogFormatter<D> {
@@ -2,6 +2,9 @@ pub mod action_graph;
 pub mod asset_canister;
 pub mod bootstrap;
 pub mod boundary_node;
+pub mod canisters;
+pub mod capabilities;
+pub mod clock_skew;
 pub mod config;
 pub mod constants;
 pub mod context;
@@ -11,6 +14,8 @@ pub mod event;
 pub mod farm;
 pub mod group;
 pub mod ic;
+pub mod jumphost;
+pub mod log_budget;
 pub mod log_events;
 pub mod logger;
 pub mod nested;
@@ -21,7 +26,9 @@ pub mod pot_dsl;
 pub mod process;
 pub mod prometheus_vm;
 pub mod report;
+pub mod repro;
 pub mod resource;
+pub mod setup_fragments;
 pub mod simulate_network;
 pub mod subprocess_ipc;
 pub mod subprocess_task;
@@ -29,6 +36,8 @@ pub mod task;
 pub mod task_scheduler;
 pub mod test_env;
 pub mod test_env_api;
+pub mod test_events;
 pub mod test_setup;
 pub mod timeout;
 pub mod universal_vm;
+pub mod vector_vm;
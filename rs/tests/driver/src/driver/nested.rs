@@ -1,5 +1,6 @@
+use crate::driver::ic::VmResources;
 use crate::driver::port_allocator::AddrType;
-use crate::driver::resource::AllocatedVm;
+use crate::driver::resource::{AllocatedVm, DiskImage};
 use crate::driver::test_env::TestEnv;
 use crate::driver::test_env_api::get_ssh_session_from_env;
 use crate::driver::test_env_api::*;
@@ -24,14 +25,46 @@ pub const NESTED_VMS_DIR: &str = "nested_vms";
 pub const NESTED_VM_PATH: &str = "vm.json";
 pub const NESTED_CONFIGURED_IMAGE_PATH: &str = "config.img.zst";
 pub const NESTED_NETWORK_PATH: &str = "ips.json";
+pub const NESTED_CONSOLE_URL_PATH: &str = "console_url.json";
 
+/// A VM with nested virtualization enabled, which boots SetupOS to install
+/// HostOS, which in turn runs GuestOS.
+#[derive(Clone, Debug)]
 pub struct NestedNode {
     pub name: String,
+    /// Overrides the default VM resources of the group.
+    pub vm_resources: VmResources,
+    /// The image the VM boots from. By default, an empty disk SetupOS
+    /// installs HostOS onto.
+    pub primary_image: Option<DiskImage>,
+    /// A zstd-compressed SetupOS image that is attached as bootable USB disk
+    /// instead of one configured from `ENV_DEPS__DEV_SETUPOS_IMG_TAR_ZST`.
+    pub setupos_image: Option<PathBuf>,
 }
 
 impl NestedNode {
     pub fn new(name: String) -> Self {
-        NestedNode { name }
+        NestedNode {
+            name,
+            vm_resources: VmResources::default(),
+            primary_image: None,
+            setupos_image: None,
+        }
+    }
+
+    pub fn with_vm_resources(mut self, vm_resources: VmResources) -> Self {
+        self.vm_resources = vm_resources;
+        self
+    }
+
+    pub fn with_primary_image(mut self, primary_image: DiskImage) -> Self {
+        self.primary_image = Some(primary_image);
+        self
+    }
+
+    pub fn with_setupos_image(mut self, setupos_image: PathBuf) -> Self {
+        self.setupos_image = Some(setupos_image);
+        self
     }
 }
 
@@ -67,6 +100,21 @@ impl NestedVm {
 
         self.env.read_json_object(ip_path)
     }
+
+    /// The IPv6 address of the VM, i.e. of HostOS. The address of the nested
+    /// GuestOS is in `get_nested_network()`.
+    pub fn get_ipv6(&self) -> Result<Ipv6Addr> {
+        Ok(self.get_vm()?.ipv6)
+    }
+
+    /// The URL of the VNC and serial console of the VM, recorded when the VM
+    /// was started.
+    pub fn get_console_url(&self) -> Result<Url> {
+        let rel_dir: PathBuf = [NESTED_VMS_DIR, &self.name].iter().collect();
+        let console_url_path = rel_dir.join(NESTED_CONSOLE_URL_PATH);
+
+        self.env.read_json_object(console_url_path)
+    }
 }
 
 impl HasTestEnv for NestedVm {
@@ -87,6 +135,8 @@ pub trait NestedVms {
     fn get_all_nested_vms(&self) -> Result<Vec<NestedVm>>;
 
     fn write_nested_vm(&self, name: &str, vm: &AllocatedVm) -> Result<()>;
+
+    fn write_nested_vm_console_url(&self, name: &str, url: &Url) -> Result<()>;
 }
 
 impl NestedVms for TestEnv {
@@ -159,6 +209,11 @@ impl NestedVms for TestEnv {
 
         Ok(())
     }
+
+    fn write_nested_vm_console_url(&self, name: &str, url: &Url) -> Result<()> {
+        let vm_path: PathBuf = [NESTED_VMS_DIR, name].iter().collect();
+        self.write_json_object(vm_path.join(NESTED_CONSOLE_URL_PATH), url)
+    }
 }
 
 impl SshSession for NestedVm {
@@ -66,6 +66,7 @@ impl<T> Plan<T> {
             } => match ordering {
                 EvalOrder::Sequential => 1,
                 EvalOrder::Parallel => children.len(),
+                EvalOrder::Bounded(max_parallel) => children.len().min(*max_parallel),
             },
             Plan::Leaf { .. } => 1,
         }
@@ -136,4 +137,8 @@ impl<T> Plan<T> {
 pub enum EvalOrder {
     Sequential,
     Parallel,
+    /// At most the given number of children run at the same time: the children
+    /// are dealt round-robin onto that many lanes, which run in parallel and
+    /// each run their children sequentially.
+    Bounded(usize),
 }
@@ -1,4 +1,10 @@
-use std::{fmt::Display, panic::UnwindSafe};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    iter::once,
+    panic::{RefUnwindSafe, UnwindSafe},
+    sync::Arc,
+};
 
 use crate::driver::test_env::TestEnv;
 use serde::{Deserialize, Serialize};
@@ -9,6 +15,286 @@ impl<T: FnOnce(TestEnv) + UnwindSafe + Send + Sync + 'static> PotSetupFn for T {
 pub trait SysTestFn: FnOnce(TestEnv) + UnwindSafe + Send + Sync + 'static {}
 impl<T: FnOnce(TestEnv) + UnwindSafe + Send + Sync + 'static> SysTestFn for T {}
 
+/// A test body shared by all cells of a [Matrix]. Called once per cell, each
+/// time with the environment of that cell.
+pub trait MatrixTestFn: Fn(TestEnv) + RefUnwindSafe + Send + Sync + 'static {}
+impl<T: Fn(TestEnv) + RefUnwindSafe + Send + Sync + 'static> MatrixTestFn for T {}
+
+/// Sets up the IC configuration of one cell of a [Matrix].
+pub type ConfigFn = Box<dyn PotSetupFn>;
+
+/// Prefix of the names of the setup tasks of matrix cells.
+pub const MATRIX_SETUP_PREFIX: &str = "setup::";
+
+/// Returns the qualified name of the test of the matrix `name` run against the
+/// configuration labeled `label`, e.g. `counter_smoke[system]`.
+pub fn matrix_test_name(name: &str, label: &str) -> String {
+    format!("{name}[{label}]")
+}
+
+/// Splits a qualified matrix test name into matrix name and configuration
+/// label. Returns `None` for names of plain tests and of setup tasks.
+pub fn parse_matrix_test_name(test_name: &str) -> Option<(&str, &str)> {
+    if test_name.starts_with(MATRIX_SETUP_PREFIX) {
+        return None;
+    }
+    let (name, label) = test_name.strip_suffix(']')?.split_once('[')?;
+    (!name.is_empty() && !label.is_empty()).then_some((name, label))
+}
+
+/// The same test body run against multiple IC configurations. Expands into one
+/// independent cell per configuration, each with its own setup task,
+/// environment and resources; and a test named e.g. `counter_smoke[system]`.
+pub struct Matrix {
+    name: String,
+    configs: Vec<(String, ConfigFn)>,
+    test_fn: Arc<dyn MatrixTestFn>,
+}
+
+/// Creates a [Matrix] running `test_fn` once against each of the labeled
+/// `configs`.
+pub fn matrix<F: MatrixTestFn>(name: &str, configs: Vec<(&str, ConfigFn)>, test_fn: F) -> Matrix {
+    Matrix {
+        name: name.to_string(),
+        configs: configs
+            .into_iter()
+            .map(|(label, config)| (label.to_string(), config))
+            .collect(),
+        test_fn: Arc::new(test_fn),
+    }
+}
+
+/// One cell of an expanded [Matrix].
+pub struct MatrixCell {
+    /// Qualified test name, e.g. `counter_smoke[system]`.
+    pub test_name: String,
+    /// Name of the setup task (and of its environment directory).
+    pub setup_name: String,
+    pub setup_fn: ConfigFn,
+    pub test_fn: Box<dyn SysTestFn>,
+}
+
+impl Matrix {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The qualified test names of all cells, in configuration order.
+    pub fn test_names(&self) -> Vec<String> {
+        self.configs
+            .iter()
+            .map(|(label, _)| matrix_test_name(&self.name, label))
+            .collect()
+    }
+
+    /// Checks that the matrix has at least one configuration; that names and
+    /// labels are non-empty and free of brackets and path separators; and that
+    /// labels (and thus qualified test names) are unique.
+    pub fn validate(&self) -> Result<(), String> {
+        let is_valid = |s: &str| !s.is_empty() && !s.contains(['[', ']', '/']);
+        if !is_valid(&self.name) {
+            return Err(format!("Invalid matrix name: {:?}", self.name));
+        }
+        if self.configs.is_empty() {
+            return Err(format!("Matrix {} has no configurations", self.name));
+        }
+        let mut labels = BTreeSet::new();
+        for (label, _) in self.configs.iter() {
+            if !is_valid(label) {
+                return Err(format!("Invalid label {:?} in matrix {}", label, self.name));
+            }
+            if !labels.insert(label) {
+                return Err(format!(
+                    "Duplicate label {:?} in matrix {}",
+                    label, self.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Expands the matrix into one cell per configuration, all sharing the test
+    /// body.
+    pub fn expand(self) -> Vec<MatrixCell> {
+        let Matrix {
+            name,
+            configs,
+            test_fn,
+        } = self;
+        configs
+            .into_iter()
+            .map(|(label, setup_fn)| {
+                let test_name = matrix_test_name(&name, &label);
+                let test_fn = test_fn.clone();
+                MatrixCell {
+                    setup_name: format!("{MATRIX_SETUP_PREFIX}{test_name}"),
+                    test_name,
+                    setup_fn,
+                    test_fn: Box::new(move |env| test_fn(env)),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Declares that a test of a group builds on the state created by other tests
+/// of the same group, e.g. `t("upgrade").after("install")`.
+#[derive(Clone, Debug)]
+pub struct TestDependency {
+    test: String,
+    prerequisites: Vec<String>,
+}
+
+/// Starts the declaration of the dependencies of the test `test`.
+pub fn t(test: &str) -> TestDependency {
+    TestDependency {
+        test: test.to_string(),
+        prerequisites: vec![],
+    }
+}
+
+impl TestDependency {
+    /// The test only runs after `prerequisite` has passed. If `prerequisite`
+    /// fails, times out or is skipped, the test is skipped.
+    pub fn after(mut self, prerequisite: &str) -> Self {
+        self.prerequisites.push(prerequisite.to_string());
+        self
+    }
+
+    pub fn test(&self) -> &str {
+        &self.test
+    }
+
+    pub fn prerequisites(&self) -> &[String] {
+        &self.prerequisites
+    }
+}
+
+/// The dependencies declared between the tests of a group. Acyclic by
+/// construction.
+#[derive(Clone, Debug, Default)]
+pub struct TestDependencies {
+    prerequisites: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl TestDependencies {
+    pub fn is_empty(&self) -> bool {
+        self.prerequisites.is_empty()
+    }
+
+    /// The direct prerequisites of the test `test`.
+    pub fn prerequisites_of(&self, test: &str) -> impl Iterator<Item = &str> {
+        self.prerequisites
+            .get(test)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// Adds the given dependency. Fails, leaving the dependencies unchanged, if
+    /// it would introduce a cycle.
+    pub fn add(&mut self, dependency: TestDependency) -> Result<(), String> {
+        let TestDependency {
+            test,
+            prerequisites,
+        } = dependency;
+        let mut updated = self.clone();
+        for prerequisite in prerequisites {
+            if let Some(path) = updated.path(&prerequisite, &test) {
+                return Err(format!(
+                    "Declaring that test {} runs after {} introduces the dependency cycle {}",
+                    test,
+                    prerequisite,
+                    once(test.as_str())
+                        .chain(path)
+                        .collect::<Vec<_>>()
+                        .join(" -> ")
+                ));
+            }
+            updated
+                .prerequisites
+                .entry(test.clone())
+                .or_default()
+                .insert(prerequisite);
+        }
+        *self = updated;
+        Ok(())
+    }
+
+    /// Returns a chain of prerequisites leading from `from` to `to`, both
+    /// included, if `from` (transitively) depends on `to`.
+    fn path<'a>(&'a self, from: &'a str, to: &str) -> Option<Vec<&'a str>> {
+        if from == to {
+            return Some(vec![from]);
+        }
+        self.prerequisites_of(from).find_map(|prerequisite| {
+            self.path(prerequisite, to)
+                .map(|path| once(from).chain(path).collect())
+        })
+    }
+
+    /// Sorts `items`, some of which are named tests, such that every test comes
+    /// after its prerequisites. Otherwise, the order of the items is kept: an
+    /// item is only moved behind the items it (transitively) depends on, so
+    /// tests that are independent of each other stay independent.
+    pub fn sort<T>(&self, items: Vec<(Option<String>, T)>) -> Vec<T> {
+        let mut pending = items;
+        let mut done = BTreeSet::new();
+        let mut sorted = Vec::with_capacity(pending.len());
+        while !pending.is_empty() {
+            // Prerequisites that are not among the items impose no order.
+            let idx = pending
+                .iter()
+                .position(|(name, _)| {
+                    name.as_deref().map_or(true, |name| {
+                        self.prerequisites_of(name).all(|prerequisite| {
+                            done.contains(prerequisite)
+                                || !pending
+                                    .iter()
+                                    .any(|(other, _)| other.as_deref() == Some(prerequisite))
+                        })
+                    })
+                })
+                .expect("test dependencies are acyclic");
+            let (name, item) = pending.remove(idx);
+            done.extend(name);
+            sorted.push(item);
+        }
+        sorted
+    }
+
+    /// Returns the tests among `tests` which are selected, but must be skipped
+    /// since one of their prerequisites is not selected or is skipped itself;
+    /// along with the reason. `tests` must be sorted such that every test
+    /// comes after its prerequisites.
+    pub fn skipped_dependents(
+        &self,
+        tests: &[&str],
+        is_selected: impl Fn(&str) -> bool,
+    ) -> BTreeMap<String, String> {
+        let mut skipped = BTreeMap::new();
+        for test in tests.iter().filter(|test| is_selected(test)) {
+            let reason = self.prerequisites_of(test).find_map(|prerequisite| {
+                if skipped.contains_key(prerequisite) {
+                    Some(format!(
+                        "prerequisite {prerequisite} is skipped due to dependency failure"
+                    ))
+                } else if !is_selected(prerequisite) {
+                    Some(format!(
+                        "prerequisite {prerequisite} is not selected by the test filter"
+                    ))
+                } else {
+                    None
+                }
+            });
+            if let Some(reason) = reason {
+                skipped.insert(test.to_string(), reason);
+            }
+        }
+        skipped
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Default, Deserialize, Serialize)]
 pub struct TestPath(Vec<String>);
 
@@ -17,3 +303,218 @@ impl Display for TestPath {
         write!(f, "{}", self.0.join("::"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::group::TestFilter;
+    use assert_matches::assert_matches;
+
+    fn config() -> ConfigFn {
+        Box::new(|_env| {})
+    }
+
+    fn counter_matrix(labels: &[&str]) -> Matrix {
+        matrix(
+            "counter_smoke",
+            labels.iter().map(|label| (*label, config())).collect(),
+            |_env| {},
+        )
+    }
+
+    #[test]
+    fn expands_into_one_cell_per_config() {
+        let matrix = counter_matrix(&["application", "system"]);
+        assert_eq!(Ok(()), matrix.validate());
+        assert_eq!(
+            vec!["counter_smoke[application]", "counter_smoke[system]"],
+            matrix.test_names()
+        );
+
+        let cells = matrix.expand();
+        assert_eq!(
+            vec![
+                (
+                    "counter_smoke[application]",
+                    "setup::counter_smoke[application]"
+                ),
+                ("counter_smoke[system]", "setup::counter_smoke[system]"),
+            ],
+            cells
+                .iter()
+                .map(|cell| (cell.test_name.as_str(), cell.setup_name.as_str()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn expanded_names_are_unique() {
+        let cells = counter_matrix(&["1-node", "4-node", "13-node"]).expand();
+        let test_names: BTreeSet<_> = cells.iter().map(|cell| &cell.test_name).collect();
+        let setup_names: BTreeSet<_> = cells.iter().map(|cell| &cell.setup_name).collect();
+        assert_eq!(3, test_names.len());
+        assert_eq!(3, setup_names.len());
+        assert!(test_names.is_disjoint(&setup_names));
+    }
+
+    #[test]
+    fn rejects_invalid_matrices() {
+        assert_matches!(
+            counter_matrix(&["system", "system"]).validate(),
+            Err(msg) if msg.contains("Duplicate label")
+        );
+        assert_matches!(
+            counter_matrix(&["sys[tem]"]).validate(),
+            Err(msg) if msg.contains("Invalid label")
+        );
+        assert_matches!(
+            counter_matrix(&[]).validate(),
+            Err(msg) if msg.contains("no configurations")
+        );
+        assert_matches!(
+            matrix("", vec![("system", config())], |_env| {}).validate(),
+            Err(msg) if msg.contains("Invalid matrix name")
+        );
+    }
+
+    #[test]
+    fn parses_matrix_test_names() {
+        for label in ["application", "system", "4-node"] {
+            let test_name = matrix_test_name("counter_smoke", label);
+            assert_eq!(
+                Some(("counter_smoke", label)),
+                parse_matrix_test_name(&test_name)
+            );
+        }
+        assert_eq!(None, parse_matrix_test_name("plain_test"));
+        assert_eq!(None, parse_matrix_test_name("setup::counter_smoke[system]"));
+        assert_eq!(None, parse_matrix_test_name("counter_smoke[]"));
+    }
+
+    #[test]
+    fn filter_matches_cells_independently() {
+        // The `--include-tests` filter applies to qualified test names, so it can
+        // select a single cell, all cells of a matrix, or none.
+        let test_names = counter_matrix(&["application", "system"]).test_names();
+        let selected = |filter: &str| {
+            test_names
+                .iter()
+                .filter(|name| {
+                    TestFilter {
+                        include_tests: Some(filter.to_string()),
+                        ..Default::default()
+                    }
+                    .matches("group", name)
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["counter_smoke[system]"], selected("[system]"));
+        assert_eq!(test_names, selected("counter_smoke"));
+        assert!(selected("other_test").is_empty());
+    }
+
+    fn dependencies(declarations: Vec<TestDependency>) -> TestDependencies {
+        let mut dependencies = TestDependencies::default();
+        for dependency in declarations {
+            dependencies.add(dependency).unwrap();
+        }
+        dependencies
+    }
+
+    #[test]
+    fn rejects_dependency_cycles() {
+        let mut dependencies = dependencies(vec![
+            t("upgrade").after("install"),
+            t("downgrade").after("upgrade"),
+        ]);
+        assert_eq!(
+            Err("Declaring that test install runs after downgrade introduces the dependency cycle install -> downgrade -> upgrade -> install".to_string()),
+            dependencies.add(t("install").after("downgrade"))
+        );
+        assert_matches!(
+            dependencies.add(t("install").after("install")),
+            Err(msg) if msg.ends_with("install -> install")
+        );
+        // A rejected declaration leaves the dependencies unchanged.
+        assert_matches!(
+            dependencies.add(t("uninstall").after("downgrade").after("uninstall")),
+            Err(_)
+        );
+        assert_eq!(0, dependencies.prerequisites_of("uninstall").count());
+        assert_eq!(
+            vec!["upgrade"],
+            dependencies
+                .prerequisites_of("downgrade")
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn sorts_tests_after_their_prerequisites() {
+        let dependencies = dependencies(vec![
+            t("upgrade").after("install"),
+            t("downgrade").after("upgrade").after("install"),
+        ]);
+        let sorted = |names: &[&str]| {
+            dependencies.sort(
+                names
+                    .iter()
+                    .map(|name| (Some(name.to_string()), *name))
+                    .collect(),
+            )
+        };
+        assert_eq!(
+            vec!["smoke", "install", "upgrade", "downgrade", "other"],
+            sorted(&["smoke", "downgrade", "upgrade", "install", "other"])
+        );
+        // Tests that are already sorted keep their order.
+        assert_eq!(
+            vec!["install", "smoke", "upgrade", "downgrade"],
+            sorted(&["install", "smoke", "upgrade", "downgrade"])
+        );
+        // Items without a name and prerequisites which are not among the items
+        // impose no order.
+        assert_eq!(
+            vec![None, Some("upgrade")],
+            dependencies.sort(vec![
+                (None, None),
+                (Some("upgrade".to_string()), Some("upgrade"))
+            ])
+        );
+    }
+
+    #[test]
+    fn skips_dependents_of_tests_not_selected() {
+        let dependencies = dependencies(vec![
+            t("upgrade").after("install"),
+            t("downgrade").after("upgrade"),
+        ]);
+        let tests = ["install", "upgrade", "downgrade", "smoke"];
+        let skipped = |selected: &[&str]| {
+            dependencies.skipped_dependents(&tests, |name| selected.contains(&name))
+        };
+        assert!(skipped(&tests).is_empty());
+        assert_eq!(
+            BTreeMap::from([
+                (
+                    "upgrade".to_string(),
+                    "prerequisite install is not selected by the test filter".to_string()
+                ),
+                (
+                    "downgrade".to_string(),
+                    "prerequisite upgrade is skipped due to dependency failure".to_string()
+                ),
+            ]),
+            skipped(&["upgrade", "downgrade", "smoke"])
+        );
+        assert_eq!(
+            vec!["downgrade"],
+            skipped(&["install", "downgrade"])
+                .into_keys()
+                .collect::<Vec<_>>()
+        );
+        // Tests which are not selected are not reported.
+        assert!(skipped(&["smoke"]).is_empty());
+    }
+}
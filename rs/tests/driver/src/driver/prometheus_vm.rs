@@ -1,17 +1,19 @@
 use std::{
     collections::HashMap,
     fs::{self, File},
+    io::Read,
     net::Ipv6Addr,
     path::{Path, PathBuf},
     time::Duration,
 };
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use maplit::hashmap;
 use reqwest::Url;
 use serde::Serialize;
 use serde_json::json;
-use slog::{debug, info};
+use slog::{debug, info, warn};
+use ssh2::Session;
 
 use crate::driver::{
     constants::SSH_USERNAME,
@@ -38,7 +40,7 @@ use crate::retry_with_msg;
 
 use super::boundary_node::BoundaryNodeVm;
 
-const PROMETHEUS_VM_NAME: &str = "prometheus";
+pub(crate) const PROMETHEUS_VM_NAME: &str = "prometheus";
 
 /// The SHA-256 hash of the Prometheus VM disk image.
 /// The latest hash can be retrieved by downloading the SHA256SUMS file from:
@@ -52,6 +54,16 @@ fn get_default_prometheus_vm_img_url() -> String {
 
 const PROMETHEUS_DATA_DIR_TARBALL: &str = "prometheus-data-dir.tar.zst";
 
+/// The tarball of a TSDB snapshot, see `PrometheusVm::download_prometheus_data_dir`.
+pub const PROMETHEUS_SNAPSHOT_TARBALL: &str = "prometheus-snapshot.tar.zst";
+
+/// TSDB snapshots larger than this, compressed or not, are not downloaded.
+pub const PROMETHEUS_SNAPSHOT_MAX_SIZE_BYTES: u64 = 20 * 1024 * 1024 * 1024; // 20GiB
+
+const PROMETHEUS_DATA_DIR: &str = "/var/lib/prometheus";
+
+const PROMETHEUS_PORT: u16 = 9090;
+
 const PROMETHEUS_CONFIG_DIR_NAME: &str = "prometheus";
 
 const PROMETHEUS_SCRAPING_TARGETS_DIR: &str = "/etc/prometheus";
@@ -84,10 +96,39 @@ const BITCOIN_WATCHDOG_TESTNET_CANISTER_PROMETHEUS_TARGET: &str =
 const BN_PROMETHEUS_TARGET: &str = "boundary_nodes.json";
 const BN_EXPORTER_PROMETHEUS_TARGET: &str = "boundary_nodes_exporter.json";
 const IC_BOUNDARY_PROMETHEUS_TARGET: &str = "ic_boundary.json";
+/// Scraping target files of custom jobs are named `custom_<job_name>.json`.
+const CUSTOM_PROMETHEUS_TARGET_PREFIX: &str = "custom_";
+
+/// Prometheus' default scrape timeout, which must not exceed the scrape interval.
+const DEFAULT_SCRAPE_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub struct PrometheusVm {
     universal_vm: UniversalVm,
     scrape_interval: Duration,
+    scrape_configs: Vec<ScrapeConfig>,
+    retention: Option<Duration>,
+}
+
+/// A scraping job for targets other than the IC nodes, e.g. exporters run by a
+/// workload generator on a universal VM.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ScrapeConfig {
+    /// The value of the `job` label of the scraped metrics. May only consist of
+    /// ASCII alphanumerics, underscores and dashes.
+    pub job_name: String,
+    /// The `host:port` addresses to scrape, e.g. `[2a0b:21c0:4003:2:5034:46ff:fe3c:e76f]:9100`.
+    pub targets: Vec<String>,
+    pub interval: Duration,
+}
+
+impl ScrapeConfig {
+    pub fn new(job_name: &str, targets: Vec<String>, interval: Duration) -> Self {
+        Self {
+            job_name: job_name.to_string(),
+            targets,
+            interval,
+        }
+    }
 }
 
 impl Default for PrometheusVm {
@@ -113,6 +154,8 @@ impl PrometheusVm {
                 })
                 .enable_ipv4(),
             scrape_interval: Duration::from_secs(10),
+            scrape_configs: vec![],
+            retention: None,
         }
     }
 
@@ -121,6 +164,28 @@ impl PrometheusVm {
         self
     }
 
+    /// Adds a job scraping the given targets every `interval`, in addition to
+    /// the IC nodes. Jobs can also be added after the VM was started, via
+    /// `HasPrometheus::add_prometheus_scrape_config()`.
+    pub fn with_scrape_config(
+        mut self,
+        job_name: &str,
+        targets: Vec<String>,
+        interval: Duration,
+    ) -> Self {
+        self.scrape_configs
+            .push(ScrapeConfig::new(job_name, targets, interval));
+        self
+    }
+
+    /// Sets for how long Prometheus retains samples (rounded down to the second).
+    /// Requires a Prometheus image that reads the retention time from the
+    /// `storage` section of the configuration file.
+    pub fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+
     pub fn with_vm_resources(mut self, vm_resources: VmResources) -> Self {
         self.universal_vm = self.universal_vm.with_vm_resources(vm_resources);
         self
@@ -156,6 +221,11 @@ mkdir -p -m 755 {PROMETHEUS_SCRAPING_TARGETS_DIR}
 for name in replica orchestrator node_exporter; do
   echo '[]' > "{PROMETHEUS_SCRAPING_TARGETS_DIR}/$name.json"
 done
+for file in /config/{CUSTOM_PROMETHEUS_TARGET_PREFIX}*.json; do
+  if [ -f "$file" ]; then
+    cp "$file" {PROMETHEUS_SCRAPING_TARGETS_DIR}/
+  fi
+done
 
 if uname -a | grep -q Ubuntu; then
   # k8s
@@ -177,7 +247,12 @@ fi
         let grafana_dashboards_dst = config_dir.join("grafana").join("dashboards");
         debug!(log, "Copying Grafana dashboards from {grafana_dashboards_src:?} to {grafana_dashboards_dst:?} ...");
         TestEnv::shell_copy_with_deref(grafana_dashboards_src, grafana_dashboards_dst).unwrap();
-        write_prometheus_config_dir(config_dir.clone(), self.scrape_interval).unwrap();
+        write_prometheus_config_dir(config_dir.clone(), self.scrape_interval, self.retention)
+            .unwrap();
+        let group_name = GroupSetup::read_attribute(env).infra_group_name;
+        for scrape_config in self.scrape_configs.iter() {
+            write_custom_scraping_targets(&config_dir, &group_name, scrape_config)?;
+        }
 
         self.universal_vm
             .clone()
@@ -252,6 +327,117 @@ fi
         );
         Ok(())
     }
+
+    /// Takes a snapshot of the TSDB of the Prometheus VM deployed in `env` and
+    /// downloads it as a zstd-compressed tarball to the directory of the VM in
+    /// `env`, from where CI archives it. Returns the path of the tarball.
+    ///
+    /// Unlike `HasPrometheus::download_prometheus_data_dir_if_exists()`, this
+    /// keeps Prometheus running: the snapshot is taken via the admin API (which
+    /// requires Prometheus to run with `--web.enable-admin-api`) and the
+    /// tarball is streamed over SSH without being written to the disk of the
+    /// VM. Fails if the snapshot exceeds `PROMETHEUS_SNAPSHOT_MAX_SIZE_BYTES`.
+    pub fn download_prometheus_data_dir(env: &TestEnv) -> Result<PathBuf> {
+        let log = env.logger();
+        let vm_name = PROMETHEUS_VM_NAME;
+        let deployed_prometheus_vm = env.get_deployed_universal_vm(vm_name)?;
+        let session = deployed_prometheus_vm.block_on_ssh_session()?;
+
+        let response = deployed_prometheus_vm.block_on_bash_script_from_session(
+            &session,
+            &format!(
+                "curl --silent --show-error --fail -X POST http://localhost:{PROMETHEUS_PORT}/api/v1/admin/tsdb/snapshot"
+            ),
+        )?;
+        let snapshot_name = parse_tsdb_snapshot_name(&response)?;
+        let snapshot_dir = Path::new(PROMETHEUS_DATA_DIR)
+            .join("snapshots")
+            .join(&snapshot_name);
+        let destination = env
+            .get_deployed_universal_vm_dir(vm_name)
+            .join(PROMETHEUS_SNAPSHOT_TARBALL);
+        info!(
+            log,
+            "Downloading prometheus TSDB snapshot {snapshot_name} to {destination:?} ..."
+        );
+
+        let result = deployed_prometheus_vm
+            .block_on_bash_script_from_session(
+                &session,
+                &format!("sudo du --bytes --summarize '{}' | cut -f1", snapshot_dir.display()),
+            )
+            .and_then(|size| {
+                let size: u64 = size.trim().parse()?;
+                if size > PROMETHEUS_SNAPSHOT_MAX_SIZE_BYTES {
+                    bail!(
+                        "The TSDB snapshot has {size} bytes, more than the limit of {PROMETHEUS_SNAPSHOT_MAX_SIZE_BYTES} bytes"
+                    );
+                }
+                stream_tarball(
+                    &session,
+                    &snapshot_dir,
+                    &destination,
+                    PROMETHEUS_SNAPSHOT_MAX_SIZE_BYTES,
+                )
+            });
+
+        // The snapshot shares its blocks with the data directory via hard links,
+        // but it pins blocks Prometheus would otherwise compact away.
+        if let Err(e) = deployed_prometheus_vm.block_on_bash_script_from_session(
+            &session,
+            &format!("sudo rm -rf '{}'", snapshot_dir.display()),
+        ) {
+            warn!(
+                log,
+                "Failed to remove the TSDB snapshot from {vm_name}: {e:?}"
+            );
+        }
+
+        let size = result?;
+        info!(
+            log,
+            "Downloaded prometheus TSDB snapshot ({size} bytes) to {destination:?}"
+        );
+        Ok(destination)
+    }
+}
+
+/// Parses the name of the snapshot from the response of the TSDB snapshot admin
+/// API, e.g. `{"status":"success","data":{"name":"20171210T211224Z-2be650b6d019eb54"}}`.
+fn parse_tsdb_snapshot_name(response: &str) -> Result<String> {
+    let response: serde_json::Value = serde_json::from_str(response)?;
+    match response["data"]["name"].as_str() {
+        Some(name) if !name.is_empty() && !name.contains('/') => Ok(name.to_string()),
+        _ => bail!("Unexpected response of the TSDB snapshot API: {response}"),
+    }
+}
+
+/// Streams a zstd-compressed tarball of the directory `dir` on the other end of
+/// `session` to the local file `destination`, which is removed again if the
+/// tarball exceeds `max_size` bytes. Returns the size of the tarball.
+fn stream_tarball(session: &Session, dir: &Path, destination: &Path, max_size: u64) -> Result<u64> {
+    let mut channel = session.channel_session()?;
+    channel.exec(&format!(
+        "sudo tar -cf - --sparse -C '{}' . | zstd --threads=0 -10",
+        dir.display()
+    ))?;
+    let mut destination_file = File::create(destination)?;
+    let size = std::io::copy(
+        &mut (&mut channel).take(max_size + 1),
+        &mut destination_file,
+    )?;
+    if size > max_size {
+        channel.close()?;
+        drop(destination_file);
+        fs::remove_file(destination)?;
+        bail!("The tarball of {dir:?} exceeds the limit of {max_size} bytes");
+    }
+    channel.wait_close()?;
+    let exit_status = channel.exit_status()?;
+    if exit_status != 0 {
+        bail!("Creating the tarball of {dir:?} failed with exit status {exit_status}");
+    }
+    Ok(size)
 }
 
 /// The Prometheus trait allows starting a Prometheus VM,
@@ -277,6 +463,13 @@ pub trait HasPrometheus {
 
     /// Get the playnet URL of the boundary node with the given name.
     fn get_playnet_url(&self, boundary_node_name: &str) -> Option<String>;
+
+    /// Adds (or replaces, if a job with the same name exists) a custom scraping
+    /// job on the running prometheus VM.
+    ///
+    /// Custom jobs are discovered by Prometheus from their scraping target JSON
+    /// files, so it picks up the new job without a restart or config reload.
+    fn add_prometheus_scrape_config(&self, scrape_config: ScrapeConfig) -> Result<()>;
 }
 
 impl HasPrometheus for TestEnv {
@@ -290,6 +483,39 @@ impl HasPrometheus for TestEnv {
         self.sync_with_prometheus_by_name("", None)
     }
 
+    fn add_prometheus_scrape_config(&self, scrape_config: ScrapeConfig) -> Result<()> {
+        let vm_name = PROMETHEUS_VM_NAME.to_string();
+        let prometheus_config_dir = self.get_universal_vm_config_dir(&vm_name);
+        let group_name = GroupSetup::read_attribute(self).infra_group_name;
+        let file =
+            write_custom_scraping_targets(&prometheus_config_dir, &group_name, &scrape_config)?;
+
+        let deployed_prometheus_vm = self.get_deployed_universal_vm(&vm_name)?;
+        let session = deployed_prometheus_vm.block_on_ssh_session()?;
+        let from = prometheus_config_dir.join(&file);
+        let to = Path::new(PROMETHEUS_SCRAPING_TARGETS_DIR).join(&file);
+        let size = fs::metadata(&from)?.len();
+        retry_with_msg!(
+            format!("scp {from:?} to {vm_name}:{to:?}"),
+            self.logger(),
+            SCP_RETRY_TIMEOUT,
+            SCP_RETRY_BACKOFF,
+            || {
+                let mut remote_file = session.scp_send(&to, 0o644, size, None)?;
+                let mut from_file = File::open(&from)?;
+                std::io::copy(&mut from_file, &mut remote_file)?;
+                Ok(())
+            }
+        )?;
+        info!(
+            self.logger(),
+            "Added Prometheus scrape job {} with targets {:?}",
+            scrape_config.job_name,
+            scrape_config.targets
+        );
+        Ok(())
+    }
+
     fn sync_with_prometheus_by_name(&self, name: &str, mut playnet_url: Option<String>) {
         if InfraProvider::read_attribute(self) == InfraProvider::K8s {
             playnet_url = None;
@@ -387,7 +613,7 @@ sudo systemctl stop prometheus.service
 sudo tar -cf "{tarball_full_path:?}" \
     --sparse \
     --use-compress-program="zstd --threads=0 -10" \
-    -C /var/lib/prometheus .
+    -C {PROMETHEUS_DATA_DIR} .
     "#,
         );
         let session = deployed_prometheus_vm
@@ -416,7 +642,11 @@ struct PrometheusStaticConfig {
     labels: HashMap<String, String>,
 }
 
-fn write_prometheus_config_dir(config_dir: PathBuf, scrape_interval: Duration) -> Result<()> {
+fn write_prometheus_config_dir(
+    config_dir: PathBuf,
+    scrape_interval: Duration,
+    retention: Option<Duration>,
+) -> Result<()> {
     let prometheus_config_dir = config_dir.join(PROMETHEUS_CONFIG_DIR_NAME);
     fs::create_dir_all(prometheus_config_dir.clone())?;
 
@@ -444,8 +674,10 @@ fn write_prometheus_config_dir(config_dir: PathBuf, scrape_interval: Duration) -
     let bitcoin_watchdog_testnet_canister_scraping_target_path =
         Path::new(PROMETHEUS_SCRAPING_TARGETS_DIR)
             .join(BITCOIN_WATCHDOG_TESTNET_CANISTER_PROMETHEUS_TARGET);
+    let custom_scraping_targets_path = Path::new(PROMETHEUS_SCRAPING_TARGETS_DIR)
+        .join(format!("{CUSTOM_PROMETHEUS_TARGET_PREFIX}*.json"));
     let scrape_interval_str: String = format!("{}s", scrape_interval.as_secs());
-    let prometheus_config = json!({
+    let mut prometheus_config = json!({
         "global": {"scrape_interval": scrape_interval_str},
         "scrape_configs": [
             {
@@ -513,14 +745,61 @@ fn write_prometheus_config_dir(config_dir: PathBuf, scrape_interval: Duration) -
                 "enable_http2": true,
                 "file_sd_configs": [{"files": [bitcoin_watchdog_testnet_canister_scraping_target_path]}],
             },
+            {
+                // Custom jobs override the `job` label and scrape interval via the labels
+                // in their scraping target files.
+                "job_name": "custom",
+                "file_sd_configs": [{"files": [custom_scraping_targets_path]}],
+            },
         ],
     });
+    if let Some(retention) = retention {
+        prometheus_config["storage"] =
+            json!({"tsdb": {"retention": {"time": format!("{}s", retention.as_secs())}}});
+    }
     let prometheus_config_path = prometheus_config_dir.join("prometheus.yml");
     let prometheus_config_file = File::create(prometheus_config_path)?;
     serde_json::to_writer(prometheus_config_file, &prometheus_config)?;
     Ok(())
 }
 
+/// Writes the scraping target JSON file of the given custom job to
+/// `prometheus_config_dir`. Returns the name of the file.
+fn write_custom_scraping_targets(
+    prometheus_config_dir: &Path,
+    group_name: &str,
+    scrape_config: &ScrapeConfig,
+) -> Result<String> {
+    let job_name = &scrape_config.job_name;
+    if job_name.is_empty()
+        || !job_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        bail!("Invalid Prometheus job name: {job_name:?}");
+    }
+    let interval = scrape_config.interval;
+    if interval.as_secs() == 0 {
+        bail!("Scrape interval of job {job_name} must be at least one second");
+    }
+    let timeout = interval.min(DEFAULT_SCRAPE_TIMEOUT);
+
+    let file = format!("{CUSTOM_PROMETHEUS_TARGET_PREFIX}{job_name}.json");
+    serde_json::to_writer(
+        &File::create(prometheus_config_dir.join(&file))?,
+        &vec![PrometheusStaticConfig {
+            targets: scrape_config.targets.clone(),
+            labels: hashmap! {
+                "ic".to_string() => group_name.to_string(),
+                "job".to_string() => job_name.clone(),
+                "__scrape_interval__".to_string() => format!("{}s", interval.as_secs()),
+                "__scrape_timeout__".to_string() => format!("{}s", timeout.as_secs()),
+            },
+        }],
+    )?;
+    Ok(file)
+}
+
 fn sync_prometheus_config_dir_with_boundary_nodes(
     env: &TestEnv,
     prometheus_config_dir: PathBuf,
@@ -6,7 +6,16 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
-use crate::driver::event::TaskId;
+use crate::driver::{
+    capabilities::Capability,
+    event::TaskId,
+    group::SETUP_TASK_NAME,
+    pot_dsl::{parse_matrix_test_name, MATRIX_SETUP_PREFIX},
+};
+
+/// Failure messages containing any of these markers are attributed to the test
+/// infrastructure rather than to the system under test.
+const INFRA_FAILURE_MARKERS: &[&str] = &["FarmError"];
 
 #[allow(dead_code)]
 fn get_duration(
@@ -31,7 +40,13 @@ pub struct SystemGroupSummary {
     pub test_name: String,
     pub success: Vec<TaskReport>,
     pub failure: Vec<TaskReport>,
+    /// Tasks cancelled because they exceeded their (or the group's) timeout.
+    #[serde(default)]
+    pub timeout: Vec<TaskReport>,
     pub skipped: Vec<TaskReport>,
+    /// How to SSH into the jumphost deployed into the group, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jumphost: Option<String>,
 }
 
 impl Display for SystemGroupSummary {
@@ -50,6 +65,9 @@ impl SystemGroupSummary {
         for res in self.failure.iter() {
             out_lines.append(&mut res.pretty_print(max_name_len, "FAILED "));
         }
+        for res in self.timeout.iter() {
+            out_lines.append(&mut res.pretty_print(max_name_len, "TIMEOUT"));
+        }
         for res in self.skipped.iter() {
             out_lines.append(&mut res.pretty_print(max_name_len, "SKIPPED"));
         }
@@ -67,14 +85,52 @@ impl SystemGroupSummary {
         let mut summary = vec![];
         summary.push(start);
         summary.append(&mut out_lines);
+        summary.append(&mut self.pretty_print_matrices());
+        if let Some(jumphost) = &self.jumphost {
+            summary.push(format!("Jumphost: {jumphost}"));
+        }
         summary.push(end);
         summary.iter().fold(String::new(), |a, b| a + b + "\n")
     }
 
+    /// Verdicts of test matrix cells, grouped by matrix (in name order) and listed
+    /// by configuration label (in label order), for side-by-side comparison.
+    pub fn matrix_verdicts(&self) -> BTreeMap<&str, Vec<(&str, &'static str)>> {
+        let mut matrices: BTreeMap<&str, Vec<(&str, &'static str)>> = BTreeMap::new();
+        for (reports, verdict) in [
+            (&self.success, "PASSED"),
+            (&self.failure, "FAILED"),
+            (&self.timeout, "TIMEOUT"),
+            (&self.skipped, "SKIPPED"),
+        ] {
+            for report in reports.iter() {
+                if let Some((name, label)) = parse_matrix_test_name(&report.name) {
+                    matrices.entry(name).or_default().push((label, verdict));
+                }
+            }
+        }
+        matrices.values_mut().for_each(|cells| cells.sort());
+        matrices
+    }
+
+    fn pretty_print_matrices(&self) -> Vec<String> {
+        self.matrix_verdicts()
+            .into_iter()
+            .map(|(name, cells)| {
+                let cells: Vec<_> = cells
+                    .into_iter()
+                    .map(|(label, verdict)| format!("{label}={verdict}"))
+                    .collect();
+                format!("Matrix {} {}", name, cells.join(" "))
+            })
+            .collect()
+    }
+
     fn all_reports(&self) -> impl Iterator<Item = &TaskReport> {
         self.success
             .iter()
             .chain(self.failure.iter())
+            .chain(self.timeout.iter())
             .chain(self.skipped.iter())
     }
 
@@ -86,12 +142,33 @@ impl SystemGroupSummary {
         mx
     }
 
+    /// Number of failed (including timed out) tasks attributed to the system
+    /// under test.
+    pub fn test_failure_count(&self) -> usize {
+        self.failure
+            .iter()
+            .chain(self.timeout.iter())
+            .filter(|r| r.failure_kind() == FailureKind::Test)
+            .count()
+    }
+
+    /// Number of failed (including timed out) tasks attributed to the test
+    /// infrastructure.
+    pub fn infra_failure_count(&self) -> usize {
+        self.failure
+            .iter()
+            .chain(self.timeout.iter())
+            .filter(|r| r.failure_kind() == FailureKind::Infra)
+            .count()
+    }
+
     pub fn to_map(&self) -> HashMap<String, (f64, Option<String>)> {
         let mut map = HashMap::new();
         for TaskReport {
             name,
             runtime,
             message,
+            ..
         } in self.all_reports()
         {
             map.insert(name.clone(), (*runtime, message.clone()));
@@ -103,6 +180,25 @@ impl SystemGroupSummary {
 // short messages (without newlines) are appended to the end of a report line.
 // multi-line messages are indented so they are visually distinct from report lines.
 impl TaskReport {
+    /// Classifies the failure of this task: failures of setup tasks (including
+    /// those of test matrix cells) and failures reported by the infrastructure
+    /// (e.g. Farm) are infra failures, everything else is a test failure.
+    pub fn failure_kind(&self) -> FailureKind {
+        let infra_message = self.message.as_ref().is_some_and(|msg| {
+            INFRA_FAILURE_MARKERS
+                .iter()
+                .any(|marker| msg.contains(marker))
+        });
+        if self.name == SETUP_TASK_NAME
+            || self.name.starts_with(MATRIX_SETUP_PREFIX)
+            || infra_message
+        {
+            FailureKind::Infra
+        } else {
+            FailureKind::Test
+        }
+    }
+
     fn pretty_print(&self, max_name_len: usize, verb: &str) -> Vec<String> {
         let time = if self.runtime > 0.0 {
             format!(" in {:>6.2}s", self.runtime)
@@ -135,6 +231,17 @@ pub struct TaskReport {
     pub name: String,
     pub runtime: f64,
     pub message: Option<String>,
+    /// Driver APIs and system features exercised by the task.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities: Vec<Capability>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FailureKind {
+    /// A test assertion failed.
+    Test,
+    /// The test infrastructure failed (e.g. group setup, Farm).
+    Infra,
 }
 
 #[derive(Clone, Debug)]
@@ -142,6 +249,9 @@ pub enum SystemTestGroupError {
     TestDriverError {
         message: String,
     },
+    InvalidInvocation {
+        message: String,
+    },
     PreconditionViolation {
         condition: String,
         counterexample: String,
@@ -159,6 +269,9 @@ impl Display for SystemTestGroupError {
             SystemTestGroupError::TestDriverError { message } => {
                 write!(f, "Internal test driver error: {}", message)
             }
+            SystemTestGroupError::InvalidInvocation { message } => {
+                write!(f, "Invalid test driver invocation: {}", message)
+            }
             SystemTestGroupError::PreconditionViolation {
                 condition,
                 counterexample,
@@ -196,3 +309,318 @@ pub enum Outcome {
     FromParentProcess(SystemGroupSummary),
     FromSubProcess,
 }
+
+/// Exit code of the test driver (parent) process. This is a stable contract
+/// relied upon by CI; do not renumber existing variants.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DriverExitCode {
+    /// All tests passed.
+    Passed = 0,
+    /// At least one test assertion failed.
+    TestFailures = 1,
+    /// Only infrastructure failures occurred.
+    InfraFailures = 2,
+    /// The run was cancelled, e.g. by an external signal.
+    Cancelled = 3,
+    /// The driver was invoked with invalid arguments or an invalid test group.
+    InvalidInvocation = 4,
+}
+
+impl DriverExitCode {
+    pub fn from_summary(summary: &SystemGroupSummary) -> Self {
+        if summary.test_failure_count() > 0 {
+            Self::TestFailures
+        } else if summary.infra_failure_count() > 0 {
+            Self::InfraFailures
+        } else {
+            Self::Passed
+        }
+    }
+
+    pub fn from_error(error: &SystemTestGroupError) -> Self {
+        match error {
+            SystemTestGroupError::TestDriverError { .. } => Self::InfraFailures,
+            SystemTestGroupError::InvalidInvocation { .. }
+            | SystemTestGroupError::PreconditionViolation { .. } => Self::InvalidInvocation,
+            SystemTestGroupError::ExternalSignalReceived { .. } => Self::Cancelled,
+            SystemTestGroupError::SystemTestFailure(summary) => Self::from_summary(summary),
+        }
+    }
+
+    /// Errors not originating from the test driver are treated as infra failures.
+    pub fn from_anyhow(error: &anyhow::Error) -> Self {
+        error
+            .downcast_ref::<SystemTestGroupError>()
+            .map_or(Self::InfraFailures, Self::from_error)
+    }
+
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+}
+
+/// Final single-line, machine-parsable summary of a test driver run, e.g.
+/// `RESULT pots=1 tests=3 passed=2 failed=1 infra=0 skipped=0 timeout=1 duration=12.34s`.
+/// `pots` is 0 if the run ended before producing a report. `timeout` counts the
+/// `failed` and `infra` tasks that timed out.
+pub struct ResultLine<'a> {
+    pub summary: Option<&'a SystemGroupSummary>,
+    pub duration: Duration,
+}
+
+impl Display for ResultLine<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let (pots, passed, failed, infra, skipped, timeout) = match self.summary {
+            Some(s) => (
+                1,
+                s.success.len(),
+                s.test_failure_count(),
+                s.infra_failure_count(),
+                s.skipped.len(),
+                s.timeout.len(),
+            ),
+            None => (0, 0, 0, 0, 0, 0),
+        };
+        write!(
+            f,
+            "RESULT pots={} tests={} passed={} failed={} infra={} skipped={} timeout={} duration={:.2}s",
+            pots,
+            passed + failed + infra + skipped,
+            passed,
+            failed,
+            infra,
+            skipped,
+            timeout,
+            self.duration.as_secs_f64()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(name: &str, message: Option<&str>) -> TaskReport {
+        TaskReport {
+            name: name.to_string(),
+            runtime: 1.0,
+            message: message.map(String::from),
+            capabilities: vec![],
+        }
+    }
+
+    fn summary(test_failures: usize, infra_failures: usize, skipped: usize) -> SystemGroupSummary {
+        let mut failure: Vec<_> = (0..test_failures)
+            .map(|i| task(&format!("failing_test_{i}"), Some("assertion failed")))
+            .collect();
+        failure.extend((0..infra_failures).map(|i| {
+            task(
+                &format!("infra_test_{i}"),
+                Some("FarmError::TooManyRetries { message: \"Retried too many times\" }"),
+            )
+        }));
+        SystemGroupSummary {
+            test_name: "test_group".to_string(),
+            success: vec![task("passing_test", None)],
+            failure,
+            timeout: vec![],
+            skipped: (0..skipped)
+                .map(|i| task(&format!("skipped_test_{i}"), Some("Task skipped")))
+                .collect(),
+            jumphost: None,
+        }
+    }
+
+    #[test]
+    fn exit_code_from_summary_covers_all_combinations() {
+        for skipped in [0, 1] {
+            for (test_failures, infra_failures, expected) in [
+                (0, 0, DriverExitCode::Passed),
+                (1, 0, DriverExitCode::TestFailures),
+                (0, 1, DriverExitCode::InfraFailures),
+                (1, 1, DriverExitCode::TestFailures),
+            ] {
+                let summary = summary(test_failures, infra_failures, skipped);
+                assert_eq!(
+                    DriverExitCode::from_summary(&summary),
+                    expected,
+                    "test_failures={test_failures} infra_failures={infra_failures} skipped={skipped}"
+                );
+                if test_failures + infra_failures > 0 {
+                    let error = SystemTestGroupError::SystemTestFailure(summary);
+                    assert_eq!(DriverExitCode::from_error(&error), expected);
+                    assert_eq!(
+                        DriverExitCode::from_anyhow(&anyhow::Error::msg(error)),
+                        expected
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn failed_setup_is_an_infra_failure() {
+        let mut summary = summary(0, 0, 0);
+        summary
+            .failure
+            .push(task(SETUP_TASK_NAME, Some("assertion failed")));
+        assert_eq!(summary.infra_failure_count(), 1);
+        assert_eq!(
+            DriverExitCode::from_summary(&summary),
+            DriverExitCode::InfraFailures
+        );
+    }
+
+    #[test]
+    fn exit_code_from_error() {
+        let cases = [
+            (
+                SystemTestGroupError::TestDriverError {
+                    message: "boom".to_string(),
+                },
+                DriverExitCode::InfraFailures,
+            ),
+            (
+                SystemTestGroupError::InvalidInvocation {
+                    message: "unexpected argument".to_string(),
+                },
+                DriverExitCode::InvalidInvocation,
+            ),
+            (
+                SystemTestGroupError::PreconditionViolation {
+                    condition: "unique task names".to_string(),
+                    counterexample: "test".to_string(),
+                },
+                DriverExitCode::InvalidInvocation,
+            ),
+            (
+                SystemTestGroupError::ExternalSignalReceived {
+                    task_id: TaskId::Test("test".to_string()),
+                    signal: 15,
+                },
+                DriverExitCode::Cancelled,
+            ),
+        ];
+        for (error, expected) in cases {
+            assert_eq!(DriverExitCode::from_error(&error), expected, "{error}");
+            assert_eq!(
+                DriverExitCode::from_anyhow(&anyhow::Error::msg(error)),
+                expected
+            );
+        }
+        assert_eq!(
+            DriverExitCode::from_anyhow(&anyhow::anyhow!("unrelated error")),
+            DriverExitCode::InfraFailures
+        );
+    }
+
+    #[test]
+    fn exit_code_values_are_stable() {
+        assert_eq!(DriverExitCode::Passed.code(), 0);
+        assert_eq!(DriverExitCode::TestFailures.code(), 1);
+        assert_eq!(DriverExitCode::InfraFailures.code(), 2);
+        assert_eq!(DriverExitCode::Cancelled.code(), 3);
+        assert_eq!(DriverExitCode::InvalidInvocation.code(), 4);
+    }
+
+    #[test]
+    fn failed_matrix_setup_is_an_infra_failure() {
+        let mut summary = summary(0, 0, 0);
+        summary.failure.push(task(
+            "setup::counter_smoke[system]",
+            Some("assertion failed"),
+        ));
+        summary
+            .failure
+            .push(task("counter_smoke[application]", Some("assertion failed")));
+        assert_eq!(summary.infra_failure_count(), 1);
+        assert_eq!(summary.test_failure_count(), 1);
+    }
+
+    #[test]
+    fn groups_matrix_siblings() {
+        let summary = SystemGroupSummary {
+            test_name: "test_group".to_string(),
+            success: vec![
+                task("counter_smoke[system]", None),
+                task("plain_test", None),
+                task("setup::counter_smoke[system]", None),
+            ],
+            failure: vec![task("counter_smoke[application]", Some("boom"))],
+            timeout: vec![task("counter_smoke[subnet]", Some("Timeout after 5s"))],
+            skipped: vec![task("other[4-node]", Some("Task skipped"))],
+            jumphost: None,
+        };
+        assert_eq!(
+            BTreeMap::from([
+                (
+                    "counter_smoke",
+                    vec![
+                        ("application", "FAILED"),
+                        ("subnet", "TIMEOUT"),
+                        ("system", "PASSED")
+                    ]
+                ),
+                ("other", vec![("4-node", "SKIPPED")]),
+            ]),
+            summary.matrix_verdicts()
+        );
+        assert_eq!(
+            vec![
+                "Matrix counter_smoke application=FAILED subnet=TIMEOUT system=PASSED",
+                "Matrix other 4-node=SKIPPED",
+            ],
+            summary.pretty_print_matrices()
+        );
+    }
+
+    #[test]
+    fn timeouts_are_counted_as_failures_of_their_kind() {
+        let mut test_timeout = summary(0, 0, 0);
+        test_timeout
+            .timeout
+            .push(task("never_ending_test", Some("Timeout after 5s")));
+        assert_eq!(test_timeout.test_failure_count(), 1);
+        assert_eq!(test_timeout.infra_failure_count(), 0);
+        assert_eq!(
+            DriverExitCode::from_summary(&test_timeout),
+            DriverExitCode::TestFailures
+        );
+
+        let mut setup_timeout = summary(0, 0, 0);
+        setup_timeout
+            .timeout
+            .push(task(SETUP_TASK_NAME, Some("Timeout after 5s")));
+        assert_eq!(setup_timeout.test_failure_count(), 0);
+        assert_eq!(setup_timeout.infra_failure_count(), 1);
+        assert_eq!(
+            ResultLine {
+                summary: Some(&setup_timeout),
+                duration: Duration::from_secs(5),
+            }
+            .to_string(),
+            "RESULT pots=1 tests=2 passed=1 failed=0 infra=1 skipped=0 timeout=1 duration=5.00s"
+        );
+    }
+
+    #[test]
+    fn result_line_golden() {
+        let summary = summary(1, 1, 1);
+        assert_eq!(
+            ResultLine {
+                summary: Some(&summary),
+                duration: Duration::from_millis(12_340),
+            }
+            .to_string(),
+            "RESULT pots=1 tests=4 passed=1 failed=1 infra=1 skipped=1 timeout=0 duration=12.34s"
+        );
+        assert_eq!(
+            ResultLine {
+                summary: None,
+                duration: Duration::from_millis(500),
+            }
+            .to_string(),
+            "RESULT pots=0 tests=0 passed=0 failed=0 infra=0 skipped=0 timeout=0 duration=0.50s"
+        );
+    }
+}
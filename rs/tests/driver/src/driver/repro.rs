@@ -0,0 +1,449 @@
+//! Reproduction bundles of failed tests.
+//!
+//! The parent process records its command line in the root environment of the
+//! group. When a test fails, the driver writes a `repro.sh` and a `repro.json`
+//! into the environment of the test, next to the collected node logs. Running
+//! `repro.sh` on a workstation re-executes only the failed test, with the flags,
+//! the environment variables and thus the artifacts of the failed run.
+//!
+//! Environment variables are only recorded if they affect the behavior of the
+//! driver or of the tests, and never if their name suggests they hold a secret.
+
+use crate::driver::context::GroupContext;
+use crate::driver::driver_setup::{sha256_of_file, ResolvedDependencies};
+use crate::driver::group::qualified_test_name;
+use crate::driver::test_env::{TestEnv, TestEnvAttribute, DEFAULT_RNG_SEED};
+use crate::driver::test_env_api::get_ic_os_img_sha256;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+pub const REPRO_SCRIPT: &str = "repro.sh";
+pub const REPRO_JSON: &str = "repro.json";
+
+/// The environment variable holding the CAS URL of the GuestOS image.
+const GUESTOS_IMG_URL_ENV_VAR: &str = "ENV_DEPS__DEV_DISK_IMG_TAR_ZST_CAS_URL";
+
+/// Prefixes of the names of the environment variables that affect behavior.
+const RECORDED_ENV_VAR_PREFIXES: &[&str] = &["ENV_DEPS__", "IC_TEST_DRIVER_", "RUST_"];
+
+/// Substrings of the names of environment variables that are never recorded,
+/// regardless of their prefix.
+const SECRET_ENV_VAR_MARKERS: &[&str] = &[
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "CREDENTIAL",
+    "API_KEY",
+    "PRIVATE_KEY",
+    "AUTH",
+];
+
+/// Flags of the driver taking a value that are specific to the failed run and
+/// are thus not passed on by `repro.sh`.
+const DROPPED_FLAGS: &[&str] = &[
+    "--working-dir",
+    "--include-tests",
+    "--include-pattern",
+    "--skip-pattern",
+    "--test-events-socket",
+    "--reuse-env",
+];
+
+/// The command line of the parent process of the driver.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct DriverInvocation {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl TestEnvAttribute for DriverInvocation {
+    fn attribute_name() -> String {
+        "driver_invocation".to_string()
+    }
+}
+
+impl DriverInvocation {
+    /// The command line of this process.
+    pub fn current() -> Self {
+        let mut args = std::env::args();
+        Self {
+            program: args.next().unwrap_or_default(),
+            args: args.collect(),
+        }
+    }
+}
+
+/// Everything needed to re-execute a single failed test with the inputs of the
+/// failed run.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct Repro {
+    /// The failed test, as `<group>::<test>`.
+    pub test: String,
+    pub program: String,
+    /// The arguments of the driver, narrowed to the failed test. The working
+    /// directory is chosen by `repro.sh`.
+    pub args: Vec<String>,
+    /// The seed of the random number generators of the group and its tests.
+    pub rng_seed: u64,
+    /// The SHA-256 hashes of the artifacts used by the failed run, by name.
+    pub artifacts: BTreeMap<String, String>,
+    pub env: BTreeMap<String, String>,
+}
+
+impl Repro {
+    pub fn new(
+        invocation: &DriverInvocation,
+        group_name: &str,
+        test_name: &str,
+        artifacts: BTreeMap<String, String>,
+        env_vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        let test = qualified_test_name(group_name, test_name);
+        let mut args = vec![
+            "--include-pattern".to_string(),
+            format!("^{}$", regex::escape(&test)),
+        ];
+        args.extend(without_dropped_flags(&invocation.args));
+        Self {
+            test,
+            program: invocation.program.clone(),
+            args,
+            rng_seed: DEFAULT_RNG_SEED,
+            artifacts,
+            env: env_vars
+                .into_iter()
+                .filter(|(name, _)| is_recorded_env_var(name))
+                .collect(),
+        }
+    }
+
+    /// A bash script re-executing the failed test in a fresh working directory,
+    /// or in `$REPRO_WORKING_DIR` if set. `$REPRO_DRIVER` overrides the path of
+    /// the driver binary.
+    pub fn script(&self) -> String {
+        let mut script = String::new();
+        writeln!(script, "#!/usr/bin/env bash").unwrap();
+        writeln!(
+            script,
+            "# Re-executes the failed test {} with the inputs of the failed run.",
+            self.test
+        )
+        .unwrap();
+        writeln!(script, "# RNG seed: {}", self.rng_seed).unwrap();
+        if !self.artifacts.is_empty() {
+            writeln!(script, "# Artifacts (sha256):").unwrap();
+            for (name, sha256) in self.artifacts.iter() {
+                writeln!(script, "#   {name}: {sha256}").unwrap();
+            }
+        }
+        writeln!(script, "set -euo pipefail").unwrap();
+        for (name, value) in self.env.iter() {
+            writeln!(script, "export {name}={}", shell_quote(value)).unwrap();
+        }
+        writeln!(
+            script,
+            "DRIVER=${{REPRO_DRIVER:-{}}}",
+            shell_quote(&self.program)
+        )
+        .unwrap();
+        writeln!(script, "WORKING_DIR=${{REPRO_WORKING_DIR:-$(mktemp -d)}}").unwrap();
+        write!(script, "exec \"$DRIVER\" --working-dir \"$WORKING_DIR\"").unwrap();
+        for arg in self.args.iter() {
+            write!(script, " {}", shell_quote(arg)).unwrap();
+        }
+        writeln!(script).unwrap();
+        script
+    }
+
+    /// Writes `repro.sh` and `repro.json` into `dir`.
+    pub fn write_to(&self, dir: &Path) -> Result<()> {
+        let script_path = dir.join(REPRO_SCRIPT);
+        std::fs::write(&script_path, self.script())
+            .with_context(|| format!("Failed to write {script_path:?}"))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755))?;
+        }
+        let json_path = dir.join(REPRO_JSON);
+        std::fs::write(&json_path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {json_path:?}"))?;
+        Ok(())
+    }
+}
+
+/// Writes the reproduction bundle of the failed test `test_name` into `env`,
+/// the environment of the test.
+pub fn write_repro_bundle(env: &TestEnv, group_ctx: &GroupContext, test_name: &str) -> Result<()> {
+    let root_env = group_ctx.get_root_env()?;
+    let invocation = DriverInvocation::try_read_attribute(&root_env)
+        .context("The invocation of the driver was not recorded")?;
+    let repro = Repro::new(
+        &invocation,
+        &group_ctx.group_base_name,
+        test_name,
+        artifact_hashes(env),
+        std::env::vars(),
+    );
+    repro.write_to(&env.base_path())
+}
+
+/// The hashes of the GuestOS image and of the dependencies declared by the
+/// group. Artifacts whose hash cannot be determined are omitted.
+fn artifact_hashes(env: &TestEnv) -> BTreeMap<String, String> {
+    let mut artifacts = BTreeMap::new();
+    if std::env::var(GUESTOS_IMG_URL_ENV_VAR).is_ok() {
+        if let Ok(sha256) = get_ic_os_img_sha256() {
+            artifacts.insert("guestos_img".to_string(), sha256);
+        }
+    }
+    let resolved = ResolvedDependencies::try_read_attribute(env).unwrap_or_default();
+    for (source, path) in resolved.iter() {
+        if let Ok(sha256) = sha256_of_file(path) {
+            artifacts.insert(source.to_string(), sha256);
+        }
+    }
+    artifacts
+}
+
+fn is_recorded_env_var(name: &str) -> bool {
+    let upper = name.to_uppercase();
+    RECORDED_ENV_VAR_PREFIXES
+        .iter()
+        .any(|prefix| upper.starts_with(prefix))
+        && !SECRET_ENV_VAR_MARKERS
+            .iter()
+            .any(|marker| upper.contains(marker))
+}
+
+/// Removes the flags in `DROPPED_FLAGS`, given as `--flag value` or
+/// `--flag=value`, from `args`.
+fn without_dropped_flags(args: &[String]) -> Vec<String> {
+    let mut kept = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if DROPPED_FLAGS.contains(&arg.as_str()) {
+            args.next();
+        } else if !DROPPED_FLAGS
+            .iter()
+            .any(|flag| arg.starts_with(&format!("{flag}=")))
+        {
+            kept.push(arg.clone());
+        }
+    }
+    kept
+}
+
+/// Quotes `s` as a single word for bash.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GROUP: &str = "my_group";
+    const TEST: &str = "my_test";
+
+    fn invocation(args: &[&str]) -> DriverInvocation {
+        DriverInvocation {
+            program: "/runfiles/rs/tests/my_group_bin".to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    fn env_vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn repro(args: &[&str]) -> Repro {
+        Repro::new(&invocation(args), GROUP, TEST, BTreeMap::new(), vec![])
+    }
+
+    #[test]
+    fn script_narrows_the_filters_to_the_failed_test() {
+        let repro = repro(&[
+            "--working-dir",
+            "/ci/work",
+            "--include-tests",
+            "my_",
+            "--skip-pattern=other",
+            "--group-base-name",
+            GROUP,
+            "run",
+        ]);
+
+        assert_eq!(
+            repro.script().lines().last().unwrap(),
+            "exec \"$DRIVER\" --working-dir \"$WORKING_DIR\" '--include-pattern' '^my_group::my_test$' '--group-base-name' 'my_group' 'run'"
+        );
+        assert!(!repro.script().contains("/ci/work"));
+    }
+
+    #[test]
+    fn script_keeps_the_flags_affecting_behavior() {
+        let repro = repro(&[
+            "--working-dir=/ci/work",
+            "--k8s",
+            "--farm-base-url",
+            "https://farm.example.com",
+            "--debug-keepalive",
+            "--set-required-host-features=dc=zh1",
+            "--group-base-name",
+            GROUP,
+            "run",
+        ]);
+
+        assert_eq!(
+            repro.args,
+            vec![
+                "--include-pattern",
+                "^my_group::my_test$",
+                "--k8s",
+                "--farm-base-url",
+                "https://farm.example.com",
+                "--debug-keepalive",
+                "--set-required-host-features=dc=zh1",
+                "--group-base-name",
+                GROUP,
+                "run",
+            ]
+        );
+    }
+
+    #[test]
+    fn script_drops_the_flags_of_the_failed_run() {
+        let repro = repro(&[
+            "--test-events-socket",
+            "/tmp/events.sock",
+            "--reuse-env=/ci/setup",
+            "--include-pattern",
+            "my_group::.*",
+            "--group-base-name",
+            GROUP,
+            "run",
+        ]);
+
+        let script = repro.script();
+        assert!(!script.contains("events.sock"));
+        assert!(!script.contains("/ci/setup"));
+        assert!(!script.contains("my_group::.*"));
+    }
+
+    #[test]
+    fn script_escapes_regex_and_shell_metacharacters() {
+        let invocation = DriverInvocation {
+            program: "/path with spaces/it's_a_bin".to_string(),
+            args: vec!["run".to_string()],
+        };
+        let repro = Repro::new(&invocation, GROUP, "test.v2", BTreeMap::new(), vec![]);
+
+        let script = repro.script();
+        assert!(script.contains(r"DRIVER=${REPRO_DRIVER:-'/path with spaces/it'\''s_a_bin'}"));
+        assert!(script.contains(r"'^my_group::test\.v2$'"));
+    }
+
+    #[test]
+    fn script_records_the_seed_and_the_artifacts() {
+        let artifacts = BTreeMap::from([
+            ("guestos_img".to_string(), "ab".repeat(32)),
+            ("$ENV_DEPS__LEDGER_WASM".to_string(), "cd".repeat(32)),
+        ]);
+        let repro = Repro::new(
+            &invocation(&["run"]),
+            GROUP,
+            TEST,
+            artifacts.clone(),
+            vec![],
+        );
+
+        let script = repro.script();
+        assert!(script.contains(&format!("# RNG seed: {DEFAULT_RNG_SEED}")));
+        assert!(script.contains(&format!("#   guestos_img: {}", "ab".repeat(32))));
+        assert!(script.contains(&format!("#   $ENV_DEPS__LEDGER_WASM: {}", "cd".repeat(32))));
+        assert_eq!(repro.artifacts, artifacts);
+    }
+
+    #[test]
+    fn only_env_vars_affecting_behavior_are_exported() {
+        let repro = Repro::new(
+            &invocation(&["run"]),
+            GROUP,
+            TEST,
+            BTreeMap::new(),
+            env_vars(&[
+                ("ENV_DEPS__LEDGER_WASM", "rs/ledger.wasm"),
+                ("IC_TEST_DRIVER_LOG_LEVEL", "debug"),
+                ("RUST_BACKTRACE", "1"),
+                ("HOME", "/home/ci"),
+                ("PATH", "/usr/bin"),
+            ]),
+        );
+
+        assert_eq!(
+            repro.env.keys().collect::<Vec<_>>(),
+            vec![
+                "ENV_DEPS__LEDGER_WASM",
+                "IC_TEST_DRIVER_LOG_LEVEL",
+                "RUST_BACKTRACE"
+            ]
+        );
+        assert!(repro
+            .script()
+            .contains("export ENV_DEPS__LEDGER_WASM='rs/ledger.wasm'"));
+    }
+
+    #[test]
+    fn secrets_are_never_included() {
+        let secrets = [
+            ("IC_TEST_DRIVER_FARM_TOKEN", "secret-1"),
+            ("ENV_DEPS__REGISTRY_PASSWORD", "secret-2"),
+            ("RUST_API_KEY", "secret-3"),
+            ("GITHUB_TOKEN", "secret-4"),
+            ("ic_test_driver_auth_header", "secret-5"),
+        ];
+        let repro = Repro::new(
+            &invocation(&["--k8s", "run"]),
+            GROUP,
+            TEST,
+            BTreeMap::new(),
+            env_vars(&secrets),
+        );
+
+        let script = repro.script();
+        let json = serde_json::to_string(&repro).unwrap();
+        for (name, value) in secrets {
+            assert!(!script.contains(name) && !script.contains(value));
+            assert!(!json.contains(name) && !json.contains(value));
+        }
+    }
+
+    #[test]
+    fn bundle_is_written_to_the_test_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let repro = repro(&["run"]);
+
+        repro.write_to(dir.path()).unwrap();
+
+        let script = std::fs::read_to_string(dir.path().join(REPRO_SCRIPT)).unwrap();
+        assert_eq!(script, repro.script());
+        let json = std::fs::read_to_string(dir.path().join(REPRO_JSON)).unwrap();
+        assert_eq!(serde_json::from_str::<Repro>(&json).unwrap(), repro);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.path().join(REPRO_SCRIPT))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+    }
+}
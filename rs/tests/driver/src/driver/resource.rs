@@ -1,14 +1,14 @@
 use super::constants::SSH_USERNAME;
 use super::driver_setup::SSH_AUTHORIZED_PUB_KEYS_DIR;
-use crate::driver::farm::FarmResult;
 use crate::driver::farm::FileId;
 use crate::driver::farm::ImageLocation;
 use crate::driver::farm::ImageLocation::{IcOsImageViaUrl, ImageViaUrl};
 use crate::driver::farm::VMCreateResponse;
 use crate::driver::farm::{CreateVmRequest, HostFeature};
 use crate::driver::farm::{Farm, VmType};
+use crate::driver::farm::{FarmError, FarmResult, VmPlacement};
 use crate::driver::ic::{AmountOfMemoryKiB, InternetComputer, Node, NrOfVCPUs};
-use crate::driver::ic::{ImageSizeGiB, VmAllocationStrategy, VmResources};
+use crate::driver::ic::{ImageSizeGiB, VmAllocationStrategy, VmClass, VmResources, VmTopology};
 use crate::driver::nested::NestedNode;
 use crate::driver::test_env::{TestEnv, TestEnvAttribute};
 use crate::driver::test_env_api::{
@@ -21,13 +21,14 @@ use crate::k8s::tnet::TNet;
 use crate::util::block_on;
 use anyhow::{self, bail};
 use serde::{Deserialize, Serialize};
-use slog::{info, warn};
+use slog::{info, warn, Logger};
 use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::{self, Write};
 use std::net::{Ipv4Addr, Ipv6Addr};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Mutex;
 use url::Url;
 use zstd::stream::write::Encoder;
 
@@ -111,6 +112,8 @@ pub struct VmSpec {
     pub vm_allocation: Option<VmAllocationStrategy>,
     pub required_host_features: Vec<HostFeature>,
     pub alternate_template: Option<VmType>,
+    pub vm_topology: VmTopology,
+    pub vm_class: VmClass,
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
@@ -285,6 +288,8 @@ pub fn get_resource_request_for_universal_vm(
         vm_allocation: universal_vm.vm_allocation.clone(),
         required_host_features: universal_vm.required_host_features.clone(),
         alternate_template: None,
+        vm_topology: VmTopology::default(),
+        vm_class: VmClass::default(),
     });
     Ok(res_req)
 }
@@ -298,28 +303,11 @@ pub fn allocate_resources(
 
     let mut threads = vec![];
     let mut vm_responses = vec![];
+    let placement_policy = PerformancePlacementPolicy::read_or_default(env);
     for vm_config in req.vm_configs.iter() {
         let farm_cloned = farm.clone();
         let vm_name = vm_config.name.clone();
-        let create_vm_request = CreateVmRequest::new(
-            vm_name.clone(),
-            vm_config
-                .alternate_template
-                .clone()
-                .unwrap_or(VmType::Production),
-            vm_config.vcpus,
-            vm_config.memory_kibibytes,
-            vec![],
-            match &vm_config.boot_image {
-                BootImage::GroupDefault => From::from(req.primary_image.clone()),
-                BootImage::Image(disk_image) => From::from(disk_image.clone()),
-                BootImage::File(id) => ImageLocation::IcOsImageViaId { id: id.clone() },
-            },
-            vm_config.boot_image_minimal_size_gibibytes,
-            vm_config.has_ipv4,
-            vm_config.vm_allocation.clone(),
-            vm_config.required_host_features.clone(),
-        );
+        let create_vm_request = create_vm_request(vm_config, &req.primary_image);
         let group_name = group_name.clone();
 
         match InfraProvider::read_attribute(env) {
@@ -327,7 +315,12 @@ pub fn allocate_resources(
                 threads.push(std::thread::spawn(move || {
                     (
                         vm_name,
-                        farm_cloned.create_vm(&group_name, create_vm_request),
+                        create_vm_with_placement(
+                            &farm_cloned,
+                            &group_name,
+                            create_vm_request,
+                            placement_policy,
+                        ),
                     )
                 }));
             }
@@ -368,7 +361,8 @@ pub fn allocate_resources(
                 let (vm_name, created_vm) = thread
                     .join()
                     .expect("Couldn't join on the associated thread");
-                let VMCreateResponse { ipv6, mac6, .. } = created_vm?;
+                let (VMCreateResponse { ipv6, mac6, .. }, placement) = created_vm?;
+                VmPlacements::record(env, &vm_name, placement);
                 res_group.add_vm(AllocatedVm {
                     name: vm_name,
                     group_name: group_name.clone(),
@@ -396,6 +390,416 @@ pub fn allocate_resources(
     Ok(res_group)
 }
 
+fn create_vm_request(vm_config: &VmSpec, primary_image: &DiskImage) -> CreateVmRequest {
+    CreateVmRequest::new(
+        vm_config.name.clone(),
+        vm_config
+            .alternate_template
+            .clone()
+            .unwrap_or(VmType::Production),
+        vm_config.vcpus,
+        vm_config.memory_kibibytes,
+        vec![],
+        match &vm_config.boot_image {
+            BootImage::GroupDefault => From::from(primary_image.clone()),
+            BootImage::Image(disk_image) => From::from(disk_image.clone()),
+            BootImage::File(id) => ImageLocation::IcOsImageViaId { id: id.clone() },
+        },
+        vm_config.boot_image_minimal_size_gibibytes,
+        vm_config.has_ipv4,
+        vm_config.vm_allocation.clone(),
+        vm_config.required_host_features.clone(),
+    )
+    .with_topology(vm_config.vm_topology)
+    .with_vm_class(vm_config.vm_class)
+}
+
+/// What happens if Farm cannot give a `VmClass::Performance` VM a dedicated
+/// host or pinned CPUs.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
+pub enum PerformancePlacementPolicy {
+    /// Fail the setup.
+    #[default]
+    Fail,
+    /// Continue with a standard VM, and flag the VM as noisy in the reports,
+    /// see `VmPlacements::annotate`.
+    Downgrade,
+}
+
+impl PerformancePlacementPolicy {
+    /// Reads the policy of the group, which is `Fail` unless set otherwise.
+    pub fn read_or_default(env: &TestEnv) -> Self {
+        Self::try_read_attribute(env).unwrap_or_default()
+    }
+}
+
+impl TestEnvAttribute for PerformancePlacementPolicy {
+    fn attribute_name() -> String {
+        "performance_placement_policy".to_string()
+    }
+}
+
+/// The placement a VM ended up with.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct AchievedPlacement {
+    pub requested: VmClass,
+    /// As reported by Farm, if it did.
+    pub placement: Option<VmPlacement>,
+    /// A performance VM that was created as a standard one, or placed on a
+    /// shared host, according to `PerformancePlacementPolicy::Downgrade`.
+    pub downgraded: bool,
+}
+
+impl AchievedPlacement {
+    pub fn is_isolated(&self) -> bool {
+        self.placement
+            .is_some_and(|placement| placement.is_isolated())
+    }
+}
+
+/// The placements of all VMs of the group, by VM name.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
+pub struct VmPlacements(pub BTreeMap<String, AchievedPlacement>);
+
+/// Serializes the updates of the `VmPlacements` of VMs created concurrently.
+static VM_PLACEMENTS_LOCK: Mutex<()> = Mutex::new(());
+
+impl VmPlacements {
+    pub fn read_or_default(env: &TestEnv) -> Self {
+        Self::try_read_attribute(env).unwrap_or_default()
+    }
+
+    pub fn record(env: &TestEnv, vm_name: &str, placement: AchievedPlacement) {
+        Self::update(env, |placements| {
+            placements.0.insert(vm_name.to_string(), placement);
+        })
+    }
+
+    pub fn forget(env: &TestEnv, vm_name: &str) {
+        Self::update(env, |placements| {
+            placements.0.remove(vm_name);
+        })
+    }
+
+    fn update(env: &TestEnv, f: impl FnOnce(&mut Self)) {
+        let _guard = VM_PLACEMENTS_LOCK.lock().unwrap();
+        let mut placements = Self::read_or_default(env);
+        f(&mut placements);
+        placements.write_attribute(env);
+    }
+
+    /// The performance VMs that did not get an isolated placement.
+    pub fn noisy(&self) -> impl Iterator<Item = &str> {
+        self.0
+            .iter()
+            .filter(|(_, placement)| {
+                placement.requested == VmClass::Performance && !placement.is_isolated()
+            })
+            .map(|(vm_name, _)| vm_name.as_str())
+    }
+
+    /// Appends the placement of the VMs to a benchmark `report`, so that
+    /// results of runs without an isolated placement can be told apart:
+    /// `standard` if no performance VM was requested, `isolated` if all of
+    /// them got one, and `noisy` along with the affected VMs otherwise.
+    pub fn annotate(&self, report: &str) -> String {
+        let noisy: Vec<_> = self.noisy().collect();
+        let placement = if !self
+            .0
+            .values()
+            .any(|placement| placement.requested == VmClass::Performance)
+        {
+            "standard".to_string()
+        } else if noisy.is_empty() {
+            "isolated".to_string()
+        } else {
+            format!("noisy ({})", noisy.join(", "))
+        };
+        format!("{report} [placement: {placement}]")
+    }
+}
+
+impl TestEnvAttribute for VmPlacements {
+    fn attribute_name() -> String {
+        "vm_placements".to_string()
+    }
+}
+
+/// Creates the VM of `request`. A performance VM that Farm cannot place on a
+/// dedicated host or pinned CPUs fails with
+/// `FarmError::PerformancePlacementDenied`, unless `policy` allows to continue
+/// with a standard VM.
+fn create_vm_with_placement(
+    farm: &Farm,
+    group_name: &str,
+    request: CreateVmRequest,
+    policy: PerformancePlacementPolicy,
+) -> FarmResult<(VMCreateResponse, AchievedPlacement)> {
+    let vm_name = request.name.clone();
+    let requested = request.vm_class;
+    let downgrade = |reason: &str| {
+        warn!(
+            farm.logger,
+            "DOWNGRADED performance VM {}: {}. Its benchmark results will be flagged as noisy.",
+            vm_name,
+            reason
+        );
+    };
+    let created_vm = match farm.create_vm(group_name, request.clone()) {
+        Err(FarmError::PerformancePlacementDenied { message, .. })
+            if policy == PerformancePlacementPolicy::Downgrade =>
+        {
+            downgrade(&message);
+            let created_vm =
+                farm.create_vm(group_name, request.with_vm_class(VmClass::Standard))?;
+            let placement = AchievedPlacement {
+                requested,
+                placement: created_vm.placement,
+                downgraded: true,
+            };
+            return Ok((created_vm, placement));
+        }
+        result => result?,
+    };
+    let mut placement = AchievedPlacement {
+        requested,
+        placement: created_vm.placement,
+        downgraded: false,
+    };
+    if requested == VmClass::Performance && !placement.is_isolated() {
+        let reason = format!("placed on a shared host ({:?})", created_vm.placement);
+        match policy {
+            PerformancePlacementPolicy::Fail => {
+                if let Err(err) = farm.destroy_vm(group_name, &vm_name) {
+                    warn!(farm.logger, "Failed to destroy VM {}: {:?}", vm_name, err);
+                }
+                return Err(FarmError::PerformancePlacementDenied {
+                    vm_name: vm_name.clone(),
+                    message: reason,
+                });
+            }
+            PerformancePlacementPolicy::Downgrade => {
+                downgrade(&reason);
+                placement.downgraded = true;
+            }
+        }
+    }
+    Ok((created_vm, placement))
+}
+
+/// Allocates and releases single VMs, see `allocate_subnets`.
+pub trait VmAllocator: Sync {
+    fn allocate_vm(&self, vm_config: &VmSpec) -> anyhow::Result<AllocatedVm>;
+    fn release_vm(&self, vm_name: &str) -> anyhow::Result<()>;
+}
+
+/// Allocates VMs in a Farm group.
+/// The placements of the VMs are recorded in `env`, see `VmPlacements`.
+pub struct FarmVmAllocator<'a> {
+    pub farm: &'a Farm,
+    pub group_name: &'a str,
+    pub primary_image: &'a DiskImage,
+    pub env: &'a TestEnv,
+}
+
+impl VmAllocator for FarmVmAllocator<'_> {
+    fn allocate_vm(&self, vm_config: &VmSpec) -> anyhow::Result<AllocatedVm> {
+        let (VMCreateResponse { ipv6, mac6, .. }, placement) = create_vm_with_placement(
+            self.farm,
+            self.group_name,
+            create_vm_request(vm_config, self.primary_image),
+            PerformancePlacementPolicy::read_or_default(self.env),
+        )?;
+        VmPlacements::record(self.env, &vm_config.name, placement);
+        Ok(AllocatedVm {
+            name: vm_config.name.clone(),
+            group_name: self.group_name.to_string(),
+            ipv4: None,
+            ipv6,
+            mac6,
+        })
+    }
+
+    fn release_vm(&self, vm_name: &str) -> anyhow::Result<()> {
+        self.farm.destroy_vm(self.group_name, vm_name)?;
+        VmPlacements::forget(self.env, vm_name);
+        Ok(())
+    }
+}
+
+/// The VMs of a subnet, which are allocated, and retried, as a whole.
+#[derive(Clone, Debug)]
+pub struct SubnetRequest {
+    pub name: String,
+    pub vm_configs: Vec<VmSpec>,
+}
+
+/// How the VMs of a subnet were provisioned.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct SubnetProvisioning {
+    pub name: String,
+    pub attempts: usize,
+    /// The reason of every failed attempt.
+    pub failures: Vec<String>,
+    /// The VMs of the last attempt; empty if all attempts failed.
+    pub vms: Vec<String>,
+}
+
+impl SubnetProvisioning {
+    pub fn succeeded(&self) -> bool {
+        self.failures.len() < self.attempts
+    }
+}
+
+/// Which subnets needed retries to be provisioned, and why.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
+pub struct SubnetProvisioningReport {
+    pub subnets: Vec<SubnetProvisioning>,
+}
+
+impl SubnetProvisioningReport {
+    pub fn retried(&self) -> impl Iterator<Item = &SubnetProvisioning> {
+        self.subnets.iter().filter(|subnet| subnet.attempts > 1)
+    }
+
+    /// Fails if any subnet could not be provisioned.
+    pub fn check(&self) -> anyhow::Result<()> {
+        let failed: Vec<_> = self
+            .subnets
+            .iter()
+            .filter(|subnet| !subnet.succeeded())
+            .collect();
+        if failed.is_empty() {
+            return Ok(());
+        }
+        bail!(
+            "failed to provision {}",
+            failed
+                .iter()
+                .map(|subnet| format!(
+                    "{} after {} attempts (last error: {})",
+                    subnet.name,
+                    subnet.attempts,
+                    subnet.failures.last().map_or("none", String::as_str)
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl TestEnvAttribute for SubnetProvisioningReport {
+    fn attribute_name() -> String {
+        "subnet_provisioning_report".to_string()
+    }
+}
+
+/// Allocates the VMs of all subnets concurrently.
+///
+/// If any VM of a subnet cannot be allocated, the subnet's other VMs are
+/// released and the subnet is retried, up to `max_retries` times, with fresh
+/// VMs obtained from `respec`. Other subnets are not affected. The returned
+/// group contains the VMs of the subnets that could be provisioned; see
+/// `SubnetProvisioningReport::check` for whether all could.
+pub fn allocate_subnets<A: VmAllocator>(
+    allocator: &A,
+    group_name: &str,
+    subnets: Vec<SubnetRequest>,
+    max_retries: usize,
+    respec: &(dyn Fn(&VmSpec) -> anyhow::Result<VmSpec> + Sync),
+    log: &Logger,
+) -> (ResourceGroup, SubnetProvisioningReport) {
+    let results: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = subnets
+            .into_iter()
+            .map(|subnet| {
+                scope.spawn(move || allocate_subnet(allocator, subnet, max_retries, respec, log))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("subnet allocation panicked"))
+            .collect()
+    });
+
+    let mut res_group = ResourceGroup::new(group_name);
+    let mut report = SubnetProvisioningReport::default();
+    for (provisioning, vms) in results {
+        vms.into_iter().for_each(|vm| res_group.add_vm(vm));
+        report.subnets.push(provisioning);
+    }
+    (res_group, report)
+}
+
+fn allocate_subnet<A: VmAllocator>(
+    allocator: &A,
+    subnet: SubnetRequest,
+    max_retries: usize,
+    respec: &(dyn Fn(&VmSpec) -> anyhow::Result<VmSpec> + Sync),
+    log: &Logger,
+) -> (SubnetProvisioning, Vec<AllocatedVm>) {
+    let mut provisioning = SubnetProvisioning {
+        name: subnet.name,
+        attempts: 0,
+        failures: vec![],
+        vms: vec![],
+    };
+    let mut vm_configs = subnet.vm_configs;
+    loop {
+        provisioning.attempts += 1;
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = vm_configs
+                .iter()
+                .map(|vm_config| scope.spawn(move || allocator.allocate_vm(vm_config)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("VM allocation panicked"))
+                .collect()
+        });
+        let mut allocated = vec![];
+        let mut errors = vec![];
+        for (vm_config, result) in vm_configs.iter().zip(results) {
+            match result {
+                Ok(vm) => allocated.push(vm),
+                Err(err) => errors.push(format!("{}: {:?}", vm_config.name, err)),
+            }
+        }
+        if errors.is_empty() {
+            provisioning.vms = allocated.iter().map(|vm| vm.name.clone()).collect();
+            return (provisioning, allocated);
+        }
+
+        let reason = errors.join("; ");
+        warn!(
+            log,
+            "Attempt {} to allocate the VMs of {} failed: {}",
+            provisioning.attempts,
+            provisioning.name,
+            reason
+        );
+        provisioning.failures.push(reason);
+        // Don't leave the abandoned VMs running.
+        for vm in allocated {
+            if let Err(err) = allocator.release_vm(&vm.name) {
+                warn!(log, "Failed to release VM {}: {:?}", vm.name, err);
+            }
+        }
+        if provisioning.attempts > max_retries {
+            return (provisioning, vec![]);
+        }
+        match vm_configs.iter().map(respec).collect::<anyhow::Result<_>>() {
+            Ok(fresh_vm_configs) => vm_configs = fresh_vm_configs,
+            Err(err) => {
+                provisioning
+                    .failures
+                    .push(format!("failed to prepare fresh VMs: {:?}", err));
+                return (provisioning, vec![]);
+            }
+        }
+    }
+}
+
 fn vm_spec_from_node(n: &Node, default_vm_resources: Option<VmResources>) -> VmSpec {
     let vm_resources = &n.vm_resources;
     VmSpec {
@@ -421,30 +825,38 @@ fn vm_spec_from_node(n: &Node, default_vm_resources: Option<VmResources>) -> VmS
         vm_allocation: n.vm_allocation.clone(),
         required_host_features: n.required_host_features.clone(),
         alternate_template: None,
+        vm_topology: n.vm_topology,
+        vm_class: n.vm_class,
     }
 }
 
-/// Create a `VmSpec` for a given Nested VM, using the specified image file.
-fn vm_spec_from_nested_node(
+/// Create a `VmSpec` for a given Nested VM, booting from the specified image
+/// file unless the node specifies its own primary image.
+pub(crate) fn vm_spec_from_nested_node(
     node: &NestedNode,
     default_vm_resources: Option<VmResources>,
     image: FileId,
 ) -> VmSpec {
+    let vm_resources = node
+        .vm_resources
+        .or(&default_vm_resources.unwrap_or_default());
     VmSpec {
         name: node.name.clone(),
-        vcpus: default_vm_resources
-            .and_then(|vm_resources| vm_resources.vcpus)
-            .unwrap_or(DEFAULT_VCPUS_PER_VM),
-        memory_kibibytes: default_vm_resources
-            .and_then(|vm_resources| vm_resources.memory_kibibytes)
+        vcpus: vm_resources.vcpus.unwrap_or(DEFAULT_VCPUS_PER_VM),
+        memory_kibibytes: vm_resources
+            .memory_kibibytes
             .unwrap_or(DEFAULT_MEMORY_KIB_PER_VM),
-        boot_image: BootImage::File(image),
-        boot_image_minimal_size_gibibytes: default_vm_resources
-            .and_then(|vm_resources| vm_resources.boot_image_minimal_size_gibibytes),
+        boot_image: node
+            .primary_image
+            .clone()
+            .map_or(BootImage::File(image), BootImage::Image),
+        boot_image_minimal_size_gibibytes: vm_resources.boot_image_minimal_size_gibibytes,
         has_ipv4: false,
         vm_allocation: None,
         required_host_features: Vec::new(),
         alternate_template: Some(VmType::Nested),
+        vm_topology: VmTopology::default(),
+        vm_class: VmClass::default(),
     }
 }
 
@@ -488,3 +900,428 @@ pub fn build_empty_image(tmp_dir: &Path, out_file_name: &str) -> anyhow::Result<
 
     Ok(compressed_img_path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use std::sync::Mutex;
+
+    /// Fails to allocate the VMs whose names are in `failing`, and records
+    /// all calls.
+    #[derive(Default)]
+    struct FakeAllocator {
+        failing: BTreeSet<String>,
+        allocated: Mutex<Vec<String>>,
+        released: Mutex<Vec<String>>,
+    }
+
+    impl VmAllocator for FakeAllocator {
+        fn allocate_vm(&self, vm_config: &VmSpec) -> anyhow::Result<AllocatedVm> {
+            if self.failing.contains(&vm_config.name) {
+                bail!("VM did not come up");
+            }
+            self.allocated.lock().unwrap().push(vm_config.name.clone());
+            Ok(AllocatedVm {
+                name: vm_config.name.clone(),
+                group_name: "group".to_string(),
+                ipv6: Ipv6Addr::LOCALHOST,
+                mac6: String::new(),
+                ipv4: None,
+            })
+        }
+
+        fn release_vm(&self, vm_name: &str) -> anyhow::Result<()> {
+            self.released.lock().unwrap().push(vm_name.to_string());
+            Ok(())
+        }
+    }
+
+    fn vm_spec(name: &str) -> VmSpec {
+        VmSpec {
+            name: name.to_string(),
+            vcpus: DEFAULT_VCPUS_PER_VM,
+            memory_kibibytes: DEFAULT_MEMORY_KIB_PER_VM,
+            boot_image: BootImage::GroupDefault,
+            boot_image_minimal_size_gibibytes: None,
+            has_ipv4: false,
+            vm_allocation: None,
+            required_host_features: vec![],
+            alternate_template: None,
+            vm_topology: VmTopology::default(),
+            vm_class: VmClass::default(),
+        }
+    }
+
+    fn subnets() -> Vec<SubnetRequest> {
+        (0..3)
+            .map(|subnet| SubnetRequest {
+                name: format!("subnet {subnet}"),
+                vm_configs: (0..4)
+                    .map(|node| vm_spec(&format!("s{subnet}-n{node}")))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Replaces a VM by one with an `'` appended to its name.
+    fn respec(vm_config: &VmSpec) -> anyhow::Result<VmSpec> {
+        Ok(vm_spec(&format!("{}'", vm_config.name)))
+    }
+
+    fn logger() -> Logger {
+        Logger::root(slog::Discard, slog::o!())
+    }
+
+    fn sorted(mut names: Vec<String>) -> Vec<String> {
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn retries_only_the_failed_subnet() {
+        let allocator = FakeAllocator {
+            failing: BTreeSet::from(["s1-n2".to_string()]),
+            ..Default::default()
+        };
+        let (res_group, report) =
+            allocate_subnets(&allocator, "group", subnets(), 2, &respec, &logger());
+
+        report.check().unwrap();
+        let attempts: Vec<_> = report
+            .subnets
+            .iter()
+            .map(|subnet| (subnet.name.as_str(), subnet.attempts))
+            .collect();
+        assert_eq!(
+            attempts,
+            vec![("subnet 0", 1), ("subnet 1", 2), ("subnet 2", 1)]
+        );
+        let retried: Vec<_> = report.retried().collect();
+        assert_eq!(retried.len(), 1);
+        assert_eq!(retried[0].failures.len(), 1);
+        assert!(retried[0].failures[0].starts_with("s1-n2: VM did not come up"));
+
+        // The VMs of the first attempt were abandoned and released.
+        assert_eq!(
+            sorted(allocator.released.into_inner().unwrap()),
+            vec!["s1-n0", "s1-n1", "s1-n3"]
+        );
+        // The other subnets were allocated once.
+        let allocated = allocator.allocated.into_inner().unwrap();
+        for vm in ["s0-n0", "s2-n3"] {
+            assert_eq!(allocated.iter().filter(|name| *name == vm).count(), 1);
+        }
+
+        // The final topology has all subnets, with fresh VMs for subnet 1.
+        assert_eq!(
+            res_group.vms.keys().cloned().collect::<Vec<_>>(),
+            sorted(
+                (0..3)
+                    .flat_map(|subnet| (0..4).map(move |node| match subnet {
+                        1 => format!("s1-n{node}'"),
+                        _ => format!("s{subnet}-n{node}"),
+                    }))
+                    .collect()
+            )
+        );
+        assert_eq!(
+            report.subnets[1].vms,
+            vec!["s1-n0'", "s1-n1'", "s1-n2'", "s1-n3'"]
+        );
+    }
+
+    #[test]
+    fn fails_when_retries_are_exhausted() {
+        let allocator = FakeAllocator {
+            failing: BTreeSet::from([
+                "s2-n0".to_string(),
+                "s2-n0'".to_string(),
+                "s2-n0''".to_string(),
+            ]),
+            ..Default::default()
+        };
+        let (res_group, report) =
+            allocate_subnets(&allocator, "group", subnets(), 2, &respec, &logger());
+
+        let err = report.check().unwrap_err().to_string();
+        assert!(err.contains("subnet 2 after 3 attempts"), "{err}");
+        assert!(report.subnets[..2]
+            .iter()
+            .all(SubnetProvisioning::succeeded));
+        assert_eq!(report.subnets[2].failures.len(), 3);
+        assert!(report.subnets[2].vms.is_empty());
+
+        // No VM of the failed subnet is left behind.
+        let released = allocator.released.into_inner().unwrap();
+        let allocated = allocator.allocated.into_inner().unwrap();
+        let allocated_in_subnet_2: Vec<_> = allocated
+            .into_iter()
+            .filter(|name| name.starts_with("s2-"))
+            .collect();
+        assert_eq!(sorted(released), sorted(allocated_in_subnet_2));
+        assert_eq!(res_group.vms.len(), 8);
+        assert!(res_group.vms.keys().all(|name| !name.starts_with("s2-")));
+    }
+
+    mod performance_placement {
+        use super::*;
+        use mockito::Matcher;
+        use serde_json::json;
+
+        const CREATE_VM: &str = "/group/test-group/vm/vm-1";
+
+        struct Fixture {
+            server: mockito::ServerGuard,
+            farm: Farm,
+            env: TestEnv,
+            _tempdir: tempfile::TempDir,
+        }
+
+        fn fixture(policy: Option<PerformancePlacementPolicy>) -> Fixture {
+            let server = mockito::Server::new();
+            let farm = Farm::new(Url::parse(&format!("{}/", server.url())).unwrap(), logger());
+            let tempdir = tempfile::tempdir().unwrap();
+            let env = TestEnv::new_without_duplicating_logger(tempdir.path(), logger());
+            if let Some(policy) = policy {
+                policy.write_attribute(&env);
+            }
+            Fixture {
+                server,
+                farm,
+                env,
+                _tempdir: tempdir,
+            }
+        }
+
+        fn created_vm(placement: Option<serde_json::Value>) -> String {
+            let mut vm = json!({
+                "ipv6": "2a0b:21c0:4003:2:5000:ff:fe00:1",
+                "mac6": "52:00:00:00:00:01",
+                "hostname": "host-1",
+                "spec": {"vCPUs": 6, "memoryKiB": 25165824},
+            });
+            if let Some(placement) = placement {
+                vm["placement"] = placement;
+            }
+            vm.to_string()
+        }
+
+        fn performance_vm_spec() -> VmSpec {
+            VmSpec {
+                vm_class: VmClass::Performance,
+                ..vm_spec("vm-1")
+            }
+        }
+
+        fn allocate(fixture: &Fixture) -> anyhow::Result<AllocatedVm> {
+            let allocator = FarmVmAllocator {
+                farm: &fixture.farm,
+                group_name: "test-group",
+                primary_image: &DiskImage {
+                    image_type: ImageType::IcOsImage,
+                    url: Url::parse("https://example.com/disk-img.tar.zst").unwrap(),
+                    sha256: "na".to_string(),
+                },
+                env: &fixture.env,
+            };
+            allocator.allocate_vm(&performance_vm_spec())
+        }
+
+        #[test]
+        fn records_granted_placement() {
+            let mut fixture = fixture(None);
+            let create_vm = fixture
+                .server
+                .mock("POST", CREATE_VM)
+                .match_body(Matcher::PartialJson(json!({"vmClass": "performance"})))
+                .with_status(200)
+                .with_body(created_vm(Some(json!({"cpuPinned": true}))))
+                .expect(1)
+                .create();
+
+            allocate(&fixture).unwrap();
+
+            create_vm.assert();
+            let placements = VmPlacements::read_attribute(&fixture.env);
+            assert_eq!(
+                placements.0["vm-1"],
+                AchievedPlacement {
+                    requested: VmClass::Performance,
+                    placement: Some(VmPlacement {
+                        dedicated_host: false,
+                        cpu_pinned: true,
+                    }),
+                    downgraded: false,
+                }
+            );
+            assert_eq!(
+                placements.annotate("tps=100"),
+                "tps=100 [placement: isolated]"
+            );
+        }
+
+        #[test]
+        fn fails_by_default_if_placement_is_denied() {
+            let mut fixture = fixture(None);
+            let create_vm = fixture
+                .server
+                .mock("POST", CREATE_VM)
+                .with_status(400)
+                .with_body("no dedicated host available")
+                .expect(1)
+                .create();
+
+            let err = allocate(&fixture).unwrap_err();
+
+            create_vm.assert();
+            assert!(
+                matches!(
+                    err.downcast_ref::<FarmError>(),
+                    Some(FarmError::PerformancePlacementDenied { vm_name, message })
+                        if vm_name == "vm-1" && message == "no dedicated host available"
+                ),
+                "unexpected error: {err:?}"
+            );
+            assert!(VmPlacements::read_or_default(&fixture.env).0.is_empty());
+        }
+
+        #[test]
+        fn fails_and_destroys_vm_placed_on_shared_host() {
+            let mut fixture = fixture(Some(PerformancePlacementPolicy::Fail));
+            let create_vm = fixture
+                .server
+                .mock("POST", CREATE_VM)
+                .with_status(200)
+                .with_body(created_vm(None))
+                .expect(1)
+                .create();
+            let destroy_vm = fixture
+                .server
+                .mock("PUT", "/group/test-group/vm/vm-1/destroy")
+                .with_status(200)
+                .expect(1)
+                .create();
+
+            let err = allocate(&fixture).unwrap_err();
+
+            create_vm.assert();
+            destroy_vm.assert();
+            assert!(
+                matches!(
+                    err.downcast_ref::<FarmError>(),
+                    Some(FarmError::PerformancePlacementDenied { .. })
+                ),
+                "unexpected error: {err:?}"
+            );
+        }
+
+        #[test]
+        fn downgrades_denied_placement_if_allowed() {
+            let mut fixture = fixture(Some(PerformancePlacementPolicy::Downgrade));
+            let denied = fixture
+                .server
+                .mock("POST", CREATE_VM)
+                .match_body(Matcher::PartialJson(json!({"vmClass": "performance"})))
+                .with_status(400)
+                .with_body("no dedicated host available")
+                .expect(1)
+                .create();
+            let standard = fixture
+                .server
+                .mock("POST", CREATE_VM)
+                .with_status(200)
+                .with_body(created_vm(None))
+                .expect(1)
+                .create();
+
+            allocate(&fixture).unwrap();
+
+            denied.assert();
+            standard.assert();
+            let placements = VmPlacements::read_attribute(&fixture.env);
+            assert_eq!(
+                placements.0["vm-1"],
+                AchievedPlacement {
+                    requested: VmClass::Performance,
+                    placement: None,
+                    downgraded: true,
+                }
+            );
+            assert_eq!(
+                placements.annotate("tps=100"),
+                "tps=100 [placement: noisy (vm-1)]"
+            );
+        }
+
+        #[test]
+        fn downgrades_vm_placed_on_shared_host_if_allowed() {
+            let mut fixture = fixture(Some(PerformancePlacementPolicy::Downgrade));
+            let create_vm = fixture
+                .server
+                .mock("POST", CREATE_VM)
+                .with_status(200)
+                .with_body(created_vm(Some(json!({"dedicatedHost": false}))))
+                .expect(1)
+                .create();
+
+            allocate(&fixture).unwrap();
+
+            create_vm.assert();
+            let placements = VmPlacements::read_attribute(&fixture.env);
+            assert!(placements.0["vm-1"].downgraded);
+            assert_eq!(placements.noisy().collect::<Vec<_>>(), vec!["vm-1"]);
+        }
+
+        #[test]
+        fn forgets_placement_of_released_vm() {
+            let mut fixture = fixture(None);
+            let _create_vm = fixture
+                .server
+                .mock("POST", CREATE_VM)
+                .with_status(200)
+                .with_body(created_vm(Some(json!({"dedicatedHost": true}))))
+                .create();
+            let _destroy_vm = fixture
+                .server
+                .mock("PUT", "/group/test-group/vm/vm-1/destroy")
+                .with_status(200)
+                .create();
+            let allocator = FarmVmAllocator {
+                farm: &fixture.farm,
+                group_name: "test-group",
+                primary_image: &DiskImage {
+                    image_type: ImageType::IcOsImage,
+                    url: Url::parse("https://example.com/disk-img.tar.zst").unwrap(),
+                    sha256: "na".to_string(),
+                },
+                env: &fixture.env,
+            };
+
+            allocator.allocate_vm(&performance_vm_spec()).unwrap();
+            allocator.release_vm("vm-1").unwrap();
+
+            assert!(VmPlacements::read_attribute(&fixture.env).0.is_empty());
+        }
+
+        #[test]
+        fn annotates_runs_without_performance_vms_as_standard() {
+            let placements = VmPlacements(BTreeMap::from([(
+                "vm-1".to_string(),
+                AchievedPlacement {
+                    requested: VmClass::Standard,
+                    placement: None,
+                    downgraded: false,
+                },
+            )]));
+            assert_eq!(
+                placements.annotate("tps=100"),
+                "tps=100 [placement: standard]"
+            );
+            assert_eq!(
+                VmPlacements::default().annotate("tps=100"),
+                "tps=100 [placement: standard]"
+            );
+        }
+    }
+}
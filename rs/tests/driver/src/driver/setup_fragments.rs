@@ -0,0 +1,511 @@
+//! Setup functions composed from reusable fragments.
+//!
+//! A [`SetupFragment`] is a building block of the setup of a group, e.g. a
+//! system subnet or a Prometheus VM. [`SetupFragments`] applies a list of
+//! fragments in three phases, each in the order the fragments were added:
+//! 1. `before_start` of every fragment, e.g. to start VMs the IC depends on,
+//! 2. `configure` of every fragment on a fresh [`InternetComputer`], which is
+//!    then started,
+//! 3. `after_start` of every fragment, e.g. to install canisters.
+//!
+//! Every fragment declares the capabilities it provides. Fragments whose
+//! capabilities conflict, e.g. two fragments installing the NNS, are rejected
+//! when they are registered with a group via
+//! [`SystemTestGroup::with_setup_fragments`].
+//!
+//! [`SystemTestGroup::with_setup_fragments`]: crate::driver::group::SystemTestGroup::with_setup_fragments
+use crate::driver::{
+    driver_setup::Dependency,
+    ic::{InternetComputer, Subnet},
+    prometheus_vm::{HasPrometheus, PrometheusVm},
+    test_env::TestEnv,
+    test_env_api::{HasTopologySnapshot, IcNodeContainer, NnsInstallationBuilder},
+};
+use ic_registry_subnet_type::SubnetType;
+use slog::info;
+use std::fmt;
+use std::panic::UnwindSafe;
+
+/// Something a fragment provides to the setup of a group.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SetupCapability {
+    /// The NNS canisters are installed on the root subnet.
+    NnsInstalled,
+    /// The IC has `count` subnets of type `subnet_type`.
+    Subnets {
+        subnet_type: SubnetType,
+        count: usize,
+    },
+    /// The IC has `count` unassigned nodes.
+    UnassignedNodes { count: usize },
+    /// A Prometheus VM scrapes the IC.
+    Prometheus,
+}
+
+impl SetupCapability {
+    /// Whether two fragments cannot provide `self` and `other` in the same
+    /// setup.
+    ///
+    /// Counts are exclusive: if two fragments both declare subnets of the same
+    /// type, the resulting number of subnets matches neither declaration.
+    pub fn conflicts_with(&self, other: &SetupCapability) -> bool {
+        use SetupCapability::*;
+        match (self, other) {
+            (NnsInstalled, NnsInstalled) | (Prometheus, Prometheus) => true,
+            (UnassignedNodes { .. }, UnassignedNodes { .. }) => true,
+            (
+                Subnets {
+                    subnet_type: left, ..
+                },
+                Subnets {
+                    subnet_type: right, ..
+                },
+            ) => left == right,
+            _ => false,
+        }
+    }
+}
+
+/// A reusable building block of the setup of a group.
+pub trait SetupFragment: Send + Sync + UnwindSafe {
+    /// The name of the fragment, used to report conflicts.
+    fn name(&self) -> String;
+
+    /// The capabilities this fragment provides.
+    fn capabilities(&self) -> Vec<SetupCapability>;
+
+    /// The files from the runfiles this fragment depends on, see
+    /// [`crate::driver::driver_setup`].
+    fn dependencies(&self) -> Vec<Dependency> {
+        vec![]
+    }
+
+    /// Runs before the IC is started.
+    fn before_start(&self, _env: &TestEnv) {}
+
+    /// Adds to the configuration of the IC.
+    fn configure(&self, _ic: &mut InternetComputer) {}
+
+    /// Runs after the IC is started.
+    fn after_start(&self, _env: &TestEnv) {}
+}
+
+/// Two fragments of the same setup declare conflicting capabilities.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SetupFragmentConflict {
+    pub first: String,
+    pub first_capability: SetupCapability,
+    pub second: String,
+    pub second_capability: SetupCapability,
+}
+
+impl fmt::Display for SetupFragmentConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "setup fragments '{}' and '{}' declare conflicting capabilities {:?} and {:?}",
+            self.first, self.second, self.first_capability, self.second_capability
+        )
+    }
+}
+
+/// A setup function applying a list of fragments, see the module
+/// documentation.
+#[derive(Default)]
+pub struct SetupFragments {
+    fragments: Vec<Box<dyn SetupFragment>>,
+}
+
+impl SetupFragments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `fragment` to the fragments to apply.
+    pub fn with<F: SetupFragment + 'static>(mut self, fragment: F) -> Self {
+        self.fragments.push(Box::new(fragment));
+        self
+    }
+
+    /// The names of the fragments, in the order they are applied.
+    pub fn names(&self) -> Vec<String> {
+        self.fragments
+            .iter()
+            .map(|fragment| fragment.name())
+            .collect()
+    }
+
+    /// The dependencies of all fragments.
+    pub fn dependencies(&self) -> Vec<Dependency> {
+        self.fragments
+            .iter()
+            .flat_map(|fragment| fragment.dependencies())
+            .collect()
+    }
+
+    /// Returns the first pair of fragments, in the order they were added,
+    /// that declare conflicting capabilities.
+    pub fn check_conflicts(&self) -> Result<(), SetupFragmentConflict> {
+        let declared: Vec<(String, Vec<SetupCapability>)> = self
+            .fragments
+            .iter()
+            .map(|fragment| (fragment.name(), fragment.capabilities()))
+            .collect();
+        for (i, (first, first_capabilities)) in declared.iter().enumerate() {
+            for (second, second_capabilities) in &declared[i + 1..] {
+                for first_capability in first_capabilities {
+                    if let Some(second_capability) = second_capabilities
+                        .iter()
+                        .find(|capability| first_capability.conflicts_with(capability))
+                    {
+                        return Err(SetupFragmentConflict {
+                            first: first.clone(),
+                            first_capability: first_capability.clone(),
+                            second: second.clone(),
+                            second_capability: second_capability.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The configuration of the IC resulting from applying `configure` of all
+    /// fragments to a fresh `InternetComputer`.
+    pub fn configure_ic(&self) -> InternetComputer {
+        let mut ic = InternetComputer::new();
+        for fragment in &self.fragments {
+            fragment.configure(&mut ic);
+        }
+        ic
+    }
+
+    /// Sets up the group in `env` by applying all fragments.
+    pub fn apply(&self, env: TestEnv) {
+        info!(
+            env.logger(),
+            "Applying setup fragments: {}",
+            self.names().join(", ")
+        );
+        for fragment in &self.fragments {
+            fragment.before_start(&env);
+        }
+        self.configure_ic()
+            .setup_and_start(&env)
+            .expect("failed to setup IC under test");
+        for fragment in &self.fragments {
+            fragment.after_start(&env);
+        }
+    }
+}
+
+/// A one-node subnet of the given type that is optimized to be fast, see
+/// `InternetComputer::add_fast_single_node_subnet`.
+pub struct FastSingleNodeSubnet(pub SubnetType);
+
+impl SetupFragment for FastSingleNodeSubnet {
+    fn name(&self) -> String {
+        format!("fast_single_node_subnet({:?})", self.0)
+    }
+
+    fn capabilities(&self) -> Vec<SetupCapability> {
+        vec![SetupCapability::Subnets {
+            subnet_type: self.0,
+            count: 1,
+        }]
+    }
+
+    fn configure(&self, ic: &mut InternetComputer) {
+        *ic = std::mem::take(ic).add_fast_single_node_subnet(self.0);
+    }
+}
+
+/// Subnets configured by the caller, all of the same type.
+pub struct CustomSubnets(pub Vec<Subnet>);
+
+impl CustomSubnets {
+    /// A single subnet configured by the caller.
+    pub fn single(subnet: Subnet) -> Self {
+        Self(vec![subnet])
+    }
+
+    fn subnet_type(&self) -> SubnetType {
+        let subnet_type = self
+            .0
+            .first()
+            .map(|subnet| subnet.subnet_type)
+            .expect("no subnets given");
+        assert!(
+            self.0
+                .iter()
+                .all(|subnet| subnet.subnet_type == subnet_type),
+            "subnets of different types given"
+        );
+        subnet_type
+    }
+}
+
+impl SetupFragment for CustomSubnets {
+    fn name(&self) -> String {
+        format!(
+            "custom_subnets({:?} x {})",
+            self.subnet_type(),
+            self.0.len()
+        )
+    }
+
+    fn capabilities(&self) -> Vec<SetupCapability> {
+        vec![SetupCapability::Subnets {
+            subnet_type: self.subnet_type(),
+            count: self.0.len(),
+        }]
+    }
+
+    fn configure(&self, ic: &mut InternetComputer) {
+        for subnet in &self.0 {
+            *ic = std::mem::take(ic).add_subnet(subnet.clone());
+        }
+    }
+}
+
+/// The given number of unassigned nodes.
+pub struct UnassignedNodes(pub usize);
+
+impl SetupFragment for UnassignedNodes {
+    fn name(&self) -> String {
+        format!("unassigned_nodes({})", self.0)
+    }
+
+    fn capabilities(&self) -> Vec<SetupCapability> {
+        vec![SetupCapability::UnassignedNodes { count: self.0 }]
+    }
+
+    fn configure(&self, ic: &mut InternetComputer) {
+        *ic = std::mem::take(ic).with_unassigned_nodes(self.0);
+    }
+}
+
+/// A Prometheus VM, started before the IC and synced with its topology
+/// afterwards.
+pub struct Prometheus;
+
+impl SetupFragment for Prometheus {
+    fn name(&self) -> String {
+        "prometheus".to_string()
+    }
+
+    fn capabilities(&self) -> Vec<SetupCapability> {
+        vec![SetupCapability::Prometheus]
+    }
+
+    fn before_start(&self, env: &TestEnv) {
+        PrometheusVm::default()
+            .start(env)
+            .expect("failed to start prometheus VM");
+    }
+
+    fn after_start(&self, env: &TestEnv) {
+        env.sync_with_prometheus();
+    }
+}
+
+/// The NNS canisters, installed on the first node of the root subnet.
+pub struct NnsCanisters;
+
+impl SetupFragment for NnsCanisters {
+    fn name(&self) -> String {
+        "nns_canisters".to_string()
+    }
+
+    fn capabilities(&self) -> Vec<SetupCapability> {
+        vec![SetupCapability::NnsInstalled]
+    }
+
+    fn after_start(&self, env: &TestEnv) {
+        let nns_node = env
+            .topology_snapshot()
+            .root_subnet()
+            .nodes()
+            .next()
+            .expect("no node in the root subnet");
+        NnsInstallationBuilder::new()
+            .install(&nns_node, env)
+            .expect("could not install NNS canisters");
+    }
+}
+
+/// Waits until all nodes of all subnets report a healthy status.
+pub struct AwaitHealthyNodes;
+
+impl SetupFragment for AwaitHealthyNodes {
+    fn name(&self) -> String {
+        "await_healthy_nodes".to_string()
+    }
+
+    fn capabilities(&self) -> Vec<SetupCapability> {
+        vec![]
+    }
+
+    fn after_start(&self, env: &TestEnv) {
+        env.topology_snapshot().subnets().for_each(|subnet| {
+            subnet
+                .nodes()
+                .for_each(|node| node.await_status_is_healthy().unwrap())
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::group::SystemTestGroup;
+
+    fn nns_and_app_subnet() -> SetupFragments {
+        SetupFragments::new()
+            .with(FastSingleNodeSubnet(SubnetType::System))
+            .with(NnsCanisters)
+            .with(CustomSubnets::single(
+                Subnet::new(SubnetType::Application).add_nodes(4),
+            ))
+            .with(Prometheus)
+    }
+
+    #[test]
+    fn should_configure_ic_in_the_order_of_the_fragments() {
+        let ic = SetupFragments::new()
+            .with(CustomSubnets::single(
+                Subnet::new(SubnetType::Application).add_nodes(2),
+            ))
+            .with(FastSingleNodeSubnet(SubnetType::System))
+            .with(UnassignedNodes(1))
+            .configure_ic();
+
+        let subnets: Vec<(SubnetType, usize)> = ic
+            .subnets
+            .iter()
+            .map(|subnet| (subnet.subnet_type, subnet.nodes.len()))
+            .collect();
+        assert_eq!(
+            subnets,
+            vec![(SubnetType::Application, 2), (SubnetType::System, 1)]
+        );
+        assert_eq!(ic.unassigned_nodes.len(), 1);
+    }
+
+    #[test]
+    fn should_configure_same_ic_as_hand_written_setup() {
+        let hand_written = InternetComputer::new()
+            .add_fast_single_node_subnet(SubnetType::System)
+            .add_subnet(Subnet::new(SubnetType::Application).add_nodes(4));
+
+        let from_fragments = nns_and_app_subnet().configure_ic();
+
+        assert_eq!(
+            format!("{:?}", from_fragments),
+            format!("{:?}", hand_written)
+        );
+    }
+
+    #[test]
+    fn should_configure_same_ic_as_hand_written_setup_with_unassigned_nodes() {
+        let hand_written = InternetComputer::new()
+            .add_subnet(Subnet::new(SubnetType::System).add_nodes(1))
+            .add_subnet(Subnet::new(SubnetType::Application).add_nodes(1))
+            .add_subnet(Subnet::new(SubnetType::Application).add_nodes(2))
+            .with_unassigned_nodes(1);
+
+        let from_fragments = SetupFragments::new()
+            .with(CustomSubnets::single(
+                Subnet::new(SubnetType::System).add_nodes(1),
+            ))
+            .with(CustomSubnets(vec![
+                Subnet::new(SubnetType::Application).add_nodes(1),
+                Subnet::new(SubnetType::Application).add_nodes(2),
+            ]))
+            .with(UnassignedNodes(1))
+            .configure_ic();
+
+        assert_eq!(
+            format!("{:?}", from_fragments),
+            format!("{:?}", hand_written)
+        );
+    }
+
+    #[test]
+    fn should_accept_fragments_without_conflicts() {
+        assert_eq!(nns_and_app_subnet().check_conflicts(), Ok(()));
+        assert_eq!(
+            nns_and_app_subnet().names(),
+            vec![
+                "fast_single_node_subnet(System)",
+                "nns_canisters",
+                "custom_subnets(Application x 1)",
+                "prometheus"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_detect_two_nns_installations() {
+        let fragments = nns_and_app_subnet().with(NnsCanisters);
+
+        assert_eq!(
+            fragments.check_conflicts(),
+            Err(SetupFragmentConflict {
+                first: "nns_canisters".to_string(),
+                first_capability: SetupCapability::NnsInstalled,
+                second: "nns_canisters".to_string(),
+                second_capability: SetupCapability::NnsInstalled,
+            })
+        );
+    }
+
+    #[test]
+    fn should_detect_conflicting_subnet_counts() {
+        let fragments = nns_and_app_subnet().with(CustomSubnets(vec![
+            Subnet::new(SubnetType::Application).add_nodes(1),
+            Subnet::new(SubnetType::Application).add_nodes(1),
+        ]));
+
+        assert_eq!(
+            fragments.check_conflicts(),
+            Err(SetupFragmentConflict {
+                first: "custom_subnets(Application x 1)".to_string(),
+                first_capability: SetupCapability::Subnets {
+                    subnet_type: SubnetType::Application,
+                    count: 1
+                },
+                second: "custom_subnets(Application x 2)".to_string(),
+                second_capability: SetupCapability::Subnets {
+                    subnet_type: SubnetType::Application,
+                    count: 2
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn should_not_conflict_on_subnets_of_different_types() {
+        let fragments = SetupFragments::new()
+            .with(FastSingleNodeSubnet(SubnetType::System))
+            .with(FastSingleNodeSubnet(SubnetType::Application))
+            .with(FastSingleNodeSubnet(SubnetType::VerifiedApplication));
+
+        assert_eq!(fragments.check_conflicts(), Ok(()));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "setup fragments 'fast_single_node_subnet(System)' and 'custom_subnets(System x 1)'"
+    )]
+    fn should_fail_registration_of_conflicting_fragments() {
+        let _ = SystemTestGroup::new().with_setup_fragments(
+            SetupFragments::new()
+                .with(FastSingleNodeSubnet(SubnetType::System))
+                .with(CustomSubnets::single(
+                    Subnet::new(SubnetType::System).add_nodes(4),
+                )),
+        );
+    }
+}
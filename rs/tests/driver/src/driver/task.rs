@@ -53,9 +53,14 @@ pub struct EmptyTask {
     task_id: TaskId,
 }
 
+/// Prefix of the message of tests skipped since one of their prerequisites
+/// did not pass.
+pub const SKIPPED_DUE_TO_DEPENDENCY_FAILURE: &str = "Task skipped due to dependency failure";
+
 pub struct SkipTestTask {
     spawned: AtomicBool,
     task_id: TaskId,
+    message: String,
 }
 
 impl SkipTestTask {
@@ -63,8 +68,24 @@ impl SkipTestTask {
         Self {
             spawned: Default::default(),
             task_id,
+            message: "Task skipped.".to_owned(),
         }
     }
+
+    /// A task skipped since one of its prerequisites did not pass, for the
+    /// given `reason`.
+    pub fn due_to_dependency_failure(task_id: TaskId, reason: &str) -> Self {
+        Self {
+            message: dependency_failure_message(reason),
+            ..Self::new(task_id)
+        }
+    }
+}
+
+/// The message of a test skipped since one of its prerequisites did not pass,
+/// for the given `reason`.
+pub fn dependency_failure_message(reason: &str) -> String {
+    format!("{SKIPPED_DUE_TO_DEPENDENCY_FAILURE}: {reason}.")
 }
 
 pub struct SkipTestTaskHandle;
@@ -80,7 +101,7 @@ impl Task for SkipTestTask {
         }
         notify(TaskResult::Report(
             self.task_id.clone(),
-            "Task skipped.".to_owned(),
+            self.message.clone(),
         ));
         Box::new(SkipTestTaskHandle) as Box<dyn TaskHandle>
     }
@@ -1,18 +1,24 @@
 #![allow(dead_code)]
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
 
 use slog::{debug, info, Logger};
 
 use crate::driver::action_graph::ActionGraph;
+use crate::driver::capabilities::{read_capabilities, Capability};
+use crate::driver::constants::{GROUP_SETUP_DIR, TESTS_DIR};
 use crate::driver::event::TaskId;
+use crate::driver::jumphost::read_jumphost_info;
 use crate::driver::log_events;
-use crate::driver::task::Task;
+use crate::driver::task::{SkipTestTask, Task, SKIPPED_DUE_TO_DEPENDENCY_FAILURE};
 
 use super::action_graph::Node;
 use super::group::is_task_visible_to_user;
 use super::report::{SystemGroupSummary, TaskReport};
 use super::task::TaskHandle;
+use super::test_events::{read_registered_artifacts, TestEvent, TestEventSink, TestOutcome};
+use super::timeout::{is_timeout_reason, timeout_reason};
 // Be mindful when modifying this constant, as the event can be consumed by other parties.
 const JSON_REPORT_CREATED_EVENT_NAME: &str = "json_report_created_event";
 
@@ -21,6 +27,9 @@ const JSON_REPORT_CREATED_EVENT_NAME: &str = "json_report_created_event";
 pub enum TaskResult {
     Report(TaskId, String),
     Failure(TaskId, String),
+    /// The task timed out after the given duration. Fails the supervised tasks
+    /// with a timeout reason, reported separately from other failures.
+    Timeout(TaskId, Duration),
 }
 
 /// Map a task id to a task.
@@ -34,6 +43,13 @@ pub struct TaskScheduler {
     pub end_times: BTreeMap<TaskId, SystemTime>,
     pub log: Logger,
     pub test_name: String,
+    /// Directory of the group, used to look up the capabilities recorded by each task.
+    pub group_dir: PathBuf,
+    /// Receives the lifecycle events of the tests, if set.
+    pub event_sink: Option<TestEventSink>,
+    /// The tests each test depends on. A test is skipped instead of being
+    /// started, unless all of its prerequisites have passed.
+    pub prerequisites: BTreeMap<TaskId, BTreeSet<TaskId>>,
 }
 
 impl TaskScheduler {
@@ -41,7 +57,13 @@ impl TaskScheduler {
     pub fn execute(&mut self, dbg_keepalive: bool) {
         #[allow(clippy::disallowed_methods)]
         let (event_tx, event_rx) = crossbeam_channel::unbounded();
-        let log = &self.log;
+        // Cloned, as emitting test events requires mutable access to `self`.
+        let log = &self.log.clone();
+        if let Some(sink) = self.event_sink.as_mut() {
+            sink.emit(&TestEvent::PotStarted {
+                name: self.test_name.clone(),
+            });
+        }
         self.action_graph.start();
         loop {
             // bring tasks into the state prescribed by action graph
@@ -60,6 +82,7 @@ impl TaskScheduler {
                                 };
                             // debug!(log, "ag: Stopping node {:?} task {}", &node, &task_id);
                             th.cancel();
+                            self.emit_test_finished(&task_id, &node);
 
                             if dbg_keepalive && task_id.to_string() == "report" {
                                 let report = self.create_report(self.test_name.clone());
@@ -73,11 +96,28 @@ impl TaskScheduler {
                         if let Some(task_id) = maybe_task_id {
                             if !self.running_tasks.contains_key(&task_id) {
                                 // debug!(log, "ag: Starting node: {:?}, task: {}", &node, &task_id);
-                                let task = self.scheduled_tasks.get(&task_id).unwrap();
+                                let skip_task = self.unmet_prerequisite(&task_id).map(|reason| {
+                                    info!(log, "Skipping task {}: {}", &task_id, &reason);
+                                    SkipTestTask::due_to_dependency_failure(
+                                        task_id.clone(),
+                                        &reason,
+                                    )
+                                });
+                                let task: &dyn Task = match &skip_task {
+                                    Some(skip_task) => skip_task,
+                                    None => self.scheduled_tasks.get(&task_id).unwrap().as_ref(),
+                                };
                                 let tx = event_tx.clone();
                                 let cb = move |result: TaskResult| {
                                     tx.send(result).expect("Failed to send message.")
                                 };
+                                if is_task_visible_to_user(&task_id) {
+                                    if let Some(sink) = self.event_sink.as_mut() {
+                                        sink.emit(&TestEvent::TestStarted {
+                                            name: task_id.to_string(),
+                                        });
+                                    }
+                                }
                                 let th = task.spawn(Box::new(cb));
                                 Self::record_time(&mut self.start_times, &task_id);
                                 self.running_tasks.insert(task_id, (th, node_index));
@@ -95,6 +135,7 @@ impl TaskScheduler {
                                 };
                             // debug!(log, "ag: Failing node {:?} task {}", &node, &task_id);
                             th.cancel();
+                            self.emit_test_finished(&task_id, &node);
                         }
                     }
                     _ => {}
@@ -140,10 +181,81 @@ impl TaskScheduler {
                         );
                     }
                 }
+                TaskResult::Timeout(task_id, duration) => {
+                    debug!(log, "Task {:?} timed out after {:?}", &task_id, &duration);
+                    if let Some((_th, node_idx)) = self.running_tasks.get(&task_id) {
+                        self.action_graph.fail(*node_idx, timeout_reason(duration));
+                    } else {
+                        debug!(
+                            log,
+                            "Task id {} not found in running_tasks (timeout)", task_id
+                        );
+                    }
+                }
             }
         }
     }
 
+    /// Returns why the task `task_id` must be skipped, if one of its
+    /// prerequisites has not passed.
+    fn unmet_prerequisite(&self, task_id: &TaskId) -> Option<String> {
+        let prerequisites = self.prerequisites.get(task_id)?;
+        self.action_graph
+            .task_iter()
+            .filter(|(_, maybe_task_id)| {
+                maybe_task_id
+                    .as_ref()
+                    .is_some_and(|id| prerequisites.contains(id))
+            })
+            .find_map(|(node, maybe_task_id)| {
+                let finished = !matches!(node, Node::Running { active, .. } if active > 0);
+                let status = match test_outcome(&node).filter(|_| finished) {
+                    Some((TestOutcome::Passed, _)) => return None,
+                    Some((TestOutcome::Failed, _)) => "failed",
+                    Some((TestOutcome::TimedOut, _)) => "timed out",
+                    Some((
+                        TestOutcome::Skipped | TestOutcome::SkippedDueToDependencyFailure,
+                        _,
+                    )) => "was skipped",
+                    None => "has not finished",
+                };
+                Some(format!("prerequisite {} {status}", maybe_task_id?))
+            })
+    }
+
+    /// Emits the events for the user-visible task `task_id` that finished in
+    /// the state `node`: the artifacts it registered, followed by its result.
+    fn emit_test_finished(&mut self, task_id: &TaskId, node: &Node) {
+        if !is_task_visible_to_user(task_id) {
+            return;
+        }
+        let Some((result, message)) = test_outcome(node) else {
+            return;
+        };
+        let duration = self.get_duration(task_id).unwrap_or_default();
+        let artifacts = self
+            .task_dirs(task_id)
+            .iter()
+            .map(|dir| read_registered_artifacts(dir))
+            .find(|artifacts| !artifacts.is_empty())
+            .unwrap_or_default();
+        let Some(sink) = self.event_sink.as_mut() else {
+            return;
+        };
+        for path in artifacts {
+            sink.emit(&TestEvent::ArtifactRegistered {
+                test: task_id.to_string(),
+                path,
+            });
+        }
+        sink.emit(&TestEvent::TestFinished {
+            name: task_id.to_string(),
+            result,
+            duration,
+            message,
+        });
+    }
+
     fn record_time(times: &mut BTreeMap<TaskId, SystemTime>, task_id: &TaskId) {
         times.insert(task_id.clone(), SystemTime::now());
     }
@@ -161,9 +273,29 @@ impl TaskScheduler {
         )
     }
 
+    /// Returns the directories the task `task_id` may record into, in order of
+    /// precedence. Tests record into their own directory below `TESTS_DIR`,
+    /// setup tasks into the group directory.
+    fn task_dirs(&self, task_id: &TaskId) -> [PathBuf; 2] {
+        [
+            self.group_dir.join(TESTS_DIR).join(task_id.name()),
+            self.group_dir.join(task_id.name()),
+        ]
+    }
+
+    /// Returns the capabilities recorded by the task `task_id`.
+    fn get_capabilities(&self, task_id: &TaskId) -> Vec<Capability> {
+        self.task_dirs(task_id)
+            .iter()
+            .map(read_capabilities)
+            .find(|capabilities| !capabilities.is_empty())
+            .unwrap_or_default()
+    }
+
     pub fn create_report(&self, test_name: String) -> SystemGroupSummary {
         let mut success = vec![];
         let mut failure = vec![];
+        let mut timeout = vec![];
         let mut skipped = vec![];
         for (node, maybe_task_id) in self.action_graph.task_iter() {
             if let Some(task_id) = maybe_task_id {
@@ -173,32 +305,23 @@ impl TaskScheduler {
                 let duration = self
                     .get_duration(&task_id)
                     .unwrap_or_else(|| Duration::from_secs(0));
-                match node {
-                    Node::Running { active: _, message } => {
-                        // TODO: handle this with proper message/failure types
-                        // println!("message for task id {} {:?}", task_id, message);
-                        if message.is_some() && message.clone().unwrap().contains("Task skipped") {
-                            skipped.push(TaskReport {
-                                name: task_id.to_string(),
-                                runtime: duration.as_secs_f64(),
-                                message,
-                            });
-                        } else {
-                            success.push(TaskReport {
-                                name: task_id.to_string(),
-                                runtime: duration.as_secs_f64(),
-                                message,
-                            });
-                        }
-                    }
-                    Node::Failed { reason } => {
-                        failure.push(TaskReport {
-                            name: task_id.to_string(),
-                            runtime: duration.as_secs_f64(),
-                            message: reason,
-                        });
+                let capabilities = self.get_capabilities(&task_id);
+                let Some((outcome, message)) = test_outcome(&node) else {
+                    continue;
+                };
+                let report = TaskReport {
+                    name: task_id.to_string(),
+                    runtime: duration.as_secs_f64(),
+                    message,
+                    capabilities,
+                };
+                match outcome {
+                    TestOutcome::Passed => success.push(report),
+                    TestOutcome::Failed => failure.push(report),
+                    TestOutcome::TimedOut => timeout.push(report),
+                    TestOutcome::Skipped | TestOutcome::SkippedDueToDependencyFailure => {
+                        skipped.push(report)
                     }
-                    _ => {}
                 }
             }
         }
@@ -206,8 +329,43 @@ impl TaskScheduler {
             test_name,
             success,
             failure,
+            timeout,
             skipped,
+            jumphost: read_jumphost_info(self.group_dir.join(GROUP_SETUP_DIR))
+                .map(|info| info.ssh_command()),
+        }
+    }
+}
+
+/// Returns the outcome of a task in the state `node` together with the message
+/// it finished with, or `None` if the task has not finished.
+fn test_outcome(node: &Node) -> Option<(TestOutcome, Option<String>)> {
+    match node {
+        Node::Running { active: _, message } => {
+            // TODO: handle this with proper message/failure types
+            let message_contains = |s: &str| {
+                message
+                    .as_deref()
+                    .is_some_and(|message| message.contains(s))
+            };
+            let outcome = if message_contains(SKIPPED_DUE_TO_DEPENDENCY_FAILURE) {
+                TestOutcome::SkippedDueToDependencyFailure
+            } else if message_contains("Task skipped") {
+                TestOutcome::Skipped
+            } else {
+                TestOutcome::Passed
+            };
+            Some((outcome, message.clone()))
+        }
+        Node::Failed { reason } => {
+            let outcome = if reason.as_deref().is_some_and(is_timeout_reason) {
+                TestOutcome::TimedOut
+            } else {
+                TestOutcome::Failed
+            };
+            Some((outcome, reason.clone()))
         }
+        Node::Scheduled { .. } => None,
     }
 }
 
@@ -216,3 +374,216 @@ impl From<SystemGroupSummary> for log_events::LogEvent<SystemGroupSummary> {
         log_events::LogEvent::new(JSON_REPORT_CREATED_EVENT_NAME.to_string(), item)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::plan::{EvalOrder, Plan};
+    use crate::driver::task::{EmptyTask, EmptyTaskHandle, SkipTestTask, TaskResultCallback};
+    use crate::driver::test_env::TestEnv;
+    use crate::driver::test_events::read_test_events;
+    use assert_matches::assert_matches;
+    use slog::o;
+
+    /// A task that finishes as soon as it is spawned, failing with `failure`
+    /// if set.
+    struct ImmediateTask {
+        task_id: TaskId,
+        failure: Option<String>,
+    }
+
+    impl Task for ImmediateTask {
+        fn spawn(&self, notify: TaskResultCallback) -> Box<dyn TaskHandle> {
+            notify(match &self.failure {
+                None => TaskResult::Report(self.task_id.clone(), "ok".to_string()),
+                Some(reason) => TaskResult::Failure(self.task_id.clone(), reason.clone()),
+            });
+            Box::new(EmptyTaskHandle {})
+        }
+
+        fn task_id(&self) -> TaskId {
+            self.task_id.clone()
+        }
+    }
+
+    fn test_id(name: &str) -> TaskId {
+        TaskId::Test(name.to_string())
+    }
+
+    fn finished(name: &str, result: TestOutcome, message: &str) -> TestEvent {
+        TestEvent::TestFinished {
+            name: name.to_string(),
+            result,
+            duration: Duration::ZERO,
+            message: Some(message.to_string()),
+        }
+    }
+
+    #[test]
+    fn emits_lifecycle_events_of_sequential_pot() {
+        let group_dir = tempfile::tempdir().unwrap();
+        let log = Logger::root(slog::Discard, o!());
+        let artifact_dir = group_dir.path().join(TESTS_DIR).join("passing");
+        std::fs::create_dir_all(&artifact_dir).unwrap();
+        TestEnv::new_without_duplicating_logger(&artifact_dir, log.clone())
+            .register_artifact("passing.log");
+
+        let tasks: Vec<Box<dyn Task>> = vec![
+            Box::new(EmptyTask::new(test_id("dummy(0)"))),
+            Box::new(ImmediateTask {
+                task_id: test_id("passing"),
+                failure: None,
+            }),
+            Box::new(ImmediateTask {
+                task_id: test_id("failing"),
+                failure: Some("boom".to_string()),
+            }),
+            Box::new(SkipTestTask::new(test_id("skipped"))),
+        ];
+        let leaf = |name: &str| Plan::Leaf {
+            task: test_id(name),
+        };
+        let plan = Plan::Supervised {
+            supervisor: test_id("dummy(0)"),
+            ordering: EvalOrder::Sequential,
+            children: vec![leaf("passing"), leaf("failing"), leaf("skipped")],
+        };
+        let mut scheduler = TaskScheduler {
+            scheduled_tasks: tasks
+                .into_iter()
+                .map(|task| (task.task_id(), task))
+                .collect(),
+            action_graph: ActionGraph::from_plan(plan),
+            running_tasks: BTreeMap::new(),
+            start_times: BTreeMap::new(),
+            end_times: BTreeMap::new(),
+            log: log.clone(),
+            test_name: "pot".to_string(),
+            group_dir: group_dir.path().to_path_buf(),
+            event_sink: Some(TestEventSink::new(group_dir.path(), None, log).unwrap()),
+            prerequisites: BTreeMap::new(),
+        };
+
+        scheduler.execute(false);
+
+        let mut events = read_test_events(group_dir.path()).unwrap();
+        for event in events.iter_mut() {
+            if let TestEvent::TestFinished { duration, .. } = event {
+                *duration = Duration::ZERO;
+            }
+        }
+        let started = |name: &str| TestEvent::TestStarted {
+            name: name.to_string(),
+        };
+        assert_eq!(
+            events,
+            vec![
+                TestEvent::PotStarted {
+                    name: "pot".to_string()
+                },
+                started("passing"),
+                TestEvent::ArtifactRegistered {
+                    test: "passing".to_string(),
+                    path: PathBuf::from("passing.log"),
+                },
+                finished("passing", TestOutcome::Passed, "ok"),
+                started("failing"),
+                finished("failing", TestOutcome::Failed, "boom"),
+                started("skipped"),
+                finished("skipped", TestOutcome::Skipped, "Task skipped."),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_dependents_of_failed_prerequisites() {
+        let group_dir = tempfile::tempdir().unwrap();
+        let log = Logger::root(slog::Discard, o!());
+        let immediate = |name: &str, failure: Option<&str>| -> Box<dyn Task> {
+            Box::new(ImmediateTask {
+                task_id: test_id(name),
+                failure: failure.map(String::from),
+            })
+        };
+        let tasks = vec![
+            Box::new(EmptyTask::new(test_id("dummy(0)"))) as Box<dyn Task>,
+            immediate("install", Some("boom")),
+            immediate("upgrade", None),
+            immediate("downgrade", None),
+            immediate("smoke", None),
+        ];
+        let leaf = |name: &str| Plan::Leaf {
+            task: test_id(name),
+        };
+        let plan = Plan::Supervised {
+            supervisor: test_id("dummy(0)"),
+            ordering: EvalOrder::Sequential,
+            children: vec![
+                leaf("install"),
+                leaf("upgrade"),
+                leaf("downgrade"),
+                leaf("smoke"),
+            ],
+        };
+        let mut scheduler = TaskScheduler {
+            scheduled_tasks: tasks
+                .into_iter()
+                .map(|task| (task.task_id(), task))
+                .collect(),
+            action_graph: ActionGraph::from_plan(plan),
+            running_tasks: BTreeMap::new(),
+            start_times: BTreeMap::new(),
+            end_times: BTreeMap::new(),
+            log,
+            test_name: "pot".to_string(),
+            group_dir: group_dir.path().to_path_buf(),
+            event_sink: None,
+            prerequisites: BTreeMap::from([
+                (test_id("upgrade"), BTreeSet::from([test_id("install")])),
+                (test_id("downgrade"), BTreeSet::from([test_id("upgrade")])),
+            ]),
+        };
+
+        scheduler.execute(false);
+
+        let report = scheduler.create_report("pot".to_string());
+        let names_and_messages = |reports: &[TaskReport]| {
+            reports
+                .iter()
+                .map(|report| (report.name.clone(), report.message.clone().unwrap()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec![("install".to_string(), "boom".to_string())],
+            names_and_messages(&report.failure)
+        );
+        assert_eq!(
+            vec![("smoke".to_string(), "ok".to_string())],
+            names_and_messages(&report.success)
+        );
+        assert_eq!(
+            vec![
+                (
+                    "upgrade".to_string(),
+                    "Task skipped due to dependency failure: prerequisite install failed."
+                        .to_string()
+                ),
+                (
+                    "downgrade".to_string(),
+                    "Task skipped due to dependency failure: prerequisite upgrade was skipped."
+                        .to_string()
+                ),
+            ],
+            names_and_messages(&report.skipped)
+        );
+        let (node, _) = scheduler
+            .action_graph
+            .task_iter()
+            .find(|(_, task_id)| task_id == &Some(test_id("downgrade")))
+            .unwrap();
+        assert_matches!(
+            test_outcome(&node),
+            Some((TestOutcome::SkippedDueToDependencyFailure, _))
+        );
+    }
+}
@@ -5,7 +5,7 @@ use ic_sys::fs::{sync_path, write_atomically};
 use rand::{RngCore, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use slog::{info, o, warn, Drain, Logger};
 use slog_async::OverflowStrategy;
 use std::fs::{self, File};
@@ -13,9 +13,13 @@ use std::os::unix::prelude::AsRawFd;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
+use thiserror::Error;
 
+use crate::driver::capabilities::{Capability, CapabilityRecorder, CAPABILITIES_FILE};
 use crate::driver::driver_setup::{SSH_AUTHORIZED_PRIV_KEYS_DIR, SSH_AUTHORIZED_PUB_KEYS_DIR};
 use crate::driver::pot_dsl::TestPath;
+use crate::driver::resource::VmPlacements;
+use crate::driver::test_events::{register_artifact, ARTIFACTS_FILE};
 
 use crate::driver::constants::{SSH_USERNAME, SUBREPORT_LOG_PREFIX};
 
@@ -23,6 +27,34 @@ use super::farm::HostFeature;
 
 const ASYNC_CHAN_SIZE: usize = 8192;
 
+/// The schema version of objects read with `TestEnv::read_versioned()` that
+/// were stored as plain JSON, i.e. before they were written with
+/// `TestEnv::write_versioned()`.
+pub const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+/// The envelope in which `TestEnv::write_versioned()` stores objects.
+#[derive(Deserialize, Serialize)]
+struct VersionedObject<T> {
+    schema_version: u32,
+    payload: T,
+}
+
+#[derive(Debug, Error)]
+pub enum VersionedObjectError {
+    #[error("schema version {found} of the stored object does not match the expected version {expected}")]
+    SchemaMismatch { found: u32, expected: u32 },
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// Upgrades objects stored with an older schema version, see
+/// `TestEnv::read_versioned_with_migration()`.
+pub trait SchemaMigration: Sized {
+    /// Converts `payload`, stored with schema version `found`, to the current
+    /// schema. Returns `None` if there is no migration from version `found`.
+    fn migrate(found: u32, payload: serde_json::Value) -> Option<Result<Self>>;
+}
+
 /// A TestEnv represents a directory storing all state related to a test.
 ///
 /// It has operations for reading and writing objects as JSON from paths relative to the directory.
@@ -40,6 +72,7 @@ pub struct TestEnv {
 pub struct TestEnvInner {
     base_path: PathBuf,
     logger: Logger,
+    capabilities: Arc<CapabilityRecorder>,
 }
 
 impl TestEnv {
@@ -56,14 +89,22 @@ impl TestEnv {
             .fuse();
         let logger = slog::Logger::root(slog::Duplicate(logger, file_drain).fuse(), o!());
         Ok(Self {
-            inner: Arc::new(TestEnvInner { base_path, logger }),
+            inner: Arc::new(TestEnvInner {
+                capabilities: Arc::new(CapabilityRecorder::new(&base_path)),
+                base_path,
+                logger,
+            }),
         })
     }
 
     pub fn new_without_duplicating_logger<P: AsRef<Path>>(path: P, logger: Logger) -> TestEnv {
         let base_path = PathBuf::from(path.as_ref());
         Self {
-            inner: Arc::new(TestEnvInner { base_path, logger }),
+            inner: Arc::new(TestEnvInner {
+                capabilities: Arc::new(CapabilityRecorder::new(&base_path)),
+                base_path,
+                logger,
+            }),
         }
     }
 
@@ -89,6 +130,97 @@ impl TestEnv {
         .with_context(|| format!("{:?}: Could not write json object.", path))
     }
 
+    /// Writes `t` as JSON, wrapped in an envelope recording the schema
+    /// `version` of `T`, such that readers expecting another version fail with
+    /// `VersionedObjectError::SchemaMismatch` instead of misinterpreting it.
+    pub fn write_versioned<T: Serialize, P: AsRef<Path>>(
+        &self,
+        p: P,
+        version: u32,
+        t: &T,
+    ) -> Result<()> {
+        self.write_json_object(
+            p,
+            &VersionedObject {
+                schema_version: version,
+                payload: t,
+            },
+        )
+    }
+
+    /// Reads an object written with `write_versioned()`. Objects stored
+    /// without envelope are assumed to have version `LEGACY_SCHEMA_VERSION`.
+    pub fn read_versioned<T: DeserializeOwned, P: AsRef<Path>>(
+        &self,
+        p: P,
+        version: u32,
+    ) -> Result<T, VersionedObjectError> {
+        let (found, payload) = self.read_versioned_payload(&p)?;
+        if found != version {
+            return Err(VersionedObjectError::SchemaMismatch {
+                found,
+                expected: version,
+            });
+        }
+        self.deserialize_payload(&p, payload)
+    }
+
+    /// Like `read_versioned()`, but upgrades objects stored with another
+    /// schema version via `T::migrate()`.
+    pub fn read_versioned_with_migration<T: DeserializeOwned + SchemaMigration, P: AsRef<Path>>(
+        &self,
+        p: P,
+        version: u32,
+    ) -> Result<T, VersionedObjectError> {
+        let (found, payload) = self.read_versioned_payload(&p)?;
+        if found == version {
+            return self.deserialize_payload(&p, payload);
+        }
+        match T::migrate(found, payload) {
+            Some(migrated) => Ok(migrated.with_context(|| {
+                format!(
+                    "{:?}: Could not migrate from schema version {}.",
+                    self.get_json_path(&p),
+                    found
+                )
+            })?),
+            None => Err(VersionedObjectError::SchemaMismatch {
+                found,
+                expected: version,
+            }),
+        }
+    }
+
+    fn read_versioned_payload<P: AsRef<Path>>(&self, p: P) -> Result<(u32, serde_json::Value)> {
+        let value: serde_json::Value = self.read_json_object(&p)?;
+        match value.as_object() {
+            Some(fields)
+                if fields.len() == 2
+                    && fields.contains_key("schema_version")
+                    && fields.contains_key("payload") =>
+            {
+                let versioned: VersionedObject<serde_json::Value> = serde_json::from_value(value)
+                    .with_context(|| {
+                    format!(
+                        "{:?}: Could not read versioned json.",
+                        self.get_json_path(&p)
+                    )
+                })?;
+                Ok((versioned.schema_version, versioned.payload))
+            }
+            _ => Ok((LEGACY_SCHEMA_VERSION, value)),
+        }
+    }
+
+    fn deserialize_payload<T: DeserializeOwned, P: AsRef<Path>>(
+        &self,
+        p: P,
+        payload: serde_json::Value,
+    ) -> Result<T, VersionedObjectError> {
+        Ok(serde_json::from_value(payload)
+            .with_context(|| format!("{:?}: Could not read json.", self.get_json_path(&p)))?)
+    }
+
     pub fn get_path<P: AsRef<Path>>(&self, p: P) -> PathBuf {
         self.inner.base_path.join(p)
     }
@@ -101,6 +233,29 @@ impl TestEnv {
         self.inner.logger.clone()
     }
 
+    /// Records that the test using this environment exercised `capability`.
+    pub fn record_capability(&self, capability: Capability) {
+        self.inner.capabilities.record(capability);
+    }
+
+    /// Returns the recorder of the capabilities exercised within this environment.
+    pub fn capability_recorder(&self) -> Arc<CapabilityRecorder> {
+        self.inner.capabilities.clone()
+    }
+
+    /// Registers `path` as an artifact produced by the test using this
+    /// environment, to be reported in the test event log. Relative paths are
+    /// taken as relative to the directory of this environment.
+    pub fn register_artifact<P: AsRef<Path>>(&self, path: P) {
+        if let Err(e) = register_artifact(&self.inner.base_path, path.as_ref()) {
+            warn!(
+                self.logger(),
+                "Failed to register artifact {:?}: {e}",
+                path.as_ref()
+            );
+        }
+    }
+
     /// Log the final report from this test function. The test driver will incorporate this report into
     /// the overall report of the current SystemTestGroup. Ideally, this should be a single line of text
     /// with the summary of the most essential information (beyond pass / fail), e.g., a `Metrics` object.
@@ -108,12 +263,22 @@ impl TestEnv {
         info!(self.logger(), "{SUBREPORT_LOG_PREFIX}{report}");
     }
 
+    /// Like `emit_report`, but for benchmark results: the report is annotated
+    /// with the placement of the VMs, such that results of runs whose
+    /// performance VMs shared their host can be told apart.
+    pub fn emit_benchmark_report(&self, report: String) {
+        self.emit_report(VmPlacements::read_or_default(self).annotate(&report));
+    }
+
     pub fn fork_from<P: AsRef<Path>>(
         source_dir: P,
         target_dir: P,
         logger: Logger,
     ) -> Result<TestEnv> {
         Self::shell_copy(source_dir.as_ref(), target_dir.as_ref())?;
+        // Capabilities and artifacts are recorded per test, so they must not be inherited.
+        let _ = fs::remove_file(target_dir.as_ref().join(CAPABILITIES_FILE));
+        let _ = fs::remove_file(target_dir.as_ref().join(ARTIFACTS_FILE));
         sync_path(&target_dir)?;
         TestEnv::new(target_dir, logger)
     }
@@ -248,6 +413,9 @@ impl RequiredHostFeaturesFromCmdLine for TestEnv {
     }
 }
 
+/// The seed of [`HasDefaultRng::default_rng`].
+pub const DEFAULT_RNG_SEED: u64 = 42;
+
 pub trait HasDefaultRng {
     /// Returns a random number generator the seed of which is either constant
     /// or depends on the state of the underlying object.
@@ -259,7 +427,7 @@ impl HasDefaultRng for TestEnv {
     /// point, the seed will be configured through the underlying test
     /// environment.
     fn default_rng(&self) -> Box<dyn RngCore> {
-        Box::new(ChaCha8Rng::seed_from_u64(42))
+        Box::new(ChaCha8Rng::seed_from_u64(DEFAULT_RNG_SEED))
     }
 }
 
@@ -367,3 +535,154 @@ impl SshKeyGen for TestEnv {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::test_setup::{GroupSetup, GROUP_SETUP_SCHEMA_VERSION};
+    use slog::Discard;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Record {
+        name: String,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct RecordV2 {
+        names: Vec<String>,
+    }
+
+    impl SchemaMigration for RecordV2 {
+        fn migrate(found: u32, payload: serde_json::Value) -> Option<Result<Self>> {
+            match found {
+                1 => Some(
+                    serde_json::from_value::<Record>(payload)
+                        .map(|record| RecordV2 {
+                            names: vec![record.name],
+                        })
+                        .map_err(anyhow::Error::from),
+                ),
+                _ => None,
+            }
+        }
+    }
+
+    fn test_env(dir: &tempfile::TempDir) -> TestEnv {
+        TestEnv::new_without_duplicating_logger(dir.path(), Logger::root(Discard, o!()))
+    }
+
+    fn record() -> Record {
+        Record {
+            name: "node-1".to_string(),
+        }
+    }
+
+    #[test]
+    fn versioned_object_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = test_env(&dir);
+        env.write_versioned("record", 3, &record()).unwrap();
+
+        let read: Record = env.read_versioned("record", 3).unwrap();
+
+        assert_eq!(read, record());
+        let stored: serde_json::Value = env.read_json_object("record").unwrap();
+        assert_eq!(
+            stored,
+            serde_json::json!({"schema_version": 3, "payload": {"name": "node-1"}})
+        );
+    }
+
+    #[test]
+    fn reading_another_version_fails_with_schema_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = test_env(&dir);
+        env.write_versioned("record", 1, &record()).unwrap();
+
+        let err = env.read_versioned::<Record, _>("record", 2).unwrap_err();
+
+        assert!(
+            matches!(
+                err,
+                VersionedObjectError::SchemaMismatch {
+                    found: 1,
+                    expected: 2
+                }
+            ),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn plain_json_is_read_as_legacy_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = test_env(&dir);
+        env.write_json_object("record", &record()).unwrap();
+
+        let read: Record = env.read_versioned("record", LEGACY_SCHEMA_VERSION).unwrap();
+        assert_eq!(read, record());
+        assert!(matches!(
+            env.read_versioned::<Record, _>("record", LEGACY_SCHEMA_VERSION + 1),
+            Err(VersionedObjectError::SchemaMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn older_version_is_migrated() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = test_env(&dir);
+        env.write_versioned("record", 1, &record()).unwrap();
+
+        let read: RecordV2 = env.read_versioned_with_migration("record", 2).unwrap();
+
+        assert_eq!(
+            read,
+            RecordV2 {
+                names: vec!["node-1".to_string()]
+            }
+        );
+    }
+
+    #[test]
+    fn version_without_migration_fails_with_schema_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = test_env(&dir);
+        env.write_versioned("record", 3, &record()).unwrap();
+
+        let err = env
+            .read_versioned_with_migration::<RecordV2, _>("record", 2)
+            .unwrap_err();
+
+        assert!(
+            matches!(
+                err,
+                VersionedObjectError::SchemaMismatch {
+                    found: 3,
+                    expected: 2
+                }
+            ),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn group_setup_is_stored_versioned_and_read_from_legacy_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = test_env(&dir);
+        let group_setup = GroupSetup::new("group".to_string(), None);
+
+        group_setup.write_attribute(&env);
+        let stored: serde_json::Value = env.read_json_object("group_setup").unwrap();
+        assert_eq!(stored["schema_version"], GROUP_SETUP_SCHEMA_VERSION);
+        assert_eq!(
+            GroupSetup::read_attribute(&env).infra_group_name,
+            group_setup.infra_group_name
+        );
+
+        env.write_json_object("group_setup", &group_setup).unwrap();
+        assert_eq!(
+            GroupSetup::read_attribute(&env).infra_group_name,
+            group_setup.infra_group_name
+        );
+    }
+}
@@ -132,7 +132,7 @@
 //! better to let the user select a node.
 
 use super::{
-    config::NODES_INFO,
+    config::{NODES_INFO, NODES_INFO_SCHEMA_VERSION},
     driver_setup::SSH_AUTHORIZED_PRIV_KEYS_DIR,
     farm::{DnsRecord, PlaynetCertificate},
     test_setup::{GroupSetup, InfraProvider},
@@ -140,6 +140,7 @@ use super::{
 use crate::{
     driver::{
         boundary_node::BoundaryNodeVm,
+        capabilities::Capability,
         constants::{self, kibana_link, GROUP_TTL, SSH_USERNAME},
         farm::{Farm, GroupSpec},
         log_events,
@@ -215,6 +216,11 @@ use url::Url;
 pub const READY_WAIT_TIMEOUT: Duration = Duration::from_secs(500);
 pub const SSH_RETRY_TIMEOUT: Duration = Duration::from_secs(500);
 pub const RETRY_BACKOFF: Duration = Duration::from_secs(5);
+/// Backoff of the readiness-waiting helpers: retry quickly at first, as nodes
+/// often become ready shortly after the first attempt, then back off to
+/// `RETRY_BACKOFF`.
+pub const READY_WAIT_BACKOFF: Backoff =
+    Backoff::exponential(Duration::from_millis(500), RETRY_BACKOFF);
 const REGISTRY_QUERY_TIMEOUT: Duration = Duration::from_secs(5);
 const READY_RESPONSE_TIMEOUT: Duration = Duration::from_secs(6);
 // It usually takes below 60 secs to install nns canisters.
@@ -223,6 +229,10 @@ const NNS_CANISTER_INSTALL_TIMEOUT: Duration = std::time::Duration::from_secs(16
 const IC_TOPOLOGY_EVENT_NAME: &str = "ic_topology_created_event";
 const INFRA_GROUP_CREATED_EVENT_NAME: &str = "infra_group_name_created_event";
 const KIBANA_URL_CREATED_EVENT_NAME: &str = "kibana_url_created_event";
+/// Directory, relative to the test environment, the node logs are collected into.
+pub const NODE_LOGS_DIR: &str = "logs";
+/// How far back the node logs are collected when a test fails.
+pub const NODE_LOGS_WINDOW_ON_FAILURE: Duration = Duration::from_secs(60 * 60);
 pub type NodesInfo = HashMap<NodeId, Option<MaliciousBehaviour>>;
 
 pub fn bail_if_sha256_invalid(sha256: &str, opt_name: &str) -> Result<()> {
@@ -738,6 +748,163 @@ impl TopologySnapshot {
             env: self.env.clone(),
         })
     }
+
+    /// Polls the status endpoints of the nodes of all subnets concurrently
+    /// until (the configured fraction of) them report a healthy replica.
+    ///
+    /// On timeout, the error lists every unhealthy node with its last observed
+    /// health status, HTTP error and replica version.
+    pub fn block_on_all_nodes_healthy(&self, options: NodesHealthyOptions) -> Result<()> {
+        let nodes: Vec<_> = self
+            .subnets()
+            .flat_map(|subnet| subnet.nodes())
+            .map(|node| (node.node_id.to_string(), node.get_public_url()))
+            .collect();
+        block_on_nodes_healthy(&self.env.logger(), &nodes, &options)
+    }
+}
+
+/// Parameters of `TopologySnapshot::block_on_all_nodes_healthy`.
+#[derive(Clone, Debug)]
+pub struct NodesHealthyOptions {
+    /// Time between two polls of the status endpoints.
+    pub poll_interval: Duration,
+    pub timeout: Duration,
+    /// Fraction of the nodes, in (0, 1], that must be healthy, e.g. 2/3 to
+    /// tolerate faulty nodes.
+    pub min_healthy_fraction: f64,
+}
+
+impl Default for NodesHealthyOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: RETRY_BACKOFF,
+            timeout: READY_WAIT_TIMEOUT,
+            min_healthy_fraction: 1.0,
+        }
+    }
+}
+
+/// What was last observed at the status endpoint of a node.
+#[derive(Clone, Debug, Default)]
+struct NodeHealthDiagnostics {
+    health_status: Option<ReplicaHealthStatus>,
+    http_error: Option<String>,
+    replica_version: Option<String>,
+}
+
+impl NodeHealthDiagnostics {
+    fn from_status(status: Result<HttpStatusResponse>) -> Self {
+        match status {
+            Ok(status) => Self {
+                health_status: status.replica_health_status,
+                http_error: None,
+                replica_version: status.impl_version,
+            },
+            Err(err) => Self {
+                http_error: Some(format!("{err:#}")),
+                ..Self::default()
+            },
+        }
+    }
+
+    fn is_healthy(&self) -> bool {
+        self.health_status == Some(ReplicaHealthStatus::Healthy)
+    }
+}
+
+impl std::fmt::Display for NodeHealthDiagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let or_none = |value: Option<&str>| value.unwrap_or("none").to_string();
+        write!(
+            f,
+            "status: {}, http error: {}, replica version: {}",
+            or_none(self.health_status.as_ref().map(|s| s.as_ref())),
+            or_none(self.http_error.as_deref()),
+            or_none(self.replica_version.as_deref()),
+        )
+    }
+}
+
+/// Polls the status endpoints of `nodes`, given by name and public URL, until
+/// at least `options.min_healthy_fraction` of them report a healthy replica.
+fn block_on_nodes_healthy(
+    log: &Logger,
+    nodes: &[(String, Url)],
+    options: &NodesHealthyOptions,
+) -> Result<()> {
+    if !(options.min_healthy_fraction > 0.0 && options.min_healthy_fraction <= 1.0) {
+        bail!(
+            "min_healthy_fraction must be in (0, 1], got {}",
+            options.min_healthy_fraction
+        );
+    }
+    let required = (options.min_healthy_fraction * nodes.len() as f64).ceil() as usize;
+    let start = Instant::now();
+    loop {
+        let diagnostics: Vec<NodeHealthDiagnostics> = std::thread::scope(|scope| {
+            let handles: Vec<_> = nodes
+                .iter()
+                .map(|(_, url)| scope.spawn(move || get_status(url)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    NodeHealthDiagnostics::from_status(
+                        handle
+                            .join()
+                            .unwrap_or_else(|_| Err(anyhow!("status request panicked"))),
+                    )
+                })
+                .collect()
+        });
+        let healthy = diagnostics.iter().filter(|d| d.is_healthy()).count();
+        if healthy >= required {
+            info!(log, "{healthy}/{} nodes are healthy", nodes.len());
+            return Ok(());
+        }
+        if start.elapsed() >= options.timeout {
+            let unhealthy: Vec<String> = nodes
+                .iter()
+                .zip(diagnostics.iter())
+                .filter(|(_, d)| !d.is_healthy())
+                .map(|((name, url), d)| format!("  node {name} ({url}): {d}"))
+                .collect();
+            bail!(
+                "Only {healthy}/{} nodes healthy after {:?}, {required} required. Unhealthy nodes:\n{}",
+                nodes.len(),
+                options.timeout,
+                unhealthy.join("\n")
+            );
+        }
+        info!(
+            log,
+            "{healthy}/{} nodes are healthy, {required} required; retrying in {:?}",
+            nodes.len(),
+            options.poll_interval
+        );
+        std::thread::sleep(options.poll_interval);
+    }
+}
+
+/// Fetches the status of the replica at `url`, like `HasPublicApiUrl::status`
+/// but reporting malformed responses as errors.
+fn get_status(url: &Url) -> Result<HttpStatusResponse> {
+    let response = reqwest::blocking::Client::builder()
+        .timeout(READY_RESPONSE_TIMEOUT)
+        .build()?
+        .get(url.join("api/v2/status")?)
+        .send()?;
+    let status = response.status();
+    let body = response.bytes()?;
+    if !status.is_success() {
+        bail!(
+            "status check failed with {status}: `{}`",
+            String::from_utf8_lossy(&body)
+        );
+    }
+    let cbor_response: serde_cbor::Value = serde_cbor::from_slice(&body)?;
+    Ok(serde_cbor::value::from_value(cbor_response)?)
 }
 
 #[derive(Clone)]
@@ -790,7 +957,7 @@ impl IcNodeSnapshot {
     pub fn malicious_behavior(&self) -> Option<MaliciousBehaviour> {
         let nodes_info: NodesInfo = self
             .env
-            .read_json_object(NODES_INFO)
+            .read_versioned(NODES_INFO, NODES_INFO_SCHEMA_VERSION)
             .expect("Couldn't read info of the nodes from a file.");
         nodes_info
             .get(&self.node_id)
@@ -929,7 +1096,19 @@ impl IcNodeSnapshot {
         name: &str,
         arg: Option<Vec<u8>>,
     ) -> Principal {
-        let canister_bytes = load_wasm(name);
+        self.create_and_install_canister_with_bytes(load_wasm(name), arg)
+    }
+
+    /// Install the given wasm binary on the target node.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the installation fails.
+    pub fn create_and_install_canister_with_bytes(
+        &self,
+        canister_bytes: Vec<u8>,
+        arg: Option<Vec<u8>>,
+    ) -> Principal {
         let effective_canister_id = self.effective_canister_id();
 
         self.with_default_agent(move |agent| async move {
@@ -1477,6 +1656,124 @@ pub trait SshSession {
     }
 }
 
+/// SSH access to a machine as a user other than the default admin user, e.g.
+/// one added via `UniversalVm::with_ssh_users()`.
+pub trait SshUserSession: SshSession {
+    /// Return an SSH session to the machine referenced from self authenticating
+    /// as `user` with the private key at `priv_key_path`.
+    fn get_ssh_session_for_user(&self, user: &str, priv_key_path: &Path) -> Result<Session>;
+
+    /// Try a number of times to establish an SSH session to the machine
+    /// referenced from self authenticating as `user` with the private key at
+    /// `priv_key_path`.
+    fn block_on_ssh_session_for_user(&self, user: &str, priv_key_path: &Path) -> Result<Session>;
+}
+
+/// A VM whose journald logs can be collected over SSH, see [CollectNodeLogs].
+pub trait NodeLogSource: SshSession + HasVmName {}
+impl<T: SshSession + HasVmName> NodeLogSource for T {}
+
+/// The VMs whose logs were collected by [CollectNodeLogs], by VM name.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NodeLogsSummary {
+    /// VMs whose logs were written to `<vm_name>.log`.
+    pub collected: Vec<String>,
+    /// VMs that could not be reached, for which an error marker
+    /// `<vm_name>.error` was written instead.
+    pub unreachable: Vec<String>,
+}
+
+pub trait CollectNodeLogs {
+    /// Downloads the journald logs of the last `window` from all IC nodes
+    /// (assigned, unassigned and API boundary nodes) and all boundary nodes
+    /// deployed in this environment over SSH. The logs of each node are
+    /// written to `<env>/logs/<vm_name>.log`, where the VM name of an IC node
+    /// is its node ID. A node that cannot be reached does not fail the
+    /// collection; an error marker `<env>/logs/<vm_name>.error` is written
+    /// instead.
+    fn collect_node_logs(&self, window: Duration) -> Result<NodeLogsSummary>;
+
+    /// Like `collect_node_logs()`, but for the given VMs only.
+    fn collect_vm_logs(
+        &self,
+        vms: &[&dyn NodeLogSource],
+        window: Duration,
+    ) -> Result<NodeLogsSummary>;
+}
+
+impl CollectNodeLogs for TestEnv {
+    fn collect_node_logs(&self, window: Duration) -> Result<NodeLogsSummary> {
+        let mut vms: Vec<Box<dyn NodeLogSource>> = vec![];
+        // Environments without an IC (e.g. of groups without a setup) have no prep dir.
+        if self.prep_dir("").is_some() {
+            let topology = self.topology_snapshot();
+            for node in topology
+                .subnets()
+                .flat_map(|subnet| subnet.nodes())
+                .chain(topology.unassigned_nodes())
+                .chain(topology.api_boundary_nodes())
+            {
+                vms.push(Box::new(node));
+            }
+        }
+        for boundary_node in self.get_deployed_boundary_nodes() {
+            match boundary_node.get_snapshot() {
+                Ok(snapshot) => vms.push(Box::new(snapshot)),
+                Err(e) => warn!(self.logger(), "Skipping logs of boundary node: {e:?}"),
+            }
+        }
+        let vms: Vec<&dyn NodeLogSource> = vms.iter().map(|vm| vm.as_ref()).collect();
+        self.collect_vm_logs(&vms, window)
+    }
+
+    fn collect_vm_logs(
+        &self,
+        vms: &[&dyn NodeLogSource],
+        window: Duration,
+    ) -> Result<NodeLogsSummary> {
+        let mut summary = NodeLogsSummary::default();
+        if vms.is_empty() {
+            return Ok(summary);
+        }
+        let logs_dir = self.get_path(NODE_LOGS_DIR);
+        fs::create_dir_all(&logs_dir)?;
+        let script = format!(
+            "journalctl --no-pager --since \"{} seconds ago\"",
+            window.as_secs()
+        );
+        for vm in vms {
+            let vm_name = vm.vm_name();
+            // A single connection attempt, as waiting for unreachable nodes would
+            // delay the collection from all other nodes.
+            let logs = vm
+                .get_ssh_session()
+                .and_then(|session| vm.block_on_bash_script_from_session(&session, &script));
+            match logs {
+                Ok(logs) => {
+                    fs::write(logs_dir.join(format!("{vm_name}.log")), logs)?;
+                    summary.collected.push(vm_name);
+                }
+                Err(e) => {
+                    warn!(self.logger(), "Failed to collect logs of {vm_name}: {e:?}");
+                    fs::write(
+                        logs_dir.join(format!("{vm_name}.error")),
+                        format!("Failed to collect logs: {e:?}\n"),
+                    )?;
+                    summary.unreachable.push(vm_name);
+                }
+            }
+        }
+        info!(
+            self.logger(),
+            "Collected logs of {} nodes into {:?}, {} nodes were unreachable",
+            summary.collected.len(),
+            logs_dir,
+            summary.unreachable.len()
+        );
+        Ok(summary)
+    }
+}
+
 pub trait RetrieveIpv4Addr {
     /// Try a number of times to retrieve the IPv4 address from the machine referenced from self.
     fn block_on_ipv4(&self) -> Result<Ipv4Addr>;
@@ -1594,7 +1891,7 @@ pub trait HasPublicApiUrl: HasTestEnv + Send + Sync {
             &format!("await_status_is_healthy of {}", self.get_public_url()),
             self.test_env().logger(),
             READY_WAIT_TIMEOUT,
-            RETRY_BACKOFF,
+            READY_WAIT_BACKOFF,
             || {
                 self.status_is_healthy()
                     .and_then(|s| if !s { bail!("Not ready!") } else { Ok(()) })
@@ -1609,7 +1906,7 @@ pub trait HasPublicApiUrl: HasTestEnv + Send + Sync {
             &format!("await_status_is_unavailable of {}", self.get_public_url()),
             self.test_env().logger(),
             READY_WAIT_TIMEOUT,
-            RETRY_BACKOFF,
+            READY_WAIT_BACKOFF,
             || match self.status_is_healthy() {
                 Err(_) => {
                     count += 1;
@@ -1645,6 +1942,7 @@ pub trait HasPublicApiUrl: HasTestEnv + Send + Sync {
     }
 
     async fn build_default_agent_async(&self) -> Agent {
+        self.test_env().record_capability(Capability::AgentCreated);
         self.try_build_default_agent_async()
             .await
             .expect("Could not create agent")
@@ -1921,14 +2219,26 @@ where
 }
 
 pub fn get_ssh_session_from_env(env: &TestEnv, ip: IpAddr) -> Result<Session> {
+    let priv_key_path = env
+        .get_path(SSH_AUTHORIZED_PRIV_KEYS_DIR)
+        .join(SSH_USERNAME);
+    get_ssh_session_for_user(env, ip, SSH_USERNAME, &priv_key_path)
+}
+
+/// Like `get_ssh_session_from_env()`, but authenticates as `user` with the
+/// private key at `priv_key_path` instead of as the default admin user.
+pub fn get_ssh_session_for_user(
+    env: &TestEnv,
+    ip: IpAddr,
+    user: &str,
+    priv_key_path: &Path,
+) -> Result<Session> {
+    env.record_capability(Capability::SshSession);
     let tcp = TcpStream::connect((ip, 22))?;
     let mut sess = Session::new()?;
     sess.set_tcp_stream(tcp);
     sess.handshake()?;
-    let priv_key_path = env
-        .get_path(SSH_AUTHORIZED_PRIV_KEYS_DIR)
-        .join(SSH_USERNAME);
-    sess.userauth_pubkey_file(SSH_USERNAME, None, priv_key_path.as_path(), None)?;
+    sess.userauth_pubkey_file(user, None, priv_key_path, None)?;
     Ok(sess)
 }
 
@@ -1948,7 +2258,7 @@ impl SshSession for IcNodeSnapshot {
             format!("get_ssh_session to {}", ip_addr.to_string()),
             self.env.logger(),
             SSH_RETRY_TIMEOUT,
-            RETRY_BACKOFF,
+            READY_WAIT_BACKOFF,
             || { self.get_ssh_session() }
         )
     }
@@ -1956,10 +2266,50 @@ impl SshSession for IcNodeSnapshot {
 
 /* ### Auxiliary functions & helpers ### */
 
+/// Delay between two attempts of [retry] and [retry_async]. A plain
+/// `Duration` converts into a constant backoff.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Backoff {
+    initial: Duration,
+    backoff_max: Duration,
+}
+
+impl Backoff {
+    /// Waits `backoff` after every failed attempt.
+    pub const fn constant(backoff: Duration) -> Self {
+        Self {
+            initial: backoff,
+            backoff_max: backoff,
+        }
+    }
+
+    /// Waits `initial` after the first failed attempt and doubles the delay
+    /// after every further one, up to `backoff_max`.
+    pub const fn exponential(initial: Duration, backoff_max: Duration) -> Self {
+        Self {
+            initial,
+            backoff_max,
+        }
+    }
+
+    /// The delay after the given (1-based) failed attempt.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial.saturating_mul(factor).min(self.backoff_max)
+    }
+}
+
+impl From<Duration> for Backoff {
+    fn from(backoff: Duration) -> Self {
+        Self::constant(backoff)
+    }
+}
+
 /// Try executing the given closure of type `FnMut() -> Result<R>` but retry in case it returns an `Err`.
 /// Don't use `panic!` in your closure if you need it to be retried. Return an `Err` instead.
 /// The macro will also log the given message before attempting to execute the closure, every time it's being retried and when it succceeds.
 /// The log messages will include the source file path and code location of the macro call site.
+/// The backoff is either a `Duration`, for a constant backoff, or a [Backoff].
 #[macro_export]
 macro_rules! retry_with_msg {
     ($msg:expr, $log:expr, $timeout:expr, $backoff:expr, $f:expr) => {
@@ -1977,18 +2327,19 @@ pub fn retry<S: AsRef<str>, F, R>(
     msg: S,
     log: slog::Logger,
     timeout: Duration,
-    backoff: Duration,
+    backoff: impl Into<Backoff>,
     mut f: F,
 ) -> Result<R>
 where
     F: FnMut() -> Result<R>,
 {
     let msg = msg.as_ref();
+    let backoff = backoff.into();
     let mut attempt = 1;
     let start = Instant::now();
     debug!(
         log,
-        "Func=\"{msg}\" is being retried for the maximum of {timeout:?} with a linear backoff of {backoff:?}"
+        "Func=\"{msg}\" is being retried for the maximum of {timeout:?} with a backoff of {backoff:?}"
     );
     loop {
         match f() {
@@ -2009,16 +2360,25 @@ where
                 }
                 debug!(
                     log,
-                    "Func=\"{msg}\" failed on attempt {attempt}. Error: {}",
+                    "Func=\"{msg}\" failed on attempt {attempt} after {:?}. Error: {}",
+                    start.elapsed(),
                     trunc_error(err_msg)
                 );
-                std::thread::sleep(backoff);
+                std::thread::sleep(next_delay(backoff, attempt, start, timeout));
                 attempt += 1;
             }
         }
     }
 }
 
+/// The delay after the given failed attempt, shortened so that the last attempt
+/// happens at the deadline rather than after it.
+fn next_delay(backoff: Backoff, attempt: u32, start: Instant, timeout: Duration) -> Duration {
+    backoff
+        .delay(attempt)
+        .min(timeout.saturating_sub(start.elapsed()))
+}
+
 fn trunc_error(err_str: String) -> String {
     let mut short_e = err_str.replace('\n', "\\n ");
     short_e.truncate(200);
@@ -2030,6 +2390,7 @@ fn trunc_error(err_str: String) -> String {
 /// Don't use `panic!` in your closure if you need it to be retried. Return an `Err` instead.
 /// The macro will also log the given message before attempting to execute the closure, every time it's being retried and when it succceeds.
 /// The log messages will include the source file path and code location of the macro call site.
+/// The backoff is either a `Duration`, for a constant backoff, or a [Backoff].
 #[macro_export]
 macro_rules! retry_with_msg_async {
     ($msg:expr, $log:expr, $timeout:expr, $backoff:expr, $f:expr) => {
@@ -2043,11 +2404,13 @@ macro_rules! retry_with_msg_async {
     };
 }
 
+/// Async variant of [retry]. Cancel-safe: dropping the returned future drops
+/// the pending attempt or backoff sleep, and no state outlives it.
 pub async fn retry_async<S: AsRef<str>, F, Fut, R>(
     msg: S,
     log: &slog::Logger,
     timeout: Duration,
-    backoff: Duration,
+    backoff: impl Into<Backoff>,
     f: F,
 ) -> Result<R>
 where
@@ -2055,11 +2418,12 @@ where
     F: Fn() -> Fut,
 {
     let msg = msg.as_ref();
+    let backoff = backoff.into();
     let mut attempt = 1;
     let start = Instant::now();
     debug!(
         log,
-        "Func=\"{msg}\" is being retried for the maximum of {timeout:?} with a constant backoff of {backoff:?}"
+        "Func=\"{msg}\" is being retried for the maximum of {timeout:?} with a backoff of {backoff:?}"
     );
     loop {
         match f().await {
@@ -2081,10 +2445,11 @@ where
                 }
                 debug!(
                     log,
-                    "Func=\"{msg}\" failed on attempt {attempt}. Error: {}",
+                    "Func=\"{msg}\" failed on attempt {attempt} after {:?}. Error: {}",
+                    start.elapsed(),
                     trunc_error(err_msg)
                 );
-                tokio::time::sleep(backoff).await;
+                tokio::time::sleep(next_delay(backoff, attempt, start, timeout)).await;
                 attempt += 1;
             }
         }
@@ -2365,3 +2730,209 @@ pub fn emit_kibana_url_event(log: &slog::Logger, kibana_url: &str) {
     );
     event.emit_log(log);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn logger() -> Logger {
+        Logger::root(slog::Discard, slog::o!())
+    }
+
+    #[test]
+    fn exponential_backoff_is_capped() {
+        let backoff = Backoff::exponential(Duration::from_millis(100), Duration::from_millis(500));
+        let delays: Vec<_> = (1..=5).map(|attempt| backoff.delay(attempt)).collect();
+        assert_eq!(
+            delays,
+            [100, 200, 400, 500, 500]
+                .map(Duration::from_millis)
+                .to_vec()
+        );
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_millis(500));
+        assert_eq!(Backoff::from(RETRY_BACKOFF).delay(10), RETRY_BACKOFF,);
+    }
+
+    #[test]
+    fn retry_succeeds_on_third_attempt() {
+        let mut attempts = 0;
+        let result = retry(
+            "flaky",
+            logger(),
+            Duration::from_secs(10),
+            Backoff::exponential(Duration::from_millis(1), Duration::from_millis(2)),
+            || {
+                attempts += 1;
+                if attempts < 3 {
+                    bail!("attempt {attempts} failed")
+                }
+                Ok(attempts)
+            },
+        );
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn retry_returns_last_error_on_timeout() {
+        let mut attempts = 0;
+        let err = retry(
+            "always failing",
+            logger(),
+            Duration::from_millis(20),
+            Duration::from_millis(5),
+            || -> Result<()> {
+                attempts += 1;
+                bail!("attempt {attempts} failed")
+            },
+        )
+        .unwrap_err();
+        assert!(attempts > 1);
+        assert_eq!(
+            err.root_cause().to_string(),
+            format!("attempt {attempts} failed")
+        );
+        assert!(err
+            .to_string()
+            .contains("Func=\"always failing\" timed out"));
+    }
+
+    #[tokio::test]
+    async fn retry_async_succeeds_on_third_attempt() {
+        let attempts = AtomicU32::new(0);
+        let result = retry_async(
+            "flaky",
+            &logger(),
+            Duration::from_secs(10),
+            Backoff::exponential(Duration::from_millis(1), Duration::from_millis(2)),
+            || async {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+                if attempt < 3 {
+                    bail!("attempt {attempt} failed")
+                }
+                Ok(attempt)
+            },
+        )
+        .await;
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_async_is_cancel_safe() {
+        let attempts = AtomicU32::new(0);
+        let retrying = retry_async(
+            "never succeeding",
+            &logger(),
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(anyhow!("failed"))
+            },
+        );
+        // The retry is cancelled while sleeping between the attempts.
+        assert!(tokio::time::timeout(Duration::from_millis(50), retrying)
+            .await
+            .is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    fn status_server(health_status: ReplicaHealthStatus) -> mockito::ServerGuard {
+        let mut server = mockito::Server::new();
+        let status = HttpStatusResponse {
+            ic_api_version: "0.18.0".to_string(),
+            root_key: None,
+            impl_version: Some("0123456789abcdef".to_string()),
+            impl_hash: None,
+            replica_health_status: Some(health_status),
+            certified_height: None,
+        };
+        server
+            .mock("GET", "/api/v2/status")
+            .with_body(serde_cbor::to_vec(&status).unwrap())
+            .create();
+        server
+    }
+
+    fn node(name: &str, server: &mockito::ServerGuard) -> (String, Url) {
+        (name.to_string(), Url::parse(&server.url()).unwrap())
+    }
+
+    fn options(min_healthy_fraction: f64) -> NodesHealthyOptions {
+        NodesHealthyOptions {
+            poll_interval: Duration::from_millis(10),
+            timeout: Duration::from_millis(50),
+            min_healthy_fraction,
+        }
+    }
+
+    #[test]
+    fn all_nodes_healthy() {
+        let servers: Vec<_> = (0..3)
+            .map(|_| status_server(ReplicaHealthStatus::Healthy))
+            .collect();
+        let nodes: Vec<_> = servers
+            .iter()
+            .enumerate()
+            .map(|(i, server)| node(&format!("node-{i}"), server))
+            .collect();
+
+        block_on_nodes_healthy(&logger(), &nodes, &options(1.0)).unwrap();
+    }
+
+    #[test]
+    fn partially_healthy_nodes_meet_minimum_fraction() {
+        let healthy_1 = status_server(ReplicaHealthStatus::Healthy);
+        let healthy_2 = status_server(ReplicaHealthStatus::Healthy);
+        let starting = status_server(ReplicaHealthStatus::Starting);
+        let nodes = vec![
+            node("healthy-1", &healthy_1),
+            node("healthy-2", &healthy_2),
+            node("starting", &starting),
+        ];
+
+        block_on_nodes_healthy(&logger(), &nodes, &options(2.0 / 3.0)).unwrap();
+        let err = block_on_nodes_healthy(&logger(), &nodes, &options(1.0)).unwrap_err();
+        assert!(err.to_string().contains("Only 2/3 nodes healthy"), "{err}");
+    }
+
+    #[test]
+    fn timeout_lists_unhealthy_nodes_with_diagnostics() {
+        let healthy = status_server(ReplicaHealthStatus::Healthy);
+        let waiting = status_server(ReplicaHealthStatus::WaitingForCertifiedState);
+        let mut failing = mockito::Server::new();
+        failing
+            .mock("GET", "/api/v2/status")
+            .with_status(503)
+            .with_body("unavailable")
+            .create();
+        let nodes = vec![
+            node("healthy", &healthy),
+            node("waiting", &waiting),
+            node("failing", &failing),
+        ];
+
+        let err = block_on_nodes_healthy(&logger(), &nodes, &options(1.0))
+            .unwrap_err()
+            .to_string();
+
+        assert!(!err.contains("node healthy "), "{err}");
+        assert!(
+            err.contains(
+                "node waiting (http://%s/): status: WaitingForCertifiedState, http error: none, replica version: 0123456789abcdef"
+                    .replace("%s", &waiting.host_with_port())
+                    .as_str()
+            ),
+            "{err}"
+        );
+        assert!(err.contains("node failing"), "{err}");
+        assert!(err.contains("503"), "{err}");
+    }
+
+    #[test]
+    fn invalid_minimum_fraction_is_rejected() {
+        assert!(block_on_nodes_healthy(&logger(), &[], &options(0.0)).is_err());
+        assert!(block_on_nodes_healthy(&logger(), &[], &options(1.5)).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+//! Machine-readable log of the lifecycle of the tests in a group.
+//!
+//! The parent process writes a [`TestEvent`] per line as JSON to
+//! `TEST_EVENTS_FILE` in the group directory and, optionally, to a Unix socket
+//! given on the command line. Every event is written with a single unbuffered
+//! write as soon as it happens, so the log of a crashed driver is a valid
+//! prefix of the full log.
+//!
+//! Tests register artifacts with [`register_artifact`] in their own
+//! environment directory. The parent process picks them up when the test
+//! finished and emits them right before the [`TestEvent::TestFinished`] event
+//! of the test.
+
+use serde::{Deserialize, Serialize};
+use slog::{warn, Logger};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Name of the file the events are written to, relative to the group
+/// directory.
+pub const TEST_EVENTS_FILE: &str = "test_events.jsonl";
+
+/// Name of the file the artifacts registered by a test are written to,
+/// relative to the directory of the test environment.
+pub const ARTIFACTS_FILE: &str = "artifacts.jsonl";
+
+// Be mindful when modifying the events, as they are consumed by CI.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TestEvent {
+    PotStarted {
+        name: String,
+    },
+    TestStarted {
+        name: String,
+    },
+    ArtifactRegistered {
+        test: String,
+        path: PathBuf,
+    },
+    TestFinished {
+        name: String,
+        result: TestOutcome,
+        duration: Duration,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    TimedOut,
+    Skipped,
+    /// Skipped since a prerequisite of the test did not pass.
+    SkippedDueToDependencyFailure,
+}
+
+/// Writes [`TestEvent`]s to the events file and, optionally, to a Unix socket.
+pub struct TestEventSink {
+    file: File,
+    socket: Option<UnixStream>,
+    log: Logger,
+}
+
+impl TestEventSink {
+    /// Creates a sink appending to `TEST_EVENTS_FILE` in `group_dir` and, if
+    /// given, writing to the Unix socket at `socket_path`.
+    pub fn new(group_dir: &Path, socket_path: Option<&Path>, log: Logger) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(group_dir.join(TEST_EVENTS_FILE))?;
+        let socket = socket_path.map(UnixStream::connect).transpose()?;
+        Ok(Self { file, socket, log })
+    }
+
+    /// Writes `event` to the file and the socket. Failing to write is logged,
+    /// but does not affect the tests. A socket that failed once is not written
+    /// to anymore.
+    pub fn emit(&mut self, event: &TestEvent) {
+        let mut line = serde_json::to_vec(event).expect("Failed to serialize test event");
+        line.push(b'\n');
+        if let Err(e) = self.file.write_all(&line) {
+            warn!(self.log, "Failed to write test event to file: {e}");
+        }
+        if let Some(socket) = self.socket.as_mut() {
+            if let Err(e) = socket.write_all(&line) {
+                warn!(self.log, "Failed to write test event to socket: {e}");
+                self.socket = None;
+            }
+        }
+    }
+}
+
+/// Records that the test running in the environment directory `test_dir`
+/// produced the artifact at `path`.
+pub fn register_artifact(test_dir: &Path, path: &Path) -> io::Result<()> {
+    let mut line = serde_json::to_vec(path)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+    line.push(b'\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(test_dir.join(ARTIFACTS_FILE))?
+        .write_all(&line)
+}
+
+/// Returns the artifacts registered in the environment directory `test_dir`,
+/// in the order of registration. Returns an empty list if none were
+/// registered or the file cannot be read.
+pub fn read_registered_artifacts(test_dir: &Path) -> Vec<PathBuf> {
+    match File::open(test_dir.join(ARTIFACTS_FILE)) {
+        Ok(file) => read_json_lines(file),
+        Err(_) => vec![],
+    }
+}
+
+/// Reads the events written to `TEST_EVENTS_FILE` in `group_dir`. A trailing
+/// line that is incomplete (e.g. because the driver crashed while writing it)
+/// is ignored.
+pub fn read_test_events(group_dir: &Path) -> io::Result<Vec<TestEvent>> {
+    Ok(read_json_lines(File::open(
+        group_dir.join(TEST_EVENTS_FILE),
+    )?))
+}
+
+fn read_json_lines<T: for<'de> Deserialize<'de>>(file: File) -> Vec<T> {
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .map_while(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::o;
+    use std::io::Read;
+    use std::os::unix::net::UnixListener;
+
+    fn discard() -> Logger {
+        Logger::root(slog::Discard, o!())
+    }
+
+    fn test_finished(name: &str, result: TestOutcome) -> TestEvent {
+        TestEvent::TestFinished {
+            name: name.to_string(),
+            result,
+            duration: Duration::from_millis(1500),
+            message: None,
+        }
+    }
+
+    #[test]
+    fn events_are_serialized_with_snake_case_tag() {
+        let json = serde_json::to_value(test_finished("t", TestOutcome::TimedOut)).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "event": "test_finished",
+                "name": "t",
+                "result": "timed_out",
+                "duration": {"secs": 1, "nanos": 500_000_000},
+            })
+        );
+    }
+
+    #[test]
+    fn events_are_written_to_file_and_socket_immediately() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("events.sock");
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        let mut sink = TestEventSink::new(dir.path(), Some(&socket_path), discard()).unwrap();
+        let (mut socket, _) = listener.accept().unwrap();
+        let events = vec![
+            TestEvent::PotStarted {
+                name: "pot".to_string(),
+            },
+            test_finished("t", TestOutcome::Passed),
+        ];
+
+        for event in &events {
+            sink.emit(event);
+        }
+
+        // The sink is still alive, so nothing can have been flushed on drop.
+        assert_eq!(read_test_events(dir.path()).unwrap(), events);
+        drop(sink);
+        let mut received = String::new();
+        socket.read_to_string(&mut received).unwrap();
+        let received: Vec<TestEvent> = received
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(received, events);
+    }
+
+    #[test]
+    fn incomplete_trailing_event_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sink = TestEventSink::new(dir.path(), None, discard()).unwrap();
+        let event = TestEvent::TestStarted {
+            name: "t".to_string(),
+        };
+        sink.emit(&event);
+        sink.file.write_all(br#"{"event":"test_fin"#).unwrap();
+
+        assert_eq!(read_test_events(dir.path()).unwrap(), vec![event]);
+    }
+
+    #[test]
+    fn registered_artifacts_are_read_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(read_registered_artifacts(dir.path()).is_empty());
+
+        register_artifact(dir.path(), Path::new("b.log")).unwrap();
+        register_artifact(dir.path(), Path::new("/abs/a.tar")).unwrap();
+
+        assert_eq!(
+            read_registered_artifacts(dir.path()),
+            vec![PathBuf::from("b.log"), PathBuf::from("/abs/a.tar")]
+        );
+    }
+}
@@ -1,8 +1,12 @@
 use crate::driver::ic::VmResources;
-use crate::driver::test_env::TestEnvAttribute;
+use crate::driver::test_env::{TestEnv, TestEnvAttribute};
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+/// The schema version of the `GroupSetup` stored in the `TestEnv`.
+pub const GROUP_SETUP_SCHEMA_VERSION: u32 = 1;
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct GroupSetup {
     pub group_base_name: String,
@@ -41,6 +45,13 @@ impl TestEnvAttribute for GroupSetup {
     fn attribute_name() -> String {
         "group_setup".to_string()
     }
+    fn write_attribute(&self, env: &TestEnv) {
+        env.write_versioned(Self::attribute_name(), GROUP_SETUP_SCHEMA_VERSION, self)
+            .unwrap_or_else(|e| panic!("cannot write {} to TestEnv: {}", Self::attribute_name(), e))
+    }
+    fn try_read_attribute(env: &TestEnv) -> Result<Self> {
+        Ok(env.read_versioned(Self::attribute_name(), GROUP_SETUP_SCHEMA_VERSION)?)
+    }
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
@@ -16,6 +16,20 @@ use crate::driver::{
 
 use super::{task::TaskResultCallback, task_scheduler::TaskResult};
 
+/// Prefix of the failure reason of tasks cancelled by a [TimeoutTask].
+pub const TIMEOUT_REASON_PREFIX: &str = "Timeout after";
+
+/// The failure reason of tasks cancelled by a [TimeoutTask] after `duration`.
+pub fn timeout_reason(duration: Duration) -> String {
+    format!("{TIMEOUT_REASON_PREFIX} {}s", duration.as_secs())
+}
+
+/// Whether `reason` is the failure reason of a task cancelled by a
+/// [TimeoutTask].
+pub fn is_timeout_reason(reason: &str) -> bool {
+    reason.starts_with(TIMEOUT_REASON_PREFIX)
+}
+
 pub struct TimeoutTask {
     spawned: AtomicBool,
     rt: RtHandle,
@@ -47,10 +61,7 @@ impl Task for TimeoutTask {
                 tokio::time::sleep(duration).await;
                 // xxx: ignore send errors
                 if !stopped.fetch_or(true, Ordering::Relaxed) {
-                    notify(TaskResult::Failure(
-                        task_id,
-                        format!("Timeout after {}s", duration.as_secs()),
-                    ));
+                    notify(TaskResult::Timeout(task_id, duration));
                 }
             }
         });
@@ -95,7 +106,9 @@ mod tests {
         }));
         std::thread::sleep(d * 20);
         let evt = evt_rcv.recv().unwrap();
-        assert!(matches!(evt, TaskResult::Failure(task_id, _msg) if task_id == expected_task_id));
+        assert!(
+            matches!(evt, TaskResult::Timeout(task_id, duration) if task_id == expected_task_id && duration == d)
+        );
     }
 
     #[test]
@@ -1,3 +1,4 @@
+use crate::driver::capabilities::Capability;
 use crate::driver::driver_setup::SSH_AUTHORIZED_PUB_KEYS_DIR;
 use crate::driver::farm::id_of_file;
 use crate::driver::farm::AttachImageSpec;
@@ -13,8 +14,8 @@ use crate::driver::resource::{
 use crate::driver::test_env::SshKeyGen;
 use crate::driver::test_env::{TestEnv, TestEnvAttribute};
 use crate::driver::test_env_api::{
-    get_dependency_path, get_ssh_session_from_env, HasTestEnv, HasVmName, RetrieveIpv4Addr,
-    SshSession, RETRY_BACKOFF, SSH_RETRY_TIMEOUT,
+    get_dependency_path, get_ssh_session_for_user, get_ssh_session_from_env, HasTestEnv, HasVmName,
+    RetrieveIpv4Addr, SshSession, SshUserSession, RETRY_BACKOFF, SSH_RETRY_TIMEOUT,
 };
 use crate::driver::test_setup::{GroupSetup, InfraProvider};
 use crate::k8s::datavolume::DataVolumeContentType;
@@ -25,10 +26,12 @@ use crate::util::block_on;
 use anyhow::{bail, Result};
 use chrono::Duration;
 use chrono::Utc;
-use slog::info;
+use ic_crypto_sha2::Sha256;
+use serde::{Deserialize, Serialize};
+use slog::{info, warn};
 use ssh2::Session;
 use std::fs::{self, File};
-use std::io::Write;
+use std::io::{Read, Write};
 use std::net::{IpAddr, Ipv4Addr};
 use std::os::unix::prelude::PermissionsExt;
 use std::path::Path;
@@ -48,6 +51,57 @@ pub struct UniversalVm {
     pub has_ipv4: bool,
     pub primary_image: Option<DiskImage>,
     pub config: Option<UniversalVmConfig>,
+    /// Config directories in addition to the primary one in `config`, see
+    /// `UniversalVm::with_config_dir()`.
+    pub extra_config_dirs: Vec<PathBuf>,
+    pub extra_disks: Vec<DiskSpec>,
+    /// Users in addition to the default admin user, see
+    /// `UniversalVm::with_ssh_users()`.
+    pub ssh_users: Vec<SshUser>,
+}
+
+/// A user account to be created on a universal VM at first boot, which can be
+/// logged into via SSH with any of the given public keys.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Deserialize, Serialize)]
+pub struct SshUser {
+    /// Name of the user, unique per VM. Must be a valid Linux user name
+    /// (lowercase ASCII letters, digits, `_` and `-`, not starting with a digit
+    /// or `-`) and must not be the default admin user.
+    pub name: String,
+    /// The authorized public keys of the user, in OpenSSH format.
+    pub pubkeys: Vec<String>,
+}
+
+/// An additional, empty block device to be attached to a universal VM.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Deserialize, Serialize)]
+pub struct DiskSpec {
+    /// Name of the disk, unique per VM. May only consist of lowercase ASCII
+    /// letters, digits and underscores, as it is used to derive variable names.
+    pub name: String,
+    pub size_gib: u64,
+}
+
+/// An extra disk as attached to a deployed universal VM.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct AttachedDisk {
+    pub name: String,
+    pub size_gib: u64,
+    /// The path of the block device inside the VM, e.g. `/dev/sdb`.
+    pub device: String,
+}
+
+/// A config image created from an additional config directory of a universal
+/// VM, as recorded in the `TestEnv`.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct ConfigImage {
+    /// The file name of the config directory. The image is mounted under
+    /// `/config/<name>` on the VM.
+    pub name: String,
+    /// The label of the filesystem in the image.
+    pub label: String,
+    /// The SHA-256 checksum of the uncompressed image, verified on the VM
+    /// before the image is mounted.
+    pub sha256: String,
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
@@ -59,6 +113,18 @@ pub enum UniversalVmConfig {
 pub const UNIVERSAL_VMS_DIR: &str = "universal_vms";
 const CONF_IMG_FNAME: &str = "config_disk.img.zst";
 const CONF_SSH_IMG_FNAME: &str = "config_ssh_disk.img.zst";
+const EXTRA_DISKS_JSON: &str = "extra_disks.json";
+/// File written to the config directory, defining an `EXTRA_DISK_<NAME>`
+/// variable holding the device path of each extra disk.
+const EXTRA_DISKS_ENV_FNAME: &str = "extra_disks.env";
+const CONFIG_IMAGES_JSON: &str = "config_images.json";
+/// The directory from which the primary config image is created if the VM has
+/// additional config directories.
+const STAGED_CONFIG_DIR_NAME: &str = "config-staged";
+/// The name under which the activate script of the primary config directory is
+/// staged, to be run once the additional config images are mounted.
+const PRIMARY_ACTIVATE_FNAME: &str = "activate.primary";
+const ACTIVATE_FNAME: &str = "activate";
 
 const CONFIG_DIR_NAME: &str = "config";
 const CONFIG_SSH_DIR_NAME: &str = "config-ssh";
@@ -74,6 +140,9 @@ impl UniversalVm {
             has_ipv4: false,
             primary_image: Default::default(),
             config: Default::default(),
+            extra_config_dirs: Default::default(),
+            extra_disks: Default::default(),
+            ssh_users: Default::default(),
         }
     }
 
@@ -102,8 +171,22 @@ impl UniversalVm {
         self
     }
 
+    /// Configures the VM via the given config directory, which is turned into
+    /// a config image whose `activate` script is run when the VM boots.
+    ///
+    /// This method can be called multiple times: the first config directory is
+    /// the primary one, mounted under `/config`. Every further directory is
+    /// turned into a separate image, which is mounted under `/config/<name>`,
+    /// `<name>` being the file name of the directory, before the primary
+    /// `activate` script is run. Large static payloads can thus be shipped
+    /// separately from a frequently changing activate script. Additional config
+    /// directories are only supported on Farm.
     pub fn with_config_dir(mut self, config_dir: PathBuf) -> Self {
-        self.config = Some(UniversalVmConfig::Dir(config_dir));
+        if self.config.is_none() {
+            self.config = Some(UniversalVmConfig::Dir(config_dir));
+        } else {
+            self.extra_config_dirs.push(config_dir);
+        }
         self
     }
 
@@ -112,7 +195,45 @@ impl UniversalVm {
         self
     }
 
+    /// Attaches the given empty disks to the VM, in addition to the boot disk
+    /// and the config images. Only supported on Farm.
+    ///
+    /// The device paths of the disks are recorded in the `TestEnv` (see
+    /// `DeployedUniversalVm::get_extra_disks()`) and, if the VM is configured
+    /// via a config directory, written to an `extra_disks.env` file in it, for
+    /// the activation script to source.
+    pub fn with_extra_disks(mut self, extra_disks: Vec<DiskSpec>) -> Self {
+        self.extra_disks = extra_disks;
+        self
+    }
+
+    /// Creates the given users in addition to the default admin user. Their
+    /// authorized keys are written to the SSH config image, so the users exist
+    /// from the first boot on. Only supported on Farm.
+    ///
+    /// Use `SshUserSession` to log into the VM as one of these users.
+    pub fn with_ssh_users(mut self, ssh_users: Vec<SshUser>) -> Self {
+        self.ssh_users = ssh_users;
+        self
+    }
+
     pub fn start(&self, env: &TestEnv) -> Result<()> {
+        self.validate_ssh_users()?;
+        if !self.ssh_users.is_empty() && InfraProvider::read_attribute(env) != InfraProvider::Farm {
+            bail!("Additional SSH users are only supported on Farm");
+        }
+        self.validate_extra_disks()?;
+        if !self.extra_disks.is_empty() && InfraProvider::read_attribute(env) != InfraProvider::Farm
+        {
+            bail!("Extra disks are only supported on Farm");
+        }
+        let extra_config_names = self.validate_extra_config_dirs()?;
+        if !extra_config_names.is_empty()
+            && InfraProvider::read_attribute(env) != InfraProvider::Farm
+        {
+            bail!("Additional config directories are only supported on Farm");
+        }
+
         let farm = Farm::from_test_env(env, "universal VM");
         let pot_setup = GroupSetup::read_attribute(env);
 
@@ -127,13 +248,13 @@ impl UniversalVm {
 
         let univm_path: PathBuf = [UNIVERSAL_VMS_DIR, &self.name].iter().collect();
         env.write_json_object(univm_path.join("vm.json"), vm)?;
-        let universal_vm_dir = env.get_path(univm_path);
+        let universal_vm_dir = env.get_path(&univm_path);
 
         let mut image_specs = vec![];
         if InfraProvider::read_attribute(env) == InfraProvider::Farm {
             // Setup SSH image
             let config_ssh_dir = env.get_universal_vm_config_ssh_dir(&self.name);
-            setup_ssh(env, config_ssh_dir.clone())?;
+            setup_ssh(env, config_ssh_dir.clone(), &self.ssh_users)?;
             let config_ssh_img = universal_vm_dir.join(CONF_SSH_IMG_FNAME);
             create_universal_vm_config_image(&config_ssh_dir, &config_ssh_img, "SSH")?;
 
@@ -145,53 +266,100 @@ impl UniversalVm {
             image_specs.push(ssh_config_img_file_spec);
         }
 
+        // Setup extra disks. These are attached ahead of the config image, which is
+        // identified by its label; whereas the extra disks are only identifiable by
+        // their position: `usb-storage` drives are enumerated in attachment order, as
+        // `/dev/sda`, `/dev/sdb`, etc. (the boot disk being a virtio device).
+        let mut extra_disks = vec![];
+        for disk in self.extra_disks.iter() {
+            let disk_img_fname = format!("extra_disk_{}.img.zst", disk.name);
+            let disk_img = universal_vm_dir.join(&disk_img_fname);
+            std::fs::create_dir_all(&universal_vm_dir)?;
+            create_empty_disk_image(&disk_img, disk.size_gib)?;
+            image_specs.push(AttachImageSpec::new(farm.upload_file(
+                &pot_setup.infra_group_name,
+                disk_img,
+                &disk_img_fname,
+            )?));
+            extra_disks.push(AttachedDisk {
+                name: disk.name.clone(),
+                size_gib: disk.size_gib,
+                device: format!("/dev/sd{}", (b'a' + image_specs.len() as u8 - 1) as char),
+            });
+        }
+        env.write_json_object(univm_path.join(EXTRA_DISKS_JSON), &extra_disks)?;
+
         // Setup config image
         if let Some(config) = &self.config {
             let config_img = match config {
                 UniversalVmConfig::Dir(config_dir) => {
+                    if !extra_disks.is_empty() {
+                        insert_file_to_config(
+                            config_dir.clone(),
+                            EXTRA_DISKS_ENV_FNAME,
+                            extra_disks_env(&extra_disks).as_bytes(),
+                        )?;
+                    }
+                    std::fs::create_dir_all(&universal_vm_dir)?;
+                    let config_dir = if self.extra_config_dirs.is_empty() {
+                        config_dir.clone()
+                    } else {
+                        let mut config_images = vec![];
+                        for (i, (extra_config_dir, name)) in self
+                            .extra_config_dirs
+                            .iter()
+                            .zip(extra_config_names.iter())
+                            .enumerate()
+                        {
+                            let label = format!("CONFIG{}", i + 1);
+                            let fname = format!("config_disk_{name}.img.zst");
+                            let img = universal_vm_dir.join(&fname);
+                            create_universal_vm_config_image(extra_config_dir, &img, &label)?;
+                            let sha256 = sha256_of_compressed_image(&img)?;
+                            image_specs.push(upload_config_image(
+                                env,
+                                &farm,
+                                &pot_setup.infra_group_name,
+                                img,
+                                &fname,
+                            )?);
+                            config_images.push(ConfigImage {
+                                name: name.clone(),
+                                label,
+                                sha256,
+                            });
+                        }
+                        env.write_json_object(univm_path.join(CONFIG_IMAGES_JSON), &config_images)?;
+                        let staged_config_dir = universal_vm_dir.join(STAGED_CONFIG_DIR_NAME);
+                        stage_config_dir(config_dir, &staged_config_dir, &config_images)?;
+                        staged_config_dir
+                    };
                     let config_img = universal_vm_dir.join(CONF_IMG_FNAME);
-                    std::fs::create_dir_all(universal_vm_dir)?;
-                    create_universal_vm_config_image(config_dir, &config_img, "CONFIG")?;
+                    create_universal_vm_config_image(&config_dir, &config_img, "CONFIG")?;
                     config_img
                 }
-                UniversalVmConfig::Img(config_img) => config_img.to_path_buf(),
+                UniversalVmConfig::Img(config_img) => {
+                    if !extra_disks.is_empty() {
+                        warn!(
+                            env.logger(),
+                            "Universal VM {} uses a prebuilt config image, extra disk device paths are not written to it",
+                            self.name
+                        );
+                    }
+                    config_img.to_path_buf()
+                }
             };
 
             if InfraProvider::read_attribute(env) == InfraProvider::Farm {
-                let file_id = id_of_file(config_img.clone())?;
-                let mut file_spec = AttachImageSpec::new(file_id.clone());
-
-                let upload = match farm.claim_file(&pot_setup.infra_group_name, &file_id)? {
-                    ClaimResult::FileClaimed(file_expiration) => {
-                        if let Some(expiration) = file_expiration.expiration {
-                            let now = Utc::now();
-                            let ttl = expiration - now;
-                            // If the file expires within a day we upload it again
-                            // to ensure it exists for at least a month.
-                            ttl < Duration::days(1)
-                        } else {
-                            // If there's no expiration time we assume the file never expires
-                            // so we don't need to upload it again.
-                            false
-                        }
-                    }
-                    ClaimResult::FileNotFound => true,
-                };
-
-                if upload {
-                    file_spec = AttachImageSpec::new(farm.upload_file(
-                        &pot_setup.infra_group_name,
-                        config_img,
-                        CONF_IMG_FNAME,
-                    )?);
-                    info!(env.logger(), "Uploaded image: {}", file_id);
-                } else {
-                    info!(
-                        env.logger(),
-                        "Image: {} was already uploaded, no need to upload it again", file_id,
-                    );
-                }
-                image_specs.push(file_spec);
+                // The primary config image is attached last, after the
+                // additional ones, which are identified by their labels.
+                image_specs.push(upload_config_image(
+                    env,
+                    &farm,
+                    &pot_setup.infra_group_name,
+                    config_img,
+                    CONF_IMG_FNAME,
+                )?);
             } else {
                 let tnet = TNet::read_attribute(env);
                 let tnet_node = tnet.nodes.last().expect("no nodes");
@@ -235,6 +403,215 @@ impl UniversalVm {
 
         Ok(())
     }
+
+    /// Returns the names of the additional config directories, under which
+    /// they are mounted on the VM.
+    fn validate_extra_config_dirs(&self) -> Result<Vec<String>> {
+        if self.extra_config_dirs.is_empty() {
+            return Ok(vec![]);
+        }
+        let Some(UniversalVmConfig::Dir(config_dir)) = &self.config else {
+            bail!("Additional config directories require the primary config to be a directory");
+        };
+        let mut names = vec![];
+        for extra_config_dir in self.extra_config_dirs.iter() {
+            let name = extra_config_dir
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default()
+                .to_string();
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                bail!("Invalid config directory name: {:?}", extra_config_dir);
+            }
+            if names.contains(&name) {
+                bail!("Duplicate config directory name: {:?}", name);
+            }
+            if config_dir.join(&name).exists() || name == PRIMARY_ACTIVATE_FNAME {
+                bail!(
+                    "Config directory name {:?} collides with an entry of the primary config directory {:?}",
+                    name,
+                    config_dir
+                );
+            }
+            names.push(name);
+        }
+        Ok(names)
+    }
+
+    fn validate_ssh_users(&self) -> Result<()> {
+        let mut names = std::collections::BTreeSet::new();
+        for user in self.ssh_users.iter() {
+            let mut chars = user.name.chars();
+            if !chars
+                .next()
+                .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+                || !chars
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+            {
+                bail!("Invalid SSH user name: {:?}", user.name);
+            }
+            if user.name == SSH_USERNAME {
+                bail!("SSH user {:?} is reserved", user.name);
+            }
+            if !names.insert(&user.name) {
+                bail!("Duplicate SSH user name: {:?}", user.name);
+            }
+            if user.pubkeys.is_empty() {
+                bail!("SSH user {:?} must have at least one public key", user.name);
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_extra_disks(&self) -> Result<()> {
+        let mut names = std::collections::BTreeSet::new();
+        for disk in self.extra_disks.iter() {
+            if disk.name.is_empty()
+                || !disk
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            {
+                bail!("Invalid extra disk name: {:?}", disk.name);
+            }
+            if !names.insert(&disk.name) {
+                bail!("Duplicate extra disk name: {:?}", disk.name);
+            }
+            if disk.size_gib == 0 {
+                bail!("Extra disk {:?} must not be empty", disk.name);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Uploads the given config image to Farm, unless it has been uploaded before
+/// and does not expire soon.
+fn upload_config_image(
+    env: &TestEnv,
+    farm: &Farm,
+    group_name: &str,
+    config_img: PathBuf,
+    fname: &str,
+) -> Result<AttachImageSpec> {
+    let file_id = id_of_file(config_img.clone())?;
+    let upload = match farm.claim_file(group_name, &file_id)? {
+        ClaimResult::FileClaimed(file_expiration) => {
+            if let Some(expiration) = file_expiration.expiration {
+                let now = Utc::now();
+                let ttl = expiration - now;
+                // If the file expires within a day we upload it again
+                // to ensure it exists for at least a month.
+                ttl < Duration::days(1)
+            } else {
+                // If there's no expiration time we assume the file never expires
+                // so we don't need to upload it again.
+                false
+            }
+        }
+        ClaimResult::FileNotFound => true,
+    };
+
+    if upload {
+        let file_spec = AttachImageSpec::new(farm.upload_file(group_name, config_img, fname)?);
+        info!(env.logger(), "Uploaded image: {}", file_id);
+        Ok(file_spec)
+    } else {
+        info!(
+            env.logger(),
+            "Image: {} was already uploaded, no need to upload it again", file_id,
+        );
+        Ok(AttachImageSpec::new(file_id))
+    }
+}
+
+/// Computes the SHA-256 checksum of the given image, after decompressing it.
+fn sha256_of_compressed_image(img: &Path) -> Result<String> {
+    let mut decoder = zstd::stream::read::Decoder::new(File::open(img)?)?;
+    let mut sha256_hasher = Sha256::new();
+    std::io::copy(&mut decoder, &mut sha256_hasher)?;
+    Ok(hex::encode(sha256_hasher.finish()))
+}
+
+/// Copies the primary config directory to `staged_config_dir`, replacing its
+/// activate script by one which verifies and mounts the given config images
+/// before running the original activate script.
+fn stage_config_dir(
+    config_dir: &Path,
+    staged_config_dir: &Path,
+    config_images: &[ConfigImage],
+) -> Result<()> {
+    if staged_config_dir.exists() {
+        fs::remove_dir_all(staged_config_dir)?;
+    }
+    TestEnv::shell_copy_with_deref(config_dir, staged_config_dir)?;
+    let activate = staged_config_dir.join(ACTIVATE_FNAME);
+    if activate.exists() {
+        fs::rename(&activate, staged_config_dir.join(PRIMARY_ACTIVATE_FNAME))?;
+    }
+    for config_image in config_images.iter() {
+        // The mount points must exist in the (read-only) primary config image.
+        fs::create_dir_all(staged_config_dir.join(&config_image.name))?;
+    }
+    insert_file_to_config(
+        staged_config_dir.to_path_buf(),
+        ACTIVATE_FNAME,
+        mount_config_images_script(config_images).as_bytes(),
+    )
+}
+
+/// Renders the activate script verifying and mounting the given config images.
+fn mount_config_images_script(config_images: &[ConfigImage]) -> String {
+    let mut script = String::from("#!/bin/sh\nset -e\n");
+    for ConfigImage {
+        name,
+        label,
+        sha256,
+    } in config_images.iter()
+    {
+        script.push_str(&format!(
+            r#"device=/dev/disk/by-label/{label}
+for _ in $(seq 60); do
+  [ -e "$device" ] && break
+  sleep 1
+done
+echo "{sha256}  $device" | sha256sum -c -
+mount -o ro "$device" /config/{name}
+"#
+        ));
+    }
+    script.push_str(&format!(
+        r#"if [ -f /config/{PRIMARY_ACTIVATE_FNAME} ]; then
+  exec /config/{PRIMARY_ACTIVATE_FNAME}
+fi
+"#
+    ));
+    script
+}
+
+/// Creates a zstd-compressed, zero-filled raw disk image of the given size.
+fn create_empty_disk_image(output_img: &Path, size_gib: u64) -> Result<()> {
+    let zeros = std::io::repeat(0).take(size_gib << 30);
+    zstd::stream::copy_encode(zeros, File::create(output_img)?, 0)?;
+    Ok(())
+}
+
+/// Renders the contents of the `extra_disks.env` config file.
+fn extra_disks_env(extra_disks: &[AttachedDisk]) -> String {
+    extra_disks
+        .iter()
+        .map(|disk| {
+            format!(
+                "EXTRA_DISK_{}={}\n",
+                disk.name.to_ascii_uppercase(),
+                disk.device
+            )
+        })
+        .collect()
 }
 
 fn create_universal_vm_config_image(
@@ -296,6 +673,7 @@ impl UniversalVms for TestEnv {
     fn get_deployed_universal_vm(&self, name: &str) -> Result<DeployedUniversalVm> {
         let universal_vm_dir = self.get_deployed_universal_vm_dir(name);
         if universal_vm_dir.is_dir() {
+            self.record_capability(Capability::UniversalVmTouched);
             Ok(DeployedUniversalVm {
                 env: self.clone(),
                 name: name.to_string(),
@@ -345,7 +723,7 @@ pub fn insert_file_to_config(config_dir: PathBuf, file_name: &str, content: &[u8
     Ok(())
 }
 
-fn setup_ssh(env: &TestEnv, config_dir: PathBuf) -> Result<()> {
+fn setup_ssh(env: &TestEnv, config_dir: PathBuf, ssh_users: &[SshUser]) -> Result<()> {
     let ssh_authorized_pub_keys_dir = env.get_path(SSH_AUTHORIZED_PUB_KEYS_DIR);
     let config_dir_ssh_dir = config_dir.join(CONFIG_DIR_SSH_AUTHORIZED_KEYS_DIR);
     fs::create_dir_all(config_dir_ssh_dir.clone())?;
@@ -353,6 +731,17 @@ fn setup_ssh(env: &TestEnv, config_dir: PathBuf) -> Result<()> {
         ssh_authorized_pub_keys_dir.join(SSH_USERNAME),
         config_dir_ssh_dir.join(SSH_USERNAME),
     )?;
+    write_ssh_users(&config_dir_ssh_dir, ssh_users)
+}
+
+/// Writes the authorized keys of each of the given users to a file named after
+/// the user, from which the VM creates the account at first boot.
+fn write_ssh_users(authorized_keys_dir: &Path, ssh_users: &[SshUser]) -> Result<()> {
+    for user in ssh_users.iter() {
+        let mut authorized_keys = user.pubkeys.join("\n");
+        authorized_keys.push('\n');
+        fs::write(authorized_keys_dir.join(&user.name), authorized_keys)?;
+    }
     Ok(())
 }
 
@@ -379,6 +768,20 @@ impl DeployedUniversalVm {
         let p: PathBuf = [UNIVERSAL_VMS_DIR, &self.name].iter().collect();
         self.env.read_json_object(p.join("vm.json"))
     }
+
+    /// Returns the extra disks attached to the VM, as requested via
+    /// `UniversalVm::with_extra_disks()`.
+    pub fn get_extra_disks(&self) -> Result<Vec<AttachedDisk>> {
+        let p: PathBuf = [UNIVERSAL_VMS_DIR, &self.name].iter().collect();
+        self.env.read_json_object(p.join(EXTRA_DISKS_JSON))
+    }
+
+    /// Returns the config images created from the additional config
+    /// directories of the VM, see `UniversalVm::with_config_dir()`.
+    pub fn get_config_images(&self) -> Result<Vec<ConfigImage>> {
+        let p: PathBuf = [UNIVERSAL_VMS_DIR, &self.name].iter().collect();
+        self.env.read_json_object(p.join(CONFIG_IMAGES_JSON))
+    }
 }
 
 impl SshSession for DeployedUniversalVm {
@@ -399,6 +802,24 @@ impl SshSession for DeployedUniversalVm {
     }
 }
 
+impl SshUserSession for DeployedUniversalVm {
+    fn get_ssh_session_for_user(&self, user: &str, priv_key_path: &Path) -> Result<Session> {
+        let vm = self.get_vm()?;
+        get_ssh_session_for_user(&self.env, IpAddr::V6(vm.ipv6), user, priv_key_path)
+    }
+
+    fn block_on_ssh_session_for_user(&self, user: &str, priv_key_path: &Path) -> Result<Session> {
+        let vm = self.get_vm()?;
+        retry_with_msg!(
+            format!("get_ssh_session as {user} to {}", vm.ipv6.to_string()),
+            self.env.logger(),
+            SSH_RETRY_TIMEOUT,
+            RETRY_BACKOFF,
+            || { self.get_ssh_session_for_user(user, priv_key_path) }
+        )
+    }
+}
+
 const IPV4_RETRIEVE_SH_SCRIPT: &str = r#"set -e -o pipefail
 count=0
 until ipv4=$(ip -j address show dev enp2s0 \
@@ -424,3 +845,163 @@ impl RetrieveIpv4Addr for DeployedUniversalVm {
             .context("ipv4 retrieval")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn vm_with_config_dirs(config_dirs: &[PathBuf]) -> UniversalVm {
+        config_dirs
+            .iter()
+            .fold(UniversalVm::new("vm".to_string()), |vm, config_dir| {
+                vm.with_config_dir(config_dir.clone())
+            })
+    }
+
+    fn config_image(name: &str, label: &str) -> ConfigImage {
+        ConfigImage {
+            name: name.to_string(),
+            label: label.to_string(),
+            sha256: "ab".repeat(32),
+        }
+    }
+
+    #[test]
+    fn first_config_dir_is_the_primary_one() {
+        let vm = vm_with_config_dirs(&[PathBuf::from("/a/primary"), PathBuf::from("/b/payload")]);
+        assert_eq!(
+            vm.config,
+            Some(UniversalVmConfig::Dir(PathBuf::from("/a/primary")))
+        );
+        assert_eq!(vm.extra_config_dirs, vec![PathBuf::from("/b/payload")]);
+        assert_eq!(vm.validate_extra_config_dirs().unwrap(), vec!["payload"]);
+    }
+
+    #[test]
+    fn config_dirs_with_colliding_names_are_rejected() {
+        let vm = vm_with_config_dirs(&[
+            PathBuf::from("/a/primary"),
+            PathBuf::from("/b/payload"),
+            PathBuf::from("/c/payload"),
+        ]);
+        let err = vm.validate_extra_config_dirs().unwrap_err();
+        assert!(err.to_string().contains("Duplicate config directory name"));
+    }
+
+    #[test]
+    fn config_dir_colliding_with_primary_entry_is_rejected() {
+        let primary = TempDir::new().unwrap();
+        fs::create_dir(primary.path().join("payload")).unwrap();
+        let vm = vm_with_config_dirs(&[primary.path().to_path_buf(), PathBuf::from("/b/payload")]);
+        let err = vm.validate_extra_config_dirs().unwrap_err();
+        assert!(err.to_string().contains("collides"));
+    }
+
+    #[test]
+    fn extra_config_dirs_require_a_primary_config_dir() {
+        let vm = UniversalVm::new("vm".to_string())
+            .with_config_img(PathBuf::from("/a/config.img.zst"))
+            .with_config_dir(PathBuf::from("/b/payload"));
+        assert!(vm.validate_extra_config_dirs().is_err());
+    }
+
+    #[test]
+    fn staged_config_dir_mounts_images_before_running_primary_activate() {
+        let primary = TempDir::new().unwrap();
+        fs::write(primary.path().join(ACTIVATE_FNAME), "#!/bin/sh\necho hi\n").unwrap();
+        fs::write(primary.path().join("data.txt"), "data").unwrap();
+        let staged = TempDir::new().unwrap();
+        let staged_dir = staged.path().join(STAGED_CONFIG_DIR_NAME);
+        let images = [
+            config_image("corpus", "CONFIG1"),
+            config_image("payload", "CONFIG2"),
+        ];
+
+        stage_config_dir(primary.path(), &staged_dir, &images).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(staged_dir.join(PRIMARY_ACTIVATE_FNAME)).unwrap(),
+            "#!/bin/sh\necho hi\n"
+        );
+        assert!(staged_dir.join("data.txt").is_file());
+        assert!(staged_dir.join("corpus").is_dir());
+        assert!(staged_dir.join("payload").is_dir());
+        let activate = fs::read_to_string(staged_dir.join(ACTIVATE_FNAME)).unwrap();
+        for image in images.iter() {
+            let verify = format!("echo \"{}  $device\" | sha256sum -c -", image.sha256);
+            let mount = format!("mount -o ro \"$device\" /config/{}", image.name);
+            let verify_pos = activate.find(&verify).expect("checksum is not verified");
+            let mount_pos = activate.find(&mount).expect("image is not mounted");
+            assert!(verify_pos < mount_pos);
+        }
+        let run_primary = activate.find("exec /config/activate.primary").unwrap();
+        assert!(activate.rfind("mount -o ro").unwrap() < run_primary);
+        // The primary config directory is left untouched.
+        assert!(primary.path().join(ACTIVATE_FNAME).is_file());
+        assert!(!primary.path().join(PRIMARY_ACTIVATE_FNAME).exists());
+    }
+
+    fn ssh_user(name: &str, pubkeys: &[&str]) -> SshUser {
+        SshUser {
+            name: name.to_string(),
+            pubkeys: pubkeys.iter().map(|key| key.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn invalid_ssh_users_are_rejected() {
+        for (users, expected_err) in [
+            (vec![ssh_user("Alice", &["key"])], "Invalid SSH user name"),
+            (vec![ssh_user("1alice", &["key"])], "Invalid SSH user name"),
+            (vec![ssh_user("", &["key"])], "Invalid SSH user name"),
+            (vec![ssh_user(SSH_USERNAME, &["key"])], "is reserved"),
+            (
+                vec![ssh_user("alice", &["key"]), ssh_user("alice", &["key2"])],
+                "Duplicate SSH user name",
+            ),
+            (vec![ssh_user("alice", &[])], "at least one public key"),
+        ] {
+            let vm = UniversalVm::new("vm".to_string()).with_ssh_users(users.clone());
+            let err = vm.validate_ssh_users().unwrap_err();
+            assert!(
+                err.to_string().contains(expected_err),
+                "{users:?}: unexpected error {err}"
+            );
+        }
+        let vm = UniversalVm::new("vm".to_string()).with_ssh_users(vec![
+            ssh_user("alice", &["key"]),
+            ssh_user("_bob-2", &["key"]),
+        ]);
+        vm.validate_ssh_users().unwrap();
+    }
+
+    #[test]
+    fn ssh_users_get_an_authorized_keys_file_each() {
+        let dir = TempDir::new().unwrap();
+        write_ssh_users(
+            dir.path(),
+            &[
+                ssh_user("alice", &["ssh-ed25519 AAAA alice"]),
+                ssh_user("bob", &["ssh-ed25519 BBBB bob", "ssh-ed25519 CCCC bob"]),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("alice")).unwrap(),
+            "ssh-ed25519 AAAA alice\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("bob")).unwrap(),
+            "ssh-ed25519 BBBB bob\nssh-ed25519 CCCC bob\n"
+        );
+    }
+
+    #[test]
+    fn no_ssh_users_leave_the_authorized_keys_dir_untouched() {
+        let dir = TempDir::new().unwrap();
+        write_ssh_users(dir.path(), &[]).unwrap();
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,232 @@
+use std::{
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Result};
+use ic_types::NodeId;
+use regex::Regex;
+use slog::{info, warn};
+
+use crate::driver::{
+    farm::HostFeature,
+    ic::{AmountOfMemoryKiB, ImageSizeGiB, NrOfVCPUs, VmAllocationStrategy, VmResources},
+    test_env::TestEnv,
+    test_env_api::{
+        get_dependency_path, HasTopologySnapshot, IcNodeContainer, IcNodeSnapshot, SshSession,
+    },
+    universal_vm::{UniversalVm, UniversalVms},
+};
+
+pub(crate) const VECTOR_VM_NAME: &str = "vector";
+
+/// The port on which the aggregator receives the journald entries of the nodes.
+const VECTOR_PORT: u16 = 6514;
+
+/// The directory on the vector VM holding one `<node_id>.log` file per node.
+const VECTOR_LOGS_DIR: &str = "/var/lib/vector/logs";
+
+const AGGREGATOR_IMAGE_TARBALL: &str = "python3.tar";
+const AGGREGATOR_SCRIPT: &str = "vector_aggregator.py";
+
+/// The script forwarding the journal of a node to the vector VM, on the node.
+const FORWARDER_SCRIPT: &str = "/tmp/vector-forwarder.sh";
+/// The journal cursor of the last forwarded entry, on the node.
+const FORWARDER_CURSOR: &str = "/tmp/vector-forwarder.cursor";
+
+/// A universal VM aggregating the journald logs of the IC nodes, such that tests
+/// can assert on log lines without grepping the journal of every node over SSH.
+///
+/// The nodes only forward their logs once `HasVector::sync_with_vector()` was
+/// called, which must be repeated for nodes added to the topology later on.
+pub struct VectorVm {
+    universal_vm: UniversalVm,
+}
+
+/// A log line of a node, as returned by `VectorVm::query_logs()`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct LogLine {
+    /// The time at which the node wrote the entry to its journal.
+    pub timestamp: SystemTime,
+    pub message: String,
+}
+
+impl Default for VectorVm {
+    fn default() -> Self {
+        VectorVm::new()
+    }
+}
+
+impl VectorVm {
+    pub fn new() -> Self {
+        VectorVm {
+            universal_vm: UniversalVm::new(VECTOR_VM_NAME.to_string()).with_vm_resources(
+                VmResources {
+                    vcpus: Some(NrOfVCPUs::new(2)),
+                    memory_kibibytes: Some(AmountOfMemoryKiB::new(4195000)), // 4GiB
+                    boot_image_minimal_size_gibibytes: Some(ImageSizeGiB::new(50)),
+                },
+            ),
+        }
+    }
+
+    pub fn with_vm_resources(mut self, vm_resources: VmResources) -> Self {
+        self.universal_vm = self.universal_vm.with_vm_resources(vm_resources);
+        self
+    }
+
+    pub fn with_vm_allocation(mut self, vm_allocation: VmAllocationStrategy) -> Self {
+        self.universal_vm = self.universal_vm.with_vm_allocation(vm_allocation);
+        self
+    }
+
+    pub fn with_required_host_features(mut self, required_host_features: Vec<HostFeature>) -> Self {
+        self.universal_vm = self
+            .universal_vm
+            .with_required_host_features(required_host_features);
+        self
+    }
+
+    pub fn start(&self, env: &TestEnv) -> Result<()> {
+        let config_dir = env.single_activate_script_config_dir(
+            VECTOR_VM_NAME,
+            &format!(
+                r#"#!/bin/sh
+set -e
+mkdir -p -m 755 {VECTOR_LOGS_DIR}
+docker load -i /config/{AGGREGATOR_IMAGE_TARBALL}
+docker run -d --restart=always --name vector-aggregator --network host \
+    -v /config:/config:ro \
+    -v {VECTOR_LOGS_DIR}:{VECTOR_LOGS_DIR} \
+    python3:latest \
+    python3 /config/{AGGREGATOR_SCRIPT} {VECTOR_LOGS_DIR} {VECTOR_PORT}
+"#
+            ),
+        )?;
+        for file in [AGGREGATOR_IMAGE_TARBALL, AGGREGATOR_SCRIPT] {
+            std::fs::copy(
+                get_dependency_path(Path::new("rs/tests").join(file)),
+                config_dir.join(file),
+            )?;
+        }
+        self.universal_vm
+            .clone()
+            .with_config_dir(config_dir)
+            .start(env)?;
+        Ok(())
+    }
+
+    /// Returns the lines that the node `node_id` logged in the last `window`
+    /// and that match `regex`, oldest first. Lines are only available once the
+    /// node forwards its logs, see `HasVector::sync_with_vector()`.
+    pub fn query_logs(
+        env: &TestEnv,
+        node_id: NodeId,
+        regex: &Regex,
+        window: Duration,
+    ) -> Result<Vec<LogLine>> {
+        let since = SystemTime::now()
+            .checked_sub(window)
+            .unwrap_or(UNIX_EPOCH)
+            .duration_since(UNIX_EPOCH)?
+            .as_micros();
+        let deployed_vector_vm = env.get_deployed_universal_vm(VECTOR_VM_NAME)?;
+        let output = deployed_vector_vm.block_on_bash_script(&format!(
+            r#"
+file="{VECTOR_LOGS_DIR}/{node_id}.log"
+if [ -f "$file" ]; then
+    awk -F '\t' -v since={since} '$1 >= since' "$file"
+fi
+"#
+        ))?;
+        let mut lines = vec![];
+        for line in output.lines() {
+            let Some((timestamp, message)) = line.split_once('\t') else {
+                bail!("Unexpected line in the logs of {node_id}: {line:?}");
+            };
+            if regex.is_match(message) {
+                lines.push(LogLine {
+                    timestamp: UNIX_EPOCH + Duration::from_micros(timestamp.parse()?),
+                    message: message.to_string(),
+                });
+            }
+        }
+        Ok(lines)
+    }
+}
+
+/// The Vector trait allows forwarding the logs of the IC nodes to a running
+/// vector VM.
+pub trait HasVector {
+    /// Retrieves a topology snapshot and starts forwarding the journal of every
+    /// node (assigned, unassigned and API boundary nodes) to the vector VM.
+    /// Nodes that already forward their journal are skipped, so this can be
+    /// called again after nodes were added to the topology. A node starts with
+    /// the entries it logged before the forwarding was set up.
+    fn sync_with_vector(&self) -> Result<()>;
+}
+
+impl HasVector for TestEnv {
+    fn sync_with_vector(&self) -> Result<()> {
+        let deployed_vector_vm = self.get_deployed_universal_vm(VECTOR_VM_NAME)?;
+        let vector_ipv6 = deployed_vector_vm.get_vm()?.ipv6;
+        let topology_snapshot = self.topology_snapshot();
+        let nodes: Vec<IcNodeSnapshot> = topology_snapshot
+            .subnets()
+            .flat_map(|subnet| subnet.nodes())
+            .chain(topology_snapshot.unassigned_nodes())
+            .chain(topology_snapshot.api_boundary_nodes())
+            .collect();
+        let mut failed = vec![];
+        for node in nodes.iter() {
+            let script = forwarder_setup_script(node.node_id, &vector_ipv6.to_string());
+            if let Err(e) = node.block_on_bash_script(&script) {
+                warn!(
+                    self.logger(),
+                    "Failed to forward the logs of node {} to {VECTOR_VM_NAME}: {e:?}",
+                    node.node_id
+                );
+                failed.push(node.node_id);
+            }
+        }
+        if !failed.is_empty() {
+            bail!("Failed to forward the logs of nodes {failed:?} to {VECTOR_VM_NAME}");
+        }
+        info!(
+            self.logger(),
+            "Forwarding the logs of {} nodes to {VECTOR_VM_NAME}",
+            nodes.len()
+        );
+        Ok(())
+    }
+}
+
+/// Returns a script that, unless it is already running, starts a background
+/// process on the node `node_id` that streams its journal to the aggregator at
+/// `vector_ip`. The process reconnects if the connection drops and resumes
+/// after the last entry it forwarded.
+fn forwarder_setup_script(node_id: NodeId, vector_ip: &str) -> String {
+    format!(
+        r#"
+set -e
+if pgrep -f {FORWARDER_SCRIPT} > /dev/null; then
+    exit 0
+fi
+cat > {FORWARDER_SCRIPT} <<'EOF'
+#!/bin/bash
+while true; do
+    lines=()
+    if [ ! -f {FORWARDER_CURSOR} ]; then
+        lines=(--lines=all)
+    fi
+    {{
+        echo {node_id}
+        journalctl --follow --output=json --cursor-file={FORWARDER_CURSOR} "${{lines[@]}}"
+    }} > /dev/tcp/{vector_ip}/{VECTOR_PORT}
+    sleep 1
+done
+EOF
+nohup setsid bash {FORWARDER_SCRIPT} > /dev/null 2>&1 < /dev/null &
+"#
+    )
+}
@@ -642,6 +642,7 @@ spec:
                 v_cpus: vm_req.vcpus.get(),
                 memory_ki_b: vm_req.memory_kibibytes.get(),
             },
+            placement: None,
         })
     }
 
@@ -8,7 +8,10 @@ use registry_canister::mutations::{
 };
 
 use crate::{
-    driver::test_env_api::{HasPublicApiUrl, IcNodeSnapshot},
+    driver::{
+        capabilities::{self, Capability},
+        test_env_api::{HasPublicApiUrl, IcNodeSnapshot},
+    },
     util::{create_agent, runtime_from_url},
 };
 use candid::CandidType;
@@ -440,6 +443,7 @@ pub async fn submit_external_proposal_with_test_id<T: CandidType>(
     nns_function: NnsFunction,
     payload: T,
 ) -> ProposalId {
+    capabilities::record(Capability::ProposalSubmitted);
     let sender = Sender::from_keypair(&TEST_NEURON_1_OWNER_KEYPAIR);
     let neuron_id = NeuronId(TEST_NEURON_1_ID);
     submit_external_update_proposal(
@@ -485,6 +489,7 @@ pub async fn submit_update_elected_replica_versions_proposal(
     upgrade_urls: Vec<String>,
     versions_to_unelect: Vec<String>,
 ) -> ProposalId {
+    capabilities::record(Capability::ProposalSubmitted);
     submit_external_update_proposal_allowing_error(
         governance,
         sender,
@@ -538,6 +543,7 @@ pub async fn submit_deploy_guestos_to_all_subnet_nodes_proposal(
     version: ReplicaVersion,
     subnet_id: SubnetId,
 ) -> ProposalId {
+    capabilities::record(Capability::ProposalSubmitted);
     submit_external_update_proposal_allowing_error(
         governance,
         sender,
@@ -574,6 +580,7 @@ pub async fn submit_create_application_subnet_proposal(
     node_ids: Vec<NodeId>,
     replica_version: ReplicaVersion,
 ) -> ProposalId {
+    capabilities::record(Capability::ProposalSubmitted);
     let config =
         subnet_configuration::get_default_config_params(SubnetType::Application, node_ids.len());
     let payload = CreateSubnetPayload {
@@ -648,6 +655,7 @@ pub async fn submit_update_unassigned_node_version_proposal(
     neuron_id: NeuronId,
     version: String,
 ) -> ProposalId {
+    capabilities::record(Capability::ProposalSubmitted);
     submit_external_update_proposal_allowing_error(
         governance,
         sender,
@@ -686,6 +694,7 @@ pub async fn submit_update_elected_hostos_versions_proposal(
     upgrade_urls: Vec<String>,
     versions_to_unelect: Vec<String>,
 ) -> ProposalId {
+    capabilities::record(Capability::ProposalSubmitted);
     submit_external_update_proposal_allowing_error(
         governance,
         sender,
@@ -727,6 +736,7 @@ pub async fn submit_update_nodes_hostos_version_proposal(
     version: HostosVersion,
     node_ids: Vec<NodeId>,
 ) -> ProposalId {
+    capabilities::record(Capability::ProposalSubmitted);
     submit_external_update_proposal_allowing_error(
         governance,
         sender,
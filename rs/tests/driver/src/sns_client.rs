@@ -1,9 +1,12 @@
 use crate::{
-    canister_agent::HasCanisterAgentCapability,
-    canister_api::{CallMode, ListDeployedSnsesRequest, SnsRequestProvider},
+    canister_agent::{CanisterAgent, HasCanisterAgentCapability},
+    canister_api::{
+        CallMode, Icrc1BalanceOfResponse, Icrc1MetadataResponse, ListDeployedSnsesRequest,
+        SnsRequestProvider,
+    },
     driver::{
         test_env::{TestEnv, TestEnvAttribute},
-        test_env_api::{GetFirstHealthyNodeSnapshot, HasPublicApiUrl},
+        test_env_api::{GetFirstHealthyNodeSnapshot, HasPublicApiUrl, IcNodeSnapshot},
     },
     nns::{
         get_governance_canister, submit_external_proposal_with_test_id,
@@ -45,25 +48,38 @@ use ic_nns_governance_api::pb::v1::{
     Proposal,
 };
 use ic_nns_test_utils::sns_wasm::ensure_sns_wasm_gzipped;
-use ic_sns_governance::pb::v1::governance::Mode;
+use ic_sns_governance::pb::v1::{
+    governance::Mode, GetMetadataResponse, ListNeuronsResponse as ListSnsNeuronsResponse,
+};
 use ic_sns_init::pb::v1::SnsInitPayload;
-use ic_sns_swap::pb::v1::{GetStateRequest, GetStateResponse, Init, Lifecycle};
+use ic_sns_swap::pb::v1::{
+    GetDerivedStateResponse, GetStateRequest, GetStateResponse, Init, Lifecycle,
+};
 use ic_sns_wasm::pb::v1::{
     AddWasmRequest, SnsCanisterIds, SnsCanisterType, SnsWasm, UpdateSnsSubnetListRequest,
 };
 use ic_types::Cycles;
+use icrc_ledger_types::icrc1::account::Account;
 use serde::{Deserialize, Serialize};
-use slog::info;
+use slog::{info, Logger};
 use std::{str::FromStr, time::SystemTime};
 
 pub const SNS_SALE_PARAM_MIN_PARTICIPANT_ICP_E8S: u64 = E8;
 pub const SNS_SALE_PARAM_MAX_PARTICIPANT_ICP_E8S: u64 = 250_000 * E8;
 
+/// Maximum time to wait for the SNS to be deployed via proposal.
+pub const SNS_INSTALLATION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+/// Interval between polls of the SNS swap lifecycle.
+const SWAP_LIFECYCLE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SnsClient {
     pub sns_canisters: SnsCanisterIds,
     pub wallet_canister_id: PrincipalId,
     pub sns_wasm_canister_id: PrincipalId,
+    /// The subnet the SNS canisters were installed on, if known.
+    #[serde(default)]
+    pub subnet_id: Option<SubnetId>,
 }
 
 impl TestEnvAttribute for SnsClient {
@@ -137,12 +153,99 @@ impl SnsClient {
         env: &TestEnv,
         create_service_nervous_system_proposal: CreateServiceNervousSystem,
     ) -> Self {
+        SnsInstallationBuilder::new()
+            .with_create_service_nervous_system_proposal(create_service_nervous_system_proposal)
+            .install(env)
+    }
+
+    pub fn get_sns_client_for_static_testnet(env: &TestEnv) -> SnsClient {
+        let sns_canisters_str =
+            std::env::var("SNS_CANISTERS").expect("variable SNS_CANISTERS not specified");
+        let sns_canisters: ic_sns_init::SnsCanisterIds = serde_json::from_str(&sns_canisters_str)
+            .unwrap_or_else(|_| panic!("cannot parse string as JSON: `{sns_canisters_str}`"));
+
+        // Transform from a json-parsable representation to the protobuf representation which is
+        // used to init the sns canisters.
+        let sns_canisters = SnsCanisterIds {
+            governance: sns_canisters.governance.into(),
+            ledger: sns_canisters.ledger.into(),
+            root: sns_canisters.root.into(),
+            swap: sns_canisters.swap.into(),
+            index: sns_canisters.index.into(),
+        };
+
+        let sns_client = SnsClient {
+            sns_canisters,
+            // TODO: Provide a wallet canister for static testnet?
+            wallet_canister_id: PrincipalId::from_str("aaaaa-aa").unwrap(),
+            sns_wasm_canister_id: SNS_WASM_CANISTER_ID.get(),
+            subnet_id: None,
+        };
+        sns_client.write_attribute(env);
+        sns_client
+    }
+}
+
+/// Installs the SNS canister suite (root, governance, ledger, swap, index) via
+/// a `CreateServiceNervousSystem` proposal, akin to the `NnsInstallationBuilder`.
+/// Requires an installed NNS.
+///
+/// The deployed canister ids are recorded as the [SnsClient] attribute of the
+/// environment, from which [Sns::from_env] connects to them.
+pub struct SnsInstallationBuilder {
+    proposal: CreateServiceNervousSystem,
+    subnet_id: Option<SubnetId>,
+    installation_timeout: std::time::Duration,
+}
+
+impl Default for SnsInstallationBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SnsInstallationBuilder {
+    /// An SNS with "openchat-ish" parameters, installed on the first application
+    /// subnet.
+    pub fn new() -> Self {
+        Self {
+            proposal: openchat_create_service_nervous_system_proposal(),
+            subnet_id: None,
+            installation_timeout: SNS_INSTALLATION_TIMEOUT,
+        }
+    }
+
+    /// Sets the init parameters of the SNS, see e.g.
+    /// [test_create_service_nervous_system_proposal].
+    pub fn with_create_service_nervous_system_proposal(
+        mut self,
+        create_service_nervous_system_proposal: CreateServiceNervousSystem,
+    ) -> Self {
+        self.proposal = create_service_nervous_system_proposal;
+        self
+    }
+
+    /// Installs the SNS onto the given subnet instead of the first application
+    /// subnet.
+    pub fn on_subnet(mut self, subnet_id: SubnetId) -> Self {
+        self.subnet_id = Some(subnet_id);
+        self
+    }
+
+    pub fn with_overall_timeout(mut self, duration: std::time::Duration) -> Self {
+        self.installation_timeout = duration;
+        self
+    }
+
+    /// Installs the SNS, records its canister ids in `env` and checks that it is
+    /// healthy.
+    pub fn install(&self, env: &TestEnv) -> SnsClient {
         add_all_wasms_to_sns_wasm(env);
 
         let log = env.logger();
         let nns_node = env.get_first_healthy_nns_node_snapshot();
         let runtime = runtime_from_url(nns_node.get_public_url(), nns_node.effective_canister_id());
-        let app_node = env.get_first_healthy_application_node_snapshot();
+        let app_node = sns_subnet_node(env, self.subnet_id);
         let subnet_id = app_node.subnet_id().unwrap();
         let canister_agent = block_on(app_node.build_canister_agent());
 
@@ -172,19 +275,29 @@ impl SnsClient {
             log,
             "Submitting and executing CreateServiceNervousSystem proposal"
         );
-        let sns_canisters = block_on(deploy_new_sns_via_proposal(
-            env,
-            create_service_nervous_system_proposal,
-        ))
+        let sns_canisters = block_on(async {
+            tokio::time::timeout(
+                self.installation_timeout,
+                deploy_new_sns_via_proposal(env, self.proposal.clone()),
+            )
+            .await
+            .unwrap_or_else(|_| {
+                panic!(
+                    "SNS canisters were not installed within timeout of {} sec",
+                    self.installation_timeout.as_secs()
+                )
+            })
+        })
         .context("creating a new SNS")
         .unwrap();
 
         // Create SNS client and write it to the environment
         let wallet_canister_id = to_principal_id(&wallet_canister.canister_id());
-        let sns_client = Self {
+        let sns_client = SnsClient {
             sns_canisters,
             wallet_canister_id,
             sns_wasm_canister_id: SNS_WASM_CANISTER_ID.get(),
+            subnet_id: Some(subnet_id),
         };
         sns_client.write_attribute(env);
 
@@ -205,31 +318,164 @@ impl SnsClient {
 
         sns_client
     }
+}
 
-    pub fn get_sns_client_for_static_testnet(env: &TestEnv) -> SnsClient {
-        let sns_canisters_str =
-            std::env::var("SNS_CANISTERS").expect("variable SNS_CANISTERS not specified");
-        let sns_canisters: ic_sns_init::SnsCanisterIds = serde_json::from_str(&sns_canisters_str)
-            .unwrap_or_else(|_| panic!("cannot parse string as JSON: `{sns_canisters_str}`"));
+/// A healthy node of the subnet `subnet_id`, or of the first application subnet.
+fn sns_subnet_node(env: &TestEnv, subnet_id: Option<SubnetId>) -> IcNodeSnapshot {
+    match subnet_id {
+        Some(subnet_id) => env.get_first_healthy_node_snapshot_where(|s| s.subnet_id == subnet_id),
+        None => env.get_first_healthy_application_node_snapshot(),
+    }
+}
 
-        // Transform from a json-parsable representation to the protobuf representation which is
-        // used to init the sns canisters.
-        let sns_canisters = SnsCanisterIds {
-            governance: sns_canisters.governance.into(),
-            ledger: sns_canisters.ledger.into(),
-            root: sns_canisters.root.into(),
-            swap: sns_canisters.swap.into(),
-            index: sns_canisters.index.into(),
-        };
+/// A deployed SNS, with typed handles of its canisters.
+pub struct Sns {
+    client: SnsClient,
+    requests: SnsRequestProvider,
+    agent: CanisterAgent,
+    log: Logger,
+}
 
-        let sns_client = SnsClient {
-            sns_canisters,
-            // TODO: Provide a wallet canister for static testnet?
-            wallet_canister_id: PrincipalId::from_str("aaaaa-aa").unwrap(),
-            sns_wasm_canister_id: SNS_WASM_CANISTER_ID.get(),
-        };
-        sns_client.write_attribute(env);
-        sns_client
+impl Sns {
+    /// Connects to the SNS recorded in `env` (see [SnsInstallationBuilder]) via a
+    /// node of the subnet it was installed on.
+    pub async fn from_env(env: &TestEnv) -> Self {
+        let client = SnsClient::read_attribute(env);
+        let node = sns_subnet_node(env, client.subnet_id);
+        let agent = node.build_canister_agent().await;
+        Self {
+            requests: SnsRequestProvider::from_sns_client(&client),
+            client,
+            agent,
+            log: env.logger(),
+        }
+    }
+
+    pub fn client(&self) -> &SnsClient {
+        &self.client
+    }
+
+    pub fn governance(&self) -> SnsGovernance<'_> {
+        SnsGovernance { sns: self }
+    }
+
+    pub fn ledger(&self) -> SnsLedger<'_> {
+        SnsLedger { sns: self }
+    }
+
+    pub fn swap(&self) -> SnsSwap<'_> {
+        SnsSwap { sns: self }
+    }
+
+    /// Waits until the lifecycle of the SNS swap is `lifecycle`, for at most
+    /// `timeout`.
+    pub async fn await_swap_lifecycle(
+        &self,
+        lifecycle: Lifecycle,
+        timeout: std::time::Duration,
+    ) -> anyhow::Result<()> {
+        let sns = self;
+        crate::retry_with_msg_async!(
+            format!("waiting for the SNS swap lifecycle to be {lifecycle:?}"),
+            &self.log,
+            timeout,
+            SWAP_LIFECYCLE_POLL_INTERVAL,
+            move || async move {
+                let actual = sns.swap().get_lifecycle().await?;
+                if actual != lifecycle {
+                    bail!("SNS swap lifecycle is {actual:?}, expected {lifecycle:?}");
+                }
+                Ok(())
+            }
+        )
+        .await
+    }
+}
+
+/// Typed calls to the governance canister of an [Sns].
+pub struct SnsGovernance<'a> {
+    sns: &'a Sns,
+}
+
+impl SnsGovernance<'_> {
+    pub fn canister_id(&self) -> CanisterId {
+        self.sns.client.sns_canisters.governance()
+    }
+
+    pub async fn get_mode(&self) -> anyhow::Result<Mode> {
+        let request = self.sns.requests.get_sns_governance_mode();
+        let response = self.sns.agent.call_and_parse(&request).await.result()?;
+        let mode = response.mode.context("governance returned no mode")?;
+        Ok(Mode::try_from(mode)?)
+    }
+
+    pub async fn get_metadata(&self) -> anyhow::Result<GetMetadataResponse> {
+        let request = self.sns.requests.get_metadata(CallMode::Query);
+        self.sns.agent.call_and_parse(&request).await.result()
+    }
+
+    pub async fn list_neurons(
+        &self,
+        limit: u32,
+        of_principal: Option<PrincipalId>,
+    ) -> anyhow::Result<ListSnsNeuronsResponse> {
+        let request = self
+            .sns
+            .requests
+            .list_neurons(limit, None, of_principal, CallMode::Query);
+        self.sns.agent.call_and_parse(&request).await.result()
+    }
+}
+
+/// Typed calls to the ledger canister of an [Sns].
+pub struct SnsLedger<'a> {
+    sns: &'a Sns,
+}
+
+impl SnsLedger<'_> {
+    pub fn canister_id(&self) -> CanisterId {
+        self.sns.client.sns_canisters.ledger()
+    }
+
+    pub async fn icrc1_balance_of(
+        &self,
+        account: Account,
+    ) -> anyhow::Result<Icrc1BalanceOfResponse> {
+        let request = self.sns.requests.icrc1_balance_of(account, CallMode::Query);
+        self.sns.agent.call_and_parse(&request).await.result()
+    }
+
+    pub async fn icrc1_metadata(&self) -> anyhow::Result<Icrc1MetadataResponse> {
+        let request = self.sns.requests.icrc1_metadata(CallMode::Query);
+        self.sns.agent.call_and_parse(&request).await.result()
+    }
+}
+
+/// Typed calls to the swap canister of an [Sns].
+pub struct SnsSwap<'a> {
+    sns: &'a Sns,
+}
+
+impl SnsSwap<'_> {
+    pub fn canister_id(&self) -> CanisterId {
+        self.sns.client.sns_canisters.swap()
+    }
+
+    pub async fn get_state(&self) -> anyhow::Result<GetStateResponse> {
+        let request = self.sns.requests.get_state(CallMode::Query);
+        self.sns.agent.call_and_parse(&request).await.result()
+    }
+
+    pub async fn get_lifecycle(&self) -> anyhow::Result<Lifecycle> {
+        let request = self.sns.requests.get_lifecycle(CallMode::Query);
+        let response = self.sns.agent.call_and_parse(&request).await.result()?;
+        let lifecycle = response.lifecycle.context("swap returned no lifecycle")?;
+        Ok(Lifecycle::try_from(lifecycle)?)
+    }
+
+    pub async fn get_derived_state(&self) -> anyhow::Result<GetDerivedStateResponse> {
+        let request = self.sns.requests.get_derived_swap_state(CallMode::Query);
+        self.sns.agent.call_and_parse(&request).await.result()
     }
 }
 
@@ -2,6 +2,7 @@ use crate::{
     canister_agent::CanisterAgent,
     canister_api::GenericRequest,
     driver::{
+        capabilities::Capability,
         group::{MAX_RUNTIME_BLOCKING_THREADS, MAX_RUNTIME_THREADS},
         test_env_api::*,
     },
@@ -1525,6 +1526,10 @@ impl MetricsFetcher {
     where
         T: Copy + Debug + std::str::FromStr,
     {
+        if let Some(node) = self.nodes.first() {
+            node.test_env()
+                .record_capability(Capability::MetricsFetched);
+        }
         // Fetch the metrics from the nodes in parallel and collect into a result
         let metrics = join_all(
             self.nodes
@@ -43,7 +43,7 @@ use ic_system_test_driver::driver::group::SystemTestGroup;
 use ic_system_test_driver::systest;
 use ic_system_test_driver::{
     driver::{
-        ic::InternetComputer,
+        setup_fragments::{AwaitHealthyNodes, FastSingleNodeSubnet, SetupFragments},
         test_env::TestEnv,
         test_env_api::{
             HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer, NnsInstallationBuilder,
@@ -61,23 +61,17 @@ use std::convert::TryFrom;
 
 fn main() -> Result<()> {
     SystemTestGroup::new()
-        .with_setup(setup)
+        .with_setup_fragments(setup())
         .add_test(systest!(test))
         .execute_from_args()?;
     Ok(())
 }
 
-pub fn setup(env: TestEnv) {
-    InternetComputer::new()
-        .add_fast_single_node_subnet(SubnetType::System)
-        .add_fast_single_node_subnet(SubnetType::Application)
-        .setup_and_start(&env)
-        .expect("failed to setup IC under test");
-    env.topology_snapshot().subnets().for_each(|subnet| {
-        subnet
-            .nodes()
-            .for_each(|node| node.await_status_is_healthy().unwrap())
-    });
+pub fn setup() -> SetupFragments {
+    SetupFragments::new()
+        .with(FastSingleNodeSubnet(SubnetType::System))
+        .with(FastSingleNodeSubnet(SubnetType::Application))
+        .with(AwaitHealthyNodes)
 }
 
 pub fn test(env: TestEnv) {
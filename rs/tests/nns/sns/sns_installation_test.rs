@@ -0,0 +1,73 @@
+use anyhow::Result;
+use ic_registry_subnet_type::SubnetType;
+use ic_sns_governance::pb::v1::governance::Mode;
+use ic_sns_swap::pb::v1::Lifecycle;
+use ic_system_test_driver::driver::group::SystemTestGroup;
+use ic_system_test_driver::driver::ic::InternetComputer;
+use ic_system_test_driver::driver::test_env::TestEnv;
+use ic_system_test_driver::driver::test_env_api::HasTopologySnapshot;
+use ic_system_test_driver::sns_client::{
+    test_create_service_nervous_system_proposal, Sns, SnsInstallationBuilder,
+};
+use ic_system_test_driver::systest;
+use ic_system_test_driver::util::block_on;
+use slog::info;
+use sns_system_test_lib::sns_deployment::install_nns;
+use std::time::Duration;
+
+const SWAP_OPEN_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+fn setup(env: TestEnv) {
+    InternetComputer::new()
+        .add_fast_single_node_subnet(SubnetType::System)
+        .add_fast_single_node_subnet(SubnetType::Application)
+        .setup_and_start(&env)
+        .expect("failed to setup IC under test");
+    install_nns(&env, vec![], vec![]);
+}
+
+/// Deploys a minimal SNS onto the application subnet with the typed
+/// installation builder and checks that the swap, which opens immediately
+/// in the test configuration, is observable through the typed handles.
+fn test(env: TestEnv) {
+    let log = env.logger();
+    let subnet_id = env
+        .topology_snapshot()
+        .subnets()
+        .find(|subnet| subnet.subnet_type() == SubnetType::Application)
+        .expect("no application subnet")
+        .subnet_id;
+
+    let sns_client = SnsInstallationBuilder::new()
+        .with_create_service_nervous_system_proposal(test_create_service_nervous_system_proposal(1))
+        .on_subnet(subnet_id)
+        .install(&env);
+    assert_eq!(sns_client.subnet_id, Some(subnet_id));
+
+    // The deployed canister ids are recorded in the environment.
+    let sns = block_on(Sns::from_env(&env));
+    assert_eq!(sns.client().sns_canisters, sns_client.sns_canisters);
+
+    block_on(sns.await_swap_lifecycle(Lifecycle::Open, SWAP_OPEN_TIMEOUT))
+        .expect("SNS swap did not open");
+    info!(log, "SNS swap is open");
+
+    let swap_state = block_on(sns.swap().get_state()).unwrap();
+    assert_eq!(
+        swap_state.swap.expect("no swap").lifecycle(),
+        Lifecycle::Open
+    );
+    assert_eq!(
+        block_on(sns.governance().get_mode()).unwrap(),
+        Mode::PreInitializationSwap
+    );
+    assert!(!block_on(sns.ledger().icrc1_metadata()).unwrap().is_empty());
+}
+
+fn main() -> Result<()> {
+    SystemTestGroup::new()
+        .with_setup(setup)
+        .add_test(systest!(test))
+        .execute_from_args()?;
+    Ok(())
+}
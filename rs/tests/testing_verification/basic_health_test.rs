@@ -34,8 +34,8 @@ use anyhow::bail;
 use anyhow::Result;
 use ic_registry_subnet_type::SubnetType;
 use ic_system_test_driver::driver::group::SystemTestGroup;
-use ic_system_test_driver::driver::ic::{InternetComputer, Subnet};
-use ic_system_test_driver::driver::prometheus_vm::{HasPrometheus, PrometheusVm};
+use ic_system_test_driver::driver::ic::Subnet;
+use ic_system_test_driver::driver::setup_fragments::{CustomSubnets, Prometheus, SetupFragments};
 use ic_system_test_driver::driver::test_env::TestEnv;
 use ic_system_test_driver::driver::test_env_api::*;
 use ic_system_test_driver::systest;
@@ -45,31 +45,26 @@ use std::time::Duration;
 
 fn main() -> Result<()> {
     SystemTestGroup::new()
-        .with_setup(setup)
+        .with_setup_fragments(setup())
         .add_test(systest!(test))
         .execute_from_args()?;
 
     Ok(())
 }
 
-pub fn setup(env: TestEnv) {
-    PrometheusVm::default()
-        .start(&env)
-        .expect("failed to start prometheus VM");
-    InternetComputer::new()
-        .add_subnet(
+pub fn setup() -> SetupFragments {
+    SetupFragments::new()
+        .with(Prometheus)
+        .with(CustomSubnets::single(
             Subnet::new(SubnetType::System)
                 .with_random_height()
                 .add_nodes(4),
-        )
-        .add_subnet(
+        ))
+        .with(CustomSubnets::single(
             Subnet::new(SubnetType::Application)
                 .with_random_height()
                 .add_nodes(4),
-        )
-        .setup_and_start(&env)
-        .expect("failed to setup IC under test");
-    env.sync_with_prometheus();
+        ))
 }
 
 const MSG: &[u8] = b"this beautiful prose should be persisted for future generations";
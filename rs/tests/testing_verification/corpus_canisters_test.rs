@@ -0,0 +1,101 @@
+/* tag::catalog[]
+Title:: Built-in canister corpus test
+
+Goal:: Ensure the canisters of the test driver's built-in corpus can be
+installed and that their typed client wrappers work.
+
+Runbook::
+. Set up an application subnet with a single node
+. Install every corpus canister on it
+. Increment the counter, upgrade it and verify the value survives the upgrade
+. Echo payloads via query and update calls and check the caller's principal
+. Grow stable memory via the stable writer and verify the page markers
+
+Success:: All calls return the expected values.
+
+end::catalog[] */
+
+use anyhow::Result;
+use ic_registry_subnet_type::SubnetType;
+use ic_system_test_driver::driver::canisters::{
+    Corpus, CounterCanister, EchoCanister, InstallCorpusCanister, StableWriterCanister,
+};
+use ic_system_test_driver::driver::group::SystemTestGroup;
+use ic_system_test_driver::driver::ic::{InternetComputer, Subnet};
+use ic_system_test_driver::driver::test_env::TestEnv;
+use ic_system_test_driver::driver::test_env_api::*;
+use ic_system_test_driver::systest;
+use ic_system_test_driver::util::block_on;
+use slog::info;
+
+fn main() -> Result<()> {
+    SystemTestGroup::new()
+        .with_setup(setup)
+        .add_test(systest!(test))
+        .execute_from_args()?;
+
+    Ok(())
+}
+
+pub fn setup(env: TestEnv) {
+    InternetComputer::new()
+        .add_subnet(Subnet::new(SubnetType::Application).add_nodes(1))
+        .setup_and_start(&env)
+        .expect("failed to setup IC under test");
+    env.topology_snapshot().subnets().for_each(|subnet| {
+        subnet
+            .await_all_nodes_healthy()
+            .expect("failed to wait for nodes to become healthy")
+    });
+}
+
+pub fn test(env: TestEnv) {
+    let logger = env.logger();
+    let subnet = env
+        .topology_snapshot()
+        .subnets()
+        .find(|s| s.subnet_type() == SubnetType::Application)
+        .unwrap();
+    let node = subnet.nodes().next().unwrap();
+    let agent = node.build_default_agent();
+
+    let counter = CounterCanister::new(
+        agent.clone(),
+        env.install_corpus_canister(Corpus::Counter, &subnet),
+    );
+    let echo = EchoCanister::new(
+        agent.clone(),
+        env.install_corpus_canister(Corpus::Echo, &subnet),
+    );
+    let stable_writer = StableWriterCanister::new(
+        agent.clone(),
+        env.install_corpus_canister(Corpus::StableWriter, &subnet),
+    );
+
+    block_on(async {
+        info!(logger, "Exercising the counter canister ...");
+        assert_eq!(counter.read().await.unwrap(), 0);
+        assert_eq!(counter.inc().await.unwrap(), 1);
+        assert_eq!(counter.inc().await.unwrap(), 2);
+        counter.upgrade().await.unwrap();
+        assert_eq!(counter.read().await.unwrap(), 2);
+
+        info!(logger, "Exercising the echo canister ...");
+        let payload = b"Hello, corpus!".to_vec();
+        assert_eq!(echo.echo(payload.clone()).await.unwrap(), payload);
+        assert_eq!(echo.echo_update(payload.clone()).await.unwrap(), payload);
+        assert_eq!(echo.echo(vec![]).await.unwrap(), Vec::<u8>::new());
+        assert_eq!(echo.whoami().await.unwrap(), agent.get_principal().unwrap());
+
+        info!(logger, "Exercising the stable writer canister ...");
+        assert_eq!(stable_writer.size().await.unwrap(), 0);
+        assert_eq!(stable_writer.grow_and_write(3).await.unwrap(), 3);
+        assert_eq!(stable_writer.grow_and_write(2).await.unwrap(), 5);
+        for page in 0..5 {
+            assert_eq!(
+                stable_writer.read_page_marker(page).await.unwrap(),
+                StableWriterCanister::PAGE_MARKER
+            );
+        }
+    });
+}
@@ -0,0 +1,74 @@
+/* tag::catalog[]
+Title:: Counter canister matrix test
+
+Goal:: Ensure that the test driver's matrix support runs the same test body
+against multiple IC configurations, each with its own environment.
+
+Runbook::
+. Set up, in parallel, an IC with a single system subnet and an IC with a
+  single application subnet
+. Against each IC: install the counter corpus canister, increment it a few
+  times and read back the value
+
+Success:: Both `counter_smoke[system]` and `counter_smoke[application]` pass.
+
+end::catalog[] */
+
+use anyhow::Result;
+use ic_registry_subnet_type::SubnetType;
+use ic_system_test_driver::driver::canisters::{Corpus, CounterCanister, InstallCorpusCanister};
+use ic_system_test_driver::driver::group::SystemTestGroup;
+use ic_system_test_driver::driver::ic::{InternetComputer, Subnet};
+use ic_system_test_driver::driver::pot_dsl::{matrix, ConfigFn};
+use ic_system_test_driver::driver::test_env::TestEnv;
+use ic_system_test_driver::driver::test_env_api::*;
+use ic_system_test_driver::util::block_on;
+use slog::info;
+
+fn main() -> Result<()> {
+    SystemTestGroup::new()
+        .add_matrix(matrix(
+            "counter_smoke",
+            vec![
+                ("system", setup(SubnetType::System)),
+                ("application", setup(SubnetType::Application)),
+            ],
+            test,
+        ))
+        .execute_from_args()?;
+
+    Ok(())
+}
+
+fn setup(subnet_type: SubnetType) -> ConfigFn {
+    Box::new(move |env: TestEnv| {
+        InternetComputer::new()
+            .add_subnet(Subnet::new(subnet_type).add_nodes(1))
+            .setup_and_start(&env)
+            .expect("failed to setup IC under test");
+        env.topology_snapshot().subnets().for_each(|subnet| {
+            subnet
+                .await_all_nodes_healthy()
+                .expect("failed to wait for nodes to become healthy")
+        });
+    })
+}
+
+pub fn test(env: TestEnv) {
+    let logger = env.logger();
+    let subnet = env.topology_snapshot().subnets().next().unwrap();
+    info!(logger, "Testing on a {:?} subnet", subnet.subnet_type());
+    let node = subnet.nodes().next().unwrap();
+    let counter = CounterCanister::new(
+        node.build_default_agent(),
+        env.install_corpus_canister(Corpus::Counter, &subnet),
+    );
+
+    block_on(async {
+        assert_eq!(counter.read().await.unwrap(), 0);
+        for expected in 1..=3 {
+            assert_eq!(counter.inc().await.unwrap(), expected);
+        }
+        assert_eq!(counter.read().await.unwrap(), 3);
+    });
+}
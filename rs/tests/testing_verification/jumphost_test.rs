@@ -0,0 +1,72 @@
+/* tag::catalog[]
+Title:: Jumphost
+
+Goal:: Ensure that a jumphost can be attached to a running IC and that the
+tooling installed on it can reach the IC.
+
+Runbook::
+. Set up an IC with an NNS subnet and deploy a jumphost.
+. On the jumphost, run ic-admin against the first NNS URL of the context file.
+
+Success:: ic-admin on the jumphost fetches the registry version.
+
+end::catalog[] */
+
+use anyhow::{bail, Result};
+use ic_registry_subnet_type::SubnetType;
+use ic_system_test_driver::driver::group::SystemTestGroup;
+use ic_system_test_driver::driver::ic::InternetComputer;
+use ic_system_test_driver::driver::jumphost::{HasJumphost, JUMPHOST_VM_NAME};
+use ic_system_test_driver::driver::test_env::TestEnv;
+use ic_system_test_driver::driver::test_env_api::{
+    HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer, SshSession, READY_WAIT_TIMEOUT,
+    RETRY_BACKOFF,
+};
+use ic_system_test_driver::driver::universal_vm::UniversalVms;
+use ic_system_test_driver::retry_with_msg;
+use ic_system_test_driver::systest;
+use slog::info;
+
+fn main() -> Result<()> {
+    SystemTestGroup::new()
+        .with_setup(setup)
+        .add_test(systest!(test))
+        .execute_from_args()?;
+    Ok(())
+}
+
+pub fn setup(env: TestEnv) {
+    InternetComputer::new()
+        .add_fast_single_node_subnet(SubnetType::System)
+        .setup_and_start(&env)
+        .expect("failed to setup IC under test");
+    env.topology_snapshot().subnets().for_each(|subnet| {
+        subnet
+            .nodes()
+            .for_each(|node| node.await_status_is_healthy().unwrap())
+    });
+    env.deploy_jumphost().expect("failed to deploy jumphost");
+}
+
+pub fn test(env: TestEnv) {
+    let logger = env.logger();
+    let jumphost = env.get_deployed_universal_vm(JUMPHOST_VM_NAME).unwrap();
+    retry_with_msg!(
+        "Waiting for ic-admin on the jumphost to fetch the registry version",
+        logger.clone(),
+        READY_WAIT_TIMEOUT,
+        RETRY_BACKOFF,
+        || {
+            let output = jumphost.block_on_bash_script(
+                r#"NNS_URL=$(grep -A1 'nns-urls:' ~/.ic/config.yaml | tail -n1 | sed 's/^ *- *//')
+~/bin/ic-admin --nns-url "$NNS_URL" get-registry-version"#,
+            )?;
+            info!(logger, "Registry version: {}", output.trim());
+            if output.trim().parse::<u64>().is_err() {
+                bail!("Unexpected ic-admin output: {output}");
+            }
+            Ok(())
+        }
+    )
+    .expect("ic-admin on the jumphost failed to fetch the registry version");
+}
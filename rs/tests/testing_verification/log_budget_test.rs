@@ -0,0 +1,69 @@
+/* tag::catalog[]
+Title:: Log budget test
+
+Goal:: Ensure that a healthy subnet stays within a zero-critical log budget and
+that the test driver's log budget check runs at the end of the group.
+
+Runbook::
+. Set up an application subnet with four nodes
+. Install the counter corpus canister and increment it a few times
+. After the test, the driver reads the journal of every node and checks that
+  no node logged a CRITICAL entry
+
+Success:: The test and the driver's `log_budget` task both pass.
+
+end::catalog[] */
+
+use anyhow::Result;
+use ic_registry_subnet_type::SubnetType;
+use ic_system_test_driver::driver::canisters::{Corpus, CounterCanister, InstallCorpusCanister};
+use ic_system_test_driver::driver::group::SystemTestGroup;
+use ic_system_test_driver::driver::ic::{InternetComputer, Subnet};
+use ic_system_test_driver::driver::log_budget::Severity;
+use ic_system_test_driver::driver::test_env::TestEnv;
+use ic_system_test_driver::driver::test_env_api::*;
+use ic_system_test_driver::systest;
+use ic_system_test_driver::util::block_on;
+use slog::info;
+
+fn main() -> Result<()> {
+    SystemTestGroup::new()
+        .with_setup(setup)
+        .add_test(systest!(test))
+        .with_log_budget(Severity::Critical, 0)
+        .execute_from_args()?;
+
+    Ok(())
+}
+
+pub fn setup(env: TestEnv) {
+    InternetComputer::new()
+        .add_subnet(Subnet::new(SubnetType::Application).add_nodes(4))
+        .setup_and_start(&env)
+        .expect("failed to setup IC under test");
+    env.topology_snapshot().subnets().for_each(|subnet| {
+        subnet
+            .await_all_nodes_healthy()
+            .expect("failed to wait for nodes to become healthy")
+    });
+}
+
+pub fn test(env: TestEnv) {
+    let logger = env.logger();
+    let subnet = env.topology_snapshot().subnets().next().unwrap();
+    let node = subnet.nodes().next().unwrap();
+    let counter = CounterCanister::new(
+        node.build_default_agent(),
+        env.install_corpus_canister(Corpus::Counter, &subnet),
+    );
+
+    block_on(async {
+        for expected in 1..=5 {
+            assert_eq!(counter.inc().await.unwrap(), expected);
+        }
+    });
+    info!(
+        logger,
+        "Subnet made progress, the log budgets are checked after this test."
+    );
+}
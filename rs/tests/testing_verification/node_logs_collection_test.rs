@@ -0,0 +1,89 @@
+/* tag::catalog[]
+Title:: Collection of node logs
+
+Goal:: Ensure that the journald logs of nodes are collected over SSH into the
+environment of a test, and that unreachable nodes do not fail the collection.
+
+Runbook::
+. Set up two universal VMs standing in for nodes, one of which writes a marker
+  to its journal once activated.
+. Kill the other universal VM.
+. Collect the logs of both VMs.
+
+Success:: The logs of the running VM, including the marker, are written to
+`logs/<vm_name>.log`; an error marker `logs/<vm_name>.error` is written for
+the killed VM.
+
+end::catalog[] */
+
+use anyhow::Result;
+use ic_system_test_driver::driver::group::SystemTestGroup;
+use ic_system_test_driver::driver::test_env::TestEnv;
+use ic_system_test_driver::driver::test_env_api::{
+    CollectNodeLogs, HasVm, NodeLogsSummary, SshSession, NODE_LOGS_DIR,
+};
+use ic_system_test_driver::driver::universal_vm::{UniversalVm, UniversalVms};
+use ic_system_test_driver::systest;
+use slog::info;
+use std::fs;
+use std::time::Duration;
+
+const REACHABLE_VM_NAME: &str = "reachable";
+const UNREACHABLE_VM_NAME: &str = "unreachable";
+const MARKER: &str = "node-logs-collection-marker";
+const WINDOW: Duration = Duration::from_secs(60 * 60);
+
+fn main() -> Result<()> {
+    SystemTestGroup::new()
+        .with_setup(setup)
+        .add_test(systest!(test))
+        .execute_from_args()?;
+    Ok(())
+}
+
+pub fn setup(env: TestEnv) {
+    let config_dir = env
+        .single_activate_script_config_dir(
+            REACHABLE_VM_NAME,
+            &format!("#!/bin/sh\nlogger {MARKER}\n"),
+        )
+        .unwrap();
+    UniversalVm::new(String::from(REACHABLE_VM_NAME))
+        .with_config_dir(config_dir)
+        .start(&env)
+        .expect("failed to setup universal VM");
+    UniversalVm::new(String::from(UNREACHABLE_VM_NAME))
+        .start(&env)
+        .expect("failed to setup universal VM");
+}
+
+pub fn test(env: TestEnv) {
+    let logger = env.logger();
+    let reachable = env.get_deployed_universal_vm(REACHABLE_VM_NAME).unwrap();
+    let unreachable = env.get_deployed_universal_vm(UNREACHABLE_VM_NAME).unwrap();
+    reachable
+        .block_on_bash_script(&format!("journalctl | grep -q {MARKER}"))
+        .expect("The activate script did not write the marker");
+
+    info!(logger, "Killing {UNREACHABLE_VM_NAME}");
+    unreachable.vm().kill();
+
+    let summary = env
+        .collect_vm_logs(&[&reachable, &unreachable], WINDOW)
+        .expect("Failed to collect logs");
+    assert_eq!(
+        NodeLogsSummary {
+            collected: vec![REACHABLE_VM_NAME.to_string()],
+            unreachable: vec![UNREACHABLE_VM_NAME.to_string()],
+        },
+        summary
+    );
+
+    let logs_dir = env.get_path(NODE_LOGS_DIR);
+    let logs = fs::read_to_string(logs_dir.join(format!("{REACHABLE_VM_NAME}.log"))).unwrap();
+    assert!(logs.contains(MARKER), "Marker not found in collected logs");
+    assert!(logs_dir
+        .join(format!("{UNREACHABLE_VM_NAME}.error"))
+        .exists());
+    assert!(!logs_dir.join(format!("{UNREACHABLE_VM_NAME}.log")).exists());
+}
@@ -0,0 +1,89 @@
+/* tag::catalog[]
+Title:: Prometheus custom scrape configs
+
+Goal:: Ensure that custom scraping jobs, both configured when setting up the
+Prometheus VM and added while it is running, are picked up by Prometheus.
+
+Runbook::
+. Set up a Prometheus VM with a custom job scraping Prometheus itself.
+. Add a second custom job on the running VM.
+. Query the targets via the Prometheus HTTP API on the VM.
+
+Success:: Both custom jobs are listed among Prometheus' active targets.
+
+end::catalog[] */
+
+use anyhow::{bail, Result};
+use ic_system_test_driver::driver::group::SystemTestGroup;
+use ic_system_test_driver::driver::prometheus_vm::{HasPrometheus, PrometheusVm, ScrapeConfig};
+use ic_system_test_driver::driver::test_env::TestEnv;
+use ic_system_test_driver::driver::test_env_api::{SshSession, READY_WAIT_TIMEOUT, RETRY_BACKOFF};
+use ic_system_test_driver::driver::universal_vm::UniversalVms;
+use ic_system_test_driver::retry_with_msg;
+use ic_system_test_driver::systest;
+use slog::info;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+const PROMETHEUS_VM_NAME: &str = "prometheus";
+const PROMETHEUS_TARGET: &str = "localhost:9090";
+const STARTUP_JOB: &str = "startup_job";
+const RUNTIME_JOB: &str = "runtime_job";
+
+fn main() -> Result<()> {
+    SystemTestGroup::new()
+        .with_setup(setup)
+        .add_test(systest!(test))
+        .execute_from_args()?;
+    Ok(())
+}
+
+pub fn setup(env: TestEnv) {
+    PrometheusVm::default()
+        .with_scrape_config(
+            STARTUP_JOB,
+            vec![PROMETHEUS_TARGET.to_string()],
+            Duration::from_secs(5),
+        )
+        .start(&env)
+        .expect("failed to start prometheus VM");
+}
+
+pub fn test(env: TestEnv) {
+    let logger = env.logger();
+    env.add_prometheus_scrape_config(ScrapeConfig::new(
+        RUNTIME_JOB,
+        vec![PROMETHEUS_TARGET.to_string()],
+        Duration::from_secs(15),
+    ))
+    .expect("failed to add scrape config");
+
+    let prometheus_vm = env.get_deployed_universal_vm(PROMETHEUS_VM_NAME).unwrap();
+    retry_with_msg!(
+        "Waiting for both custom jobs to show up in the Prometheus targets",
+        logger.clone(),
+        READY_WAIT_TIMEOUT,
+        RETRY_BACKOFF,
+        || {
+            let output = prometheus_vm.block_on_bash_script(
+                "curl --silent --fail http://localhost:9090/api/v1/targets",
+            )?;
+            let targets: serde_json::Value = serde_json::from_str(&output)?;
+            let jobs: BTreeSet<_> = targets["data"]["activeTargets"]
+                .as_array()
+                .map(|targets| {
+                    targets
+                        .iter()
+                        .filter_map(|target| target["labels"]["job"].as_str())
+                        .collect()
+                })
+                .unwrap_or_default();
+            info!(logger, "Jobs with active targets: {:?}", jobs);
+            if !jobs.contains(STARTUP_JOB) || !jobs.contains(RUNTIME_JOB) {
+                bail!("Custom jobs missing from the active targets: {:?}", jobs);
+            }
+            Ok(())
+        }
+    )
+    .expect("Custom jobs not found in the Prometheus targets");
+}
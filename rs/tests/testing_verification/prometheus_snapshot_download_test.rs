@@ -0,0 +1,94 @@
+/* tag::catalog[]
+Title:: Prometheus TSDB snapshot download
+
+Goal:: Ensure that a snapshot of the TSDB of a running Prometheus VM can be
+downloaded to the test environment.
+
+Runbook::
+. Set up a Prometheus VM with a custom job scraping Prometheus itself.
+. Wait until Prometheus has scraped some samples.
+. Download a snapshot of the TSDB.
+. Unpack the tarball locally.
+
+Success:: The tarball exists and contains the `meta.json` of at least one block.
+
+end::catalog[] */
+
+use anyhow::{bail, Result};
+use ic_system_test_driver::driver::group::SystemTestGroup;
+use ic_system_test_driver::driver::prometheus_vm::PrometheusVm;
+use ic_system_test_driver::driver::test_env::TestEnv;
+use ic_system_test_driver::driver::test_env_api::{SshSession, READY_WAIT_TIMEOUT, RETRY_BACKOFF};
+use ic_system_test_driver::driver::universal_vm::UniversalVms;
+use ic_system_test_driver::retry_with_msg;
+use ic_system_test_driver::systest;
+use slog::info;
+use std::fs::File;
+use std::time::Duration;
+
+const PROMETHEUS_VM_NAME: &str = "prometheus";
+const PROMETHEUS_TARGET: &str = "localhost:9090";
+const SELF_JOB: &str = "self";
+
+fn main() -> Result<()> {
+    SystemTestGroup::new()
+        .with_setup(setup)
+        .add_test(systest!(test))
+        .execute_from_args()?;
+    Ok(())
+}
+
+pub fn setup(env: TestEnv) {
+    PrometheusVm::default()
+        .with_scrape_config(
+            SELF_JOB,
+            vec![PROMETHEUS_TARGET.to_string()],
+            Duration::from_secs(5),
+        )
+        .start(&env)
+        .expect("failed to start prometheus VM");
+}
+
+pub fn test(env: TestEnv) {
+    let logger = env.logger();
+    let prometheus_vm = env.get_deployed_universal_vm(PROMETHEUS_VM_NAME).unwrap();
+    retry_with_msg!(
+        "Waiting for Prometheus to scrape itself",
+        logger.clone(),
+        READY_WAIT_TIMEOUT,
+        RETRY_BACKOFF,
+        || {
+            let output = prometheus_vm.block_on_bash_script(&format!(
+                "curl --silent --fail 'http://localhost:9090/api/v1/query?query=up{{job=\"{SELF_JOB}\"}}'"
+            ))?;
+            let response: serde_json::Value = serde_json::from_str(&output)?;
+            if response["data"]["result"]
+                .as_array()
+                .map_or(true, |result| result.is_empty())
+            {
+                bail!("No samples scraped yet: {output}");
+            }
+            Ok(())
+        }
+    )
+    .expect("Prometheus did not scrape itself");
+
+    let tarball = PrometheusVm::download_prometheus_data_dir(&env)
+        .expect("failed to download the TSDB snapshot");
+    assert!(tarball.is_file(), "{tarball:?} does not exist");
+
+    let mut archive = tar::Archive::new(
+        zstd::Decoder::new(File::open(&tarball).unwrap()).expect("not a zstd file"),
+    );
+    let block_metas: Vec<_> = archive
+        .entries()
+        .expect("not a tarball")
+        .map(|entry| entry.unwrap().path().unwrap().into_owned())
+        .filter(|path| path.file_name().is_some_and(|name| name == "meta.json"))
+        .collect();
+    info!(logger, "Blocks in {tarball:?}: {block_metas:?}");
+    assert!(
+        !block_metas.is_empty(),
+        "{tarball:?} does not contain the meta.json of any block"
+    );
+}
@@ -4,6 +4,7 @@ use ic_system_test_driver::driver::test_env::TestEnv;
 use ic_system_test_driver::systest;
 use slog::info;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::process;
 use std::time::Duration;
 
@@ -66,6 +67,20 @@ fn get_all_e2e_test_scenarios() -> HashMap<String, SystemTestGroup> {
                 .with_timeout_per_test(Duration::from_secs(10))
                 .without_farm(),
         ),
+        (
+            "test_with_per_test_timeout".to_string(),
+            SystemTestGroup::new()
+                .with_setup(setup_to_succeed)
+                .add_parallel(
+                    SystemTestSubGroup::new()
+                        .add_test(
+                            systest!(test_to_succeed_7sec).with_timeout(Duration::from_secs(3)),
+                        )
+                        .add_test(systest!(test_to_succeed_1sec)),
+                )
+                .with_timeout_per_test(Duration::from_secs(60))
+                .without_farm(),
+        ),
         (
             "test_duplicate_tasks".to_string(),
             SystemTestGroup::new()
@@ -151,6 +166,18 @@ fn get_all_e2e_test_scenarios() -> HashMap<String, SystemTestGroup> {
                 )
                 .without_farm(),
         ),
+        (
+            "test_with_parallelism".to_string(),
+            SystemTestGroup::new()
+                .with_setup(setup_to_succeed)
+                .with_parallelism(2)
+                .add_test(systest!(parallel_test_a))
+                .add_test(systest!(parallel_test_b))
+                .add_test(systest!(parallel_test_c))
+                .add_test(systest!(parallel_test_d))
+                .add_test(systest!(serial_test).serial())
+                .without_farm(),
+        ),
         (
             "test_group_timeout_in_test_task".to_string(),
             SystemTestGroup::new()
@@ -225,6 +252,59 @@ fn test_to_fail(_: TestEnv) {
     panic!("this test panics");
 }
 
+/// The directory, shared by all tests of the group, in which each test of the
+/// `test_with_parallelism` scenario holds a marker file while it runs.
+fn running_tests_dir(env: &TestEnv) -> PathBuf {
+    env.base_path()
+        .parent()
+        .expect("test env dir should have a parent dir")
+        .join("running_tests")
+}
+
+/// Runs for 5 seconds while checking that at most two tests run at the same
+/// time and that no other test writes into its environment.
+fn parallel_test(env: TestEnv, name: &str) {
+    let running_tests_dir = running_tests_dir(&env);
+    std::fs::create_dir_all(&running_tests_dir).unwrap();
+    std::fs::write(running_tests_dir.join(name), "").unwrap();
+    let running_tests = std::fs::read_dir(&running_tests_dir).unwrap().count();
+    assert!(
+        running_tests <= 2,
+        "{running_tests} tests are running at the same time"
+    );
+
+    let written_by = env.base_path().join("written_by");
+    std::fs::write(&written_by, name).unwrap();
+    std::thread::sleep(Duration::from_secs(5));
+    assert_eq!(std::fs::read_to_string(&written_by).unwrap(), name);
+
+    std::fs::remove_file(running_tests_dir.join(name)).unwrap();
+}
+
+fn parallel_test_a(env: TestEnv) {
+    parallel_test(env, "parallel_test_a");
+}
+
+fn parallel_test_b(env: TestEnv) {
+    parallel_test(env, "parallel_test_b");
+}
+
+fn parallel_test_c(env: TestEnv) {
+    parallel_test(env, "parallel_test_c");
+}
+
+fn parallel_test_d(env: TestEnv) {
+    parallel_test(env, "parallel_test_d");
+}
+
+fn serial_test(env: TestEnv) {
+    let running_tests_dir = running_tests_dir(&env);
+    let running_tests = std::fs::read_dir(&running_tests_dir)
+        .map(|entries| entries.count())
+        .unwrap_or(0);
+    assert_eq!(running_tests, 0, "the serial test does not run alone");
+}
+
 fn never_ending_task(env: TestEnv) {
     info!(
         env.logger(),
@@ -7,6 +7,7 @@ use std::{
     env,
     path::PathBuf,
     process::{Command, Output},
+    time::{Duration, Instant},
 };
 use tempfile::Builder;
 
@@ -46,6 +47,14 @@ fn assert_test_summary_size(
     );
 }
 
+fn assert_timeouts_size(summary: &SystemGroupSummary, expected_timeouts: usize) {
+    assert_eq!(
+        summary.timeout.len(),
+        expected_timeouts,
+        "Number of timed out tests in the report doesn't match expected."
+    );
+}
+
 // It is important that each #[test] creates a unique tmp directory.
 fn create_unique_working_dir() -> PathBuf {
     let prefix_path = PathBuf::from(env::var("TEST_TMPDIR").unwrap());
@@ -202,11 +211,12 @@ fn test_scenario_with_skipped_panic_test_succeeds() {
         String::from_utf8_lossy(&result.stderr)
     );
     let summary = extract_report(result.stderr).expect("Failed to extract report from logs.");
+    // As no test is selected, not even the setup is executed.
     assert_test_summary_size(
-        &summary, /* successes */ 1, /* failures */ 0, /* skipped */ 1,
+        &summary, /* successes */ 0, /* failures */ 0, /* skipped */ 2,
     );
-    assert_name_and_message_eq(&summary.success[0], "setup", SUCCESS);
-    assert_name_and_message_eq(&summary.skipped[0], "test_to_fail", SKIP);
+    assert_name_and_message_eq(&summary.skipped[0], "setup", SKIP);
+    assert_name_and_message_eq(&summary.skipped[1], "test_to_fail", SKIP);
 }
 
 #[test]
@@ -273,6 +283,74 @@ fn test_scenario_with_two_skipped_panic_tests_succeeds() {
     assert_name_and_message_eq(&summary.skipped[1], "test_to_fail_2", SKIP);
 }
 
+#[test]
+fn test_scenario_with_include_and_skip_patterns_succeeds() {
+    let working_dir = create_unique_working_dir();
+    let scenario_name = "test_with_two_panics";
+    let binary_path = env::current_dir().unwrap().join(BINARY_PATH);
+    let mut cmd = Command::new(binary_path);
+    cmd.env("TEST_SCENARIO_NAME", scenario_name).args([
+        "--working-dir",
+        working_dir.to_str().unwrap(),
+        "--group-base-name",
+        "test-driver-e2e-scenarios",
+        "--include-pattern",
+        "^test-driver-e2e-scenarios::test_to_",
+        "--skip-pattern",
+        "_fail",
+        "run",
+    ]);
+    let result = cmd.output().expect("failed to execute process");
+    assert!(
+        result.status.success(),
+        "{}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+    let summary = extract_report(result.stderr).expect("Failed to extract report from logs.");
+    assert_test_summary_size(
+        &summary, /* successes */ 2, /* failures */ 0, /* skipped */ 2,
+    );
+    assert_name_and_message_eq(&summary.success[0], "setup", SUCCESS);
+    assert_name_and_message_eq(&summary.success[1], "test_to_succeed", SUCCESS);
+    assert_name_and_message_eq(&summary.skipped[0], "test_to_fail", SKIP);
+    assert_name_and_message_eq(&summary.skipped[1], "test_to_fail_2", SKIP);
+}
+
+#[test]
+fn test_scenario_list_prints_selected_tests() {
+    let working_dir = create_unique_working_dir();
+    let scenario_name = "test_with_two_panics";
+    let binary_path = env::current_dir().unwrap().join(BINARY_PATH);
+    let mut cmd = Command::new(binary_path);
+    cmd.env("TEST_SCENARIO_NAME", scenario_name).args([
+        "--working-dir",
+        working_dir.to_str().unwrap(),
+        "--group-base-name",
+        "test-driver-e2e-scenarios",
+        "--include-pattern",
+        "fail",
+        "--list",
+        "run",
+    ]);
+    let result = cmd.output().expect("failed to execute process");
+    assert!(
+        result.status.success(),
+        "{}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&result.stdout)
+            .lines()
+            .collect::<Vec<_>>(),
+        vec![
+            "test-driver-e2e-scenarios::test_to_fail",
+            "test-driver-e2e-scenarios::test_to_fail_2"
+        ]
+    );
+    // Listing does not execute anything.
+    assert!(extract_report(result.stderr).is_none());
+}
+
 #[test]
 fn test_scenario_with_setup_panic_fails() {
     let result = execute_test_scenario_with_default_cmd("test_with_setup_panic");
@@ -303,16 +381,39 @@ fn test_that_runs_out_of_time() {
     );
     let summary = extract_report(result.stderr).expect("Failed to extract report from logs.");
     assert_test_summary_size(
-        &summary, /* successes */ 1, /* failures */ 1, /* skipped */ 0,
+        &summary, /* successes */ 1, /* failures */ 0, /* skipped */ 0,
     );
+    assert_timeouts_size(&summary, 1);
     assert_name_and_message_eq(&summary.success[0], "setup", SUCCESS);
     assert_name_and_message_eq(
-        &summary.failure[0],
+        &summary.timeout[0],
         "never_ending_task",
         Some("Timeout after 10s"),
     );
 }
 
+#[test]
+fn test_per_test_timeout_cancels_only_that_test() {
+    let result = execute_test_scenario_with_default_cmd("test_with_per_test_timeout");
+    assert!(
+        !result.status.success(),
+        "{}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+    let summary = extract_report(result.stderr).expect("Failed to extract report from logs.");
+    assert_test_summary_size(
+        &summary, /* successes */ 2, /* failures */ 0, /* skipped */ 0,
+    );
+    assert_timeouts_size(&summary, 1);
+    assert_name_and_message_eq(&summary.success[0], "setup", SUCCESS);
+    assert_name_and_message_eq(&summary.success[1], "test_to_succeed_1sec", SUCCESS);
+    assert_name_and_message_eq(
+        &summary.timeout[0],
+        "test_to_succeed_7sec",
+        Some("Timeout after 3s"),
+    );
+}
+
 #[test]
 fn test_duplicate_tasks() {
     let result = execute_test_scenario_with_default_cmd("test_duplicate_tasks");
@@ -429,6 +530,33 @@ fn test_that_runs_2_parallel_tasks_then_one_task_then_2_parallel_tasks() {
     assert_name_and_message_eq(&summary.success[5], "test_to_succeed_7sec", SUCCESS);
 }
 
+#[test]
+fn test_with_parallelism_runs_tests_concurrently_and_reports_them_in_order() {
+    let start = Instant::now();
+    let result = execute_test_scenario_with_default_cmd("test_with_parallelism");
+    let elapsed = start.elapsed();
+    assert!(
+        result.status.success(),
+        "{}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+    let summary = extract_report(result.stderr).expect("Failed to extract report from logs.");
+    assert_test_summary_size(
+        &summary, /* successes */ 6, /* failures */ 0, /* skipped */ 0,
+    );
+    assert_name_and_message_eq(&summary.success[0], "setup", SUCCESS);
+    assert_name_and_message_eq(&summary.success[1], "parallel_test_a", SUCCESS);
+    assert_name_and_message_eq(&summary.success[2], "parallel_test_b", SUCCESS);
+    assert_name_and_message_eq(&summary.success[3], "parallel_test_c", SUCCESS);
+    assert_name_and_message_eq(&summary.success[4], "parallel_test_d", SUCCESS);
+    assert_name_and_message_eq(&summary.success[5], "serial_test", SUCCESS);
+    // Run one after the other, the four tests of 5 seconds take 20 seconds.
+    assert!(
+        elapsed < Duration::from_secs(18),
+        "the tests took {elapsed:?}, i.e. they did not run in parallel"
+    );
+}
+
 #[test]
 fn test_that_runs_2_parallel_tasks_then_one_failing_task_then_2_parallel_tasks() {
     let result = execute_test_scenario_with_default_cmd(
@@ -468,11 +596,12 @@ fn test_overall_group_timeout_in_test() {
     );
     let summary = extract_report(result.stderr).expect("Failed to extract report from logs.");
     assert_test_summary_size(
-        &summary, /* successes */ 1, /* failures */ 1, /* skipped */ 0,
+        &summary, /* successes */ 1, /* failures */ 0, /* skipped */ 0,
     );
+    assert_timeouts_size(&summary, 1);
     assert_name_and_message_eq(&summary.success[0], "setup", SUCCESS);
     assert_name_and_message_eq(
-        &summary.failure[0],
+        &summary.timeout[0],
         "never_ending_task",
         Some("Timeout after 5s"),
     );
@@ -488,10 +617,11 @@ fn test_overall_group_timeout_in_setup() {
     );
     // panic!("{:?}", result);
     let summary = extract_report(result.stderr).expect("Failed to extract report from logs.");
-    assert_test_summary_size(&summary, 0, 2, 0);
-    assert_name_and_message_eq(&summary.failure[0], "setup", Some("Timeout after 5s"));
+    assert_test_summary_size(&summary, 0, 0, 0);
+    assert_timeouts_size(&summary, 2);
+    assert_name_and_message_eq(&summary.timeout[0], "setup", Some("Timeout after 5s"));
     assert_name_and_message_eq(
-        &summary.failure[1],
+        &summary.timeout[1],
         "test_to_succeed",
         Some("Timeout after 5s"),
     );
@@ -515,9 +645,10 @@ fn test_test_spawning_proc_gets_stopped() {
     assert!(err_str.contains("magicchild1"));
     assert!(!err_str.contains("magicchild10"));
     let summary = extract_report(result.stderr).expect("Failed to extract report from logs.");
-    assert_test_summary_size(&summary, 1, 1, 0);
+    assert_test_summary_size(&summary, 1, 0, 0);
+    assert_timeouts_size(&summary, 1);
     assert_name_and_message_eq(
-        &summary.failure[0],
+        &summary.timeout[0],
         "spawning_process",
         Some("Timeout after 5s"),
     );
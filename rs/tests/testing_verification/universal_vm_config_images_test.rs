@@ -0,0 +1,83 @@
+/* tag::catalog[]
+Title:: Universal VM config images
+
+Goal:: Ensure that a universal VM configured via multiple config directories
+mounts all of them, after verifying their checksums, before running the
+activate script of the primary one.
+
+Runbook::
+. Set up a universal VM with a primary config directory, whose activate
+  script copies a file from an additional config directory, and the
+  additional config directory.
+. SSH into the VM and read the file from the mounted additional config image
+  and its copy made by the activate script.
+
+Success:: Both config images are mounted and the activate script saw the
+contents of the additional one.
+
+end::catalog[] */
+
+use anyhow::Result;
+use ic_system_test_driver::driver::group::SystemTestGroup;
+use ic_system_test_driver::driver::test_env::TestEnv;
+use ic_system_test_driver::driver::test_env_api::SshSession;
+use ic_system_test_driver::driver::universal_vm::{UniversalVm, UniversalVms};
+use ic_system_test_driver::systest;
+use slog::info;
+use std::fs;
+
+const UNIVERSAL_VM_NAME: &str = "config-images";
+const PAYLOAD_DIR_NAME: &str = "payload";
+const PAYLOAD_FILE_NAME: &str = "payload.txt";
+const PAYLOAD: &str = "static payload";
+
+fn main() -> Result<()> {
+    SystemTestGroup::new()
+        .with_setup(setup)
+        .add_test(systest!(test))
+        .execute_from_args()?;
+    Ok(())
+}
+
+pub fn setup(env: TestEnv) {
+    let config_dir = env
+        .single_activate_script_config_dir(
+            UNIVERSAL_VM_NAME,
+            &format!(
+                "#!/bin/sh\ncp /config/{PAYLOAD_DIR_NAME}/{PAYLOAD_FILE_NAME} /tmp/{PAYLOAD_FILE_NAME}\n"
+            ),
+        )
+        .unwrap();
+    let payload_dir = env.get_path(PAYLOAD_DIR_NAME);
+    fs::create_dir_all(&payload_dir).unwrap();
+    fs::write(payload_dir.join(PAYLOAD_FILE_NAME), PAYLOAD).unwrap();
+
+    UniversalVm::new(String::from(UNIVERSAL_VM_NAME))
+        .with_config_dir(config_dir)
+        .with_config_dir(payload_dir)
+        .start(&env)
+        .expect("failed to setup universal VM");
+}
+
+pub fn test(env: TestEnv) {
+    let logger = env.logger();
+    let universal_vm = env.get_deployed_universal_vm(UNIVERSAL_VM_NAME).unwrap();
+
+    let config_images = universal_vm.get_config_images().unwrap();
+    assert_eq!(1, config_images.len());
+    assert_eq!(PAYLOAD_DIR_NAME, config_images[0].name);
+
+    info!(logger, "Reading the payload from the mounted config image");
+    let mounted = universal_vm
+        .block_on_bash_script(&format!(
+            "cat /config/{PAYLOAD_DIR_NAME}/{PAYLOAD_FILE_NAME}"
+        ))
+        .expect("Failed to read the payload from the mounted config image");
+    assert_eq!(PAYLOAD, mounted.trim());
+
+    info!(logger, "Reading the payload copied by the activate script");
+    let copied = universal_vm
+        .block_on_bash_script(&format!("cat /tmp/{PAYLOAD_FILE_NAME}"))
+        .expect("Failed to read the payload copied by the activate script");
+    assert_eq!(PAYLOAD, copied.trim());
+}
@@ -0,0 +1,67 @@
+/* tag::catalog[]
+Title:: Universal VM extra disks
+
+Goal:: Ensure that extra disks requested via `UniversalVm::with_extra_disks()`
+are attached to the VM with the requested size, at the recorded device path.
+
+Runbook::
+. Set up a universal VM with a 4 GiB extra disk.
+. SSH into the VM and query the size of the recorded block device.
+
+Success:: The block device exists and is exactly 4 GiB large.
+
+end::catalog[] */
+
+use anyhow::{bail, Result};
+use ic_system_test_driver::driver::group::SystemTestGroup;
+use ic_system_test_driver::driver::test_env::TestEnv;
+use ic_system_test_driver::driver::test_env_api::SshSession;
+use ic_system_test_driver::driver::universal_vm::{DiskSpec, UniversalVm, UniversalVms};
+use ic_system_test_driver::systest;
+use slog::info;
+
+const UNIVERSAL_VM_NAME: &str = "extra-disks";
+const EXTRA_DISK_NAME: &str = "data";
+const EXTRA_DISK_SIZE_GIB: u64 = 4;
+
+fn main() -> Result<()> {
+    SystemTestGroup::new()
+        .with_setup(setup)
+        .add_test(systest!(test))
+        .execute_from_args()?;
+    Ok(())
+}
+
+pub fn setup(env: TestEnv) {
+    UniversalVm::new(String::from(UNIVERSAL_VM_NAME))
+        .with_extra_disks(vec![DiskSpec {
+            name: EXTRA_DISK_NAME.to_string(),
+            size_gib: EXTRA_DISK_SIZE_GIB,
+        }])
+        .start(&env)
+        .expect("failed to setup universal VM");
+}
+
+pub fn test(env: TestEnv) {
+    let logger = env.logger();
+    let universal_vm = env.get_deployed_universal_vm(UNIVERSAL_VM_NAME).unwrap();
+
+    let extra_disks = universal_vm.get_extra_disks().unwrap();
+    assert_eq!(1, extra_disks.len());
+    let disk = &extra_disks[0];
+    assert_eq!(EXTRA_DISK_NAME, disk.name);
+    assert_eq!(EXTRA_DISK_SIZE_GIB, disk.size_gib);
+
+    info!(logger, "Querying the size of {}", disk.device);
+    let size_bytes = universal_vm
+        .block_on_bash_script(&format!(
+            "lsblk --bytes --nodeps --noheadings --output SIZE {}",
+            disk.device
+        ))
+        .and_then(|output| match output.trim().parse::<u64>() {
+            Ok(size_bytes) => Ok(size_bytes),
+            Err(e) => bail!("Unexpected lsblk output {:?}: {}", output, e),
+        })
+        .expect("Failed to query the extra disk size");
+    assert_eq!(EXTRA_DISK_SIZE_GIB << 30, size_bytes);
+}
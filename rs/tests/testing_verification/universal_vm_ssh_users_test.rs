@@ -0,0 +1,109 @@
+/* tag::catalog[]
+Title:: Universal VM SSH users
+
+Goal:: Ensure that users added via `UniversalVm::with_ssh_users()` exist at
+first boot and can only be logged into with their own keys, while the default
+admin user is unaffected.
+
+Runbook::
+. Generate a key pair for each of two users.
+. Set up a universal VM with both users.
+. Log into the VM as each user with their own key and run `whoami`.
+. Try to log into the VM as each user with the key of the other user.
+. Log into the VM as the default admin user.
+
+Success:: Each user can log in with their own key only and the admin user can
+still log in.
+
+end::catalog[] */
+
+use anyhow::{bail, Result};
+use ic_system_test_driver::driver::group::SystemTestGroup;
+use ic_system_test_driver::driver::test_env::TestEnv;
+use ic_system_test_driver::driver::test_env_api::{SshSession, SshUserSession};
+use ic_system_test_driver::driver::universal_vm::{SshUser, UniversalVm, UniversalVms};
+use ic_system_test_driver::systest;
+use slog::info;
+use std::path::PathBuf;
+use std::process::Command;
+
+const UNIVERSAL_VM_NAME: &str = "ssh-users";
+const USERS: [&str; 2] = ["alice", "bob"];
+const SSH_USERS_KEYS_DIR: &str = "ssh_users_keys";
+
+fn main() -> Result<()> {
+    SystemTestGroup::new()
+        .with_setup(setup)
+        .add_test(systest!(test))
+        .execute_from_args()?;
+    Ok(())
+}
+
+/// Generates an ed25519 key pair for `user` in the `TestEnv` (see
+/// `priv_key_path()`), returning the public key.
+fn ssh_keygen(env: &TestEnv, user: &str) -> Result<String> {
+    std::fs::create_dir_all(env.get_path(SSH_USERS_KEYS_DIR))?;
+    let priv_key = priv_key_path(env, user);
+    let status = Command::new("ssh-keygen")
+        .args(["-t", "ed25519", "-N", "", "-C", user, "-f"])
+        .arg(&priv_key)
+        .status()?;
+    if !status.success() {
+        bail!("ssh-keygen failed for {user}: {status}");
+    }
+    let pubkey = std::fs::read_to_string(priv_key.with_extension("pub"))?;
+    Ok(pubkey.trim().to_string())
+}
+
+fn priv_key_path(env: &TestEnv, user: &str) -> PathBuf {
+    env.get_path(SSH_USERS_KEYS_DIR).join(user)
+}
+
+pub fn setup(env: TestEnv) {
+    let ssh_users = USERS
+        .iter()
+        .map(|user| {
+            let pubkey = ssh_keygen(&env, user).expect("failed to generate SSH keys");
+            SshUser {
+                name: user.to_string(),
+                pubkeys: vec![pubkey],
+            }
+        })
+        .collect();
+    UniversalVm::new(String::from(UNIVERSAL_VM_NAME))
+        .with_ssh_users(ssh_users)
+        .start(&env)
+        .expect("failed to setup universal VM");
+}
+
+pub fn test(env: TestEnv) {
+    let logger = env.logger();
+    let universal_vm = env.get_deployed_universal_vm(UNIVERSAL_VM_NAME).unwrap();
+
+    for user in USERS {
+        info!(logger, "Logging in as {user} with their own key");
+        let session = universal_vm
+            .block_on_ssh_session_for_user(user, &priv_key_path(&env, user))
+            .unwrap_or_else(|e| panic!("{user} cannot log in with their own key: {e}"));
+        let whoami = universal_vm
+            .block_on_bash_script_from_session(&session, "whoami")
+            .unwrap();
+        assert_eq!(user, whoami.trim());
+
+        for other in USERS.iter().filter(|other| **other != user) {
+            info!(logger, "Logging in as {user} with the key of {other}");
+            assert!(
+                universal_vm
+                    .get_ssh_session_for_user(user, &priv_key_path(&env, other))
+                    .is_err(),
+                "{user} can log in with the key of {other}"
+            );
+        }
+    }
+
+    info!(logger, "Logging in as the default admin user");
+    let whoami = universal_vm
+        .block_on_bash_script("whoami")
+        .expect("the admin user cannot log in");
+    assert_eq!("admin", whoami.trim());
+}
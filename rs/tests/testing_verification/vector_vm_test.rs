@@ -0,0 +1,102 @@
+/* tag::catalog[]
+Title:: Vector VM log forwarding
+
+Goal:: Ensure that the journald logs of the IC nodes are forwarded to the
+vector VM and can be queried from there.
+
+Runbook::
+. Set up a vector VM and a subnet with two nodes.
+. Forward the logs of all nodes to the vector VM.
+. Write a unique marker line to the journal of one node with `logger`.
+. Synchronize the vector VM with the topology again.
+. Query the logs of both nodes for the marker on the vector VM.
+
+Success:: The marker is found exactly once in the logs of the node that wrote
+it, and not in the logs of the other node.
+
+end::catalog[] */
+
+use anyhow::{bail, Result};
+use ic_registry_subnet_type::SubnetType;
+use ic_system_test_driver::driver::group::SystemTestGroup;
+use ic_system_test_driver::driver::ic::{InternetComputer, Subnet};
+use ic_system_test_driver::driver::test_env::TestEnv;
+use ic_system_test_driver::driver::test_env_api::{
+    HasTopologySnapshot, IcNodeContainer, SshSession, READY_WAIT_TIMEOUT, RETRY_BACKOFF,
+};
+use ic_system_test_driver::driver::vector_vm::{HasVector, VectorVm};
+use ic_system_test_driver::retry_with_msg;
+use ic_system_test_driver::systest;
+use regex::Regex;
+use slog::info;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const WINDOW: Duration = Duration::from_secs(60 * 60);
+
+fn main() -> Result<()> {
+    SystemTestGroup::new()
+        .with_setup(setup)
+        .add_test(systest!(test))
+        .execute_from_args()?;
+    Ok(())
+}
+
+pub fn setup(env: TestEnv) {
+    VectorVm::default()
+        .start(&env)
+        .expect("failed to start vector VM");
+    InternetComputer::new()
+        .add_subnet(Subnet::new(SubnetType::Application).add_nodes(2))
+        .setup_and_start(&env)
+        .expect("failed to setup IC under test");
+    env.topology_snapshot().subnets().for_each(|subnet| {
+        subnet
+            .await_all_nodes_healthy()
+            .expect("failed to wait for nodes to become healthy")
+    });
+    env.sync_with_vector()
+        .expect("failed to forward the logs of the nodes to the vector VM");
+}
+
+pub fn test(env: TestEnv) {
+    let logger = env.logger();
+    let mut nodes = env.topology_snapshot().subnets().next().unwrap().nodes();
+    let node = nodes.next().unwrap();
+    let other_node = nodes.next().unwrap();
+
+    let marker = format!(
+        "vector-vm-test-marker-{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos()
+    );
+    node.block_on_bash_script(&format!("logger {marker}"))
+        .expect("failed to write the marker to the journal");
+    info!(logger, "Wrote {marker} to the journal of {}", node.node_id);
+
+    // Synchronizing again must not forward the logs of a node twice.
+    env.sync_with_vector()
+        .expect("failed to synchronize the vector VM again");
+
+    let regex = Regex::new(&regex::escape(&marker)).unwrap();
+    let lines = retry_with_msg!(
+        format!("Waiting for {marker} to show up on the vector VM"),
+        logger.clone(),
+        READY_WAIT_TIMEOUT,
+        RETRY_BACKOFF,
+        || {
+            let lines = VectorVm::query_logs(&env, node.node_id, &regex, WINDOW)?;
+            if lines.is_empty() {
+                bail!("{marker} not found in the logs of {}", node.node_id);
+            }
+            Ok(lines)
+        }
+    )
+    .expect("the marker was not forwarded to the vector VM");
+    assert_eq!(lines.len(), 1, "unexpected lines: {lines:?}");
+
+    let other_lines = VectorVm::query_logs(&env, other_node.node_id, &regex, WINDOW)
+        .expect("failed to query the logs of the other node");
+    assert!(other_lines.is_empty(), "unexpected lines: {other_lines:?}");
+}
@@ -0,0 +1,54 @@
+#!/usr/bin/env python3
+"""
+Log aggregator of the vector VM, see `rs/tests/driver/src/driver/vector_vm.rs`.
+
+Accepts TCP connections from the IC nodes. The first line of a connection is the
+ID of the sending node, every following line is a journald entry in JSON format
+(`journalctl --output=json`). Each entry is appended to `<LOGS_DIR>/<node_id>.log`
+as `<realtime timestamp in microseconds>\t<message>`.
+"""
+
+import json
+import os
+import re
+import socket
+import socketserver
+import sys
+
+LOGS_DIR = sys.argv[1]
+PORT = int(sys.argv[2])
+NODE_ID_PATTERN = re.compile(r"^[a-z0-9-]+$")
+
+
+def message_of(entry):
+    message = entry.get("MESSAGE", "")
+    # journald encodes messages that are not valid UTF-8 as arrays of bytes.
+    if isinstance(message, list):
+        message = bytes(message).decode("utf-8", errors="replace")
+    return message.replace("\n", " ")
+
+
+class Handler(socketserver.StreamRequestHandler):
+    def handle(self):
+        node_id = self.rfile.readline().decode("utf-8", errors="replace").strip()
+        if not NODE_ID_PATTERN.match(node_id):
+            return
+        with open(os.path.join(LOGS_DIR, f"{node_id}.log"), "a", buffering=1) as log:
+            for line in self.rfile:
+                try:
+                    entry = json.loads(line)
+                except ValueError:
+                    continue
+                log.write(f"{entry.get('__REALTIME_TIMESTAMP', '0')}\t{message_of(entry)}\n")
+
+
+class Server(socketserver.ThreadingTCPServer):
+    address_family = socket.AF_INET6
+    allow_reuse_address = True
+    daemon_threads = True
+
+
+if __name__ == "__main__":
+    os.makedirs(LOGS_DIR, exist_ok=True)
+    with Server(("::", PORT), Handler) as server:
+        server.serve_forever()
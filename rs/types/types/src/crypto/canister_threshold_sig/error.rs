@@ -7,6 +7,7 @@ use crate::{Height, NodeId, RegistryVersion};
 use ic_protobuf::proxy::ProxyDecodeError;
 use ic_protobuf::registry::crypto::v1::AlgorithmId as AlgorithmIdProto;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
 pub enum IDkgTranscriptIdError {
@@ -135,14 +136,30 @@ pub enum IDkgOpenTranscriptError {
     TransientInternalError {
         internal_error: String,
     },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
 }
 impl_display_using_debug!(IDkgOpenTranscriptError);
 
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
 pub enum IDkgRetainKeysError {
-    InternalError { internal_error: String },
-    SerializationError { internal_error: String },
-    TransientInternalError { internal_error: String },
+    InternalError {
+        internal_error: String,
+    },
+    SerializationError {
+        internal_error: String,
+    },
+    TransientInternalError {
+        internal_error: String,
+    },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
 }
 impl_display_using_debug!(IDkgRetainKeysError);
 
@@ -177,6 +194,11 @@ pub enum IDkgLoadTranscriptError {
     TransientInternalError {
         internal_error: String,
     },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
 }
 impl_display_using_debug!(IDkgLoadTranscriptError);
 
@@ -252,6 +274,11 @@ pub enum IDkgVerifyDealingPrivateError {
     TransientInternalError {
         internal_error: String,
     },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
 }
 impl_display_using_debug!(IDkgVerifyDealingPrivateError);
 
@@ -323,11 +350,24 @@ impl_display_using_debug!(ThresholdEcdsaVerifySigShareError);
 
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
 pub enum ThresholdEcdsaCreateSigShareError {
-    InternalError { internal_error: String },
+    InternalError {
+        internal_error: String,
+    },
     NotAReceiver,
-    SerializationError { internal_error: String },
-    SecretSharesNotFound { commitment_string: String },
-    TransientInternalError { internal_error: String },
+    SerializationError {
+        internal_error: String,
+    },
+    SecretSharesNotFound {
+        commitment_string: String,
+    },
+    TransientInternalError {
+        internal_error: String,
+    },
+    /// The call of `method` to the remote vault timed out after `elapsed`.
+    Timeout {
+        method: String,
+        elapsed: Duration,
+    },
 }
 impl_display_using_debug!(ThresholdEcdsaCreateSigShareError);
 
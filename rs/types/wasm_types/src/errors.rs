@@ -95,6 +95,12 @@ pub enum WasmValidationError {
         size: usize,
         allowed: usize,
     },
+    /// The body of a function was too large.
+    FunctionBodyTooLarge {
+        index: usize,
+        size: usize,
+        allowed: usize,
+    },
     /// The code section is too large.
     CodeSectionTooLarge { size: u32, allowed: u32 },
     /// The total module size is too large.
@@ -199,6 +205,15 @@ impl std::fmt::Display for WasmValidationError {
                 "Wasm module contains a function at index {index} \
                     of size {size} that exceeds the maximum allowed size of {allowed}.",
             ),
+            Self::FunctionBodyTooLarge {
+                index,
+                size,
+                allowed,
+            } => write!(
+                f,
+                "Wasm module contains a function at index {index} \
+                    with a body of {size} bytes that exceeds the maximum allowed size of {allowed} bytes.",
+            ),
             Self::CodeSectionTooLarge { size, allowed } => write!(
                 f,
                 "Wasm module code section size of {size} \
@@ -271,6 +286,12 @@ impl AsErrorHelp for WasmValidationError {
                     .to_string(),
                 doc_link: doc_ref("wasm-module-function-too-large"),
             },
+            WasmValidationError::FunctionBodyTooLarge { .. } => ErrorHelp::UserError {
+                suggestion: "Try breaking large functions up into multiple \
+                smaller functions."
+                    .to_string(),
+                doc_link: doc_ref("wasm-module-function-too-large"),
+            },
             WasmValidationError::CodeSectionTooLarge { .. } => ErrorHelp::UserError {
                 suggestion: "Try shrinking the module code section using tools like \
                 `ic-wasm` or splitting the logic across multiple canisters."